@@ -430,6 +430,39 @@ static inline int rnd_mix(int const rnd_a, int seed) {
     return seed;
 }
 
+// Integer hash (two rounds of multiply-xorshift).
+static inline uint rnd_hash(uint x) {
+    x ^= x >> 16;
+    x *= 0x7feb352dU;
+    x ^= x >> 15;
+    x *= 0x846ca68bU;
+    x ^= x >> 16;
+    return x;
+}
+
+// RND_CTR(): Counter-based random number. Stateless: always returns the same
+// value for the same seed, index, and counter.
+static inline uint rnd_ctr(uint const seed, uint const idx, uint const ctr) {
+    return rnd_hash(rnd_hash(rnd_hash(ctr) ^ idx) ^ seed);
+}
+
+// RND_BELOW(): Maps a random number onto [0, n).
+static inline uint rnd_below(uint const rnd, uint const n) {
+    return (uint)(((ulong)rnd * (ulong)n) >> 32);
+}
+
+// NEUROMOD_STEP(): Scales or gates a learning step (-1, 0, or 1) by a
+// neuromodulation value within [-127, 127]. The step is taken with a
+// probability of `abs(neuromod) / 127` and is reversed if `neuromod` is
+// negative. A value of 127 always returns the step unchanged.
+static inline int neuromod_step(int const step, char const neuromod, int const rnd,
+            uint const syn_idx)
+{
+    int const is_open = (int)rnd_below(rnd_ctr((uint)rnd, syn_idx, NEUROMODULATION_RND_CTR), 127)
+        < (int)abs(neuromod);
+    return is_open ? ((neuromod < 0) ? -step : step) : 0;
+}
+
 /* RND INC/DEC NOTES:
         - Must cap at the min and max limits (-127, 127).
         - Must not get stuck at max limit. If at max, must be decrementable. At min, who cares.
@@ -560,6 +593,8 @@ static inline void dst_syns__active__mtpot_mtdep(
             int const pr_l2i,
             // Depression rate inverse log2 (1/log2):
             int const dr_l2i,
+            // Neuromodulation value of the cell's column:
+            char const neuromod,
             int const rnd,
             __global uchar* const syn_flag_sets,
             // TODO: Switch to `u8` (`uchar`):
@@ -585,16 +620,15 @@ static inline void dst_syns__active__mtpot_mtdep(
         // int const syn_is_active = syn_state != 0;
 
         // TODO: De-branch
+        int step;
         if (syn_prev_active) {
-            int const should_inc = rnd_inc(rnd, (syn_idz + i), syn_strength, pr_l2i, pr_mask);
-            syn_strength += should_inc;
+            step = rnd_inc(rnd, (syn_idz + i), syn_strength, pr_l2i, pr_mask);
         } else {
-            int const should_dec = rnd_dec(rnd, (syn_idz + i), syn_strength, dr_l2i, dr_mask);
-            syn_strength -= should_dec;
+            step = 0 - rnd_dec(rnd, (syn_idz + i), syn_strength, dr_l2i, dr_mask);
         }
 
         // syn_flag_sets[i] = syn_flag_set;
-        syn_strengths[i] = syn_strength;
+        syn_strengths[i] = add_sat(syn_strength, (char)neuromod_step(step, neuromod, rnd, i));
     }
 
 }
@@ -605,6 +639,9 @@ static inline void prx_syns__active__mtp_ltd(
             __global const uchar* const syn_states,
             uint const syn_idz,
             uint const syns_per_den,
+            int const pr_l2i,
+            int const dr_l2i,
+            char const neuromod,
             int const rnd,
             // TODO: Switch to `u8` (`uchar`):
             __global char* const syn_strengths)
@@ -613,12 +650,10 @@ static inline void prx_syns__active__mtp_ltd(
 
     // TODO: Pre-calculate host side:
     // Potentiation rate:
-    int const pr_l2i = 0;
     int pr_mask = 0x7F << pr_l2i;
     lshft_mask(&pr_mask, pr_l2i);
 
     // Depression rate:
-    int const dr_l2i = 2;
     int dr_mask = 0x7F << dr_l2i;
     lshft_mask(&dr_mask, dr_l2i);
 
@@ -630,10 +665,9 @@ static inline void prx_syns__active__mtp_ltd(
         int const should_dec = rnd_dec(rnd, (syn_idz + i), syn_strength, dr_l2i, dr_mask);
         int const syn_is_active = syn_state != 0;
 
-        syn_strength += mul24(syn_is_active, should_inc);
-        syn_strength -= mul24(!syn_is_active, should_dec);
+        int const step = mul24(syn_is_active, should_inc) - mul24(!syn_is_active, should_dec);
 
-        syn_strengths[i] = syn_strength;
+        syn_strengths[i] = add_sat(syn_strength, (char)neuromod_step(step, neuromod, rnd, i));
     }
 
 }
@@ -891,6 +925,10 @@ __kernel void ssc_mtp_simple(
         __private uint const cel_axn_idz,
         //__private uint const tufts_per_cel,
         __private uint const syns_per_tft,
+        __private int const pr_l2i,
+        __private int const dr_l2i,
+        __global const char* const neuromods,
+        __private uint const neuromod_col_count,
         __private uint const rnd,
         // __global int* const aux_ints_0,
         // TODO: Switch to `u8` (`uchar`):
@@ -910,7 +948,9 @@ __kernel void ssc_mtp_simple(
 
     if (axn_state) {
         uint const syn_idz = calc_syn_idz_OLD(tuft_id, cel_count, cel_id, syns_per_tft);
-        prx_syns__active__mtp_ltd(syn_states, syn_idz, syns_per_tft, rnd, syn_strengths);
+        char const neuromod = neuromods[cel_id % neuromod_col_count];
+        prx_syns__active__mtp_ltd(syn_states, syn_idz, syns_per_tft, pr_l2i, dr_l2i, neuromod,
+            rnd, syn_strengths);
     }
 }
 
@@ -924,6 +964,10 @@ __kernel void ssc_mtp(
             __private uint const cel_lyr_axn_idz,
             __private uint const cels_per_grp,
             __private uchar const syns_per_tft,
+            __private int const pr_l2i,
+            __private int const dr_l2i,
+            __global const char* const neuromods,
+            __private uint const neuromod_col_count,
             __private uint const rnd,
             // __global int* const aux_ints_0,
             // TODO: Switch to `u8` (`uchar`):
@@ -949,7 +993,9 @@ __kernel void ssc_mtp(
 
         if (axn_state) {
             uint const syn_idz = calc_syn_idz_OLD(tuft_id, cel_count, cel_idx, syns_per_tft);
-            prx_syns__active__mtp_ltd(syn_states, syn_idz, syns_per_tft, rnd, syn_strengths);
+            char const neuromod = neuromods[cel_idx % neuromod_col_count];
+            prx_syns__active__mtp_ltd(syn_states, syn_idz, syns_per_tft, pr_l2i, dr_l2i, neuromod,
+                rnd, syn_strengths);
         }
     }
 }
@@ -1216,6 +1262,8 @@ __kernel void tft_dst_mtp(
         __private uint const axn_idz_cel_lyr,
         __private int const potentiation_rate_l2i,
         __private int const depression_rate_l2i,
+        __global const char* const neuromods,
+        __private uint const neuromod_col_count,
         __private int const rnd,
         __global uchar* const syn_flag_sets,
         __global uchar* const cel_flag_sets,
@@ -1258,7 +1306,8 @@ __kernel void tft_dst_mtp(
                 syn_idz_celtft;
 
             dst_syns__active__mtpot_mtdep(syn_states, syn_idz_prev_best_den_tft, syns_per_den,
-                potentiation_rate_l2i, depression_rate_l2i, rnd, syn_flag_sets, syn_strengths);
+                potentiation_rate_l2i, depression_rate_l2i, neuromods[cel_idx % neuromod_col_count],
+                rnd, syn_flag_sets, syn_strengths);
         }
 
         cel_flag_set &= ~CEL_PREV_ACTIVE_FLAG;
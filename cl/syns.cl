@@ -1,3 +1,9 @@
+// SYN_REGROW_ATTEMPTS_MAX: Candidate sources tried per synapse by
+// `tft_regrow_syns` before leaving it for its next regrowth pass.
+#define SYN_REGROW_ATTEMPTS_MAX            64
+// SYN_SRC_POOL_ENTRY_LEN: Values per source pool entry (see `tft_regrow_syns`).
+#define SYN_SRC_POOL_ENTRY_LEN             5
+
 
 static inline uchar syn_fire(uchar const axn_state) {
     return ((axn_state != 0) << 7) + (axn_state >> (SYNAPSE_AXON_BIAS_LOG2));
@@ -387,3 +393,103 @@ __kernel void layer_cycle_syns_wow_vec4(
     }
 }
 
+
+// Regrows each synapse of a tuft within [syn_idz, syn_idn) whose strength is
+// at or below `str_floor`. One work item per dendrite, beginning with
+// dendrite `den_id_tft_z` of the tuft.
+//
+// `src_pool` contains one `SYN_SRC_POOL_ENTRY_LEN` entry per source slice id
+// pool element: { slc_id, ofs_idz, ofs_len, v_reach, u_reach }. Spatial
+// entries choose an offset pair from `src_offs` (v, u interleaved) and a
+// strength scaled by the offset's distance from the reach and by a factor
+// within [-str_dev, str_dev]. Nonspatial entries (`ofs_len == 0`) choose
+// each offset from [-reach, reach] with a strength of zero. `str_default` is
+// then added (saturating) to the strength.
+//
+// A new source must differ from every source on its dendrite unless
+// `src_saturated` is set. Random numbers are drawn from `rnd_ctr` keyed by
+// `rnd_seed`, the synapse index, and the attempt, making results
+// independent of work item scheduling.
+__kernel void tft_regrow_syns(
+        __global int const* const src_pool,
+        __global char const* const src_offs,
+        __private uint const src_pool_len,
+        __private uint const tft_syn_idz,
+        __private uint const syns_per_den,
+        __private uint const col_count,
+        __private uint const u_size,
+        __private uchar const src_saturated,
+        __private char const str_floor,
+        __private char const str_dev,
+        __private char const str_default,
+        __private uint const den_id_tft_z,
+        __private uint const syn_idz,
+        __private uint const syn_idn,
+        __private uint const rnd_seed,
+        __global char* const syn_strengths,
+        __global uchar* const syn_src_slc_ids,
+        __global char* const syn_src_col_v_offs,
+        __global char* const syn_src_col_u_offs)
+{
+    uint const den_syn_idz = tft_syn_idz + ((den_id_tft_z + get_global_id(0)) * syns_per_den);
+    uint const den_syn_idn = den_syn_idz + syns_per_den;
+    uint const regrow_idn = min(den_syn_idn, syn_idn);
+
+    for (uint syn_idx = max(den_syn_idz, syn_idz); syn_idx < regrow_idn; syn_idx++) {
+        if (syn_strengths[syn_idx] > str_floor) { continue; }
+
+        for (uint attempt = 0; attempt < SYN_REGROW_ATTEMPTS_MAX; attempt++) {
+            uint const ctr = attempt << 2;
+            __global int const* const entry = src_pool + (rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr),
+                src_pool_len) * SYN_SRC_POOL_ENTRY_LEN);
+
+            uchar const slc_id = (uchar)entry[0];
+            uint const ofs_len = (uint)entry[2];
+            int const v_reach = entry[3];
+            int const u_reach = entry[4];
+            char v_ofs;
+            char u_ofs;
+            char strength;
+
+            if (ofs_len == 0) {
+                v_ofs = (char)((int)rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 1),
+                    (uint)((v_reach << 1) + 1)) - v_reach);
+                u_ofs = (char)((int)rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 2),
+                    (uint)((u_reach << 1) + 1)) - u_reach);
+                strength = 0;
+            } else {
+                uint const ofs_idx = (uint)entry[1] + rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 1),
+                    ofs_len);
+                v_ofs = src_offs[ofs_idx << 1];
+                u_ofs = src_offs[(ofs_idx << 1) + 1];
+
+                char const intensity = (char)(((v_reach - (int)abs((int)v_ofs)) +
+                    (u_reach - (int)abs((int)u_ofs))) >> SYNAPSE_STRENGTH_INTENSITY_REDUCTION_L2);
+                char const str_factor = (char)((int)rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 3),
+                    (uint)(((int)str_dev << 1) + 1)) - (int)str_dev);
+                strength = (char)(intensity * str_factor);
+            }
+
+            strength = add_sat(strength, str_default);
+
+            int const src_key = ((int)slc_id * (int)col_count) + ((int)v_ofs * (int)u_size) +
+                (int)u_ofs;
+            int is_unique = 1;
+
+            for (uint den_syn_idx = den_syn_idz; den_syn_idx < den_syn_idn; den_syn_idx++) {
+                int const key = ((int)syn_src_slc_ids[den_syn_idx] * (int)col_count) +
+                    ((int)syn_src_col_v_offs[den_syn_idx] * (int)u_size) +
+                    (int)syn_src_col_u_offs[den_syn_idx];
+                is_unique &= key != src_key;
+            }
+
+            if (is_unique || src_saturated) {
+                syn_strengths[syn_idx] = strength;
+                syn_src_slc_ids[syn_idx] = slc_id;
+                syn_src_col_v_offs[syn_idx] = v_ofs;
+                syn_src_col_u_offs[syn_idx] = u_ofs;
+                break;
+            }
+        }
+    }
+}
@@ -69,7 +69,7 @@ impl EvalSequence {
             layers.insert(layer.sub().addr().clone(), layer);
         }
 
-        let sdrs = Sdrs::new(100, ENCODE_DIMS_0);
+        let sdrs = Sdrs::new(100, ENCODE_DIMS_0, None);
 
         // Define the number of iters to first train then collect for each
         // sample period. All learning and other cell parameters (activity,
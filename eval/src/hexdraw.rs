@@ -155,7 +155,7 @@ pub fn eval(sub: Option<&str>) {
 
     let in_tract_idx = cortex.thal().tract().index_of(v0_ext_lyr_addr).unwrap();
     let in_tract_buffer = cortex.thal().tract().buffer_rwvec(in_tract_idx).unwrap().clone();
-    let axns = cortex.areas().by_key(PRI_AREA).unwrap().axns().states().clone();
+    let axns = cortex.areas().by_key(PRI_AREA).unwrap().axns().states().device().unwrap().clone();
     let area_map = cortex.areas().by_key(PRI_AREA).unwrap().area_map().clone();
 
     let controls = ::spawn_threads(cortex, PRI_AREA, true);
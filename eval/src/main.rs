@@ -22,15 +22,15 @@ mod old_test;
 
 use std::thread;
 use std::sync::mpsc::{self, Sender, Receiver};
-use rand::{FromEntropy, rngs::SmallRng};
+use rand::rngs::SmallRng;
 use rand::distributions::{Range, Distribution};
 // use vibi::bismit::futures::executor;
 // use qutex::QrwLock;
 use vibi::window;
 use vibi::bismit::ocl::{Buffer, RwVec};
-use vibi::bismit::{encode, Cortex, SubcorticalNucleusLayer, TractSender,
+use vibi::bismit::{cmn, encode, Cortex, SubcorticalNucleusLayer, TractSender,
     TractReceiver, CorticalDims, TractDims, Thalamus};
-use vibi::bismit::flywheel::{Flywheel, Command, Request, Response};
+use vibi::bismit::flywheel::{Flywheel, Command, Request, Response, AreaInfo};
 use vibi::bismit::map::{AreaMap, AxonTopology, LayerAddress, AxonDomain};
 
 
@@ -48,6 +48,7 @@ pub struct Controls {
     pub cmd_tx: Sender<Command>,
     pub req_tx: Sender<Request>,
     pub res_rx: Receiver<Response>,
+    pub pri_area_info: Box<AreaInfo>,
     pub th_flywheel: thread::JoinHandle<()>,
     pub th_win: Option<thread::JoinHandle<()>>,
 }
@@ -64,7 +65,7 @@ pub fn spawn_threads(cortex: Cortex, pri_area_name: &'static str, vibi: bool)
     let (control_response_tx, control_response_rx) = mpsc::channel();
     let control_command_tx = command_tx;
 
-    let mut flywheel = Flywheel::new(cortex, command_rx, pri_area_name);
+    let mut flywheel = Flywheel::new(cortex, command_rx);
     flywheel.add_req_res_pair(vibi_request_rx, vibi_response_tx);
     flywheel.add_req_res_pair(control_request_rx, control_response_tx);
 
@@ -75,6 +76,18 @@ pub fn spawn_threads(cortex: Cortex, pri_area_name: &'static str, vibi: bool)
         flywheel.spin();
     }).expect("Error creating 'flywheel' thread");
 
+    // Primary area info:
+    control_request_tx.send(Request::AreaInfo { area_name: pri_area_name.to_owned() })
+        .expect("Controls::spawn_threads: Flywheel has exited");
+    let pri_area_info = loop {
+        match control_response_rx.recv() {
+            Ok(Response::AreaInfo(info)) => break info,
+            Ok(Response::Error(err)) => panic!("Controls::spawn_threads: {}", err),
+            Ok(_) => continue,
+            Err(_) => panic!("Controls::spawn_threads: Flywheel has exited"),
+        }
+    };
+
     // Vibi thread:
     let th_win = if vibi {
         Some(thread::Builder::new().name("win".to_string()).spawn(move || {
@@ -89,6 +102,7 @@ pub fn spawn_threads(cortex: Cortex, pri_area_name: &'static str, vibi: bool)
         cmd_tx: control_command_tx,
         req_tx: control_request_tx,
         res_rx: control_response_rx,
+        pri_area_info,
         th_flywheel,
         th_win,
     }
@@ -130,17 +144,31 @@ fn main() {
             .help("Specifies an evaluation argument")
             .required(false)
             .index(2))
+        .arg(Arg::with_name("seed")
+            .help("Seeds every random number generator for a reproducible run")
+            .long("seed")
+            .takes_value(true)
+            .value_name("SEED"))
         .get_matches();
 
     let sub = matches.value_of("SUBEVALUATION");
 
+    let seed = match matches.value_of("seed").map(|s| s.parse::<u64>()) {
+        Some(Ok(seed)) => Some(seed),
+        Some(Err(err)) => {
+            println!("Invalid seed specified: {}", err);
+            return;
+        },
+        None => None,
+    };
+
     match matches.value_of("EVALUATION").unwrap() {
-        "spatial" => spatial::eval(),
+        "spatial" => spatial::eval(seed),
         "hexdraw" => hexdraw::eval(sub),
-        "motor" => motor::eval(),
-        "sensory" => sensory::eval(),
-        "sequence" => sequence::eval(),
-        "old_test" => old_test::eval(),
+        "motor" => motor::eval(seed),
+        "sensory" => sensory::eval(seed),
+        "sequence" => sequence::eval(seed),
+        "old_test" => old_test::eval(seed),
         e @ _ => println!("Unknown evaluation specified: {}", e),
     }
 
@@ -412,7 +440,7 @@ pub struct Sdrs {
 }
 
 impl Sdrs {
-    pub fn new<D>(pattern_count: usize, dims: D) -> Sdrs
+    pub fn new<D>(pattern_count: usize, dims: D, seed: Option<u64>) -> Sdrs
             where D: Into<TractDims> {
         let dims = dims.into();
         const SPARSITY: usize = 48;
@@ -420,7 +448,7 @@ impl Sdrs {
         let cell_count = dims.to_len();
         let active_cell_count = cell_count / SPARSITY;
 
-        let mut rng = SmallRng::from_entropy();
+        let mut rng = cmn::seeded_rng(seed, "eval::Sdrs", &[]);
 
         // Produce randomized indexes:
         let pattern_indices: Vec<_> = (0..pattern_count).map(|_| {
@@ -512,14 +540,15 @@ impl SeqCursor {
     /// * seq_lens: (min, max) sequence lengths.
     /// * seq_count: number of sequences to generate.
     /// * src_idx_count: Length of the source pool.
+    /// * seed: master seed (see `cmn::seeded_rng`), or `None` for entropy.
     ///
-    pub fn new(seq_lens: (usize, usize), seq_count: usize, src_idx_count: usize)
-            -> SeqCursor {
+    pub fn new(seq_lens: (usize, usize), seq_count: usize, src_idx_count: usize,
+            seed: Option<u64>) -> SeqCursor {
         assert!(seq_lens.1 >= seq_lens.0, "SeqCursor::new(): Sequence length range \
             ('seq_lens') invalid. High end must at least be equal to low end: '{:?}'.",
                 seq_lens);
 
-        let mut rng = SmallRng::from_entropy();
+        let mut rng = cmn::seeded_rng(seed, "eval::SeqCursor", &[]);
         let mut sequences = Vec::with_capacity(seq_count);
 
         // Build sequences of source indexes:
@@ -4,13 +4,13 @@
 
 // use std::mem;
 use std::collections::{HashMap};
-use rand::{self, FromEntropy, rngs::SmallRng};
+use rand::{self, rngs::SmallRng};
 use rand::distributions::{Range, Distribution};
 use qutex::QrwLock;
 use vibi::bismit::futures::{Future, FutureExt};
 use vibi::bismit::{map, Result as CmnResult, Cortex, CorticalAreaSettings, Thalamus,
     SubcorticalNucleus, SubcorticalNucleusLayer, CompletionPool, CorticalAreas};
-use vibi::bismit::cmn::{TractFrameMut, TractDims};
+use vibi::bismit::cmn::{self, TractFrameMut, TractDims};
 use vibi::bismit::encode::{self, Vector2dWriter};
 use vibi::bismit::map::*;
 use ::{IncrResult, TrialIter, Layer, Pathway, InputSource};
@@ -49,7 +49,7 @@ struct EvalMotor {
 
 impl EvalMotor {
     pub fn new<S: Into<String>>(layer_map_schemes: &LayerMapSchemeList,
-            area_schemes: &AreaSchemeList, area_name: S)
+            area_schemes: &AreaSchemeList, area_name: S, seed: Option<u64>)
             -> EvalMotor {
         let area_name = area_name.into();
         let area_scheme = &area_schemes[&area_name];
@@ -72,7 +72,7 @@ impl EvalMotor {
         let cell_count = (ENCODE_DIM * ENCODE_DIM) as usize;
         let sdr_active_count = cell_count / SPARSITY;
 
-        let mut rng = SmallRng::from_entropy();
+        let mut rng = cmn::seeded_rng(seed, "eval::EvalMotor", &[]);
 
         // Produce randomized indexes:
         let pattern_indices: Vec<_> = (0..pattern_count).map(|_| {
@@ -232,17 +232,21 @@ impl SubcorticalNucleus for EvalMotor {
 }
 
 
-pub fn eval() {
+pub fn eval(seed: Option<u64>) {
     let layer_map_schemes = define_lm_schemes();
     let area_schemes = define_a_schemes();
 
     let eval_nucl = EvalMotor::new(&layer_map_schemes,
-        &area_schemes, IN_AREA);
+        &area_schemes, IN_AREA, seed);
 
-    let cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
+    let mut cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
         .ca_settings(ca_settings())
         .subcortical_nucleus(eval_nucl);
 
+    if let Some(seed) = seed {
+        cortex_builder = cortex_builder.seed(seed);
+    }
+
     let cortex = cortex_builder.build().unwrap();
 
     let controls = ::spawn_threads(cortex, PRI_AREA, true);
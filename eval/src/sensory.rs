@@ -5,14 +5,14 @@
 
 // use std::mem;
 use std::collections::{HashMap};
-use rand::{self, FromEntropy, rngs::SmallRng};
+use rand::{self, rngs::SmallRng};
 use rand::distributions::{Range, Distribution};
 use qutex::QrwLock;
 use vibi::bismit::futures::{executor, Future, FutureExt};
 use vibi::bismit::{map, Result as CmnResult, Cortex, CorticalAreaSettings, Thalamus,
     SubcorticalNucleus, SubcorticalNucleusLayer, CompletionPool, CorticalAreas};
 use vibi::bismit::map::*;
-use vibi::bismit::cmn::{TractFrameMut, TractDims, CorticalDims};
+use vibi::bismit::cmn::{self, TractFrameMut, TractDims, CorticalDims};
 use vibi::bismit::encode::{self, Vector2dWriter};
 use ::{IncrResult, TrialIter, Layer, Pathway, InputSource};
 use ::spatial::{TrialData, TrialResults};
@@ -50,7 +50,7 @@ struct EvalSensory {
 
 impl EvalSensory {
     pub fn new<S: Into<String>>(layer_map_schemes: &LayerMapSchemeList,
-            area_schemes: &AreaSchemeList, area_name: S)
+            area_schemes: &AreaSchemeList, area_name: S, seed: Option<u64>)
             -> EvalSensory {
         let area_name = area_name.into();
         let area_scheme = &area_schemes[&area_name];
@@ -80,7 +80,7 @@ impl EvalSensory {
         let cell_count = (ENCODE_DIM * ENCODE_DIM) as usize;
         let sdr_active_count = cell_count / SPARSITY;
 
-        let mut rng = SmallRng::from_entropy();
+        let mut rng = cmn::seeded_rng(seed, "eval::EvalSensory", &[]);
 
         // Produce randomized indexes:
         let pattern_indices: Vec<_> = (0..pattern_count).map(|_| {
@@ -253,17 +253,21 @@ impl SubcorticalNucleus for EvalSensory {
 }
 
 
-pub fn eval() {
+pub fn eval(seed: Option<u64>) {
     let layer_map_schemes = define_lm_schemes();
     let area_schemes = define_a_schemes();
 
     let eval_nucl = EvalSensory::new(&layer_map_schemes,
-        &area_schemes, IN_AREA);
+        &area_schemes, IN_AREA, seed);
 
-    let cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
+    let mut cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
         .ca_settings(ca_settings())
         .subcortical_nucleus(eval_nucl);
 
+    if let Some(seed) = seed {
+        cortex_builder = cortex_builder.seed(seed);
+    }
+
     let cortex = cortex_builder.build().unwrap();
 
     let controls = ::spawn_threads(cortex, PRI_AREA, true);
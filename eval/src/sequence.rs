@@ -12,7 +12,7 @@ use std::mem;
 use std::collections::{HashMap, BTreeMap};
 use std::ops::Range;
 use smallvec::SmallVec;
-use rand::{self, FromEntropy, rngs::SmallRng};
+use rand::{self, rngs::SmallRng};
 use rand::distributions::{Range as RandRange, Distribution};
 // use ansi_term::Colour::{Blue, Red, Cyan, Green};
 use qutex::{Qutex, Guard, QrwLock, ReadGuard as QrwReadGuard};
@@ -232,7 +232,7 @@ struct EvalSequence {
 
 impl EvalSequence {
     pub fn new<S: Into<String>>(layer_map_schemes: &LayerMapSchemeList,
-            area_schemes: &AreaSchemeList, area_name: S)
+            area_schemes: &AreaSchemeList, area_name: S, seed: Option<u64>)
             -> EvalSequence {
         let area_name = area_name.into();
         let area_scheme = &area_schemes[&area_name];
@@ -261,10 +261,10 @@ impl EvalSequence {
             "For this evaluation, the encoding dims must equal the area dims. \
             The encoding is representative of layer IV output.");
 
-        let sdrs = Arc::new(Sdrs::new(200, ENCODE_DIMS_0));
-        // let sdr_cursor = SeqCursor::new((4, 8), 25, sdrs.len());
+        let sdrs = Arc::new(Sdrs::new(200, ENCODE_DIMS_0, seed));
+        // let sdr_cursor = SeqCursor::new((4, 8), 25, sdrs.len(), seed);
         let max_seq_len = 5;
-        let sdr_cursor = SeqCursor::new((5, 5), 1, sdrs.len());
+        let sdr_cursor = SeqCursor::new((5, 5), 1, sdrs.len(), seed);
         let trials = Qutex::new(Trials::new(max_seq_len, sdrs.clone()));
 
         EvalSequence {
@@ -539,17 +539,21 @@ pub fn ca_settings() -> CorticalAreaSettings {
 }
 
 
-pub fn eval() {
+pub fn eval(seed: Option<u64>) {
     let layer_map_schemes = define_lm_schemes();
     let area_schemes = define_a_schemes();
 
     let eval_nucl = EvalSequence::new(&layer_map_schemes,
-        &area_schemes, IN_AREA);
+        &area_schemes, IN_AREA, seed);
 
-    let cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
+    let mut cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
         .ca_settings(ca_settings())
         .subcortical_nucleus(eval_nucl);
 
+    if let Some(seed) = seed {
+        cortex_builder = cortex_builder.seed(seed);
+    }
+
     let cortex = cortex_builder.build().unwrap();
 
     let controls = ::spawn_threads(cortex, PRI_AREA, false);
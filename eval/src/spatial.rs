@@ -2,11 +2,11 @@
 
 use std::mem;
 use std::collections::{BTreeMap, HashMap};
-use rand::{FromEntropy, rngs::SmallRng};
+use rand::rngs::SmallRng;
 use rand::distributions::{Range, Distribution};
 use qutex::QrwLock;
 use vibi::bismit::futures::{executor, FutureExt};
-use vibi::bismit::{map, encode, Result as CmnResult, Cortex, CorticalAreaSettings, Thalamus,
+use vibi::bismit::{map, cmn, encode, Result as CmnResult, Cortex, CorticalAreaSettings, Thalamus,
     SubcorticalNucleus, SubcorticalNucleusLayer, CompletionPool, /*CompletionPoolRemote,*/ TractReceiver,
     SamplerKind, SamplerBufferKind, CorticalAreas};
 use vibi::bismit::map::*;
@@ -404,7 +404,8 @@ struct EvalSpatial {
 
 impl EvalSpatial {
     pub fn new<S: Into<String>>(layer_map_schemes: &LayerMapSchemeList,
-            area_schemes: &AreaSchemeList, area_name: S, /*completion_pool_remote: CompletionPoolRemote*/)
+            area_schemes: &AreaSchemeList, area_name: S, seed: Option<u64>,
+            /*completion_pool_remote: CompletionPoolRemote*/)
             -> EvalSpatial {
         let area_name = area_name.into();
         let area_scheme = &area_schemes[&area_name];
@@ -427,7 +428,7 @@ impl EvalSpatial {
         let cell_count = (ENCODE_DIM * ENCODE_DIM) as usize;
         let sdr_active_count = cell_count / SPARSITY;
 
-        let mut rng = SmallRng::from_entropy();
+        let mut rng = cmn::seeded_rng(seed, "eval::EvalSpatial", &[]);
 
         // Produce randomized indexes:
         let pattern_indices: Vec<_> = (0..pattern_count).map(|_| {
@@ -495,19 +496,19 @@ impl SubcorticalNucleus for EvalSpatial {
         let pri_area = cortical_areas.by_key_mut(PRI_AREA).unwrap();
 
         let l4_axns = pri_area.sampler(SamplerKind::Axons(Some(v1_l4_lyr_addr)),
-                SamplerBufferKind::Single, true);
+                SamplerBufferKind::Single, true)?;
 
         // Layer 4 spatial dendrite activity ratings (pre-inhib):
         let l4_den_actvs = pri_area.sampler(SamplerKind::DenActivities(v1_l4_lyr_addr),
-                SamplerBufferKind::Single, false);
+                SamplerBufferKind::Single, false)?;
 
         // Layer 4 spatial cell activity ratings (axon activity, post-inhib):
         let l4_cel_actvs = pri_area.sampler(SamplerKind::SomaActivities(v1_l4_lyr_addr),
-                SamplerBufferKind::Single, false);
+                SamplerBufferKind::Single, false)?;
 
         // Layer 4 spatial cell energies (restlessness):
         let l4_cel_enrgs = pri_area.sampler(SamplerKind::SomaEnergies(v1_l4_lyr_addr),
-                SamplerBufferKind::Single, false);
+                SamplerBufferKind::Single, false)?;
 
         self.samplers = Some(Samplers { l4_axns, l4_den_actvs, l4_cel_actvs,
             l4_cel_enrgs });
@@ -662,17 +663,21 @@ impl SubcorticalNucleus for EvalSpatial {
 }
 
 
-pub fn eval() {
+pub fn eval(seed: Option<u64>) {
     let layer_map_schemes = define_lm_schemes();
     let area_schemes = define_a_schemes();
 
-    let cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
+    let mut cortex_builder = Cortex::builder(layer_map_schemes, area_schemes)
         .ca_settings(ca_settings());
 
+    if let Some(seed) = seed {
+        cortex_builder = cortex_builder.seed(seed);
+    }
+
     // let completion_pool_remote = cortex_builder.get_completion_pool_remote();
 
     let eval_nucl = EvalSpatial::new(cortex_builder.get_layer_map_schemes(),
-        cortex_builder.get_area_schemes(), IN_AREA, /*completion_pool_remote*/);
+        cortex_builder.get_area_schemes(), IN_AREA, seed, /*completion_pool_remote*/);
     let cortex_builder = cortex_builder.subcortical_nucleus(eval_nucl);
 
     let cortex = cortex_builder.build().unwrap();
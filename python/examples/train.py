@@ -0,0 +1,39 @@
+"""Drives a small cortex from Python, feeding random input and watching the
+primary spatial layer."""
+
+import numpy as np
+import pybismit as bm
+
+AREA_SIDE = 24
+
+layer_maps = [
+    bm.LayerMapScheme("visual")
+        .layer(bm.LayerScheme("aff_in").axonal("spatial")
+            .input([("afferent", ["THAL_SP"])]))
+        .layer(bm.LayerScheme("iv").depth(1).tags(["PSAL"]).local()
+            .cellular(bm.CellScheme.spiny_stellate()
+                .tft(bm.TuftScheme.basal().proximal()
+                    .syns_per_den(32)
+                    .thresh_init(400)
+                    .src_lyr("aff_in", syn_reach=8))))
+        .layer(bm.LayerScheme("iv_inhib")
+            .cellular(bm.CellScheme.inhibitory_basket_surround("iv", 4))),
+    bm.LayerMapScheme("external", kind="subcortical")
+        .layer(bm.LayerScheme("ganglion").depth(1).axonal("spatial")
+            .output(["THAL_SP"])),
+]
+
+areas = [
+    bm.AreaScheme("v0", "external", AREA_SIDE).sensory_tract().subcortex(),
+    bm.AreaScheme("v1", "visual", AREA_SIDE).eff_areas(["v0"]),
+]
+
+cortex = bm.Cortex.builder(layer_maps, areas).input_generator("v0").build()
+iv_states = cortex.sampler("v1", "soma_states", layer_name="iv")
+
+for i in range(100):
+    frame = (np.random.rand(AREA_SIDE * AREA_SIDE) > 0.9).astype(np.uint8) * 255
+    cortex.write_tract("v0", frame)
+    cortex.cycle()
+    states = iv_states.read()
+    print("cycle {}: {} active cells".format(i, np.count_nonzero(states)))
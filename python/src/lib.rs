@@ -0,0 +1,557 @@
+//! Python bindings for building and driving a `Cortex`.
+//!
+//! Scheme builders are immutable from Python: each builder method returns a
+//! new, modified copy which allows the familiar chained style:
+//!
+//! ```python
+//! import pybismit as bm
+//!
+//! lmap = bm.LayerMapScheme("visual").layer(
+//!     bm.LayerScheme("iv").depth(1).tags(["PSAL"]).local().cellular(
+//!         bm.CellScheme.spiny_stellate().tft(
+//!             bm.TuftScheme.basal().proximal().syns_per_den(32).thresh_init(400)
+//!                 .src_lyr("aff_in", syn_reach=8))))
+//! ```
+//!
+
+extern crate bismit;
+extern crate numpy;
+extern crate pyo3;
+extern crate ron;
+
+use std::fmt::Display;
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use bismit::{Cortex, InputGeneratorHandle, SamplerKind, SamplerBufferKind,
+    TractReceiver, ReadBuffer, LayerAddress, SchemeConfig};
+use bismit::map::{LayerMapSchemeList, AreaSchemeList, LayerMapKind, LayerTags,
+    AxonTopology, AxonDomain, AxonSignature, AxonTag, AxonTags, InputTrack, ControlCellKind,
+    EncoderScheme, TuftScheme, TuftSourceLayer, TuftSchemeDefinition, CellScheme,
+    CellSchemeDefinition, LayerScheme, LayerSchemeDefinition, LayerMapScheme, AreaScheme};
+
+
+fn py_err<E: Display>(err: E) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+fn value_err<S: Into<String>>(msg: S) -> PyErr {
+    PyValueError::new_err(msg.into())
+}
+
+
+/// An axon tag given either by preset name (e.g. `"THAL_SP"`) or by custom
+/// id.
+#[derive(FromPyObject)]
+enum AxonTagArg {
+    Name(String),
+    Id(u16),
+}
+
+fn axon_tags(tags: Vec<AxonTagArg>) -> PyResult<AxonTags> {
+    let tags = tags.into_iter().map(|tag| match tag {
+        AxonTagArg::Id(id) => Ok(AxonTag::custom(id)),
+        AxonTagArg::Name(name) => AxonTag::preset(&name)
+            .ok_or_else(|| value_err(format!("Unknown axon tag: '{}'.", name))),
+    }).collect::<PyResult<Vec<_>>>()?;
+    Ok(AxonTags::new(&tags))
+}
+
+fn layer_tags(names: Vec<String>) -> PyResult<LayerTags> {
+    names.iter().fold(Ok(LayerTags::DEFAULT), |tags, name| {
+        let tag = LayerTags::from_name(name)
+            .ok_or_else(|| value_err(format!("Unknown layer tag: '{}'.", name)))?;
+        tags.map(|tags| tags | tag)
+    })
+}
+
+fn input_track(track: &str) -> PyResult<InputTrack> {
+    match track.to_lowercase().as_str() {
+        "afferent" => Ok(InputTrack::Afferent),
+        "efferent" => Ok(InputTrack::Efferent),
+        "other" => Ok(InputTrack::Other),
+        _ => Err(value_err(format!("Unknown input track: '{}'.", track))),
+    }
+}
+
+fn axon_topology(topology: &str) -> PyResult<AxonTopology> {
+    match topology.to_lowercase().as_str() {
+        "spatial" => Ok(AxonTopology::Spatial),
+        "nonspatial" => Ok(AxonTopology::Nonspatial),
+        "none" => Ok(AxonTopology::None),
+        _ => Err(value_err(format!("Unknown axon topology: '{}'.", topology))),
+    }
+}
+
+/// Parses a snake case sampler kind name (e.g. `"soma_states"`).
+fn sampler_kind(kind: &str, lyr_addr: Option<LayerAddress>) -> PyResult<SamplerKind> {
+    if kind == "axons" { return Ok(SamplerKind::Axons(lyr_addr)); }
+
+    let lyr_addr = lyr_addr.ok_or_else(|| value_err(format!("A layer must be specified \
+        for sampler kind: '{}'.", kind)))?;
+
+    match kind {
+        "soma_states" => Ok(SamplerKind::SomaStates(lyr_addr)),
+        "soma_energies" => Ok(SamplerKind::SomaEnergies(lyr_addr)),
+        "soma_activities" => Ok(SamplerKind::SomaActivities(lyr_addr)),
+        "soma_flag_sets" => Ok(SamplerKind::SomaFlagSets(lyr_addr)),
+        "tuft_states" => Ok(SamplerKind::TuftStates(lyr_addr)),
+        "tuft_best_den_ids" => Ok(SamplerKind::TuftBestDenIds(lyr_addr)),
+        "tuft_best_den_states_raw" => Ok(SamplerKind::TuftBestDenStatesRaw(lyr_addr)),
+        "tuft_best_den_states" => Ok(SamplerKind::TuftBestDenStates(lyr_addr)),
+        "tuft_prev_states" => Ok(SamplerKind::TuftPrevStates(lyr_addr)),
+        "tuft_prev_best_den_ids" => Ok(SamplerKind::TuftPrevBestDenIds(lyr_addr)),
+        "tuft_prev_best_den_states_raw" => Ok(SamplerKind::TuftPrevBestDenStatesRaw(lyr_addr)),
+        "tuft_prev_best_den_states" => Ok(SamplerKind::TuftPrevBestDenStates(lyr_addr)),
+        "den_states" => Ok(SamplerKind::DenStates(lyr_addr)),
+        "den_states_raw" => Ok(SamplerKind::DenStatesRaw(lyr_addr)),
+        "den_energies" => Ok(SamplerKind::DenEnergies(lyr_addr)),
+        "den_activities" => Ok(SamplerKind::DenActivities(lyr_addr)),
+        "den_thresholds" => Ok(SamplerKind::DenThresholds(lyr_addr)),
+        "syn_states" => Ok(SamplerKind::SynStates(lyr_addr)),
+        "syn_strengths" => Ok(SamplerKind::SynStrengths(lyr_addr)),
+        "syn_src_slc_ids" => Ok(SamplerKind::SynSrcSlcIds(lyr_addr)),
+        "syn_src_col_v_offs" => Ok(SamplerKind::SynSrcColVOffs(lyr_addr)),
+        "syn_src_col_u_offs" => Ok(SamplerKind::SynSrcColUOffs(lyr_addr)),
+        "syn_flag_sets" => Ok(SamplerKind::SynFlagSets(lyr_addr)),
+        "anomaly" => Ok(SamplerKind::Anomaly(lyr_addr)),
+        _ => Err(value_err(format!("Unknown sampler kind: '{}'.", kind))),
+    }
+}
+
+
+/// A tuft scheme builder.
+#[pyclass(name = "TuftScheme")]
+#[derive(Clone)]
+struct PyTuftScheme {
+    inner: TuftSchemeDefinition,
+}
+
+#[pymethods]
+impl PyTuftScheme {
+    #[staticmethod]
+    fn basal() -> Self { PyTuftScheme { inner: TuftScheme::basal() } }
+
+    #[staticmethod]
+    fn apical() -> Self { PyTuftScheme { inner: TuftScheme::apical() } }
+
+    fn proximal(&self) -> Self { PyTuftScheme { inner: self.inner.clone().proximal() } }
+
+    fn distal(&self) -> Self { PyTuftScheme { inner: self.inner.clone().distal() } }
+
+    fn dens_per_tft(&self, dens_per_tft: u32) -> Self {
+        PyTuftScheme { inner: self.inner.clone().dens_per_tft(dens_per_tft) }
+    }
+
+    fn syns_per_den(&self, syns_per_den: u32) -> Self {
+        PyTuftScheme { inner: self.inner.clone().syns_per_den(syns_per_den) }
+    }
+
+    fn max_active_dens_l2(&self, max_active_dens_l2: u8) -> Self {
+        PyTuftScheme { inner: self.inner.clone().max_active_dens_l2(max_active_dens_l2) }
+    }
+
+    fn thresh_init(&self, thresh_init: u32) -> Self {
+        PyTuftScheme { inner: self.inner.clone().thresh_init(thresh_init) }
+    }
+
+    #[pyo3(signature = (name, syn_reach = 0, prevalence = 1))]
+    fn src_lyr(&self, name: String, syn_reach: i8, prevalence: u8) -> Self {
+        PyTuftScheme { inner: self.inner.clone().src_lyr(TuftSourceLayer::define(name)
+            .syn_reach(syn_reach)
+            .prevalence(prevalence)) }
+    }
+}
+
+
+/// A cell scheme builder.
+#[pyclass(name = "CellScheme")]
+#[derive(Clone)]
+struct PyCellScheme {
+    inner: CellSchemeDefinition,
+}
+
+#[pymethods]
+impl PyCellScheme {
+    #[staticmethod]
+    fn pyramidal() -> Self { PyCellScheme { inner: CellScheme::pyramidal() } }
+
+    #[staticmethod]
+    fn spiny_stellate() -> Self { PyCellScheme { inner: CellScheme::spiny_stellate() } }
+
+    #[staticmethod]
+    #[pyo3(signature = (host_lyr_name, field_radius, exe_order = 0))]
+    fn inhibitory_basket_surround(host_lyr_name: String, field_radius: u8, exe_order: usize)
+            -> Self {
+        PyCellScheme { inner: CellScheme::control(
+            ControlCellKind::InhibitoryBasketSurround { host_lyr_name, field_radius }, exe_order) }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (host_lyr_name, field_radius, exe_order = 0))]
+    fn activity_smoother(host_lyr_name: String, field_radius: u8, exe_order: usize) -> Self {
+        PyCellScheme { inner: CellScheme::control(
+            ControlCellKind::ActivitySmoother { host_lyr_name, field_radius }, exe_order) }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (host_lyr_name, exe_order = 0))]
+    fn pyr_outputter(host_lyr_name: String, exe_order: usize) -> Self {
+        PyCellScheme { inner: CellScheme::control(
+            ControlCellKind::PyrOutputter { host_lyr_name }, exe_order) }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (host_lyr_name, exe_order = 0))]
+    fn intra_column_inhib(host_lyr_name: String, exe_order: usize) -> Self {
+        PyCellScheme { inner: CellScheme::control(
+            ControlCellKind::IntraColumnInhib { host_lyr_name }, exe_order) }
+    }
+
+    fn tft(&self, tft: PyTuftScheme) -> Self {
+        PyCellScheme { inner: self.inner.clone().tft(tft.inner) }
+    }
+}
+
+
+/// A layer scheme builder.
+#[pyclass(name = "LayerScheme")]
+#[derive(Clone)]
+struct PyLayerScheme {
+    inner: LayerSchemeDefinition,
+}
+
+#[pymethods]
+impl PyLayerScheme {
+    #[new]
+    fn new(name: String) -> Self {
+        PyLayerScheme { inner: LayerScheme::define(name) }
+    }
+
+    fn depth(&self, depth: u8) -> Self {
+        PyLayerScheme { inner: self.inner.clone().depth(depth) }
+    }
+
+    /// Layer tags by name (e.g. `["PSAL"]`).
+    fn tags(&self, tags: Vec<String>) -> PyResult<Self> {
+        Ok(PyLayerScheme { inner: self.inner.clone().tags(layer_tags(tags)?) })
+    }
+
+    /// An axonal layer with topology: `"spatial"`, `"nonspatial"`, or
+    /// `"none"`.
+    fn axonal(&self, topology: &str) -> PyResult<Self> {
+        Ok(PyLayerScheme { inner: self.inner.clone().axonal(axon_topology(topology)?) })
+    }
+
+    fn cellular(&self, cell_scheme: PyCellScheme) -> Self {
+        PyLayerScheme { inner: self.inner.clone().cellular(cell_scheme.inner) }
+    }
+
+    /// An input layer receiving axons matching each of the `(track, tags)`
+    /// signatures in `sigs` (e.g. `[("afferent", ["THAL_SP"])]`).
+    fn input(&self, sigs: Vec<(String, Vec<AxonTagArg>)>) -> PyResult<Self> {
+        let sigs = sigs.into_iter().map(|(track, tags)| {
+            Ok(AxonSignature::new(Some(input_track(&track)?), axon_tags(tags)?))
+        }).collect::<PyResult<Vec<_>>>()?;
+        Ok(PyLayerScheme { inner: self.inner.clone().axon_domain(AxonDomain::Input(sigs)) })
+    }
+
+    /// An output layer with axons marked with `tags`.
+    fn output(&self, tags: Vec<AxonTagArg>) -> PyResult<Self> {
+        let sig = AxonSignature::new(None, axon_tags(tags)?);
+        Ok(PyLayerScheme { inner: self.inner.clone().axon_domain(AxonDomain::Output(sig)) })
+    }
+
+    fn local(&self) -> Self {
+        PyLayerScheme { inner: self.inner.clone().axon_domain(AxonDomain::Local) }
+    }
+}
+
+
+/// A layer map scheme builder.
+#[pyclass(name = "LayerMapScheme")]
+#[derive(Clone)]
+struct PyLayerMapScheme {
+    inner: LayerMapScheme,
+}
+
+#[pymethods]
+impl PyLayerMapScheme {
+    /// `kind` is either `"cortical"` or `"subcortical"`.
+    #[new]
+    #[pyo3(signature = (name, kind = "cortical"))]
+    fn new(name: String, kind: &str) -> PyResult<Self> {
+        let kind = match kind.to_lowercase().as_str() {
+            "cortical" => LayerMapKind::Cortical,
+            "subcortical" => LayerMapKind::Subcortical,
+            _ => return Err(value_err(format!("Unknown layer map kind: '{}'.", kind))),
+        };
+        Ok(PyLayerMapScheme { inner: LayerMapScheme::new(name, kind) })
+    }
+
+    fn layer(&self, layer: PyLayerScheme) -> Self {
+        PyLayerMapScheme { inner: self.inner.clone().layer(layer.inner) }
+    }
+
+    #[getter]
+    fn name(&self) -> String { self.inner.name().to_owned() }
+}
+
+
+/// An area scheme builder.
+#[pyclass(name = "AreaScheme")]
+#[derive(Clone)]
+struct PyAreaScheme {
+    inner: AreaScheme,
+}
+
+#[pymethods]
+impl PyAreaScheme {
+    #[new]
+    fn new(name: String, layer_map_name: String, side: u32) -> Self {
+        PyAreaScheme { inner: AreaScheme::new(name, layer_map_name, side) }
+    }
+
+    #[staticmethod]
+    fn irregular(name: String, layer_map_name: String, v_size: u32, u_size: u32) -> Self {
+        PyAreaScheme { inner: AreaScheme::irregular(name, layer_map_name,
+            [v_size, u_size]) }
+    }
+
+    /// Input is written using `Cortex.write_tract`.
+    fn sensory_tract(&self) -> Self {
+        PyAreaScheme { inner: self.inner.clone().encoder(EncoderScheme::SensoryTract) }
+    }
+
+    /// Input is written using `Cortex.write_values`.
+    fn vector_encoder(&self, ranges: Vec<(f32, f32)>) -> Self {
+        PyAreaScheme { inner: self.inner.clone().encoder(EncoderScheme::VectorEncoder { ranges }) }
+    }
+
+    /// Any encoder, given in the RON config format (e.g.
+    /// `"Stripes(stripe_size: 4, zeros_first: true)"`).
+    fn encoder(&self, encoder: &str) -> PyResult<Self> {
+        let encoder: EncoderScheme = ron::de::from_str(encoder).map_err(py_err)?;
+        Ok(PyAreaScheme { inner: self.inner.clone().encoder(encoder) })
+    }
+
+    fn subcortex(&self) -> Self {
+        PyAreaScheme { inner: self.inner.clone().subcortex() }
+    }
+
+    fn eff_areas(&self, eff_areas: Vec<String>) -> Self {
+        PyAreaScheme { inner: self.inner.clone().eff_areas(eff_areas) }
+    }
+
+    fn other_area(&self, area_name: String) -> Self {
+        PyAreaScheme { inner: self.inner.clone().other_area(area_name, None) }
+    }
+
+    #[getter]
+    fn name(&self) -> String { self.inner.name().to_owned() }
+}
+
+
+/// Collects schemes and input generators and builds a `Cortex`.
+#[pyclass(name = "CortexBuilder", unsendable)]
+struct PyCortexBuilder {
+    schemes: Option<(LayerMapSchemeList, AreaSchemeList)>,
+    input_generators: Vec<String>,
+    seed: Option<u64>,
+}
+
+#[pymethods]
+impl PyCortexBuilder {
+    /// Adds an input generator for the area named `area_name`. Areas using
+    /// the `sensory_tract` and `vector_encoder` encoders require one.
+    fn input_generator(mut slf: PyRefMut<Self>, area_name: String) -> PyRefMut<Self> {
+        slf.input_generators.push(area_name);
+        slf
+    }
+
+    /// Sets the master seed used by every area and input generator,
+    /// making runs reproducible.
+    fn seed(mut slf: PyRefMut<Self>, seed: u64) -> PyRefMut<Self> {
+        slf.seed = Some(seed);
+        slf
+    }
+
+    fn build(&mut self) -> PyResult<PyCortex> {
+        let (layer_map_sl, area_sl) = self.schemes.take()
+            .ok_or_else(|| py_err("CortexBuilder.build: Already built."))?;
+
+        let mut builder = Cortex::builder(layer_map_sl, area_sl);
+        for area_name in self.input_generators.iter() {
+            builder = builder.input_generator(area_name.as_str());
+        }
+        if let Some(seed) = self.seed { builder = builder.seed(seed); }
+
+        Ok(PyCortex { cortex: builder.build().map_err(py_err)? })
+    }
+}
+
+
+/// A frame copied from a sampler while the GIL is released.
+enum Frame {
+    U8(Vec<u8>),
+    I8(Vec<i8>),
+}
+
+
+/// A readable view of cortical cells or axons.
+#[pyclass(name = "Sampler", unsendable)]
+struct PySampler {
+    rx: TractReceiver,
+}
+
+#[pymethods]
+impl PySampler {
+    /// Returns the latest frame as a NumPy array (`uint8` or `int8`) or
+    /// `None` if `wait` is false and no fresh frame is available.
+    ///
+    /// The GIL is released while waiting for the frame.
+    #[pyo3(signature = (wait = true))]
+    fn read(&self, py: Python, wait: bool) -> PyResult<Option<PyObject>> {
+        let rx = &self.rx;
+        let frame = py.allow_threads(|| -> PyResult<Option<Frame>> {
+            let read_buffer = match rx.recv(wait).wait().map_err(py_err)? {
+                Some(rb) => rb,
+                None => return Ok(None),
+            };
+
+            let is_u8 = match read_buffer {
+                ReadBuffer::RwVecU8(_) | ReadBuffer::FutureReadGuardU8(_) => true,
+                ReadBuffer::RwVecI8(_) | ReadBuffer::FutureReadGuardI8(_) => false,
+            };
+
+            // The read guard is released once copied:
+            if is_u8 {
+                let rg = read_buffer.read_u8().wait().map_err(py_err)?;
+                Ok(Some(Frame::U8(rg.as_slice().to_vec())))
+            } else {
+                let rg = read_buffer.read_i8().wait().map_err(py_err)?;
+                Ok(Some(Frame::I8(rg.as_slice().to_vec())))
+            }
+        })?;
+
+        Ok(frame.map(|frame| match frame {
+            Frame::U8(vec) => PyArray1::from_vec(py, vec).to_object(py),
+            Frame::I8(vec) => PyArray1::from_vec(py, vec).to_object(py),
+        }))
+    }
+}
+
+
+/// A cortex.
+#[pyclass(name = "Cortex", unsendable)]
+struct PyCortex {
+    cortex: Cortex,
+}
+
+impl PyCortex {
+    fn input_generator(&self, area_name: &str) -> PyResult<&InputGeneratorHandle> {
+        let thal = self.cortex.thal();
+        let idx = thal.input_generator_idx(area_name).map_err(py_err)?;
+        thal.input_generator(idx).map_err(py_err)
+    }
+}
+
+#[pymethods]
+impl PyCortex {
+    #[staticmethod]
+    fn builder(layer_maps: Vec<PyLayerMapScheme>, areas: Vec<PyAreaScheme>) -> PyCortexBuilder {
+        let mut layer_map_sl = LayerMapSchemeList::new();
+        for lmap in layer_maps { layer_map_sl.add(lmap.inner); }
+        let area_sl = areas.into_iter().fold(AreaSchemeList::new(), |sl, a| sl.area(a.inner));
+
+        PyCortexBuilder { schemes: Some((layer_map_sl, area_sl)), input_generators: Vec::new(),
+            seed: None }
+    }
+
+    /// Returns a builder using the schemes in a RON config file.
+    #[staticmethod]
+    fn builder_from_file(path: &str) -> PyResult<PyCortexBuilder> {
+        let (layer_map_sl, area_sl) = SchemeConfig::load(path).map_err(py_err)?.into_parts();
+        Ok(PyCortexBuilder { schemes: Some((layer_map_sl, area_sl)),
+            input_generators: Vec::new(), seed: None })
+    }
+
+    #[pyo3(signature = (cycles = 1))]
+    fn cycle(&mut self, py: Python, cycles: u32) -> PyResult<()> {
+        for _ in 0..cycles {
+            self.cortex.cycle().map_err(py_err)?;
+            py.check_signals()?;
+        }
+        Ok(())
+    }
+
+    fn finish_queues(&self) {
+        self.cortex.finish_queues();
+    }
+
+    fn area_names(&self) -> Vec<String> {
+        self.cortex.areas().iter().map(|a| a.area_map().area_name().to_owned()).collect()
+    }
+
+    /// Writes a frame to the input tract of the area named `area_name`
+    /// (which must use the `sensory_tract` encoder).
+    fn write_tract(&self, area_name: &str, tract: PyReadonlyArray1<u8>) -> PyResult<()> {
+        let tract = tract.as_slice()?;
+        self.input_generator(area_name)?.write_tract(tract).map_err(py_err)
+    }
+
+    /// Writes values to the area named `area_name` (which must use the
+    /// `vector_encoder` encoder).
+    fn write_values(&self, area_name: &str, values: Vec<f32>) -> PyResult<()> {
+        self.input_generator(area_name)?.write_values(&values).map_err(py_err)
+    }
+
+    /// Returns a sampler of `kind` (e.g. `"axons"`, `"soma_states"`) for the
+    /// layer named `layer_name` within the area named `area_name`. A layer
+    /// is optional only for `"axons"`.
+    #[pyo3(signature = (area_name, kind, layer_name = None, backpressure = false))]
+    fn sampler(&mut self, area_name: &str, kind: &str, layer_name: Option<&str>,
+            backpressure: bool) -> PyResult<PySampler> {
+        let area = self.cortex.areas_mut().by_key_mut(area_name)
+            .ok_or_else(|| value_err(format!("No area named: '{}'.", area_name)))?;
+
+        let lyr_addr = match layer_name {
+            Some(layer_name) => Some(area.area_map().layer_map().layers().by_key(layer_name)
+                .ok_or_else(|| value_err(format!("No layer named '{}' in area '{}'.",
+                    layer_name, area_name)))?
+                .layer_addr()),
+            None => None,
+        };
+
+        let kind = sampler_kind(kind, lyr_addr)?;
+        let rx = area.sampler(kind, SamplerBufferKind::Single, backpressure)
+            .map_err(|err| value_err(err.to_string()))?;
+        Ok(PySampler { rx })
+    }
+
+    fn set_learning(&mut self, enabled: bool) {
+        self.cortex.set_learning_enabled(enabled);
+    }
+
+    fn save_state(&mut self, path: &str) -> PyResult<()> {
+        self.cortex.save_state(path).map_err(py_err)
+    }
+
+    fn load_state(&mut self, path: &str) -> PyResult<()> {
+        self.cortex.load_state(path).map_err(py_err)
+    }
+}
+
+
+#[pymodule]
+fn pybismit(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyTuftScheme>()?;
+    m.add_class::<PyCellScheme>()?;
+    m.add_class::<PyLayerScheme>()?;
+    m.add_class::<PyLayerMapScheme>()?;
+    m.add_class::<PyAreaScheme>()?;
+    m.add_class::<PyCortexBuilder>()?;
+    m.add_class::<PyCortex>()?;
+    m.add_class::<PySampler>()?;
+    Ok(())
+}
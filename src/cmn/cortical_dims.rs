@@ -30,7 +30,7 @@ use SlcId;
 // than a cell and it's stored inverted. Don't think too hard about it.
 
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 /// Dimensions for a cortical, area, layer, slice, or other subdivison thereof.
 pub struct CorticalDims {
     depth: SlcId, // in cell-edges (NxMx1)
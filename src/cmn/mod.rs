@@ -14,6 +14,7 @@ mod error;
 mod tract_frame;
 mod map_store;
 mod double_buffer_mutex;
+mod seed;
 // mod xorshiftrng;
 pub mod util;
 pub mod completion_pool;
@@ -28,7 +29,7 @@ use std::collections::{BTreeMap, HashSet};
 // use std::ops::AddAssign;
 use num::{FromPrimitive, };
 // use num::{Num, NumCast};
-use rand::{FromEntropy, rngs::SmallRng};
+use rand::Rng;
 use rand::distributions::{Distribution, Range};
 #[allow(unused_imports)]
 use find_folder::Search;
@@ -46,6 +47,7 @@ pub use self::tract_frame::{TractFrame, TractFrameMut};
 pub use self::map_store::MapStore;
 pub use self::slice_dims::{calc_scale, scale};
 pub use self::double_buffer_mutex::DoubleBufferMutex;
+pub use self::seed::{child_seed, seeded_rng, rng_from_u64, hash_str, SplitMix64};
 
 // // A clone of the counterpart types in the `rand` crate. Duplicated due to
 // // some sort of bug with deriving `Debug`.
@@ -230,11 +232,20 @@ pub const DENDRITE_DEFAULT_INITIAL_THRESHOLD: u32 = 0;
 //pub const LEARNING_ACTIVE: bool = true;
 pub const SYNAPSE_REACH_MAX: i8 = std::i8::MAX;
 pub const SYNAPSE_REACH_MIN: i8 = std::i8::MIN + 1;
+// Defaults for `cortex::LearningParams`:
 pub const SYNAPSE_STRENGTH_FLOOR: i8 = -25;             // DIRECTLY AFFECTS LEARNING RATE
 pub const SYNAPSE_REGROWTH_INTERVAL: usize = 200;         // DIRECTLY AFFECTS LEARNING RATE
-pub const SYNAPSE_STRENGTH_INITIAL_DEVIATION: i8 = 5;
+pub const SYNAPSE_STRENGTH_INITIAL_DEVIATION: i8 = 3;
 pub const DST_SYNAPSE_STRENGTH_DEFAULT: i8 = 0;
 pub const PRX_SYNAPSE_STRENGTH_DEFAULT: i8 = 0;
+pub const DST_POTENTIATION_RATE_L2I: i32 = 0;
+pub const DST_DEPRESSION_RATE_L2I: i32 = 5;
+pub const PRX_POTENTIATION_RATE_L2I: i32 = 0;
+pub const PRX_DEPRESSION_RATE_L2I: i32 = 2;
+pub const SYNAPSE_STRENGTH_INTENSITY_REDUCTION_L2: i8 = 3;
+// Neuromodulation (see `cortex::Neuromodulation`):
+pub const NEUROMODULATION_UNITY: i8 = 127;
+pub const NEUROMODULATION_RND_CTR: u32 = 0x4e4d;
 pub const MAX_HRZ_DIM_SIZE: u32 = 255;
 
 // Scaling coefficient. Higher values create more potential precision.
@@ -438,6 +449,9 @@ pub fn base_build_options<'b>() -> ProgramBuilder<'b> {
     pb.cmplr_def("DEN_BASAL_PROXIMAL_FLAG", DEN_BASAL_PROXIMAL_FLAG as i32);
     pb.cmplr_def("DEN_BASAL_DISTAL_FLAG", DEN_BASAL_DISTAL_FLAG as i32);
     pb.cmplr_def("DEN_APICAL_DISTAL_FLAG", DEN_APICAL_DISTAL_FLAG as i32);
+    pb.cmplr_def("SYNAPSE_STRENGTH_INTENSITY_REDUCTION_L2",
+        SYNAPSE_STRENGTH_INTENSITY_REDUCTION_L2 as i32);
+    pb.cmplr_def("NEUROMODULATION_RND_CTR", NEUROMODULATION_RND_CTR as i32);
     pb
 }
 
@@ -521,8 +535,10 @@ pub fn hex_tile_offs_scaled(radius: SrcOfs) -> Vec<(SrcOfs, SrcOfs)> {
 /* SPARSE_VEC():
 
     sp_fctr_log2: sparsity factor (log2)
+    rng: see `cmn::seeded_rng` for a reproducible generator
 */
-pub fn sparse_vec<T: OclScl>(size: usize, min_val: T, max_val: T, sp_fctr_log2: usize) -> Vec<T> {
+pub fn sparse_vec<T: OclScl, R: Rng>(size: usize, min_val: T, max_val: T, sp_fctr_log2: usize,
+        rng: &mut R) -> Vec<T> {
     let mut vec: Vec<T> = iter::repeat(min_val).cycle().take(size).collect();
 
     let len = vec.len();
@@ -532,12 +548,11 @@ pub fn sparse_vec<T: OclScl>(size: usize, min_val: T, max_val: T, sp_fctr_log2:
     let range_max: i64 = max_val.to_i64().expect("cmn::sparse_vec(): max_val.to_i64()") as i64 + 1;
     let range_min: i64 = min_val.to_i64().expect("cmn::sparse_vec(): min_val.to_i64()") as i64;
 
-    let mut rng = SmallRng::from_entropy();
     let val_range = Range::new(range_min, range_max);
     let idx_range = Range::new(0, 1 << sp_fctr_log2);
 
     for i in 0..notes {
-        vec[(i << sp_fctr_log2) + idx_range.sample(&mut rng)] = FromPrimitive::from_i64(val_range.sample(&mut rng)).expect("cmn::sparse_vec()");
+        vec[(i << sp_fctr_log2) + idx_range.sample(rng)] = FromPrimitive::from_i64(val_range.sample(rng)).expect("cmn::sparse_vec()");
         //vec[(i << sp_fctr_log2) + idx_range.sample(&mut rng)] = std::num::cast(val_range.sample(&mut rng)).expect("cmn.rs");
     }
 
@@ -0,0 +1,81 @@
+//! Seed derivation for reproducible runs.
+//!
+//! A single master seed (see `CorticalAreaSettings::seed` and
+//! `cortex::Builder::seed`) is expanded into a stable child seed for each
+//! random number generator, keyed by a component name and a list of
+//! integers (usually an area and layer id). Child seeds depend only upon
+//! the master seed and the key so that the order in which components are
+//! constructed does not matter.
+//!
+
+use rand::{FromEntropy, SeedableRng, rngs::SmallRng};
+
+
+const GOLDEN_GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+
+/// FNV-1a, used to derive a stable seed from a string.
+pub fn hash_str(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// The SplitMix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+
+/// SplitMix64, a small deterministic generator (unlike `rand`, its output
+/// will never change between versions).
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(GOLDEN_GAMMA);
+        mix(self.0)
+    }
+
+    /// Returns a value within `[0, n)`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+
+/// Returns a child seed derived from `master`, `component` and `key`.
+pub fn child_seed(master: u64, component: &str, key: &[u64]) -> u64 {
+    let mut state = mix(master ^ hash_str(component));
+    for &k in key {
+        state = mix(state.wrapping_add(GOLDEN_GAMMA) ^ k);
+    }
+    state
+}
+
+/// Returns a generator seeded from the child seed for `component` and `key`
+/// or, if `master` is `None`, from entropy.
+pub fn seeded_rng(master: Option<u64>, component: &str, key: &[u64]) -> SmallRng {
+    match master {
+        Some(master) => rng_from_u64(child_seed(master, component, key)),
+        None => SmallRng::from_entropy(),
+    }
+}
+
+/// Returns a generator whose full seed is expanded from `seed`.
+pub fn rng_from_u64(seed: u64) -> SmallRng {
+    let mut full_seed = <SmallRng as SeedableRng>::Seed::default();
+    let mut gen = SplitMix64::new(seed);
+    for chunk in full_seed.as_mut().chunks_mut(8) {
+        let word = gen.next_u64();
+        for (i, byte) in chunk.iter_mut().enumerate() {
+            *byte = (word >> (i * 8)) as u8;
+        }
+    }
+    SmallRng::from_seed(full_seed)
+}
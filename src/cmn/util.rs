@@ -1,6 +1,23 @@
-
 use ocl::{Buffer, OclPrm};
 
 pub fn buffer_uid<T: OclPrm>(buf: &Buffer<T>) -> u64 {
     buf.as_core().as_ptr() as u64
 }
+
+
+/// A block of memory with an id which is unique for as long as it exists.
+pub trait BufferUid {
+    fn buffer_uid(&self) -> u64;
+}
+
+impl<T: OclPrm> BufferUid for Buffer<T> {
+    fn buffer_uid(&self) -> u64 {
+        buffer_uid(self)
+    }
+}
+
+impl<'b, B: BufferUid> BufferUid for &'b B {
+    fn buffer_uid(&self) -> u64 {
+        (**self).buffer_uid()
+    }
+}
@@ -0,0 +1,223 @@
+//! Per-cycle anomaly scores for temporal (pyramidal) layers.
+//!
+//! A cell is considered predicted when any of its distal tufts was active on
+//! the previous cycle. The raw anomaly score of a cycle is the fraction of
+//! active cells which were not predicted. The likelihood compares a short
+//! term average of raw scores against their long term distribution, rising
+//! toward `1.0` when recent input is unusually poorly predicted.
+//!
+
+use std::collections::VecDeque;
+use cmn::{CmnError, CmnResult};
+use map::LayerAddress;
+
+
+/// The number of raw scores making up the long term distribution.
+pub const LIKELIHOOD_WINDOW: usize = 1000;
+/// The number of recent raw scores averaged before comparison.
+pub const LIKELIHOOD_SHORT_WINDOW: usize = 10;
+/// The number of raw scores required before a likelihood other than `0.5`
+/// is reported.
+pub const LIKELIHOOD_MIN_SAMPLES: usize = 100;
+/// The minimum standard deviation used, preventing a perfectly predictable
+/// history from producing extreme likelihoods.
+const LIKELIHOOD_MIN_STD_DEV: f64 = 0.01;
+
+
+/// Returns the complementary error function of `x` (Abramowitz & Stegun
+/// 7.1.26, maximum error: 1.5e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 +
+        t * (-1.453152027 + t * 1.061405429))));
+    let erfc_z = poly * (-z * z).exp();
+    if x >= 0.0 { erfc_z } else { 2.0 - erfc_z }
+}
+
+
+/// The anomaly score of a single layer for a single cycle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyScore {
+    lyr_addr: LayerAddress,
+    cycle: u64,
+    active: u32,
+    predicted: u32,
+    raw: f32,
+    likelihood: f32,
+}
+
+impl AnomalyScore {
+    /// The length of a sampler frame (see `SamplerKind::Anomaly`).
+    pub const FRAME_LEN: usize = 24;
+
+    pub fn new(lyr_addr: LayerAddress, cycle: u64, active: u32, predicted: u32, likelihood: f32)
+            -> AnomalyScore {
+        assert!(predicted <= active);
+        let raw = if active == 0 { 0.0 } else { 1.0 - (predicted as f32 / active as f32) };
+        AnomalyScore { lyr_addr, cycle, active, predicted, raw, likelihood }
+    }
+
+    /// Encodes this score as a sampler frame: the active count, predicted
+    /// count, raw score, likelihood, and the low and high halves of the
+    /// cycle, each as four little-endian bytes.
+    pub fn to_frame(&self) -> [u8; 24] {
+        let mut frame = [0u8; 24];
+        let words = [self.active, self.predicted, self.raw.to_bits(), self.likelihood.to_bits(),
+            self.cycle as u32, (self.cycle >> 32) as u32];
+        for (chunk, &word) in frame.chunks_mut(4).zip(words.iter()) {
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                *byte = (word >> (i * 8)) as u8;
+            }
+        }
+        frame
+    }
+
+    /// Decodes a sampler frame produced by `::to_frame`.
+    pub fn from_frame(lyr_addr: LayerAddress, frame: &[u8]) -> CmnResult<AnomalyScore> {
+        if frame.len() != AnomalyScore::FRAME_LEN {
+            return Err(CmnError::new(format!("AnomalyScore::from_frame: Invalid frame length: \
+                {} (expected: {}).", frame.len(), AnomalyScore::FRAME_LEN)));
+        }
+        let word = |i: usize| frame[(i * 4)..(i * 4 + 4)].iter().rev()
+            .fold(0u32, |word, &byte| (word << 8) | byte as u32);
+
+        Ok(AnomalyScore {
+            lyr_addr,
+            cycle: word(4) as u64 | (word(5) as u64) << 32,
+            active: word(0),
+            predicted: word(1),
+            raw: f32::from_bits(word(2)),
+            likelihood: f32::from_bits(word(3)),
+        })
+    }
+
+    /// The address of the scored layer.
+    #[inline] pub fn lyr_addr(&self) -> LayerAddress { self.lyr_addr }
+
+    /// The index of the scored cycle, counting from the first cycle scored
+    /// by the layer's tracker (see `AnomalyTracker::update`).
+    #[inline] pub fn cycle(&self) -> u64 { self.cycle }
+
+    /// The number of active cells.
+    #[inline] pub fn active(&self) -> u32 { self.active }
+
+    /// The number of active cells which were predicted on the previous cycle.
+    #[inline] pub fn predicted(&self) -> u32 { self.predicted }
+
+    /// The fraction of active cells which were not predicted (`0.0` when no
+    /// cells are active).
+    #[inline] pub fn raw(&self) -> f32 { self.raw }
+
+    /// The probability that recent raw scores are higher than usual.
+    #[inline] pub fn likelihood(&self) -> f32 { self.likelihood }
+}
+
+
+/// Computes the anomaly score of a layer each cycle from its axon and tuft
+/// states.
+#[derive(Debug, Clone)]
+pub struct AnomalyTracker {
+    lyr_addr: LayerAddress,
+    cell_count: usize,
+    distal_tft_ids: Vec<usize>,
+    predicted: Vec<bool>,
+    history: VecDeque<f32>,
+    latest: Option<AnomalyScore>,
+    next_cycle: u64,
+}
+
+impl AnomalyTracker {
+    /// Returns a new tracker for a layer of `cell_count` cells whose distal
+    /// tufts have the ids `distal_tft_ids`.
+    pub fn new(lyr_addr: LayerAddress, cell_count: usize, distal_tft_ids: Vec<usize>)
+            -> CmnResult<AnomalyTracker> {
+        if distal_tft_ids.is_empty() {
+            return Err(format!("AnomalyTracker::new: Layer {} has no distal tufts and cannot \
+                make predictions.", lyr_addr).into());
+        }
+        Ok(AnomalyTracker {
+            lyr_addr,
+            cell_count,
+            distal_tft_ids,
+            predicted: vec![false; cell_count],
+            history: VecDeque::with_capacity(LIKELIHOOD_WINDOW),
+            latest: None,
+            next_cycle: 0,
+        })
+    }
+
+    /// Returns the likelihood of the most recent raw scores given the long
+    /// term distribution.
+    fn likelihood(&self) -> f32 {
+        if self.history.len() < LIKELIHOOD_MIN_SAMPLES { return 0.5; }
+
+        let count = self.history.len() as f64;
+        let mean = self.history.iter().map(|&s| s as f64).sum::<f64>() / count;
+        let var = self.history.iter().map(|&s| (s as f64 - mean).powi(2)).sum::<f64>() / count;
+        let std_dev = var.sqrt().max(LIKELIHOOD_MIN_STD_DEV);
+
+        let short_len = LIKELIHOOD_SHORT_WINDOW.min(self.history.len());
+        let short_mean = self.history.iter().rev().take(short_len)
+            .map(|&s| s as f64).sum::<f64>() / short_len as f64;
+
+        // The normal cumulative distribution at the short term average:
+        let z = (short_mean - mean) / std_dev;
+        (1.0 - 0.5 * erfc(z / ::std::f64::consts::SQRT_2)) as f32
+    }
+
+    /// Scores the current cycle and records which cells are predicted for the
+    /// next.
+    ///
+    /// Each score is given the next cycle index, which is not affected by
+    /// `::reset`.
+    ///
+    /// `axon_states` are the layer's axons (one per cell) and `tuft_states`
+    /// are its tuft states (tuft-major, `cell_count` per tuft).
+    pub fn update(&mut self, axon_states: &[u8], tuft_states: &[u8]) -> CmnResult<AnomalyScore> {
+        let cell_count = self.cell_count;
+        if axon_states.len() != cell_count ||
+                self.distal_tft_ids.iter().any(|&id| tuft_states.len() < (id + 1) * cell_count) {
+            return Err(format!("AnomalyTracker::update: Invalid state lengths for layer {} \
+                (axons: {}, tufts: {}, cells: {}).", self.lyr_addr, axon_states.len(),
+                tuft_states.len(), self.cell_count).into());
+        }
+
+        let mut active = 0;
+        let mut predicted = 0;
+        for (&axn, &was_predicted) in axon_states.iter().zip(self.predicted.iter()) {
+            if axn != 0 {
+                active += 1;
+                if was_predicted { predicted += 1; }
+            }
+        }
+
+        let distal_tft_ids = &self.distal_tft_ids;
+        for (cel_idx, pred) in self.predicted.iter_mut().enumerate() {
+            *pred = distal_tft_ids.iter()
+                .any(|&tft_id| tuft_states[tft_id * cell_count + cel_idx] != 0);
+        }
+
+        let raw = AnomalyScore::new(self.lyr_addr, 0, active, predicted, 0.0).raw();
+        if self.history.len() == LIKELIHOOD_WINDOW { self.history.pop_front(); }
+        self.history.push_back(raw);
+
+        let score = AnomalyScore::new(self.lyr_addr, self.next_cycle, active, predicted,
+            self.likelihood());
+        self.next_cycle += 1;
+        self.latest = Some(score);
+        Ok(score)
+    }
+
+    /// Forgets all predictions and score history.
+    pub fn reset(&mut self) {
+        for pred in self.predicted.iter_mut() { *pred = false; }
+        self.history.clear();
+        self.latest = None;
+    }
+
+    /// The score of the most recent cycle.
+    #[inline] pub fn latest(&self) -> Option<AnomalyScore> { self.latest }
+    #[inline] pub fn lyr_addr(&self) -> LayerAddress { self.lyr_addr }
+    #[inline] pub fn cell_count(&self) -> usize { self.cell_count }
+}
@@ -1,13 +1,14 @@
 use std::collections::HashMap;
 use std::ops::Range;
+use futures::executor;
 use futures::future::{FutureExt};
-use ocl::{ProQue, Buffer, Event, EventList, Queue, MemFlags};
+use ocl::{Buffer, Event, EventList, Queue, MemFlags};
 use ocl::traits::MemLen;
 use cmn::{self, CmnError, CmnResult};
 use map::{AreaMap, LayerAddress, ExecutionGraph, AxonDomainRoute, CommandRelations, CorticalBuffer,
     ThalamicTract, CommandUid};
 use ::{Thalamus, CompletionPool};
-use cortex::{SensoryFilter};
+use cortex::{SensoryFilter, SnapshotWriter, SnapshotReader, Backend, Storage};
 #[cfg(any(test, feature = "eval"))]
 pub use self::tests::{AxonSpaceTest, AxnCoords};
 
@@ -73,6 +74,27 @@ impl IoInfo {
 }
 
 
+/// The command queues used for axon space I/O by an area using the device
+/// backend.
+#[derive(Debug, Clone)]
+pub struct IoQueues {
+    pub read: Queue,
+    pub write: Queue,
+    // Only necessary until we use bufferstream/sink. Also, multiple layers
+    // using this queue could cause deadlock.
+    pub unmap: Queue,
+}
+
+impl IoQueues {
+    /// Blocks until all commands in each queue have completed.
+    pub fn finish(&self) -> CmnResult<()> {
+        self.write.finish()?;
+        self.read.finish()?;
+        self.unmap.finish().map_err(CmnError::from)
+    }
+}
+
+
 /// A group of `IoInfo` structs sharing a common `AxonDomainRoute`.
 ///
 #[derive(Debug)]
@@ -86,7 +108,7 @@ impl IoInfoGroup {
             tract_src_lyr_addrs: Vec<(LayerAddress, Option<LayerAddress>)>,
             filter_chains: &Vec<(LayerAddress, Vec<SensoryFilter>)>,
             exe_graph: &mut ExecutionGraph,
-            axon_states: &Buffer<u8>,
+            axon_states: &Storage<u8>,
             thal: &Thalamus,
         ) -> IoInfoGroup
     {
@@ -216,7 +238,7 @@ pub struct IoInfoCache {
 
 impl IoInfoCache {
     pub fn new(area_map: &AreaMap, filter_chains: &Vec<(LayerAddress, Vec<SensoryFilter>)>,
-        exe_graph: &mut ExecutionGraph, axon_states: &Buffer<u8>, thal: &Thalamus) -> IoInfoCache
+        exe_graph: &mut ExecutionGraph, axon_states: &Storage<u8>, thal: &Thalamus) -> IoInfoCache
     {
         let group_route_list = [AxonDomainRoute::Input, AxonDomainRoute::Output];
 
@@ -305,31 +327,52 @@ impl IoInfoCache {
 #[derive(Debug)]
 pub struct AxonSpace {
     area_id: usize,
-    area_name: &'static str,
-    states: Buffer<u8>,
+    area_name: String,
+    states: Storage<u8>,
     filter_chains: Vec<(LayerAddress, Vec<SensoryFilter>)>,
     io_info: IoInfoCache,
-    read_queue: Queue,
-    write_queue: Queue,
-    // Only necessary until we use bufferstream/sink. Also, multiple layers
-    // using this queue could cause deadlock.
-    unmap_queue: Queue,
+    io_queues: Option<IoQueues>,
 }
 
 impl AxonSpace {
-    pub fn new(area_map: &AreaMap, ocl_pq: &ProQue, read_queue: Queue, write_queue: Queue,
-            unmap_queue: Queue, exe_graph: &mut ExecutionGraph, thal: &mut Thalamus)
+    /// Returns a new axon space.
+    ///
+    /// `io_queues` are required by, and only used with, the device backend.
+    /// Sensory filters have no host implementation so an area using the
+    /// host backend may not have filter chains.
+    pub fn new(area_map: &AreaMap, backend: &Backend, io_queues: Option<IoQueues>,
+            exe_graph: &mut ExecutionGraph, thal: &mut Thalamus)
             -> CmnResult<AxonSpace>
     {
-        println!("{mt}{mt}AXONS::NEW(): new axons with: total axons: {}",
-            area_map.slice_map().to_len_padded(ocl_pq.max_wg_size().unwrap()), mt = cmn::MT);
+        let (states, ocl_pq, write_queue) = match (backend.pq(), io_queues.as_ref()) {
+            (Some(ocl_pq), Some(io_queues)) => {
+                println!("{mt}{mt}AXONS::NEW(): new axons with: total axons: {}",
+                    area_map.slice_map().to_len_padded(ocl_pq.max_wg_size().unwrap()), mt = cmn::MT);
+
+                let states = Buffer::<u8>::builder()
+                    .queue(io_queues.write.clone())
+                    .flags(MemFlags::new().read_write().alloc_host_ptr())
+                    .len(area_map.slice_map())
+                    .fill_val(0)
+                    .build()?;
+
+                (Storage::Device(states), Some(ocl_pq), Some(io_queues.write.clone()))
+            },
+            (None, _) => {
+                println!("{mt}{mt}AXONS::NEW(): new axons with: total axons: {}",
+                    area_map.slice_map().to_len(), mt = cmn::MT);
+
+                if !area_map.filter_chain_schemes().is_empty() {
+                    return Err(format!("AxonSpace::new: Area '{}' has sensory filter chains, \
+                        which require the device backend (sensory filters have no host \
+                        implementation).", area_map.area_name()).into());
+                }
 
-        let states = Buffer::<u8>::builder()
-            .queue(write_queue.clone())
-            .flags(MemFlags::new().read_write().alloc_host_ptr())
-            .len(area_map.slice_map())
-            .fill_val(0)
-            .build()?;
+                (backend.buffer::<u8>(area_map.slice_map().to_len(), 0)?, None, None)
+            },
+            (Some(_), None) => return Err("AxonSpace::new: The device backend requires I/O \
+                queues.".into()),
+        };
 
         /*=============================================================================
         =================================== FILTERS ===================================
@@ -355,7 +398,7 @@ impl AxonSpace {
 
                     let (output_buffer, output_slc_range) = if filter_is_last {
                         debug_assert!(i == 0);
-                        (&states,
+                        (states.device()?,
                             src_lyr_info.tar_slc_range().clone())
                     } else {
                         debug_assert!(i > 0);
@@ -397,8 +440,8 @@ impl AxonSpace {
                         src_lyr_info.dims(),
                         output_buffer,
                         output_slc_range,
-                        &ocl_pq,
-                        &write_queue,
+                        ocl_pq.expect("AxonSpace::new: Internal error: no device backend"),
+                        write_queue.as_ref().expect("AxonSpace::new: Internal error: no I/O queues"),
                         exe_graph)?
                 };
 
@@ -419,24 +462,26 @@ impl AxonSpace {
 
         Ok(AxonSpace {
             area_id: area_map.area_id(),
-            area_name: area_map.area_name(),
+            area_name: area_map.area_name().to_owned(),
             states,
             filter_chains,
             io_info,
-            read_queue,
-            write_queue,
-            unmap_queue,
+            io_queues,
         })
     }
 
     /// Creates a sub buffer for a range of axon space.
+    ///
+    /// Only available with the device backend.
     pub fn create_sub_buffer(&self, range: &Range<u32>) -> CmnResult<Buffer<u8>> {
         assert!((range.end as usize) <= self.states.len());
-        self.states.create_sub_buffer(None, range.start as usize, range.len())
+        self.states.device()?.create_sub_buffer(None, range.start as usize, range.len())
             .map_err(|err| CmnError::from(err))
     }
 
     /// Creates a sub buffer for a layer of axon space.
+    ///
+    /// Only available with the device backend.
     pub fn create_layer_sub_buffer(&self, src_lyr_addr: LayerAddress, route: AxonDomainRoute)
             -> CmnResult<Buffer<u8>> {
         let flags = match route {
@@ -452,7 +497,7 @@ impl AxonSpace {
         let origin = lyr_info.axon_range.start;
         let len = lyr_info.axon_range.len();
 
-        self.states.create_sub_buffer(flags, origin, len).map_err(|err| CmnError::from(err))
+        self.states.device()?.create_sub_buffer(flags, origin, len).map_err(|err| CmnError::from(err))
     }
 
     pub fn set_exe_order_intake(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
@@ -526,7 +571,15 @@ impl AxonSpace {
                     if let &IoExeCmd::Write(_, cmd_idx) = io_lyr.exe_cmd() {
                         let event = if DISABLE_IO {
                             None
+                        } else if let Storage::Host(_) = self.states {
+                            // Host storage is written once the tract is readable:
+                            let reader = executor::block_on(future_reader)?;
+                            debug_assert_eq!(reader.len(), axon_range.len());
+                            self.states.write_at(&reader, axon_range.start as usize)?;
+                            None
                         } else {
+                            let (states, io_queues) = (self.states.device()?,
+                                self.io_queues.as_ref().expect("AxonSpace::intake: No I/O queues"));
                             exe_graph.cmd(cmd_idx).unwrap().event().map(|ev| ev.wait_for().unwrap());
                             let mut ev = Event::empty();
 
@@ -545,8 +598,8 @@ impl AxonSpace {
 
                             ////////////// MAP BUFFER:
                             let future_map = unsafe {
-                                self.states.map()
-                                    .queue(&self.write_queue)
+                                states.map()
+                                    .queue(&io_queues.write)
                                     .write_invalidate()
                                     .offset(axon_range.start as usize)
                                     .len(axon_range.len())
@@ -559,7 +612,7 @@ impl AxonSpace {
                                     .enq_async()?
                                     // .ewait_unmap(exe_graph.get_req_events(cmd_idx)?)
                                     .enew_unmap(&mut ev)
-                                    .with_unmap_queue(self.unmap_queue.clone())
+                                    .with_unmap_queue(io_queues.unmap.clone())
                             };
 
                             let future_write = future_reader.join(future_map)
@@ -598,6 +651,13 @@ impl AxonSpace {
                 if let &IoExeCmd::Read(_, cmd_idx) = io_lyr.exe_cmd() {
                     let event = if DISABLE_IO || DISABLE_OUTPUT {
                         None
+                    } else if let Storage::Host(_) = self.states {
+                        // Host storage is read once the tract is writable:
+                        let mut writer = executor::block_on(
+                            thal.tract().write(io_lyr.tract_area_id())?)?;
+                        debug_assert_eq!(io_lyr.axon_range().len(), writer.len());
+                        self.states.read_into(io_lyr.axon_range().start as usize, &mut writer[..])?;
+                        None
                     } else {
                         let future_writer = thal.tract().write(io_lyr.tract_area_id())?;
                         let future_writer_len = unsafe { (*future_writer.as_ptr()).len() };
@@ -605,8 +665,8 @@ impl AxonSpace {
 
                         let mut ev = Event::empty();
 
-                        let future_read = Box::new(self.states.read(future_writer)
-                            .queue(&self.read_queue)
+                        let future_read = Box::new(self.states.device()?.read(future_writer)
+                            .queue(&self.io_queues.as_ref().expect("AxonSpace::output: No I/O queues").read)
                             .offset(io_lyr.axon_range().start as usize)
                             .len(io_lyr.axon_range().len())
                             .ewait(exe_graph.get_req_events(cmd_idx)?)
@@ -628,14 +688,25 @@ impl AxonSpace {
         Ok(())
     }
 
-    pub fn states(&self) -> &Buffer<u8> { &self.states }
+    /// Writes axon states to a snapshot.
+    pub fn save_state(&self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_buffer("axns.states", &self.states)
+    }
+
+    /// Restores axon states from a snapshot.
+    pub fn load_state(&self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_buffer("axns.states", &self.states)
+    }
+
+    pub fn states(&self) -> &Storage<u8> { &self.states }
+    pub fn io_queues(&self) -> Option<&IoQueues> { self.io_queues.as_ref() }
     pub fn area_id(&self) -> usize { self.area_id }
     pub fn filter_chains(&self) -> &[(LayerAddress, Vec<SensoryFilter>)] { self.filter_chains.as_slice() }
     pub fn filter_chains_mut(&mut self) -> &mut [(LayerAddress, Vec<SensoryFilter>)] {
         self.filter_chains.as_mut_slice() }
     pub fn io_info(&self) -> &IoInfoCache { &self.io_info }
     pub fn io_info_mut(&mut self) -> &mut IoInfoCache { &mut self.io_info }
-    pub fn area_name(&self) -> &'static str { self.area_name }
+    pub fn area_name<'s>(&'s self) -> &'s str { &self.area_name }
 }
 
 
@@ -654,16 +725,15 @@ pub mod tests {
 
     impl AxonSpaceTest for AxonSpace {
         fn axon_state(&self, idx: usize) -> u8 {
-            self.states.default_queue().unwrap().finish().unwrap();
+            self.states.finish().unwrap();
             let mut sdr = vec![0u8];
-            self.states.cmd().read(&mut sdr).offset(idx).enq().unwrap();
+            self.states.read_into(idx, &mut sdr).unwrap();
             sdr[0]
         }
 
         fn write_to_axon(&mut self, val: u8, idx: u32) {
-            self.states.default_queue().unwrap().finish().unwrap();
-            let sdr = vec![val];
-            self.states.cmd().write(&sdr).offset(idx as usize).enq().unwrap();
+            self.states.finish().unwrap();
+            self.states.write_at(&[val], idx as usize).unwrap();
         }
     }
 
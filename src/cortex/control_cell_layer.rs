@@ -6,6 +6,7 @@ use std::collections::BTreeMap;
 // use ocl::ProQue;
 use cmn::CmnResult;
 use map::{/*AreaMap,*/ LayerAddress, ExecutionGraph, /*CellScheme*/};
+use cortex::{SnapshotWriter, SnapshotReader};
 // use cortex::{AxonSpace, DataCellLayer, CorticalAreaSettings};
 
 pub trait ControlCellLayer: 'static + Debug + Send {
@@ -16,6 +17,12 @@ pub trait ControlCellLayer: 'static + Debug + Send {
     fn layer_name<'s>(&'s self) -> &'s str;
     fn layer_addr(&self) -> LayerAddress;
     fn host_layer_addr(&self) -> LayerAddress;
+
+    /// Writes any state not derivable from the layer scheme to a snapshot.
+    fn save_state(&mut self, _wtr: &mut SnapshotWriter) -> CmnResult<()> { Ok(()) }
+
+    /// Restores state previously written by `::save_state`.
+    fn load_state(&mut self, _rdr: &mut SnapshotReader) -> CmnResult<()> { Ok(()) }
 }
 
 pub type ControlCellLayers = BTreeMap<(LayerAddress, usize), Box<ControlCellLayer>>;
\ No newline at end of file
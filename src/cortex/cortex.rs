@@ -1,6 +1,7 @@
 // #![allow(unused_imports, dead_code, unused_variables)]
 
 // use std::thread::{self, JoinHandle};
+use std::path::Path;
 use time;
 // use futures::{executor, SinkExt, StreamExt, Future};
 // use futures::StreamExt;
@@ -9,11 +10,11 @@ use time;
 // use tokio_core::reactor::Core;
 #[cfg(feature = "profile")]
 use cpuprofiler::PROFILER;
-use ocl::{self, Platform, Context, Device};
 use cmn::{CmnResult, MapStore};
-use cortex::{CorticalArea, CorticalAreaSettings, CompletionPool, /*CompletionPoolRemote*/};
-use map::{LayerMapSchemeList, LayerMapKind, AreaSchemeList};
-use subcortex::{Subcortex, SubcorticalNucleus, Thalamus};
+use cortex::{CorticalArea, CorticalAreaSettings, CompletionPool, /*CompletionPoolRemote*/
+    SnapshotWriter, SnapshotReader, DeviceSelector, Placement, DevicePool};
+use map::{LayerMapSchemeList, LayerMapKind, AreaSchemeList, SchemeConfig};
+use subcortex::{Subcortex, SubcorticalNucleus, Thalamus, InputGenerator};
 
 
 // This will need to be increased as the amount of work the pool is expected
@@ -21,7 +22,7 @@ use subcortex::{Subcortex, SubcorticalNucleus, Thalamus};
 const WORK_POOL_BUFFER_SIZE: usize = 32;
 
 
-pub type CorticalAreas = MapStore<&'static str, CorticalArea>;
+pub type CorticalAreas = MapStore<String, CorticalArea>;
 
 
 
@@ -47,31 +48,78 @@ impl Cortex {
         Builder::new(layer_map_sl, area_sl)
     }
 
+    /// Returns a new `CortexBuilder` using the layer map and area schemes
+    /// loaded from the config file at `path` (see `SchemeConfig`).
+    pub fn builder_from_file<P: AsRef<Path>>(path: P) -> CmnResult<Builder> {
+        let (layer_map_sl, area_sl) = SchemeConfig::load(path)?.into_parts();
+        Ok(Builder::new(layer_map_sl, area_sl))
+    }
+
     /// Creates and returns a new `Cortex`;
+    ///
+    /// Each cortical area is placed on a device according to `placement`
+    /// (see `Placement`). Areas not specified, or all areas if `placement` is
+    /// `None`, are spread across the devices of the default type on the
+    /// default platform.
+    ///
+    /// If `ca_settings` specifies host kernels, no devices are used and no
+    /// OpenCL platform is required. Placements are not allowed.
     pub fn new(layer_map_sl: LayerMapSchemeList, area_sl: AreaSchemeList,
             ca_settings: Option<CorticalAreaSettings>, mut subcortex: Subcortex,
-            completion_pool: Option<CompletionPool>) -> CmnResult<Cortex> {
+            completion_pool: Option<CompletionPool>, placement: Option<Placement>)
+            -> CmnResult<Cortex> {
         println!("\nInitializing Cortex... ");
         let time_start = time::get_time();
-        let platform = Platform::new(ocl::core::default_platform().unwrap());
-        let device_type = ocl::core::default_device_type().unwrap();
-        let ocl_context: Context = Context::builder()
-            .platform(platform)
-            .devices(Device::specifier().type_flags(device_type))
-            .build().expect("CorticalArea::new(): ocl_context creation error");
-
-        let mut thal = Thalamus::new(layer_map_sl, area_sl, &subcortex, &ocl_context).unwrap();
+        let placement = placement.unwrap_or(Placement::new());
+        let host_kernels = ca_settings.as_ref().map(|s| s.host_kernels).unwrap_or(false);
+
+        let mut device_pool = if host_kernels {
+            if !placement.area_names().is_empty() {
+                return Err("Cortex::new: Device placement cannot be specified for areas \
+                    using host kernels.".into());
+            }
+            None
+        } else {
+            Some(DevicePool::new()?)
+        };
+
+        let mut thal = match device_pool {
+            Some(ref mut device_pool) => Thalamus::new(layer_map_sl, area_sl, &subcortex,
+                Some(&device_pool.default_context()?))?,
+            None => Thalamus::new(layer_map_sl, area_sl, &subcortex, None)?,
+        };
         let mut areas = MapStore::new();
-        let mut device_idx = 1;
 
         let area_maps = thal.area_maps().to_owned();
 
+        for area_name in placement.area_names() {
+            if area_maps.by_key(area_name).is_none() {
+                return Err(format!("Cortex::new: Device placement specified for unknown \
+                    area: '{}'.", area_name).into());
+            }
+        }
+
         // Construct cortical areas:
         for area_map in area_maps.values().into_iter().filter(|area_map|
                 area_map.layer_map().layer_map_kind() != &LayerMapKind::Subcortical) {
-            areas.insert(area_map.area_name(), CorticalArea::new(area_map.clone(),
-                device_idx, &ocl_context, ca_settings.clone(), &mut thal)?);
-            device_idx += 1;
+            let area = match device_pool {
+                Some(ref mut device_pool) => {
+                    let dev_plc = device_pool.place(placement.selector(area_map.area_name()))?;
+                    CorticalArea::new(area_map.clone(), dev_plc.device_idx(),
+                        Some(dev_plc.context()), ca_settings.clone(), &mut thal)?
+                },
+                None => CorticalArea::new(area_map.clone(), 0, None, ca_settings.clone(),
+                    &mut thal)?,
+            };
+            areas.insert(area_map.area_name().to_owned(), area);
+        }
+
+        // Bridge events between contexts if more than one is in use:
+        if device_pool.as_ref().map(|dp| dp.context_count() > 1).unwrap_or(false) {
+            for area in areas.values_mut() {
+                let context = area.ocl_pq().map(|pq| pq.context().clone());
+                area.exe_graph_mut().set_context(context);
+            }
         }
 
         // Wire up subcortical pathway channels:
@@ -127,12 +175,73 @@ impl Cortex {
         &self.thal
     }
 
+    /// Enables or disables learning for all areas at runtime (see
+    /// `CorticalArea::set_learning_enabled`).
+    pub fn set_learning_enabled(&mut self, enabled: bool) {
+        for area in self.areas.values_mut() {
+            area.set_learning_enabled(enabled);
+        }
+    }
+
     /// Blocks until all command queues are finished.
     pub fn finish_queues(&self) {
         for area in self.areas.values() {
             area.finish_queues();
         }
     }
+
+    /// Saves the complete state of every cortical area to the file at `path`.
+    ///
+    /// The file header contains a fingerprint of each area's layer map and
+    /// dimensions which `::load_state` uses to refuse mismatched snapshots.
+    /// Blocks until all queues have finished.
+    pub fn save_state<P: AsRef<Path>>(&mut self, path: P) -> CmnResult<()> {
+        self.finish_queues();
+        let mut wtr = SnapshotWriter::create(path)?;
+        wtr.write_u64(self.areas.len() as u64)?;
+
+        for area in self.areas.values() {
+            wtr.write_str(area.area_map().area_name())?;
+            wtr.write_str(&area.state_fingerprint())?;
+        }
+
+        for area in self.areas.values_mut() {
+            area.save_state(&mut wtr)?;
+        }
+        wtr.finish()
+    }
+
+    /// Restores the state of every cortical area from a snapshot previously
+    /// written by `::save_state`.
+    ///
+    /// Every area fingerprint is checked before any state is modified. The
+    /// cortex must have been built from the same layer map and area schemes.
+    pub fn load_state<P: AsRef<Path>>(&mut self, path: P) -> CmnResult<()> {
+        let mut rdr = SnapshotReader::open(path)?;
+        let area_count = rdr.read_u64()? as usize;
+
+        if area_count != self.areas.len() {
+            return Err(format!("Cortex::load_state: Snapshot area count mismatch \
+                (snapshot: {}, cortex: {}).", area_count, self.areas.len()).into());
+        }
+
+        for area in self.areas.values() {
+            let area_name = rdr.read_str()?;
+            let fingerprint = rdr.read_str()?;
+
+            if area_name != area.area_map().area_name() ||
+                    fingerprint != area.state_fingerprint() {
+                return Err(format!("Cortex::load_state: Snapshot does not match the layer map \
+                    or dimensions of area '{}'.\n  snapshot: {}\n  cortex:   {}",
+                    area.area_map().area_name(), fingerprint, area.state_fingerprint()).into());
+            }
+        }
+
+        for area in self.areas.values_mut() {
+            area.load_state(&mut rdr)?;
+        }
+        Ok(())
+    }
 }
 
 impl Drop for Cortex {
@@ -146,12 +255,23 @@ impl Drop for Cortex {
 }
 
 
+/// A nucleus added to a `Builder`.
+enum BuilderNucleus {
+    /// An input generator, constructed by `Builder::build` using the
+    /// builder's seed.
+    InputGenerator(String),
+    Nucleus(Box<SubcorticalNucleus>),
+}
+
+
 pub struct Builder {
     layer_maps: LayerMapSchemeList,
     areas: AreaSchemeList,
     ca_settings: Option<CorticalAreaSettings>,
-    subcortex: Subcortex,
+    nuclei: Vec<BuilderNucleus>,
     completion_pool: Option<CompletionPool>,
+    placement: Option<Placement>,
+    seed: Option<u64>,
 }
 
 impl Builder {
@@ -160,8 +280,10 @@ impl Builder {
             layer_maps,
             areas,
             ca_settings: None,
-            subcortex: Subcortex::new(),
+            nuclei: Vec::new(),
             completion_pool: None,
+            placement: None,
+            seed: None,
         }
     }
 
@@ -182,6 +304,29 @@ impl Builder {
         self
     }
 
+    /// Sets the master seed of every area (see `CorticalAreaSettings::seed`),
+    /// overriding any seed set within `ca_settings`.
+    ///
+    /// The seed is also used by input generators added with
+    /// `::input_generator`. Input generators constructed elsewhere must use
+    /// `InputGenerator::with_seed` with the same seed.
+    pub fn seed(mut self, seed: u64) -> Builder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Specifies the device placement of every area (see `Placement`).
+    pub fn placement(mut self, placement: Placement) -> Builder {
+        self.placement = Some(placement);
+        self
+    }
+
+    /// Specifies the device on which the area named `area_name` is placed.
+    pub fn area_device<S: Into<String>>(mut self, area_name: S, selector: DeviceSelector) -> Builder {
+        self.placement.get_or_insert_with(Placement::new).set_area(area_name, selector);
+        self
+    }
+
     // pub fn sub(mut self, sub: Subcortex) -> Builder {
     //     self.sub = Some(sub);
     //     self
@@ -189,13 +334,44 @@ impl Builder {
 
     pub fn subcortical_nucleus<N>(mut self, nucl: N) -> Builder
             where N: SubcorticalNucleus {
-        self.subcortex.add_nucleus(nucl);
+        self.nuclei.push(BuilderNucleus::Nucleus(Box::new(nucl)));
+        self
+    }
+
+    /// Adds an input generator for the area named `area_name`.
+    ///
+    /// The input generator is constructed when the cortex is built and its
+    /// encoder is seeded from the master seed (see `::seed` and
+    /// `CorticalAreaSettings::seed`), if any.
+    pub fn input_generator<S: Into<String>>(mut self, area_name: S) -> Builder {
+        self.nuclei.push(BuilderNucleus::InputGenerator(area_name.into()));
         self
     }
 
     pub fn build(self) -> CmnResult<Cortex> {
         let completion_pool = self.completion_pool.unwrap_or(CompletionPool::new(WORK_POOL_BUFFER_SIZE)?);
-        Cortex::new(self.layer_maps, self.areas, self.ca_settings, self.subcortex,
-            Some(completion_pool))
+        let ca_settings = match self.seed {
+            Some(seed) => Some(self.ca_settings.unwrap_or(CorticalAreaSettings::new()).seed(seed)),
+            None => self.ca_settings,
+        };
+        let seed = ca_settings.as_ref().and_then(|s| s.seed);
+
+        let mut subcortex = Subcortex::new();
+        for nucleus in self.nuclei {
+            match nucleus {
+                BuilderNucleus::InputGenerator(area_name) => {
+                    let input_gen = match seed {
+                        Some(seed) => InputGenerator::with_seed(&self.layer_maps, &self.areas,
+                            &area_name, seed)?,
+                        None => InputGenerator::new(&self.layer_maps, &self.areas, &area_name)?,
+                    };
+                    subcortex.add_nucleus(input_gen);
+                },
+                BuilderNucleus::Nucleus(nucl) => subcortex.add_boxed_nucleus(nucl),
+            }
+        }
+
+        Cortex::new(self.layer_maps, self.areas, ca_settings, subcortex,
+            Some(completion_pool), self.placement)
     }
 }
@@ -2,18 +2,22 @@
 
 use std::collections::{HashSet, BTreeMap};
 use std::ops::Range;
+use std::sync::{Arc, Mutex};
 use rand::rngs::SmallRng;
 use futures::FutureExt;
-use ocl::{flags, Device, ProQue, Context, Buffer, Event, Queue, RwVec};
+use futures::executor;
+use ocl::{flags, Device, ProQue, Context, Event, Queue, RwVec};
 use ocl::core::CommandQueueProperties;
 use ocl::builders::{BuildOpt, ProgramBuilder};
-use cmn::{self, CmnResult, CorticalDims};
+use cmn::{self, CmnError, CmnResult, CorticalDims};
 use map::{AreaMap, SliceTractMap, LayerKind, DataCellKind, ControlCellKind,
-    ExecutionGraph, CellClass, LayerTags, LayerAddress, CommandUid, CommandRelations, CorticalBuffer};
+    ExecutionGraph, CellClass, LayerTags, LayerAddress, CommandUid, CommandRelations, CorticalBuffer,
+    DendriteKind};
 use ::Thalamus;
 use cortex::{AxonSpace, InhibitoryInterneuronNetwork, PyramidalLayer,
     SpinyStellateLayer, DataCellLayer, ControlCellLayer, ActivitySmoother, PyrOutputter,
-    CompletionPool, ControlCellLayers, IntraColumnInhib};
+    CompletionPool, ControlCellLayers, IntraColumnInhib, SnapshotWriter, SnapshotReader,
+    AnomalyTracker, AnomalyScore, LearningParams, Neuromodulation, Backend, Storage, IoQueues};
 use subcortex::{self, TractSender, TractReceiver};
 
 #[cfg(any(test, feature = "eval"))]
@@ -86,7 +90,119 @@ pub enum SamplerKind {
     SynSrcColVOffs(LayerAddress),
     SynSrcColUOffs(LayerAddress),
     SynFlagSets(LayerAddress),
+    /// The anomaly score of a temporal layer (see `AnomalyScore::from_frame`).
+    Anomaly(LayerAddress),
+}
+
+impl SamplerKind {
+    /// Returns the address of the sampled layer, if any.
+    pub fn layer_addr(&self) -> Option<LayerAddress> {
+        match *self {
+            SamplerKind::None => None,
+            SamplerKind::Axons(lyr_addr) => lyr_addr,
+            SamplerKind::SomaStates(lyr_addr) |
+            SamplerKind::SomaEnergies(lyr_addr) |
+            SamplerKind::SomaActivities(lyr_addr) |
+            SamplerKind::SomaFlagSets(lyr_addr) |
+            SamplerKind::TuftStates(lyr_addr) |
+            SamplerKind::TuftBestDenIds(lyr_addr) |
+            SamplerKind::TuftBestDenStatesRaw(lyr_addr) |
+            SamplerKind::TuftBestDenStates(lyr_addr) |
+            SamplerKind::TuftPrevStates(lyr_addr) |
+            SamplerKind::TuftPrevBestDenIds(lyr_addr) |
+            SamplerKind::TuftPrevBestDenStatesRaw(lyr_addr) |
+            SamplerKind::TuftPrevBestDenStates(lyr_addr) |
+            SamplerKind::DenStates(lyr_addr) |
+            SamplerKind::DenStatesRaw(lyr_addr) |
+            SamplerKind::DenEnergies(lyr_addr) |
+            SamplerKind::DenActivities(lyr_addr) |
+            SamplerKind::DenThresholds(lyr_addr) |
+            SamplerKind::SynStates(lyr_addr) |
+            SamplerKind::SynStrengths(lyr_addr) |
+            SamplerKind::SynSrcSlcIds(lyr_addr) |
+            SamplerKind::SynSrcColVOffs(lyr_addr) |
+            SamplerKind::SynSrcColUOffs(lyr_addr) |
+            SamplerKind::SynFlagSets(lyr_addr) |
+            SamplerKind::Anomaly(lyr_addr) => Some(lyr_addr),
+        }
+    }
+
+    /// Returns true if frames sampled from this kind are `i8` rather than
+    /// `u8`.
+    pub fn is_signed(&self) -> bool {
+        match *self {
+            SamplerKind::SynStrengths(_) | SamplerKind::SynSrcColVOffs(_) |
+                SamplerKind::SynSrcColUOffs(_) => true,
+            _ => false,
+        }
+    }
+}
+
+macro_rules! impl_sampler_kind_codes {
+    ($($code:tt => $variant:ident,)+) => {
+        impl SamplerKind {
+            /// Returns a stable code identifying the variant (regardless of
+            /// layer address), used by recordings and the flywheel wire
+            /// format.
+            pub fn code(&self) -> u8 {
+                match *self {
+                    SamplerKind::None => 0,
+                    SamplerKind::Axons(_) => 1,
+                    $( SamplerKind::$variant(_) => $code, )+
+                }
+            }
 
+            /// Returns the variant name for `code` (e.g. "SomaStates").
+            pub fn code_name(code: u8) -> Option<&'static str> {
+                match code {
+                    0 => Some("None"),
+                    1 => Some("Axons"),
+                    $( $code => Some(stringify!($variant)), )+
+                    _ => None,
+                }
+            }
+
+            /// Returns the variant for `code` sampling `lyr_addr`.
+            ///
+            /// Returns `None` if `code` is unknown or if `lyr_addr` is
+            /// required (or disallowed) but absent (or present).
+            pub fn from_code(code: u8, lyr_addr: Option<LayerAddress>) -> Option<SamplerKind> {
+                match (code, lyr_addr) {
+                    (0, None) => Some(SamplerKind::None),
+                    (1, lyr_addr) => Some(SamplerKind::Axons(lyr_addr)),
+                    $( ($code, Some(lyr_addr)) => Some(SamplerKind::$variant(lyr_addr)), )+
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl_sampler_kind_codes! {
+    2 => SomaStates,
+    3 => SomaEnergies,
+    4 => SomaActivities,
+    5 => SomaFlagSets,
+    6 => TuftStates,
+    7 => TuftBestDenIds,
+    8 => TuftBestDenStatesRaw,
+    9 => TuftBestDenStates,
+    10 => TuftPrevStates,
+    11 => TuftPrevBestDenIds,
+    12 => TuftPrevBestDenStatesRaw,
+    13 => TuftPrevBestDenStates,
+    14 => DenStates,
+    15 => DenStatesRaw,
+    16 => DenEnergies,
+    17 => DenActivities,
+    18 => DenThresholds,
+    19 => SynStates,
+    20 => SynStrengths,
+    21 => SynSrcSlcIds,
+    22 => SynSrcColVOffs,
+    23 => SynSrcColUOffs,
+    24 => SynFlagSets,
+    25 => Anomaly,
 }
 
 
@@ -113,6 +229,34 @@ impl Sampler {
 }
 
 
+/// Anomaly scoring for a single layer (see
+/// `CorticalArea::set_anomaly_tracking`).
+///
+/// Layer states are read into `axon_states` and `tuft_states` each cycle
+/// without blocking and scored on the completion pool.
+#[derive(Debug)]
+struct AnomalyLayer {
+    tracker: Arc<Mutex<AnomalyTracker>>,
+    axon_range: Range<usize>,
+    axon_states: RwVec<u8>,
+    tuft_states: RwVec<u8>,
+    samplers: Arc<Mutex<Vec<TractSender>>>,
+    cmd_uid: CommandUid,
+    cmd_idx: Option<usize>,
+}
+
+impl AnomalyLayer {
+    fn lyr_addr(&self) -> LayerAddress {
+        self.tracker.lock().unwrap().lyr_addr()
+    }
+
+    fn set_exe_order(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        self.cmd_idx = Some(exe_graph.order_command(self.cmd_uid)?);
+        Ok(())
+    }
+}
+
+
 #[derive(Debug)]
 enum Layer {
     SpinyStellateLayer(SpinyStellateLayer),
@@ -180,10 +324,24 @@ impl Layer {
         }
     }
 
-    fn regrow(&mut self) {
+    fn regrow(&mut self, chunk_size: Option<usize>, exe_graph: &ExecutionGraph) -> CmnResult<()> {
         match *self {
-            Layer::SpinyStellateLayer(ref mut lyr) => lyr.regrow(),
-            Layer::PyramidalLayer(ref mut lyr) => lyr.regrow(),
+            Layer::SpinyStellateLayer(ref mut lyr) => lyr.regrow(chunk_size, exe_graph),
+            Layer::PyramidalLayer(ref mut lyr) => lyr.regrow(chunk_size, exe_graph),
+        }
+    }
+
+    fn set_learning_enabled(&mut self, enabled: bool) {
+        match *self {
+            Layer::SpinyStellateLayer(ref mut lyr) => lyr.set_learning_enabled(enabled),
+            Layer::PyramidalLayer(ref mut lyr) => lyr.set_learning_enabled(enabled),
+        }
+    }
+
+    fn set_learning_params(&mut self, params: &LearningParams) -> CmnResult<()> {
+        match *self {
+            Layer::SpinyStellateLayer(ref mut lyr) => lyr.set_learning_params(params),
+            Layer::PyramidalLayer(ref mut lyr) => lyr.set_learning_params(params),
         }
     }
 
@@ -259,7 +417,15 @@ pub struct CorticalAreaSettings {
     pub disable_sscs: bool,
     pub disable_mcols: bool,
     pub disable_regrowth: bool,
+    pub regrowth_chunk_size: Option<usize>,
+    pub device_regrowth: bool,
+    pub learning_params: LearningParams,
+    pub seed: Option<u64>,
     pub disable_learning: bool,
+    pub host_kernels: bool,
+    pub queue_profiling: bool,
+    pub strict_exe_graph: bool,
+    pub print_exe_graph_issues: bool,
     pub build_options: Vec<BuildOpt>,
 }
 
@@ -273,7 +439,15 @@ impl CorticalAreaSettings {
             disable_sscs: false,
             disable_mcols: false,
             disable_regrowth: false,
+            regrowth_chunk_size: None,
+            device_regrowth: false,
+            learning_params: LearningParams::new(),
+            seed: None,
             disable_learning: false,
+            host_kernels: false,
+            queue_profiling: false,
+            strict_exe_graph: false,
+            print_exe_graph_issues: false,
             build_options: Vec::new(),
         }
     }
@@ -314,12 +488,92 @@ impl CorticalAreaSettings {
         self
     }
 
+    /// Sets the number of synapses per layer regrown each cycle.
+    ///
+    /// Defaults to the number of synapses in the layer divided by
+    /// `LearningParams::syn_regrowth_interval` (each synapse is considered
+    /// for regrowth once per interval).
+    pub fn regrowth_chunk_size(mut self, chunk_size: usize) -> CorticalAreaSettings {
+        assert!(chunk_size > 0, "CorticalAreaSettings::regrowth_chunk_size: \
+            'chunk_size' must be greater than zero.");
+        self.regrowth_chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Regrows synapses entirely on the device.
+    ///
+    /// Replacement sources are chosen and deduplicated by a kernel
+    /// (`tft_regrow_syns`) instead of being read back, regrown on the host,
+    /// and written back. Sources are drawn from the same distributions
+    /// either way.
+    ///
+    /// [NOTE]: The device makes at most `host_kernels::SYN_REGROW_ATTEMPTS_MAX`
+    /// attempts to find a source unique within a synapse's dendrite and,
+    /// failing that, leaves the synapse at the floor to be retried on the
+    /// next pass. Host regrowth instead retries until a unique source is
+    /// found.
+    pub fn device_regrowth(mut self) -> CorticalAreaSettings {
+        self.device_regrowth = true;
+        self
+    }
+
+    /// Sets the initial learning and regrowth parameters (see
+    /// `CorticalArea::set_learning_params`).
+    pub fn learning_params(mut self, params: LearningParams) -> CorticalAreaSettings {
+        self.learning_params = params;
+        self
+    }
+
+    /// Sets a master seed from which the seed of every random number
+    /// generator within the area is derived (keyed by layer and component).
+    ///
+    /// Areas built from identical configurations and seeds produce identical
+    /// synapse layouts and axon states. Generators are seeded from entropy
+    /// when no seed is set.
+    pub fn seed(mut self, seed: u64) -> CorticalAreaSettings {
+        self.seed = Some(seed);
+        self
+    }
+
     /// Disable learning for all layers.
     pub fn disable_learning(mut self) -> CorticalAreaSettings {
         self.disable_learning = true;
         self
     }
 
+    /// Runs all cortical kernels on the host using the reference
+    /// implementations in `host_kernels` instead of enqueuing them on the
+    /// device.
+    ///
+    /// Buffers are kept in host memory and no OpenCL platform is required.
+    /// Areas using sensory filters are not supported.
+    pub fn host_kernels(mut self) -> CorticalAreaSettings {
+        self.host_kernels = true;
+        self
+    }
+
+    /// Enables queue profiling, recording the device execution time of each
+    /// command in the area's execution graph (see `ExecutionGraph::to_dot`).
+    pub fn queue_profiling(mut self) -> CorticalAreaSettings {
+        self.queue_profiling = true;
+        self
+    }
+
+    /// Causes area creation to fail on any execution graph validation issue
+    /// (such as a stale read) rather than only recording it (see
+    /// `ExecutionGraph::validate` and `ExecutionGraph::issues`).
+    pub fn strict_exe_graph(mut self) -> CorticalAreaSettings {
+        self.strict_exe_graph = true;
+        self
+    }
+
+    /// Prints a warning for each non-fatal execution graph validation issue
+    /// found when an area is created.
+    pub fn print_exe_graph_issues(mut self) -> CorticalAreaSettings {
+        self.print_exe_graph_issues = true;
+        self
+    }
+
     /// Adds a build option.
     //
     // BuildOpt::include_def("DEFINITION", 1)
@@ -342,7 +596,7 @@ impl CorticalAreaSettings {
 #[derive(Debug)]
 pub struct CorticalArea {
     area_id: usize,
-    name: &'static str,
+    name: String,
     dims: CorticalDims,
     area_map: AreaMap,
     axns: AxonSpace,
@@ -351,15 +605,15 @@ pub struct CorticalArea {
     /// Interneuron layers.
     control_layers: ControlCellLayers,
     aux: Aux,
-    ocl_pq: ProQue,
-    write_queue: Queue,
-    read_queue: Queue,
-    unmap_queue: Queue,
-    counter: usize,
+    backend: Backend,
+    io_queues: Option<IoQueues>,
     settings: CorticalAreaSettings,
+    learning_enabled: bool,
     cycle_order: Vec<usize>,
     exe_graph: ExecutionGraph,
     samplers: Vec<Sampler>,
+    anomaly_layers: Vec<AnomalyLayer>,
+    neuromod: Neuromodulation,
 }
 
 impl CorticalArea {
@@ -373,76 +627,103 @@ impl CorticalArea {
     // purpose of getting precise slice ids for layers in other areas for use
     // by the execution graph system.
     //
-    pub fn new(area_map: AreaMap, device_idx: usize, ocl_context: &Context,
+    // `ocl_context` is required unless `settings` specifies host kernels.
+    //
+    pub fn new(area_map: AreaMap, device_idx: usize, ocl_context: Option<&Context>,
             settings: Option<CorticalAreaSettings>, thal: &mut Thalamus) -> CmnResult<CorticalArea> {
         // let emsg = "cortical_area::CorticalArea::new()";
         let area_id = area_map.area_id();
-        let area_name = area_map.area_name();
+        let area_name = area_map.area_name().to_owned();
         let settings = settings.unwrap_or(CorticalAreaSettings::new());
+        settings.learning_params.validate()?;
+
+        if settings.host_kernels && settings.device_regrowth {
+            return Err(format!("CorticalArea::new: Area \"{}\": Device regrowth is not \
+                available with host kernels.", area_name).into());
+        }
 
         println!("\n\nCORTICALAREA::NEW(): Creating Cortical Area: \"{}\"...", area_name);
 
-        let mut p_bldr = area_map.gen_build_options();
+        ///// TODO: Revisit this... is increment useful anymore?
+        // let dims = area_map.dims().clone_with_incr(ocl_pq.max_wg_size().unwrap());
+        let dims = area_map.dims().clone();
 
-        if KERNEL_DEBUG_SYMBOLS && cfg!(target_os = "linux") {
-            if ocl_context.platform()?.unwrap().vendor()?.contains("Intel") {
-                panic!("[cortical_area::KERNEL_DEBUG_SYMBOLS == true]: \
-                    Cannot debug kernels on an Intel based driver platform (not sure why).
-                    Use the AMD platform drivers with Intel devices instead.");
-            }
-            // * TODO: Save kernel file for debugging on Intel.
-            // // Optionally pass `-g` and `-s {cl path}` flags to compiler:
-            // let debug_opts = format!("-g -s \"{}\"", kernel_path);
+        let (backend, io_queues) = if settings.host_kernels {
+            println!("{mt}CORTICALAREA::NEW(): Area \"{}\" details: \
+                (u_size: {}, v_size: {}, depth: {}), eff_areas: {:?}, aff_areas: {:?}, \n\
+                {mt}{mt}backend: host",
+                area_name, dims.u_size(), dims.v_size(), dims.depth(), area_map.eff_areas(),
+                area_map.aff_areas(), mt = cmn::MT);
 
-            let debug_opts = "-g";
-            p_bldr.cmplr_opt(debug_opts);
-        };
+            (Backend::Host, None)
+        } else {
+            let ocl_context = ocl_context.ok_or_else(|| CmnError::new(format!(
+                "CorticalArea::new: An OpenCL context is required for area \"{}\" \
+                unless it uses host kernels.", area_name)))?;
 
-        settings.add_build_options(&mut p_bldr);
+            let mut p_bldr = area_map.gen_build_options();
 
-        let mut queue_flags = if QUEUE_OUT_OF_ORDER {
-            flags::QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE
-        } else {
-            CommandQueueProperties::empty()
-        };
+            if KERNEL_DEBUG_SYMBOLS && cfg!(target_os = "linux") {
+                if ocl_context.platform()?.unwrap().vendor()?.contains("Intel") {
+                    panic!("[cortical_area::KERNEL_DEBUG_SYMBOLS == true]: \
+                        Cannot debug kernels on an Intel based driver platform (not sure why).
+                        Use the AMD platform drivers with Intel devices instead.");
+                }
+                // * TODO: Save kernel file for debugging on Intel.
+                // // Optionally pass `-g` and `-s {cl path}` flags to compiler:
+                // let debug_opts = format!("-g -s \"{}\"", kernel_path);
 
-        queue_flags = queue_flags | if QUEUE_PROFILING {
-            flags::QUEUE_PROFILING_ENABLE
-        } else {
-            CommandQueueProperties::empty()
-        };
+                let debug_opts = "-g";
+                p_bldr.cmplr_opt(debug_opts);
+            };
 
-        let ocl_pq = ProQue::builder()
-            .device(device_idx)
-            .context(ocl_context.clone())
-            .prog_bldr(p_bldr)
-            .queue_properties(queue_flags)
-            .build().expect("CorticalArea::new(): ocl_pq.build(): error");
-
-        let (write_queue, read_queue, unmap_queue) = if SEPARATE_IO_QUEUES {
-            (Queue::new(ocl_context, ocl_pq.device().clone(), Some(queue_flags))?,
-                Queue::new(ocl_context, ocl_pq.device().clone(), Some(queue_flags))?,
-                Queue::new(ocl_context, ocl_pq.device().clone(), Some(queue_flags))?)
-        } else {
-            (ocl_pq.queue().clone(), ocl_pq.queue().clone(), ocl_pq.queue().clone())
-        };
+            settings.add_build_options(&mut p_bldr);
 
-        ///// TODO: Revisit this... is increment useful anymore?
-        // let dims = area_map.dims().clone_with_incr(ocl_pq.max_wg_size().unwrap());
-        let dims = area_map.dims().clone();
+            let mut queue_flags = if QUEUE_OUT_OF_ORDER {
+                flags::QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE
+            } else {
+                CommandQueueProperties::empty()
+            };
 
-        println!("{mt}CORTICALAREA::NEW(): Area \"{}\" details: \
-            (u_size: {}, v_size: {}, depth: {}), eff_areas: {:?}, aff_areas: {:?}, \n\
-            {mt}{mt}device_idx: [{}], device.name(): {}, device.vendor(): {}",
-            area_name, dims.u_size(), dims.v_size(), dims.depth(), area_map.eff_areas(),
-            area_map.aff_areas(), device_idx, ocl_pq.device().name()?.trim(),
-            ocl_pq.device().vendor()?.trim(), mt = cmn::MT);
+            queue_flags = queue_flags | if QUEUE_PROFILING || settings.queue_profiling {
+                flags::QUEUE_PROFILING_ENABLE
+            } else {
+                CommandQueueProperties::empty()
+            };
+
+            let ocl_pq = ProQue::builder()
+                .device(device_idx)
+                .context(ocl_context.clone())
+                .prog_bldr(p_bldr)
+                .queue_properties(queue_flags)
+                .build().expect("CorticalArea::new(): ocl_pq.build(): error");
+
+            let (write_queue, read_queue, unmap_queue) = if SEPARATE_IO_QUEUES {
+                (Queue::new(ocl_context, ocl_pq.device().clone(), Some(queue_flags))?,
+                    Queue::new(ocl_context, ocl_pq.device().clone(), Some(queue_flags))?,
+                    Queue::new(ocl_context, ocl_pq.device().clone(), Some(queue_flags))?)
+            } else {
+                (ocl_pq.queue().clone(), ocl_pq.queue().clone(), ocl_pq.queue().clone())
+            };
+
+            println!("{mt}CORTICALAREA::NEW(): Area \"{}\" details: \
+                (u_size: {}, v_size: {}, depth: {}), eff_areas: {:?}, aff_areas: {:?}, \n\
+                {mt}{mt}device_idx: [{}], device.name(): {}, device.vendor(): {}",
+                area_name, dims.u_size(), dims.v_size(), dims.depth(), area_map.eff_areas(),
+                area_map.aff_areas(), device_idx, ocl_pq.device().name()?.trim(),
+                ocl_pq.device().vendor()?.trim(), mt = cmn::MT);
+
+            (Backend::Device(ocl_pq), Some(IoQueues { read: read_queue, write: write_queue,
+                unmap: unmap_queue }))
+        };
 
         /*=============================================================================
         =============================== EXECUTION GRAPH ===============================
         =============================================================================*/
 
         let mut exe_graph = ExecutionGraph::new();
+        exe_graph.set_profiling(QUEUE_PROFILING || settings.queue_profiling);
+        exe_graph.set_strict(settings.strict_exe_graph);
 
         /*=============================================================================
         ================================ CELLS & AXONS ================================
@@ -451,8 +732,8 @@ impl CorticalArea {
         // let mut mcols = None;
         let mut data_layers = Layers::new();
         let mut control_layers: ControlCellLayers = BTreeMap::new();
-        let axns = AxonSpace::new(&area_map, &ocl_pq, read_queue.clone(),
-            write_queue.clone(), unmap_queue.clone(), &mut exe_graph, thal)?;
+        let axns = AxonSpace::new(&area_map, &backend, io_queues.clone(), &mut exe_graph, thal)?;
+        let mut neuromod = Neuromodulation::new(area_id, &dims, &backend)?;
 
         /*=============================================================================
         ================================== DATA CELLS =================================
@@ -470,16 +751,16 @@ impl CorticalArea {
                         Some(&DataCellKind::Pyramidal) => {
                             let pyrs_dims = dims.clone_with_depth(layer.depth());
                             let pyr_lyr = try!(PyramidalLayer::new(layer.name(), layer.layer_id(),
-                                pyrs_dims, cell_scheme.clone(), &area_map, &axns, &ocl_pq,
-                                settings.clone(), &mut exe_graph));
+                                pyrs_dims, cell_scheme.clone(), &area_map, &axns, &backend,
+                                settings.clone(), &mut neuromod, &mut exe_graph));
                             data_layers.push(Layer::pyr(pyr_lyr));
                         },
                         Some(&DataCellKind::SpinyStellate) => {
                             let sscs_map_dims = dims.clone_with_depth(layer.depth());
 
                             let ssc_lyr = try!(SpinyStellateLayer::new(layer.name(), layer.layer_id(),
-                                sscs_map_dims, cell_scheme.clone(), &area_map, &axns, &ocl_pq,
-                                settings.clone(), &mut exe_graph));
+                                sscs_map_dims, cell_scheme.clone(), &area_map, &axns, &backend,
+                                settings.clone(), &mut neuromod, &mut exe_graph));
                             data_layers.push(Layer::ssc(ssc_lyr));
                         },
                         _ => (),
@@ -489,6 +770,9 @@ impl CorticalArea {
             }
         }
 
+        // Neuromodulation is written only if a learning kernel reads it:
+        neuromod.add_write_command(&mut exe_graph)?;
+
 
         /*=============================================================================
         ================================ CONTROL CELLS ================================
@@ -514,7 +798,7 @@ impl CorticalArea {
 
                         let cc_lyr = InhibitoryInterneuronNetwork::new(layer.name(),
                             layer.layer_id(), cell_scheme.clone(), host_lyr, &axns, &area_map,
-                            &ocl_pq, settings.clone(), &mut exe_graph)?;
+                            &backend, settings.clone(), &mut exe_graph)?;
 
                         insert_control_layer(&mut control_layers, layer.name(), cc_lyr,
                             host_lyr, exe_order);
@@ -524,7 +808,7 @@ impl CorticalArea {
                         let host_lyr = data_layers.by_name(host_lyr_name)?;
 
                         let cc_lyr = ActivitySmoother::new(layer.name(), layer.layer_id(),
-                            cell_scheme.clone(), host_lyr, &axns, &area_map, &ocl_pq,
+                            cell_scheme.clone(), host_lyr, &axns, &area_map, &backend,
                             settings.clone(), &mut exe_graph)?;
 
                         insert_control_layer(&mut control_layers, layer.name(), cc_lyr,
@@ -535,7 +819,7 @@ impl CorticalArea {
                         let host_lyr = data_layers.by_name(host_lyr_name)?;
 
                         let cc_lyr = PyrOutputter::new(layer.name(), layer.layer_id(),
-                            cell_scheme.clone(), host_lyr, &axns, &area_map, &ocl_pq,
+                            cell_scheme.clone(), host_lyr, &axns, &area_map, &backend,
                             settings.clone(), &mut exe_graph)?;
 
                         insert_control_layer(&mut control_layers, layer.name(), cc_lyr,
@@ -546,7 +830,7 @@ impl CorticalArea {
                         let host_lyr = data_layers.by_name(host_lyr_name)?;
 
                         let cc_lyr = IntraColumnInhib::new(layer.name(), layer.layer_id(),
-                            cell_scheme.clone(), host_lyr, &axns, &area_map, &ocl_pq,
+                            cell_scheme.clone(), host_lyr, &axns, &area_map, &backend,
                             settings.clone(), &mut exe_graph)?;
 
                         insert_control_layer(&mut control_layers, layer.name(), cc_lyr,
@@ -561,7 +845,7 @@ impl CorticalArea {
         ===================================== AUX =====================================
         =============================================================================*/
 
-        let aux = Aux::new(1 << 15, &ocl_pq);
+        let aux = Aux::new(1 << 15, &backend)?;
 
         // <<<<< TODO: CLEAN THIS UP >>>>>
         // MAKE ABOVE LIKE BELOW (eliminateset_arg() methods and just call directly on buffer)
@@ -626,18 +910,26 @@ impl CorticalArea {
             data_layers,
             control_layers,
             aux: aux,
-            ocl_pq: ocl_pq,
-            write_queue: write_queue,
-            read_queue: read_queue,
-            unmap_queue: unmap_queue,
-            counter: 0,
+            backend,
+            io_queues,
+            learning_enabled: !settings.disable_learning,
             settings: settings,
             cycle_order,
             exe_graph: exe_graph,
             samplers: Vec::with_capacity(8),
+            anomaly_layers: Vec::new(),
+            neuromod,
         };
 
         cortical_area.order()?;
+
+        if cortical_area.settings.print_exe_graph_issues {
+            for issue in cortical_area.exe_graph.issues() {
+                println!("{mt}CORTICALAREA::NEW(): Area \"{}\" execution graph warning: {}",
+                    cortical_area.name, issue, mt = cmn::MT);
+            }
+        }
+
         Ok(cortical_area)
     }
 
@@ -648,6 +940,9 @@ impl CorticalArea {
         // (1.) Axon Intake:
         self.axns.set_exe_order_intake(&mut self.exe_graph)?;
 
+        // (1b.) Neuromodulation Write:
+        self.neuromod.set_exe_order(&mut self.exe_graph)?;
+
         // (2.) SSTs Cycle:
         for lyr_idx in self.cycle_order.clone() {
             let lyr = self.data_layers.lyrs.get_mut(lyr_idx).unwrap();
@@ -705,8 +1000,14 @@ impl CorticalArea {
             sampler.set_exe_order(&mut self.exe_graph)?;
         }
 
-        // Lock and populate execution graph:
-        Ok(self.exe_graph.lock())
+        // (11.) Anomaly Scores:
+        for anomaly_layer in self.anomaly_layers.iter_mut() {
+            anomaly_layer.set_exe_order(&mut self.exe_graph)?;
+        }
+
+        // Validate, lock, and populate execution graph:
+        self.exe_graph.lock()?;
+        Ok(())
     }
 
     /// Cycles the area: running kernels, intaking, and outputting.
@@ -718,6 +1019,9 @@ impl CorticalArea {
         self.axns.intake(thal, &mut self.exe_graph, self.settings.bypass_filters,
             completion_pool)?;
 
+        // (1b.) Neuromodulation Write:
+        self.neuromod.write(&mut self.exe_graph)?;
+
         // (2.) SSTs Cycle:
         if !self.settings.disable_sscs {
             for &lyr_idx in self.cycle_order.iter() {
@@ -770,39 +1074,42 @@ impl CorticalArea {
         //     self.mcols.output(&mut self.exe_graph)?;
         // }
 
-        // (8.) Regrow:
-        if !self.settings.disable_regrowth {
-            self.regrow();
-        }
-
         // self.flush_queues();
 
-        // (9.) Axon Output:
+        // (8.) Axon Output:
         self.axns.output(thal, &mut self.exe_graph, completion_pool)?;
 
-        // (10.) Samplers:
+        // (9.) Samplers:
         self.cycle_samplers(completion_pool)?;
 
+        // (10.) Anomaly Scores:
+        self.cycle_anomaly(completion_pool)?;
+
+        // (11.) Regrow (after the samplers, which may read synapse buffers):
+        self.regrow()?;
+
         // println!("######### Cycle complete.");
 
         Ok(())
     }
 
-    /// Attaches synapses which are below strength threshold to new axons.
-    pub fn regrow(&mut self) {
+    /// Queues the next chunk of synapses of each layer to be checked and,
+    /// if below strength threshold, attached to new axons (see
+    /// `Synapses::regrow`).
+    ///
+    /// Does not block. Each chunk is read once every command of the current
+    /// cycle using it, samplers included, has completed and is written back
+    /// to the device when its layer next cycles. Must be called after the
+    /// samplers have been cycled.
+    pub fn regrow(&mut self) -> CmnResult<()> {
         if !self.settings.disable_regrowth {
-            if self.counter >= cmn::SYNAPSE_REGROWTH_INTERVAL {
-                self.finish_queues();
-                //print!("$");
-                for &lyr_idx in self.cycle_order.iter() {
-                    let lyr = self.data_layers.lyrs.get_mut(lyr_idx).unwrap();
-                    lyr.regrow();
-                }
-                self.counter = 0;
-            } else {
-                self.counter += 1;
+            let chunk_size = self.settings.regrowth_chunk_size;
+            for &lyr_idx in self.cycle_order.iter() {
+                let lyr = self.data_layers.lyrs.get_mut(lyr_idx).unwrap();
+                lyr.regrow(chunk_size, &self.exe_graph)?;
             }
         }
+        Ok(())
     }
 
     /// Cycles through sampling requests.
@@ -815,9 +1122,19 @@ impl CorticalArea {
                 .expect(&format!("CorticalArea::cycle_samplers: Invalid layer: {}", layer_addr))
         }
 
-        fn cycle<T: OclPrm>(buf: &Buffer<T>, fwg: FutureWriteGuard<Vec<T>>,
+        // Host storage is copied immediately and leaves `new_event` empty.
+        fn cycle<T: OclPrm>(buf: &Storage<T>, fwg: FutureWriteGuard<Vec<T>>,
                 sampler: &Sampler, cmd_idx: usize, exe_graph: &mut ExecutionGraph,
                 new_event: &mut Event, completion_pool: &mut CompletionPool) -> CmnResult<()> {
+            let buf = match *buf {
+                Storage::Device(ref buf) => buf,
+                Storage::Host(_) => {
+                    let mut guard = executor::block_on(fwg)?;
+                    let dst_range = sampler.tx.buffer_idx_range();
+                    return buf.read_into(sampler.src_idx_range.start, &mut guard[dst_range]);
+                },
+            };
+
             let future_read = buf.cmd().read(fwg)
                 .offset(sampler.src_idx_range.start)
                 .len(sampler.src_idx_range.len())
@@ -834,6 +1151,8 @@ impl CorticalArea {
         // // NOTE: Enable sleep only for testing:
         // ::std::thread::sleep(::std::time::Duration::from_millis(1000));
 
+        let is_host = self.backend.is_host();
+
         for sampler in &self.samplers {
             let cmd_idx = sampler.cmd_idx.expect("sampler order not set");
 
@@ -967,7 +1286,8 @@ impl CorticalArea {
                     },
                     _ => unimplemented!(),
                 }
-                self.exe_graph.set_cmd_event(cmd_idx, Some(new_event))?;
+                let new_event = if is_host { None } else { Some(new_event) };
+                self.exe_graph.set_cmd_event(cmd_idx, new_event)?;
             } else {
                 self.exe_graph.set_cmd_event(cmd_idx, None)?;
             }
@@ -975,65 +1295,164 @@ impl CorticalArea {
         Ok(())
     }
 
+    /// Queues scoring of each layer with anomaly tracking enabled.
+    ///
+    /// Does not block. Layer states are read once the execution graph allows
+    /// and are scored on the completion pool, after which the score is sent
+    /// to any anomaly samplers. Each layer's reads wait for the previous
+    /// cycle's scoring to complete, keeping scores in cycle order.
+    ///
+    /// Layers in host storage are read and scored before returning.
+    fn cycle_anomaly(&mut self, completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        // Scores a layer and sends the score to its samplers.
+        fn score(tracker: &Mutex<AnomalyTracker>, samplers: &Mutex<Vec<TractSender>>,
+                axon_states: &[u8], tuft_states: &[u8]) -> CmnResult<()> {
+            let score = tracker.lock().unwrap().update(axon_states, tuft_states)?;
+
+            for tx in samplers.lock().unwrap().iter() {
+                if let Some(write_buf) = tx.send().wait()? {
+                    let mut frame = write_buf.write_u8().wait()?;
+                    frame.copy_from_slice(&score.to_frame());
+                }
+            }
+            Ok(())
+        }
+
+        for anomaly_layer in self.anomaly_layers.iter() {
+            let cmd_idx = anomaly_layer.cmd_idx.expect("anomaly layer order not set");
+            let lyr = self.data_layers.by_addr(anomaly_layer.lyr_addr())?;
+
+            if self.backend.is_host() {
+                let mut tuft_states = executor::block_on(anomaly_layer.tuft_states.clone().write())?;
+                lyr.tufts().states().read_into(0, &mut tuft_states[..])?;
+                let mut axon_states = executor::block_on(anomaly_layer.axon_states.clone().write())?;
+                self.axns.states().read_into(anomaly_layer.axon_range.start, &mut axon_states[..])?;
+
+                score(&anomaly_layer.tracker, &anomaly_layer.samplers, &axon_states, &tuft_states)?;
+                self.exe_graph.set_cmd_event(cmd_idx, None)?;
+                continue;
+            }
+
+            let tufts_states = lyr.tufts().states().device()?;
+            let axns_states = self.axns.states().device()?;
+
+            let mut tft_event = Event::empty();
+            let future_tfts = tufts_states.cmd().read(anomaly_layer.tuft_states.clone())
+                .ewait(self.exe_graph.get_req_events(cmd_idx)?)
+                .enew(&mut tft_event)
+                .enq_async()?;
+
+            // The axon read waits on the tuft read so that its event marks
+            // the completion of both:
+            let mut new_event = Event::empty();
+            let future_axns = axns_states.cmd().read(anomaly_layer.axon_states.clone())
+                .offset(anomaly_layer.axon_range.start)
+                .len(anomaly_layer.axon_range.len())
+                .ewait(&tft_event)
+                .enew(&mut new_event)
+                .enq_async()?;
+            self.exe_graph.set_cmd_event(cmd_idx, Some(new_event))?;
+
+            let tracker = anomaly_layer.tracker.clone();
+            let samplers = anomaly_layer.samplers.clone();
+
+            let future_score = future_axns.join(future_tfts)
+                .map(move |(axon_states, tuft_states)| {
+                    // The guards are held until scoring completes:
+                    score(&tracker, &samplers, &axon_states, &tuft_states)
+                        .unwrap_or_else(|err| panic!("{}", err));
+                })
+                .map_err(|err| panic!("{}", err));
+
+            completion_pool.complete_work(Box::new(future_score))?;
+        }
+        Ok(())
+    }
+
     /// Creates and adds a sampler from the provided transmitter and
     /// configures the execution graph appropriately.
     fn add_sampler(&mut self, cmd_srcs: Vec<CorticalBuffer>, kind: SamplerKind,
-            src_idx_range: Option<Range<usize>>, tx: TractSender) {
+            src_idx_range: Option<Range<usize>>, tx: TractSender) -> CmnResult<()> {
         // Add command to graph and get uid:
         self.exe_graph.unlock();
-        let cmd_uid = self.exe_graph.add_command(
-                CommandRelations::cortical_sample(cmd_srcs))
-            .expect("CorticalArea::sampler: Error adding exe. graph command");
+        let cmd_uid = match self.exe_graph.add_command(
+                CommandRelations::cortical_sample(cmd_srcs)) {
+            Ok(cmd_uid) => cmd_uid,
+            Err(err) => {
+                // Relock the graph before bailing:
+                self.order()?;
+                return Err(format!("CorticalArea::sampler: Error adding exe. graph \
+                    command: {}", err).into());
+            },
+        };
         // Create and push sampler:
         self.samplers.push(Sampler::new(kind, src_idx_range, tx, cmd_uid));
         // Repopulate execution graph:
-        self.order().expect("CorticalArea::sampler: Error reordering");
+        self.order()
     }
 
     /// Creates a tract channel and configures execution graph appropriately.
     fn sampler_rx_single_u8(&mut self, len: usize, cmd_srcs: Vec<CorticalBuffer>,
-            kind: SamplerKind, src_idx_range: Option<Range<usize>>, backpressure: bool) -> TractReceiver {
+            kind: SamplerKind, src_idx_range: Option<Range<usize>>, backpressure: bool) -> CmnResult<TractReceiver> {
         // Create a new tract channel:
         let (tx, rx) = subcortex::tract_channel_single_u8(RwVec::from(vec![0u8; len]), None,
             backpressure);
         // Add sampler and config exe graph:
-        self.add_sampler(cmd_srcs, kind, src_idx_range, tx);
-        rx
+        self.add_sampler(cmd_srcs, kind, src_idx_range, tx)?;
+        Ok(rx)
     }
 
     /// Creates a tract channel and configures execution graph appropriately.
     fn sampler_rx_single_i8(&mut self, len: usize, cmd_srcs: Vec<CorticalBuffer>,
-            kind: SamplerKind, src_idx_range: Option<Range<usize>>, backpressure: bool) -> TractReceiver {
+            kind: SamplerKind, src_idx_range: Option<Range<usize>>, backpressure: bool) -> CmnResult<TractReceiver> {
         // Create a new tract channel:
         let (tx, rx) = subcortex::tract_channel_single_i8(RwVec::from(vec![0i8; len]), None,
             backpressure);
         // Add sampler and config exe graph:
-        self.add_sampler(cmd_srcs, kind, src_idx_range, tx);
-        rx
+        self.add_sampler(cmd_srcs, kind, src_idx_range, tx)?;
+        Ok(rx)
     }
 
     /// Requests a cortical 'sampler' which provides external read access to
     /// cortical cells and axons.
+    ///
+    /// Returns an error if the layer or sampler kind is invalid for this
+    /// area.
     pub fn sampler(&mut self, kind: SamplerKind, buffer_kind: SamplerBufferKind,
-            backpressure: bool) -> TractReceiver {
-        fn slc_range(area_map: &AreaMap, layer_id: usize) -> Range<usize> {
+            backpressure: bool) -> CmnResult<TractReceiver> {
+        fn slc_range(area_map: &AreaMap, layer_id: usize) -> CmnResult<Range<usize>> {
             area_map.layer_map().layer_info(layer_id)
-                .expect(&format!("CorticalArea::sample: Invalid layer: [id:{}]", layer_id))
+                .ok_or(format!("CorticalArea::sampler: Invalid layer: [id:{}]", layer_id))?
                 .slc_range()
-                .expect(&format!("CorticalArea::sample: Layer [id:{}] has no slices", layer_id))
-                .clone()
+                .cloned()
+                .ok_or(format!("CorticalArea::sampler: Layer [id:{}] has no slices", layer_id)
+                    .into())
         }
 
-        fn lyr<'a>(data_layers: &'a Layers, lyr_addr: LayerAddress) -> &'a DataCellLayer {
-            data_layers.by_addr(lyr_addr)
-                .expect(&format!("CorticalArea::sample: Invalid layer: {}", lyr_addr))
+        fn lyr<'a>(data_layers: &'a Layers, lyr_addr: LayerAddress)
+                -> CmnResult<&'a DataCellLayer> {
+            data_layers.by_addr(lyr_addr).map_err(|err| {
+                format!("CorticalArea::sampler: Invalid layer: {}: {}", lyr_addr, err).into()
+            })
         }
 
         match kind {
+            // Anomaly (computed on the host and not part of the execution graph):
+            SamplerKind::Anomaly(lyr_addr) => {
+                self.set_anomaly_tracking(lyr_addr, true)?;
+                let (tx, rx) = subcortex::tract_channel_single_u8(
+                    RwVec::from(vec![0u8; AnomalyScore::FRAME_LEN]), None, backpressure);
+                let anomaly_layer = self.anomaly_layers.iter()
+                    .find(|al| al.lyr_addr() == lyr_addr)
+                    .ok_or("CorticalArea::sampler: Anomaly tracking not enabled.")?;
+                anomaly_layer.samplers.lock().unwrap().push(tx);
+                Ok(rx)
+            },
+
             // Axons:
             SamplerKind::Axons(lyr_addr) => {
                 let slc_range = match lyr_addr {
-                    Some(addr) => slc_range(&self.area_map, addr.layer_id()),
+                    Some(addr) => slc_range(&self.area_map, addr.layer_id())?,
                     None => 0..self.area_map.slice_map().depth() as usize,
                 };
                 let axon_range = self.area_map.slice_map().axon_range(slc_range.clone());
@@ -1045,14 +1464,15 @@ impl CorticalArea {
                         self.sampler_rx_single_u8(axon_range.len(), cmd_srcs, kind.clone(),
                             Some(axon_range), backpressure)
                     },
-                    _ => unimplemented!(),
+                    bk @ _ => Err(format!("CorticalArea::sampler: Unsupported buffer kind \
+                        for axons: {:?}", bk).into()),
                 }
             },
 
             // Soma:
             SamplerKind::SomaStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     (lyr.soma().len(),
                         vec![CorticalBuffer::data_soma_lyr(lyr.soma(), lyr_addr)])
                 };
@@ -1060,7 +1480,7 @@ impl CorticalArea {
             },
             SamplerKind::SomaEnergies(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     (lyr.energies().len(),
                         vec![CorticalBuffer::data_soma_lyr(lyr.energies(), lyr_addr)])
                 };
@@ -1068,7 +1488,7 @@ impl CorticalArea {
             },
             SamplerKind::SomaActivities(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     (lyr.activities().len(),
                         vec![CorticalBuffer::data_soma_lyr(lyr.activities(), lyr_addr)])
                 };
@@ -1076,7 +1496,7 @@ impl CorticalArea {
             },
             SamplerKind::SomaFlagSets(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     (lyr.flag_sets().len(),
                         vec![CorticalBuffer::data_soma_lyr(lyr.flag_sets(), lyr_addr,)])
                 };
@@ -1086,7 +1506,7 @@ impl CorticalArea {
             // Tufts:
             SamplerKind::TuftStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().states(), lyr_addr, tft_id)
                     }).collect();
@@ -1096,7 +1516,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftBestDenIds(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().best_den_ids(), lyr_addr, tft_id)
                     }).collect();
@@ -1106,7 +1526,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftBestDenStatesRaw(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().best_den_states_raw(), lyr_addr, tft_id)
                     }).collect();
@@ -1116,7 +1536,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftBestDenStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().best_den_states(), lyr_addr, tft_id)
                     }).collect();
@@ -1126,7 +1546,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftPrevStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().prev_states(), lyr_addr, tft_id)
                     }).collect();
@@ -1136,7 +1556,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftPrevBestDenIds(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().prev_best_den_ids(), lyr_addr, tft_id)
                     }).collect();
@@ -1146,7 +1566,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftPrevBestDenStatesRaw(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().prev_best_den_states_raw(), lyr_addr, tft_id)
                     }).collect();
@@ -1156,7 +1576,7 @@ impl CorticalArea {
             },
             SamplerKind::TuftPrevBestDenStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_tft(lyr.tufts().prev_best_den_states(), lyr_addr, tft_id)
                     }).collect();
@@ -1168,7 +1588,7 @@ impl CorticalArea {
             // Dens:
             SamplerKind::DenStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_den_tft(lyr.dens().states(), lyr_addr, tft_id)
                     }).collect();
@@ -1178,7 +1598,7 @@ impl CorticalArea {
             },
             SamplerKind::DenStatesRaw(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_den_tft(lyr.dens().states_raw(), lyr_addr, tft_id)
                     }).collect();
@@ -1188,7 +1608,7 @@ impl CorticalArea {
             },
             SamplerKind::DenEnergies(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_den_tft(lyr.dens().energies(), lyr_addr, tft_id)
                     }).collect();
@@ -1198,7 +1618,7 @@ impl CorticalArea {
             },
             SamplerKind::DenActivities(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_den_tft(lyr.dens().activities(), lyr_addr, tft_id)
                     }).collect();
@@ -1208,7 +1628,7 @@ impl CorticalArea {
             },
             SamplerKind::DenThresholds(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_den_tft(lyr.dens().thresholds(), lyr_addr, tft_id)
                     }).collect();
@@ -1220,7 +1640,7 @@ impl CorticalArea {
             // Syns:
             SamplerKind::SynStates(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_syn_tft(lyr.dens().syns().states(), lyr_addr, tft_id)
                     }).collect();
@@ -1230,7 +1650,7 @@ impl CorticalArea {
             },
             SamplerKind::SynStrengths(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_syn_tft(lyr.dens().syns().strengths(), lyr_addr, tft_id)
                     }).collect();
@@ -1240,7 +1660,7 @@ impl CorticalArea {
             },
             SamplerKind::SynSrcSlcIds(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_syn_tft(lyr.dens().syns().src_slc_ids(), lyr_addr, tft_id)
                     }).collect();
@@ -1250,7 +1670,7 @@ impl CorticalArea {
             },
             SamplerKind::SynSrcColVOffs(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_syn_tft(lyr.dens().syns().src_col_v_offs(), lyr_addr, tft_id)
                     }).collect();
@@ -1260,7 +1680,7 @@ impl CorticalArea {
             },
             SamplerKind::SynSrcColUOffs(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_syn_tft(lyr.dens().syns().src_col_u_offs(), lyr_addr, tft_id)
                     }).collect();
@@ -1270,7 +1690,7 @@ impl CorticalArea {
             },
             SamplerKind::SynFlagSets(lyr_addr) => {
                 let (len, cmd_srcs) = {
-                    let lyr = lyr(&self.data_layers, lyr_addr);
+                    let lyr = lyr(&self.data_layers, lyr_addr)?;
                     let srcs = (0..lyr.tft_count()).map(|tft_id| {
                         CorticalBuffer::data_syn_tft(lyr.dens().syns().flag_sets(), lyr_addr, tft_id)
                     }).collect();
@@ -1279,20 +1699,226 @@ impl CorticalArea {
                 self.sampler_rx_single_u8(len, cmd_srcs, kind.clone(), None, backpressure)
             },
 
-            sk @ _ => panic!("Unknown sampler kind: {:?}", sk),
+            sk @ _ => Err(format!("CorticalArea::sampler: Unsupported sampler kind: {:?}",
+                sk).into()),
         }
     }
 
     /// Blocks until all previously queued OpenCL commands in all
     /// command-queues are issued to the associated device and have completed.
     pub fn finish_queues(&self) {
-        self.write_queue.finish().unwrap();
-        self.ocl_pq.queue().finish().unwrap();
-        self.read_queue.finish().unwrap();
-        self.unmap_queue.finish().unwrap();
+        if let Some(ref io_queues) = self.io_queues {
+            io_queues.finish().unwrap();
+        }
+        if let Some(ocl_pq) = self.backend.pq() {
+            ocl_pq.queue().finish().unwrap();
+        }
         self.exe_graph.finish().unwrap();
     }
 
+    /// Enables or disables learning for all layers at runtime.
+    ///
+    /// Has no effect if learning was disabled in the settings used to
+    /// construct this area (no learning commands exist in the execution
+    /// graph).
+    pub fn set_learning_enabled(&mut self, enabled: bool) {
+        if self.settings.disable_learning { return; }
+        for lyr in self.data_layers.lyrs.iter_mut() {
+            lyr.set_learning_enabled(enabled);
+        }
+        self.learning_enabled = enabled;
+    }
+
+    /// Enables or disables synapse regrowth at runtime.
+    pub fn set_regrowth_enabled(&mut self, enabled: bool) {
+        self.settings.disable_regrowth = !enabled;
+    }
+
+    /// Replaces the learning and regrowth parameters of every layer.
+    ///
+    /// Kernel arguments are updated immediately and take effect from the next
+    /// cycle. Synapses which have already grown keep their strengths.
+    pub fn set_learning_params(&mut self, params: LearningParams) -> CmnResult<()> {
+        params.validate()?;
+        for lyr in self.data_layers.lyrs.iter_mut() {
+            lyr.set_learning_params(&params)?;
+        }
+        self.settings.learning_params = params;
+        Ok(())
+    }
+
+    /// Sets the neuromodulation value of every column (see
+    /// `cortex::Neuromodulation`).
+    ///
+    /// Takes effect from the next cycle and remains in effect until changed.
+    /// Does not block and may be called from within
+    /// `SubcorticalNucleus::pre_cycle`.
+    pub fn set_neuromodulation(&mut self, value: i8) -> CmnResult<()> {
+        self.neuromod.set(value)
+    }
+
+    /// Sets the neuromodulation value of each column individually.
+    ///
+    /// `values` must contain one value per column.
+    pub fn set_neuromodulation_by_column(&mut self, values: &[i8]) -> CmnResult<()> {
+        self.neuromod.set_by_column(values)
+    }
+
+    /// Enables or disables per-cycle anomaly scoring for a temporal
+    /// (pyramidal) layer with at least one distal tuft.
+    ///
+    /// Layer states are read back to the host and scored on the completion
+    /// pool without blocking the cycle (see `::anomaly_scores`). Disabling
+    /// scoring also disconnects any anomaly samplers for the layer.
+    pub fn set_anomaly_tracking(&mut self, lyr_addr: LayerAddress, enabled: bool)
+            -> CmnResult<()> {
+        let anomaly_idx = self.anomaly_layers.iter().position(|al| al.lyr_addr() == lyr_addr);
+
+        if enabled {
+            if anomaly_idx.is_some() { return Ok(()); }
+
+            let (tracker, axon_range, cmd_srcs, tuft_states_len) = {
+                let layer = self.data_layers.lyrs.iter().find(|lyr| lyr.layer_addr() == lyr_addr)
+                    .ok_or(format!("CorticalArea::set_anomaly_tracking: No layer with {} found.",
+                        lyr_addr))?;
+                if layer.tags().contains(LayerTags::SPATIAL) {
+                    return Err(format!("CorticalArea::set_anomaly_tracking: Layer '{}' is not a \
+                        temporal layer.", layer.layer_name()).into());
+                }
+                let lyr = layer.as_data_cell_layer()?;
+                let distal_tft_ids = lyr.cell_scheme().tft_schemes().iter()
+                    .filter(|ts| ts.den_kind() == DendriteKind::Distal)
+                    .map(|ts| ts.tft_id())
+                    .collect();
+                let tracker = AnomalyTracker::new(lyr_addr, lyr.dims().to_len(), distal_tft_ids)?;
+
+                let slc_range = self.area_map.layer_map().layer_info(lyr_addr.layer_id())
+                    .and_then(|li| li.slc_range().cloned())
+                    .ok_or("CorticalArea::set_anomaly_tracking: Layer has no slices.")?;
+                let axon_range = self.area_map.slice_map().axon_range(slc_range.clone());
+                let axn_states = self.axns.states();
+                let area_id = self.area_id;
+                let mut cmd_srcs: Vec<CorticalBuffer> = slc_range.map(|slc_id| {
+                    CorticalBuffer::axon_slice(axn_states, area_id, slc_id as u8)
+                }).collect();
+                cmd_srcs.extend((0..lyr.tft_count()).map(|tft_id| {
+                    CorticalBuffer::data_tft(lyr.tufts().states(), lyr_addr, tft_id)
+                }));
+                (tracker, axon_range, cmd_srcs, lyr.tufts().states().len())
+            };
+
+            self.exe_graph.unlock();
+            let cmd_uid = match self.exe_graph.add_command(
+                    CommandRelations::cortical_sample(cmd_srcs)) {
+                Ok(cmd_uid) => cmd_uid,
+                Err(err) => {
+                    // Relock the graph before bailing:
+                    self.order()?;
+                    return Err(format!("CorticalArea::set_anomaly_tracking: Error adding exe. \
+                        graph command: {}", err).into());
+                },
+            };
+
+            self.anomaly_layers.push(AnomalyLayer {
+                tracker: Arc::new(Mutex::new(tracker)),
+                axon_states: RwVec::from(vec![0u8; axon_range.len()]),
+                axon_range,
+                tuft_states: RwVec::from(vec![0u8; tuft_states_len]),
+                samplers: Arc::new(Mutex::new(Vec::new())),
+                cmd_uid,
+                cmd_idx: None,
+            });
+            self.order()?;
+        } else if let Some(idx) = anomaly_idx {
+            let anomaly_layer = self.anomaly_layers.remove(idx);
+            self.exe_graph.unlock();
+            self.exe_graph.remove_command(anomaly_layer.cmd_uid)?;
+            self.order()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the most recent score of each layer with anomaly tracking
+    /// enabled.
+    ///
+    /// Scores are computed on the completion pool and may lag the most
+    /// recent cycle until its work has completed.
+    pub fn anomaly_scores(&self) -> Vec<AnomalyScore> {
+        self.anomaly_layers.iter().filter_map(|al| al.tracker.lock().unwrap().latest()).collect()
+    }
+
+    /// Returns a description of this area's layer map, dimensions, and the
+    /// cell scheme (including tuft schemes) of each data cell layer.
+    ///
+    /// Stored in snapshots and compared before a snapshot is loaded.
+    pub fn state_fingerprint(&self) -> String {
+        let mut fp = format!("area: '{}', dims: [{}, {}, {}]", self.name, self.dims.depth(),
+            self.dims.v_size(), self.dims.u_size());
+        for layer in self.area_map.layer_map().iter() {
+            fp.push_str(&format!("; layer: '{}' [id: {}, depth: {}, slc_range: {:?}]",
+                layer.name(), layer.layer_id(), layer.depth(), layer.slc_range()));
+        }
+        for lyr in self.data_layers.lyrs.iter() {
+            if let Ok(dcl) = lyr.as_data_cell_layer() {
+                fp.push_str(&format!("; cells: '{}' {:?}", lyr.layer_name(), dcl.cell_scheme()));
+            }
+        }
+        fp
+    }
+
+    /// Writes the learning parameters and the contents of every buffer
+    /// belonging to this area, along with all rng seeds, regrowth positions,
+    /// and neuromodulation values, to a snapshot.
+    ///
+    /// Blocks until all queues have finished.
+    pub fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        self.finish_queues();
+        wtr.write_str(&self.name)?;
+        self.settings.learning_params.save_state(wtr)?;
+        self.axns.save_state(wtr)?;
+
+        for lyr in self.data_layers.lyrs.iter_mut() {
+            lyr.as_data_cell_layer_mut()?.save_state(wtr)?;
+        }
+
+        for cc_lyr in self.control_layers.values_mut() {
+            cc_lyr.save_state(wtr)?;
+        }
+
+        wtr.write_vals("neuromod_values", self.neuromod.values())
+    }
+
+    /// Restores the state written by `::save_state`.
+    ///
+    /// Blocks until all queues have finished.
+    pub fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        self.finish_queues();
+        let name = rdr.read_str()?;
+        if name != self.name {
+            return Err(format!("CorticalArea::load_state: Snapshot area name mismatch \
+                (expected: '{}', found: '{}').", self.name, name).into());
+        }
+        let learning_params = LearningParams::load_state(rdr)?;
+        self.set_learning_params(learning_params)?;
+        self.axns.load_state(rdr)?;
+
+        for lyr in self.data_layers.lyrs.iter_mut() {
+            lyr.as_data_cell_layer_mut()?.load_state(rdr)?;
+        }
+
+        for cc_lyr in self.control_layers.values_mut() {
+            cc_lyr.load_state(rdr)?;
+        }
+
+        // Restored values are written to the device on the next cycle:
+        let neuromod_values = rdr.read_vals("neuromod_values",
+            self.neuromod.col_count() as usize)?;
+        self.neuromod.set_by_column(&neuromod_values)?;
+
+        self.finish_queues();
+        Ok(())
+    }
+
     /// Returns an immutable reference to the requested data cell layer.
     ///
     /// This performs a linear search through all layers.
@@ -1309,15 +1935,21 @@ impl CorticalArea {
 
     #[inline] pub fn axns(&self) -> &AxonSpace { &self.axns }
     #[inline] pub fn dims(&self) -> &CorticalDims { &self.dims }
-    #[inline] pub fn afferent_target_names(&self) -> &[&'static str] { &self.area_map.aff_areas() }
-    #[inline] pub fn efferent_target_names(&self) -> &[&'static str] { &self.area_map.eff_areas() }
-    #[inline] pub fn ocl_pq(&self) -> &ProQue { &self.ocl_pq }
-    #[inline] pub fn device(&self) -> Device { self.ocl_pq.queue().device() }
+    #[inline] pub fn afferent_target_names(&self) -> &[String] { &self.area_map.aff_areas() }
+    #[inline] pub fn efferent_target_names(&self) -> &[String] { &self.area_map.eff_areas() }
+    #[inline] pub fn backend(&self) -> &Backend { &self.backend }
+    #[inline] pub fn ocl_pq(&self) -> Option<&ProQue> { self.backend.pq() }
+    #[inline] pub fn device(&self) -> Option<Device> { self.backend.pq().map(|pq| pq.queue().device()) }
     #[inline] pub fn axon_tract_map(&self) -> SliceTractMap { self.area_map.slice_map().tract_map() }
     #[inline] pub fn area_map(&self) -> &AreaMap { &self.area_map }
     #[inline] pub fn area_id(&self) -> usize { self.area_id }
     #[inline] pub fn aux(&self) -> &Aux { &self.aux }
+    #[inline] pub fn exe_graph(&self) -> &ExecutionGraph { &self.exe_graph }
     #[inline] pub fn exe_graph_mut(&mut self) -> &mut ExecutionGraph { &mut self.exe_graph }
+    #[inline] pub fn learning_enabled(&self) -> bool { self.learning_enabled }
+    #[inline] pub fn regrowth_enabled(&self) -> bool { !self.settings.disable_regrowth }
+    #[inline] pub fn learning_params(&self) -> &LearningParams { &self.settings.learning_params }
+    #[inline] pub fn neuromodulation(&self) -> &Neuromodulation { &self.neuromod }
 }
 
 impl Drop for CorticalArea {
@@ -1332,30 +1964,22 @@ impl Drop for CorticalArea {
 
 #[derive(Debug)]
 pub struct Aux {
-    pub ints_0: Buffer<i32>,
-    pub ints_1: Buffer<i32>,
+    pub ints_0: Storage<i32>,
+    pub ints_1: Storage<i32>,
 }
 
 impl Aux {
-    pub fn new(len: usize, ocl_pq: &ProQue) -> Aux {
-        let ints_0 = Buffer::<i32>::builder()
-            .queue(ocl_pq.queue().clone())
-            .len(len)
-            .fill_val(i32::min_value())
-            .build().unwrap();
-
-        let ints_1 = Buffer::<i32>::builder()
-            .queue(ocl_pq.queue().clone())
-            .len(len)
-            .fill_val(i32::min_value())
-            .build().unwrap();
-
-        ocl_pq.queue().finish().unwrap();
-
-        Aux {
+    pub fn new(len: usize, backend: &Backend) -> CmnResult<Aux> {
+        let ints_0 = backend.buffer::<i32>(len, i32::min_value())?;
+        let ints_1 = backend.buffer::<i32>(len, i32::min_value())?;
+
+        ints_0.finish()?;
+        ints_1.finish()?;
+
+        Ok(Aux {
             ints_0: ints_0,
             ints_1: ints_1,
-        }
+        })
     }
 }
 
@@ -1446,15 +2070,13 @@ pub mod tests {
 
             self.finish_queues();
 
-            let mut vec = vec![0; self.aux.ints_0.len()];
-
             print!("aux.ints_0: ");
             let view_radius = 1 << 24;
-            self.aux.ints_0.read(&mut vec).enq().unwrap();
+            let vec = self.aux.ints_0.read().unwrap();
             util::print_slice(&vec, 1 << 0, Some((0 - view_radius, view_radius)), None, false);
 
             print!("aux.ints_1: ");
-            self.aux.ints_1.read(&mut vec).enq().unwrap();
+            let vec = self.aux.ints_1.read().unwrap();
             util::print_slice(&vec, 1 << 0, Some((0 - view_radius, view_radius)), None, false);
         }
 
@@ -1463,10 +2085,8 @@ pub mod tests {
 
             self.finish_queues();
 
-            let mut vec = vec![0; self.axns.states().len()];
-
             print!("axns: ");
-            self.axns.states().read(&mut vec).enq().unwrap();
+            let vec = self.axns.states().read().unwrap();
             util::print_slice(&vec, 1 << 0, None, None, false);
         }
 
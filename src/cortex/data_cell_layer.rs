@@ -2,8 +2,8 @@
 //!
 
 use std::fmt::Debug;
-use ocl::Buffer;
-use cortex::{Dendrites, ControlCellLayers, Tufts};
+use cortex::Storage;
+use cortex::{Dendrites, ControlCellLayers, Tufts, SnapshotWriter, SnapshotReader};
 use cmn::{CmnResult, CorticalDims};
 use map::{CellScheme, ExecutionGraph, LayerAddress};
 
@@ -15,12 +15,12 @@ pub trait DataCellLayer: 'static + Debug + Send {
     fn layer_addr(&self) -> LayerAddress;
     fn cycle(&mut self, &mut ControlCellLayers, &mut ExecutionGraph) -> CmnResult<()>;
     fn learn(&mut self, &mut ExecutionGraph) -> CmnResult <()> ;
-    fn regrow(&mut self);
-    fn soma(&self) -> &Buffer<u8>;
-    fn soma_mut(&mut self) -> &mut Buffer<u8>;
-    fn energies(&self) -> &Buffer<u8>;
-    fn activities(&self) -> &Buffer<u8>;
-    fn flag_sets(&self) -> &Buffer<u8>;
+    fn regrow(&mut self, Option<usize>, &ExecutionGraph) -> CmnResult<()>;
+    fn soma(&self) -> &Storage<u8>;
+    fn soma_mut(&mut self) -> &mut Storage<u8>;
+    fn energies(&self) -> &Storage<u8>;
+    fn activities(&self) -> &Storage<u8>;
+    fn flag_sets(&self) -> &Storage<u8>;
     fn dims(&self) -> &CorticalDims;
     fn axon_range(&self) -> (usize, usize);
     fn axon_slc_ids(&self) -> &[u8];
@@ -30,6 +30,8 @@ pub trait DataCellLayer: 'static + Debug + Send {
     fn tufts(&self) -> &Tufts;
     fn dens(&self) -> &Dendrites;
     fn dens_mut(&mut self) -> &mut Dendrites;
+    fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()>;
+    fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()>;
 }
 
 
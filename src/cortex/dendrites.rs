@@ -1,10 +1,12 @@
-use rand::{Rng, FromEntropy, rngs::SmallRng};
-use ocl::{ProQue, SpatialDims, Buffer, Kernel, Event};
+use rand::{Rng, rngs::SmallRng};
+use ocl::{SpatialDims, Buffer, Kernel, Event};
 use ocl::traits::OclPrm;
 use cmn::{self, CmnResult, CorticalDims};
 use map::{AreaMap, CellScheme, ExecutionGraph, CommandRelations,
     CorticalBuffer, LayerAddress, CommandUid};
-use cortex::{AxonSpace, Synapses};
+use cortex::{AxonSpace, Synapses, SnapshotWriter, SnapshotReader, LearningParams, Backend,
+    Storage};
+use cortex::host_kernels;
 #[cfg(any(test, feature = "eval"))]
 pub use self::tests::{DenCoords, DendritesTest, den_idx};
 
@@ -16,11 +18,11 @@ pub struct Dendrites {
     layer_id: usize,
     dims: CorticalDims,
     kernels: Vec<Kernel>,
-    thresholds: Buffer<u8>,
-    states_raw: Buffer<u8>,
-    states: Buffer<u8>,
-    energies: Buffer<u8>,
-    activities: Buffer<u8>,
+    thresholds: Storage<u8>,
+    states_raw: Storage<u8>,
+    states: Storage<u8>,
+    energies: Storage<u8>,
+    activities: Storage<u8>,
     activity_counter: usize,
     syns: Synapses,
     den_idzs_by_tft: Vec<u32>,
@@ -29,6 +31,9 @@ pub struct Dendrites {
     exe_cmd_idxs: Vec<usize>,
     rng: SmallRng,
     bypass_exe_graph: bool,
+    host_kernels: bool,
+    syns_per_den_by_tft: Vec<u32>,
+    thresholds_by_tft: Vec<u32>,
 }
 
 impl Dendrites {
@@ -39,8 +44,12 @@ impl Dendrites {
             cell_scheme: CellScheme,
             area_map: &AreaMap,
             axons: &AxonSpace,
-            ocl_pq: &ProQue,
+            backend: &Backend,
             bypass_exe_graph: bool,
+            host_kernels: bool,
+            device_regrowth: bool,
+            learning_params: LearningParams,
+            seed: Option<u64>,
             exe_graph: &mut ExecutionGraph)
             -> CmnResult<Dendrites> {
         let layer_name = layer_name.into();
@@ -52,6 +61,8 @@ impl Dendrites {
         let mut den_counts_by_tft = Vec::with_capacity(tft_count);
         let mut exe_cmd_uids = Vec::with_capacity(tft_count);
         let exe_cmd_idxs = Vec::with_capacity(tft_count);
+        let mut syns_per_den_by_tft = Vec::with_capacity(tft_count);
+        let mut thresholds_by_tft = Vec::with_capacity(tft_count);
         let mut den_count_ttl = 0u32;
 
         for tft_scheme in cell_scheme.tft_schemes() {
@@ -74,11 +85,11 @@ impl Dendrites {
             // println!("");
         }
 
-        let states_raw = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([den_count_ttl]).fill_val(0).build()?;
-        let states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([den_count_ttl]).fill_val(0).build()?;
-        let thresholds = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([den_count_ttl]).fill_val(0).build()?;
-        let energies = Buffer::builder().queue(ocl_pq.queue().clone()).len(den_count_ttl).fill_val(0).build()?;
-        let activities = Buffer::builder().queue(ocl_pq.queue().clone()).len(den_count_ttl).fill_val(0).build()?;
+        let states_raw = backend.buffer::<u8>(den_count_ttl as usize, 0)?;
+        let states = backend.buffer::<u8>(den_count_ttl as usize, 0)?;
+        let thresholds = backend.buffer::<u8>(den_count_ttl as usize, 0)?;
+        let energies = backend.buffer::<u8>(den_count_ttl as usize, 0)?;
+        let activities = backend.buffer::<u8>(den_count_ttl as usize, 0)?;
         // energies.cmd().fill(255, None).enq().unwrap();
         // energies.cmd().fill(1, None).enq().unwrap();
         // energies.default_queue().unwrap().finish().unwrap();
@@ -87,7 +98,8 @@ impl Dendrites {
             layer_name, dims, states.len(), mt = cmn::MT);
 
         let syns = Synapses::new(layer_name.clone(), layer_id, dims, cell_scheme.clone(),
-            area_map, axons, ocl_pq, bypass_exe_graph, exe_graph)?;
+            area_map, axons, backend, bypass_exe_graph, host_kernels, device_regrowth,
+            learning_params, seed, exe_graph)?;
 
         /*=============================================================================
         ===============================================================================
@@ -100,6 +112,8 @@ impl Dendrites {
         {
             let syns_per_den = tft_scheme.syns_per_den();
             let den_threshold = tft_scheme.thresh_init().unwrap_or(cmn::DENDRITE_DEFAULT_INITIAL_THRESHOLD);
+            syns_per_den_by_tft.push(syns_per_den);
+            thresholds_by_tft.push(den_threshold);
 
             assert!(tft_id == tft_scheme.tft_id());
 
@@ -115,23 +129,25 @@ impl Dendrites {
             =============================================================================*/
 
             let kern_name = "den_cycle_tft";
-            kernels.push(ocl_pq.kernel_builder(kern_name)
-                .global_work_size(SpatialDims::One(tft_den_count as usize))
-                .arg(syns.states())
-                .arg(syns.strengths())
-                .arg(&tft_den_idz)
-                .arg(&tft_syn_idz)
-                .arg(&syns_per_den)
-                .arg(&den_threshold)
-                .arg_named("rnd", &0)
-                .arg(&energies)
-                .arg(&activities)
-                .arg(&states_raw)
-                .arg_named("aux_ints_0", None::<&Buffer<i32>>)
-                .arg_named("aux_ints_1", None::<&Buffer<i32>>)
-                .arg(&states)
-                .build()?
-            );
+            if let Some(ocl_pq) = backend.pq() {
+                kernels.push(ocl_pq.kernel_builder(kern_name)
+                    .global_work_size(SpatialDims::One(tft_den_count as usize))
+                    .arg(syns.states().device()?)
+                    .arg(syns.strengths().device()?)
+                    .arg(&tft_den_idz)
+                    .arg(&tft_syn_idz)
+                    .arg(&syns_per_den)
+                    .arg(&den_threshold)
+                    .arg_named("rnd", &0)
+                    .arg(energies.device()?)
+                    .arg(activities.device()?)
+                    .arg(states_raw.device()?)
+                    .arg_named("aux_ints_0", None::<&Buffer<i32>>)
+                    .arg_named("aux_ints_1", None::<&Buffer<i32>>)
+                    .arg(states.device()?)
+                    .build()?
+                );
+            }
 
             if !bypass_exe_graph {
                 exe_cmd_uids.push(exe_graph.add_command(CommandRelations::cortical_kernel(
@@ -170,8 +186,12 @@ impl Dendrites {
             den_counts_by_tft: den_counts_by_tft,
             exe_cmd_uids,
             exe_cmd_idxs,
-            rng: SmallRng::from_entropy(),
+            rng: cmn::seeded_rng(seed, "dendrites", &[layer_addr.area_id() as u64,
+                layer_addr.layer_id() as u64]),
             bypass_exe_graph,
+            host_kernels,
+            syns_per_den_by_tft,
+            thresholds_by_tft,
         })
     }
 
@@ -192,6 +212,12 @@ impl Dendrites {
         if PRNT { println!("    Dens: Cycling syns..."); }
         self.syns.cycle(exe_graph)?;
 
+        if self.host_kernels {
+            self.cycle_host(exe_graph)?;
+            self.activity_counter += 1;
+            return Ok(());
+        }
+
         for (kern, &cmd_idx) in self.kernels.iter_mut().zip(self.exe_cmd_idxs.iter()) {
             if PRNT { println!("    Dens: Cycling kern_cycle (exe_cmd_idx: [{}])...", cmd_idx); }
 
@@ -208,8 +234,42 @@ impl Dendrites {
         Ok(())
     }
 
-    pub fn regrow(&mut self) {
-        self.syns.regrow();
+    /// Cycles dendrites using the host kernels.
+    fn cycle_host(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let mut activities = host_kernels::read(&self.activities)?;
+        let mut states_raw = host_kernels::read(&self.states_raw)?;
+        let mut states = host_kernels::read(&self.states)?;
+
+        for (tft_id, &cmd_idx) in self.exe_cmd_idxs.iter().enumerate() {
+            let syn_states = host_kernels::read(self.syns.states())?;
+            let syn_strengths = host_kernels::read(self.syns.strengths())?;
+            let den_idz = self.den_idzs_by_tft[tft_id];
+            let den_count = self.den_counts_by_tft[tft_id];
+
+            host_kernels::den_cycle_tft(&syn_states, &syn_strengths, den_idz,
+                self.syns.syn_idzs_by_tft()[tft_id], self.syns_per_den_by_tft[tft_id],
+                self.thresholds_by_tft[tft_id], self.rng.gen::<i32>(), den_count,
+                &mut activities, &mut states_raw, &mut states);
+
+            let den_range = den_idz as usize..(den_idz + den_count) as usize;
+            host_kernels::write_range(&self.activities, &activities, den_range.clone())?;
+            host_kernels::write_range(&self.states_raw, &states_raw, den_range.clone())?;
+            host_kernels::write_range(&self.states, &states, den_range)?;
+            exe_graph.set_cmd_event(cmd_idx, None)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn regrow(&mut self, chunk_size: Option<usize>, exe_graph: &ExecutionGraph)
+            -> CmnResult<()> {
+        self.syns.regrow(chunk_size, exe_graph)
+    }
+
+    /// Sets the regrowth parameters of this layer's synapses.
+    #[inline]
+    pub fn set_learning_params(&mut self, params: &LearningParams) -> CmnResult<()> {
+        self.syns.set_learning_params(params)
     }
 
     // Debugging purposes
@@ -230,20 +290,44 @@ impl Dendrites {
         self.activity_counter = 0;
     }
 
+    /// Writes dendrite and synapse state to a snapshot.
+    pub fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_buffer("dens.thresholds", &self.thresholds)?;
+        wtr.write_buffer("dens.states_raw", &self.states_raw)?;
+        wtr.write_buffer("dens.states", &self.states)?;
+        wtr.write_buffer("dens.energies", &self.energies)?;
+        wtr.write_buffer("dens.activities", &self.activities)?;
+        wtr.write_u64(self.activity_counter as u64)?;
+        wtr.write_rng("dens.rng", &self.rng)?;
+        self.syns.save_state(wtr)
+    }
+
+    /// Restores dendrite and synapse state from a snapshot.
+    pub fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_buffer("dens.thresholds", &self.thresholds)?;
+        rdr.read_buffer("dens.states_raw", &self.states_raw)?;
+        rdr.read_buffer("dens.states", &self.states)?;
+        rdr.read_buffer("dens.energies", &self.energies)?;
+        rdr.read_buffer("dens.activities", &self.activities)?;
+        self.activity_counter = rdr.read_u64()? as usize;
+        rdr.read_rng("dens.rng", &mut self.rng)?;
+        self.syns.load_state(rdr)
+    }
+
     #[inline] pub fn layer_id(&self) -> usize { self.layer_id }
-    #[inline] pub fn thresholds(&self) -> &Buffer<u8> { &self.thresholds }
-    #[inline] pub fn states_raw(&self) -> &Buffer<u8> { &self.states_raw }
-    #[inline] pub fn states(&self) -> &Buffer<u8> { &self.states }
-    #[inline] pub fn states_mut(&mut self) -> &mut Buffer<u8> { &mut self.states }
-    #[inline] pub fn energies(&self) -> &Buffer<u8> { &self.energies }
-    #[inline] pub fn activities(&self) -> &Buffer<u8> { &self.activities }
+    #[inline] pub fn thresholds(&self) -> &Storage<u8> { &self.thresholds }
+    #[inline] pub fn states_raw(&self) -> &Storage<u8> { &self.states_raw }
+    #[inline] pub fn states(&self) -> &Storage<u8> { &self.states }
+    #[inline] pub fn states_mut(&mut self) -> &mut Storage<u8> { &mut self.states }
+    #[inline] pub fn energies(&self) -> &Storage<u8> { &self.energies }
+    #[inline] pub fn activities(&self) -> &Storage<u8> { &self.activities }
     #[inline] pub fn activity_counter(&self) -> usize { self.activity_counter }
     #[inline] pub fn dims(&self) -> &CorticalDims { &self.dims }
     #[inline] pub fn syns(&self) -> &Synapses { &self.syns }
     #[inline] pub fn syns_mut(&mut self) -> &mut Synapses { &mut self.syns }
     #[inline] pub fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] pub fn count(&self) -> u32 { self.states.len() as u32 }
-    #[inline] pub fn tft_count(&self) -> usize { self.kernels.len() }
+    #[inline] pub fn tft_count(&self) -> usize { self.den_idzs_by_tft.len() }
     #[inline] pub fn den_idzs_by_tft(&self) -> &[u32] { self.den_idzs_by_tft.as_slice() }
     #[inline] pub fn den_counts_by_tft(&self) -> &[u32] { self.den_counts_by_tft.as_slice() }
 }
@@ -256,15 +340,15 @@ pub mod tests {
     use std::ops::{Range};
     use std::fmt::{Display, Formatter, Result};
     use rand::distributions::{Distribution, Range as RandRange};
-    use ocl::{util, OclPrm, Buffer};
+    use ocl::{util, OclPrm};
     // use tests;
     use cmn::{CorticalDims};
-    use cortex::{SynapsesTest, TuftDims, CelCoords, syn_idx};
+    use cortex::{SynapsesTest, TuftDims, CelCoords, Storage, syn_idx};
     use super::{Dendrites};
 
-    pub fn read_idx_direct<T: OclPrm>(idx: usize, buf: &Buffer<T>) -> T {
+    pub fn read_idx_direct<T: OclPrm>(idx: usize, buf: &Storage<T>) -> T {
         let mut val: [T; 1] = [Default::default()];
-        buf.cmd().read(&mut val[..]).offset(idx).enq().unwrap();
+        buf.read_into(idx, &mut val[..]).unwrap();
         val[0]
     }
 
@@ -284,20 +368,20 @@ pub mod tests {
 
     impl DendritesTest for Dendrites {
         fn set_all_to_zero(&mut self, set_syns_zero: bool) {
-            self.thresholds.default_queue().unwrap().finish().unwrap();
-            self.states_raw.default_queue().unwrap().finish().unwrap();
-            self.states.default_queue().unwrap().finish().unwrap();
-            self.energies.default_queue().unwrap().finish().unwrap();
+            self.thresholds.finish().unwrap();
+            self.states_raw.finish().unwrap();
+            self.states.finish().unwrap();
+            self.energies.finish().unwrap();
 
-            self.thresholds.cmd().fill(0, None).enq().unwrap();
-            self.states_raw.cmd().fill(0, None).enq().unwrap();
-            self.states.cmd().fill(0, None).enq().unwrap();
-            self.energies.cmd().fill(0, None).enq().unwrap();
+            self.thresholds.fill(0).unwrap();
+            self.states_raw.fill(0).unwrap();
+            self.states.fill(0).unwrap();
+            self.energies.fill(0).unwrap();
 
-            self.thresholds.default_queue().unwrap().finish().unwrap();
-            self.states_raw.default_queue().unwrap().finish().unwrap();
-            self.states.default_queue().unwrap().finish().unwrap();
-            self.energies.default_queue().unwrap().finish().unwrap();
+            self.thresholds.finish().unwrap();
+            self.states_raw.finish().unwrap();
+            self.states.finish().unwrap();
+            self.energies.finish().unwrap();
 
             if set_syns_zero { self.syns.set_all_to_zero() };
         }
@@ -359,14 +443,12 @@ pub mod tests {
         }
 
         fn print_range(&self, idx_range: Option<Range<usize>>) {
-            let mut vec = vec![0; self.states.len()];
-
             print!("dens.states_raw: ");
-            self.states_raw.read(&mut vec).enq().unwrap();
+            let vec = self.states_raw.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
 
             print!("dens.states: ");
-            self.states.read(&mut vec).enq().unwrap();
+            let vec = self.states.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
         }
 
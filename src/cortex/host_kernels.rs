@@ -0,0 +1,921 @@
+//! Host-side reference implementations of the cortical kernels.
+//!
+//! Each function in this module mirrors the OpenCL kernel of the same name
+//! found in `cl/bismit.cl`, `cl/syns.cl`, or `cl/control.cl`, processing the
+//! kernel's entire global work size sequentially. Integer widths, wrapping,
+//! and truncation behave exactly as they do on the device (including a few
+//! long-standing quirks, which are noted where they occur) so that the
+//! results of each function can be compared byte-for-byte with the output of
+//! its device counterpart.
+//!
+//! Kernels are selected for use in place of their OpenCL versions with
+//! `CorticalAreaSettings::host_kernels`, which also places every buffer of
+//! the area in host memory (see `Storage`). No OpenCL platform is required.
+//! Each component reads the buffers it needs, computes on the host, then
+//! writes its results back before marking its command complete. Host
+//! commands complete in execution graph order and record no events.
+//!
+//! Sensory filters have no host implementation. Areas with filter chains
+//! require the device kernels.
+//!
+
+use std::ops::Range;
+use ocl::OclPrm;
+use cmn::{self, CmnResult, SYN_PREV_ACTIVE_FLAG, CEL_PREV_ACTIVE_FLAG, DEN_BASAL_PROXIMAL_FLAG,
+    DEN_BASAL_DISTAL_FLAG, DEN_APICAL_DISTAL_FLAG, NEUROMODULATION_RND_CTR};
+use map::{AreaMap, SliceMap};
+use cortex::{AxonSpace, DataCellLayer, Storage};
+use {SrcOfs, SlcId};
+
+// `cl/bismit.cl`:
+pub const SYNAPSE_AXON_BIAS_LOG2: u8 = 1;
+pub const DENDRITE_ACTIVITY_DECAY_FACTOR: u16 = 1536;
+// `cl/syns.cl`:
+pub const SYN_REGROW_ATTEMPTS_MAX: u32 = 64;
+pub const SYN_SRC_POOL_ENTRY_LEN: usize = 5;
+// `cl/control.cl`:
+pub const CELL_ACTIVITY_DECAY_FACTOR: u16 = 768;
+pub const INHIB_INFL_CENTER_OFFSET: i32 = 1;
+pub const INHIB_INFL_HORIZ_OFFSET: i32 = 3;
+
+
+/*=============================================================================
+================================== TRANSFER ===================================
+=============================================================================*/
+
+/// Reads the entire contents of a buffer (blocking).
+pub fn read<T: OclPrm>(buf: &Storage<T>) -> CmnResult<Vec<T>> {
+    buf.read()
+}
+
+/// Reads `range` of a buffer (blocking).
+pub fn read_range<T: OclPrm>(buf: &Storage<T>, range: Range<usize>) -> CmnResult<Vec<T>> {
+    buf.read_range(range)
+}
+
+/// Writes `vec` to a buffer beginning at `offset` (blocking).
+pub fn write_at<T: OclPrm>(buf: &Storage<T>, vec: &[T], offset: usize) -> CmnResult<()> {
+    buf.write_at(vec, offset)
+}
+
+/// Writes `vec[range]` to the same range within a buffer (blocking).
+pub fn write_range<T: OclPrm>(buf: &Storage<T>, vec: &[T], range: Range<usize>) -> CmnResult<()> {
+    if range.start < range.end {
+        buf.write_at(&vec[range.clone()], range.start)?;
+    }
+    Ok(())
+}
+
+
+/// The buffers and geometry of a control cell layer's host layer, retained
+/// for use by the host kernels.
+#[derive(Debug, Clone)]
+pub struct HostLayer {
+    pub soma: Storage<u8>,
+    pub energies: Storage<u8>,
+    pub activities: Storage<u8>,
+    pub axn_states: Storage<u8>,
+    pub slc_geo: AxonSliceGeometry,
+    pub depth: SlcId,
+    pub v_size: u32,
+    pub u_size: u32,
+    pub base_axn_slc: SlcId,
+    /// The range of axons belonging to the host layer. Only this range is
+    /// written back to `axn_states`.
+    pub axn_range: Range<usize>,
+}
+
+impl HostLayer {
+    pub fn new(host_lyr: &DataCellLayer, axns: &AxonSpace, area_map: &AreaMap) -> HostLayer {
+        let base_axn_slc = area_map.layer_slc_ids(&[host_lyr.layer_name()])[0];
+        let depth = host_lyr.dims().depth();
+
+        HostLayer {
+            soma: host_lyr.soma().clone(),
+            energies: host_lyr.energies().clone(),
+            activities: host_lyr.activities().clone(),
+            axn_states: axns.states().clone(),
+            slc_geo: AxonSliceGeometry::new(area_map.slice_map()),
+            depth,
+            v_size: host_lyr.dims().v_size(),
+            u_size: host_lyr.dims().u_size(),
+            base_axn_slc,
+            axn_range: area_map.slice_map().axon_range(base_axn_slc as usize..
+                (base_axn_slc + depth) as usize),
+        }
+    }
+}
+
+
+/*=============================================================================
+================================== INDEXING ===================================
+=============================================================================*/
+
+/// Host-side copy of the axon slice constants compiled into each kernel
+/// program (`AXN_SLC_IDZS`, `AXN_SLC_V_SIZES`, et al.).
+#[derive(Debug, Clone)]
+pub struct AxonSliceGeometry {
+    idzs: Vec<u32>,
+    v_sizes: Vec<u32>,
+    u_sizes: Vec<u32>,
+    v_scales: Vec<u8>,
+    u_scales: Vec<u8>,
+    v_mids: Vec<u8>,
+    u_mids: Vec<u8>,
+}
+
+impl AxonSliceGeometry {
+    /// Returns the geometry for an area's slice map.
+    ///
+    /// Scales and mids are truncated to `u8` as they are within the
+    /// `__constant uchar` arrays on the device.
+    pub fn new(slice_map: &SliceMap) -> AxonSliceGeometry {
+        AxonSliceGeometry {
+            idzs: slice_map.axon_idzs().to_owned(),
+            v_sizes: slice_map.v_sizes().to_owned(),
+            u_sizes: slice_map.u_sizes().to_owned(),
+            v_scales: slice_map.v_scales().iter().map(|&s| s as u8).collect(),
+            u_scales: slice_map.u_scales().iter().map(|&s| s as u8).collect(),
+            v_mids: slice_map.v_mids().iter().map(|&m| m as u8).collect(),
+            u_mids: slice_map.u_mids().iter().map(|&m| m as u8).collect(),
+        }
+    }
+
+    /// Returns a geometry of `slc_count` contiguous, unscaled slices each
+    /// `v_size` x `u_size` in size.
+    pub fn uniform(slc_count: usize, v_size: u32, u_size: u32) -> AxonSliceGeometry {
+        AxonSliceGeometry {
+            idzs: (0..slc_count as u32).map(|slc_id| slc_id * v_size * u_size).collect(),
+            v_sizes: vec![v_size; slc_count],
+            u_sizes: vec![u_size; slc_count],
+            v_scales: vec![cmn::SLC_SCL_COEFF as u8; slc_count],
+            u_scales: vec![cmn::SLC_SCL_COEFF as u8; slc_count],
+            v_mids: vec![0; slc_count],
+            u_mids: vec![0; slc_count],
+        }
+    }
+
+    /// Returns the linear index of an axon and whether or not that index is
+    /// within the bounds of its slice (`axn_idx_3d_unsafe`).
+    pub fn axn_idx_3d_unsafe(&self, slc_id: SlcId, v_id_unscaled: u32, v_ofs: SrcOfs,
+            u_id_unscaled: u32, u_ofs: SrcOfs) -> (u32, bool) {
+        let slc = slc_id as usize;
+        let v_size = self.v_sizes[slc] as i32;
+        let u_size = self.u_sizes[slc] as i32;
+
+        // Middle offsets are added to the `char` offsets in place:
+        let v_ofs = v_ofs.wrapping_add(self.v_mids[slc] as i8) as i32;
+        let u_ofs = u_ofs.wrapping_add(self.u_mids[slc] as i8) as i32;
+
+        let v_id_scaled = (v_id_unscaled.wrapping_mul(self.v_scales[slc] as u32)
+            >> cmn::SLC_SCL_COEFF_L2) as i32;
+        let u_id_scaled = (u_id_unscaled.wrapping_mul(self.u_scales[slc] as u32)
+            >> cmn::SLC_SCL_COEFF_L2) as i32;
+
+        let idx_is_safe = coord_is_safe(v_size, v_id_scaled, v_ofs) &&
+            coord_is_safe(u_size, u_id_scaled, u_ofs);
+
+        let idx = self.idzs[slc].wrapping_add(((v_id_scaled + v_ofs).wrapping_mul(u_size)
+            .wrapping_add(u_id_scaled + u_ofs)) as u32);
+
+        (idx, idx_is_safe)
+    }
+
+    /// Returns the state of an axon or zero if out of bounds
+    /// (`axn_state_3d_safe`).
+    #[inline]
+    pub fn axn_state_3d_safe(&self, slc_id: SlcId, v_id: u32, v_ofs: SrcOfs, u_id: u32,
+            u_ofs: SrcOfs, axn_states: &[u8]) -> u8 {
+        match self.axn_idx_3d_unsafe(slc_id, v_id, v_ofs, u_id, u_ofs) {
+            (idx, true) => axn_states[idx as usize],
+            (_, false) => 0,
+        }
+    }
+
+    #[inline] pub fn slc_count(&self) -> usize { self.idzs.len() }
+}
+
+
+/// Bounds check for a single dimension of a cellular coordinate.
+#[inline]
+pub fn coord_is_safe(dim_size: i32, coord_id: i32, coord_ofs: i32) -> bool {
+    let coord_ttl = coord_id + coord_ofs;
+    (coord_ttl >= 0) & (coord_ttl < dim_size)
+}
+
+/// Linear index of a cell (not accurate for axons).
+#[inline]
+pub fn cel_idx_3d_unsafe(slc_id_lyr: u32, v_size: u32, v_id: i32, u_size: u32, u_id: i32) -> u32 {
+    ((slc_id_lyr as i32).wrapping_mul((v_size as i32).wrapping_mul(u_size as i32))
+        .wrapping_add(v_id.wrapping_mul(u_size as i32).wrapping_add(u_id))) as u32
+}
+
+/// Returns the state of a cell or zero if out of bounds.
+#[inline]
+fn cel_state_3d_safe(slc_id_lyr: u32, v_size: u32, v_id: i32, v_ofs: i32, u_size: u32,
+        u_id: i32, u_ofs: i32, cel_states: &[u8]) -> u8 {
+    if coord_is_safe(v_size as i32, v_id, v_ofs) && coord_is_safe(u_size as i32, u_id, u_ofs) {
+        cel_states[cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id + v_ofs, u_size, u_id + u_ofs) as usize]
+    } else {
+        0
+    }
+}
+
+
+/*=============================================================================
+===================================== RND =====================================
+=============================================================================*/
+
+/// Cheap xorshift random number.
+#[inline]
+pub fn rnd_mix(rnd_a: i32, seed: i32) -> i32 {
+    let mut seed = seed;
+    seed ^= (seed ^ rnd_a) << 13;
+    seed ^= seed >> 17;
+    seed ^= seed << 5;
+    seed
+}
+
+/// Integer hash (two rounds of multiply-xorshift).
+#[inline]
+pub fn rnd_hash(x: u32) -> u32 {
+    let mut x = x;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
+
+/// Counter-based random number. Always the same for the same seed, index,
+/// and counter.
+#[inline]
+pub fn rnd_ctr(seed: u32, idx: u32, ctr: u32) -> u32 {
+    rnd_hash(rnd_hash(rnd_hash(ctr) ^ idx) ^ seed)
+}
+
+/// Maps a random number onto `[0, n)`.
+#[inline]
+pub fn rnd_below(rnd: u32, n: u32) -> u32 {
+    ((rnd as u64 * n as u64) >> 32) as u32
+}
+
+/// Scales or gates a learning step (-1, 0, or 1) by a neuromodulation value
+/// (`neuromod_step`).
+#[inline]
+pub fn neuromod_step(step: i32, neuromod: i8, rnd: i32, syn_idx: u32) -> i32 {
+    let is_open = (rnd_below(rnd_ctr(rnd as u32, syn_idx, NEUROMODULATION_RND_CTR), 127) as i32) <
+        (neuromod as i32).abs();
+    if is_open { if neuromod < 0 { -step } else { step } } else { 0 }
+}
+
+/// Returns the learning rate mask for an inverse log2 rate (`lshft_mask`).
+#[inline]
+pub fn lr_mask(lr_l2i: i32) -> i32 {
+    (0x7F << lr_l2i) | ((1 << lr_l2i) - 1)
+}
+
+/// Returns true if a signed value should be incremented.
+#[inline]
+pub fn rnd_inc(rnd_a: i32, seed: i32, val: i8, lr_mask: i32) -> bool {
+    (rnd_mix(rnd_a, seed) & lr_mask) > ((val as i32).abs() + (lr_mask - 0x7f))
+}
+
+/// Returns true if a signed value should be decremented.
+#[inline]
+pub fn rnd_dec(rnd_a: i32, seed: i32, val: i8, lr_mask: i32) -> bool {
+    let val_is_max = (val == 127) as i32;
+    (rnd_mix(rnd_a, seed) & lr_mask) > (((val as i32).abs() - val_is_max) + (lr_mask - 0x7f))
+}
+
+/// Returns true if an unsigned value should be incremented.
+#[inline]
+pub fn rnd_inc_u(rnd_a: i32, seed: i32, val: u8) -> bool {
+    (rnd_mix(rnd_a, seed) & 0xFF) > val as i32
+}
+
+/// Returns true (approximately) every `cutoff / 65536` calls.
+#[inline]
+pub fn rnd_0xffff(rnd: i32, seed: i32, cutoff: u16) -> bool {
+    (rnd_mix(rnd, seed) & 0xFFFF) < cutoff as i32
+}
+
+/// Updates an activity rating.
+#[inline]
+pub fn update_activity_rating(activity_rating: u8, is_active: bool, rnd: i32, rnd_seed: i32,
+        decay_factor: u16) -> u8 {
+    let mut activity_rating = activity_rating;
+    activity_rating = activity_rating.wrapping_add(
+        (rnd_inc_u(rnd, rnd_seed, activity_rating) & is_active) as u8);
+    activity_rating.wrapping_sub(
+        (rnd_0xffff(rnd, rnd_seed << 1, decay_factor) & (activity_rating > 0)) as u8)
+}
+
+
+/*=============================================================================
+=================================== SYNAPSES ==================================
+=============================================================================*/
+
+/// Returns the synapse state resulting from a source axon state.
+#[inline]
+pub fn syn_fire(axn_state: u8) -> u8 {
+    (((axn_state != 0) as u8) << 7) + (axn_state >> SYNAPSE_AXON_BIAS_LOG2)
+}
+
+/// Updates flags to indicate activity of the prior state.
+pub fn tft_set_syn_flags(states: &[u8], flag_sets: &mut [u8]) {
+    for (&state, flag_set) in states.iter().zip(flag_sets.iter_mut()) {
+        *flag_set = (*flag_set & !SYN_PREV_ACTIVE_FLAG) |
+            (((state != 0) as u8) * SYN_PREV_ACTIVE_FLAG);
+    }
+}
+
+/// Processes synapses for a tuft.
+///
+/// Also stands in for the `layer_cycle_syns_wow` variants, which produce
+/// identical results.
+pub fn tft_cycle_syns(axn_states: &[u8], slc_geo: &AxonSliceGeometry,
+        syn_src_col_u_offs: &[SrcOfs], syn_src_col_v_offs: &[SrcOfs], syn_src_slc_ids: &[SlcId],
+        syn_idz_tft: u32, syns_per_tft: u32, layer_depth: SlcId, v_size: u32, u_size: u32,
+        syn_states: &mut [u8]) {
+    for slc_id_lyr in 0..layer_depth as u32 {
+        for v_id in 0..v_size {
+            for u_id in 0..u_size {
+                let syn_idz = (cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id as i32, u_size,
+                    u_id as i32) * syns_per_tft) + syn_idz_tft;
+
+                for syn_idx in syn_idz as usize..(syn_idz + syns_per_tft) as usize {
+                    let axn_state = slc_geo.axn_state_3d_safe(syn_src_slc_ids[syn_idx], v_id,
+                        syn_src_col_v_offs[syn_idx], u_id, syn_src_col_u_offs[syn_idx], axn_states);
+                    syn_states[syn_idx] = syn_fire(axn_state);
+                }
+            }
+        }
+    }
+}
+
+/// Regrows each synapse of a tuft within `[syn_idz, syn_idn)` whose strength
+/// is at or below `str_floor`, processing `den_count` dendrites beginning
+/// with dendrite `den_id_tft_z` of the tuft.
+///
+/// New strengths are scaled by a factor within `[-str_dev, str_dev]` then
+/// offset by `str_default` (saturating).
+///
+/// See `SynSrcSlice::regrowth_pool` for the layout of `src_pool` and
+/// `src_offs`.
+pub fn tft_regrow_syns(src_pool: &[i32], src_offs: &[SrcOfs], tft_syn_idz: u32,
+        syns_per_den: u32, col_count: u32, u_size: u32, src_saturated: bool, str_floor: i8,
+        str_dev: i8, str_default: i8, den_id_tft_z: u32, den_count: u32, syn_idz: u32,
+        syn_idn: u32, rnd_seed: u32, syn_strengths: &mut [i8], syn_src_slc_ids: &mut [SlcId],
+        syn_src_col_v_offs: &mut [SrcOfs], syn_src_col_u_offs: &mut [SrcOfs]) {
+    let src_pool_len = (src_pool.len() / SYN_SRC_POOL_ENTRY_LEN) as u32;
+    let src_key = |slc_id: SlcId, v_ofs: SrcOfs, u_ofs: SrcOfs| -> i32 {
+        (slc_id as i32 * col_count as i32) + (v_ofs as i32 * u_size as i32) + u_ofs as i32
+    };
+
+    for den_id_tft in den_id_tft_z..(den_id_tft_z + den_count) {
+        let den_syn_idz = tft_syn_idz + (den_id_tft * syns_per_den);
+        let den_syn_idn = den_syn_idz + syns_per_den;
+
+        for syn_idx in den_syn_idz.max(syn_idz)..den_syn_idn.min(syn_idn) {
+            if syn_strengths[syn_idx as usize] > str_floor { continue; }
+
+            for attempt in 0..SYN_REGROW_ATTEMPTS_MAX {
+                let ctr = attempt << 2;
+                let entry_idz = rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr), src_pool_len) as usize *
+                    SYN_SRC_POOL_ENTRY_LEN;
+                let entry = &src_pool[entry_idz..(entry_idz + SYN_SRC_POOL_ENTRY_LEN)];
+
+                let slc_id = entry[0] as SlcId;
+                let ofs_len = entry[2] as u32;
+                let v_reach = entry[3];
+                let u_reach = entry[4];
+
+                let (v_ofs, u_ofs, strength) = if ofs_len == 0 {
+                    let v_ofs = rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 1),
+                        ((v_reach << 1) + 1) as u32) as i32 - v_reach;
+                    let u_ofs = rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 2),
+                        ((u_reach << 1) + 1) as u32) as i32 - u_reach;
+                    (v_ofs as SrcOfs, u_ofs as SrcOfs, 0)
+                } else {
+                    let ofs_idx = (entry[1] as u32 + rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 1),
+                        ofs_len)) as usize;
+                    let v_ofs = src_offs[ofs_idx << 1];
+                    let u_ofs = src_offs[(ofs_idx << 1) + 1];
+
+                    let intensity = (((v_reach - (v_ofs as i32).abs()) +
+                        (u_reach - (u_ofs as i32).abs())) >>
+                        cmn::SYNAPSE_STRENGTH_INTENSITY_REDUCTION_L2) as i8;
+                    let str_factor = (rnd_below(rnd_ctr(rnd_seed, syn_idx, ctr + 3),
+                        ((str_dev as i32) * 2 + 1) as u32) as i32 - str_dev as i32) as i8;
+                    (v_ofs, u_ofs, intensity.wrapping_mul(str_factor))
+                };
+                let strength = strength.saturating_add(str_default);
+
+                let new_key = src_key(slc_id, v_ofs, u_ofs);
+                let is_unique = (den_syn_idz as usize..den_syn_idn as usize).all(|idx| {
+                    src_key(syn_src_slc_ids[idx], syn_src_col_v_offs[idx],
+                        syn_src_col_u_offs[idx]) != new_key
+                });
+
+                if is_unique || src_saturated {
+                    syn_strengths[syn_idx as usize] = strength;
+                    syn_src_slc_ids[syn_idx as usize] = slc_id;
+                    syn_src_col_v_offs[syn_idx as usize] = v_ofs;
+                    syn_src_col_u_offs[syn_idx as usize] = u_ofs;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+
+/*=============================================================================
+================================== DENDRITES ==================================
+=============================================================================*/
+
+/// Cycles the dendrites of a tuft.
+pub fn den_cycle_tft(syn_states: &[u8], syn_strengths: &[i8], tft_den_idz: u32,
+        tft_syn_idz: u32, syns_per_den: u32, den_threshold: u32, rnd: i32, tft_den_count: u32,
+        den_activities: &mut [u8], den_states_raw: &mut [u8], den_states: &mut [u8]) {
+    // At `den_reduction = 4`, each syn state will amount to between 8 and 16:
+    let den_reduction = 4;
+
+    for den_id_lyrtft in 0..tft_den_count {
+        let syn_idz_den = (den_id_lyrtft * syns_per_den) + tft_syn_idz;
+        let syn_idn_den = syn_idz_den + syns_per_den;
+
+        let mut syn_sum = 0i32;
+        let mut syn_sum_raw = 0i32;
+
+        for syn_idx in syn_idz_den as usize..syn_idn_den as usize {
+            let syn_state = syn_states[syn_idx] as i32;
+            syn_sum += (syn_strengths[syn_idx] >= 0) as i32 * syn_state;
+            syn_sum_raw += syn_state;
+        }
+
+        let den_is_active = (syn_sum as u32) > den_threshold;
+        syn_sum *= den_is_active as i32;
+
+        let den_idx = (den_id_lyrtft + tft_den_idz) as usize;
+
+        let rnd_seed = ((syn_sum_raw as u32).wrapping_add(den_threshold)) | den_idx as u32;
+        den_activities[den_idx] = update_activity_rating(den_activities[den_idx], den_is_active,
+            rnd, rnd_seed as i32, DENDRITE_ACTIVITY_DECAY_FACTOR);
+
+        den_states_raw[den_idx] = clamp_u8(syn_sum_raw >> den_reduction);
+        den_states[den_idx] = clamp_u8(syn_sum >> den_reduction);
+    }
+}
+
+
+/*=============================================================================
+==================================== TUFTS ====================================
+=============================================================================*/
+
+/// Determines the best dendrite state for each cell-tuft.
+pub fn tft_cycle(den_states_raw: &[u8], den_states: &[u8], lyrtft_cel_idz: u32,
+        lyrtft_den_idz: u32, dens_per_tft: u32, max_active_dens_l2: u8, lyrtft_cel_count: u32,
+        celtft_prev_best_den_ids: &mut [u8], celtft_prev_best_den_states_raw: &mut [u8],
+        celtft_prev_best_den_states: &mut [u8], celtft_prev_states: &mut [u8],
+        celtft_best_den_ids: &mut [u8], celtft_best_den_states_raw: &mut [u8],
+        celtft_best_den_states: &mut [u8], celtft_states: &mut [u8]) {
+    for cel_id_lyrtft in 0..lyrtft_cel_count {
+        let cel_den_idz = lyrtft_den_idz + (cel_id_lyrtft * dens_per_tft);
+
+        let mut best_den_id = 0u32;
+        let mut best_den_state_raw = 0u32;
+        let mut best_den_state = 0u32;
+        let mut active_den_state_sum = 0u32;
+
+        for den_id_celtft in 0..dens_per_tft {
+            let den_idx = (cel_den_idz + den_id_celtft) as usize;
+            let den_state_raw = den_states_raw[den_idx] as u32;
+            let den_state = den_states[den_idx] as u32;
+
+            if den_state_raw > best_den_state_raw {
+                best_den_id = den_id_celtft;
+                best_den_state_raw = den_state_raw;
+            }
+
+            if den_state > best_den_state {
+                best_den_state = den_state;
+            }
+
+            active_den_state_sum = active_den_state_sum.wrapping_add(den_state);
+        }
+
+        // Scale `active_den_state_sum` based on a max of `max_active_dens_l2` within 0-255:
+        let active_den_state_max_l2 = max_active_dens_l2 as u32 + 8;
+        let celtft_state = if active_den_state_sum >= (1 << active_den_state_max_l2) {
+            255
+        } else {
+            (active_den_state_sum >> max_active_dens_l2) as u8
+        };
+
+        let celtft_idx = (lyrtft_cel_idz + cel_id_lyrtft) as usize;
+
+        celtft_prev_best_den_ids[celtft_idx] = celtft_best_den_ids[celtft_idx];
+        celtft_prev_best_den_states_raw[celtft_idx] = celtft_best_den_states_raw[celtft_idx];
+        celtft_prev_best_den_states[celtft_idx] = celtft_best_den_states[celtft_idx];
+        celtft_prev_states[celtft_idx] = celtft_states[celtft_idx];
+
+        celtft_best_den_ids[celtft_idx] = best_den_id as u8;
+        celtft_best_den_states_raw[celtft_idx] = best_den_state_raw as u8;
+        celtft_best_den_states[celtft_idx] = best_den_state as u8;
+        celtft_states[celtft_idx] = celtft_state;
+    }
+}
+
+
+/*=============================================================================
+=================================== LEARNING ==================================
+=============================================================================*/
+
+/// Distal synapse medium-term potentiation/depression for a single dendrite
+/// (`dst_syns__active__mtpot_mtdep`).
+///
+/// `syn_flag_sets` and `syn_strengths` begin at synapse `syn_ofs`.
+///
+/// [NOTE]: The seed passed to `rnd_inc`/`rnd_dec` is `syn_idz + i` where `i`
+/// is already an absolute index. This matches the device.
+fn dst_syns_active_mtpot_mtdep(syn_idz: u32, syns_per_den: u32, pr_l2i: i32, dr_l2i: i32,
+        neuromod: i8, rnd: i32, syn_ofs: u32, syn_flag_sets: &[u8], syn_strengths: &mut [i8]) {
+    let pr_mask = lr_mask(pr_l2i);
+    let dr_mask = lr_mask(dr_l2i);
+
+    for i in syn_idz..(syn_idz + syns_per_den) {
+        let seed = syn_idz.wrapping_add(i) as i32;
+        let syn_idx_lcl = (i - syn_ofs) as usize;
+        let syn_strength = syn_strengths[syn_idx_lcl];
+        let syn_prev_active = (syn_flag_sets[syn_idx_lcl] & SYN_PREV_ACTIVE_FLAG) ==
+            SYN_PREV_ACTIVE_FLAG;
+
+        let step = if syn_prev_active {
+            rnd_inc(rnd, seed, syn_strength, pr_mask) as i32
+        } else {
+            -(rnd_dec(rnd, seed, syn_strength, dr_mask) as i32)
+        };
+
+        syn_strengths[syn_idx_lcl] = syn_strength
+            .saturating_add(neuromod_step(step, neuromod, rnd, i) as i8);
+    }
+}
+
+/// Proximal synapse potentiation/depression for a single dendrite
+/// (`prx_syns__active__mtp_ltd`).
+fn prx_syns_active_mtp_ltd(syn_states: &[u8], syn_idz: u32, syns_per_den: u32, pr_l2i: i32,
+        dr_l2i: i32, neuromod: i8, rnd: i32, syn_strengths: &mut [i8]) {
+    let pr_mask = lr_mask(pr_l2i);
+    let dr_mask = lr_mask(dr_l2i);
+
+    for i in syn_idz..(syn_idz + syns_per_den) {
+        let seed = syn_idz.wrapping_add(i) as i32;
+        let syn_strength = syn_strengths[i as usize];
+        let should_inc = rnd_inc(rnd, seed, syn_strength, pr_mask);
+        let should_dec = rnd_dec(rnd, seed, syn_strength, dr_mask);
+        let syn_is_active = syn_states[i as usize] != 0;
+
+        let step = (syn_is_active & should_inc) as i32 - (!syn_is_active & should_dec) as i32;
+
+        syn_strengths[i as usize] = syn_strength
+            .saturating_add(neuromod_step(step, neuromod, rnd, i) as i8);
+    }
+}
+
+/// Spiny stellate potentiation, one cell per work item (`ssc_mtp_simple`).
+pub fn ssc_mtp_simple(axn_states: &[u8], syn_states: &[u8], cel_axn_idz: u32,
+        syns_per_tft: u32, pr_l2i: i32, dr_l2i: i32, neuromods: &[i8], neuromod_col_count: u32,
+        rnd: u32, cel_count: u32, syn_strengths: &mut [i8]) {
+    for cel_id in 0..cel_count {
+        if axn_states[(cel_axn_idz + cel_id) as usize] != 0 {
+            let syn_idz = calc_syn_idz_old(0, cel_count, cel_id, syns_per_tft);
+            let neuromod = neuromods[(cel_id % neuromod_col_count) as usize];
+            prx_syns_active_mtp_ltd(syn_states, syn_idz, syns_per_tft, pr_l2i, dr_l2i,
+                neuromod, rnd as i32, syn_strengths);
+        }
+    }
+}
+
+/// Spiny stellate potentiation over cell groups (`ssc_mtp`).
+///
+/// [NOTE]: As on the device, the cell count used to calculate the tuft
+/// offset is the number of cell *groups*.
+pub fn ssc_mtp(axn_states: &[u8], syn_states: &[u8], cel_lyr_axn_idz: u32, cels_per_grp: u32,
+        syns_per_tft: u8, pr_l2i: i32, dr_l2i: i32, neuromods: &[i8], neuromod_col_count: u32,
+        rnd: u32, tft_count: u32, cel_grp_count: u32, syn_strengths: &mut [i8]) {
+    for tuft_id in 0..tft_count {
+        for cel_grp_id in 0..cel_grp_count {
+            let cel_idz = cel_grp_id * cels_per_grp;
+
+            for i in 0..cels_per_grp {
+                let cel_idx = cel_idz + i;
+
+                if axn_states[(cel_lyr_axn_idz + cel_idx) as usize] != 0 {
+                    let syn_idz = calc_syn_idz_old(tuft_id, cel_grp_count, cel_idx,
+                        syns_per_tft as u32);
+                    let neuromod = neuromods[(cel_idx % neuromod_col_count) as usize];
+                    prx_syns_active_mtp_ltd(syn_states, syn_idz, syns_per_tft as u32, pr_l2i,
+                        dr_l2i, neuromod, rnd as i32, syn_strengths);
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn calc_syn_idz_old(tuft_id: u32, cel_count: u32, cel_id: u32, syns_per_tft: u32) -> u32 {
+    (tuft_id * cel_count * syns_per_tft) + (cel_id * syns_per_tft)
+}
+
+/// Distal tuft medium-term potentiation and depression.
+///
+/// Unlike the device kernel, every slice covers only the range belonging to
+/// a single tuft of a cell layer: `axn_states`, `cel_flag_sets` and the
+/// previous best dendrite slices have one element per cell, and
+/// `syn_flag_sets` and `syn_strengths` begin at synapse `tft_syn_idz` (which
+/// is otherwise used only to derive random seeds, as on the device).
+pub fn tft_dst_mtp(axn_states: &[u8], tft_cel_prev_best_den_ids: &[u8],
+        tft_cel_prev_best_den_states_raw: &[u8], tft_syn_idz: u32,
+        syns_per_den: u32, syns_per_tft: u32, cels_per_cel_grp: u32, cel_grp_count: u32,
+        pr_l2i: i32, dr_l2i: i32, neuromods: &[i8],
+        neuromod_col_count: u32, rnd: i32, syn_flag_sets: &[u8], cel_flag_sets: &mut [u8],
+        syn_strengths: &mut [i8]) {
+    for cel_idx in 0..(cel_grp_count * cels_per_cel_grp) {
+        let cel_axn_idx = cel_idx as usize;
+        let tft_cel_idx = cel_idx as usize;
+        let syn_idz_celtft = (cel_idx * syns_per_tft) + tft_syn_idz;
+
+        let mut cel_flag_set = cel_flag_sets[cel_idx as usize];
+
+        let cel_is_active = axn_states[cel_axn_idx] != 0;
+        let cel_prev_active = (cel_flag_set & CEL_PREV_ACTIVE_FLAG) == CEL_PREV_ACTIVE_FLAG;
+        let cel_newly_active = !cel_prev_active & cel_is_active;
+        let tft_prev_active = tft_cel_prev_best_den_states_raw[tft_cel_idx] != 0;
+
+        if cel_newly_active & tft_prev_active {
+            let prev_best_den_id_celtft = tft_cel_prev_best_den_ids[tft_cel_idx] as u32;
+            let syn_idz_prev_best_den_tft = (prev_best_den_id_celtft * syns_per_den) +
+                syn_idz_celtft;
+
+            let neuromod = neuromods[(cel_idx % neuromod_col_count) as usize];
+            dst_syns_active_mtpot_mtdep(syn_idz_prev_best_den_tft, syns_per_den, pr_l2i, dr_l2i,
+                neuromod, rnd, tft_syn_idz, syn_flag_sets, syn_strengths);
+        }
+
+        cel_flag_set &= !CEL_PREV_ACTIVE_FLAG;
+        cel_flag_set |= (cel_is_active as u8) * CEL_PREV_ACTIVE_FLAG;
+        cel_flag_sets[cel_idx as usize] = cel_flag_set;
+    }
+}
+
+
+/*=============================================================================
+===================================== SOMA ====================================
+=============================================================================*/
+
+/// Sets the spiny stellate cell state, adding energy contributions.
+pub fn ssc_cycle(energies: &mut [u8], cel_states: &mut [u8]) {
+    for (energy, cel_state) in energies.iter_mut().zip(cel_states.iter_mut()) {
+        let state = *cel_state as u32;
+        let is_active = state != 0;
+
+        // If the cell is relatively high in energy and is active, fire:
+        let is_restless = (*energy > 191) & is_active;
+        // If the cell has gone unused, fire:
+        let is_dark = *energy == 255;
+
+        // If cell has fired, reduce energy:
+        if is_dark | is_restless { *energy -= 64; }
+
+        *cel_state = clamp_u8((state + (is_restless as u32 * 255) +
+            (is_dark as u32 * 255)) as i32);
+    }
+}
+
+/// Cycles each pyramidal cell.
+///
+/// [NOTE]: The device kernel assigns `bsl_prx_contrib + bsl_dst_contrib` and
+/// discards the apical distal contribution (a stray comma operator). This is
+/// reproduced here, so `_apc_dst_tft_id` is unused.
+pub fn pyr_cycle(tft_prev_states: &[u8], tft_states: &[u8], enabled_tft_flags: u8,
+        bsl_prx_tft_id: u8, bsl_dst_tft_id: u8, _apc_dst_tft_id: u8, pyr_states: &mut [u8]) {
+    let cel_count = pyr_states.len();
+    let bsl_prx_is_enabled = (enabled_tft_flags & DEN_BASAL_PROXIMAL_FLAG) != 0;
+    let bsl_dst_is_enabled = (enabled_tft_flags & DEN_BASAL_DISTAL_FLAG) != 0;
+    let _apc_dst_is_enabled = (enabled_tft_flags & DEN_APICAL_DISTAL_FLAG) != 0;
+
+    for cel_idx in 0..cel_count {
+        let bsl_prx_state = if bsl_prx_is_enabled {
+            tft_states[(bsl_prx_tft_id as usize * cel_count) + cel_idx]
+        } else {
+            0
+        };
+        let bsl_dst_state = if bsl_dst_is_enabled {
+            tft_prev_states[(bsl_dst_tft_id as usize * cel_count) + cel_idx]
+        } else {
+            0
+        };
+
+        let cel_is_active = bsl_prx_state != 0;
+
+        // Divide by 4 but don't let small values get rounded to 0:
+        let bsl_prx_is_min = (bsl_prx_state <= 3) && cel_is_active;
+        let bsl_prx_contrib = (bsl_prx_state >> 2) + bsl_prx_is_min as u8;
+
+        // Divide by 2, ignore rounding:
+        let bsl_dst_contrib = (cel_is_active as u8) * (bsl_dst_state >> 1);
+
+        pyr_states[cel_idx] = bsl_prx_contrib.wrapping_add(bsl_dst_contrib);
+    }
+}
+
+
+/*=============================================================================
+=================================== CONTROL ===================================
+=============================================================================*/
+
+/// Cell inhibition: reads from soma, writes to axons.
+pub fn inhib_simple(cel_states: &[u8], slc_geo: &AxonSliceGeometry, depth: SlcId, v_size: u32,
+        u_size: u32, cel_base_axn_slc: SlcId, inhib_radius: i32, rnd: i32,
+        activities: &mut [u8], axn_states: &mut [u8]) {
+    let radius_pos = inhib_radius;
+    let radius_neg = 0 - radius_pos;
+    let influence_horizon = radius_pos + INHIB_INFL_HORIZ_OFFSET;
+    let influence_max = influence_horizon * influence_horizon;
+
+    for slc_id_lyr in 0..depth as u32 {
+        for v_id in 0..v_size {
+            for u_id in 0..u_size {
+                let cel_idx = cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id as i32, u_size,
+                    u_id as i32);
+                let (cel_axn_idx, idx_is_safe) = slc_geo.axn_idx_3d_unsafe(
+                    slc_id_lyr as SlcId + cel_base_axn_slc, v_id, 0, u_id, 0);
+
+                let cel_state = if idx_is_safe { cel_states[cel_idx as usize] } else { 0 };
+                let mut uninhibited = true;
+
+                for v_ofs in radius_neg..(radius_pos + 1) {
+                    let v_neg = 0 - v_ofs;
+                    let u_z = ::std::cmp::max(radius_neg, v_neg - radius_pos);
+                    let u_m = ::std::cmp::min(radius_pos, v_neg + radius_pos);
+
+                    for u_ofs in u_z..(u_m + 1) {
+                        let neighbor_state = cel_state_3d_safe(slc_id_lyr, v_size, v_id as i32,
+                            v_ofs, u_size, u_id as i32, u_ofs, cel_states);
+                        let w_ofs = (0 - v_ofs) - u_ofs;
+                        let distance = (v_ofs.abs() + u_ofs.abs() + w_ofs.abs()) >> 1;
+                        let distance_ofs = distance - INHIB_INFL_CENTER_OFFSET;
+
+                        let cel_influence_factor = influence_max;
+                        let nei_influence_factor = influence_max - (distance_ofs * distance_ofs);
+
+                        let cel_influence = cel_state as i32 * cel_influence_factor;
+                        let nei_influence = neighbor_state as i32 * nei_influence_factor;
+
+                        uninhibited &= cel_influence >= nei_influence;
+                    }
+                }
+
+                axn_states[cel_axn_idx as usize] = (uninhibited as u8) * cel_state;
+
+                let axon_is_active = uninhibited & (cel_state != 0);
+                activities[cel_idx as usize] = update_activity_rating(
+                    activities[cel_idx as usize], axon_is_active, rnd,
+                    (cel_state as u32 | cel_idx) as i32, CELL_ACTIVITY_DECAY_FACTOR);
+            }
+        }
+    }
+}
+
+/// Copies cell states directly to axons (inhibition disabled).
+pub fn inhib_passthrough(cel_states: &[u8], slc_geo: &AxonSliceGeometry, depth: SlcId,
+        v_size: u32, u_size: u32, cel_base_axn_slc: SlcId, rnd: i32, activities: &mut [u8],
+        axn_states: &mut [u8]) {
+    for slc_id_lyr in 0..depth as u32 {
+        for v_id in 0..v_size {
+            for u_id in 0..u_size {
+                let cel_idx = cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id as i32, u_size,
+                    u_id as i32);
+                let (cel_axn_idx, _) = slc_geo.axn_idx_3d_unsafe(
+                    slc_id_lyr as SlcId + cel_base_axn_slc, v_id, 0, u_id, 0);
+
+                let cel_state = cel_states[cel_idx as usize];
+                axn_states[cel_axn_idx as usize] = cel_state;
+                activities[cel_idx as usize] = update_activity_rating(
+                    activities[cel_idx as usize], cel_state != 0, rnd,
+                    (cel_state as u32 | cel_idx) as i32, CELL_ACTIVITY_DECAY_FACTOR);
+            }
+        }
+    }
+}
+
+/// Smooths cell activity by manipulating cell energies.
+pub fn smooth_activity(centers_v: &[i32], centers_u: &[i32], v_size: u32, u_size: u32,
+        radius: i32, src_lyr_depth: SlcId, cel_actvs: &[u8], cel_energies: &mut [u8]) {
+    let radius_pos = radius;
+    let radius_neg = 0 - radius_pos;
+
+    for (&center_v, &center_u) in centers_v.iter().zip(centers_u.iter()) {
+        let mut least_active_cel_idx = 0u32;
+        let mut least_active_cel_actv = 255u8;
+        let mut most_active_cel_idx = 0u32;
+        let mut most_active_cel_actv = 0u8;
+
+        for slc_id_lyr in 0..src_lyr_depth as u32 {
+            for v_ofs in radius_neg..(radius_pos + 1) {
+                let v_neg = 0 - v_ofs;
+                let u_z = ::std::cmp::max(radius_neg, v_neg - radius_pos);
+                let u_m = ::std::cmp::min(radius_pos, v_neg + radius_pos);
+
+                for u_ofs in u_z..(u_m + 1) {
+                    let v_id = center_v + v_ofs;
+                    let u_id = center_u + u_ofs;
+
+                    if !(coord_is_safe(v_size as i32, v_id, 0) &&
+                            coord_is_safe(u_size as i32, u_id, 0)) {
+                        continue;
+                    }
+
+                    let cel_idx = cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id, u_size, u_id);
+                    let cel_actv = cel_actvs[cel_idx as usize];
+
+                    if cel_actv <= least_active_cel_actv {
+                        least_active_cel_idx = cel_idx;
+                        least_active_cel_actv = cel_actv;
+                    }
+
+                    if cel_actv >= most_active_cel_actv {
+                        most_active_cel_idx = cel_idx;
+                        most_active_cel_actv = cel_actv;
+                    }
+                }
+            }
+        }
+
+        let distinct = least_active_cel_idx != most_active_cel_idx;
+
+        // Least active (boost energy):
+        let least_active_cel_energy = cel_energies[least_active_cel_idx as usize];
+        cel_energies[least_active_cel_idx as usize] = least_active_cel_energy +
+            ((least_active_cel_energy < 255) & (most_active_cel_actv < 255) & distinct) as u8;
+
+        // Most active (sap energy):
+        let most_active_cel_energy = cel_energies[most_active_cel_idx as usize];
+        cel_energies[most_active_cel_idx as usize] = most_active_cel_energy -
+            ((most_active_cel_energy > 0) & (most_active_cel_actv > 0) & distinct) as u8;
+    }
+}
+
+/// Copies pyramidal cell states to axons.
+pub fn pyr_output(cel_states: &[u8], slc_geo: &AxonSliceGeometry, depth: SlcId, v_size: u32,
+        u_size: u32, cel_base_axn_slc: SlcId, axn_states: &mut [u8]) {
+    for slc_id_lyr in 0..depth as u32 {
+        for v_id in 0..v_size {
+            for u_id in 0..u_size {
+                let cel_idx = cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id as i32, u_size,
+                    u_id as i32);
+                let (cel_axn_idx, _) = slc_geo.axn_idx_3d_unsafe(
+                    slc_id_lyr as SlcId + cel_base_axn_slc, v_id, 0, u_id, 0);
+                axn_states[cel_axn_idx as usize] = cel_states[cel_idx as usize];
+            }
+        }
+    }
+}
+
+/// Inhibits cells which are not the best in their column.
+///
+/// [NOTE]: As on the device, the running best state is reset to zero
+/// whenever a cell is not at least as active as the current best.
+pub fn inhib_intra_column(cel_states: &[u8], slc_geo: &AxonSliceGeometry, depth: SlcId,
+        v_size: u32, u_size: u32, cel_base_axn_slc: SlcId, axn_states: &mut [u8]) {
+    for v_id in 0..v_size {
+        for u_id in 0..u_size {
+            let mut best_cel_state = 0u8;
+
+            for slc_id_lyr in 0..depth as u32 {
+                let cel_idx = cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id as i32, u_size,
+                    u_id as i32);
+                let cel_state = cel_states[cel_idx as usize];
+                best_cel_state = ((cel_state >= best_cel_state) as u8) * cel_state;
+            }
+
+            for slc_id_lyr in 0..depth as u32 {
+                let cel_idx = cel_idx_3d_unsafe(slc_id_lyr, v_size, v_id as i32, u_size,
+                    u_id as i32);
+                let (cel_axn_idx, _) = slc_geo.axn_idx_3d_unsafe(
+                    slc_id_lyr as SlcId + cel_base_axn_slc, v_id, 0, u_id, 0);
+                let cel_state = cel_states[cel_idx as usize];
+                axn_states[cel_axn_idx as usize] = ((cel_state >= best_cel_state) as u8) * cel_state;
+            }
+        }
+    }
+}
+
+
+#[inline]
+fn clamp_u8(val: i32) -> u8 {
+    if val < 0 { 0 } else if val > 255 { 255 } else { val as u8 }
+}
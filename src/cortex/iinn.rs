@@ -1,8 +1,11 @@
-use rand::{Rng, FromEntropy, rngs::SmallRng};
-use cmn::{CmnResult};
+use rand::{Rng, rngs::SmallRng};
+use cmn::{self, CmnResult};
 use map::{AreaMap, LayerAddress, ExecutionGraph, CommandRelations, CorticalBuffer, CellScheme, CommandUid};
-use ocl::{Kernel, ProQue, SpatialDims, Event};
-use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings};
+use ocl::{Kernel, SpatialDims, Event};
+use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings, SnapshotWriter,
+    SnapshotReader, Backend};
+use cortex::host_kernels::{self, HostLayer};
+use cortex::storage::{device_kernel, device_kernel_mut};
 
 /// Basket cells.
 #[derive(Debug)]
@@ -10,10 +13,12 @@ pub struct InhibitoryInterneuronNetwork {
     layer_name: String,
     layer_addr: LayerAddress,
     host_lyr_addr: LayerAddress,
-    kern_inhib_simple: Kernel,
-    kern_inhib_passthrough: Kernel,
+    kern_inhib_simple: Option<Kernel>,
+    kern_inhib_passthrough: Option<Kernel>,
     exe_cmd_uid: CommandUid,
     exe_cmd_idx: usize,
+    inhib_radius: i32,
+    host_lyr: HostLayer,
     rng: SmallRng,
     settings: CorticalAreaSettings,
 }
@@ -22,7 +27,7 @@ impl InhibitoryInterneuronNetwork {
     // FIXME: This function should take a 'bypass' argument instead of `::cycle`.
     pub fn new<S>(layer_name: S, layer_id: usize, scheme: CellScheme,
             host_lyr: &DataCellLayer, axns: &AxonSpace, area_map: &AreaMap,
-            ocl_pq: &ProQue, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
+            backend: &Backend, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
             -> CmnResult<InhibitoryInterneuronNetwork>
             where S: Into<String> {
         let layer_name = layer_name.into();
@@ -37,33 +42,39 @@ impl InhibitoryInterneuronNetwork {
 
         // Simple (active) kernel:
         let kern_inhib_simple_name = "inhib_simple";
-        let kern_inhib_simple = ocl_pq.kernel_builder(kern_inhib_simple_name)
-            .global_work_size(SpatialDims::Three(host_lyr.dims().depth() as usize, host_lyr.dims().v_size() as usize,
-                host_lyr.dims().u_size() as usize))
-            .local_work_size(SpatialDims::Three(1, 8, 8 as usize))
-            .arg(host_lyr.soma())
-            // .arg(host_lyr.energies())
-            .arg(&host_lyr_base_axn_slc)
-            .arg(&inhib_radius)
-            // .arg_named::<i32>("rnd", &0)
-            .arg_named("rnd", &0i32)
-            .arg(host_lyr.activities())
-            // .arg_named("aux_ints_0", None)
-            // .arg_named("aux_ints_1", None)
-            .arg(axns.states())
-            .build()?;
+        let kern_inhib_simple = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_inhib_simple_name)
+                .global_work_size(SpatialDims::Three(host_lyr.dims().depth() as usize, host_lyr.dims().v_size() as usize,
+                    host_lyr.dims().u_size() as usize))
+                .local_work_size(SpatialDims::Three(1, 8, 8 as usize))
+                .arg(host_lyr.soma().device()?)
+                // .arg(host_lyr.energies())
+                .arg(&host_lyr_base_axn_slc)
+                .arg(&inhib_radius)
+                // .arg_named::<i32>("rnd", &0)
+                .arg_named("rnd", &0i32)
+                .arg(host_lyr.activities().device()?)
+                // .arg_named("aux_ints_0", None)
+                // .arg_named("aux_ints_1", None)
+                .arg(axns.states().device()?)
+                .build()?),
+            None => None,
+        };
 
         // Passthrough kernel:
         let kern_inhib_passthrough_name = "inhib_passthrough";
-        let kern_inhib_passthrough = ocl_pq.kernel_builder(kern_inhib_passthrough_name)
-            .global_work_size(SpatialDims::Three(host_lyr.dims().depth() as usize, host_lyr.dims().v_size() as usize,
-                host_lyr.dims().u_size() as usize))
-            .arg(host_lyr.soma())
-            .arg(&host_lyr_base_axn_slc)
-            .arg_named("rnd", &0i32)
-            .arg(host_lyr.activities())
-            .arg(axns.states())
-            .build()?;
+        let kern_inhib_passthrough = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_inhib_passthrough_name)
+                .global_work_size(SpatialDims::Three(host_lyr.dims().depth() as usize, host_lyr.dims().v_size() as usize,
+                    host_lyr.dims().u_size() as usize))
+                .arg(host_lyr.soma().device()?)
+                .arg(&host_lyr_base_axn_slc)
+                .arg_named("rnd", &0i32)
+                .arg(host_lyr.activities().device()?)
+                .arg(axns.states().device()?)
+                .build()?),
+            None => None,
+        };
 
         // let exe_cmd_srcs = (0..host_lyr.tft_count())
         //     .map(|host_lyr_tft_id| CorticalBuffer::data_den_tft(&host_lyr.soma(),
@@ -88,7 +99,10 @@ impl InhibitoryInterneuronNetwork {
             kern_inhib_passthrough: kern_inhib_passthrough,
             exe_cmd_uid,
             exe_cmd_idx: 0,
-            rng: SmallRng::from_entropy(),
+            inhib_radius,
+            host_lyr: HostLayer::new(host_lyr, axns, area_map),
+            rng: cmn::seeded_rng(settings.seed, "iinn", &[layer_addr.area_id() as u64,
+                layer_addr.layer_id() as u64]),
             settings: settings,
         })
     }
@@ -100,19 +114,22 @@ impl InhibitoryInterneuronNetwork {
 
     // FIXME: `::new` should take the `bypass` argument instead.
     pub fn cycle(&mut self, exe_graph: &mut ExecutionGraph, _host_lyr_addr: LayerAddress) -> CmnResult<()> {
+        if self.settings.host_kernels { return self.cycle_host(exe_graph); }
+
         let mut event = Event::empty();
 
         if self.settings.bypass_inhib {
             unsafe {
-                self.kern_inhib_passthrough.cmd()
+                device_kernel(&self.kern_inhib_passthrough)?.cmd()
                 .ewait(exe_graph.get_req_events(self.exe_cmd_idx)?)
                 .enew(&mut event)
                 .enq()?;
             }
         } else {
-            self.kern_inhib_simple.set_arg("rnd", self.rng.gen::<i32>()).unwrap();
+            let kern_inhib_simple = device_kernel_mut(&mut self.kern_inhib_simple)?;
+            kern_inhib_simple.set_arg("rnd", self.rng.gen::<i32>()).unwrap();
             unsafe {
-                self.kern_inhib_simple.cmd()
+                kern_inhib_simple.cmd()
                     .ewait(exe_graph.get_req_events(self.exe_cmd_idx)?)
                     .enew(&mut event)
                     .enq()?;
@@ -124,6 +141,28 @@ impl InhibitoryInterneuronNetwork {
         Ok(())
     }
 
+    /// Cycles using the host kernels.
+    fn cycle_host(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let hl = &self.host_lyr;
+        let cel_states = host_kernels::read(&hl.soma)?;
+        let mut activities = host_kernels::read(&hl.activities)?;
+        let mut axn_states = host_kernels::read(&hl.axn_states)?;
+
+        if self.settings.bypass_inhib {
+            host_kernels::inhib_passthrough(&cel_states, &hl.slc_geo, hl.depth, hl.v_size,
+                hl.u_size, hl.base_axn_slc, 0, &mut activities, &mut axn_states);
+        } else {
+            host_kernels::inhib_simple(&cel_states, &hl.slc_geo, hl.depth, hl.v_size, hl.u_size,
+                hl.base_axn_slc, self.inhib_radius, self.rng.gen::<i32>(), &mut activities,
+                &mut axn_states);
+        }
+
+        host_kernels::write_range(&hl.activities, &activities, 0..activities.len())?;
+        host_kernels::write_range(&hl.axn_states, &axn_states, hl.axn_range.clone())?;
+        exe_graph.set_cmd_event(self.exe_cmd_idx, None)?;
+        Ok(())
+    }
+
     #[inline] pub fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] pub fn layer_addr(&self) -> LayerAddress { self.layer_addr }
 
@@ -149,4 +188,12 @@ impl ControlCellLayer for InhibitoryInterneuronNetwork {
     fn layer_name<'s>(&'s self) -> &'s str { self.layer_name() }
     fn layer_addr(&self) -> LayerAddress { self.layer_addr }
     fn host_layer_addr(&self) -> LayerAddress { self.host_lyr_addr }
+
+    fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_rng("iinn.rng", &self.rng)
+    }
+
+    fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_rng("iinn.rng", &mut self.rng)
+    }
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
 // use rand::Rng;
 use cmn::{CmnResult};
 use map::{AreaMap, LayerAddress, ExecutionGraph, CommandRelations, CorticalBuffer, CellScheme, CommandUid};
-use ocl::{Kernel, ProQue, SpatialDims, Event};
-use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings};
+use ocl::{Kernel, SpatialDims, Event};
+use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings, Backend};
+use cortex::host_kernels::{self, HostLayer};
+use cortex::storage::device_kernel;
 
 
 /// Basket cells.
@@ -11,9 +13,10 @@ pub struct IntraColumnInhib {
     layer_name: String,
     layer_addr: LayerAddress,
     host_lyr_addr: LayerAddress,
-    kern: Kernel,
+    kern: Option<Kernel>,
     exe_cmd_uid: CommandUid,
     exe_cmd_idx: usize,
+    host_lyr: HostLayer,
     // rng: cmn::SmallRng,
     settings: CorticalAreaSettings,
 }
@@ -21,7 +24,7 @@ pub struct IntraColumnInhib {
 impl IntraColumnInhib {
     pub fn new<S>(layer_name: S, layer_id: usize, _scheme: CellScheme,
             host_lyr: &DataCellLayer, axns: &AxonSpace, area_map: &AreaMap,
-            ocl_pq: &ProQue, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
+            backend: &Backend, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
             -> CmnResult<IntraColumnInhib>
             where S: Into<String> {
         let layer_name = layer_name.into();
@@ -34,16 +37,19 @@ impl IntraColumnInhib {
 
         // Simple (active) kernel:
         let kern_name = "inhib_intra_column";
-        let kern = ocl_pq.kernel_builder(kern_name)
-            .global_work_size(SpatialDims::Two(
-                host_lyr.dims().v_size() as usize,
-                host_lyr.dims().u_size() as usize,
-            ))
-            .arg(host_lyr.soma())
-            .arg(&host_lyr.dims().depth())
-            .arg(&host_lyr_base_axn_slc)
-            .arg(axns.states())
-            .build()?;
+        let kern = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name)
+                .global_work_size(SpatialDims::Two(
+                    host_lyr.dims().v_size() as usize,
+                    host_lyr.dims().u_size() as usize,
+                ))
+                .arg(host_lyr.soma().device()?)
+                .arg(&host_lyr.dims().depth())
+                .arg(&host_lyr_base_axn_slc)
+                .arg(axns.states().device()?)
+                .build()?),
+            None => None,
+        };
 
         let exe_cmd_srcs = vec![CorticalBuffer::data_soma_lyr(host_lyr.soma(), host_lyr.layer_addr())];
 
@@ -62,6 +68,7 @@ impl IntraColumnInhib {
             kern: kern,
             exe_cmd_uid,
             exe_cmd_idx: 0,
+            host_lyr: HostLayer::new(host_lyr, axns, area_map),
             // rng: SmallRng::from_entropy(),
             settings: settings,
         })
@@ -73,11 +80,13 @@ impl IntraColumnInhib {
     }
 
     pub fn cycle(&mut self, exe_graph: &mut ExecutionGraph, _host_lyr_addr: LayerAddress) -> CmnResult<()> {
+        if self.settings.host_kernels { return self.cycle_host(exe_graph); }
+
         let mut event = Event::empty();
 
         // self.kern.set_arg("rnd", self.rng.gen::<i32>()).unwrap();
         unsafe {
-            self.kern.cmd()
+            device_kernel(&self.kern)?.cmd()
                 .ewait(exe_graph.get_req_events(self.exe_cmd_idx)?)
                 .enew(&mut event)
                 .enq()?;
@@ -88,6 +97,18 @@ impl IntraColumnInhib {
         Ok(())
     }
 
+    /// Cycles using the host kernels.
+    fn cycle_host(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let hl = &self.host_lyr;
+        let cel_states = host_kernels::read(&hl.soma)?;
+        let mut axn_states = host_kernels::read(&hl.axn_states)?;
+        host_kernels::inhib_intra_column(&cel_states, &hl.slc_geo, hl.depth, hl.v_size,
+            hl.u_size, hl.base_axn_slc, &mut axn_states);
+        host_kernels::write_range(&hl.axn_states, &axn_states, hl.axn_range.clone())?;
+        exe_graph.set_cmd_event(self.exe_cmd_idx, None)?;
+        Ok(())
+    }
+
     #[inline] pub fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
 
 }
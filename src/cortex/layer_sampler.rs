@@ -8,7 +8,7 @@ use map::{axon_idx, AxonBoundError};
 use cortex::{Cell as CellMap, Tuft as TuftMap, Dendrite as DendriteMap, Synapse as SynapseMap};
 use ::{Error as CmnError, Thalamus, CorticalAreas,  SamplerKind, CellSampleIdxs,
     FutureCorticalSamples, CorticalSampler, CorticalSamples, LayerAddress,
-    DataCellLayerMap, SlcId};
+    DataCellLayerMap, SlcId, AnomalyScore};
 
 
 /// Determines start and end indexes for a range and length.
@@ -602,6 +602,7 @@ pub struct CorticalLayerSamples {
     syn_src_col_v_offs: Option<ReadGuard<Vec<i8>>>,
     syn_src_col_u_offs: Option<ReadGuard<Vec<i8>>>,
     syn_flag_sets: Option<ReadGuard<Vec<u8>>>,
+    anomaly: Option<AnomalyScore>,
 }
 
 impl CorticalLayerSamples {
@@ -630,6 +631,9 @@ impl CorticalLayerSamples {
         let syn_src_col_v_offs = samples.take_sample(&SamplerKind::SynSrcColVOffs(map.layer_addr())).map(|s| s.into_i8());
         let syn_src_col_u_offs = samples.take_sample(&SamplerKind::SynSrcColUOffs(map.layer_addr())).map(|s| s.into_i8());
         let syn_flag_sets = samples.take_sample(&SamplerKind::SynFlagSets(map.layer_addr())).map(|s| s.into_u8());
+        let anomaly = samples.take_sample(&SamplerKind::Anomaly(map.layer_addr())).map(|s| {
+            AnomalyScore::from_frame(map.layer_addr(), &s.into_u8()).expect("CorticalLayerSamples::new")
+        });
 
         if let Some(ref vec) = axon_states { assert!(vec.len() >= (map.axon_idz() + map.dims().cells()) as usize); }
         let cell_count = map.dims().cells() as usize;
@@ -684,6 +688,7 @@ impl CorticalLayerSamples {
             syn_src_col_v_offs,
             syn_src_col_u_offs,
             syn_flag_sets,
+            anomaly,
         }
     }
 
@@ -783,6 +788,10 @@ impl CorticalLayerSamples {
         self.syn_flag_sets.as_ref()
     }
 
+    pub fn anomaly(&self) -> Option<&AnomalyScore> {
+        self.anomaly.as_ref()
+    }
+
     /// Returns a cell sample.
     pub fn cell<'l>(&'l self, slc_id_lyr: SlcId, v_id: u32, u_id: u32) -> Cell<'l> {
         Cell { layer: self, map: self.map.cell(slc_id_lyr, v_id, u_id) }
@@ -866,6 +875,11 @@ impl CorticalLayerSampler {
             rx.set_backpressure(bp);
         }
     }
+
+    /// Returns the layer map of the sampled layer.
+    pub fn map(&self) -> &DataCellLayerMap {
+        &self.map
+    }
 }
 
 
@@ -901,6 +915,7 @@ pub struct CorticalLayerSamplerBuilder<'b> {
     syn_src_col_v_offs: bool,
     syn_src_col_u_offs: bool,
     syn_flag_sets: bool,
+    anomaly: bool,
 }
 
 impl<'b> CorticalLayerSamplerBuilder<'b> {
@@ -935,6 +950,7 @@ impl<'b> CorticalLayerSamplerBuilder<'b> {
             syn_src_col_v_offs: false,
             syn_src_col_u_offs: false,
             syn_flag_sets: false,
+            anomaly: false,
         }
     }
 
@@ -1120,6 +1136,13 @@ impl<'b> CorticalLayerSamplerBuilder<'b> {
         self
     }
 
+    /// Includes the layer's anomaly score (enabling anomaly tracking for the
+    /// layer).
+    pub fn anomaly<'a>(&'a mut self) -> &'a mut CorticalLayerSamplerBuilder<'b> {
+        self.anomaly = true;
+        self
+    }
+
     /// Build and return a new `CorticalLayerSampler`.
     pub fn build(&mut self) -> CorticalLayerSampler {
         let layer_addr = self.thal.layer_addr(self.area_name, self.layer_name);
@@ -1150,6 +1173,7 @@ impl<'b> CorticalLayerSamplerBuilder<'b> {
         if self.syn_src_col_v_offs { sampler_kinds.push(SamplerKind::SynSrcColVOffs(layer_addr),) }
         if self.syn_src_col_u_offs { sampler_kinds.push(SamplerKind::SynSrcColUOffs(layer_addr),) }
         if self.syn_flag_sets { sampler_kinds.push(SamplerKind::SynFlagSets(layer_addr),) }
+        if self.anomaly { sampler_kinds.push(SamplerKind::Anomaly(layer_addr),) }
 
         let map = DataCellLayerMap::from_names(self.area_name, self.layer_name, self.thal);
 
@@ -0,0 +1,166 @@
+//! Per-area learning and regrowth parameters.
+//!
+//! Each parameter is passed to the kernels which use it as an argument
+//! (rather than being compiled in) and may therefore be changed between
+//! cycles using `CorticalArea::set_learning_params`.
+//!
+
+use cmn::{self, CmnResult};
+use cortex::{SnapshotWriter, SnapshotReader};
+
+
+/// The largest valid learning rate (`*_rate_l2i`).
+pub const LEARNING_RATE_L2I_MAX: i32 = 24;
+
+
+/// Learning and regrowth parameters for a cortical area.
+///
+/// Defaults are the corresponding constants in `cmn`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LearningParams {
+    /// Synapses at or below this strength are regrown.
+    pub syn_strength_floor: i8,
+    /// The number of cycles over which every synapse is considered for
+    /// regrowth once (when no regrowth chunk size is set).
+    pub syn_regrowth_interval: usize,
+    /// The strength of a newly grown spatial synapse is its distance based
+    /// intensity multiplied by a factor chosen uniformly from
+    /// `[-deviation, deviation]`.
+    pub syn_strength_initial_deviation: i8,
+    /// Added to the strength of every newly grown distal synapse.
+    pub dst_syn_strength_default: i8,
+    /// Added to the strength of every newly grown proximal synapse.
+    pub prx_syn_strength_default: i8,
+    /// The inverse log2 rate at which active distal synapses are potentiated.
+    pub dst_potentiation_rate_l2i: i32,
+    /// The inverse log2 rate at which inactive distal synapses are depressed.
+    pub dst_depression_rate_l2i: i32,
+    /// The inverse log2 rate at which active proximal synapses are
+    /// potentiated.
+    pub prx_potentiation_rate_l2i: i32,
+    /// The inverse log2 rate at which inactive proximal synapses are
+    /// depressed.
+    pub prx_depression_rate_l2i: i32,
+}
+
+impl LearningParams {
+    /// Returns a new parameter block containing the defaults.
+    pub fn new() -> LearningParams {
+        LearningParams {
+            syn_strength_floor: cmn::SYNAPSE_STRENGTH_FLOOR,
+            syn_regrowth_interval: cmn::SYNAPSE_REGROWTH_INTERVAL,
+            syn_strength_initial_deviation: cmn::SYNAPSE_STRENGTH_INITIAL_DEVIATION,
+            dst_syn_strength_default: cmn::DST_SYNAPSE_STRENGTH_DEFAULT,
+            prx_syn_strength_default: cmn::PRX_SYNAPSE_STRENGTH_DEFAULT,
+            dst_potentiation_rate_l2i: cmn::DST_POTENTIATION_RATE_L2I,
+            dst_depression_rate_l2i: cmn::DST_DEPRESSION_RATE_L2I,
+            prx_potentiation_rate_l2i: cmn::PRX_POTENTIATION_RATE_L2I,
+            prx_depression_rate_l2i: cmn::PRX_DEPRESSION_RATE_L2I,
+        }
+    }
+
+    /// Sets the strength at or below which synapses are regrown.
+    pub fn syn_strength_floor(mut self, floor: i8) -> LearningParams {
+        self.syn_strength_floor = floor;
+        self
+    }
+
+    /// Sets the number of cycles over which every synapse is considered for
+    /// regrowth once.
+    pub fn syn_regrowth_interval(mut self, interval: usize) -> LearningParams {
+        self.syn_regrowth_interval = interval;
+        self
+    }
+
+    /// Sets the deviation of the strength factor of newly grown synapses.
+    pub fn syn_strength_initial_deviation(mut self, deviation: i8) -> LearningParams {
+        self.syn_strength_initial_deviation = deviation;
+        self
+    }
+
+    /// Sets the strength added to newly grown distal synapses.
+    pub fn dst_syn_strength_default(mut self, strength: i8) -> LearningParams {
+        self.dst_syn_strength_default = strength;
+        self
+    }
+
+    /// Sets the strength added to newly grown proximal synapses.
+    pub fn prx_syn_strength_default(mut self, strength: i8) -> LearningParams {
+        self.prx_syn_strength_default = strength;
+        self
+    }
+
+    /// Sets the distal potentiation and depression rates.
+    pub fn dst_rates_l2i(mut self, potentiation: i32, depression: i32) -> LearningParams {
+        self.dst_potentiation_rate_l2i = potentiation;
+        self.dst_depression_rate_l2i = depression;
+        self
+    }
+
+    /// Sets the proximal potentiation and depression rates.
+    pub fn prx_rates_l2i(mut self, potentiation: i32, depression: i32) -> LearningParams {
+        self.prx_potentiation_rate_l2i = potentiation;
+        self.prx_depression_rate_l2i = depression;
+        self
+    }
+
+    /// Writes every parameter to a snapshot.
+    pub fn save_state(&self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_vals("learning_params.strengths", &[self.syn_strength_floor,
+            self.syn_strength_initial_deviation, self.dst_syn_strength_default,
+            self.prx_syn_strength_default])?;
+        wtr.write_vals("learning_params.rates_l2i", &[self.dst_potentiation_rate_l2i,
+            self.dst_depression_rate_l2i, self.prx_potentiation_rate_l2i,
+            self.prx_depression_rate_l2i])?;
+        wtr.write_vals("learning_params.syn_regrowth_interval",
+            &[self.syn_regrowth_interval as u64])
+    }
+
+    /// Reads the parameters written by `::save_state`.
+    pub fn load_state(rdr: &mut SnapshotReader) -> CmnResult<LearningParams> {
+        let strengths = rdr.read_vals::<i8>("learning_params.strengths", 4)?;
+        let rates = rdr.read_vals::<i32>("learning_params.rates_l2i", 4)?;
+        let interval = rdr.read_vals::<u64>("learning_params.syn_regrowth_interval", 1)?;
+
+        let params = LearningParams {
+            syn_strength_floor: strengths[0],
+            syn_regrowth_interval: interval[0] as usize,
+            syn_strength_initial_deviation: strengths[1],
+            dst_syn_strength_default: strengths[2],
+            prx_syn_strength_default: strengths[3],
+            dst_potentiation_rate_l2i: rates[0],
+            dst_depression_rate_l2i: rates[1],
+            prx_potentiation_rate_l2i: rates[2],
+            prx_depression_rate_l2i: rates[3],
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Returns an error if any parameter is out of range.
+    pub fn validate(&self) -> CmnResult<()> {
+        if self.syn_regrowth_interval == 0 {
+            return Err("LearningParams::validate: 'syn_regrowth_interval' must be greater \
+                than zero.".into());
+        }
+        if self.syn_strength_initial_deviation < 0 ||
+                self.syn_strength_initial_deviation == ::std::i8::MAX {
+            return Err(format!("LearningParams::validate: 'syn_strength_initial_deviation' \
+                must be within [0, {}] (found: {}).", ::std::i8::MAX - 1,
+                self.syn_strength_initial_deviation).into());
+        }
+        let rates = [self.dst_potentiation_rate_l2i, self.dst_depression_rate_l2i,
+            self.prx_potentiation_rate_l2i, self.prx_depression_rate_l2i];
+        if rates.iter().any(|&l2i| l2i < 0 || l2i > LEARNING_RATE_L2I_MAX) {
+            return Err(format!("LearningParams::validate: Learning rates must be within \
+                [0, {}] (found: {:?}).", LEARNING_RATE_L2I_MAX, rates).into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for LearningParams {
+    fn default() -> LearningParams {
+        LearningParams::new()
+    }
+}
@@ -15,6 +15,14 @@ mod data_cell_layer;
 mod control_cell_layer;
 mod pyr_outputter;
 mod intra_column_inhib;
+mod snapshot;
+mod placement;
+mod anomaly;
+mod learning_params;
+mod neuromodulation;
+mod storage;
+pub mod host_kernels;
+pub mod recorder;
 #[cfg(any(test, feature = "eval"))]
 mod sampler;
 #[cfg(any(test, feature = "eval"))]
@@ -26,7 +34,7 @@ pub use cmn::completion_pool::{CompletionPool, /*CompletionPoolRemote,*/ Complet
 
 pub use self::cortex::{Cortex, CorticalAreas};
 pub use self::cortical_area::{CorticalArea, CorticalAreaSettings, SamplerKind, SamplerBufferKind};
-pub use self::axon_space::AxonSpace;
+pub use self::axon_space::{AxonSpace, IoQueues};
 pub use self::synapses::{Synapses, TuftDims};
 pub use self::iinn::InhibitoryInterneuronNetwork;
 pub use self::smoother::ActivitySmoother;
@@ -39,6 +47,14 @@ pub use self::data_cell_layer::DataCellLayer;
 pub use self::control_cell_layer::{ControlCellLayer, ControlCellLayers};
 pub use self::pyr_outputter::PyrOutputter;
 pub use self::intra_column_inhib::IntraColumnInhib;
+pub use self::snapshot::{SnapshotWriter, SnapshotReader};
+pub use self::placement::{DeviceSelector, Placement, DevicePlacement, DevicePool};
+pub use self::recorder::{Recorder, RecordingReader};
+pub use self::anomaly::{AnomalyScore, AnomalyTracker, LIKELIHOOD_WINDOW, LIKELIHOOD_SHORT_WINDOW,
+    LIKELIHOOD_MIN_SAMPLES};
+pub use self::learning_params::{LearningParams, LEARNING_RATE_L2I_MAX};
+pub use self::neuromodulation::Neuromodulation;
+pub use self::storage::{Storage, Backend};
 #[cfg(any(test, feature = "eval"))]
 pub use self::sampler::{CorticalSampler, FutureCorticalSamples, CorticalSamples, CellSampleIdxs};
 #[cfg(any(test, feature = "eval"))]
@@ -0,0 +1,167 @@
+//! Neuromodulation: a learning signal broadcast to a cortical area.
+//!
+//! Each column of an area has a modulation value within `[-127, 127]` which
+//! scales or gates the potentiation and depression of every learning kernel
+//! (`ssc_mtp`, `tft_dst_mtp`) operating on cells within that column. Each
+//! learning step is taken with a probability of `abs(value) / 127` and is
+//! reversed (potentiation becoming depression and vice versa) when the
+//! value is negative. `NEUROMODULATION_UNITY` (the default) leaves learning
+//! unmodified and zero prevents it entirely.
+//!
+//! Values are typically published by a `SubcorticalNucleus` from within
+//! `::pre_cycle` (as a reward, surprise, or attention signal) using
+//! `CorticalArea::set_neuromodulation` or
+//! `CorticalArea::set_neuromodulation_by_column`. A value remains in effect
+//! until changed.
+//!
+
+use ocl::Event;
+use cmn::{CmnResult, CorticalDims, NEUROMODULATION_UNITY};
+use map::{ExecutionGraph, CommandRelations, CorticalBuffer, CommandUid};
+use cortex::{Backend, Storage};
+
+
+/// Returns an error if `value` is outside of `[-127, 127]`.
+fn check_value(value: i8) -> CmnResult<()> {
+    if value == ::std::i8::MIN {
+        return Err(format!("Neuromodulation: Modulation values must be within [{}, {}] \
+            (found: {}).", -NEUROMODULATION_UNITY, NEUROMODULATION_UNITY, value).into());
+    }
+    Ok(())
+}
+
+
+/// The per-column neuromodulation values of a cortical area.
+///
+/// Changes are staged on the host and written to the buffer once per cycle,
+/// before any learning kernel runs.
+#[derive(Debug)]
+pub struct Neuromodulation {
+    area_id: usize,
+    values: Vec<i8>,
+    staged: Vec<i8>,
+    pending: bool,
+    buffer: Storage<i8>,
+    reader_count: usize,
+    write_cmd_uid: Option<CommandUid>,
+    write_cmd_idx: Option<usize>,
+    write_event: Option<Event>,
+}
+
+impl Neuromodulation {
+    /// Returns a new set of values, one for each column of `dims`, each set
+    /// to `NEUROMODULATION_UNITY`.
+    pub fn new(area_id: usize, dims: &CorticalDims, backend: &Backend) -> CmnResult<Neuromodulation> {
+        let col_count = dims.columns() as usize;
+        let buffer = backend.buffer(col_count, NEUROMODULATION_UNITY)?;
+
+        Ok(Neuromodulation {
+            area_id,
+            values: vec![NEUROMODULATION_UNITY; col_count],
+            staged: vec![NEUROMODULATION_UNITY; col_count],
+            pending: false,
+            buffer,
+            reader_count: 0,
+            write_cmd_uid: None,
+            write_cmd_idx: None,
+            write_event: None,
+        })
+    }
+
+    /// Registers a learning command as a reader of the buffer,
+    /// returning the buffer to add to that command's sources.
+    pub fn register_reader(&mut self) -> CorticalBuffer {
+        self.reader_count += 1;
+        CorticalBuffer::neuromodulation(&self.buffer, self.area_id)
+    }
+
+    /// Adds the command which writes staged values to the buffer. Must be
+    /// called after all readers have been registered.
+    ///
+    /// No command is added if there are no readers (when learning is
+    /// disabled, for example).
+    pub fn add_write_command(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        if self.reader_count > 0 {
+            self.write_cmd_uid = Some(exe_graph.add_command(CommandRelations::cortical_kernel(
+                "neuromodulation_write", Vec::new(),
+                vec![CorticalBuffer::neuromodulation(&self.buffer, self.area_id)]))?);
+        }
+        Ok(())
+    }
+
+    /// Orders the write command within the execution graph.
+    pub fn set_exe_order(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        if let Some(cmd_uid) = self.write_cmd_uid {
+            self.write_cmd_idx = Some(exe_graph.order_command(cmd_uid)?);
+        }
+        Ok(())
+    }
+
+    /// Sets every column to `value`, taking effect on the next cycle.
+    pub fn set(&mut self, value: i8) -> CmnResult<()> {
+        check_value(value)?;
+        for v in self.values.iter_mut() { *v = value; }
+        self.pending = true;
+        Ok(())
+    }
+
+    /// Sets the value of each column, taking effect on the next cycle.
+    pub fn set_by_column(&mut self, values: &[i8]) -> CmnResult<()> {
+        if values.len() != self.values.len() {
+            return Err(format!("Neuromodulation::set_by_column: One value per column is \
+                required (expected: {}, found: {}).", self.values.len(), values.len()).into());
+        }
+        for &value in values { check_value(value)?; }
+        self.values.copy_from_slice(values);
+        self.pending = true;
+        Ok(())
+    }
+
+    /// Enqueues a write of any changed values to the buffer (host storage is
+    /// written immediately).
+    pub fn write(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let cmd_idx = match self.write_cmd_idx {
+            Some(cmd_idx) => cmd_idx,
+            None => return Ok(()),
+        };
+
+        if !self.pending {
+            exe_graph.set_cmd_event(cmd_idx, None)?;
+            return Ok(());
+        }
+
+        if self.buffer.is_host() {
+            self.buffer.write_at(&self.values, 0)?;
+            exe_graph.set_cmd_event(cmd_idx, None)?;
+            self.pending = false;
+            return Ok(());
+        }
+
+        // The staged values may not be altered while a previous write is in
+        // flight:
+        if let Some(ref event) = self.write_event { event.wait_for()?; }
+        self.staged.copy_from_slice(&self.values);
+
+        let mut event = Event::empty();
+        unsafe {
+            self.buffer.device()?.write(&self.staged[..])
+                .block(false)
+                .ewait(exe_graph.get_req_events(cmd_idx)?)
+                .enew(&mut event)
+                .enq()?;
+        }
+        self.write_event = Some(event.clone());
+        exe_graph.set_cmd_event(cmd_idx, Some(event))?;
+        self.pending = false;
+        Ok(())
+    }
+
+    /// Returns the value of each column, including changes not yet written.
+    #[inline] pub fn values(&self) -> &[i8] { &self.values }
+
+    /// Returns the buffer.
+    #[inline] pub fn buffer(&self) -> &Storage<i8> { &self.buffer }
+
+    /// Returns the number of columns (and values).
+    #[inline] pub fn col_count(&self) -> u32 { self.values.len() as u32 }
+}
@@ -0,0 +1,286 @@
+//! Placement of cortical areas onto OpenCL devices.
+//!
+//! Each area is assigned a device using a `DeviceSelector`. Areas placed on
+//! a device of the default type on the default platform share the default
+//! context, which contains only those devices. One further context is created
+//! for each platform on which any other device is used.
+//!
+//! Platforms are enumerated only when a selector requires it. Platforms
+//! which fail to enumerate are skipped by the type and round-robin selectors.
+//!
+//! No device memory is shared between areas: all axon traffic between areas
+//! (and to and from subcortical nuclei) is staged through the host-resident
+//! thalamic tract. Areas on different contexts therefore need no special
+//! treatment beyond keeping their events apart, which each area's
+//! `ExecutionGraph` handles when the cortex spans more than one context (see
+//! `ExecutionGraph::set_context`).
+//!
+
+use std::collections::HashMap;
+use ocl::{self, Platform, Device, Context};
+use ocl::flags::DeviceType;
+use cmn::{CmnError, CmnResult};
+
+
+/// Selects the device a cortical area is placed on.
+#[derive(Debug, Clone)]
+pub enum DeviceSelector {
+    /// The next device of the default device type on the default platform.
+    ///
+    /// Consecutive areas using this selector are spread round-robin across
+    /// those devices.
+    Default,
+    /// A specific device, by index, as ordered by `Platform::list` and
+    /// `Device::list_all`.
+    Index { platform: usize, device: usize },
+    /// The next device of the specified type, cycling across the matching
+    /// devices of every platform.
+    Type(DeviceType),
+    /// The next device, cycling across every device of every platform.
+    RoundRobin,
+}
+
+impl Default for DeviceSelector {
+    fn default() -> DeviceSelector {
+        DeviceSelector::Default
+    }
+}
+
+
+/// Per-area device selectors.
+///
+/// Areas without an explicit selector use the default selector
+/// (`DeviceSelector::Default` unless otherwise specified).
+#[derive(Debug, Clone, Default)]
+pub struct Placement {
+    default: DeviceSelector,
+    areas: HashMap<String, DeviceSelector>,
+}
+
+impl Placement {
+    /// Returns a new placement which places every area using
+    /// `DeviceSelector::Default`.
+    pub fn new() -> Placement {
+        Placement::default()
+    }
+
+    /// Sets the selector used for areas without an explicit selector.
+    pub fn default_selector(mut self, selector: DeviceSelector) -> Placement {
+        self.default = selector;
+        self
+    }
+
+    /// Sets the selector for the area named `area_name`.
+    pub fn area<S: Into<String>>(mut self, area_name: S, selector: DeviceSelector) -> Placement {
+        self.set_area(area_name, selector);
+        self
+    }
+
+    /// Sets the selector for the area named `area_name`.
+    pub fn set_area<S: Into<String>>(&mut self, area_name: S, selector: DeviceSelector) {
+        self.areas.insert(area_name.into(), selector);
+    }
+
+    /// Returns the selector for the area named `area_name`.
+    pub fn selector(&self, area_name: &str) -> &DeviceSelector {
+        self.areas.get(area_name).unwrap_or(&self.default)
+    }
+
+    /// Returns the names of all areas with an explicit selector.
+    pub fn area_names(&self) -> Vec<&str> {
+        self.areas.keys().map(|n| n.as_str()).collect()
+    }
+}
+
+
+/// A device chosen for an area along with the context containing it.
+#[derive(Debug, Clone)]
+pub struct DevicePlacement {
+    platform_idx: usize,
+    device: Device,
+    device_idx: usize,
+    context: Context,
+}
+
+impl DevicePlacement {
+    /// The index of the platform, as ordered by `Platform::list`.
+    #[inline] pub fn platform_idx(&self) -> usize { self.platform_idx }
+    /// The device.
+    #[inline] pub fn device(&self) -> Device { self.device }
+    /// The index of the device within `::context`.
+    #[inline] pub fn device_idx(&self) -> usize { self.device_idx }
+    /// The context shared by all areas placed on this platform.
+    #[inline] pub fn context(&self) -> &Context { &self.context }
+}
+
+
+/// Resolves device selectors into placements, creating contexts as needed.
+#[derive(Debug)]
+pub struct DevicePool {
+    platforms: Vec<Platform>,
+    devices: HashMap<usize, Vec<Device>>,
+    default_platform_idx: usize,
+    default_device_type: DeviceType,
+    default_context: Option<Context>,
+    contexts: HashMap<usize, Context>,
+    next_default: usize,
+    next_by_type: HashMap<u64, usize>,
+    next_any: usize,
+}
+
+impl DevicePool {
+    /// Returns a new pool. Devices are enumerated as needed.
+    pub fn new() -> CmnResult<DevicePool> {
+        let platforms = Platform::list();
+        let default_platform = Platform::new(ocl::core::default_platform()?);
+        let default_platform_idx = platforms.iter().position(|p| *p == default_platform)
+            .ok_or(CmnError::from("DevicePool::new: Default platform not found."))?;
+        let default_device_type = ocl::core::default_device_type()?;
+
+        Ok(DevicePool {
+            platforms,
+            devices: HashMap::new(),
+            default_platform_idx,
+            default_device_type,
+            default_context: None,
+            contexts: HashMap::new(),
+            // Matches the historical behavior of placing the first area on
+            // the second default device (wrapping):
+            next_default: 1,
+            next_by_type: HashMap::new(),
+            next_any: 0,
+        })
+    }
+
+    /// Returns the devices of a platform matching `device_type`.
+    fn devices_of_type(&self, platform_idx: usize, device_type: DeviceType) -> CmnResult<Vec<Device>> {
+        Device::list(&self.platforms[platform_idx], Some(device_type)).map_err(CmnError::from)
+    }
+
+    /// Returns every device of a platform, enumerating them if necessary.
+    fn devices(&mut self, platform_idx: usize) -> CmnResult<Vec<Device>> {
+        if let Some(devices) = self.devices.get(&platform_idx) {
+            return Ok(devices.clone());
+        }
+
+        let devices = Device::list_all(&self.platforms[platform_idx])?;
+        self.devices.insert(platform_idx, devices.clone());
+        Ok(devices)
+    }
+
+    /// Returns the context for a platform, creating it (containing every
+    /// device on that platform) if necessary.
+    fn context(&mut self, platform_idx: usize) -> CmnResult<Context> {
+        if let Some(context) = self.contexts.get(&platform_idx) {
+            return Ok(context.clone());
+        }
+
+        let devices = self.devices(platform_idx)?;
+        let context = Context::builder()
+            .platform(self.platforms[platform_idx])
+            .devices(devices)
+            .build()?;
+        self.contexts.insert(platform_idx, context.clone());
+        Ok(context)
+    }
+
+    /// Resolves a selector into a placement.
+    pub fn place(&mut self, selector: &DeviceSelector) -> CmnResult<DevicePlacement> {
+        let (platform_idx, device) = match *selector {
+            DeviceSelector::Default => {
+                let (pidx, dtype) = (self.default_platform_idx, self.default_device_type);
+                let devices = self.devices_of_type(pidx, dtype)?;
+                if devices.is_empty() {
+                    return Err(format!("DevicePool::place: No devices of the default type \
+                        ({:?}) found on the default platform.", dtype).into());
+                }
+                let device = devices[self.next_default % devices.len()];
+                self.next_default += 1;
+                (pidx, device)
+            },
+            DeviceSelector::Index { platform, device } => {
+                if platform >= self.platforms.len() {
+                    return Err(format!("DevicePool::place: Invalid platform index: {} \
+                        ({} platforms found).", platform, self.platforms.len()).into());
+                }
+                let devices = self.devices(platform)?;
+                let device = devices.get(device).cloned()
+                    .ok_or_else(|| CmnError::new(format!("DevicePool::place: Invalid device \
+                        index: {} (platform: {}, devices found: {}).", device, platform,
+                        devices.len())))?;
+                (platform, device)
+            },
+            DeviceSelector::Type(device_type) => {
+                let mut candidates = Vec::new();
+                for pidx in 0..self.platforms.len() {
+                    if let Ok(devices) = self.devices_of_type(pidx, device_type) {
+                        for device in devices {
+                            candidates.push((pidx, device));
+                        }
+                    }
+                }
+                if candidates.is_empty() {
+                    return Err(format!("DevicePool::place: No devices of type {:?} found.",
+                        device_type).into());
+                }
+                let next = self.next_by_type.entry(device_type.bits()).or_insert(0);
+                let candidate = candidates[*next % candidates.len()];
+                *next += 1;
+                candidate
+            },
+            DeviceSelector::RoundRobin => {
+                let mut candidates = Vec::new();
+                for pidx in 0..self.platforms.len() {
+                    if let Ok(devices) = self.devices(pidx) {
+                        for device in devices {
+                            candidates.push((pidx, device));
+                        }
+                    }
+                }
+                if candidates.is_empty() {
+                    return Err("DevicePool::place: No devices found.".into());
+                }
+                let candidate = candidates[self.next_any % candidates.len()];
+                self.next_any += 1;
+                candidate
+            },
+        };
+
+        // Devices of the default type on the default platform use the
+        // default context:
+        let default_context = if platform_idx == self.default_platform_idx {
+            Some(self.default_context()?)
+        } else {
+            None
+        };
+        let context = match default_context {
+            Some(ref context) if context.devices().contains(&device) => context.clone(),
+            _ => self.context(platform_idx)?,
+        };
+        let device_idx = context.devices().iter().position(|&d| d == device)
+            .ok_or(CmnError::from("DevicePool::place: Device not found within context."))?;
+
+        Ok(DevicePlacement { platform_idx, device, device_idx, context })
+    }
+
+    /// Returns the context containing the devices of the default type on
+    /// the default platform, creating it if necessary.
+    pub fn default_context(&mut self) -> CmnResult<Context> {
+        if let Some(ref context) = self.default_context {
+            return Ok(context.clone());
+        }
+
+        let (pidx, dtype) = (self.default_platform_idx, self.default_device_type);
+        let context = Context::builder()
+            .platform(self.platforms[pidx])
+            .devices(self.devices_of_type(pidx, dtype)?)
+            .build()?;
+        self.default_context = Some(context.clone());
+        Ok(context)
+    }
+
+    /// Returns the number of contexts created so far.
+    pub fn context_count(&self) -> usize {
+        self.contexts.len() + self.default_context.iter().count()
+    }
+}
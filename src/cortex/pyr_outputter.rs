@@ -1,7 +1,9 @@
 use cmn::{CmnResult};
 use map::{AreaMap, LayerAddress, ExecutionGraph, CommandRelations, CorticalBuffer, CellScheme, CommandUid};
-use ocl::{Kernel, ProQue, SpatialDims, /*Buffer,*/ Event, /*MemFlags*/};
-use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings};
+use ocl::{Kernel, SpatialDims, /*Buffer,*/ Event, /*MemFlags*/};
+use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings, Backend};
+use cortex::host_kernels::{self, HostLayer};
+use cortex::storage::device_kernel;
 
 
 #[derive(Debug)]
@@ -9,16 +11,17 @@ pub struct PyrOutputter {
     layer_name: String,
     layer_addr: LayerAddress,
     host_lyr_addr: LayerAddress,
-    kern: Kernel,
+    kern: Option<Kernel>,
     exe_cmd_uid: CommandUid,
     exe_cmd_idx: usize,
+    host_lyr: HostLayer,
     settings: CorticalAreaSettings,
 }
 
 impl PyrOutputter {
     pub fn new<S>(layer_name: S, layer_id: usize, _scheme: CellScheme,
             host_lyr: &DataCellLayer, axns: &AxonSpace, area_map: &AreaMap,
-            ocl_pq: &ProQue, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
+            backend: &Backend, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
             -> CmnResult<PyrOutputter>
             where S: Into<String> {
         let layer_name = layer_name.into();
@@ -28,20 +31,23 @@ impl PyrOutputter {
 
         // Kernel:
         let kern_name = "pyr_output";
-        let kern = ocl_pq.kernel_builder(kern_name)
-            .global_work_size(SpatialDims::Three(
-                host_lyr.dims().depth() as usize,
-                host_lyr.dims().v_size() as usize,
-                host_lyr.dims().u_size() as usize,
-            ))
-            // .arg(host_lyr.dims().v_size())
-            // .arg(host_lyr.dims().u_size())
-            .arg(host_lyr.soma())
-            .arg(&host_lyr_base_axn_slc)
-            // .arg_named("aux_ints_0", None)
-            // .arg_named("aux_ints_1", None)
-            .arg(axns.states())
-            .build()?;
+        let kern = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name)
+                .global_work_size(SpatialDims::Three(
+                    host_lyr.dims().depth() as usize,
+                    host_lyr.dims().v_size() as usize,
+                    host_lyr.dims().u_size() as usize,
+                ))
+                // .arg(host_lyr.dims().v_size())
+                // .arg(host_lyr.dims().u_size())
+                .arg(host_lyr.soma().device()?)
+                .arg(&host_lyr_base_axn_slc)
+                // .arg_named("aux_ints_0", None)
+                // .arg_named("aux_ints_1", None)
+                .arg(axns.states().device()?)
+                .build()?),
+            None => None,
+        };
 
         let exe_cmd_srcs = vec![CorticalBuffer::data_soma_lyr(host_lyr.soma(), host_lyr.layer_addr())];
 
@@ -61,6 +67,7 @@ impl PyrOutputter {
             kern,
             exe_cmd_uid,
             exe_cmd_idx: 0,
+            host_lyr: HostLayer::new(host_lyr, axns, area_map),
             settings: settings,
         })
     }
@@ -71,9 +78,11 @@ impl PyrOutputter {
     }
 
     pub fn cycle(&mut self, exe_graph: &mut ExecutionGraph, _host_lyr_addr: LayerAddress) -> CmnResult<()> {
+        if self.settings.host_kernels { return self.cycle_host(exe_graph); }
+
         let mut event = Event::empty();
         unsafe {
-            self.kern.cmd()
+            device_kernel(&self.kern)?.cmd()
                 .ewait(exe_graph.get_req_events(self.exe_cmd_idx)?)
                 .enew(&mut event)
                 .enq()?;
@@ -82,6 +91,18 @@ impl PyrOutputter {
         Ok(())
     }
 
+    /// Cycles using the host kernels.
+    fn cycle_host(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let hl = &self.host_lyr;
+        let cel_states = host_kernels::read(&hl.soma)?;
+        let mut axn_states = host_kernels::read(&hl.axn_states)?;
+        host_kernels::pyr_output(&cel_states, &hl.slc_geo, hl.depth, hl.v_size, hl.u_size,
+            hl.base_axn_slc, &mut axn_states);
+        host_kernels::write_range(&hl.axn_states, &axn_states, hl.axn_range.clone())?;
+        exe_graph.set_cmd_event(self.exe_cmd_idx, None)?;
+        Ok(())
+    }
+
     #[inline] pub fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] pub fn layer_addr(&self) -> LayerAddress { self.layer_addr }
 
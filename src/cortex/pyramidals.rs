@@ -1,12 +1,14 @@
-use rand::{FromEntropy, rngs::SmallRng};
+use rand::rngs::SmallRng;
 use cmn::{self, CmnResult, CorticalDims, DEN_BASAL_PROXIMAL_FLAG,
     DEN_BASAL_DISTAL_FLAG, DEN_APICAL_DISTAL_FLAG};
-use ocl::{ProQue, SpatialDims, Buffer, Kernel, Result as OclResult, Event};
+use ocl::{SpatialDims, Buffer, Kernel, Result as OclResult, Event};
 use ocl::traits::OclPrm;
 use map::{AreaMap, CellScheme, ExecutionGraph, CommandRelations,
     CorticalBuffer, LayerAddress, LayerTags, CommandUid, DendriteClass, DendriteKind};
 use cortex::{Dendrites, AxonSpace, CorticalAreaSettings, DataCellLayer, ControlCellLayers,
-    Tufts};
+    Tufts, SnapshotWriter, SnapshotReader, LearningParams, Neuromodulation, Backend, Storage};
+use cortex::host_kernels;
+use cortex::storage::device_kernel;
 
 const PRNT: bool = false;
 
@@ -19,17 +21,21 @@ pub struct PyramidalLayer {
     dims: CorticalDims,
     tft_count: usize,
     cell_scheme: CellScheme,
-    pyr_cycle_kernel: Kernel,
+    pyr_cycle_kernel: Option<Kernel>,
+    enabled_tft_flags: u8,
+    bsl_prx_tft_id: u8,
+    bsl_dst_tft_id: u8,
+    apc_dst_tft_id: u8,
     axon_slc_ids: Vec<u8>,
     pyr_lyr_axon_idz: u32,
     rng: SmallRng,
 
-    states: Buffer<u8>,
+    states: Storage<u8>,
     // TODO: Remove:
     // best_den_states_raw: Buffer<u8>,
-    flag_sets: Buffer<u8>,
-    energies: Buffer<u8>,
-    activities: Buffer<u8>,
+    flag_sets: Storage<u8>,
+    energies: Storage<u8>,
+    activities: Storage<u8>,
 
     tufts: Tufts,
 
@@ -47,8 +53,9 @@ impl PyramidalLayer {
             cell_scheme: CellScheme,
             area_map: &AreaMap,
             axons: &AxonSpace,
-            ocl_pq: &ProQue,
+            backend: &Backend,
             settings: CorticalAreaSettings,
+            neuromod: &mut Neuromodulation,
             exe_graph: &mut ExecutionGraph)
             -> CmnResult<PyramidalLayer> {
         let layer_name = layer_name.into();
@@ -63,11 +70,11 @@ impl PyramidalLayer {
         let cel_count = dims.to_len();
         let celtft_count = cel_count * tft_count;
 
-        let states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([cel_count]).fill_val(0).build()?;
+        let states = backend.buffer::<u8>(cel_count, 0)?;
         // let best_den_states_raw = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([cel_count]).fill_val(0).build()?;
-        let flag_sets = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([cel_count]).fill_val(0).build()?;
-        let energies = Buffer::builder().queue(ocl_pq.queue().clone()).len(cel_count).fill_val(0).build()?;
-        let activities = Buffer::builder().queue(ocl_pq.queue().clone()).len(cel_count).fill_val(0).build()?;
+        let flag_sets = backend.buffer::<u8>(cel_count, 0)?;
+        let energies = backend.buffer::<u8>(cel_count, 0)?;
+        let activities = backend.buffer::<u8>(cel_count, 0)?;
 
         println!("{mt}{mt}PYRAMIDALS::NEW(): \
             layer: '{}', base_axon_slc: {}, pyr_lyr_axon_idz: {}, tft_count: {}, \
@@ -77,7 +84,7 @@ impl PyramidalLayer {
 
         let tufts = Tufts::new(layer_name.clone(), layer_addr, dims, cell_scheme.clone(),
             area_map, axons, &axon_slc_ids, pyr_lyr_axon_idz, &states,
-            &flag_sets, ocl_pq, settings.clone(), exe_graph)?;
+            &flag_sets, backend, settings.clone(), neuromod, exe_graph)?;
 
         let mut enabled_tft_flags = 0u8;
         let mut enabled_tft_ttl = 0;
@@ -146,23 +153,25 @@ impl PyramidalLayer {
         // ;
 
         let kern_name = "pyr_cycle";
-        let pyr_cycle_kernel = ocl_pq.kernel_builder(kern_name)
-            .global_work_size(SpatialDims::One(cel_count))
-            // .arg(tufts.best_den_ids())
-            .arg(tufts.best_den_states_raw())
-            // .arg(tufts.best_den_states())
-            .arg(tufts.states())
-            .arg(&(tft_count as u8))
-            .arg(&enabled_tft_flags)
-            .arg(&bsl_prx_tft_id.unwrap_or(0))
-            .arg(&bsl_dst_tft_id.unwrap_or(0))
-            .arg(&apc_dst_tft_id.unwrap_or(0))
-            // .arg(&best_den_states_raw)
-            .arg_named("aux_ints_0", None::<&Buffer<i32>>)
-            .arg_named("aux_ints_1", None::<&Buffer<i32>>)
-            .arg(&states)
-            .build()?
-        ;
+        let pyr_cycle_kernel = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name)
+                .global_work_size(SpatialDims::One(cel_count))
+                // .arg(tufts.best_den_ids())
+                .arg(tufts.best_den_states_raw().device()?)
+                // .arg(tufts.best_den_states())
+                .arg(tufts.states().device()?)
+                .arg(&(tft_count as u8))
+                .arg(&enabled_tft_flags)
+                .arg(&bsl_prx_tft_id.unwrap_or(0))
+                .arg(&bsl_dst_tft_id.unwrap_or(0))
+                .arg(&apc_dst_tft_id.unwrap_or(0))
+                // .arg(&best_den_states_raw)
+                .arg_named("aux_ints_0", None::<&Buffer<i32>>)
+                .arg_named("aux_ints_1", None::<&Buffer<i32>>)
+                .arg(states.device()?)
+                .build()?),
+            None => None,
+        };
 
         let mut cycle_cmd_srcs: Vec<CorticalBuffer> = Vec::with_capacity(3 * tft_count);
 
@@ -194,9 +203,14 @@ impl PyramidalLayer {
             tft_count: tft_count,
             cell_scheme: cell_scheme,
             pyr_cycle_kernel: pyr_cycle_kernel,
+            enabled_tft_flags,
+            bsl_prx_tft_id: bsl_prx_tft_id.unwrap_or(0),
+            bsl_dst_tft_id: bsl_dst_tft_id.unwrap_or(0),
+            apc_dst_tft_id: apc_dst_tft_id.unwrap_or(0),
             axon_slc_ids: axon_slc_ids,
             pyr_lyr_axon_idz: pyr_lyr_axon_idz,
-            rng: SmallRng::from_entropy(),
+            rng: cmn::seeded_rng(settings.seed, "pyramidals", &[layer_addr.area_id() as u64,
+                layer_addr.layer_id() as u64]),
             states: states,
             // best_den_states_raw: best_den_states_raw,
             flag_sets: flag_sets,
@@ -262,18 +276,33 @@ impl PyramidalLayer {
         self.tufts.set_arg(name, env, using_aux_cycle, using_aux_learning)?;
 
         if using_aux_cycle {
-            self.pyr_cycle_kernel.set_arg(name, Some(env))?;
+            if let Some(ref mut pyr_cycle_kernel) = self.pyr_cycle_kernel {
+                pyr_cycle_kernel.set_arg(name, Some(env))?;
+            }
         }
 
         Ok(())
     }
 
+    /// Enables or disables learning without altering the execution graph.
+    #[inline] pub fn set_learning_enabled(&mut self, enabled: bool) {
+        self.tufts.set_learning_enabled(enabled);
+    }
+
+    /// Sets the learning rates and regrowth parameters used from the next
+    /// cycle onward.
+    pub fn set_learning_params(&mut self, params: &LearningParams) -> CmnResult<()> {
+        self.tufts.set_learning_params(params)?;
+        self.settings.learning_params = *params;
+        Ok(())
+    }
+
     #[inline] pub fn layer_id(&self) -> usize { self.layer_addr.layer_id() }
     #[inline] pub fn layer_addr(&self) -> LayerAddress { self.layer_addr }
     #[inline] pub fn layer_tags(&self) -> LayerTags { self.layer_tags }
-    #[inline] pub fn states(&self) -> &Buffer<u8> { &self.states }
+    #[inline] pub fn states(&self) -> &Storage<u8> { &self.states }
     // #[inline] pub fn best_den_states_raw(&self) -> &Buffer<u8> { &self.best_den_states_raw }
-    #[inline] pub fn flag_sets(&self) -> &Buffer<u8> { &self.flag_sets }
+    #[inline] pub fn flag_sets(&self) -> &Storage<u8> { &self.flag_sets }
     #[inline] pub fn tufts(&self) -> &Tufts { &self.tufts }
 }
 
@@ -286,11 +315,11 @@ impl DataCellLayer for PyramidalLayer {
         self.tufts.learn(exe_graph)
     }
 
-    /// Prunes and regrows synapses.
+    /// Queues the next chunk of synapses for pruning and regrowth.
     #[inline]
-    fn regrow(&mut self) {
+    fn regrow(&mut self, chunk_size: Option<usize>, exe_graph: &ExecutionGraph) -> CmnResult<()> {
         if PRNT { printlnc!(yellow: "Pyrs: Regrowing dens..."); }
-        self.dens_mut().regrow();
+        self.dens_mut().regrow(chunk_size, exe_graph)
     }
 
     /// Enqueues cycle kernels.
@@ -303,7 +332,7 @@ impl DataCellLayer for PyramidalLayer {
         }
 
         // [DEBUG]: TEMPORARY:
-        if PRNT { self.pyr_cycle_kernel.default_queue().unwrap().finish().unwrap(); }
+        if PRNT { self.states.finish()?; }
 
         self.tufts.cycle(control_layers, exe_graph)?;
 
@@ -311,12 +340,23 @@ impl DataCellLayer for PyramidalLayer {
 
         // Soma:
         if let Some(cycle_cmd_idx) = self.cycle_exe_cmd_idx {
-            let mut event = Event::empty();
-            unsafe {
-                self.pyr_cycle_kernel.cmd().ewait(exe_graph.get_req_events(cycle_cmd_idx)?)
-                    .enew(&mut event).enq()?;
+            if self.settings.host_kernels {
+                let tft_prev_states = host_kernels::read(self.tufts.best_den_states_raw())?;
+                let tft_states = host_kernels::read(self.tufts.states())?;
+                let mut states = host_kernels::read(&self.states)?;
+                host_kernels::pyr_cycle(&tft_prev_states, &tft_states, self.enabled_tft_flags,
+                    self.bsl_prx_tft_id, self.bsl_dst_tft_id, self.apc_dst_tft_id, &mut states);
+                host_kernels::write_range(&self.states, &states, 0..states.len())?;
+                exe_graph.set_cmd_event(cycle_cmd_idx, None)?;
+            } else {
+                let mut event = Event::empty();
+                unsafe {
+                    device_kernel(&self.pyr_cycle_kernel)?.cmd()
+                        .ewait(exe_graph.get_req_events(cycle_cmd_idx)?)
+                        .enew(&mut event).enq()?;
+                }
+                exe_graph.set_cmd_event(cycle_cmd_idx, Some(event))?;
             }
-            exe_graph.set_cmd_event(cycle_cmd_idx, Some(event))?;
         }
 
         // Control Post:
@@ -328,7 +368,7 @@ impl DataCellLayer for PyramidalLayer {
         self.learn(exe_graph)?;
 
         // [DEBUG]: TEMPORARY:
-        if PRNT { self.pyr_cycle_kernel.default_queue().unwrap().finish().unwrap(); }
+        if PRNT { self.states.finish()?; }
         if PRNT { printlnc!(yellow: "Pyrs: Cycling complete for layer: '{}'.", self.layer_name); }
 
         Ok(())
@@ -342,11 +382,11 @@ impl DataCellLayer for PyramidalLayer {
 
     #[inline] fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] fn layer_addr(&self) -> LayerAddress{ self.layer_addr }
-    #[inline] fn soma(&self) -> &Buffer<u8> { &self.states }
-    #[inline] fn soma_mut(&mut self) -> &mut Buffer<u8> { &mut self.states }
-    #[inline] fn energies(&self) -> &Buffer<u8> { &self.energies }
-    #[inline] fn activities(&self) -> &Buffer<u8> { &self.activities }
-    #[inline] fn flag_sets(&self) -> &Buffer<u8> { &self.flag_sets }
+    #[inline] fn soma(&self) -> &Storage<u8> { &self.states }
+    #[inline] fn soma_mut(&mut self) -> &mut Storage<u8> { &mut self.states }
+    #[inline] fn energies(&self) -> &Storage<u8> { &self.energies }
+    #[inline] fn activities(&self) -> &Storage<u8> { &self.activities }
+    #[inline] fn flag_sets(&self) -> &Storage<u8> { &self.flag_sets }
     #[inline] fn dims(&self) -> &CorticalDims { &self.dims }
     #[inline] fn axon_slc_ids(&self) -> &[u8] { self.axon_slc_ids.as_slice() }
     #[inline] fn base_axon_slc(&self) -> u8 { self.axon_slc_ids[0] }
@@ -355,6 +395,24 @@ impl DataCellLayer for PyramidalLayer {
     #[inline] fn tufts(&self) -> &Tufts { &self.tufts }
     #[inline] fn dens(&self) -> &Dendrites { self.tufts.dens() }
     #[inline] fn dens_mut(&mut self) -> &mut Dendrites { self.tufts.dens_mut() }
+
+    fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_buffer("pyrs.states", &self.states)?;
+        wtr.write_buffer("pyrs.flag_sets", &self.flag_sets)?;
+        wtr.write_buffer("pyrs.energies", &self.energies)?;
+        wtr.write_buffer("pyrs.activities", &self.activities)?;
+        wtr.write_rng("pyrs.rng", &self.rng)?;
+        self.tufts.save_state(wtr)
+    }
+
+    fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_buffer("pyrs.states", &self.states)?;
+        rdr.read_buffer("pyrs.flag_sets", &self.flag_sets)?;
+        rdr.read_buffer("pyrs.energies", &self.energies)?;
+        rdr.read_buffer("pyrs.activities", &self.activities)?;
+        rdr.read_rng("pyrs.rng", &mut self.rng)?;
+        self.tufts.load_state(rdr)
+    }
 }
 
 
@@ -367,10 +425,13 @@ pub mod tests {
     use ocl::util;
     use cmn::{self};
     use cortex::{PyramidalLayer, DataCellLayer, DataCellLayerTest, CelCoords};
+    use cortex::storage::device_kernel;
 
     impl DataCellLayerTest for PyramidalLayer {
         fn cycle_solo(&self) {
-            self.pyr_cycle_kernel.default_queue().unwrap().finish().unwrap();
+            let pyr_cycle_kernel = device_kernel(&self.pyr_cycle_kernel)
+                .expect("PyramidalLayer::cycle_self_only: pyr_cycle_kernel");
+            pyr_cycle_kernel.default_queue().unwrap().finish().unwrap();
 
             // for cycle_kern in self.tft_cycle_kernels.iter() {
             //     cycle_kern.default_queue().unwrap().finish().unwrap();
@@ -381,11 +442,11 @@ pub mod tests {
             self.tufts.cycle_solo();
 
             unsafe {
-                self.pyr_cycle_kernel.cmd().enq()
+                pyr_cycle_kernel.cmd().enq()
                     .expect("PyramidalLayer::cycle_self_only: pyr_cycle_kernel");
             }
 
-            self.pyr_cycle_kernel.default_queue().unwrap().finish().unwrap();
+            pyr_cycle_kernel.default_queue().unwrap().finish().unwrap();
         }
 
         fn learn_solo(&mut self) {
@@ -481,27 +542,27 @@ pub mod tests {
         }
 
         fn set_all_to_zero(&mut self) { // MOVE TO TEST TRAIT IMPL
-            self.states.default_queue().unwrap().finish().unwrap();
-            self.flag_sets.default_queue().unwrap().finish().unwrap();
-            self.tufts.best_den_ids().default_queue().unwrap().finish().unwrap();
-            self.tufts.best_den_states().default_queue().unwrap().finish().unwrap();
-            self.tufts.best_den_states_raw().default_queue().unwrap().finish().unwrap();
-
-            self.states.cmd().fill(0, None).enq().unwrap();
-            self.flag_sets.cmd().fill(0, None).enq().unwrap();
-            self.tufts.best_den_ids().cmd().fill(0, None).enq().unwrap();
-            self.tufts.best_den_states().cmd().fill(0, None).enq().unwrap();
-            self.tufts.best_den_states_raw().cmd().fill(0, None).enq().unwrap();
+            self.states.finish().unwrap();
+            self.flag_sets.finish().unwrap();
+            self.tufts.best_den_ids().finish().unwrap();
+            self.tufts.best_den_states().finish().unwrap();
+            self.tufts.best_den_states_raw().finish().unwrap();
+
+            self.states.fill(0).unwrap();
+            self.flag_sets.fill(0).unwrap();
+            self.tufts.best_den_ids().fill(0).unwrap();
+            self.tufts.best_den_states().fill(0).unwrap();
+            self.tufts.best_den_states_raw().fill(0).unwrap();
             //self.best2_den_ids.cmd().fill(&[0], None).enq().unwrap();            // <<<<< SLATED FOR REMOVAL
             //self.best2_den_states.cmd().fill(&[0], None).enq().unwrap();        // <<<<< SLATED FOR REMOVAL
 
             // self.energies.cmd().fill(&[0], None).enq().unwrap();                // <<<<< SLATED FOR REMOVAL
 
-            self.states.default_queue().unwrap().finish().unwrap();
-            self.flag_sets.default_queue().unwrap().finish().unwrap();
-            self.tufts.best_den_ids().default_queue().unwrap().finish().unwrap();
-            self.tufts.best_den_states().default_queue().unwrap().finish().unwrap();
-            self.tufts.best_den_states_raw().default_queue().unwrap().finish().unwrap();
+            self.states.finish().unwrap();
+            self.flag_sets.finish().unwrap();
+            self.tufts.best_den_ids().finish().unwrap();
+            self.tufts.best_den_states().finish().unwrap();
+            self.tufts.best_den_states_raw().finish().unwrap();
         }
 
 
@@ -0,0 +1,660 @@
+//! Recording of sampler streams to disk for offline analysis.
+//!
+//! A recording file begins with a header (magic bytes, format version,
+//! compression, and chunk size) followed by a description of each stream:
+//! area name, sampler kind code (see `SamplerKind::code`), sampled layer
+//! name (if any), element signedness, area dimensions, the `SliceTractMap`
+//! of the sampled slices, and the name and output axon tags (by name, see
+//! `AxonTag::name`) of each output layer among those slices.
+//!
+//! The remainder of the file is a sequence of chunks, each containing the
+//! frames of up to `chunk_cycles` consecutive cycles:
+//!
+//! * chunk: `first_cycle: u64`, `cycle_count: u32`, `byte_len: u64`, frames
+//! * frame: `raw_len: u32`, `enc_len: u32`, `enc_len` bytes
+//!
+//! with one frame per stream per cycle, in stream order. The chunk byte
+//! length allows a reader to skip ahead without decoding intermediate chunks.
+//!
+//! `Compression::ZeroRuns` encodes each frame as alternating runs of zeros
+//! and literal bytes, which suits the sparse activity of most layers.
+//!
+//! All integers are stored little-endian.
+//!
+
+use std::fs::File;
+use std::io::{self, Read, Write, Seek, SeekFrom, BufReader, BufWriter};
+use std::path::Path;
+use futures::executor;
+use cmn::{CmnError, CmnResult, CorticalDims};
+use map::{SliceTractMap, AxonDomain, AxonTag, AxonTags};
+use subcortex::{TractReceiver, FutureReadGuardVec, ReadGuardVec};
+use cortex::{Cortex, CorticalArea, SamplerKind, SamplerBufferKind};
+
+
+static RECORDING_MAGIC: &'static [u8; 8] = b"BSMTRCRD";
+const RECORDING_VERSION: u32 = 2;
+const DEFAULT_CHUNK_CYCLES: u32 = 64;
+
+
+fn put_u32(buf: &mut Vec<u8>, val: u32) {
+    buf.extend_from_slice(&[val as u8, (val >> 8) as u8, (val >> 16) as u8, (val >> 24) as u8]);
+}
+
+fn put_u64(buf: &mut Vec<u8>, val: u64) {
+    put_u32(buf, val as u32);
+    put_u32(buf, (val >> 32) as u32);
+}
+
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    put_u32(buf, s.len() as u32);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn get_u8<R: Read>(rdr: &mut R) -> CmnResult<u8> {
+    let mut byte = [0u8; 1];
+    rdr.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn get_u32<R: Read>(rdr: &mut R) -> CmnResult<u32> {
+    let mut bytes = [0u8; 4];
+    rdr.read_exact(&mut bytes)?;
+    Ok((bytes[0] as u32) | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 |
+        (bytes[3] as u32) << 24)
+}
+
+fn get_u64<R: Read>(rdr: &mut R) -> CmnResult<u64> {
+    let lo = get_u32(rdr)? as u64;
+    let hi = get_u32(rdr)? as u64;
+    Ok(lo | (hi << 32))
+}
+
+fn get_str<R: Read>(rdr: &mut R) -> CmnResult<String> {
+    let len = get_u32(rdr)? as usize;
+    let mut bytes = vec![0u8; len];
+    rdr.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|err| format!("RecordingReader: {}", err).into())
+}
+
+/// Reads a `u64` or returns `None` if the reader is at the end of the file.
+fn get_u64_or_eof<R: Read>(rdr: &mut R) -> CmnResult<Option<u64>> {
+    let mut bytes = [0u8; 8];
+    let mut filled = 0;
+    while filled < bytes.len() {
+        match rdr.read(&mut bytes[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err("RecordingReader: Truncated chunk header.".into()),
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(Some(bytes.iter().enumerate().fold(0u64, |val, (i, &b)| val | (b as u64) << (i * 8))))
+}
+
+
+/// Appends `val` to `buf` as an LEB128 varint.
+fn put_varint(buf: &mut Vec<u8>, mut val: usize) {
+    while val >= 0x80 {
+        buf.push((val as u8) | 0x80);
+        val >>= 7;
+    }
+    buf.push(val as u8);
+}
+
+/// Reads an LEB128 varint from `bytes` starting at `*pos`.
+fn get_varint(bytes: &[u8], pos: &mut usize) -> CmnResult<usize> {
+    let mut val = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or(CmnError::from("ZeroRuns: Truncated frame."))?;
+        *pos += 1;
+        val |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 { return Ok(val); }
+        shift += 7;
+        if shift >= 64 { return Err("ZeroRuns: Invalid varint.".into()); }
+    }
+}
+
+
+/// Frame compression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// Alternating (zero run length, literal run length, literals) triplets.
+    ZeroRuns,
+}
+
+impl Compression {
+    fn to_u8(&self) -> u8 {
+        match *self {
+            Compression::None => 0,
+            Compression::ZeroRuns => 1,
+        }
+    }
+
+    fn from_u8(val: u8) -> CmnResult<Compression> {
+        match val {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::ZeroRuns),
+            v => Err(format!("RecordingReader: Unknown compression: {}.", v).into()),
+        }
+    }
+
+    /// Encodes `raw`, appending to `buf`.
+    fn encode(&self, raw: &[u8], buf: &mut Vec<u8>) {
+        match *self {
+            Compression::None => buf.extend_from_slice(raw),
+            Compression::ZeroRuns => {
+                let mut i = 0;
+                while i < raw.len() {
+                    let zeros_start = i;
+                    while i < raw.len() && raw[i] == 0 { i += 1; }
+                    let lits_start = i;
+                    while i < raw.len() && raw[i] != 0 { i += 1; }
+                    put_varint(buf, lits_start - zeros_start);
+                    put_varint(buf, i - lits_start);
+                    buf.extend_from_slice(&raw[lits_start..i]);
+                }
+            },
+        }
+    }
+
+    /// Decodes `enc` into a frame of `raw_len` bytes.
+    fn decode(&self, enc: &[u8], raw_len: usize) -> CmnResult<Vec<u8>> {
+        let raw = match *self {
+            Compression::None => enc.to_vec(),
+            Compression::ZeroRuns => {
+                let mut raw = Vec::with_capacity(raw_len);
+                let mut pos = 0;
+                while pos < enc.len() {
+                    let zeros = get_varint(enc, &mut pos)?;
+                    let lits = get_varint(enc, &mut pos)?;
+                    if raw.len() + zeros + lits > raw_len || pos + lits > enc.len() {
+                        return Err("ZeroRuns: Frame overruns its length.".into());
+                    }
+                    raw.resize(raw.len() + zeros, 0);
+                    raw.extend_from_slice(&enc[pos..pos + lits]);
+                    pos += lits;
+                }
+                raw
+            },
+        };
+
+        if raw.len() != raw_len {
+            return Err(format!("RecordingReader: Frame length mismatch (expected: {}, \
+                found: {}).", raw_len, raw.len()).into());
+        }
+        Ok(raw)
+    }
+}
+
+
+/// A description of a recorded sampler stream.
+#[derive(Debug, Clone)]
+pub struct StreamInfo {
+    area_name: String,
+    kind_code: u8,
+    layer_name: Option<String>,
+    signed: bool,
+    area_dims: CorticalDims,
+    tract_map: SliceTractMap,
+    output_layers: Vec<(String, AxonTags)>,
+}
+
+impl StreamInfo {
+    fn write(&self, buf: &mut Vec<u8>) {
+        put_str(buf, &self.area_name);
+        buf.push(self.kind_code);
+        put_str(buf, self.layer_name.as_ref().map(|n| n.as_str()).unwrap_or(""));
+        buf.push(self.signed as u8);
+        buf.push(self.area_dims.depth());
+        put_u32(buf, self.area_dims.v_size());
+        put_u32(buf, self.area_dims.u_size());
+        put_u32(buf, self.tract_map.tags().len() as u32);
+        for ((tag, &v_size), &u_size) in self.tract_map.tags().iter()
+                .zip(self.tract_map.v_sizes()).zip(self.tract_map.u_sizes()) {
+            put_str(buf, tag);
+            put_u32(buf, v_size);
+            put_u32(buf, u_size);
+        }
+        put_u32(buf, self.output_layers.len() as u32);
+        for &(ref lyr_name, ref axn_tags) in self.output_layers.iter() {
+            put_str(buf, lyr_name);
+            let names: Vec<String> = axn_tags.iter().filter_map(|tag| tag.name()).collect();
+            put_u32(buf, names.len() as u32);
+            for name in names.iter() {
+                put_str(buf, name);
+            }
+        }
+    }
+
+    fn read<R: Read>(rdr: &mut R) -> CmnResult<StreamInfo> {
+        let area_name = get_str(rdr)?;
+        let kind_code = get_u8(rdr)?;
+        if SamplerKind::code_name(kind_code).is_none() {
+            return Err(format!("RecordingReader: Unknown sampler kind code: {}.",
+                kind_code).into());
+        }
+        let layer_name = get_str(rdr)?;
+        let layer_name = if layer_name.is_empty() { None } else { Some(layer_name) };
+        let signed = get_u8(rdr)? != 0;
+        let depth = get_u8(rdr)?;
+        let v_size = get_u32(rdr)?;
+        let u_size = get_u32(rdr)?;
+        let slc_count = get_u32(rdr)? as usize;
+        let (mut tags, mut v_sizes, mut u_sizes) = (Vec::with_capacity(slc_count),
+            Vec::with_capacity(slc_count), Vec::with_capacity(slc_count));
+        for _ in 0..slc_count {
+            tags.push(get_str(rdr)?);
+            v_sizes.push(get_u32(rdr)?);
+            u_sizes.push(get_u32(rdr)?);
+        }
+        let output_layer_count = get_u32(rdr)? as usize;
+        let mut output_layers = Vec::with_capacity(output_layer_count.min(256));
+        for _ in 0..output_layer_count {
+            let lyr_name = get_str(rdr)?;
+            let tag_count = get_u32(rdr)? as usize;
+            let mut tags = Vec::with_capacity(tag_count.min(256));
+            for _ in 0..tag_count {
+                let tag_name = get_str(rdr)?;
+                tags.push(AxonTag::from_name(&tag_name).ok_or_else(|| CmnError::new(
+                    format!("RecordingReader: Unknown axon tag: '{}'.", tag_name)))?);
+            }
+            output_layers.push((lyr_name, AxonTags::new(&tags)));
+        }
+
+        Ok(StreamInfo {
+            area_name,
+            kind_code,
+            layer_name,
+            signed,
+            area_dims: CorticalDims::new(depth, v_size, u_size),
+            tract_map: SliceTractMap::new(&tags, &v_sizes, &u_sizes),
+            output_layers,
+        })
+    }
+
+    /// The name of the sampled area.
+    #[inline] pub fn area_name(&self) -> &str { &self.area_name }
+    /// The sampler kind code (see `SamplerKind::code`).
+    #[inline] pub fn kind_code(&self) -> u8 { self.kind_code }
+    /// The sampler kind name (e.g. "Axons").
+    pub fn kind_name(&self) -> &'static str {
+        SamplerKind::code_name(self.kind_code).unwrap_or("Unknown")
+    }
+    /// The name of the sampled layer or `None` for `SamplerKind::Axons(None)`.
+    #[inline] pub fn layer_name(&self) -> Option<&str> {
+        self.layer_name.as_ref().map(|n| n.as_str())
+    }
+    /// Whether or not this is a stream of axons (`SamplerKind::Axons`).
+    #[inline] pub fn is_axons(&self) -> bool {
+        self.kind_code == SamplerKind::Axons(None).code()
+    }
+    /// Whether or not frame elements are `i8` rather than `u8`.
+    #[inline] pub fn is_signed(&self) -> bool { self.signed }
+    /// The dimensions of the sampled area.
+    #[inline] pub fn area_dims(&self) -> &CorticalDims { &self.area_dims }
+    /// The slices of the sampled layer (or of the whole area for
+    /// `SamplerKind::Axons(None)`).
+    #[inline] pub fn tract_map(&self) -> &SliceTractMap { &self.tract_map }
+    /// The name and output axon tags of each output layer within the
+    /// sampled slices.
+    #[inline] pub fn output_layers(&self) -> &[(String, AxonTags)] { &self.output_layers }
+}
+
+
+/// Reads a sampler frame, blocking until a new frame is available.
+fn read_frame(rx: &TractReceiver) -> CmnResult<Vec<u8>> {
+    let read_buffer = rx.recv(true).wait()?
+        .ok_or(CmnError::from("Recorder::record: Sampler channel closed."))?;
+
+    let frame = match executor::block_on(FutureReadGuardVec::from(read_buffer))? {
+        ReadGuardVec::U8(ref rg) => rg.to_vec(),
+        ReadGuardVec::I8(ref rg) => rg.iter().map(|&v| v as u8).collect(),
+    };
+    Ok(frame)
+}
+
+
+/// Records the frames of a set of samplers to a file, once per cycle.
+///
+/// Samplers are created with backpressure so that no frame is missed: the
+/// sampled areas will not write a new frame until the previous one has been
+/// recorded. `::record` must therefore be called once after every cycle.
+///
+/// Buffered frames are written when dropped but any error is then ignored.
+/// Call `::finish` to flush and check for errors.
+#[derive(Debug)]
+pub struct Recorder {
+    wtr: BufWriter<File>,
+    compression: Compression,
+    chunk_cycles: u32,
+    streams: Vec<StreamInfo>,
+    rxs: Vec<TractReceiver>,
+    header_written: bool,
+    chunk: Vec<u8>,
+    chunk_first_cycle: u64,
+    chunk_cycle_count: u32,
+    next_cycle: u64,
+}
+
+impl Recorder {
+    /// Creates (or truncates) the file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P, compression: Compression) -> CmnResult<Recorder> {
+        Ok(Recorder {
+            wtr: BufWriter::new(File::create(path)?),
+            compression,
+            chunk_cycles: DEFAULT_CHUNK_CYCLES,
+            streams: Vec::with_capacity(8),
+            rxs: Vec::with_capacity(8),
+            header_written: false,
+            chunk: Vec::new(),
+            chunk_first_cycle: 0,
+            chunk_cycle_count: 0,
+            next_cycle: 0,
+        })
+    }
+
+    /// Sets the number of cycles stored in each chunk (default: 64).
+    pub fn chunk_cycles(mut self, chunk_cycles: u32) -> CmnResult<Recorder> {
+        if chunk_cycles == 0 {
+            return Err("Recorder::chunk_cycles: Must be greater than zero.".into());
+        }
+        self.chunk_cycles = chunk_cycles;
+        Ok(self)
+    }
+
+    /// Creates a sampler of `kind` on `area` and adds it as a stream,
+    /// returning the stream index.
+    ///
+    /// All streams must be added before the first call to `::record`.
+    pub fn subscribe(&mut self, area: &mut CorticalArea, kind: SamplerKind) -> CmnResult<usize> {
+        if self.header_written {
+            return Err("Recorder::subscribe: Streams cannot be added once recording \
+                has begun.".into());
+        }
+
+        let (layer_name, tract_map) = match kind.layer_addr() {
+            Some(lyr_addr) => {
+                let lyr_info = area.area_map().layer_map().layer_info(lyr_addr.layer_id())
+                    .ok_or_else(|| CmnError::new(format!("Recorder::subscribe: Invalid \
+                        layer: {}.", lyr_addr)))?;
+                let slc_range = lyr_info.slc_range().cloned()
+                    .ok_or_else(|| CmnError::new(format!("Recorder::subscribe: Layer {} \
+                        has no slices.", lyr_addr)))?;
+                (Some(lyr_info.name().to_owned()),
+                    area.area_map().slice_map().tract_map_range(slc_range))
+            },
+            None => (None, area.axon_tract_map()),
+        };
+
+        let mut output_layers: Vec<(String, AxonTags)> = Vec::new();
+        for lyr_name in tract_map.tags().iter() {
+            if output_layers.iter().any(|&(ref name, _)| name == lyr_name) { continue; }
+            let lyr_info = area.area_map().layer_map().layers().by_key(lyr_name.as_str());
+            if let Some(&AxonDomain::Output(ref sig)) = lyr_info.map(|li| li.axon_domain()) {
+                output_layers.push((lyr_name.clone(), sig.tags().clone()));
+            }
+        }
+
+        self.streams.push(StreamInfo {
+            area_name: area.area_map().area_name().to_owned(),
+            kind_code: kind.code(),
+            layer_name,
+            signed: kind.is_signed(),
+            area_dims: area.dims().clone(),
+            tract_map,
+            output_layers,
+        });
+        self.rxs.push(area.sampler(kind, SamplerBufferKind::Single, true)?);
+        Ok(self.streams.len() - 1)
+    }
+
+    /// Creates a sampler of `kind` on the area named `area_name` and adds it
+    /// as a stream, returning the stream index.
+    pub fn subscribe_area(&mut self, cortex: &mut Cortex, area_name: &str, kind: SamplerKind)
+            -> CmnResult<usize> {
+        let area = cortex.areas_mut().by_key_mut(area_name).ok_or_else(|| CmnError::new(
+            format!("Recorder::subscribe_area: No area named '{}'.", area_name)))?;
+        self.subscribe(area, kind)
+    }
+
+    fn write_header(&mut self) -> CmnResult<()> {
+        let mut buf = Vec::with_capacity(256);
+        buf.extend_from_slice(RECORDING_MAGIC);
+        put_u32(&mut buf, RECORDING_VERSION);
+        buf.push(self.compression.to_u8());
+        put_u32(&mut buf, self.chunk_cycles);
+        put_u32(&mut buf, self.streams.len() as u32);
+        for stream in self.streams.iter() {
+            stream.write(&mut buf);
+        }
+        self.wtr.write_all(&buf)?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    fn flush_chunk(&mut self) -> CmnResult<()> {
+        if self.chunk_cycle_count == 0 { return Ok(()); }
+        let mut header = Vec::with_capacity(20);
+        put_u64(&mut header, self.chunk_first_cycle);
+        put_u32(&mut header, self.chunk_cycle_count);
+        put_u64(&mut header, self.chunk.len() as u64);
+        self.wtr.write_all(&header)?;
+        self.wtr.write_all(&self.chunk)?;
+        self.chunk.clear();
+        self.chunk_cycle_count = 0;
+        Ok(())
+    }
+
+    /// Reads one frame from every stream (blocking until each is available)
+    /// and records them as the next cycle.
+    pub fn record(&mut self) -> CmnResult<()> {
+        if !self.header_written { self.write_header()?; }
+
+        if self.chunk_cycle_count == 0 {
+            self.chunk_first_cycle = self.next_cycle;
+        }
+
+        for rx in self.rxs.iter() {
+            let frame = read_frame(rx)?;
+            put_u32(&mut self.chunk, frame.len() as u32);
+            let len_pos = self.chunk.len();
+            put_u32(&mut self.chunk, 0);
+            self.compression.encode(&frame, &mut self.chunk);
+            let enc_len = (self.chunk.len() - len_pos - 4) as u32;
+            self.chunk[len_pos..len_pos + 4].copy_from_slice(&[enc_len as u8,
+                (enc_len >> 8) as u8, (enc_len >> 16) as u8, (enc_len >> 24) as u8]);
+        }
+
+        self.chunk_cycle_count += 1;
+        self.next_cycle += 1;
+
+        if self.chunk_cycle_count >= self.chunk_cycles {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Writes any buffered frames and flushes the file.
+    pub fn finish(mut self) -> CmnResult<()> {
+        if !self.header_written { self.write_header()?; }
+        self.flush_chunk()?;
+        self.wtr.flush().map_err(CmnError::from)
+    }
+
+    /// Returns the stream descriptions.
+    #[inline] pub fn streams(&self) -> &[StreamInfo] { &self.streams }
+    /// Returns the number of cycles recorded so far.
+    #[inline] pub fn cycles_recorded(&self) -> u64 { self.next_cycle }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if self.header_written {
+            self.flush_chunk().ok();
+            self.wtr.flush().ok();
+        }
+    }
+}
+
+
+/// The frames of every stream for one recorded cycle.
+#[derive(Debug, Clone)]
+pub struct RecordedCycle {
+    cycle: u64,
+    frames: Vec<Vec<u8>>,
+}
+
+impl RecordedCycle {
+    /// The cycle index, counting from the first recorded cycle.
+    #[inline] pub fn cycle(&self) -> u64 { self.cycle }
+    /// The frames of every stream, in stream order.
+    #[inline] pub fn frames(&self) -> &[Vec<u8>] { &self.frames }
+    /// The frame of stream `stream_idx`.
+    #[inline] pub fn frame(&self, stream_idx: usize) -> &[u8] { &self.frames[stream_idx] }
+
+    /// The frame of stream `stream_idx` reinterpreted as `i8` (see
+    /// `StreamInfo::is_signed`).
+    pub fn frame_i8(&self, stream_idx: usize) -> Vec<i8> {
+        self.frames[stream_idx].iter().map(|&v| v as i8).collect()
+    }
+}
+
+
+/// Reads a recording file cycle by cycle.
+#[derive(Debug)]
+pub struct RecordingReader {
+    rdr: BufReader<File>,
+    compression: Compression,
+    chunk_cycles: u32,
+    streams: Vec<StreamInfo>,
+    data_start: u64,
+    chunk_remaining: u32,
+    next_cycle: u64,
+}
+
+impl RecordingReader {
+    /// Opens the file at `path` and reads the header and stream
+    /// descriptions.
+    pub fn open<P: AsRef<Path>>(path: P) -> CmnResult<RecordingReader> {
+        let mut rdr = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        rdr.read_exact(&mut magic)?;
+        if &magic != RECORDING_MAGIC {
+            return Err("RecordingReader::open: Not a bismit recording file.".into());
+        }
+
+        let version = get_u32(&mut rdr)?;
+        if version != RECORDING_VERSION {
+            return Err(format!("RecordingReader::open: Unsupported recording version: {} \
+                (expected: {}).", version, RECORDING_VERSION).into());
+        }
+
+        let compression = Compression::from_u8(get_u8(&mut rdr)?)?;
+        let chunk_cycles = get_u32(&mut rdr)?;
+        let stream_count = get_u32(&mut rdr)? as usize;
+        let mut streams = Vec::with_capacity(stream_count);
+        for _ in 0..stream_count {
+            streams.push(StreamInfo::read(&mut rdr)?);
+        }
+        let data_start = rdr.seek(SeekFrom::Current(0))?;
+
+        Ok(RecordingReader { rdr, compression, chunk_cycles, streams, data_start,
+            chunk_remaining: 0, next_cycle: 0 })
+    }
+
+    /// Reads the next chunk header, returning false at the end of the file.
+    fn next_chunk(&mut self) -> CmnResult<bool> {
+        let first_cycle = match get_u64_or_eof(&mut self.rdr)? {
+            Some(c) => c,
+            None => return Ok(false),
+        };
+        self.chunk_remaining = get_u32(&mut self.rdr)?;
+        let _byte_len = get_u64(&mut self.rdr)?;
+        self.next_cycle = first_cycle;
+        Ok(true)
+    }
+
+    /// Reads the next recorded cycle or returns `None` at the end of the
+    /// file.
+    pub fn next_cycle(&mut self) -> CmnResult<Option<RecordedCycle>> {
+        while self.chunk_remaining == 0 {
+            if !self.next_chunk()? { return Ok(None); }
+        }
+
+        let mut frames = Vec::with_capacity(self.streams.len());
+        for _ in 0..self.streams.len() {
+            let raw_len = get_u32(&mut self.rdr)? as usize;
+            let enc_len = get_u32(&mut self.rdr)? as usize;
+            let mut enc = vec![0u8; enc_len];
+            self.rdr.read_exact(&mut enc)?;
+            frames.push(self.compression.decode(&enc, raw_len)?);
+        }
+
+        let cycle = self.next_cycle;
+        self.next_cycle += 1;
+        self.chunk_remaining -= 1;
+        Ok(Some(RecordedCycle { cycle, frames }))
+    }
+
+    /// Positions the reader so that the next call to `::next_cycle` returns
+    /// cycle `cycle`. Returns false if the recording does not contain it.
+    ///
+    /// Chunks preceding the one containing `cycle` are skipped without being
+    /// read.
+    pub fn seek_cycle(&mut self, cycle: u64) -> CmnResult<bool> {
+        self.rdr.seek(SeekFrom::Start(self.data_start))?;
+        self.chunk_remaining = 0;
+
+        loop {
+            let first_cycle = match get_u64_or_eof(&mut self.rdr)? {
+                Some(c) => c,
+                None => return Ok(false),
+            };
+            let cycle_count = get_u32(&mut self.rdr)?;
+            let byte_len = get_u64(&mut self.rdr)?;
+
+            if cycle < first_cycle + cycle_count as u64 {
+                self.chunk_remaining = cycle_count;
+                self.next_cycle = first_cycle;
+                break;
+            }
+            self.rdr.seek(SeekFrom::Current(byte_len as i64))?;
+        }
+
+        // Skip frames within the chunk:
+        while self.next_cycle < cycle {
+            for _ in 0..self.streams.len() {
+                let _raw_len = get_u32(&mut self.rdr)?;
+                let enc_len = get_u32(&mut self.rdr)?;
+                self.rdr.seek(SeekFrom::Current(enc_len as i64))?;
+            }
+            self.next_cycle += 1;
+            self.chunk_remaining -= 1;
+        }
+        Ok(true)
+    }
+
+    /// Returns the stream descriptions.
+    #[inline] pub fn streams(&self) -> &[StreamInfo] { &self.streams }
+    /// Returns the frame compression.
+    #[inline] pub fn compression(&self) -> Compression { self.compression }
+    /// Returns the number of cycles per chunk.
+    #[inline] pub fn chunk_cycles(&self) -> u32 { self.chunk_cycles }
+}
+
+impl Iterator for RecordingReader {
+    type Item = CmnResult<RecordedCycle>;
+
+    fn next(&mut self) -> Option<CmnResult<RecordedCycle>> {
+        match self.next_cycle() {
+            Ok(Some(rc)) => Some(Ok(rc)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
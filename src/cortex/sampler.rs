@@ -149,7 +149,7 @@ impl CorticalSampler {
         let mut rxs = Vec::with_capacity(sampler_kinds.len());
 
         for sk in sampler_kinds.into_iter() {
-            let rx = area.sampler(sk.clone(), SamplerBufferKind::Single, true);
+            let rx = area.sampler(sk.clone(), SamplerBufferKind::Single, true).unwrap();
             rxs.push((sk, rx))
         }
 
@@ -15,8 +15,10 @@
 
 use cmn::{CmnResult};
 use map::{AreaMap, LayerAddress, ExecutionGraph, CommandRelations, CorticalBuffer, CellScheme, CommandUid};
-use ocl::{Kernel, ProQue, SpatialDims, Buffer, Event, MemFlags};
-use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings};
+use ocl::{Kernel, SpatialDims, Event};
+use cortex::{AxonSpace, ControlCellLayer, DataCellLayer, CorticalAreaSettings, Backend, Storage};
+use cortex::host_kernels::{self, HostLayer};
+use cortex::storage::device_kernel;
 
 
 const CYCLE_FREQUENCY: usize = 0xFF;
@@ -64,9 +66,13 @@ pub struct ActivitySmoother {
     layer_name: String,
     layer_addr: LayerAddress,
     host_lyr_addr: LayerAddress,
-    centers_v: Buffer<i32>,
-    centers_u: Buffer<i32>,
-    kern: Kernel,
+    centers_v: Storage<i32>,
+    centers_u: Storage<i32>,
+    centers_v_vec: Vec<i32>,
+    centers_u_vec: Vec<i32>,
+    group_radius: i32,
+    host_lyr: HostLayer,
+    kern: Option<Kernel>,
     // kern_inhib_passthrough: Kernel,
     exe_cmd_uid: CommandUid,
     exe_cmd_idx: usize,
@@ -77,7 +83,7 @@ pub struct ActivitySmoother {
 impl ActivitySmoother {
     pub fn new<S>(layer_name: S, layer_id: usize, scheme: CellScheme,
             host_lyr: &DataCellLayer, axns: &AxonSpace, area_map: &AreaMap,
-            ocl_pq: &ProQue, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
+            backend: &Backend, settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph)
             -> CmnResult<ActivitySmoother>
             where S: Into<String> {
         let layer_name = layer_name.into();
@@ -91,26 +97,27 @@ impl ActivitySmoother {
         assert!(centers_v_vec.len() == centers_u_vec.len());
         let cell_count = centers_v_vec.len();
 
-        let centers_v = Buffer::builder().queue(ocl_pq.queue().clone()).len(cell_count)
-                .copy_host_slice(&centers_v_vec).flags(MemFlags::new().copy_host_ptr()).build()?;
-        let centers_u = Buffer::builder().queue(ocl_pq.queue().clone()).len(cell_count)
-                .copy_host_slice(&centers_u_vec).flags(MemFlags::new().copy_host_ptr()).build()?;
+        let centers_v = backend.buffer_from(&centers_v_vec)?;
+        let centers_u = backend.buffer_from(&centers_u_vec)?;
 
         // Kernel:
         let kern_name = "smooth_activity";
-        let kern = ocl_pq.kernel_builder(kern_name)
-            .global_work_size(SpatialDims::One(cell_count))
-            .arg(&centers_v)
-            .arg(&centers_u)
-            .arg(&host_lyr.dims().v_size())
-            .arg(&host_lyr.dims().u_size())
-            .arg(&group_radius)
-            .arg(&host_lyr.dims().depth())
-            .arg(host_lyr.activities())
-            // .arg_named("aux_ints_0", None)
-            // .arg_named("aux_ints_1", None)
-            .arg(host_lyr.energies())
-            .build()?;
+        let kern = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name)
+                .global_work_size(SpatialDims::One(cell_count))
+                .arg(centers_v.device()?)
+                .arg(centers_u.device()?)
+                .arg(&host_lyr.dims().v_size())
+                .arg(&host_lyr.dims().u_size())
+                .arg(&group_radius)
+                .arg(&host_lyr.dims().depth())
+                .arg(host_lyr.activities().device()?)
+                // .arg_named("aux_ints_0", None)
+                // .arg_named("aux_ints_1", None)
+                .arg(host_lyr.energies().device()?)
+                .build()?),
+            None => None,
+        };
 
         let exe_cmd_srcs = (0..host_lyr.tft_count())
             .map(|host_lyr_tft_id| CorticalBuffer::data_den_tft(&host_lyr.soma(),
@@ -132,6 +139,10 @@ impl ActivitySmoother {
             host_lyr_addr: host_lyr.layer_addr(),
             centers_v,
             centers_u,
+            centers_v_vec,
+            centers_u_vec,
+            group_radius,
+            host_lyr: HostLayer::new(host_lyr, axns, area_map),
             kern,
             exe_cmd_uid,
             exe_cmd_idx: 0,
@@ -147,15 +158,24 @@ impl ActivitySmoother {
 
     pub fn cycle(&mut self, exe_graph: &mut ExecutionGraph, _host_lyr_addr: LayerAddress) -> CmnResult<()> {
         if self.cycle_count & CYCLE_FREQUENCY == 0 {
-
-            let mut event = Event::empty();
-            unsafe {
-                self.kern.cmd()
-                    .ewait(exe_graph.get_req_events(self.exe_cmd_idx)?)
-                    .enew(&mut event)
-                    .enq()?;
+            if self.settings.host_kernels {
+                let hl = &self.host_lyr;
+                let activities = host_kernels::read(&hl.activities)?;
+                let mut energies = host_kernels::read(&hl.energies)?;
+                host_kernels::smooth_activity(&self.centers_v_vec, &self.centers_u_vec, hl.v_size,
+                    hl.u_size, self.group_radius, hl.depth, &activities, &mut energies);
+                host_kernels::write_range(&hl.energies, &energies, 0..energies.len())?;
+                exe_graph.set_cmd_event(self.exe_cmd_idx, None)?;
+            } else {
+                let mut event = Event::empty();
+                unsafe {
+                    device_kernel(&self.kern)?.cmd()
+                        .ewait(exe_graph.get_req_events(self.exe_cmd_idx)?)
+                        .enew(&mut event)
+                        .enq()?;
+                }
+                exe_graph.set_cmd_event(self.exe_cmd_idx, Some(event))?;
             }
-            exe_graph.set_cmd_event(self.exe_cmd_idx, Some(event))?;
         } else {
             exe_graph.set_cmd_event(self.exe_cmd_idx, None)?;
         }
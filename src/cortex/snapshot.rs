@@ -0,0 +1,200 @@
+//! Network state snapshots.
+//!
+//! A snapshot file begins with a short header (magic bytes, format version,
+//! and one fingerprint per cortical area) followed by the contents of every
+//! per-area buffer, in the order in which they are visited by
+//! `CorticalArea::save_state`.
+//!
+//! All integers are stored little-endian. Each buffer and each rng seed is
+//! preceded by its name and length so that any mismatch between the file and
+//! the cortex being restored is reported precisely instead of being silently
+//! misinterpreted.
+//!
+//! Rng state is not serializable directly. Instead, when saved, a seed is
+//! drawn from a clone of each rng and written, leaving the running rng (and
+//! therefore the saving run) undisturbed. A restored rng is seeded with that
+//! seed. Every run restored from the same snapshot therefore continues
+//! identically, though not exactly as the saving run does.
+//!
+
+use std::fs::File;
+use std::io::{Read, Write, BufReader, BufWriter};
+use std::path::Path;
+use std::{mem, slice};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use ocl::traits::OclPrm;
+use cmn::{CmnError, CmnResult};
+use cortex::Storage;
+
+
+static SNAPSHOT_MAGIC: &'static [u8; 8] = b"BSMTSNAP";
+const SNAPSHOT_VERSION: u32 = 4;
+
+
+fn as_bytes<T: OclPrm>(vals: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(vals.as_ptr() as *const u8, vals.len() * mem::size_of::<T>()) }
+}
+
+fn as_bytes_mut<T: OclPrm>(vals: &mut [T]) -> &mut [u8] {
+    unsafe { slice::from_raw_parts_mut(vals.as_mut_ptr() as *mut u8, vals.len() * mem::size_of::<T>()) }
+}
+
+
+/// Writes cortex state to a snapshot file.
+#[derive(Debug)]
+pub struct SnapshotWriter {
+    wtr: BufWriter<File>,
+}
+
+impl SnapshotWriter {
+    /// Creates (or truncates) the file at `path` and writes the file header.
+    pub fn create<P: AsRef<Path>>(path: P) -> CmnResult<SnapshotWriter> {
+        let mut wtr = SnapshotWriter { wtr: BufWriter::new(File::create(path)?) };
+        wtr.wtr.write_all(SNAPSHOT_MAGIC)?;
+        wtr.write_u32(SNAPSHOT_VERSION)?;
+        Ok(wtr)
+    }
+
+    pub fn write_u32(&mut self, val: u32) -> CmnResult<()> {
+        let bytes = [val as u8, (val >> 8) as u8, (val >> 16) as u8, (val >> 24) as u8];
+        self.wtr.write_all(&bytes).map_err(CmnError::from)
+    }
+
+    pub fn write_u64(&mut self, val: u64) -> CmnResult<()> {
+        self.write_u32(val as u32)?;
+        self.write_u32((val >> 32) as u32)
+    }
+
+    pub fn write_str(&mut self, s: &str) -> CmnResult<()> {
+        self.write_u64(s.len() as u64)?;
+        self.wtr.write_all(s.as_bytes()).map_err(CmnError::from)
+    }
+
+    /// Reads the entire contents of `buf` (blocking) and writes it under the
+    /// name, `name`.
+    pub fn write_buffer<T: OclPrm>(&mut self, name: &str, buf: &Storage<T>) -> CmnResult<()> {
+        let vec = buf.read()?;
+        self.write_vals(name, &vec)
+    }
+
+    /// Writes the host-side values, `vals`, under the name, `name`.
+    pub fn write_vals<T: OclPrm>(&mut self, name: &str, vals: &[T]) -> CmnResult<()> {
+        self.write_str(name)?;
+        self.write_u64(vals.len() as u64)?;
+        self.wtr.write_all(as_bytes(vals)).map_err(CmnError::from)
+    }
+
+    /// Writes a seed drawn from a clone of `rng`, leaving `rng` itself
+    /// unchanged.
+    pub fn write_rng(&mut self, name: &str, rng: &SmallRng) -> CmnResult<()> {
+        let mut seed = <SmallRng as SeedableRng>::Seed::default();
+        rng.clone().fill(seed.as_mut());
+        self.write_str(name)?;
+        self.write_u64(seed.as_mut().len() as u64)?;
+        self.wtr.write_all(seed.as_mut()).map_err(CmnError::from)
+    }
+
+    /// Flushes all buffered data to disk.
+    pub fn finish(mut self) -> CmnResult<()> {
+        self.wtr.flush().map_err(CmnError::from)
+    }
+}
+
+
+/// Reads cortex state from a snapshot file.
+#[derive(Debug)]
+pub struct SnapshotReader {
+    rdr: BufReader<File>,
+}
+
+impl SnapshotReader {
+    /// Opens the file at `path` and verifies the file header.
+    pub fn open<P: AsRef<Path>>(path: P) -> CmnResult<SnapshotReader> {
+        let mut rdr = SnapshotReader { rdr: BufReader::new(File::open(path)?) };
+
+        let mut magic = [0u8; 8];
+        rdr.rdr.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err("SnapshotReader::open: Not a bismit snapshot file.".into());
+        }
+
+        let version = rdr.read_u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("SnapshotReader::open: Unsupported snapshot version: {} \
+                (expected: {}).", version, SNAPSHOT_VERSION).into());
+        }
+        Ok(rdr)
+    }
+
+    pub fn read_u32(&mut self) -> CmnResult<u32> {
+        let mut bytes = [0u8; 4];
+        self.rdr.read_exact(&mut bytes)?;
+        Ok((bytes[0] as u32) | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 |
+            (bytes[3] as u32) << 24)
+    }
+
+    pub fn read_u64(&mut self) -> CmnResult<u64> {
+        let lo = self.read_u32()? as u64;
+        let hi = self.read_u32()? as u64;
+        Ok(lo | (hi << 32))
+    }
+
+    pub fn read_str(&mut self) -> CmnResult<String> {
+        let len = self.read_u64()? as usize;
+        let mut bytes = vec![0u8; len];
+        self.rdr.read_exact(&mut bytes)?;
+        String::from_utf8(bytes).map_err(|err| format!("SnapshotReader::read_str: {}", err).into())
+    }
+
+    /// Reads a name and verifies that it matches `name`.
+    fn expect_name(&mut self, name: &str) -> CmnResult<()> {
+        let found = self.read_str()?;
+        if found != name {
+            return Err(format!("SnapshotReader: Snapshot entry mismatch (expected: '{}', \
+                found: '{}').", name, found).into());
+        }
+        Ok(())
+    }
+
+    /// Reads the next entry, verifies its name and length, and writes it
+    /// (blocking) to `buf`.
+    pub fn read_buffer<T: OclPrm>(&mut self, name: &str, buf: &Storage<T>) -> CmnResult<()> {
+        self.expect_name(name)?;
+        let len = self.read_u64()? as usize;
+        if len != buf.len() {
+            return Err(format!("SnapshotReader::read_buffer: Length mismatch for '{}' \
+                (snapshot: {}, buffer: {}).", name, len, buf.len()).into());
+        }
+        let mut vec = vec![Default::default(); len];
+        self.rdr.read_exact(as_bytes_mut(&mut vec))?;
+        buf.write_at(&vec, 0)
+    }
+
+    /// Reads the next entry, verifies its name and length, and returns its
+    /// values.
+    pub fn read_vals<T: OclPrm>(&mut self, name: &str, len: usize) -> CmnResult<Vec<T>> {
+        self.expect_name(name)?;
+        let found_len = self.read_u64()? as usize;
+        if found_len != len {
+            return Err(format!("SnapshotReader::read_vals: Length mismatch for '{}' \
+                (snapshot: {}, expected: {}).", name, found_len, len).into());
+        }
+        let mut vec = vec![Default::default(); len];
+        self.rdr.read_exact(as_bytes_mut(&mut vec))?;
+        Ok(vec)
+    }
+
+    /// Reads the next entry as a seed and reseeds `rng` with it.
+    pub fn read_rng(&mut self, name: &str, rng: &mut SmallRng) -> CmnResult<()> {
+        self.expect_name(name)?;
+        let mut seed = <SmallRng as SeedableRng>::Seed::default();
+        let len = self.read_u64()? as usize;
+        if len != seed.as_mut().len() {
+            return Err(format!("SnapshotReader::read_rng: Seed length mismatch for '{}' \
+                (snapshot: {}, expected: {}).", name, len, seed.as_mut().len()).into());
+        }
+        self.rdr.read_exact(seed.as_mut())?;
+        *rng = SmallRng::from_seed(seed);
+        Ok(())
+    }
+}
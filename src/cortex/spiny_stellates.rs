@@ -1,14 +1,16 @@
 // #![allow(unused_imports)]
 
 // use std::collections::BTreeMap;
-use rand::{Rng, FromEntropy, rngs::SmallRng};
+use rand::{Rng, rngs::SmallRng};
 use cmn::{self, CmnResult, CorticalDims};
 use map::{AreaMap};
-use ocl::{Kernel, ProQue, Buffer, Event, SpatialDims};
+use ocl::{Kernel, Event, SpatialDims};
 use map::{CellScheme, ExecutionGraph, CommandRelations,
     CorticalBuffer, LayerAddress, LayerTags, CommandUid};
 use cortex::{Dendrites, AxonSpace, CorticalAreaSettings, DataCellLayer, ControlCellLayers,
-    Tufts};
+    Tufts, SnapshotWriter, SnapshotReader, LearningParams, Neuromodulation, Backend, Storage};
+use cortex::host_kernels;
+use cortex::storage::{device_kernel, device_kernel_mut};
 
 
 const PRNT: bool = false;
@@ -26,10 +28,14 @@ pub struct SpinyStellateLayer {
     axon_slc_ids: Vec<u8>,
     // base_axon_slc: u8,
     lyr_axon_idz: u32,
-    kern_cycle: Kernel,
-    kern_mtp: Kernel,
-    energies: Buffer<u8>,
-    activities: Buffer<u8>,
+    syns_per_tft: u32,
+    axn_states: Storage<u8>,
+    kern_cycle: Option<Kernel>,
+    kern_mtp: Option<Kernel>,
+    energies: Storage<u8>,
+    activities: Storage<u8>,
+    neuromods: Storage<i8>,
+    neuromod_col_count: u32,
     pub dens: Dendrites,
     rng: SmallRng,
     cycle_exe_cmd_uid: Option<CommandUid>,
@@ -37,13 +43,15 @@ pub struct SpinyStellateLayer {
     mtp_exe_cmd_uid: Option<CommandUid>,
     mtp_exe_cmd_idx: Option<usize>,
     settings: CorticalAreaSettings,
+    learning_enabled: bool,
     control_lyr_idxs: Vec<(LayerAddress, usize)>,
 }
 
 impl SpinyStellateLayer {
     pub fn new<S: Into<String>>(layer_name: S, layer_id: usize, dims: CorticalDims, cell_scheme: CellScheme,
-            area_map: &AreaMap, axons: &AxonSpace, ocl_pq: &ProQue,
-            settings: CorticalAreaSettings, exe_graph: &mut ExecutionGraph,
+            area_map: &AreaMap, axons: &AxonSpace, backend: &Backend,
+            settings: CorticalAreaSettings, neuromod: &mut Neuromodulation,
+            exe_graph: &mut ExecutionGraph,
     ) -> CmnResult<SpinyStellateLayer> {
         let layer_name = layer_name.into();
         let layer_addr = LayerAddress::new(area_map.area_id(), layer_id);
@@ -58,16 +66,17 @@ impl SpinyStellateLayer {
 
         let syns_per_tft = cell_scheme.tft_schemes()[ssc_tft_id].syns_per_tft();
 
-        let energies = Buffer::builder().queue(ocl_pq.queue().clone()).len(dims).fill_val(0).build()?;
-        let activities = Buffer::builder().queue(ocl_pq.queue().clone()).len(dims).fill_val(0).build()?;
+        let energies = backend.buffer::<u8>(dims.to_len(), 0)?;
+        let activities = backend.buffer::<u8>(dims.to_len(), 0)?;
 
         println!("{mt}{mt}SPINYSTELLATES::NEW(): base_axon_slc: {}, lyr_axon_idz: {}, dims: {:?}",
             base_axon_slc, lyr_axon_idz, dims, mt = cmn::MT);
 
         // let dens_dims = dims.clone_with_ptl2(cell_scheme.dens_per_tft_l2 as i8);
         let dens = try!(Dendrites::new(layer_name.clone(), layer_id, dims, cell_scheme.clone(),
-            area_map, axons, ocl_pq,
-            settings.disable_sscs, exe_graph));
+            area_map, axons, backend,
+            settings.disable_sscs, settings.host_kernels, settings.device_regrowth,
+            settings.learning_params, settings.seed, exe_graph));
         let _grp_count = cmn::OPENCL_MINIMUM_WORKGROUP_SIZE;
         let _cels_per_grp = dims.per_subgrp(_grp_count).expect("SpinyStellateLayer::new()");
 
@@ -76,12 +85,15 @@ impl SpinyStellateLayer {
         =============================================================================*/
 
         let kern_name = "ssc_cycle";
-        let kern_cycle = ocl_pq.kernel_builder(kern_name)
-            // .global_work_size(dims)
-            .global_work_size(SpatialDims::Three(dims.depth() as usize, dims.v_size() as usize, dims.u_size() as usize))
-            .arg(&energies)
-            .arg(dens.states())
-            .build()?;
+        let kern_cycle = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name)
+                // .global_work_size(dims)
+                .global_work_size(SpatialDims::Three(dims.depth() as usize, dims.v_size() as usize, dims.u_size() as usize))
+                .arg(energies.device()?)
+                .arg(dens.states().device()?)
+                .build()?),
+            None => None,
+        };
 
         // let mut cycle_cmd_srcs = Vec::with_capacity(2);
         // // cycle_cmd_srcs.push(CorticalBuffer::data_syn_tft(dens.syns().states(), layer_addr, ssc_tft_id));
@@ -96,20 +108,27 @@ impl SpinyStellateLayer {
         };
 
         let kern_name = "ssc_mtp_simple";
-        let kern_mtp = ocl_pq.kernel_builder(kern_name)
-            // .global_work_size(dims)
-            .global_work_size(SpatialDims::Two(1, dims.cells() as usize))
-            .arg(axons.states())
-            .arg(dens.syns().states())
-            .arg(&lyr_axon_idz)
-            // .arg(cels_per_grp)
-            .arg(&syns_per_tft)
-            // CURRENTLY UNUSED:
-            .arg_named("rnd", 0u32)
-            // .arg_named("aux_ints_0", None)
-            // .arg_named("aux_ints_1", None)
-            .arg(dens.syns().strengths())
-            .build()?;
+        let kern_mtp = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name)
+                // .global_work_size(dims)
+                .global_work_size(SpatialDims::Two(1, dims.cells() as usize))
+                .arg(axons.states().device()?)
+                .arg(dens.syns().states().device()?)
+                .arg(&lyr_axon_idz)
+                // .arg(cels_per_grp)
+                .arg(&syns_per_tft)
+                .arg_named("pr_l2i", &settings.learning_params.prx_potentiation_rate_l2i)
+                .arg_named("dr_l2i", &settings.learning_params.prx_depression_rate_l2i)
+                .arg(neuromod.buffer().device()?)
+                .arg(&neuromod.col_count())
+                // CURRENTLY UNUSED:
+                .arg_named("rnd", 0u32)
+                // .arg_named("aux_ints_0", None)
+                // .arg_named("aux_ints_1", None)
+                .arg(dens.syns().strengths().device()?)
+                .build()?),
+            None => None,
+        };
 
         ////// KEEP ME:
             // let kern_name = "ssc_mtp";
@@ -121,6 +140,8 @@ impl SpinyStellateLayer {
             //     .arg(lyr_axon_idz)
             //     .arg(_cels_per_grp)
             //     .arg(syns_per_tft_l2)
+            //     .arg_named("pr_l2i", &settings.learning_params.prx_potentiation_rate_l2i)
+            //     .arg_named("dr_l2i", &settings.learning_params.prx_depression_rate_l2i)
             //     .arg_named::<u32>("rnd", None)
             //     // .arg_named("aux_ints_0", None)
             //     // .arg_named("aux_ints_1", None)
@@ -139,6 +160,7 @@ impl SpinyStellateLayer {
         let mtp_exe_cmd_uid = if settings.disable_sscs | settings.disable_learning {
             None
         } else {
+            mtp_cmd_srcs.push(neuromod.register_reader());
             Some(exe_graph.add_command(CommandRelations::cortical_kernel(kern_name, mtp_cmd_srcs,
                 vec![CorticalBuffer::data_syn_tft(dens.syns().strengths(), layer_addr, ssc_tft_id)]))?)
         };
@@ -157,17 +179,23 @@ impl SpinyStellateLayer {
             axon_slc_ids: axon_slc_ids,
             // base_axon_slc: base_axon_slc,
             lyr_axon_idz: lyr_axon_idz,
+            syns_per_tft,
+            axn_states: axons.states().clone(),
             kern_cycle: kern_cycle,
             kern_mtp: kern_mtp,
             energies,
             activities,
-            rng: SmallRng::from_entropy(),
+            neuromods: neuromod.buffer().clone(),
+            neuromod_col_count: neuromod.col_count(),
+            rng: cmn::seeded_rng(settings.seed, "spiny_stellates", &[layer_addr.area_id() as u64,
+                layer_addr.layer_id() as u64]),
             dens: dens,
             cycle_exe_cmd_uid,
             cycle_exe_cmd_idx: None,
             mtp_exe_cmd_uid,
             mtp_exe_cmd_idx: None,
             settings,
+            learning_enabled: true,
             control_lyr_idxs: Vec::with_capacity(4),
         })
     }
@@ -233,12 +261,22 @@ impl SpinyStellateLayer {
 
         // Cycle soma (currently adds energies to den states):
         if let Some(cycle_cmd_idx) = self.cycle_exe_cmd_idx {
-            let mut event = Event::empty();
-            unsafe {
-                self.kern_cycle.cmd().ewait(exe_graph.get_req_events(cycle_cmd_idx)?)
-                    .enew(&mut event).enq()?;
+            if self.settings.host_kernels {
+                let mut energies = host_kernels::read(&self.energies)?;
+                let mut cel_states = host_kernels::read(self.dens.states())?;
+                host_kernels::ssc_cycle(&mut energies, &mut cel_states);
+                host_kernels::write_range(&self.energies, &energies, 0..energies.len())?;
+                host_kernels::write_range(self.dens.states(), &cel_states, 0..cel_states.len())?;
+                exe_graph.set_cmd_event(cycle_cmd_idx, None)?;
+            } else {
+                let mut event = Event::empty();
+                unsafe {
+                    device_kernel(&self.kern_cycle)?.cmd()
+                        .ewait(exe_graph.get_req_events(cycle_cmd_idx)?)
+                        .enew(&mut event).enq()?;
+                }
+                exe_graph.set_cmd_event(cycle_cmd_idx, Some(event))?;
             }
-            exe_graph.set_cmd_event(cycle_cmd_idx, Some(event))?;
         }
 
         // Post cycle:
@@ -255,20 +293,43 @@ impl SpinyStellateLayer {
     #[inline]
     pub fn learn(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
         if let Some(cmd_idx) = self.mtp_exe_cmd_idx {
+            if !self.learning_enabled {
+                exe_graph.set_cmd_event(cmd_idx, None)?;
+                return Ok(());
+            }
+
             if PRNT { printlnc!(royal_blue: "Ssts: Performing learning for layer: '{}'...", self.layer_name); }
             let rnd = self.rng.gen::<u32>();
-            self.kern_mtp.set_arg("rnd", rnd).unwrap();
 
-            let mut event = Event::empty();
-            unsafe { self.kern_mtp.cmd().ewait(exe_graph.get_req_events(cmd_idx)?).enew(&mut event).enq()?; }
-            exe_graph.set_cmd_event(cmd_idx, Some(event))?;
+            if self.settings.host_kernels {
+                let axn_states = host_kernels::read(&self.axn_states)?;
+                let syn_states = host_kernels::read(self.dens.syns().states())?;
+                let mut syn_strengths = host_kernels::read(self.dens.syns().strengths())?;
+                let neuromods = host_kernels::read(&self.neuromods)?;
+                let params = &self.settings.learning_params;
+                host_kernels::ssc_mtp_simple(&axn_states, &syn_states, self.lyr_axon_idz,
+                    self.syns_per_tft, params.prx_potentiation_rate_l2i,
+                    params.prx_depression_rate_l2i, &neuromods, self.neuromod_col_count, rnd,
+                    self.dims.cells(), &mut syn_strengths);
+                host_kernels::write_range(self.dens.syns().strengths(), &syn_strengths,
+                    0..syn_strengths.len())?;
+                exe_graph.set_cmd_event(cmd_idx, None)?;
+            } else {
+                let kern_mtp = device_kernel_mut(&mut self.kern_mtp)?;
+                kern_mtp.set_arg("rnd", rnd).unwrap();
+
+                let mut event = Event::empty();
+                unsafe { kern_mtp.cmd().ewait(exe_graph.get_req_events(cmd_idx)?).enew(&mut event).enq()?; }
+                exe_graph.set_cmd_event(cmd_idx, Some(event))?;
+            }
             if PRNT { printlnc!(royal_blue: "Ssts: Learning complete for layer: '{}'.", self.layer_name); }
         }
         Ok(())
     }
 
-    #[inline] pub fn regrow(&mut self) {
-        self.dens.regrow();
+    #[inline] pub fn regrow(&mut self, chunk_size: Option<usize>, exe_graph: &ExecutionGraph)
+            -> CmnResult<()> {
+        self.dens.regrow(chunk_size, exe_graph)
     }
 
     #[inline]
@@ -277,12 +338,27 @@ impl SpinyStellateLayer {
         (self.lyr_axon_idz as usize, sscs_axon_idn as usize)
     }
 
+    /// Enables or disables learning without altering the execution graph.
+    #[inline] pub fn set_learning_enabled(&mut self, enabled: bool) { self.learning_enabled = enabled; }
+
+    /// Sets the learning rates and regrowth parameters used from the next
+    /// cycle onward.
+    pub fn set_learning_params(&mut self, params: &LearningParams) -> CmnResult<()> {
+        self.dens.set_learning_params(params)?;
+        if let Some(ref mut kern_mtp) = self.kern_mtp {
+            kern_mtp.set_arg("pr_l2i", params.prx_potentiation_rate_l2i)?;
+            kern_mtp.set_arg("dr_l2i", params.prx_depression_rate_l2i)?;
+        }
+        self.settings.learning_params = *params;
+        Ok(())
+    }
+
     #[inline] pub fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] pub fn layer_tags(&self) -> LayerTags { self.layer_tags }
     #[inline] pub fn layer_addr(&self) -> LayerAddress { self.layer_addr }
-    #[inline] pub fn soma(&self) -> &Buffer<u8> { self.dens.states() }
-    #[inline] pub fn energies(&self) -> &Buffer<u8> { &self.energies }
-    #[inline] pub fn activities(&self) -> &Buffer<u8> { &self.activities }
+    #[inline] pub fn soma(&self) -> &Storage<u8> { self.dens.states() }
+    #[inline] pub fn energies(&self) -> &Storage<u8> { &self.energies }
+    #[inline] pub fn activities(&self) -> &Storage<u8> { &self.activities }
     #[inline] pub fn dims(&self) -> &CorticalDims { &self.dims }
     #[inline] pub fn axon_slc_ids(&self) -> &[u8] { self.axon_slc_ids.as_slice() }
     #[inline] pub fn base_axon_slc(&self) -> u8 { self.axon_slc_ids[0] }
@@ -305,8 +381,8 @@ impl DataCellLayer for SpinyStellateLayer {
     }
 
     #[inline]
-    fn regrow(&mut self) {
-        self.regrow()
+    fn regrow(&mut self, chunk_size: Option<usize>, exe_graph: &ExecutionGraph) -> CmnResult<()> {
+        self.regrow(chunk_size, exe_graph)
     }
 
     #[inline]
@@ -316,11 +392,11 @@ impl DataCellLayer for SpinyStellateLayer {
 
     #[inline] fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] fn layer_addr(&self) -> LayerAddress { self.layer_addr }
-    #[inline] fn soma(&self) -> &Buffer<u8> { self.dens.states() }
-    #[inline] fn soma_mut(&mut self) -> &mut Buffer<u8> { self.dens.states_mut() }
-    #[inline] fn energies(&self) -> &Buffer<u8> { &self.energies }
-    #[inline] fn activities(&self) -> &Buffer<u8> { &self.activities }
-    #[inline] fn flag_sets(&self) -> &Buffer<u8> { unimplemented!() }
+    #[inline] fn soma(&self) -> &Storage<u8> { self.dens.states() }
+    #[inline] fn soma_mut(&mut self) -> &mut Storage<u8> { self.dens.states_mut() }
+    #[inline] fn energies(&self) -> &Storage<u8> { &self.energies }
+    #[inline] fn activities(&self) -> &Storage<u8> { &self.activities }
+    #[inline] fn flag_sets(&self) -> &Storage<u8> { unimplemented!() }
     #[inline] fn dims(&self) -> &CorticalDims { &self.dims }
     #[inline] fn axon_slc_ids(&self) -> &[u8] { self.axon_slc_ids.as_slice() }
     #[inline] fn base_axon_slc(&self) -> u8 { self.axon_slc_ids[0] }
@@ -329,6 +405,20 @@ impl DataCellLayer for SpinyStellateLayer {
     #[inline] fn tufts(&self) -> &Tufts { unimplemented!(); }
     #[inline] fn dens(&self) -> &Dendrites { &self.dens }
     #[inline] fn dens_mut(&mut self) -> &mut Dendrites { &mut self.dens }
+
+    fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_buffer("sscs.energies", &self.energies)?;
+        wtr.write_buffer("sscs.activities", &self.activities)?;
+        wtr.write_rng("sscs.rng", &self.rng)?;
+        self.dens.save_state(wtr)
+    }
+
+    fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_buffer("sscs.energies", &self.energies)?;
+        rdr.read_buffer("sscs.activities", &self.activities)?;
+        rdr.read_rng("sscs.rng", &mut self.rng)?;
+        self.dens.load_state(rdr)
+    }
 }
 
 
@@ -342,6 +432,7 @@ pub mod tests {
     // use ocl::util;
     use cmn::{self};
     use cortex::{SpinyStellateLayer, DendritesTest, DataCellLayerTest, CelCoords};
+    use cortex::storage::device_kernel_mut;
 
     impl DataCellLayerTest for SpinyStellateLayer {
         fn cycle_solo(&self) {
@@ -350,16 +441,18 @@ pub mod tests {
         }
 
         fn learn_solo(&mut self) {
-            self.kern_mtp.default_queue().unwrap().finish().unwrap();
+            let kern_mtp = device_kernel_mut(&mut self.kern_mtp)
+                .expect("<SpinyStellateLayer as DataCellLayerTest>::learn_solo [0]");
+            kern_mtp.default_queue().unwrap().finish().unwrap();
             let rnd = self.rng.gen::<u32>();
-            self.kern_mtp.set_arg("rnd", rnd).unwrap();
+            kern_mtp.set_arg("rnd", rnd).unwrap();
 
             unsafe {
-            self.kern_mtp.cmd().enq()
+            kern_mtp.cmd().enq()
                 .expect("<SpinyStellateLayer as DataCellLayerTest>::learn_solo [1]");
             }
 
-            self.kern_mtp.default_queue().unwrap().finish().unwrap();
+            kern_mtp.default_queue().unwrap().finish().unwrap();
         }
 
         /// Prints a range of pyramidal buffers.
@@ -438,11 +531,11 @@ pub mod tests {
         }
 
         fn set_all_to_zero(&mut self) {
-            self.dens.states().default_queue().unwrap().finish().unwrap();
+            self.dens.states().finish().unwrap();
 
-            self.dens.states().cmd().fill(0, None).enq().unwrap();
+            self.dens.states().fill(0).unwrap();
 
-            self.dens.states().default_queue().unwrap().finish().unwrap();
+            self.dens.states().finish().unwrap();
         }
     }
 }
@@ -0,0 +1,243 @@
+//! Cortical buffer storage and compute backends.
+//!
+//! Every buffer belonging to a cortical area is a `Storage`: an OpenCL
+//! buffer when the area runs its kernels on a device, or a block of host
+//! memory when it runs the reference kernels in `host_kernels` (see
+//! `CorticalAreaSettings::host_kernels`). An area using the host backend
+//! requires no OpenCL platform.
+//!
+//! Transfers to and from host storage complete before returning. Commands
+//! run on the host therefore leave no event in the execution graph and
+//! those which follow need not wait.
+//!
+
+use std::ops::Range;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use ocl::{Buffer, Kernel, ProQue, OclPrm};
+use cmn::{CmnError, CmnResult};
+use cmn::util::{self, BufferUid};
+
+
+fn read_lock<T>(vec: &RwLock<Vec<T>>) -> RwLockReadGuard<Vec<T>> {
+    vec.read().unwrap_or_else(|err| err.into_inner())
+}
+
+fn write_lock<T>(vec: &RwLock<Vec<T>>) -> RwLockWriteGuard<Vec<T>> {
+    vec.write().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Returns an error unless `range` lies within a buffer of length `len`.
+fn check_range(range: &Range<usize>, len: usize, caller: &str) -> CmnResult<()> {
+    if range.start > range.end || range.end > len {
+        return Err(format!("{}: Range ({:?}) out of bounds for a buffer of length {}.",
+            caller, range, len).into());
+    }
+    Ok(())
+}
+
+
+/// The memory backing a cortical buffer.
+#[derive(Debug, Clone)]
+pub enum Storage<T: OclPrm> {
+    /// An OpenCL buffer.
+    Device(Buffer<T>),
+    /// Host memory.
+    Host(Arc<RwLock<Vec<T>>>),
+}
+
+impl<T: OclPrm> Storage<T> {
+    /// Returns new host storage containing `vec`.
+    pub fn host(vec: Vec<T>) -> Storage<T> {
+        Storage::Host(Arc::new(RwLock::new(vec)))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        match *self {
+            Storage::Device(ref buf) => buf.len(),
+            Storage::Host(ref vec) => read_lock(vec).len(),
+        }
+    }
+
+    /// Returns true if this is host memory.
+    pub fn is_host(&self) -> bool {
+        match *self {
+            Storage::Device(_) => false,
+            Storage::Host(_) => true,
+        }
+    }
+
+    /// Returns the OpenCL buffer, if any.
+    pub fn as_device(&self) -> Option<&Buffer<T>> {
+        match *self {
+            Storage::Device(ref buf) => Some(buf),
+            Storage::Host(_) => None,
+        }
+    }
+
+    /// Returns the OpenCL buffer or an error if this is host memory.
+    pub fn device(&self) -> CmnResult<&Buffer<T>> {
+        self.as_device().ok_or(CmnError::new("Storage::device: Host memory has no device \
+            buffer (only available to areas not using host kernels)."))
+    }
+
+    /// Reads the entire contents (blocking).
+    pub fn read(&self) -> CmnResult<Vec<T>> {
+        match *self {
+            Storage::Device(ref buf) => {
+                let mut vec = vec![T::default(); buf.len()];
+                buf.cmd().read(&mut vec).enq()?;
+                Ok(vec)
+            },
+            Storage::Host(ref vec) => Ok(read_lock(vec).clone()),
+        }
+    }
+
+    /// Reads `range` (blocking).
+    pub fn read_range(&self, range: Range<usize>) -> CmnResult<Vec<T>> {
+        let mut vec = vec![T::default(); range.len()];
+        self.read_into(range.start, &mut vec)?;
+        Ok(vec)
+    }
+
+    /// Reads `dst.len()` elements beginning at `offset` into `dst`
+    /// (blocking).
+    pub fn read_into(&self, offset: usize, dst: &mut [T]) -> CmnResult<()> {
+        if dst.is_empty() { return Ok(()); }
+
+        match *self {
+            Storage::Device(ref buf) => {
+                buf.cmd().read(dst).offset(offset).enq()?;
+            },
+            Storage::Host(ref vec) => {
+                let vec = read_lock(vec);
+                let range = offset..(offset + dst.len());
+                check_range(&range, vec.len(), "Storage::read_into")?;
+                dst.copy_from_slice(&vec[range]);
+            },
+        }
+        Ok(())
+    }
+
+    /// Writes `src` beginning at `offset` (blocking).
+    pub fn write_at(&self, src: &[T], offset: usize) -> CmnResult<()> {
+        if src.is_empty() { return Ok(()); }
+
+        match *self {
+            Storage::Device(ref buf) => {
+                buf.cmd().write(src).offset(offset).enq()?;
+            },
+            Storage::Host(ref vec) => {
+                let mut vec = write_lock(vec);
+                let range = offset..(offset + src.len());
+                check_range(&range, vec.len(), "Storage::write_at")?;
+                vec[range].copy_from_slice(src);
+            },
+        }
+        Ok(())
+    }
+
+    /// Sets every element to `val` (blocking).
+    pub fn fill(&self, val: T) -> CmnResult<()> {
+        match *self {
+            Storage::Device(ref buf) => {
+                buf.cmd().fill(val, None).enq()?;
+            },
+            Storage::Host(ref vec) => {
+                for elem in write_lock(vec).iter_mut() {
+                    *elem = val;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Blocks until all commands enqueued on the default queue of a device
+    /// buffer have completed. Does nothing for host memory.
+    pub fn finish(&self) -> CmnResult<()> {
+        if let Storage::Device(ref buf) = *self {
+            if let Some(queue) = buf.default_queue() {
+                queue.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: OclPrm> BufferUid for Storage<T> {
+    fn buffer_uid(&self) -> u64 {
+        match *self {
+            Storage::Device(ref buf) => util::buffer_uid(buf),
+            Storage::Host(ref vec) => &**vec as *const RwLock<Vec<T>> as usize as u64,
+        }
+    }
+}
+
+
+/// The compute backend of a cortical area.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    /// OpenCL kernels built from the area's program and device buffers
+    /// created on its default queue.
+    Device(ProQue),
+    /// The reference kernels of `host_kernels` and host memory.
+    Host,
+}
+
+impl Backend {
+    /// Returns new storage of length `len` with every element set to
+    /// `fill_val`.
+    pub fn buffer<T: OclPrm>(&self, len: usize, fill_val: T) -> CmnResult<Storage<T>> {
+        match *self {
+            Backend::Device(ref pq) => Ok(Storage::Device(Buffer::<T>::builder()
+                .queue(pq.queue().clone())
+                .len(len)
+                .fill_val(fill_val)
+                .build()?)),
+            Backend::Host => Ok(Storage::host(vec![fill_val; len])),
+        }
+    }
+
+    /// Returns new storage containing a copy of `vals`.
+    pub fn buffer_from<T: OclPrm>(&self, vals: &[T]) -> CmnResult<Storage<T>> {
+        match *self {
+            Backend::Device(ref pq) => Ok(Storage::Device(Buffer::<T>::builder()
+                .queue(pq.queue().clone())
+                .len(vals.len())
+                .copy_host_slice(vals)
+                .build()?)),
+            Backend::Host => Ok(Storage::host(vals.to_vec())),
+        }
+    }
+
+    /// Returns the program and queue of a device backend.
+    pub fn pq(&self) -> Option<&ProQue> {
+        match *self {
+            Backend::Device(ref pq) => Some(pq),
+            Backend::Host => None,
+        }
+    }
+
+    /// Returns true if this is the host backend.
+    pub fn is_host(&self) -> bool {
+        match *self {
+            Backend::Device(_) => false,
+            Backend::Host => true,
+        }
+    }
+}
+
+
+/// Returns the kernel built by a device backend or an error if there is none
+/// (as with the host backend).
+pub fn device_kernel<'k>(kern: &'k Option<Kernel>) -> CmnResult<&'k Kernel> {
+    kern.as_ref().ok_or(CmnError::new("No device kernel has been built (the area is using \
+        the host backend)."))
+}
+
+/// Returns the kernel built by a device backend, mutably, or an error if
+/// there is none.
+pub fn device_kernel_mut<'k>(kern: &'k mut Option<Kernel>) -> CmnResult<&'k mut Kernel> {
+    kern.as_mut().ok_or(CmnError::new("No device kernel has been built (the area is using \
+        the host backend)."))
+}
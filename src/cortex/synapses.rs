@@ -5,8 +5,6 @@
 //! - [high priority] Testing:
 //!   - [INCOMPLETE] Check for uniqueness and correct distribution frequency
 //!     among src_slcs and cols
-//! - [low priority] Optimization:
-//!   - Split grow into smaller, more frequent pieces.
 //!
 //! Synapse index space (the address space shared by all of the synapse
 //! 'property' buffers, `states`, `strengths`, et al.) is first divided by
@@ -62,15 +60,29 @@
 //!  ... **for indexing purposes** tufts are parent to slices, which are
 //!  parent to cells (then dendrites, then synapses).
 //!
+//! Regrowth is incremental: each cycle, one chunk of synapse index space is
+//! read back to the host (see `Synapses::regrow`) then regrown and written
+//! back to the device at the start of the following cycle, cycling through
+//! the entire index space once every `len / chunk_size` cycles. Both the read
+//! and the write wait for every command of the cycle which uses the chunk,
+//! samplers included, which is why regrowth is queued last.
+//!
+//! With `CorticalAreaSettings::device_regrowth`, each chunk is instead
+//! regrown in place by the `tft_regrow_syns` kernel as soon as the current
+//! cycle's commands have finished with it, without any transfers.
+//!
 
 
-use rand::{FromEntropy, rngs::SmallRng};
+use std::ops::Range;
+use rand::{Rng, rngs::SmallRng};
 use cmn::{self, CmnResult, CorticalDims};
-use map::{AreaMap, SynSrcSlices, SynSrcIdxCache, SynSrc, LayerAddress};
-use ocl::{ProQue, SpatialDims, Buffer, Kernel, Result as OclResult, Event};
+use map::{AreaMap, SynSrcSlices, SynSrcIdxCache, SynSrc, LayerAddress, DendriteKind};
+use ocl::{SpatialDims, Buffer, Kernel, Result as OclResult, Event, EventList};
 use ocl::traits::OclPrm;
-use map::{CellScheme, ExecutionGraph, CommandRelations, CorticalBuffer, CommandUid};
-use cortex::AxonSpace;
+use map::{CellScheme, ExecutionGraph, CommandRelations, CorticalBuffer, CommandUid, MemoryBlock};
+use cortex::{AxonSpace, SnapshotWriter, SnapshotReader, LearningParams, Backend, Storage};
+use cortex::host_kernels::{self, AxonSliceGeometry};
+use cortex::storage::device_kernel;
 use {SrcOfs, SlcId};
 
 #[cfg(any(test, feature = "eval"))]
@@ -106,20 +118,21 @@ impl TuftDims {
 pub struct Synapses {
     layer_name: String,
     layer_id: usize,
+    layer_addr: LayerAddress,
     dims: CorticalDims,
-    kernel_flags: Kernel,
+    kernel_flags: Option<Kernel>,
     kernels_cycle: Vec<Kernel>,
     src_idx_caches_by_tft: Vec<SynSrcIdxCache>,
     syn_src_slices: SynSrcSlices,
     rng: SmallRng,
 
-    states: Buffer<u8>,
+    states: Storage<u8>,
     // TODO: Switch to `u8` (`uchar`):
-    strengths: Buffer<i8>,
-    src_slc_ids: Buffer<SlcId>,
-    src_col_v_offs: Buffer<SrcOfs>,
-    src_col_u_offs: Buffer<SrcOfs>,
-    flag_sets: Buffer<u8>,
+    strengths: Storage<i8>,
+    src_slc_ids: Storage<SlcId>,
+    src_col_v_offs: Storage<SrcOfs>,
+    src_col_u_offs: Storage<SrcOfs>,
+    flag_sets: Storage<u8>,
 
     // TODO: Switch to `u8` (`uchar`):
     vec_strengths: Vec<i8>,
@@ -130,22 +143,42 @@ pub struct Synapses {
     syn_idzs_by_tft: Vec<u32>,
     syn_counts_by_tft: Vec<u32>,
     tft_dims_by_tft: Vec<TuftDims>,
+    den_kinds_by_tft: Vec<DendriteKind>,
+    learning_params: LearningParams,
 
     exe_cmd_uid_flags: Option<CommandUid>,
     exe_cmd_idx_flags: usize,
     exe_cmd_uids_cycle: Vec<CommandUid>,
     exe_cmd_idxs_cycle: Vec<usize>,
     bypass_exe_graph: bool,
+
+    host_kernels: bool,
+    axn_states: Storage<u8>,
+    axn_slc_geo: AxonSliceGeometry,
+
+    regrow_cursor: usize,
+    regrow_pending: Option<Range<usize>>,
+    regrow_read_events: EventList,
+    regrow_wait_events: EventList,
+    regrow_write_events: EventList,
+    regrow_wait_cmd_idxs: Option<Vec<usize>>,
+
+    device_regrowth: bool,
+    kernels_regrow: Vec<Kernel>,
+    regrow_src_pools: Vec<Storage<i32>>,
+    regrow_src_offs: Vec<Storage<SrcOfs>>,
 }
 
 impl Synapses {
     pub fn new<S: Into<String>>(layer_name: S, layer_id: usize, dims: CorticalDims,
             cell_scheme: CellScheme,
             area_map: &AreaMap, axons: &AxonSpace,
-            ocl_pq: &ProQue, bypass_exe_graph: bool, exe_graph: &mut ExecutionGraph)
+            backend: &Backend, bypass_exe_graph: bool, host_kernels: bool, device_regrowth: bool,
+            learning_params: LearningParams, seed: Option<u64>, exe_graph: &mut ExecutionGraph)
             -> CmnResult<Synapses> {
         let layer_name = layer_name.into();
-        let syn_src_slices = SynSrcSlices::new(layer_id, cell_scheme.tft_schemes(), area_map)?;
+        let mut syn_src_slices = SynSrcSlices::new(layer_id, cell_scheme.tft_schemes(), area_map)?;
+        syn_src_slices.set_str_deviation(learning_params.syn_strength_initial_deviation);
 
         let tft_count = cell_scheme.tft_count();
         let layer_addr = LayerAddress::new(area_map.area_id(), layer_id);
@@ -155,6 +188,7 @@ impl Synapses {
         let mut syn_idzs_by_tft = Vec::with_capacity(tft_count);
         let mut syn_counts_by_tft = Vec::with_capacity(tft_count);
         let mut tft_dims_by_tft = Vec::with_capacity(tft_count);
+        let mut den_kinds_by_tft = Vec::with_capacity(tft_count);
         let mut exe_cmd_uid_flags = None;
         let exe_cmd_idx_flags = 0;
         let mut exe_cmd_uids_cycle = Vec::with_capacity(tft_count);
@@ -183,6 +217,7 @@ impl Synapses {
                 tft_scheme.syns_per_den());
 
             tft_dims_by_tft.push(tft_dims.clone());
+            den_kinds_by_tft.push(tft_scheme.den_kind());
 
             let is_saturated = syn_src_slices.by_tft()[tft_id].is_saturated();
 
@@ -203,12 +238,12 @@ impl Synapses {
         =============================================================================*/
 
         // let slc_pool = Buffer::with_vec(cmn::SYNAPSE_ROW_POOL_SIZE, 0, ocl_pq); // BRING THIS BACK
-        let states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([syn_count_ttl]).fill_val(0).build()?;
-        let strengths = Buffer::<i8>::builder().queue(ocl_pq.queue().clone()).len([syn_count_ttl]).fill_val(0).build()?;
-        let src_slc_ids = Buffer::<SlcId>::builder().queue(ocl_pq.queue().clone()).len([syn_count_ttl]).fill_val(0).build()?;
-        let src_col_v_offs = Buffer::<SrcOfs>::builder().queue(ocl_pq.queue().clone()).len([syn_count_ttl]).fill_val(0).build()?;
-        let src_col_u_offs = Buffer::<SrcOfs>::builder().queue(ocl_pq.queue().clone()).len([syn_count_ttl]).fill_val(0).build()?;
-        let flag_sets = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([syn_count_ttl]).fill_val(0).build()?;
+        let states = backend.buffer::<u8>(syn_count_ttl as usize, 0)?;
+        let strengths = backend.buffer::<i8>(syn_count_ttl as usize, 0)?;
+        let src_slc_ids = backend.buffer::<SlcId>(syn_count_ttl as usize, 0)?;
+        let src_col_v_offs = backend.buffer::<SrcOfs>(syn_count_ttl as usize, 0)?;
+        let src_col_u_offs = backend.buffer::<SrcOfs>(syn_count_ttl as usize, 0)?;
+        let flag_sets = backend.buffer::<u8>(syn_count_ttl as usize, 0)?;
 
         debug_assert!(strengths.len() == src_slc_ids.len() &&
             strengths.len() == src_col_v_offs.len() &&
@@ -220,11 +255,14 @@ impl Synapses {
 
         // Sets the `SYN_PREV_ACTIVE_FLAG` bit.
         let kern_name_flags = "tft_set_syn_flags";
-        let kernel_flags = ocl_pq.kernel_builder(kern_name_flags)
+        let kernel_flags = match backend.pq() {
+            Some(ocl_pq) => Some(ocl_pq.kernel_builder(kern_name_flags)
                 .global_work_size(syn_count_ttl)
-                .arg(&states)
-                .arg(&flag_sets)
-                .build()?;
+                .arg(states.device()?)
+                .arg(flag_sets.device()?)
+                .build()?),
+            None => None,
+        };
 
         if !bypass_exe_graph {
             let cmd_srcs: Vec<CorticalBuffer> = cell_scheme.tft_schemes().iter().enumerate()
@@ -268,22 +306,24 @@ impl Synapses {
                 "layer_cycle_syns_wow"
             };
 
-            kernels_cycle.push(
-                ocl_pq.kernel_builder(kern_name)
-                    .global_work_size(SpatialDims::Two(dims.v_size() as usize, (dims.u_size()) as usize))
-                    .local_work_size(SpatialDims::Two(min_wg_sqrt, min_wg_sqrt))
-                    .arg(axons.states())
-                    .arg(&src_col_u_offs)
-                    .arg(&src_col_v_offs)
-                    .arg(&src_slc_ids)
-                    .arg(&tft_syn_idz)
-                    .arg(&syns_per_tft)
-                    .arg(&(dims.depth() as SlcId))
-                    .arg_named("aux_ints_0", None::<&Buffer<i32>>)
-                    .arg_named("aux_ints_1", None::<&Buffer<i32>>)
-                    .arg(&states)
-                    .build()?
-            );
+            if let Some(ocl_pq) = backend.pq() {
+                kernels_cycle.push(
+                    ocl_pq.kernel_builder(kern_name)
+                        .global_work_size(SpatialDims::Two(dims.v_size() as usize, (dims.u_size()) as usize))
+                        .local_work_size(SpatialDims::Two(min_wg_sqrt, min_wg_sqrt))
+                        .arg(axons.states().device()?)
+                        .arg(src_col_u_offs.device()?)
+                        .arg(src_col_v_offs.device()?)
+                        .arg(src_slc_ids.device()?)
+                        .arg(&tft_syn_idz)
+                        .arg(&syns_per_tft)
+                        .arg(&(dims.depth() as SlcId))
+                        .arg_named("aux_ints_0", None::<&Buffer<i32>>)
+                        .arg_named("aux_ints_1", None::<&Buffer<i32>>)
+                        .arg(states.device()?)
+                        .build()?
+                );
+            }
 
             if !bypass_exe_graph {
                 let mut cmd_srcs: Vec<CorticalBuffer> = syn_src_slices.by_tft()[tft_id]
@@ -305,6 +345,58 @@ impl Synapses {
         ===============================================================================
         =============================================================================*/
 
+        let mut kernels_regrow = Vec::new();
+        let mut regrow_src_pools = Vec::new();
+        let mut regrow_src_offs = Vec::new();
+
+        if device_regrowth {
+            for (tft_id, (tft_scheme, &tft_syn_idz)) in cell_scheme.tft_schemes().iter()
+                    .zip(syn_idzs_by_tft.iter())
+                    .enumerate() {
+                let src_slice = &syn_src_slices.by_tft()[tft_id];
+                let (pool_vec, mut offs_vec) = src_slice.regrowth_pool();
+                let pool_len = (pool_vec.len() / host_kernels::SYN_SRC_POOL_ENTRY_LEN) as u32;
+
+                // Buffers may not be empty (nonspatial sources have no offsets):
+                if offs_vec.is_empty() { offs_vec = vec![0; 2]; }
+
+                let src_pool = backend.buffer_from(&pool_vec)?;
+                let src_offs = backend.buffer_from(&offs_vec)?;
+
+                if let Some(ocl_pq) = backend.pq() {
+                    kernels_regrow.push(ocl_pq.kernel_builder("tft_regrow_syns")
+                        .arg(src_pool.device()?)
+                        .arg(src_offs.device()?)
+                        .arg(&pool_len)
+                        .arg(&tft_syn_idz)
+                        .arg(&tft_scheme.syns_per_den())
+                        .arg(&dims.columns())
+                        .arg(&dims.u_size())
+                        .arg(&(src_slice.is_saturated() as u8))
+                        .arg_named("str_floor", &learning_params.syn_strength_floor)
+                        .arg_named("str_dev", &learning_params.syn_strength_initial_deviation)
+                        .arg_named("str_default", &str_default(tft_scheme.den_kind(),
+                            &learning_params))
+                        .arg_named("den_id_tft_z", &0u32)
+                        .arg_named("syn_idz", &0u32)
+                        .arg_named("syn_idn", &0u32)
+                        .arg_named("rnd_seed", &0u32)
+                        .arg(strengths.device()?)
+                        .arg(src_slc_ids.device()?)
+                        .arg(src_col_v_offs.device()?)
+                        .arg(src_col_u_offs.device()?)
+                        .build()?);
+                }
+
+                regrow_src_pools.push(src_pool);
+                regrow_src_offs.push(src_offs);
+            }
+        }
+
+        /*=============================================================================
+        ===============================================================================
+        =============================================================================*/
+
         // These are for learning (to avoid allocating it every time).
         let vec_strengths = vec![0; strengths.len()];
         let vec_src_slc_ids = vec![0; src_slc_ids.len()];
@@ -314,12 +406,14 @@ impl Synapses {
         let mut syns = Synapses {
             layer_name: layer_name,
             layer_id: layer_id,
+            layer_addr,
             dims: dims,
             kernel_flags,
             kernels_cycle,
             src_idx_caches_by_tft: src_idx_caches_by_tft,
             syn_src_slices: syn_src_slices,
-            rng: SmallRng::from_entropy(),
+            rng: cmn::seeded_rng(seed, "synapses", &[layer_addr.area_id() as u64,
+                layer_addr.layer_id() as u64]),
             states: states,
             strengths: strengths,
             src_slc_ids: src_slc_ids,
@@ -333,19 +427,36 @@ impl Synapses {
             syn_counts_by_tft: syn_counts_by_tft,
             syn_idzs_by_tft: syn_idzs_by_tft,
             tft_dims_by_tft: tft_dims_by_tft,
+            den_kinds_by_tft,
+            learning_params,
             exe_cmd_uid_flags,
             exe_cmd_idx_flags,
             exe_cmd_uids_cycle,
             exe_cmd_idxs_cycle,
             bypass_exe_graph,
+            host_kernels,
+            axn_states: axons.states().clone(),
+            axn_slc_geo: AxonSliceGeometry::new(area_map.slice_map()),
+            regrow_cursor: 0,
+            regrow_pending: None,
+            regrow_read_events: EventList::new(),
+            regrow_wait_events: EventList::new(),
+            regrow_write_events: EventList::new(),
+            regrow_wait_cmd_idxs: None,
+            device_regrowth,
+            kernels_regrow,
+            regrow_src_pools,
+            regrow_src_offs,
         };
 
-        syns.grow(true);
+        syns.grow(true)?;
 
         Ok(syns)
     }
 
     pub fn set_exe_order(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        self.regrow_wait_cmd_idxs = None;
+
         if !self.bypass_exe_graph {
             // Flags kernel:
             self.exe_cmd_idx_flags = exe_graph.order_command(self.exe_cmd_uid_flags.unwrap())?;
@@ -360,11 +471,20 @@ impl Synapses {
         Ok(())
     }
 
-    pub fn cycle(&self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+    /// Applies any pending regrowth chunk then enqueues the cycle kernels.
+    pub fn cycle(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        self.regrow_apply()?;
+
+        if self.host_kernels {
+            self.cycle_host(exe_graph)?;
+            self.regrow_write_events.clear();
+            return Ok(());
+        }
+
         // Flags kernel:
         let mut event = Event::empty();
         unsafe {
-            self.kernel_flags.cmd()
+            device_kernel(&self.kernel_flags)?.cmd()
                 .ewait(exe_graph.get_req_events(self.exe_cmd_idx_flags)?)
                 .enew(&mut event)
                 .enq()?;
@@ -376,19 +496,282 @@ impl Synapses {
             if PRNT { printlnc!(white: "    Syns: Enqueuing kernel: '{}' \
                 (exe_cmd_idx: [{}])...", kern.name()?, cmd_idx); }
 
+            // Wait for any regrowth writes in addition to the requisite
+            // commands (all later readers of the source and strength
+            // buffers depend on these kernels):
             let mut event = Event::empty();
-            unsafe { kern.cmd().ewait(exe_graph.get_req_events(cmd_idx)?).enew(&mut event).enq()?; }
+            unsafe {
+                let mut wait_list = exe_graph.get_req_events(cmd_idx)?.to_vec();
+                wait_list.extend(self.regrow_write_events.iter().map(|ev| *ev.as_ptr_ref()));
+                kern.cmd().ewait(&wait_list[..]).enew(&mut event).enq()?;
+            }
             exe_graph.set_cmd_event(cmd_idx, Some(event))?;
 
             if PRNT { kern.default_queue().unwrap().finish().unwrap(); }
         }
 
+        self.regrow_write_events.clear();
         Ok(())
     }
 
-    #[inline]
-    pub fn regrow(&mut self) {
-        self.grow(false);
+    /// Cycles synapses using the host kernels.
+    fn cycle_host(&self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        // Flags:
+        let mut states = host_kernels::read(&self.states)?;
+        let mut flag_sets = host_kernels::read(&self.flag_sets)?;
+        host_kernels::tft_set_syn_flags(&states, &mut flag_sets);
+        host_kernels::write_range(&self.flag_sets, &flag_sets, 0..flag_sets.len())?;
+        exe_graph.set_cmd_event(self.exe_cmd_idx_flags, None)?;
+
+        let src_col_u_offs = host_kernels::read(&self.src_col_u_offs)?;
+        let src_col_v_offs = host_kernels::read(&self.src_col_v_offs)?;
+        let src_slc_ids = host_kernels::read(&self.src_slc_ids)?;
+
+        // Cycle:
+        for (tft_id, &cmd_idx) in self.exe_cmd_idxs_cycle.iter().enumerate() {
+            let axn_states = host_kernels::read(&self.axn_states)?;
+            let syn_idz = self.syn_idzs_by_tft[tft_id];
+            let syns_per_tft = self.tft_dims_by_tft[tft_id].syns_per_tft();
+
+            host_kernels::tft_cycle_syns(&axn_states, &self.axn_slc_geo, &src_col_u_offs,
+                &src_col_v_offs, &src_slc_ids, syn_idz, syns_per_tft, self.dims.depth(),
+                self.dims.v_size(), self.dims.u_size(), &mut states);
+
+            let syn_idn = syn_idz + self.syn_counts_by_tft[tft_id];
+            host_kernels::write_range(&self.states, &states, syn_idz as usize..syn_idn as usize)?;
+            exe_graph.set_cmd_event(cmd_idx, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the indexes of every command in the execution graph which
+    /// reads from or writes to the source or strength buffers of this layer.
+    fn find_regrow_wait_cmd_idxs(&self, exe_graph: &ExecutionGraph) -> Vec<usize> {
+        let blocks: Vec<MemoryBlock> = (0..self.tft_count()).flat_map(|tft_id| vec![
+                MemoryBlock::CorticalBuffer(CorticalBuffer::data_syn_tft(&self.strengths,
+                    self.layer_addr, tft_id)),
+                MemoryBlock::CorticalBuffer(CorticalBuffer::data_syn_tft(&self.src_slc_ids,
+                    self.layer_addr, tft_id)),
+            ])
+            .collect();
+
+        (0..exe_graph.cmd_count()).filter(|&cmd_idx| {
+            exe_graph.cmd_relations(cmd_idx).map(|rels| {
+                rels.sources().iter().chain(rels.targets().iter()).any(|blk| blocks.contains(blk))
+            }).unwrap_or(false)
+        }).collect()
+    }
+
+    /// Queues the next chunk of up to `chunk_size` synapses for regrowth.
+    ///
+    /// The chunk's strengths and sources are read back to the host without
+    /// blocking, once every command of the current cycle using them
+    /// (including any sampler reads) has completed. Synapses within the
+    /// chunk which are at or below `LearningParams::syn_strength_floor` are
+    /// then regrown and written back at the start of the next cycle, again
+    /// only once those same commands have completed (see `::cycle`). Must
+    /// therefore be called after all of the current cycle's commands,
+    /// samplers included, have been enqueued.
+    ///
+    /// When device regrowth is enabled, the chunk is instead regrown on the
+    /// device, once those same commands have completed, and nothing is left
+    /// pending (see `::regrow_device`).
+    ///
+    /// A `chunk_size` of `None` regrows `ceil(len / syn_regrowth_interval)`
+    /// synapses each cycle (see `LearningParams::syn_regrowth_interval`), so
+    /// that every synapse is considered once per interval. Does nothing if a
+    /// chunk is already pending.
+    pub fn regrow(&mut self, chunk_size: Option<usize>, exe_graph: &ExecutionGraph)
+            -> CmnResult<()> {
+        if self.regrow_pending.is_some() { return Ok(()); }
+
+        let len = self.len();
+        let interval = self.learning_params.syn_regrowth_interval;
+        let chunk_size = chunk_size.unwrap_or((len + interval - 1) / interval).max(1);
+        let range = self.regrow_cursor..(self.regrow_cursor + chunk_size).min(len);
+        self.regrow_cursor = if range.end == len { 0 } else { range.end };
+
+        if self.regrow_wait_cmd_idxs.is_none() {
+            self.regrow_wait_cmd_idxs = Some(self.find_regrow_wait_cmd_idxs(exe_graph));
+        }
+
+        let mut wait_events = EventList::new();
+        if let Some(ref cmd_idxs) = self.regrow_wait_cmd_idxs {
+            for &cmd_idx in cmd_idxs.iter() {
+                if let Some(event) = exe_graph.cmd(cmd_idx).and_then(|cmd| cmd.event()) {
+                    wait_events.push(event.clone());
+                }
+            }
+        }
+
+        if self.device_regrowth { return self.regrow_device(range, wait_events); }
+
+        if self.host_kernels {
+            // Host storage leaves no events and is read immediately:
+            self.strengths.read_into(range.start, &mut self.vec_strengths[range.clone()])?;
+            self.src_slc_ids.read_into(range.start, &mut self.vec_src_slc_ids[range.clone()])?;
+            self.src_col_v_offs.read_into(range.start, &mut self.vec_src_col_v_offs[range.clone()])?;
+            self.src_col_u_offs.read_into(range.start, &mut self.vec_src_col_u_offs[range.clone()])?;
+        } else {
+            unsafe {
+                self.strengths.device()?.cmd().read(&mut self.vec_strengths[range.clone()])
+                    .offset(range.start).block(false).ewait(&wait_events)
+                    .enew(&mut self.regrow_read_events).enq()?;
+                self.src_slc_ids.device()?.cmd().read(&mut self.vec_src_slc_ids[range.clone()])
+                    .offset(range.start).block(false).ewait(&wait_events)
+                    .enew(&mut self.regrow_read_events).enq()?;
+                self.src_col_v_offs.device()?.cmd().read(&mut self.vec_src_col_v_offs[range.clone()])
+                    .offset(range.start).block(false).ewait(&wait_events)
+                    .enew(&mut self.regrow_read_events).enq()?;
+                self.src_col_u_offs.device()?.cmd().read(&mut self.vec_src_col_u_offs[range.clone()])
+                    .offset(range.start).block(false).ewait(&wait_events)
+                    .enew(&mut self.regrow_read_events).enq()?;
+            }
+        }
+
+        self.regrow_wait_events = wait_events;
+        self.regrow_pending = Some(range);
+        Ok(())
+    }
+
+    /// Regrows every synapse within `syn_range` at or below
+    /// `LearningParams::syn_strength_floor` using the `tft_regrow_syns` kernel
+    /// once `wait_events`, and any earlier regrowth, have completed (or
+    /// immediately, using its host reference, when host kernels are enabled).
+    ///
+    /// The cycle kernels of the next cycle wait for the regrowth to complete
+    /// (see `::cycle`).
+    fn regrow_device(&mut self, syn_range: Range<usize>, mut wait_events: EventList)
+            -> CmnResult<()> {
+        let rnd_seed = self.rng.gen::<u32>();
+        for event in self.regrow_write_events.iter() {
+            wait_events.push(event.clone());
+        }
+
+        if self.host_kernels {
+            return self.regrow_device_host(syn_range, rnd_seed);
+        }
+
+        for tft_id in 0..self.tft_count() {
+            let (den_id_tft_z, den_count, syn_idz, syn_idn) =
+                match self.regrow_tft_range(tft_id, &syn_range) {
+                    Some(tft_range) => tft_range,
+                    None => continue,
+                };
+
+            {
+                let kern = &mut self.kernels_regrow[tft_id];
+                kern.set_arg("den_id_tft_z", den_id_tft_z)?;
+                kern.set_arg("syn_idz", syn_idz)?;
+                kern.set_arg("syn_idn", syn_idn)?;
+                kern.set_arg("rnd_seed", rnd_seed)?;
+            }
+
+            unsafe {
+                self.kernels_regrow[tft_id].cmd()
+                    .gws(den_count as usize)
+                    .ewait(&wait_events)
+                    .enew(&mut self.regrow_write_events)
+                    .enq()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Regrows `syn_range` with `host_kernels::tft_regrow_syns` (blocking).
+    fn regrow_device_host(&mut self, syn_range: Range<usize>, rnd_seed: u32) -> CmnResult<()> {
+        let mut strengths = host_kernels::read(&self.strengths)?;
+        let mut src_slc_ids = host_kernels::read(&self.src_slc_ids)?;
+        let mut src_col_v_offs = host_kernels::read(&self.src_col_v_offs)?;
+        let mut src_col_u_offs = host_kernels::read(&self.src_col_u_offs)?;
+
+        for tft_id in 0..self.tft_count() {
+            let (den_id_tft_z, den_count, syn_idz, syn_idn) =
+                match self.regrow_tft_range(tft_id, &syn_range) {
+                    Some(tft_range) => tft_range,
+                    None => continue,
+                };
+
+            let src_pool = host_kernels::read(&self.regrow_src_pools[tft_id])?;
+            let src_offs = host_kernels::read(&self.regrow_src_offs[tft_id])?;
+            let is_saturated = self.syn_src_slices.by_tft()[tft_id].is_saturated();
+            let params = &self.learning_params;
+
+            host_kernels::tft_regrow_syns(&src_pool, &src_offs, self.syn_idzs_by_tft[tft_id],
+                self.tft_dims_by_tft[tft_id].syns_per_den(), self.dims.columns(),
+                self.dims.u_size(), is_saturated, params.syn_strength_floor,
+                params.syn_strength_initial_deviation,
+                str_default(self.den_kinds_by_tft[tft_id], params), den_id_tft_z, den_count,
+                syn_idz, syn_idn, rnd_seed, &mut strengths, &mut src_slc_ids,
+                &mut src_col_v_offs, &mut src_col_u_offs);
+        }
+
+        host_kernels::write_range(&self.strengths, &strengths, syn_range.clone())?;
+        host_kernels::write_range(&self.src_slc_ids, &src_slc_ids, syn_range.clone())?;
+        host_kernels::write_range(&self.src_col_v_offs, &src_col_v_offs, syn_range.clone())?;
+        host_kernels::write_range(&self.src_col_u_offs, &src_col_u_offs, syn_range)?;
+        Ok(())
+    }
+
+    /// Returns the first dendrite id and the dendrite count (within the tuft)
+    /// along with the synapse index range of the portion of `syn_range`
+    /// belonging to a tuft, if any.
+    fn regrow_tft_range(&self, tft_id: usize, syn_range: &Range<usize>)
+            -> Option<(u32, u32, u32, u32)> {
+        let tft_syn_idz = self.syn_idzs_by_tft[tft_id];
+        let tft_syn_idn = tft_syn_idz + self.syn_counts_by_tft[tft_id];
+        let syn_idz = tft_syn_idz.max(syn_range.start as u32);
+        let syn_idn = tft_syn_idn.min(syn_range.end as u32);
+        if syn_idz >= syn_idn { return None; }
+
+        let syns_per_den = self.tft_dims_by_tft[tft_id].syns_per_den();
+        let den_id_tft_z = (syn_idz - tft_syn_idz) / syns_per_den;
+        let den_id_tft_n = (syn_idn - tft_syn_idz + syns_per_den - 1) / syns_per_den;
+        Some((den_id_tft_z, den_id_tft_n - den_id_tft_z, syn_idz, syn_idn))
+    }
+
+    /// Regrows the pending chunk (if any) and queues its sources and
+    /// strengths to be written back to the device.
+    ///
+    /// Blocks until the pending chunk has been read, which normally
+    /// completes while the remainder of the previous cycle executes.
+    fn regrow_apply(&mut self) -> CmnResult<()> {
+        let range = match self.regrow_pending.take() {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+
+        self.regrow_read_events.wait_for()?;
+        self.regrow_read_events.clear();
+        self.grow_range(range.clone(), false);
+
+        if self.host_kernels {
+            self.strengths.write_at(&self.vec_strengths[range.clone()], range.start)?;
+            self.src_slc_ids.write_at(&self.vec_src_slc_ids[range.clone()], range.start)?;
+            self.src_col_v_offs.write_at(&self.vec_src_col_v_offs[range.clone()], range.start)?;
+            self.src_col_u_offs.write_at(&self.vec_src_col_u_offs[range.clone()], range.start)?;
+        } else {
+            // The reads already waited for the commands (samplers included)
+            // which used the chunk during the previous cycle but the writes
+            // must not rely on that:
+            unsafe {
+                self.strengths.device()?.cmd().write(&self.vec_strengths[range.clone()])
+                    .offset(range.start).block(false).ewait(&self.regrow_wait_events)
+                    .enew(&mut self.regrow_write_events).enq()?;
+                self.src_slc_ids.device()?.cmd().write(&self.vec_src_slc_ids[range.clone()])
+                    .offset(range.start).block(false).ewait(&self.regrow_wait_events)
+                    .enew(&mut self.regrow_write_events).enq()?;
+                self.src_col_v_offs.device()?.cmd().write(&self.vec_src_col_v_offs[range.clone()])
+                    .offset(range.start).block(false).ewait(&self.regrow_wait_events)
+                    .enew(&mut self.regrow_write_events).enq()?;
+                self.src_col_u_offs.device()?.cmd().write(&self.vec_src_col_u_offs[range.clone()])
+                    .offset(range.start).block(false).ewait(&self.regrow_wait_events)
+                    .enew(&mut self.regrow_write_events).enq()?;
+            }
+        }
+        self.regrow_wait_events.clear();
+        Ok(())
     }
 
     // Debugging purposes
@@ -408,12 +791,18 @@ impl Synapses {
 
     // [FIXME] TODO: VERIFY AXON INDEX SAFETY (notes below and in syn_src_map.rs).
     // - Will need to know u and v coords of host cell or deconstruct from syn_idx.
+    //
+    // [NOTE]: Retries until a unique source is found. Device regrowth
+    // (`tft_regrow_syns`) instead gives up after `SYN_REGROW_ATTEMPTS_MAX`
+    // attempts, leaving the synapse at the floor until its next pass.
     fn regrow_syn(&mut self, syn_idx: usize, tft_id: usize, _: bool) {
         debug_assert!(syn_idx < self.src_slc_ids.len());
         debug_assert!(syn_idx < self.src_col_v_offs.len());
         debug_assert!(syn_idx < self.src_col_u_offs.len());
         debug_assert!(tft_id < self.src_idx_caches_by_tft.len());
 
+        let strength_default = str_default(self.den_kinds_by_tft[tft_id], &self.learning_params);
+
         loop {
             let old_src = unsafe { SynSrc {
                 slc_id: *self.vec_src_slc_ids.get_unchecked(syn_idx),
@@ -434,7 +823,8 @@ impl Synapses {
                     *self.vec_src_slc_ids.get_unchecked_mut(syn_idx) = new_src.slc_id;
                     *self.vec_src_col_v_offs.get_unchecked_mut(syn_idx) = new_src.v_ofs;
                     *self.vec_src_col_u_offs.get_unchecked_mut(syn_idx) = new_src.u_ofs;
-                    *self.vec_strengths.get_unchecked_mut(syn_idx) = new_src.strength;
+                    *self.vec_strengths.get_unchecked_mut(syn_idx) =
+                        new_src.strength.saturating_add(strength_default);
                 }
 
                 if DEBUG_GROW && DEBUG_REGROW_DETAIL { print!("$"); }
@@ -445,30 +835,42 @@ impl Synapses {
         }
     }
 
-    // [FIXME]: THIS IS A PERFORMANCE NIGHTMARE (since we have to stop the
-    // world to do it).
-    // - SET UP AN EVENTLIST.
-    // - BREAK THIS DOWN INTO PIECES.
-    // - PROCESS SMALLER CHUNKS MORE FREQUENTLY.
-    //
-    fn grow(&mut self, init: bool) {
+    /// Grows every synapse (blocking). Only used during initialization.
+    fn grow(&mut self, init: bool) -> CmnResult<()> {
+        // Fill our vectors with fresh data;
+        self.strengths.read_into(0, &mut self.vec_strengths)?;
+        self.src_slc_ids.read_into(0, &mut self.vec_src_slc_ids)?;
+        self.src_col_v_offs.read_into(0, &mut self.vec_src_col_v_offs)?;
+        self.src_col_u_offs.read_into(0, &mut self.vec_src_col_u_offs)?;
+
+        let len = self.len();
+        self.grow_range(0..len, init);
+
+        self.strengths.write_at(&self.vec_strengths, 0)?;
+        self.src_slc_ids.write_at(&self.vec_src_slc_ids, 0)?;
+        self.src_col_v_offs.write_at(&self.vec_src_col_v_offs, 0)?;
+        self.src_col_u_offs.write_at(&self.vec_src_col_u_offs, 0)
+    }
+
+    /// Grows (if `init` is set) or regrows every synapse within `syn_range`
+    /// using the host-side source and strength vectors.
+    fn grow_range(&mut self, syn_range: Range<usize>, init: bool) {
         if DEBUG_GROW && DEBUG_REGROW_DETAIL && !init {
             println!("REGROW: [PRE:(SLICE)(OFFSET)(STRENGTH)=>($:UNIQUE, ^:DUPL)=>POST:\
                 (SLICE)(OFFSET)(STRENGTH)]\n");
         }
 
-        // Fill our vectors with fresh data;
-        self.strengths.cmd().read(&mut self.vec_strengths).enq().unwrap();
-        self.src_slc_ids.cmd().read(&mut self.vec_src_slc_ids).enq().unwrap();
-        self.src_col_v_offs.cmd().read(&mut self.vec_src_col_v_offs).enq().unwrap();
-        self.src_col_u_offs.cmd().read(&mut self.vec_src_col_u_offs).enq().unwrap();
-
         let tft_count = self.syn_idzs_by_tft.len();
         debug_assert!(tft_count == self.syn_counts_by_tft.len());
+        let str_floor = self.learning_params.syn_strength_floor;
 
         for tft_id in 0..tft_count {
-            let syn_idz = unsafe { *self.syn_idzs_by_tft.get_unchecked(tft_id) as usize };
-            let syn_idn = unsafe { syn_idz + *self.syn_counts_by_tft.get_unchecked(tft_id) as usize };
+            let tft_syn_idz = unsafe { *self.syn_idzs_by_tft.get_unchecked(tft_id) as usize };
+            let tft_syn_idn = unsafe { tft_syn_idz +
+                *self.syn_counts_by_tft.get_unchecked(tft_id) as usize };
+            let syn_idz = tft_syn_idz.max(syn_range.start);
+            let syn_idn = tft_syn_idn.min(syn_range.end);
+            if syn_idz >= syn_idn { continue; }
 
             if DEBUG_GROW && init {
                 // NOTE: Not sure if this is what we want to see:
@@ -486,33 +888,123 @@ impl Synapses {
                 debug_assert!(syn_idx < self.vec_strengths.len());
 
                 if init || (unsafe { *self.vec_strengths
-                        .get_unchecked(syn_idx) } <= cmn::SYNAPSE_STRENGTH_FLOOR)
+                        .get_unchecked(syn_idx) } <= str_floor)
                 {
                     self.regrow_syn(syn_idx, tft_id, init);
                 }
             }
 
         }
+    }
+
+    /// Sets the parameters used by all subsequent regrowth.
+    ///
+    /// Any chunk already queued is regrown using the new parameters.
+    pub fn set_learning_params(&mut self, params: &LearningParams) -> CmnResult<()> {
+        params.validate()?;
+        self.syn_src_slices.set_str_deviation(params.syn_strength_initial_deviation);
+
+        for (kern, &den_kind) in self.kernels_regrow.iter_mut().zip(self.den_kinds_by_tft.iter()) {
+            kern.set_arg("str_floor", params.syn_strength_floor)?;
+            kern.set_arg("str_dev", params.syn_strength_initial_deviation)?;
+            kern.set_arg("str_default", str_default(den_kind, params))?;
+        }
 
-        self.strengths.cmd().write(&self.vec_strengths).enq().unwrap();
-        self.src_slc_ids.cmd().write(&self.vec_src_slc_ids).enq().unwrap();
-        self.src_col_v_offs.cmd().write(&self.vec_src_col_v_offs).enq().unwrap();
-        self.src_col_u_offs.cmd().write(&self.vec_src_col_u_offs).enq().unwrap();
+        self.learning_params = *params;
+        Ok(())
+    }
+
+    /// Writes all synapse buffers, the regrowth position, and the regrowth
+    /// rng seed to a snapshot.
+    ///
+    /// All queues must have finished.
+    pub fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_buffer("syns.states", &self.states)?;
+        wtr.write_buffer("syns.strengths", &self.strengths)?;
+        wtr.write_buffer("syns.src_slc_ids", &self.src_slc_ids)?;
+        wtr.write_buffer("syns.src_col_v_offs", &self.src_col_v_offs)?;
+        wtr.write_buffer("syns.src_col_u_offs", &self.src_col_u_offs)?;
+        wtr.write_buffer("syns.flag_sets", &self.flag_sets)?;
+        wtr.write_u64(self.regrow_cursor as u64)?;
+        let pending = self.regrow_pending.clone().unwrap_or(0..0);
+        wtr.write_u64(pending.start as u64)?;
+        wtr.write_u64(pending.end as u64)?;
+        wtr.write_rng("syns.rng", &self.rng)
+    }
+
+    /// Restores all synapse buffers, the regrowth position, and the regrowth
+    /// rng seed from a snapshot then rebuilds the source index caches used
+    /// for duplicate detection.
+    ///
+    /// All queues must have finished.
+    pub fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_buffer("syns.states", &self.states)?;
+        rdr.read_buffer("syns.strengths", &self.strengths)?;
+        rdr.read_buffer("syns.src_slc_ids", &self.src_slc_ids)?;
+        rdr.read_buffer("syns.src_col_v_offs", &self.src_col_v_offs)?;
+        rdr.read_buffer("syns.src_col_u_offs", &self.src_col_u_offs)?;
+        rdr.read_buffer("syns.flag_sets", &self.flag_sets)?;
+        let cursor = rdr.read_u64()? as usize;
+        let pending = (rdr.read_u64()? as usize)..(rdr.read_u64()? as usize);
+        rdr.read_rng("syns.rng", &mut self.rng)?;
+
+        if cursor > self.len() || pending.end > self.len() || pending.start > pending.end {
+            return Err(format!("Synapses::load_state: Invalid regrowth position for layer \
+                '{}' (cursor: {}, pending: {:?}, synapses: {}).", self.layer_name, cursor,
+                pending, self.len()).into());
+        }
+        self.regrow_cursor = cursor;
+        self.regrow_pending = if pending.start < pending.end { Some(pending) } else { None };
+        self.regrow_read_events.clear();
+        self.regrow_write_events.clear();
+
+        // The pending chunk's strengths are read along with all sources:
+        self.strengths.read_into(0, &mut self.vec_strengths)?;
+        self.src_slc_ids.read_into(0, &mut self.vec_src_slc_ids)?;
+        self.src_col_v_offs.read_into(0, &mut self.vec_src_col_v_offs)?;
+        self.src_col_u_offs.read_into(0, &mut self.vec_src_col_u_offs)?;
+
+        for (tft_id, cache) in self.src_idx_caches_by_tft.iter_mut().enumerate() {
+            let syn_idz = self.syn_idzs_by_tft[tft_id] as usize;
+            let syn_idn = syn_idz + self.syn_counts_by_tft[tft_id] as usize;
+            cache.clear();
+
+            for syn_idx in syn_idz..syn_idn {
+                cache.insert_existing(syn_idx, &SynSrc {
+                    slc_id: self.vec_src_slc_ids[syn_idx],
+                    v_ofs: self.vec_src_col_v_offs[syn_idx],
+                    u_ofs: self.vec_src_col_u_offs[syn_idx],
+                    strength: 0,
+                });
+            }
+        }
+        Ok(())
     }
 
     #[inline] pub fn len(&self) -> usize { self.states.len() }
     #[inline] pub fn layer_id(&self) -> usize { self.layer_id }
     #[inline] pub fn lyr_dims(&self) -> &CorticalDims { &self.dims }
-    #[inline] pub fn states(&self) -> &Buffer<u8> { &self.states }
-    #[inline] pub fn strengths(&self) -> &Buffer<i8> { &self.strengths }
-    #[inline] pub fn src_slc_ids(&self) -> &Buffer<SlcId> { &self.src_slc_ids }
-    #[inline] pub fn src_col_v_offs(&self) -> &Buffer<SrcOfs> { &self.src_col_v_offs }
-    #[inline] pub fn src_col_u_offs(&self) -> &Buffer<SrcOfs> { &self.src_col_u_offs }
-    #[inline] pub fn flag_sets(&self) -> &Buffer<u8> { &self.flag_sets }
+    #[inline] pub fn states(&self) -> &Storage<u8> { &self.states }
+    #[inline] pub fn strengths(&self) -> &Storage<i8> { &self.strengths }
+    #[inline] pub fn src_slc_ids(&self) -> &Storage<SlcId> { &self.src_slc_ids }
+    #[inline] pub fn src_col_v_offs(&self) -> &Storage<SrcOfs> { &self.src_col_v_offs }
+    #[inline] pub fn src_col_u_offs(&self) -> &Storage<SrcOfs> { &self.src_col_u_offs }
+    #[inline] pub fn flag_sets(&self) -> &Storage<u8> { &self.flag_sets }
     #[inline] pub fn count(&self) -> u32 { self.states.len() as u32 }
     #[inline] pub fn tft_count(&self) -> usize { self.src_idx_caches_by_tft.len() }
     #[inline] pub fn syn_idzs_by_tft(&self) -> &[u32] { self.syn_idzs_by_tft.as_slice() }
     #[inline] pub fn tft_dims_by_tft(&self) -> &[TuftDims] { self.tft_dims_by_tft.as_slice() }
+    #[inline] pub fn learning_params(&self) -> &LearningParams { &self.learning_params }
+}
+
+
+/// Returns the strength added to newly grown synapses on dendrites of kind
+/// `den_kind`.
+fn str_default(den_kind: DendriteKind, params: &LearningParams) -> i8 {
+    match den_kind {
+        DendriteKind::Proximal => params.prx_syn_strength_default,
+        _ => params.dst_syn_strength_default,
+    }
 }
 
 
@@ -538,6 +1030,7 @@ pub mod tests {
         fn set_all_to_zero(&mut self);
         fn set_src_offs(&mut self, v_ofs: SrcOfs, u_ofs: SrcOfs, idx: usize);
         fn set_src_slc(&mut self, src_slc_id: SlcId, idx: usize);
+        fn set_strength(&mut self, strength: i8, idx: usize);
         fn syn_state(&self, idx: u32) -> u8;
         fn rand_syn_coords(&mut self, cel_coords: CelCoords) -> SynCoords;
         fn cycle_solo(&self);
@@ -550,54 +1043,55 @@ pub mod tests {
 
     impl SynapsesTest for Synapses {
         fn set_offs_to_zero(&mut self) {
-            self.src_col_v_offs.default_queue().unwrap().finish().unwrap();
-            self.src_col_u_offs.default_queue().unwrap().finish().unwrap();
+            self.src_col_v_offs.finish().unwrap();
+            self.src_col_u_offs.finish().unwrap();
 
-            self.src_col_v_offs.cmd().fill(0, None).enq().unwrap();
-            self.src_col_u_offs.cmd().fill(0, None).enq().unwrap();
+            self.src_col_v_offs.fill(0).unwrap();
+            self.src_col_u_offs.fill(0).unwrap();
 
-            self.src_col_v_offs.default_queue().unwrap().finish().unwrap();
-            self.src_col_u_offs.default_queue().unwrap().finish().unwrap();
+            self.src_col_v_offs.finish().unwrap();
+            self.src_col_u_offs.finish().unwrap();
         }
 
         fn set_all_to_zero(&mut self) {
-            self.states.default_queue().unwrap().finish().unwrap();
-            self.strengths.default_queue().unwrap().finish().unwrap();
-            self.src_slc_ids.default_queue().unwrap().finish().unwrap();
-            self.src_col_u_offs.default_queue().unwrap().finish().unwrap();
-            self.src_col_v_offs.default_queue().unwrap().finish().unwrap();
-            self.flag_sets.default_queue().unwrap().finish().unwrap();
-
-            self.states.cmd().fill(0, None).enq().unwrap();
-            self.strengths.cmd().fill(0, None).enq().unwrap();
-            self.src_slc_ids.cmd().fill(0, None).enq().unwrap();
-            self.src_col_u_offs.cmd().fill(0, None).enq().unwrap();
-            self.src_col_v_offs.cmd().fill(0, None).enq().unwrap();
-            self.flag_sets.cmd().fill(0, None).enq().unwrap();
-
-            self.states.default_queue().unwrap().finish().unwrap();
-            self.strengths.default_queue().unwrap().finish().unwrap();
-            self.src_slc_ids.default_queue().unwrap().finish().unwrap();
-            self.src_col_u_offs.default_queue().unwrap().finish().unwrap();
-            self.src_col_v_offs.default_queue().unwrap().finish().unwrap();
-            self.flag_sets.default_queue().unwrap().finish().unwrap();
+            self.states.finish().unwrap();
+            self.strengths.finish().unwrap();
+            self.src_slc_ids.finish().unwrap();
+            self.src_col_u_offs.finish().unwrap();
+            self.src_col_v_offs.finish().unwrap();
+            self.flag_sets.finish().unwrap();
+
+            self.states.fill(0).unwrap();
+            self.strengths.fill(0).unwrap();
+            self.src_slc_ids.fill(0).unwrap();
+            self.src_col_u_offs.fill(0).unwrap();
+            self.src_col_v_offs.fill(0).unwrap();
+            self.flag_sets.fill(0).unwrap();
+
+            self.states.finish().unwrap();
+            self.strengths.finish().unwrap();
+            self.src_slc_ids.finish().unwrap();
+            self.src_col_u_offs.finish().unwrap();
+            self.src_col_v_offs.finish().unwrap();
+            self.flag_sets.finish().unwrap();
         }
 
         fn set_src_offs(&mut self, v_ofs: SrcOfs, u_ofs: SrcOfs, idx: usize) {
-            let sdr_v = vec![v_ofs];
-            let sdr_u = vec![u_ofs];
-            self.src_col_v_offs.cmd().write(&sdr_v[..]).offset(idx as usize).enq().unwrap();
-            self.src_col_u_offs.cmd().write(&sdr_u[..]).offset(idx as usize).enq().unwrap();
+            self.src_col_v_offs.write_at(&[v_ofs], idx).unwrap();
+            self.src_col_u_offs.write_at(&[u_ofs], idx).unwrap();
         }
 
         fn set_src_slc(&mut self, src_slc_id: SlcId, idx: usize) {
-            let sdr = vec![src_slc_id];
-            self.src_slc_ids.cmd().write(&sdr[..]).offset(idx as usize).enq().unwrap();
+            self.src_slc_ids.write_at(&[src_slc_id], idx).unwrap();
+        }
+
+        fn set_strength(&mut self, strength: i8, idx: usize) {
+            self.strengths.write_at(&[strength], idx).unwrap();
         }
 
         fn syn_state(&self, idx: u32) -> u8 {
             let mut sdr = vec![0u8];
-            self.states.cmd().read(&mut sdr[..]).offset(idx as usize).enq().unwrap();
+            self.states.read_into(idx as usize, &mut sdr[..]).unwrap();
             sdr[0]
         }
 
@@ -650,12 +1144,10 @@ pub mod tests {
         }
 
         fn print_src_slc_ids(&self, idx_range: Option<Range<usize>>) {
-            let mut vec = vec![0; self.states.len()];
-
             let interval = if idx_range.is_some() { 1 << 0 } else { 1 << 8 };
 
             print!("syns.src_slc_ids: ");
-            self.src_slc_ids.read(&mut vec).enq().unwrap();
+            let vec = self.src_slc_ids.read().unwrap();
             util::print_slice(&vec, interval, None, idx_range, false);
         }
 
@@ -668,34 +1160,28 @@ pub mod tests {
         // idx_range: Option<Range<usize>>, show_zeros: bool)
         //
         fn print_range(&self, idx_range: Option<Range<usize>>) {
-            let mut vec = vec![0; self.states.len()];
-
             print!("syns.states: ");
-            self.states.read(&mut vec).enq().unwrap();
+            let vec = self.states.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
 
             print!("syns.flag_sets: ");
-            self.flag_sets.read(&mut vec).enq().unwrap();
+            let vec = self.flag_sets.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
 
             // print!("syns.src_slc_ids: ");
-            // self.src_slc_ids.read(&mut vec).enq().unwrap();
+            // let vec = self.src_slc_ids.read().unwrap();
             // util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
 
-            let mut vec = vec![0i8; self.states.len()];
-
             print!("syns.strengths: ");
-            self.strengths.read(&mut vec).enq().unwrap();
+            let vec = self.strengths.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
 
-            let mut vec = vec![0 as SrcOfs; self.states.len()];
-
             print!("syns.src_col_v_offs: ");
-            self.src_col_v_offs.read(&mut vec).enq().unwrap();
+            let vec = self.src_col_v_offs.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
 
             print!("syns.src_col_u_offs: ");
-            self.src_col_u_offs.read(&mut vec).enq().unwrap();
+            let vec = self.src_col_u_offs.read().unwrap();
             util::print_slice(&vec, 1 << 0, None, idx_range.clone(), false);
         }
 
@@ -1,16 +1,43 @@
 #![allow(unused_imports)]
 
-use rand::{Rng, FromEntropy, rngs::SmallRng};
+use rand::{Rng, rngs::SmallRng};
 use cmn::{self, CmnResult, CorticalDims};
-use ocl::{ProQue, SpatialDims, Buffer, Kernel, Result as OclResult, Event};
+use ocl::{SpatialDims, Buffer, Kernel, Result as OclResult, Event};
 use std::collections::BTreeMap;
 use ocl::traits::OclPrm;
 use map::{AreaMap, CellScheme, DendriteClass, DendriteKind, ExecutionGraph, CommandRelations,
     CorticalBuffer, LayerAddress, LayerTags, CommandUid};
-use cortex::{Dendrites, AxonSpace, CorticalAreaSettings, DataCellLayer, ControlCellLayers};
+use cortex::{Dendrites, AxonSpace, CorticalAreaSettings, DataCellLayer, ControlCellLayers,
+    SnapshotWriter, SnapshotReader, LearningParams, Neuromodulation, Backend, Storage};
+use cortex::host_kernels;
 
 const PRNT: bool = false;
 
+
+/// Scalar `tft_cycle` arguments retained for the host kernels.
+#[derive(Debug, Clone)]
+struct TftCycleArgs {
+    tft_cel_idz: u32,
+    tft_den_idz: u32,
+    dens_per_tft: u32,
+    max_active_dens_l2: u8,
+}
+
+
+/// Scalar `tft_dst_mtp` arguments retained for the host kernels.
+#[derive(Debug, Clone)]
+struct TftDstMtpArgs {
+    tft_cel_idz: u32,
+    tft_syn_idz: u32,
+    syns_per_den: u32,
+    syns_per_tft: u32,
+    cels_per_cel_grp: u32,
+    cel_grp_count: u32,
+    potentiation_rate_l2i: i32,
+    depression_rate_l2i: i32,
+}
+
+
 #[derive(Debug)]
 pub struct Tufts {
     layer_name: String,
@@ -18,14 +45,14 @@ pub struct Tufts {
     dims: CorticalDims,
     tft_count: usize,
 
-    prev_best_den_ids: Buffer<u8>,
-    prev_best_den_states_raw: Buffer<u8>,
-    prev_best_den_states: Buffer<u8>,
-    prev_states: Buffer<u8>,
-    best_den_ids: Buffer<u8>,
-    best_den_states_raw: Buffer<u8>,
-    best_den_states: Buffer<u8>,
-    states: Buffer<u8>,
+    prev_best_den_ids: Storage<u8>,
+    prev_best_den_states_raw: Storage<u8>,
+    prev_best_den_states: Storage<u8>,
+    prev_states: Storage<u8>,
+    best_den_ids: Storage<u8>,
+    best_den_states_raw: Storage<u8>,
+    best_den_states: Storage<u8>,
+    states: Storage<u8>,
 
     mtp_kernels: Vec<Kernel>,
     cycle_kernels: Vec<Kernel>,
@@ -36,7 +63,16 @@ pub struct Tufts {
 
     dens: Dendrites,
     settings: CorticalAreaSettings,
+    learning_enabled: bool,
     rng: SmallRng,
+
+    cycle_args: Vec<TftCycleArgs>,
+    mtp_args: Vec<TftDstMtpArgs>,
+    axn_states: Storage<u8>,
+    cel_flag_sets: Storage<u8>,
+    cel_lyr_axn_idz: u32,
+    neuromods: Storage<i8>,
+    neuromod_col_count: u32,
 }
 
 impl Tufts {
@@ -49,10 +85,11 @@ impl Tufts {
             axons: &AxonSpace,
             cel_axn_slc_ids: &[u8],
             cel_lyr_axn_idz: u32,
-            cel_states: &Buffer<u8>,
-            cel_flag_sets: &Buffer<u8>,
-            ocl_pq: &ProQue,
+            cel_states: &Storage<u8>,
+            cel_flag_sets: &Storage<u8>,
+            backend: &Backend,
             settings: CorticalAreaSettings,
+            neuromod: &mut Neuromodulation,
             exe_graph: &mut ExecutionGraph)
             -> CmnResult<Tufts> {
         let layer_name = layer_name.into();
@@ -60,17 +97,18 @@ impl Tufts {
         let cel_count = dims.to_len();
         let celtft_count = cel_count * tft_count;
 
-        let prev_best_den_ids = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let prev_best_den_states_raw = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let prev_best_den_states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let prev_states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let best_den_ids = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let best_den_states_raw = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let best_den_states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
-        let states = Buffer::<u8>::builder().queue(ocl_pq.queue().clone()).len([celtft_count]).fill_val(0).build()?;
+        let prev_best_den_ids = backend.buffer::<u8>(celtft_count, 0)?;
+        let prev_best_den_states_raw = backend.buffer::<u8>(celtft_count, 0)?;
+        let prev_best_den_states = backend.buffer::<u8>(celtft_count, 0)?;
+        let prev_states = backend.buffer::<u8>(celtft_count, 0)?;
+        let best_den_ids = backend.buffer::<u8>(celtft_count, 0)?;
+        let best_den_states_raw = backend.buffer::<u8>(celtft_count, 0)?;
+        let best_den_states = backend.buffer::<u8>(celtft_count, 0)?;
+        let states = backend.buffer::<u8>(celtft_count, 0)?;
 
         let dens = Dendrites::new(layer_name.clone(), layer_addr.layer_id(), dims, cell_scheme.clone(),
-            area_map, axons, ocl_pq, settings.disable_pyrs, exe_graph)?;
+            area_map, axons, backend, settings.disable_pyrs, settings.host_kernels,
+            settings.device_regrowth, settings.learning_params, settings.seed, exe_graph)?;
 
         let mut mtp_kernels = Vec::with_capacity(tft_count);
         let mut cycle_kernels = Vec::with_capacity(tft_count);
@@ -78,6 +116,8 @@ impl Tufts {
         let cycle_exe_cmd_idxs = Vec::with_capacity(tft_count);
         let mut mtp_exe_cmd_uids = Vec::with_capacity(tft_count);
         let mtp_exe_cmd_idxs = Vec::with_capacity(tft_count);
+        let mut cycle_args = Vec::with_capacity(tft_count);
+        let mut mtp_args = Vec::with_capacity(tft_count);
         // let mut den_kinds = Vec::with_capacity(tft_count);
         let mut den_count_ttl = 0u32;
         let mut syn_count_ttl = 0u32;
@@ -104,27 +144,36 @@ impl Tufts {
             ===============================================================================
             =============================================================================*/
 
+            cycle_args.push(TftCycleArgs {
+                tft_cel_idz,
+                tft_den_idz,
+                dens_per_tft,
+                max_active_dens_l2: tft_scheme.max_active_dens_l2(),
+            });
+
             let kern_name = "tft_cycle";
-            cycle_kernels.push(ocl_pq.kernel_builder(kern_name)
-                .global_work_size(SpatialDims::One(cel_count))
-                .arg(dens.states_raw())
-                .arg(dens.states())
-                .arg(&tft_cel_idz)
-                .arg(&tft_den_idz)
-                .arg(&dens_per_tft)
-                .arg(&tft_scheme.max_active_dens_l2())
-                .arg(&prev_best_den_ids)
-                .arg(&prev_best_den_states_raw)
-                .arg(&prev_best_den_states)
-                .arg(&prev_states)
-                .arg(&best_den_ids)
-                .arg(&best_den_states_raw)
-                .arg(&best_den_states)
-                .arg_named("aux_ints_0", None::<&Buffer<i32>>)
-                .arg_named("aux_ints_1", None::<&Buffer<i32>>)
-                .arg(&states)
-                .build()?
-            );
+            if let Some(ocl_pq) = backend.pq() {
+                cycle_kernels.push(ocl_pq.kernel_builder(kern_name)
+                    .global_work_size(SpatialDims::One(cel_count))
+                    .arg(dens.states_raw().device()?)
+                    .arg(dens.states().device()?)
+                    .arg(&tft_cel_idz)
+                    .arg(&tft_den_idz)
+                    .arg(&dens_per_tft)
+                    .arg(&tft_scheme.max_active_dens_l2())
+                    .arg(prev_best_den_ids.device()?)
+                    .arg(prev_best_den_states_raw.device()?)
+                    .arg(prev_best_den_states.device()?)
+                    .arg(prev_states.device()?)
+                    .arg(best_den_ids.device()?)
+                    .arg(best_den_states_raw.device()?)
+                    .arg(best_den_states.device()?)
+                    .arg_named("aux_ints_0", None::<&Buffer<i32>>)
+                    .arg_named("aux_ints_1", None::<&Buffer<i32>>)
+                    .arg(states.device()?)
+                    .build()?
+                );
+            }
 
             if !settings.disable_pyrs {
                 cycle_exe_cmd_uids.push(exe_graph.add_command(CommandRelations::cortical_kernel(
@@ -155,37 +204,54 @@ impl Tufts {
                         // let cel_grp_count = cmn::OPENCL_MINIMUM_WORKGROUP_SIZE;
                         let cel_grp_count = 64;
                         let cels_per_cel_grp = dims.per_subgrp(cel_grp_count)?;
-                        let potentiation_rate_l2i = 0i32;
-                        let depression_rate_l2i = 5i32;
+                        let potentiation_rate_l2i =
+                            settings.learning_params.dst_potentiation_rate_l2i;
+                        let depression_rate_l2i =
+                            settings.learning_params.dst_depression_rate_l2i;
+
+                        mtp_args.push(TftDstMtpArgs {
+                            tft_cel_idz,
+                            tft_syn_idz,
+                            syns_per_den,
+                            syns_per_tft,
+                            cels_per_cel_grp,
+                            cel_grp_count,
+                            potentiation_rate_l2i,
+                            depression_rate_l2i,
+                        });
 
                         let kern_name = "tft_dst_mtp";
-                        mtp_kernels.push(ocl_pq.kernel_builder(kern_name)
-                            .global_work_size(SpatialDims::One(cel_grp_count as usize))
-                            .arg(axons.states())
-                            .arg(cel_states)
-                            .arg(&prev_best_den_ids)
-                            .arg(&prev_best_den_states_raw)
-                            .arg(dens.states())
-                            .arg(dens.syns().states())
-                            // .arg(tfts_per_cel as u32)
-                            .arg(&tft_cel_idz)
-                            .arg(&tft_den_idz)
-                            .arg(&tft_syn_idz)
-                            .arg(&dens_per_tft)
-                            .arg(&syns_per_den)
-                            .arg(&syns_per_tft)
-                            .arg(&cels_per_cel_grp)
-                            .arg(&cel_lyr_axn_idz)
-                            .arg_named("pr_l2i", &potentiation_rate_l2i)
-                            .arg_named("dr_l2i", &depression_rate_l2i)
-                            .arg_named("rnd", &0i32)
-                            .arg(dens.syns().flag_sets())
-                            .arg(cel_flag_sets)
-                            .arg_named("aux_ints_0", None::<&Buffer<i32>>)
-                            .arg_named("aux_ints_1", None::<&Buffer<i32>>)
-                            .arg(dens.syns().strengths())
-                            .build()?
-                        );
+                        if let Some(ocl_pq) = backend.pq() {
+                            mtp_kernels.push(ocl_pq.kernel_builder(kern_name)
+                                .global_work_size(SpatialDims::One(cel_grp_count as usize))
+                                .arg(axons.states().device()?)
+                                .arg(cel_states.device()?)
+                                .arg(prev_best_den_ids.device()?)
+                                .arg(prev_best_den_states_raw.device()?)
+                                .arg(dens.states().device()?)
+                                .arg(dens.syns().states().device()?)
+                                // .arg(tfts_per_cel as u32)
+                                .arg(&tft_cel_idz)
+                                .arg(&tft_den_idz)
+                                .arg(&tft_syn_idz)
+                                .arg(&dens_per_tft)
+                                .arg(&syns_per_den)
+                                .arg(&syns_per_tft)
+                                .arg(&cels_per_cel_grp)
+                                .arg(&cel_lyr_axn_idz)
+                                .arg_named("pr_l2i", &potentiation_rate_l2i)
+                                .arg_named("dr_l2i", &depression_rate_l2i)
+                                .arg(neuromod.buffer().device()?)
+                                .arg(&neuromod.col_count())
+                                .arg_named("rnd", &0i32)
+                                .arg(dens.syns().flag_sets().device()?)
+                                .arg(cel_flag_sets.device()?)
+                                .arg_named("aux_ints_0", None::<&Buffer<i32>>)
+                                .arg_named("aux_ints_1", None::<&Buffer<i32>>)
+                                .arg(dens.syns().strengths().device()?)
+                                .build()?
+                            );
+                        }
 
                         let mut mtp_cmd_srcs: Vec<CorticalBuffer> = cel_axn_slc_ids.iter()
                             .map(|&slc_id|
@@ -197,6 +263,7 @@ impl Tufts {
                         mtp_cmd_srcs.push(CorticalBuffer::data_tft(&prev_best_den_states_raw, layer_addr, tft_id));
                         mtp_cmd_srcs.push(CorticalBuffer::data_den_tft(dens.states(), layer_addr, tft_id));
                         mtp_cmd_srcs.push(CorticalBuffer::data_syn_tft(dens.syns().states(), layer_addr, tft_id));
+                        mtp_cmd_srcs.push(neuromod.register_reader());
 
                         mtp_exe_cmd_uids.push(exe_graph.add_command(CommandRelations::cortical_kernel(
                             kern_name, mtp_cmd_srcs,
@@ -217,6 +284,9 @@ impl Tufts {
         assert!(den_count_ttl == dens.count());
         assert!(syn_count_ttl == dens.syns().count());
 
+        let rng = cmn::seeded_rng(settings.seed, "tufts", &[layer_addr.area_id() as u64,
+            layer_addr.layer_id() as u64]);
+
         Ok(Tufts {
             layer_name,
             layer_addr,
@@ -241,7 +311,16 @@ impl Tufts {
 
             dens,
             settings,
-            rng: SmallRng::from_entropy(),
+            learning_enabled: true,
+            rng,
+
+            cycle_args,
+            mtp_args,
+            axn_states: axons.states().clone(),
+            cel_flag_sets: cel_flag_sets.clone(),
+            cel_lyr_axn_idz,
+            neuromods: neuromod.buffer().clone(),
+            neuromod_col_count: neuromod.col_count(),
         })
     }
 
@@ -305,6 +384,15 @@ impl Tufts {
         if PRNT { printlnc!(yellow: "  Tfts: Performing learning for layer: '{}'...",
             self.layer_name); }
 
+        if !self.learning_enabled {
+            for &cmd_idx in self.mtp_exe_cmd_idxs.iter() {
+                exe_graph.set_cmd_event(cmd_idx, None)?;
+            }
+            return Ok(());
+        }
+
+        if self.settings.host_kernels { return self.learn_host(exe_graph); }
+
         for (mtp_kernel, &cmd_idx) in self.mtp_kernels.iter_mut()
                 .zip(self.mtp_exe_cmd_idxs.iter()) {
             if PRNT { printlnc!(yellow: "  Tfts: Setting scalar to a random value..."); }
@@ -338,6 +426,8 @@ impl Tufts {
         if PRNT { printlnc!(yellow: "  Tfts: Cycling dens..."); }
         self.dens.cycle(exe_graph)?;
 
+        if self.settings.host_kernels { return self.cycle_host(exe_graph); }
+
         // Tufts:
         for (tft_id, (cycle_kernel, &cmd_idx)) in self.cycle_kernels.iter()
                 .zip(self.cycle_exe_cmd_idxs.iter()).enumerate()
@@ -357,17 +447,140 @@ impl Tufts {
         Ok(())
     }
 
+    /// Cycles tufts using the host kernels.
+    fn cycle_host(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let mut prev_best_den_ids = host_kernels::read(&self.prev_best_den_ids)?;
+        let mut prev_best_den_states_raw = host_kernels::read(&self.prev_best_den_states_raw)?;
+        let mut prev_best_den_states = host_kernels::read(&self.prev_best_den_states)?;
+        let mut prev_states = host_kernels::read(&self.prev_states)?;
+        let mut best_den_ids = host_kernels::read(&self.best_den_ids)?;
+        let mut best_den_states_raw = host_kernels::read(&self.best_den_states_raw)?;
+        let mut best_den_states = host_kernels::read(&self.best_den_states)?;
+        let mut states = host_kernels::read(&self.states)?;
+        let cel_count = self.dims.to_len() as u32;
+
+        for (args, &cmd_idx) in self.cycle_args.iter().zip(self.cycle_exe_cmd_idxs.iter()) {
+            let den_states_raw = host_kernels::read(self.dens.states_raw())?;
+            let den_states = host_kernels::read(self.dens.states())?;
+
+            host_kernels::tft_cycle(&den_states_raw, &den_states, args.tft_cel_idz,
+                args.tft_den_idz, args.dens_per_tft, args.max_active_dens_l2, cel_count,
+                &mut prev_best_den_ids, &mut prev_best_den_states_raw, &mut prev_best_den_states,
+                &mut prev_states, &mut best_den_ids, &mut best_den_states_raw,
+                &mut best_den_states, &mut states);
+
+            let range = args.tft_cel_idz as usize..(args.tft_cel_idz + cel_count) as usize;
+            host_kernels::write_range(&self.prev_best_den_ids, &prev_best_den_ids, range.clone())?;
+            host_kernels::write_range(&self.prev_best_den_states_raw, &prev_best_den_states_raw, range.clone())?;
+            host_kernels::write_range(&self.prev_best_den_states, &prev_best_den_states, range.clone())?;
+            host_kernels::write_range(&self.prev_states, &prev_states, range.clone())?;
+            host_kernels::write_range(&self.best_den_ids, &best_den_ids, range.clone())?;
+            host_kernels::write_range(&self.best_den_states_raw, &best_den_states_raw, range.clone())?;
+            host_kernels::write_range(&self.best_den_states, &best_den_states, range.clone())?;
+            host_kernels::write_range(&self.states, &states, range)?;
+            exe_graph.set_cmd_event(cmd_idx, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Performs learning using the host kernels.
+    ///
+    /// Each tuft reads and writes only its own ranges. The cell flag sets
+    /// are shared by every tuft of the layer and, as on the device, are
+    /// updated by each tuft in turn.
+    fn learn_host(&mut self, exe_graph: &mut ExecutionGraph) -> CmnResult<()> {
+        let cel_count = self.dims.cells() as usize;
+        let cel_axn_range = self.cel_lyr_axn_idz as usize..self.cel_lyr_axn_idz as usize + cel_count;
+
+        for (args, &cmd_idx) in self.mtp_args.iter().zip(self.mtp_exe_cmd_idxs.iter()) {
+            let rnd = self.rng.gen::<i32>();
+            let tft_cel_range = args.tft_cel_idz as usize..args.tft_cel_idz as usize + cel_count;
+            let syn_range = args.tft_syn_idz as usize..
+                (args.tft_syn_idz + (self.dims.cells() * args.syns_per_tft)) as usize;
+
+            let axn_states = host_kernels::read_range(&self.axn_states, cel_axn_range.clone())?;
+            let prev_best_den_ids = host_kernels::read_range(&self.prev_best_den_ids,
+                tft_cel_range.clone())?;
+            let prev_best_den_states_raw = host_kernels::read_range(
+                &self.prev_best_den_states_raw, tft_cel_range)?;
+            let syn_flag_sets = host_kernels::read_range(self.dens.syns().flag_sets(),
+                syn_range.clone())?;
+            let mut syn_strengths = host_kernels::read_range(self.dens.syns().strengths(),
+                syn_range.clone())?;
+            let mut cel_flag_sets = host_kernels::read_range(&self.cel_flag_sets, 0..cel_count)?;
+            let neuromods = host_kernels::read(&self.neuromods)?;
+
+            host_kernels::tft_dst_mtp(&axn_states, &prev_best_den_ids, &prev_best_den_states_raw,
+                args.tft_syn_idz, args.syns_per_den, args.syns_per_tft, args.cels_per_cel_grp,
+                args.cel_grp_count, args.potentiation_rate_l2i, args.depression_rate_l2i,
+                &neuromods, self.neuromod_col_count, rnd, &syn_flag_sets, &mut cel_flag_sets,
+                &mut syn_strengths);
+
+            host_kernels::write_at(&self.cel_flag_sets, &cel_flag_sets, 0)?;
+            host_kernels::write_at(self.dens.syns().strengths(), &syn_strengths, syn_range.start)?;
+            exe_graph.set_cmd_event(cmd_idx, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes tuft, dendrite, and synapse state to a snapshot.
+    pub fn save_state(&mut self, wtr: &mut SnapshotWriter) -> CmnResult<()> {
+        wtr.write_buffer("tfts.prev_best_den_ids", &self.prev_best_den_ids)?;
+        wtr.write_buffer("tfts.prev_best_den_states_raw", &self.prev_best_den_states_raw)?;
+        wtr.write_buffer("tfts.prev_best_den_states", &self.prev_best_den_states)?;
+        wtr.write_buffer("tfts.prev_states", &self.prev_states)?;
+        wtr.write_buffer("tfts.best_den_ids", &self.best_den_ids)?;
+        wtr.write_buffer("tfts.best_den_states_raw", &self.best_den_states_raw)?;
+        wtr.write_buffer("tfts.best_den_states", &self.best_den_states)?;
+        wtr.write_buffer("tfts.states", &self.states)?;
+        wtr.write_rng("tfts.rng", &self.rng)?;
+        self.dens.save_state(wtr)
+    }
+
+    /// Restores tuft, dendrite, and synapse state from a snapshot.
+    pub fn load_state(&mut self, rdr: &mut SnapshotReader) -> CmnResult<()> {
+        rdr.read_buffer("tfts.prev_best_den_ids", &self.prev_best_den_ids)?;
+        rdr.read_buffer("tfts.prev_best_den_states_raw", &self.prev_best_den_states_raw)?;
+        rdr.read_buffer("tfts.prev_best_den_states", &self.prev_best_den_states)?;
+        rdr.read_buffer("tfts.prev_states", &self.prev_states)?;
+        rdr.read_buffer("tfts.best_den_ids", &self.best_den_ids)?;
+        rdr.read_buffer("tfts.best_den_states_raw", &self.best_den_states_raw)?;
+        rdr.read_buffer("tfts.best_den_states", &self.best_den_states)?;
+        rdr.read_buffer("tfts.states", &self.states)?;
+        rdr.read_rng("tfts.rng", &mut self.rng)?;
+        self.dens.load_state(rdr)
+    }
+
+    /// Enables or disables learning without altering the execution graph.
+    #[inline] pub fn set_learning_enabled(&mut self, enabled: bool) { self.learning_enabled = enabled; }
+
+    /// Sets the distal learning rates and regrowth parameters used from the
+    /// next cycle onward.
+    pub fn set_learning_params(&mut self, params: &LearningParams) -> CmnResult<()> {
+        self.dens.set_learning_params(params)?;
+        for (mtp_kernel, args) in self.mtp_kernels.iter_mut().zip(self.mtp_args.iter_mut()) {
+            mtp_kernel.set_arg("pr_l2i", params.dst_potentiation_rate_l2i)?;
+            mtp_kernel.set_arg("dr_l2i", params.dst_depression_rate_l2i)?;
+            args.potentiation_rate_l2i = params.dst_potentiation_rate_l2i;
+            args.depression_rate_l2i = params.dst_depression_rate_l2i;
+        }
+        self.settings.learning_params = *params;
+        Ok(())
+    }
+
     #[inline] pub fn layer_name<'s>(&'s self) -> &'s str { &self.layer_name }
     #[inline] pub fn layer_addr(&self) -> LayerAddress { self.layer_addr }
     #[inline] pub fn dims(&self) -> &CorticalDims { &self.dims }
-    #[inline] pub fn prev_states(&self) -> &Buffer<u8> { &self.prev_states }
-    #[inline] pub fn prev_best_den_ids(&self) -> &Buffer<u8> { &self.prev_best_den_ids }
-    #[inline] pub fn prev_best_den_states_raw(&self) -> &Buffer<u8> { &self.prev_best_den_states_raw }
-    #[inline] pub fn prev_best_den_states(&self) -> &Buffer<u8> { &self.prev_best_den_states }
-    #[inline] pub fn states(&self) -> &Buffer<u8> { &self.states }
-    #[inline] pub fn best_den_ids(&self) -> &Buffer<u8> { &self.best_den_ids }
-    #[inline] pub fn best_den_states_raw(&self) -> &Buffer<u8> { &self.best_den_states_raw }
-    #[inline] pub fn best_den_states(&self) -> &Buffer<u8> { &self.best_den_states }
+    #[inline] pub fn prev_states(&self) -> &Storage<u8> { &self.prev_states }
+    #[inline] pub fn prev_best_den_ids(&self) -> &Storage<u8> { &self.prev_best_den_ids }
+    #[inline] pub fn prev_best_den_states_raw(&self) -> &Storage<u8> { &self.prev_best_den_states_raw }
+    #[inline] pub fn prev_best_den_states(&self) -> &Storage<u8> { &self.prev_best_den_states }
+    #[inline] pub fn states(&self) -> &Storage<u8> { &self.states }
+    #[inline] pub fn best_den_ids(&self) -> &Storage<u8> { &self.best_den_ids }
+    #[inline] pub fn best_den_states_raw(&self) -> &Storage<u8> { &self.best_den_states_raw }
+    #[inline] pub fn best_den_states(&self) -> &Storage<u8> { &self.best_den_states }
     #[inline] pub fn dens(&self) -> &Dendrites { &self.dens }
     #[inline] pub fn dens_mut(&mut self) -> &mut Dendrites { &mut self.dens }
     #[inline] pub fn count(&self) -> usize { self.tft_count }
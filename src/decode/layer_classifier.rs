@@ -0,0 +1,177 @@
+//! A nucleus which classifies the activity of a cortical layer.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{Sender, Receiver};
+use futures::Never;
+use futures::future::FutureExt;
+use cmn::{CmnError, CmnResult};
+use map::{LayerAddress, AreaSchemeList};
+use cortex::{CompletionPool, CorticalAreas, CorticalLayerSampler};
+use subcortex::{Thalamus, SubcorticalNucleus, SubcorticalNucleusLayer};
+use super::{SdrClassifier, Prediction};
+
+
+/// The predictions made on a particular cycle.
+#[derive(Debug, Clone)]
+pub struct Classification {
+    cycle: usize,
+    label: Option<usize>,
+    predictions: Vec<Prediction>,
+}
+
+impl Classification {
+    /// The cycle on which the sampled activity was produced.
+    #[inline] pub fn cycle(&self) -> usize { self.cycle }
+
+    /// The label supplied for this cycle, if any.
+    #[inline] pub fn label(&self) -> Option<usize> { self.label }
+
+    /// One prediction per classifier step.
+    #[inline] pub fn predictions(&self) -> &[Prediction] { &self.predictions }
+}
+
+
+/// Trains an `SdrClassifier` on the axons of a cortical layer.
+///
+/// A `CorticalLayerSampler` is subscribed to the layer when pathways are
+/// created. Labels are sent to `label_rx` as `(cycle, label)` pairs, where
+/// `cycle` counts cycles from zero (see `Classification::cycle`), and may be
+/// sent ahead of time. Each cycle, the label for that cycle is used to train
+/// the classifier (a cycle without a label is classified without training
+/// and labels for cycles which have already passed are discarded). Once the
+/// layer's samples arrive, the resulting `Classification` (or the error
+/// which prevented it) is sent to `classification_tx`. Classification runs
+/// on the completion pool; the sampler's backpressure keeps cycles in order.
+///
+/// The classifier is shared and may be inspected at any time (to check
+/// `SdrClassifier::accuracy`, for example) using `::classifier`.
+///
+pub struct LayerClassifier {
+    area_name: String,
+    area_id: usize,
+    src_area_name: String,
+    src_layer_name: String,
+    classifier: Arc<Mutex<SdrClassifier>>,
+    sampler: Option<CorticalLayerSampler>,
+    label_rx: Receiver<(usize, usize)>,
+    pending_labels: BTreeMap<usize, usize>,
+    classification_tx: Sender<CmnResult<Classification>>,
+    cycle_counter: usize,
+}
+
+impl LayerClassifier {
+    /// Returns a new `LayerClassifier`, for the area named `area_name`
+    /// within `area_schemes`, which will classify the layer named
+    /// `src_layer_name` within the area named `src_area_name`.
+    ///
+    /// The classifier's input length must equal the layer's cell count.
+    pub fn new<S1, S2>(area_schemes: &AreaSchemeList, area_name: &str, src_area_name: S1,
+            src_layer_name: S2, classifier: SdrClassifier, label_rx: Receiver<(usize, usize)>,
+            classification_tx: Sender<CmnResult<Classification>>) -> CmnResult<LayerClassifier>
+            where S1: Into<String>, S2: Into<String> {
+        let area_id = area_schemes.get_area_by_key(area_name)
+            .ok_or_else(|| CmnError::new(format!("LayerClassifier::new: No area named '{}' \
+                found.", area_name)))?
+            .area_id();
+
+        Ok(LayerClassifier {
+            area_name: area_name.to_owned(),
+            area_id,
+            src_area_name: src_area_name.into(),
+            src_layer_name: src_layer_name.into(),
+            classifier: Arc::new(Mutex::new(classifier)),
+            sampler: None,
+            label_rx,
+            pending_labels: BTreeMap::new(),
+            classification_tx,
+            cycle_counter: 0,
+        })
+    }
+
+    /// Returns the shared classifier.
+    pub fn classifier(&self) -> Arc<Mutex<SdrClassifier>> {
+        self.classifier.clone()
+    }
+}
+
+impl SubcorticalNucleus for LayerClassifier {
+    fn create_pathways(&mut self, thal: &mut Thalamus, cortical_areas: &mut CorticalAreas)
+            -> CmnResult<()> {
+        let sampler = CorticalLayerSampler::builder(&self.src_area_name, &self.src_layer_name,
+                thal, cortical_areas)
+            .axons()
+            .build();
+
+        let input_len = self.classifier.lock()
+            .map_err(|_| CmnError::from("LayerClassifier::create_pathways: Classifier poisoned."))?
+            .input_len();
+        let cell_count = sampler.map().cell_count() as usize;
+        if input_len != cell_count {
+            return Err(format!("LayerClassifier::create_pathways: The classifier input length \
+                ({}) does not match the cell count of layer '{}' (area: '{}'): {}.", input_len,
+                self.src_layer_name, self.src_area_name, cell_count).into());
+        }
+
+        self.sampler = Some(sampler);
+        Ok(())
+    }
+
+    fn pre_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
+            _completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        Ok(())
+    }
+
+    /// Post-cycle:
+    ///
+    /// * Queues classification of the layer's sampled axon states.
+    ///
+    fn post_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
+            completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        let sampler = self.sampler.as_ref()
+            .ok_or("LayerClassifier::post_cycle: Pathways have not been created.")?;
+
+        let cycle = self.cycle_counter;
+        for (label_cycle, label) in self.label_rx.try_iter() {
+            if label_cycle >= cycle { self.pending_labels.insert(label_cycle, label); }
+        }
+        let label = self.pending_labels.remove(&cycle);
+        let classifier = self.classifier.clone();
+        let classification_tx = self.classification_tx.clone();
+
+        let future_classify = sampler.recv()
+            .and_then(move |samples| -> CmnResult<Classification> {
+                let axon_idz = samples.map().axon_idz() as usize;
+                let cell_count = samples.map().cell_count() as usize;
+                let axns = samples.axon_states()
+                    .ok_or("LayerClassifier: Axon states not sampled.")?;
+                let predictions = classifier.lock()
+                    .map_err(|_| CmnError::from("LayerClassifier: Classifier poisoned."))?
+                    .compute(&axns[axon_idz..(axon_idz + cell_count)], label)?;
+                Ok(Classification { cycle, label, predictions })
+            })
+            .then(move |res| {
+                // The receiver may have been dropped, in which case the
+                // result is simply discarded:
+                classification_tx.send(res).ok();
+                Ok::<(), Never>(())
+            });
+
+        completion_pool.complete_work(Box::new(future_classify))?;
+
+        self.cycle_counter += 1;
+        Ok(())
+    }
+
+    fn layer(&self, _addr: LayerAddress) -> Option<&SubcorticalNucleusLayer> {
+        None
+    }
+
+    fn area_name<'a>(&'a self) -> &'a str {
+        &self.area_name
+    }
+
+    fn area_id(&self) -> usize {
+        self.area_id
+    }
+}
@@ -0,0 +1,9 @@
+//! Decoders which map cortical activity back onto labels and values.
+
+mod sdr_classifier;
+#[cfg(any(test, feature = "eval"))]
+mod layer_classifier;
+
+pub use self::sdr_classifier::{SdrClassifier, Prediction, Accuracy, DEFAULT_ALPHA};
+#[cfg(any(test, feature = "eval"))]
+pub use self::layer_classifier::{LayerClassifier, Classification};
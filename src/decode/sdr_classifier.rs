@@ -0,0 +1,270 @@
+//! An online SDR classifier.
+//!
+//! Learns a mapping from the active axons (or cells) of a layer to a label
+//! (a bucket index) for any number of steps into the future using a single
+//! layer softmax network trained by stochastic gradient descent.
+//!
+
+use std::collections::VecDeque;
+use cmn::{CmnError, CmnResult};
+
+
+/// The learning rate used unless otherwise specified.
+pub const DEFAULT_ALPHA: f32 = 0.001;
+
+
+/// A prediction of the label `step` cycles in the future.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prediction {
+    step: usize,
+    bucket: usize,
+    probabilities: Vec<f32>,
+    value: Option<f64>,
+}
+
+impl Prediction {
+    /// The number of cycles ahead which this prediction is for.
+    #[inline] pub fn step(&self) -> usize { self.step }
+
+    /// The most likely bucket.
+    #[inline] pub fn bucket(&self) -> usize { self.bucket }
+
+    /// The probability assigned to the most likely bucket.
+    #[inline] pub fn confidence(&self) -> f32 { self.probabilities[self.bucket] }
+
+    /// The probability assigned to every bucket.
+    #[inline] pub fn probabilities(&self) -> &[f32] { &self.probabilities }
+
+    /// The probability weighted scalar value (available when a scalar range
+    /// has been set with `SdrClassifier::scalar_range`).
+    #[inline] pub fn value(&self) -> Option<f64> { self.value }
+}
+
+
+/// Running prediction accuracy for a single step.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Accuracy {
+    correct: usize,
+    total: usize,
+}
+
+impl Accuracy {
+    /// The number of predictions which matched the label which followed.
+    #[inline] pub fn correct(&self) -> usize { self.correct }
+
+    /// The number of predictions evaluated.
+    #[inline] pub fn total(&self) -> usize { self.total }
+
+    /// The fraction of predictions which were correct (`0.0` if none have
+    /// been evaluated).
+    pub fn ratio(&self) -> f32 {
+        if self.total == 0 { 0.0 } else { self.correct as f32 / self.total as f32 }
+    }
+}
+
+
+/// The active indices and predictions made on a past cycle.
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    active: Vec<u32>,
+    predicted: Vec<usize>,
+}
+
+
+/// Maps layer activity to labels for one or more steps ahead.
+///
+/// Each step has its own weight matrix (`input_len` x `bucket_count`).
+/// Every call to `compute` first predicts the labels of the coming cycles
+/// from the current activity, then, if a label is supplied, trains each
+/// step's weights using the activity seen `step` cycles ago and scores the
+/// predictions made at that time.
+///
+#[derive(Debug, Clone)]
+pub struct SdrClassifier {
+    input_len: usize,
+    steps: Vec<usize>,
+    bucket_count: usize,
+    alpha: f32,
+    scalar_range: Option<(f64, f64)>,
+    weights: Vec<Vec<f32>>,
+    history: VecDeque<HistoryEntry>,
+    accuracy: Vec<Accuracy>,
+}
+
+impl SdrClassifier {
+    /// Returns a new classifier for inputs of `input_len` values which
+    /// predicts one of `bucket_count` labels for each of `steps` cycles in
+    /// the future (`0` classifies the current cycle).
+    pub fn new(input_len: usize, steps: &[usize], bucket_count: usize) -> CmnResult<SdrClassifier> {
+        if input_len == 0 || bucket_count == 0 || steps.is_empty() {
+            return Err("SdrClassifier::new: 'input_len', 'bucket_count' and 'steps' must not \
+                be empty.".into());
+        }
+        let mut sorted = steps.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        if sorted.len() != steps.len() {
+            return Err("SdrClassifier::new: 'steps' must not contain duplicates.".into());
+        }
+
+        Ok(SdrClassifier {
+            input_len,
+            steps: steps.to_vec(),
+            bucket_count,
+            alpha: DEFAULT_ALPHA,
+            scalar_range: None,
+            weights: vec![vec![0.0; input_len * bucket_count]; steps.len()],
+            history: VecDeque::with_capacity(sorted[sorted.len() - 1] + 1),
+            accuracy: vec![Accuracy::default(); steps.len()],
+        })
+    }
+
+    /// Sets the learning rate. Defaults to `DEFAULT_ALPHA`.
+    pub fn alpha(mut self, alpha: f32) -> SdrClassifier {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Divides `[min, max]` evenly into the buckets, allowing scalar labels
+    /// to be used with `compute_scalar` and a scalar value to be estimated
+    /// for each prediction.
+    pub fn scalar_range(mut self, min: f64, max: f64) -> SdrClassifier {
+        assert!(max > min, "SdrClassifier::scalar_range: 'max' must be greater than 'min'.");
+        self.scalar_range = Some((min, max));
+        self
+    }
+
+    /// Returns the bucket containing `value` (clamped to the scalar range).
+    pub fn bucket_of(&self, value: f64) -> Option<usize> {
+        self.scalar_range.map(|(min, max)| {
+            let pos = (value - min) / (max - min) * self.bucket_count as f64;
+            (pos.max(0.0) as usize).min(self.bucket_count - 1)
+        })
+    }
+
+    /// Returns the value at the center of bucket `bucket`.
+    pub fn bucket_value(&self, bucket: usize) -> Option<f64> {
+        self.scalar_range.map(|(min, max)| {
+            min + (bucket as f64 + 0.5) * (max - min) / self.bucket_count as f64
+        })
+    }
+
+    /// Returns the softmax output of step index `step_idx` for `active`.
+    fn infer(&self, step_idx: usize, active: &[u32]) -> Vec<f32> {
+        let weights = &self.weights[step_idx];
+        let mut probs = vec![0.0f32; self.bucket_count];
+
+        for &idx in active {
+            let row = &weights[(idx as usize * self.bucket_count)..
+                ((idx as usize + 1) * self.bucket_count)];
+            for (p, &w) in probs.iter_mut().zip(row.iter()) { *p += w; }
+        }
+
+        let max = probs.iter().cloned().fold(::std::f32::MIN, f32::max);
+        let mut sum = 0.0;
+        for p in probs.iter_mut() {
+            *p = (*p - max).exp();
+            sum += *p;
+        }
+        for p in probs.iter_mut() { *p /= sum; }
+        probs
+    }
+
+    /// Predicts the labels following `frame`, then learns `label` (if any)
+    /// as the outcome of the activity preceding it by each step.
+    ///
+    /// Any non-zero value within `frame` is considered active.
+    pub fn compute(&mut self, frame: &[u8], label: Option<usize>) -> CmnResult<Vec<Prediction>> {
+        if frame.len() != self.input_len {
+            return Err(format!("SdrClassifier::compute: Frame length ({}) does not match the \
+                input length ({}).", frame.len(), self.input_len).into());
+        }
+        if let Some(l) = label {
+            if l >= self.bucket_count {
+                return Err(CmnError::new(format!("SdrClassifier::compute: Label ({}) out of \
+                    range (bucket count: {}).", l, self.bucket_count)));
+            }
+        }
+
+        let active: Vec<u32> = frame.iter().enumerate()
+            .filter(|&(_, &v)| v > 0)
+            .map(|(i, _)| i as u32)
+            .collect();
+
+        let predictions: Vec<Prediction> = self.steps.iter().enumerate().map(|(step_idx, &step)| {
+            let probabilities = self.infer(step_idx, &active);
+            let bucket = probabilities.iter().enumerate()
+                .fold(0, |best, (b, &p)| if p > probabilities[best] { b } else { best });
+            let value = self.scalar_range.map(|_| {
+                probabilities.iter().enumerate()
+                    .map(|(b, &p)| p as f64 * self.bucket_value(b).unwrap()).sum()
+            });
+            Prediction { step, bucket, probabilities, value }
+        }).collect();
+
+        let max_step = self.steps.iter().cloned().max().unwrap();
+        if self.history.len() > max_step { self.history.pop_front(); }
+        self.history.push_back(HistoryEntry {
+            active,
+            predicted: predictions.iter().map(|p| p.bucket).collect(),
+        });
+
+        if let Some(label) = label {
+            for step_idx in 0..self.steps.len() {
+                let step = self.steps[step_idx];
+                if step >= self.history.len() { continue; }
+                let entry_idx = self.history.len() - 1 - step;
+
+                {
+                    let acc = &mut self.accuracy[step_idx];
+                    acc.total += 1;
+                    if self.history[entry_idx].predicted[step_idx] == label { acc.correct += 1; }
+                }
+
+                let probs = self.infer(step_idx, &self.history[entry_idx].active);
+                let weights = &mut self.weights[step_idx];
+                for &idx in self.history[entry_idx].active.iter() {
+                    let row_idz = idx as usize * self.bucket_count;
+                    for (b, &p) in probs.iter().enumerate() {
+                        let target = if b == label { 1.0 } else { 0.0 };
+                        weights[row_idz + b] += self.alpha * (target - p);
+                    }
+                }
+            }
+        }
+
+        Ok(predictions)
+    }
+
+    /// Like `compute` but accepts a scalar label which is converted to a
+    /// bucket using the scalar range.
+    pub fn compute_scalar(&mut self, frame: &[u8], value: Option<f64>)
+            -> CmnResult<Vec<Prediction>> {
+        if self.scalar_range.is_none() {
+            return Err("SdrClassifier::compute_scalar: No scalar range has been set.".into());
+        }
+        let label = value.map(|v| self.bucket_of(v).unwrap());
+        self.compute(frame, label)
+    }
+
+    /// Returns the running accuracy of predictions made `step` cycles ahead.
+    pub fn accuracy(&self, step: usize) -> Option<Accuracy> {
+        self.steps.iter().position(|&s| s == step).map(|idx| self.accuracy[idx])
+    }
+
+    /// Resets the running accuracy of every step.
+    pub fn reset_accuracy(&mut self) {
+        for acc in self.accuracy.iter_mut() { *acc = Accuracy::default(); }
+    }
+
+    /// Clears the activity history, preventing the next labels from being
+    /// associated with activity which preceded a discontinuity (such as the
+    /// start of a new sequence).
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    #[inline] pub fn input_len(&self) -> usize { self.input_len }
+    #[inline] pub fn steps(&self) -> &[usize] { &self.steps }
+    #[inline] pub fn bucket_count(&self) -> usize { self.bucket_count }
+}
@@ -0,0 +1,385 @@
+//! PCM WAV input encoded as tonotopic activity.
+//!
+//! Each window of samples is transformed with a short-time FFT and reduced
+//! to a filterbank of logarithmically spaced frequency bands. Band `v` of the
+//! tract (low to high frequency) is activated at a position along the u axis
+//! proportional to the band's intensity.
+
+use std::fs::File;
+use std::io::Read;
+use std::f32::consts::PI;
+use std::path::Path;
+use cmn::{CmnError, CmnResult, TractDims, TractFrameMut};
+use map::LayerAddress;
+use ::InputGeneratorTract;
+
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+
+fn le_u16(bytes: &[u8]) -> u16 {
+    (bytes[0] as u16) | (bytes[1] as u16) << 8
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24
+}
+
+fn decode_u8(b: &[u8]) -> f32 { (b[0] as f32 - 128.0) / 128.0 }
+fn decode_i16(b: &[u8]) -> f32 { le_u16(b) as i16 as f32 / 32768.0 }
+fn decode_i24(b: &[u8]) -> f32 { ((le_u32(&[0, b[0], b[1], b[2]]) as i32) >> 8) as f32 / 8388608.0 }
+fn decode_i32(b: &[u8]) -> f32 { le_u32(b) as i32 as f32 / 2147483648.0 }
+fn decode_f32(b: &[u8]) -> f32 { f32::from_bits(le_u32(b)) }
+
+
+/// The samples of a WAV file, mixed down to a single channel and scaled to
+/// [-1.0, 1.0].
+#[derive(Debug, Clone)]
+pub struct WavData {
+    sample_rate: u32,
+    channels: u16,
+    samples: Vec<f32>,
+}
+
+impl WavData {
+    /// Reads an uncompressed (8, 16, 24 or 32-bit integer PCM or 32-bit
+    /// float) WAV file.
+    pub fn open<P: AsRef<Path>>(path: P) -> CmnResult<WavData> {
+        let mut bytes = Vec::new();
+        File::open(path.as_ref())?.read_to_end(&mut bytes)?;
+        WavData::from_bytes(&bytes)
+    }
+
+    /// Parses the contents of a WAV file.
+    pub fn from_bytes(bytes: &[u8]) -> CmnResult<WavData> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("WavData: Not a RIFF/WAVE file.".into());
+        }
+
+        let mut fmt: Option<(u16, u16, u32, u16)> = None;
+        let mut data: Option<&[u8]> = None;
+        let mut pos = 12;
+
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let len = le_u32(&bytes[pos + 4..pos + 8]) as usize;
+            let body_start = pos + 8;
+            let body_end = body_start + len;
+            if body_end > bytes.len() { return Err("WavData: Truncated chunk.".into()); }
+            let body = &bytes[body_start..body_end];
+
+            if id == b"fmt " {
+                if body.len() < 16 { return Err("WavData: Invalid 'fmt ' chunk.".into()); }
+                let mut format = le_u16(&body[0..2]);
+                if format == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+                    format = le_u16(&body[24..26]);
+                }
+                fmt = Some((format, le_u16(&body[2..4]), le_u32(&body[4..8]), le_u16(&body[14..16])));
+            } else if id == b"data" {
+                data = Some(body);
+            }
+
+            // Chunks are padded to an even length:
+            pos = body_end + (len & 1);
+        }
+
+        let (format, channels, sample_rate, bits) = fmt
+            .ok_or(CmnError::from("WavData: Missing 'fmt ' chunk."))?;
+        let data = data.ok_or(CmnError::from("WavData: Missing 'data' chunk."))?;
+
+        if channels == 0 || sample_rate == 0 {
+            return Err("WavData: Invalid channel count or sample rate.".into());
+        }
+
+        let decode: fn(&[u8]) -> f32 = match (format, bits) {
+            (WAVE_FORMAT_PCM, 8) => decode_u8,
+            (WAVE_FORMAT_PCM, 16) => decode_i16,
+            (WAVE_FORMAT_PCM, 24) => decode_i24,
+            (WAVE_FORMAT_PCM, 32) => decode_i32,
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => decode_f32,
+            (f, b) => return Err(format!("WavData: Unsupported sample format (format tag: \
+                {:#06x}, bits per sample: {}).", f, b).into()),
+        };
+
+        let frame_len = (bits as usize / 8) * channels as usize;
+        let samples = data.chunks(frame_len)
+            .filter(|frame| frame.len() == frame_len)
+            .map(|frame| {
+                frame.chunks(bits as usize / 8).map(decode).sum::<f32>() / channels as f32
+            })
+            .collect();
+
+        Ok(WavData { sample_rate, channels, samples })
+    }
+
+    #[inline] pub fn sample_rate(&self) -> u32 { self.sample_rate }
+    /// The number of channels in the original file.
+    #[inline] pub fn channels(&self) -> u16 { self.channels }
+    /// The mixed-down samples.
+    #[inline] pub fn samples(&self) -> &[f32] { &self.samples }
+}
+
+
+/// In-place iterative radix-2 FFT. `re.len()` must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let ang = -2.0 * PI / len as f32;
+        let mut start = 0;
+        while start < n {
+            for k in 0..(len / 2) {
+                let (w_re, w_im) = ((ang * k as f32).cos(), (ang * k as f32).sin());
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+            start += len;
+        }
+        len <<= 1;
+    }
+}
+
+
+/// Streams one or more WAV files into a tract as frequency-by-intensity
+/// activity.
+///
+/// Every `cycles_per_frame` cycles the encoder advances by `hop_len`
+/// samples and analyzes the following `window_len` samples. Files are
+/// played in order with the window restarting at the beginning of each.
+#[derive(Debug)]
+pub struct WavAudio {
+    files: Vec<WavData>,
+    file_idx: usize,
+    sample_idx: usize,
+    window_len: usize,
+    hop_len: usize,
+    window: Vec<f32>,
+    band_bins: Vec<(usize, usize)>,
+    freq_range: (f32, f32),
+    db_range: (f32, f32),
+    active_width: u32,
+    cycles_per_frame: usize,
+    repeat_counter: usize,
+    loop_files: bool,
+    intensities: Vec<f32>,
+    tract_dims: TractDims,
+}
+
+impl WavAudio {
+    /// Creates a new encoder for a tract with one frequency band per row
+    /// (v) spanning `freq_range` (Hz).
+    ///
+    /// `window_len` must be a power of two. All files must share a sample
+    /// rate.
+    pub fn new<S: AsRef<str>>(file_names: &[S], freq_range: (f32, f32), window_len: usize,
+            hop_len: usize, cycles_per_frame: usize, tract_dims: &TractDims)
+            -> CmnResult<WavAudio>
+    {
+        if file_names.is_empty() {
+            return Err("WavAudio::new: No files specified.".into());
+        }
+        if !window_len.is_power_of_two() || window_len < 2 {
+            return Err(format!("WavAudio::new: The window length ({}) must be a power of two.",
+                window_len).into());
+        }
+        if hop_len == 0 || cycles_per_frame == 0 {
+            return Err("WavAudio::new: 'hop_len' and 'cycles_per_frame' must be greater \
+                than zero.".into());
+        }
+        if tract_dims.depth() != 1 {
+            return Err(format!("WavAudio::new: Tract depth must be one (found: {}).",
+                tract_dims.depth()).into());
+        }
+
+        let mut files = Vec::with_capacity(file_names.len());
+        for file_name in file_names {
+            files.push(WavData::open(super::resolve_data_file(file_name.as_ref())?)?);
+        }
+        let sample_rate = files[0].sample_rate();
+        if let Some(f) = files.iter().position(|f| f.sample_rate() != sample_rate) {
+            return Err(format!("WavAudio::new: Sample rate mismatch ('{}': {} Hz, '{}': {} Hz).",
+                file_names[0].as_ref(), sample_rate, file_names[f].as_ref(),
+                files[f].sample_rate()).into());
+        }
+
+        let nyquist = sample_rate as f32 / 2.0;
+        if !(freq_range.0 > 0.0 && freq_range.0 < freq_range.1 && freq_range.1 <= nyquist) {
+            return Err(format!("WavAudio::new: Invalid frequency range: {:?} (nyquist: {} Hz).",
+                freq_range, nyquist).into());
+        }
+
+        // Hann window:
+        let window = (0..window_len).map(|i| {
+            0.5 - 0.5 * (2.0 * PI * i as f32 / (window_len - 1) as f32).cos()
+        }).collect();
+
+        let mut wa = WavAudio {
+            files,
+            file_idx: 0,
+            sample_idx: 0,
+            window_len,
+            hop_len,
+            window,
+            band_bins: Vec::new(),
+            freq_range,
+            db_range: (-60.0, 0.0),
+            active_width: 1,
+            cycles_per_frame,
+            repeat_counter: 0,
+            loop_files: true,
+            intensities: vec![0.0; tract_dims.v_size() as usize],
+            tract_dims: tract_dims.clone(),
+        };
+        wa.band_bins = wa.calc_band_bins(sample_rate);
+        wa.analyze();
+        Ok(wa)
+    }
+
+    /// Sets the range of band levels (in dBFS) mapped onto the u axis.
+    /// Bands quieter than the lower bound are left inactive. Defaults to
+    /// `(-60.0, 0.0)`.
+    pub fn db_range(mut self, db_range: (f32, f32)) -> CmnResult<WavAudio> {
+        if !(db_range.0 < db_range.1) {
+            return Err(format!("WavAudio::db_range: Invalid range: {:?}.", db_range).into());
+        }
+        self.db_range = db_range;
+        self.analyze();
+        Ok(self)
+    }
+
+    /// Sets the number of consecutive cells activated per band. Defaults to
+    /// one.
+    pub fn active_width(mut self, active_width: u32) -> WavAudio {
+        self.active_width = active_width.max(1);
+        self
+    }
+
+    /// Sets whether or not to restart from the first file after the last.
+    /// Defaults to true.
+    pub fn loop_files(mut self, loop_files: bool) -> WavAudio {
+        self.loop_files = loop_files;
+        self
+    }
+
+    /// Returns the FFT bin range of each band, logarithmically spaced across
+    /// the frequency range.
+    fn calc_band_bins(&self, sample_rate: u32) -> Vec<(usize, usize)> {
+        let band_count = self.tract_dims.v_size() as usize;
+        let bin_hz = sample_rate as f32 / self.window_len as f32;
+        let max_bin = self.window_len / 2;
+        let (lo, hi) = (self.freq_range.0.ln(), self.freq_range.1.ln());
+
+        (0..band_count).map(|b| {
+            let edge = |i: usize| (lo + (hi - lo) * i as f32 / band_count as f32).exp() / bin_hz;
+            let bin_lo = (edge(b).round() as usize).min(max_bin);
+            let bin_hi = (edge(b + 1).round() as usize).max(bin_lo + 1).min(max_bin + 1);
+            (bin_lo, bin_hi)
+        }).collect()
+    }
+
+    /// Computes band intensities (0.0 - 1.0, or negative if below the
+    /// range) for the current window.
+    fn analyze(&mut self) {
+        let samples = match self.files.get(self.file_idx) {
+            Some(f) => f.samples(),
+            None => {
+                for i in self.intensities.iter_mut() { *i = -1.0; }
+                return;
+            },
+        };
+
+        let mut re: Vec<f32> = (0..self.window_len).map(|i| {
+            samples.get(self.sample_idx + i).cloned().unwrap_or(0.0) * self.window[i]
+        }).collect();
+        let mut im = vec![0.0f32; self.window_len];
+        fft(&mut re, &mut im);
+
+        // Scales the magnitude of a full-scale sinusoid to 1.0 (0 dBFS):
+        let scale = 2.0 / self.window.iter().sum::<f32>();
+        let (db_lo, db_hi) = self.db_range;
+
+        for (intensity, &(bin_lo, bin_hi)) in self.intensities.iter_mut().zip(self.band_bins.iter()) {
+            let peak = (bin_lo..bin_hi)
+                .map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt() * scale)
+                .fold(0.0f32, f32::max);
+            let db = 20.0 * (peak + 1e-9).log10();
+            *intensity = if db < db_lo { -1.0 } else { ((db - db_lo) / (db_hi - db_lo)).min(1.0) };
+        }
+    }
+
+    /// Advances the window by one hop.
+    pub fn advance(&mut self) {
+        self.sample_idx += self.hop_len;
+
+        let file_len = self.files.get(self.file_idx).map(|f| f.samples().len()).unwrap_or(0);
+        if self.sample_idx >= file_len {
+            self.sample_idx = 0;
+            self.file_idx += 1;
+            if self.file_idx >= self.files.len() && self.loop_files {
+                self.file_idx = 0;
+            }
+        }
+        self.analyze();
+    }
+
+    /// The current band intensities, from low to high frequency. Inactive
+    /// bands are negative.
+    #[inline] pub fn intensities(&self) -> &[f32] { &self.intensities }
+    /// The index of the file currently being played.
+    #[inline] pub fn file_idx(&self) -> usize { self.file_idx }
+    /// Returns true once every file has been played (if not looping).
+    #[inline] pub fn is_finished(&self) -> bool { self.file_idx >= self.files.len() }
+}
+
+impl InputGeneratorTract for WavAudio {
+    fn write_into(&mut self, tract_frame: &mut TractFrameMut, _: LayerAddress) {
+        assert!(tract_frame.dims() == &self.tract_dims, "WavAudio::write_into: Tract \
+            dimension mismatch.");
+        let u_size = self.tract_dims.u_size() as usize;
+        let width = (self.active_width as usize).min(u_size);
+        let frame = tract_frame.frame_mut();
+
+        for (v, &intensity) in self.intensities.iter().enumerate() {
+            let row = &mut frame[(v * u_size)..((v + 1) * u_size)];
+            for axn in row.iter_mut() { *axn = 0; }
+
+            if intensity >= 0.0 {
+                let u_start = ((intensity * (u_size - width) as f32).round() as usize)
+                    .min(u_size - width);
+                for axn in row[u_start..(u_start + width)].iter_mut() { *axn = 255; }
+            }
+        }
+    }
+
+    fn cycle_next(&mut self) {
+        self.repeat_counter += 1;
+
+        if self.repeat_counter >= self.cycles_per_frame {
+            self.repeat_counter = 0;
+            self.advance();
+        }
+    }
+}
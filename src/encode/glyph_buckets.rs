@@ -1,7 +1,8 @@
 use std::iter;
 use std::path::PathBuf;
 // use find_folder::Search;
-use super::IdxData;
+use cmn::CmnResult;
+use super::{IdxData, IdxType};
 
 const PRNT: bool = false;
 const PRINT_EVERY: usize = 10000;
@@ -14,21 +15,31 @@ pub struct GlyphBuckets {
 }
 
 impl GlyphBuckets {
-    pub fn new(label_file: PathBuf, image_file: PathBuf) -> GlyphBuckets {
+    pub fn new(label_file: PathBuf, image_file: PathBuf) -> CmnResult<GlyphBuckets> {
         let bucket_count = 10;
         // let label_file = Search::ParentsThenKids(3, 3).for_folder("tmp_data")
         //     .expect("GlyphBuckets::new(): 'label_file'").join("train-labels-idx1-ubyte");
         // let image_file = Search::ParentsThenKids(3, 3).for_folder("tmp_data")
         //     .expect("GlyphBuckets::new(): 'image_file'").join("train-images-idx3-ubyte");
-        let labels = IdxData::new(label_file, false);
-        let mut images = IdxData::new(image_file, true);
+        let labels = IdxData::new(label_file, false)?;
+        let mut images = IdxData::new(image_file, true)?;
 
-        assert!(images.dims().len() == 3, "GlyphBuckets::new(): Source idx file must have three \
-            dimensions.");
+        if images.dims().len() != 3 {
+            return Err(format!("GlyphBuckets::new(): Source idx file ('{}') must have three \
+                dimensions.", images.file_path().display()).into());
+        }
+
+        if images.dims()[0] != labels.dims()[0] {
+            return Err(format!("GlyphBuckets::new(): The images file ('{}') must contain the \
+                same number of elements as the labels file ('{}').",
+                images.file_path().display(), labels.file_path().display()).into());
+        }
 
-        assert!(images.dims()[0] == labels.dims()[0], "GlyphBuckets::new(): The images file \
-            ('{}') must contain the same number of elements as the labels file ('{}').",
-            images.file_path().display(), labels.file_path().display());
+        if images.elem_type() != IdxType::U8 {
+            return Err(format!("GlyphBuckets::new(): The images file ('{}') must contain \
+                unsigned bytes (found: {:?}).", images.file_path().display(),
+                images.elem_type()).into());
+        }
 
         let image_count = images.dims()[0];
         let image_dims = (images.dims()[1], images.dims()[2]);
@@ -40,9 +51,11 @@ impl GlyphBuckets {
             |_| Vec::with_capacity(bucket_len_approx)).collect();
 
         for i in 0..image_count {
-            let label: u8 = labels[i];
-            assert!(label as usize <= bucket_count, "GlyphBuckets::new(): \
-                label ({}) exceeds bucket count ({}).", label, bucket_count);
+            let label = labels.element_f64(i);
+            if label < 0.0 || label as usize >= bucket_count {
+                return Err(format!("GlyphBuckets::new(): label ({}) exceeds bucket count ({}).",
+                    label, bucket_count).into());
+            }
 
             // let img_idz = i * image_len;
             // let img_idn = img_idz + image_len;
@@ -56,7 +69,7 @@ impl GlyphBuckets {
             // bucket.reserve(image_len);
             // debug_assert!(prev_len + image_len >= bucket.capacity());
             // unsafe { bucket.set_len(prev_len + image_len); }
-            images.read_into_vec(image_len, &mut bucket);
+            images.read_into_vec(image_len, &mut bucket)?;
 
             if PRNT && i % PRINT_EVERY == 0 {
             // if i >= 10000 && i < 10010 {
@@ -79,11 +92,11 @@ impl GlyphBuckets {
 
         let cursors = iter::repeat(0).take(buckets.len()).collect();
 
-        GlyphBuckets {
+        Ok(GlyphBuckets {
             buckets: buckets,
             glyph_dims: image_dims,
             cursors: cursors,
-        }
+        })
     }
 
     #[inline]
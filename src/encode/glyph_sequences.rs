@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use rand::distributions::{Distribution, Range};
 use rand::{FromEntropy, rngs::SmallRng};
-use cmn::{CorticalDims, TractFrameMut};
+use cmn::{CmnResult, CorticalDims, TractFrameMut};
 use map::{self, LayerAddress, AxonTags};
 use subcortex::{InputGeneratorTract, InputGeneratorLayer, /*SubcorticalNucleusLayer*/};
 use encode::GlyphBuckets;
@@ -69,7 +69,16 @@ impl GlyphSequences {
 
     pub fn new(layers: &mut HashMap<LayerAddress, InputGeneratorLayer>, seq_lens: (usize, usize),
                 seq_count: usize, scale: f32, hrz_dims: (u32, u32), label_file: PathBuf,
-                image_file: PathBuf) -> GlyphSequences
+                image_file: PathBuf) -> CmnResult<GlyphSequences>
+    {
+        GlyphSequences::with_rng(layers, seq_lens, seq_count, scale, hrz_dims, label_file,
+            image_file, SmallRng::from_entropy())
+    }
+
+    /// Returns a new set of glyph sequences generated using `rng`.
+    pub fn with_rng(layers: &mut HashMap<LayerAddress, InputGeneratorLayer>, seq_lens: (usize, usize),
+                seq_count: usize, scale: f32, hrz_dims: (u32, u32), label_file: PathBuf,
+                image_file: PathBuf, mut rng: SmallRng) -> CmnResult<GlyphSequences>
     {
         assert!(seq_lens.1 >= seq_lens.0, "GlyphSequences::new(): Sequence length range ('seq_lens') \
             invalid. High end must at least be equal to low end: '{:?}'.", seq_lens);
@@ -107,8 +116,7 @@ impl GlyphSequences {
 
         }
 
-        let buckets = GlyphBuckets::new(label_file, image_file);
-        let mut rng = SmallRng::from_entropy();
+        let buckets = GlyphBuckets::new(label_file, image_file)?;
         let mut sequences = Vec::with_capacity(seq_count);
 
         // Build sequences of bucket_ids:
@@ -124,7 +132,7 @@ impl GlyphSequences {
             sequences.push(seq);
         }
 
-        GlyphSequences {
+        Ok(GlyphSequences {
             // sequences: sequences,
             buckets: buckets,
             // layer_dims: [layer_dims.clone(), layer_dims.clone()],
@@ -134,7 +142,7 @@ impl GlyphSequences {
             val_layer_addr: val_layer_addr.expect("GlyphSequences::new(): Nonspatial layer address not set."),
             cursor: SeqReader::new(sequences),
             scale: scale,
-        }
+        })
     }
 
     pub fn sequences(&self) -> &Vec<Vec<usize>> {
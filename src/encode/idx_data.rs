@@ -1,127 +1,358 @@
-use std::error::Error;
+use std::fmt;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::{BufReader, SeekFrom};
+use std::io::BufReader;
 use std::path::{PathBuf, Path};
-use std::iter;
 use std::ops::{Index, IndexMut, Range, RangeTo, RangeFrom, RangeFull};
-// use find_folder::Search;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use cmn::{CmnError, CmnResult};
+
+
+/// The element type of an IDX file, as coded by the third byte of its magic
+/// number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdxType {
+    U8,
+    I8,
+    I16,
+    I32,
+    F32,
+    F64,
+}
+
+impl IdxType {
+    pub fn from_code(code: u8) -> CmnResult<IdxType> {
+        match code {
+            0x08 => Ok(IdxType::U8),
+            0x09 => Ok(IdxType::I8),
+            0x0B => Ok(IdxType::I16),
+            0x0C => Ok(IdxType::I32),
+            0x0D => Ok(IdxType::F32),
+            0x0E => Ok(IdxType::F64),
+            c => Err(format!("IdxType::from_code: Invalid IDX data type: {:#04x}.", c).into()),
+        }
+    }
 
-#[derive(Debug)]
+    pub fn code(&self) -> u8 {
+        match *self {
+            IdxType::U8 => 0x08,
+            IdxType::I8 => 0x09,
+            IdxType::I16 => 0x0B,
+            IdxType::I32 => 0x0C,
+            IdxType::F32 => 0x0D,
+            IdxType::F64 => 0x0E,
+        }
+    }
+
+    /// The size of an element in bytes.
+    pub fn size(&self) -> usize {
+        match *self {
+            IdxType::U8 | IdxType::I8 => 1,
+            IdxType::I16 => 2,
+            IdxType::I32 | IdxType::F32 => 4,
+            IdxType::F64 => 8,
+        }
+    }
+
+    /// Decodes a single big-endian element.
+    fn decode_f64(&self, bytes: &[u8]) -> f64 {
+        let be = bytes.iter().fold(0u64, |val, &b| (val << 8) | b as u64);
+        match *self {
+            IdxType::U8 => be as u8 as f64,
+            IdxType::I8 => be as u8 as i8 as f64,
+            IdxType::I16 => be as u16 as i16 as f64,
+            IdxType::I32 => be as u32 as i32 as f64,
+            IdxType::F32 => f32::from_bits(be as u32) as f64,
+            IdxType::F64 => f64::from_bits(be),
+        }
+    }
+}
+
+
+/// A type which may be stored in an IDX file.
+pub trait IdxElement: Copy {
+    const IDX_TYPE: IdxType;
+
+    /// Appends the big-endian representation of `self`.
+    fn write_be(&self, buf: &mut Vec<u8>);
+    /// Converts a decoded element.
+    fn from_f64(val: f64) -> Self;
+}
+
+macro_rules! impl_idx_element {
+    ($ty:ty, $idx_ty:expr, $bits_ty:ty, $to_bits:expr) => {
+        impl IdxElement for $ty {
+            const IDX_TYPE: IdxType = $idx_ty;
+
+            fn write_be(&self, buf: &mut Vec<u8>) {
+                let bits: $bits_ty = $to_bits(*self);
+                let size = ::std::mem::size_of::<$bits_ty>();
+                for i in (0..size).rev() {
+                    buf.push((bits >> (i * 8)) as u8);
+                }
+            }
+
+            fn from_f64(val: f64) -> $ty { val as $ty }
+        }
+    };
+}
+
+impl_idx_element!(u8, IdxType::U8, u8, |v: u8| v);
+impl_idx_element!(i8, IdxType::I8, u8, |v: i8| v as u8);
+impl_idx_element!(i16, IdxType::I16, u16, |v: i16| v as u16);
+impl_idx_element!(i32, IdxType::I32, u32, |v: i32| v as u32);
+impl_idx_element!(f32, IdxType::F32, u32, |v: f32| v.to_bits());
+impl_idx_element!(f64, IdxType::F64, u64, |v: f64| v.to_bits());
+
+
+/// Opens a possibly gzip-compressed file.
+fn open_reader(file_path: &Path) -> CmnResult<Box<BufRead + Send>> {
+    let mut reader = BufReader::new(File::open(file_path).map_err(|err| CmnError::new(
+        format!("IdxData: Couldn't open '{}': {}", file_path.display(), err)))?);
+
+    let is_gzip = {
+        let head = reader.fill_buf()?;
+        head.len() >= 2 && head[0] == 0x1f && head[1] == 0x8b
+    };
+
+    if is_gzip {
+        Ok(Box::new(BufReader::new(GzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Reads an IDX header, returning the element type and dimension sizes.
+fn read_header(reader: &mut Read, file_path: &Path) -> CmnResult<(IdxType, Vec<usize>)> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(|err| CmnError::new(format!("IdxData: Couldn't \
+        read the header of '{}': {}", file_path.display(), err)))?;
+
+    if magic[0] != 0 || magic[1] != 0 {
+        return Err(format!("IdxData: '{}' is not an IDX file.", file_path.display()).into());
+    }
+
+    let elem_type = IdxType::from_code(magic[2])?;
+    let mut dims = Vec::with_capacity(magic[3] as usize);
+
+    for _ in 0..magic[3] {
+        let mut size = [0u8; 4];
+        reader.read_exact(&mut size).map_err(|err| CmnError::new(format!("IdxData: Couldn't \
+            read the dimensions of '{}': {}", file_path.display(), err)))?;
+        dims.push(size.iter().fold(0usize, |val, &b| (val << 8) | b as usize));
+    }
+
+    if dims.is_empty() {
+        return Err(format!("IdxData: '{}' has no dimensions.", file_path.display()).into());
+    }
+    Ok((elem_type, dims))
+}
+
+
+/// The contents of an IDX file, optionally gzip-compressed.
+///
+/// Elements are stored as their raw (big-endian) bytes. Use `::elements` or
+/// `::element_f64` to decode elements of types other than `IdxType::U8`.
 pub struct IdxData {
     file_path: PathBuf,
-    file_reader: BufReader<File>,
+    file_reader: Box<BufRead + Send>,
+    elem_type: IdxType,
     data: Vec<u8>,
     dims: Vec<usize>,
+    item_len: usize,
+    data_len: usize,
+    items_read: usize,
 }
 
 impl IdxData {
-    /// Reads a .idx file all at once into a vector `data` or piece at a time via the
-    /// `.read_item()` function.
-    ///
-    /// # Panics
-    /// File::open(), BufReader::read()[x2], BufReader::read_to_end(), Invalid idx file format,
+    /// Reads an IDX file all at once into `::data` or, if `stream_mode` is
+    /// set, one item (sub-matrix along the first dimension) at a time via
+    /// `::next_item` or `::read_into_vec`.
     ///
-    /// [FIXME]: Consolidate error handling and return CmnResult instead of panicing.
-    pub fn new(file_path: PathBuf, stream_mode: bool) -> IdxData {
-        let path_display = file_path.display();
-
-        let file = match File::open(&file_path) {
-            Err(why) => panic!("\ncouldn't open '{}': {}", path_display, Error::description(&why)),
-            Ok(file) => file,
+    /// Gzip-compressed files are detected automatically.
+    pub fn new(file_path: PathBuf, stream_mode: bool) -> CmnResult<IdxData> {
+        let mut reader = open_reader(&file_path)?;
+        let (elem_type, dims) = read_header(&mut reader, &file_path)?;
+
+        // Dimension sizes come from the file and may be arbitrarily large:
+        let item_len = dims[1..].iter().fold(Some(elem_type.size()),
+            |len, &d| len.and_then(|len| len.checked_mul(d)));
+        let data_len = item_len.and_then(|item_len| item_len.checked_mul(dims[0]));
+        let (item_len, data_len) = match (item_len, data_len) {
+            (Some(il), Some(dl)) => (il, dl),
+            _ => return Err(format!("IdxData::new: The dimensions of '{}' ({:?}) are too large.",
+                file_path.display(), dims).into()),
         };
 
-        let mut reader = BufReader::new(file);
-        let mut header_magic: Vec<u8> = iter::repeat(0).take(4).collect();
+        let mut idx = IdxData {
+            file_path,
+            file_reader: reader,
+            elem_type,
+            data: Vec::new(),
+            dims,
+            item_len,
+            data_len,
+            items_read: 0,
+        };
 
-        match reader.read(&mut header_magic[..]) {
-            Err(why) => panic!("\ncouldn't read '{}': {}", path_display, Error::description(&why)),
-            Ok(_) => (), //println!("{} contains:\n{:?}\n{} bytes read.", path_display, header_magic, bytes),
+        if !stream_mode {
+            let len = idx.data_len();
+            let mut data = Vec::new();
+            idx.read_into_vec(len, &mut data)?;
+            idx.data = data;
+            idx.items_read = idx.dims[0];
         }
+        Ok(idx)
+    }
 
-        let magic_data_type = header_magic[2];
-        let magic_dims = header_magic[3] as usize;
-        assert!(magic_data_type == 8, format!("IDX file: '{}' does not contain unsigned bytes.", path_display));
-
-        let mut header_dim_sizes_bytes: Vec<u8> = iter::repeat(0).take(magic_dims * 4).collect();
+    /// Writes an IDX file containing `elements` with dimension sizes `dims`,
+    /// gzip-compressing it if `gzip` is set.
+    pub fn write<T: IdxElement, P: AsRef<Path>>(file_path: P, dims: &[usize], elements: &[T],
+            gzip: bool) -> CmnResult<()>
+    {
+        let file_path = file_path.as_ref();
 
-        match reader.read(&mut header_dim_sizes_bytes[..]) {
-            Err(why) => panic!("\ncouldn't read '{}': {}", path_display, Error::description(&why)),
-            Ok(_) => (), //println!("{} contains:\n{:?}\n{} bytes read.", path_display, header_dim_sizes_bytes, bytes),
+        if dims.is_empty() || dims.len() > 255 {
+            return Err(format!("IdxData::write: Invalid dimension count: {}.", dims.len()).into());
         }
-
-        // let ttl_header_len = 4 + (magic_dims * 4);
-        let mut dim_sizes: Vec<usize> = iter::repeat(0).take(magic_dims).collect();
-
-        for i in 0..magic_dims {
-            let header_ofs = 4 * i;
-            dim_sizes[i] =
-                (header_dim_sizes_bytes[header_ofs] as usize) << 24
-                | (header_dim_sizes_bytes[header_ofs + 1] as usize) << 16
-                | (header_dim_sizes_bytes[header_ofs + 2] as usize) << 8
-                | (header_dim_sizes_bytes[header_ofs + 3] as usize)
-            ;
+        let len = dims.iter().fold(Some(1usize), |len, &d| len.and_then(|len| len.checked_mul(d)));
+        if len != Some(elements.len()) {
+            return Err(format!("IdxData::write: Element count ({}) does not match the \
+                dimensions ({:?}).", elements.len(), dims).into());
         }
 
-        let mut buffer_cap: usize = 1;
-
-        for &size in &dim_sizes {
-            buffer_cap *= size as usize;
+        let mut buf = Vec::with_capacity(4 + dims.len() * 4 + elements.len() * T::IDX_TYPE.size());
+        buf.extend_from_slice(&[0, 0, T::IDX_TYPE.code(), dims.len() as u8]);
+        for &size in dims {
+            if size > u32::max_value() as usize {
+                return Err(format!("IdxData::write: Dimension too large: {}.", size).into());
+            }
+            buf.extend_from_slice(&[(size >> 24) as u8, (size >> 16) as u8, (size >> 8) as u8,
+                size as u8]);
+        }
+        for elem in elements {
+            elem.write_be(&mut buf);
         }
 
-        let mut idx_buffer: Vec<u8> = Vec::with_capacity(buffer_cap);
+        let file = File::create(file_path).map_err(|err| CmnError::new(format!(
+            "IdxData::write: Couldn't create '{}': {}", file_path.display(), err)))?;
+        if gzip {
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            encoder.write_all(&buf)?;
+            encoder.finish()?;
+        } else {
+            let mut file = file;
+            file.write_all(&buf)?;
+        }
+        Ok(())
+    }
 
-        if !stream_mode {
-            // TODO: CONVERT TO STREAM
-            match reader.read_to_end(&mut idx_buffer) {
-                Err(why) => panic!("\ncouldn't read '{}': {}", path_display, Error::description(&why)),
-                Ok(bytes) => println!("{}: {} bytes read.", path_display, bytes),
-            }
+    /// Reads `bytes_to_read` bytes from the file, appending them to `vec`.
+    ///
+    /// `vec` grows only as data is actually read so that a header claiming
+    /// more data than the file contains fails without first allocating it.
+    pub fn read_into_vec(&mut self, bytes_to_read: usize, vec: &mut Vec<u8>) -> CmnResult<()> {
+        let prev_len = vec.len();
+        let bytes_read = (&mut self.file_reader).take(bytes_to_read as u64).read_to_end(vec)
+            .map_err(|err| CmnError::new(format!("IdxData: Couldn't read '{}': {}",
+                self.file_path.display(), err)))?;
+
+        if bytes_read != bytes_to_read {
+            vec.truncate(prev_len);
+            return Err(format!("IdxData: Couldn't read '{}' (truncated?): Expected {} bytes, \
+                found {}.", self.file_path.display(), bytes_to_read, bytes_read).into());
         }
+        Ok(())
+    }
 
-        let header_len_bytes = header_magic.len() + header_dim_sizes_bytes.len();
+    /// Reads the next item (sub-matrix along the first dimension) in stream
+    /// mode, returning `None` after the last.
+    pub fn next_item(&mut self) -> CmnResult<Option<Vec<u8>>> {
+        if self.items_read >= self.dims[0] { return Ok(None); }
+        let mut item = Vec::new();
+        let item_len = self.item_len();
+        self.read_into_vec(item_len, &mut item)?;
+        self.items_read += 1;
+        Ok(Some(item))
+    }
+
+    /// Restarts streaming from the first item.
+    pub fn rewind(&mut self) -> CmnResult<()> {
+        let mut reader = open_reader(&self.file_path)?;
+        read_header(&mut reader, &self.file_path)?;
+        self.file_reader = reader;
+        self.items_read = 0;
+        Ok(())
+    }
 
-        match reader.seek(SeekFrom::Start(header_len_bytes as u64)) {
-            Err(why) => panic!("\ncouldn't seek to '[{}]': {}", header_len_bytes,
-                Error::description(&why)),
-            Ok(_) => (),
+    /// Decodes all loaded elements as `T`, which must match the element
+    /// type of the file.
+    pub fn elements<T: IdxElement>(&self) -> CmnResult<Vec<T>> {
+        if T::IDX_TYPE != self.elem_type {
+            return Err(format!("IdxData::elements: Element type mismatch (file: {:?}, \
+                requested: {:?}).", self.elem_type, T::IDX_TYPE).into());
         }
+        Ok(self.data.chunks(self.elem_type.size())
+            .map(|e| T::from_f64(self.elem_type.decode_f64(e)))
+            .collect())
+    }
 
-        println!("IDXREADER: initialized with dimensions: {:?}", dim_sizes);
+    /// Decodes the loaded element at `idx`.
+    pub fn element_f64(&self, idx: usize) -> f64 {
+        let size = self.elem_type.size();
+        self.elem_type.decode_f64(&self.data[(idx * size)..((idx + 1) * size)])
+    }
 
-        IdxData {
-            file_path: file_path.to_path_buf(),
-            file_reader: reader,
-            data: idx_buffer,
-            dims: dim_sizes,
+    /// Returns the loaded elements converted to unsigned bytes.
+    ///
+    /// Unsigned bytes are returned as is, signed bytes are offset by 128, and
+    /// all other types are scaled linearly from their minimum and maximum
+    /// values to [0, 255].
+    pub fn to_u8_normalized(&self) -> Vec<u8> {
+        match self.elem_type {
+            IdxType::U8 => self.data.clone(),
+            _ => self.normalized(),
         }
     }
 
-    // // TODO: RETURN RESULT
-    // pub fn read(&mut self, buf: &mut [u8]) {
-    //     match self.file_reader.read_exact(buf) {
-    //         Err(why) => panic!("\ncouldn't read '{}': {}", self.file_path.display(),
-    //             Error::description(&why)),
-    //         Ok(_) => (), //println!("{} contains:\n{:?}\n{} bytes read.", path_display, header_dim_sizes_bytes, bytes),
-    //     }
-    // }
-
-    // Feels like this probably exists in std somewhere...
-    pub fn read_into_vec(&mut self, bytes_to_read: usize, vec: &mut Vec<u8>) {
-        let prev_len = vec.len();
-        let new_len = prev_len + bytes_to_read;
+    /// Consumes this `IdxData`, returning the loaded elements converted to
+    /// unsigned bytes (see `::to_u8_normalized`) without copying unsigned
+    /// byte data.
+    pub fn into_u8_normalized(self) -> Vec<u8> {
+        match self.elem_type {
+            IdxType::U8 => self.data,
+            _ => self.normalized(),
+        }
+    }
 
-        vec.reserve(bytes_to_read);
-        debug_assert!(new_len <= vec.capacity());
-        unsafe { vec.set_len(new_len); }
+    fn normalized(&self) -> Vec<u8> {
+        match self.elem_type {
+            IdxType::U8 => self.data.clone(),
+            IdxType::I8 => self.data.iter().map(|&b| b ^ 0x80).collect(),
+            _ => {
+                let count = self.data.len() / self.elem_type.size();
+                let vals: Vec<f64> = (0..count).map(|i| self.element_f64(i)).collect();
+                let (min, max) = vals.iter().fold((::std::f64::MAX, ::std::f64::MIN),
+                    |(min, max), &v| (min.min(v), max.max(v)));
+                let range = if max > min { max - min } else { 1.0 };
+                vals.iter().map(|&v| (((v - min) / range) * 255.0).round() as u8).collect()
+            },
+        }
+    }
 
-        let idx_range = prev_len..new_len;
+    /// The number of bytes in one item (sub-matrix along the first
+    /// dimension).
+    #[inline]
+    pub fn item_len(&self) -> usize {
+        self.item_len
+    }
 
-        match self.file_reader.read_exact(&mut vec[idx_range]) {
-            Err(why) => panic!("\ncouldn't read '{}': {}", self.file_path.display(),
-                Error::description(&why)),
-            Ok(_) => (), //println!("{} contains:\n{:?}\n{} bytes read.", path_display, header_dim_sizes_bytes, bytes),
-        }
+    /// The total number of bytes of element data in the file.
+    #[inline]
+    pub fn data_len(&self) -> usize {
+        self.data_len
     }
 
     #[inline]
@@ -129,6 +360,12 @@ impl IdxData {
         self.file_path.as_path()
     }
 
+    #[inline]
+    pub fn elem_type(&self) -> IdxType {
+        self.elem_type
+    }
+
+    /// The raw (big-endian) bytes of the loaded elements.
     #[inline]
     pub fn data(&self) -> &[u8] {
         &self.data[..]
@@ -140,18 +377,28 @@ impl IdxData {
     }
 }
 
+impl fmt::Debug for IdxData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdxData")
+            .field("file_path", &self.file_path)
+            .field("elem_type", &self.elem_type)
+            .field("dims", &self.dims)
+            .field("data.len()", &self.data.len())
+            .field("items_read", &self.items_read)
+            .finish()
+    }
+}
+
 impl Index<usize> for IdxData {
     type Output = u8;
     #[inline]
     fn index<'a>(&'a self, index: usize) -> &'a u8 {
-        // &self.data[index]
         &(*self.data)[index]
     }
 }
 impl IndexMut<usize> for IdxData {
     #[inline]
     fn index_mut<'a>(&'a mut self, index: usize) -> &'a mut u8 {
-        // &mut self.data[index]
         &mut (*self.data)[index]
     }
 }
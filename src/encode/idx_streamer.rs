@@ -1,12 +1,12 @@
 // use std::path::PathBuf;
-use find_folder::Search;
-use cmn::{CorticalDims, Sdr, TractFrameMut, TractDims};
+use cmn::{CmnResult, CorticalDims, Sdr, TractFrameMut, TractDims};
 use map::LayerAddress;
 use ::InputGeneratorTract;
 use super::IdxData;
 
-//    IDXREADER: Reads IDX files containing a series of two dimensional matrices of unsigned
-//    bytes (u8) into a ganglion (SDR frame buffer: &Sdr)
+//    IDXREADER: Reads IDX files containing a series of two dimensional matrices into a ganglion
+//    (SDR frame buffer: &Sdr). Elements other than unsigned bytes are normalized to [0, 255]
+//    (see `IdxData::to_u8_normalized`).
 #[derive(Debug)]
 pub struct IdxStreamer {
     layer_dims: CorticalDims,
@@ -20,23 +20,23 @@ pub struct IdxStreamer {
     // image_height: usize,
     image_dims: (usize, usize),
     // image_len: usize,
-    idx_data: IdxData,
+    idx_dims: Vec<usize>,
+    frames: Vec<u8>,
 }
 
 impl IdxStreamer {
-    /// # Panics
-    /// All sorts of reasons...
     pub fn new(layer_dims: CorticalDims, file_path_string: String, cycles_per_frame: usize,
-                scale_factor: f32) -> IdxStreamer
+                scale_factor: f32) -> CmnResult<IdxStreamer>
     {
-        let file_path = Search::ParentsThenKids(3, 3).for_folder("tmp_data")
-            .expect("IdxStreamer::new()").join(&file_path_string);
+        let file_path = super::resolve_data_file(&file_path_string)?;
         // let file_path = PathBuf::from(file_path_string.clone());
-        let idx_data = IdxData::new(file_path, false);
+        let idx_data = IdxData::new(file_path, false)?;
         let dim_count = idx_data.dims().len();
 
-        assert!(dim_count <= 3, "IdxStreamer::new(): Cannot handle idx files with more than \
-            three dimensions. [file: '{}']", file_path_string);
+        if dim_count > 3 {
+            return Err(format!("IdxStreamer::new(): Cannot handle idx files with more than \
+                three dimensions. [file: '{}']", file_path_string).into());
+        }
         // let image_width = if dim_count > 1 { idx_data.dims()[1] } else { 1 };
         // let image_height = if dim_count > 2 { idx_data.dims()[2] } else { 1 };
 
@@ -47,21 +47,27 @@ impl IdxStreamer {
 
         println!("IDXREADER: initialized with dimensions: {:?}", idx_data.dims());
 
-        IdxStreamer {
+        // Only the normalized frames are retained:
+        let idx_dims = idx_data.dims().to_vec();
+        let frames_count = idx_dims[0];
+        let frames = idx_data.into_u8_normalized();
+
+        Ok(IdxStreamer {
             layer_dims: layer_dims,
             cycles_per_frame: cycles_per_frame,
             scale_factor: scale_factor,
             repeat_counter: 0,
             frame_counter: 0,
-            frames_count: idx_data.dims()[0],
+            frames_count: frames_count,
             loop_frames: None,
             // image_width: image_width,
             // image_height: image_height,
             image_dims: image_dims,
             // image_len: image_width * image_height,
             // image_len: image_len,
-            idx_data: idx_data,
-        }
+            idx_dims: idx_dims,
+            frames: frames,
+        })
     }
 
     pub fn loop_frames(mut self, frames_to_loop: u32) -> IdxStreamer {
@@ -77,7 +83,7 @@ impl IdxStreamer {
         let img_idz = frame_idx * self.image_len();
 
         for idx in 0..self.image_len() {
-            tract_frame[idx] = self.idx_data.data()[img_idz + idx];
+            tract_frame[idx] = self.frames[img_idz + idx];
         }
 
         return self.image_len();
@@ -88,7 +94,7 @@ impl IdxStreamer {
         assert!(frame_idx < self.frames_count);
         let img_idz = frame_idx * self.image_len();
 
-        return self.idx_data.data()[img_idz];
+        return self.frames[img_idz];
 
     }
 
@@ -157,10 +163,10 @@ impl InputGeneratorTract for IdxStreamer {
         let img_idz = self.frame_counter * self.image_len();
         let img_idn = img_idz + self.image_len();
 
-        match self.idx_data.dims().len() {
-            3 => self.encode_2d_image(&self.idx_data.data()[img_idz..img_idn], tract_frame),
+        match self.idx_dims.len() {
+            3 => self.encode_2d_image(&self.frames[img_idz..img_idn], tract_frame),
             2 => panic!("\nOne dimensional (linear) idx images not yet supported (trival to add)."),
-            1 => self.encode_scalar(&self.idx_data.data()[img_idz..img_idn], tract_frame),
+            1 => self.encode_scalar(&self.frames[img_idz..img_idn], tract_frame),
             _ => panic!("\nIdx files with more than three or less than one dimension(s) not supported."),
         }
 
@@ -0,0 +1,388 @@
+//! Streams ordinary image files (PNG, PGM, PPM) onto the hex grid.
+
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use png;
+use cmn::{CmnError, CmnResult, TractDims, TractFrameMut};
+use map::LayerAddress;
+use ::InputGeneratorTract;
+use super::encode_2d_image;
+
+
+static IMAGE_EXTENSIONS: &'static [&'static str] = &["png", "pgm", "ppm", "pnm"];
+
+
+/// A decoded 8-bit grayscale or RGB image.
+#[derive(Debug, Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    channels: usize,
+    data: Vec<u8>,
+}
+
+impl Image {
+    /// Decodes a PNG, PGM (P2/P5) or PPM (P3/P6) file, determined by its
+    /// contents rather than its extension.
+    ///
+    /// Alpha channels are discarded and samples wider than eight bits are
+    /// reduced to eight.
+    pub fn open<P: AsRef<Path>>(path: P) -> CmnResult<Image> {
+        let path = path.as_ref();
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        let image = if bytes.starts_with(b"\x89PNG") {
+            Image::decode_png(&bytes)
+        } else if bytes.len() >= 2 && bytes[0] == b'P' {
+            Image::decode_netpbm(&bytes)
+        } else {
+            Err("unrecognized file format".into())
+        };
+        image.map_err(|err| CmnError::new(format!("Image::open: Unable to decode '{}': {}",
+            path.display(), err)))
+    }
+
+    fn decode_png(bytes: &[u8]) -> CmnResult<Image> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info()
+            .map_err(|err| CmnError::new(format!("{}", err)))?;
+
+        let (width, height) = (info.width as usize, info.height as usize);
+        let src_channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::RGB | png::ColorType::Indexed => 3,
+            png::ColorType::RGBA => 4,
+        };
+        let channels = if src_channels < 3 { 1 } else { 3 };
+        let line_size = info.line_size;
+
+        // Validate the dimensions before allocating anything:
+        let len = raster_len(&[width, height, channels])?;
+        let buf_len = raster_len(&[line_size, height])?;
+        if line_size < raster_len(&[width, src_channels])? || info.buffer_size() < buf_len {
+            return Err("inconsistent line size".into());
+        }
+
+        let mut buf = vec![0u8; info.buffer_size()];
+        reader.next_frame(&mut buf).map_err(|err| CmnError::new(format!("{}", err)))?;
+
+        let mut data = Vec::with_capacity(len);
+        for y in 0..height {
+            let line = &buf[(y * line_size)..(y * line_size + width * src_channels)];
+            for px in line.chunks(src_channels) {
+                data.extend_from_slice(&px[..channels]);
+            }
+        }
+        Ok(Image { width, height, channels, data })
+    }
+
+    fn decode_netpbm(bytes: &[u8]) -> CmnResult<Image> {
+        let (channels, binary) = match bytes[1] {
+            b'2' => (1, false),
+            b'3' => (3, false),
+            b'5' => (1, true),
+            b'6' => (3, true),
+            b => return Err(format!("unsupported netpbm format: 'P{}'", b as char).into()),
+        };
+
+        // Reads the next whitespace separated decimal, skipping comments:
+        let mut pos = 2;
+        let next_num = |pos: &mut usize| -> CmnResult<usize> {
+            loop {
+                match bytes.get(*pos) {
+                    Some(&b'#') => {
+                        while bytes.get(*pos).map(|&b| b != b'\n').unwrap_or(false) { *pos += 1; }
+                    },
+                    Some(b) if b.is_ascii_whitespace() => *pos += 1,
+                    Some(_) => break,
+                    None => return Err("truncated header".into()),
+                }
+            }
+            let start = *pos;
+            while bytes.get(*pos).map(|b| b.is_ascii_digit()).unwrap_or(false) { *pos += 1; }
+            ::std::str::from_utf8(&bytes[start..*pos]).unwrap().parse::<usize>()
+                .map_err(|_| CmnError::from("invalid header value"))
+        };
+
+        let width = next_num(&mut pos)?;
+        let height = next_num(&mut pos)?;
+        let max_val = next_num(&mut pos)?;
+        if max_val == 0 || max_val > 65535 {
+            return Err(format!("invalid maximum value: {}", max_val).into());
+        }
+
+        let len = raster_len(&[width, height, channels])?;
+        let scale = |val: usize| ((val.min(max_val) * 255) / max_val) as u8;
+
+        if binary {
+            // A single whitespace character separates the header and data:
+            pos += 1;
+            let sample_size = if max_val > 255 { 2 } else { 1 };
+            let raster = raster_len(&[len, sample_size]).ok()
+                .and_then(|raster_len| pos.checked_add(raster_len))
+                .and_then(|end| bytes.get(pos..end))
+                .ok_or(CmnError::from("truncated raster"))?;
+            let mut data = Vec::with_capacity(len);
+            for sample in raster.chunks(sample_size) {
+                let val = sample.iter().fold(0usize, |val, &b| (val << 8) | b as usize);
+                data.push(scale(val));
+            }
+            Ok(Image { width, height, channels, data })
+        } else {
+            // Each sample occupies at least two bytes (a digit and a
+            // separator), save the last:
+            if len > 0 && (len - 1) > bytes.len().saturating_sub(pos) / 2 {
+                return Err("truncated raster".into());
+            }
+            let mut data = Vec::with_capacity(len);
+            for _ in 0..len {
+                let val = next_num(&mut pos)?;
+                data.push(scale(val));
+            }
+            Ok(Image { width, height, channels, data })
+        }
+    }
+
+    /// Returns the image reduced to a single luma channel.
+    pub fn to_grayscale(&self) -> Vec<u8> {
+        if self.channels == 1 { return self.data.clone(); }
+        self.data.chunks(self.channels).map(|px| {
+            ((px[0] as u32 * 299 + px[1] as u32 * 587 + px[2] as u32 * 114) / 1000) as u8
+        }).collect()
+    }
+
+    /// Returns the values of channel `channel_idx` (0: red, 1: green, 2:
+    /// blue). Grayscale images return their only channel.
+    pub fn channel(&self, channel_idx: usize) -> Vec<u8> {
+        if self.channels == 1 { return self.data.clone(); }
+        self.data.chunks(self.channels).map(|px| px[channel_idx]).collect()
+    }
+
+    #[inline] pub fn width(&self) -> usize { self.width }
+    #[inline] pub fn height(&self) -> usize { self.height }
+    #[inline] pub fn channels(&self) -> usize { self.channels }
+    #[inline] pub fn data(&self) -> &[u8] { &self.data }
+}
+
+
+/// Returns the product of `dims`, or an error if it overflows.
+fn raster_len(dims: &[usize]) -> CmnResult<usize> {
+    dims.iter().fold(Some(1usize), |len, &dim| len.and_then(|len| len.checked_mul(dim)))
+        .ok_or(CmnError::from("image dimensions too large"))
+}
+
+
+/// Returns the image files within `paths`, expanding each directory into
+/// its (sorted) image files.
+fn list_images<S: AsRef<str>>(paths: &[S]) -> CmnResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        let path = super::resolve_data_file(path.as_ref())?;
+
+        if path.is_dir() {
+            let mut dir_files = Vec::new();
+            for entry in fs::read_dir(&path)? {
+                let file = entry?.path();
+                let is_image = file.extension().and_then(|e| e.to_str())
+                    .map(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                    .unwrap_or(false);
+                if file.is_file() && is_image { dir_files.push(file); }
+            }
+            dir_files.sort();
+            files.extend(dir_files);
+        } else {
+            files.push(path);
+        }
+    }
+
+    if files.is_empty() {
+        return Err("ImageDir::new: No image files found.".into());
+    }
+    Ok(files)
+}
+
+
+/// A layer fed by an `ImageDir`.
+#[derive(Debug, Clone)]
+struct ImageLayer {
+    addr: LayerAddress,
+    dims: TractDims,
+    channel: Option<usize>,
+}
+
+
+/// Streams a list of image files (or directories of image files) into one
+/// or more layers, resampling each image onto the hex grid with
+/// `encode_2d_image`.
+///
+/// In grayscale mode every layer receives the luma of each image. Otherwise
+/// exactly three layers are required, which receive the red, green, and
+/// blue channels respectively (in layer order).
+///
+/// Files which fail to decode are skipped (see `::skipped_files`). At least
+/// one file must decode.
+#[derive(Debug)]
+pub struct ImageDir {
+    files: Vec<PathBuf>,
+    file_idx: usize,
+    skipped: BTreeSet<usize>,
+    layers: Vec<ImageLayer>,
+    scale: f32,
+    cycles_per_frame: usize,
+    repeat_counter: usize,
+    loop_images: bool,
+    image: Option<Image>,
+    planes: Vec<Vec<u8>>,
+}
+
+impl ImageDir {
+    pub fn new<S: AsRef<str>>(paths: &[S], layer_addrs: &[LayerAddress], tract_dims: &[TractDims],
+            grayscale: bool, scale: f32, cycles_per_frame: usize) -> CmnResult<ImageDir>
+    {
+        assert!(layer_addrs.len() == tract_dims.len());
+
+        if !grayscale && layer_addrs.len() != 3 {
+            return Err(format!("ImageDir::new: Per-channel encoding requires exactly three \
+                layers (found: {}).", layer_addrs.len()).into());
+        }
+        if cycles_per_frame == 0 || !(scale > 0.0) {
+            return Err("ImageDir::new: 'cycles_per_frame' and 'scale' must be greater than \
+                zero.".into());
+        }
+
+        let layers = layer_addrs.iter().zip(tract_dims.iter()).enumerate()
+            .map(|(i, (&addr, &dims))| ImageLayer {
+                addr,
+                dims,
+                channel: if grayscale { None } else { Some(i) },
+            })
+            .collect();
+
+        let files = list_images(paths)?;
+        let mut skipped = BTreeSet::new();
+        let mut first = Err(CmnError::from("ImageDir::new: No image files found."));
+        for (file_idx, file) in files.iter().enumerate() {
+            match Image::open(file) {
+                Ok(img) => {
+                    first = Ok((file_idx, img));
+                    break;
+                },
+                Err(err) => {
+                    skipped.insert(file_idx);
+                    first = Err(err);
+                },
+            }
+        }
+        let (file_idx, image) = first.map_err(|err| CmnError::new(format!("ImageDir::new: \
+            None of the {} image files found could be decoded (last error: {}).",
+            files.len(), err)))?;
+
+        let mut id = ImageDir {
+            files,
+            file_idx,
+            skipped,
+            layers,
+            scale,
+            cycles_per_frame,
+            repeat_counter: 0,
+            loop_images: true,
+            image: None,
+            planes: Vec::new(),
+        };
+        id.set_image(Some(image));
+        Ok(id)
+    }
+
+    /// Sets whether or not to restart from the first image after the last.
+    /// Defaults to true.
+    pub fn loop_images(mut self, loop_images: bool) -> ImageDir {
+        self.loop_images = loop_images;
+        self
+    }
+
+    fn set_image(&mut self, image: Option<Image>) {
+        self.planes = match image {
+            Some(ref img) => self.layers.iter().map(|lyr| match lyr.channel {
+                Some(c) => img.channel(c),
+                None => img.to_grayscale(),
+            }).collect(),
+            None => Vec::new(),
+        };
+        self.image = image;
+    }
+
+    /// Advances to the next decodable image, skipping (and recording) any
+    /// file which fails to decode.
+    ///
+    /// Returns an error, and clears the current image, if no decodable
+    /// images remain.
+    pub fn advance(&mut self) -> CmnResult<()> {
+        for _ in 0..self.files.len() {
+            self.file_idx += 1;
+            if self.file_idx >= self.files.len() {
+                if !self.loop_images {
+                    self.set_image(None);
+                    return Ok(());
+                }
+                self.file_idx = 0;
+            }
+
+            if self.skipped.contains(&self.file_idx) { continue; }
+
+            match Image::open(&self.files[self.file_idx]) {
+                Ok(img) => {
+                    self.set_image(Some(img));
+                    return Ok(());
+                },
+                Err(_) => { self.skipped.insert(self.file_idx); },
+            }
+        }
+        self.set_image(None);
+        Err("ImageDir::advance: No decodable images remain.".into())
+    }
+
+    /// The paths of all files skipped so far because they failed to decode.
+    pub fn skipped_files(&self) -> Vec<&Path> {
+        self.skipped.iter().map(|&file_idx| self.files[file_idx].as_path()).collect()
+    }
+
+    /// The path of the current image.
+    pub fn file(&self) -> Option<&Path> {
+        self.files.get(self.file_idx).map(|f| f.as_path())
+    }
+
+    /// The current image, or `None` once every image has been shown (if not
+    /// looping).
+    #[inline] pub fn image(&self) -> Option<&Image> { self.image.as_ref() }
+}
+
+impl InputGeneratorTract for ImageDir {
+    fn write_into(&mut self, frame: &mut TractFrameMut, addr: LayerAddress) {
+        let lyr_idx = self.layers.iter().position(|lyr| lyr.addr == addr)
+            .expect("ImageDir::write_into: Unknown layer address.");
+
+        for axn in frame.frame_mut().iter_mut() { *axn = 0; }
+
+        if let Some(ref img) = self.image {
+            encode_2d_image((img.width(), img.height()), &self.layers[lyr_idx].dims, self.scale,
+                &self.planes[lyr_idx], frame);
+        }
+    }
+
+    fn cycle_next(&mut self) {
+        self.repeat_counter += 1;
+
+        if self.repeat_counter >= self.cycles_per_frame {
+            self.repeat_counter = 0;
+            // Once no decodable images remain the image is cleared and
+            // nothing further is written (see `::advance`):
+            if self.image.is_some() { self.advance().ok(); }
+        }
+    }
+}
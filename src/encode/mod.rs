@@ -9,19 +9,25 @@ mod reverso_scalar_sequence;
 mod vector_encoder;
 mod hex_mold_test;
 mod scalar_sdr_gradiant;
+mod replay;
+mod audio;
+mod image_dir;
+mod text_sequence;
 pub mod idx_streamer;
 
 // use std::cmp;
 use std::ops::AddAssign;
+use std::path::PathBuf;
 use std::fmt::{Debug, Display};
 use num::{Num, NumCast};
+use find_folder::Search;
 // use rand;
 // use rand::distributions::{Range, Distribution};
-use cmn::{TractFrameMut, ParaHexArray};
-pub use self::write::{ScalarGlyphWriter, ScalarSdrWriter, Vector2dWriter, encode_scalar,
+use cmn::{CmnError, CmnResult, TractFrameMut, ParaHexArray};
+pub use self::write::{ScalarGlyphWriter, ScalarSdrWriter, Vector2dWriter, encode_scalar, encode_scalar_with_rng,
     encode_hex_mold_scaled, gen_axn_idxs, write_rand_subset_linear, write_rand_subset_stochastic};
 pub use self::idx_streamer::IdxStreamer;
-pub use self::idx_data::IdxData;
+pub use self::idx_data::{IdxData, IdxType, IdxElement};
 pub use self::glyph_buckets::GlyphBuckets;
 pub use self::glyph_sequences::GlyphSequences;
 pub use self::sensory_tract::SensoryTract;
@@ -30,6 +36,10 @@ pub use self::reverso_scalar_sequence::ReversoScalarSequence;
 pub use self::vector_encoder::VectorEncoder;
 pub use self::hex_mold_test::HexMoldTest;
 pub use self::scalar_sdr_gradiant::ScalarSdrGradiant;
+pub use self::replay::Replay;
+pub use self::audio::{WavAudio, WavData};
+pub use self::image_dir::{ImageDir, Image};
+pub use self::text_sequence::{TextSequence, TextSettings, Vocabulary, tokenize};
 
 
 
@@ -43,6 +53,23 @@ impl<T> ScalarEncodable for T where T: Num + NumCast + PartialOrd + Debug + Disp
 
 
 
+/// Resolves the path of a data file, searching the 'tmp_data' folder if it
+/// does not exist relative to the working directory.
+fn resolve_data_file(file_name: &str) -> CmnResult<PathBuf> {
+    let path = PathBuf::from(file_name);
+    if path.exists() { return Ok(path); }
+
+    let path = Search::ParentsThenKids(3, 3).for_folder("tmp_data")
+        .map_err(|err| CmnError::new(format!("Unable to find '{}': {:?}", file_name, err)))?
+        .join(file_name);
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(format!("Unable to find '{}'.", file_name).into())
+    }
+}
+
+
 fn calc_offs(v_size: usize, u_size: usize, y_size: usize, x_size: usize, hex_side: f32) -> (f32, f32) {
     let v_mid = v_size >> 1;
     let u_mid = u_size >> 1;
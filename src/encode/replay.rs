@@ -0,0 +1,227 @@
+//! Replays recorded axon frames (see `cortex::recorder`) into the layers of
+//! an input generator area.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use cmn::{CmnError, CmnResult, CorticalDims, TractFrameMut};
+use map::{LayerAddress, AxonDomain};
+use cortex::recorder::{RecordingReader, RecordedCycle, StreamInfo};
+use subcortex::{InputGeneratorTract, InputGeneratorLayer};
+
+
+/// The location of a layer's axons within a recording.
+#[derive(Debug, Clone)]
+struct ReplaySource {
+    stream_idx: usize,
+    axn_range: Range<usize>,
+}
+
+
+/// Finds the axons of a layer within a recorded stream.
+///
+/// The slices of the stream tagged with the layer's name must be contiguous,
+/// equal in number to the layer's depth, and each must have the layer's
+/// dimensions.
+fn find_slices(stream: &StreamInfo, lyr_name: &str, lyr_dims: &CorticalDims)
+        -> CmnResult<Option<Range<usize>>>
+{
+    let tract_map = stream.tract_map();
+    let slc_ids: Vec<usize> = tract_map.tags().iter().enumerate()
+        .filter(|&(_, tag)| tag == lyr_name)
+        .map(|(slc_id, _)| slc_id)
+        .collect();
+
+    if slc_ids.is_empty() { return Ok(None); }
+
+    let slc_range = slc_ids[0]..(slc_ids[slc_ids.len() - 1] + 1);
+
+    if slc_range.len() != slc_ids.len() || slc_ids.len() != lyr_dims.depth() as usize {
+        return Err(format!("Replay: Layer '{}' has a depth of {} but stream '{}' ('{}') \
+            contains {} non-contiguous or mismatched slices with that name.", lyr_name,
+            lyr_dims.depth(), stream.area_name(), stream.kind_name(), slc_ids.len()).into());
+    }
+
+    for slc_id in slc_range.clone() {
+        let (v_size, u_size) = (tract_map.v_sizes()[slc_id], tract_map.u_sizes()[slc_id]);
+        if v_size != lyr_dims.v_size() || u_size != lyr_dims.u_size() {
+            return Err(format!("Replay: Slice {} of stream '{}' ('{}') has dimensions \
+                {}x{} but layer '{}' is {}x{}.", slc_id, stream.area_name(), stream.kind_name(),
+                v_size, u_size, lyr_name, lyr_dims.v_size(), lyr_dims.u_size()).into());
+        }
+    }
+
+    Ok(Some(tract_map.axn_id_range(slc_range)))
+}
+
+
+/// Feeds frames from a recording file into the layers of an input
+/// generator area, one recorded cycle every `cycles_per_frame` cycles.
+///
+/// Each layer is matched with the slices of a recorded output layer, within
+/// an axon stream, having the same output axon tags (see
+/// `StreamInfo::output_layers`). A recording of a single layer
+/// (`SamplerKind::Axons(Some(..))`) may instead drive an area containing a
+/// single layer of the same depth and dimensions regardless of its tags.
+///
+/// Errors encountered while replaying (such as a corrupt recording) zero
+/// all further frames and are available from `::error`.
+///
+/// Once the recording is exhausted, layers are either fed from the first
+/// recorded cycle again (`looped`) or zeroed.
+#[derive(Debug)]
+pub struct Replay {
+    reader: RecordingReader,
+    sources: HashMap<LayerAddress, ReplaySource>,
+    cycles_per_frame: usize,
+    looped: bool,
+    repeat_counter: usize,
+    current: Option<RecordedCycle>,
+    error: Option<CmnError>,
+}
+
+impl Replay {
+    pub fn new<P: AsRef<Path>>(file: P, layers: &HashMap<LayerAddress, InputGeneratorLayer>,
+            cycles_per_frame: usize, looped: bool) -> CmnResult<Replay>
+    {
+        let file = file.as_ref();
+        let mut reader = RecordingReader::open(file)?;
+        let mut sources = HashMap::with_capacity(layers.len());
+
+        if cycles_per_frame == 0 {
+            return Err("Replay::new: 'cycles_per_frame' must be greater than zero.".into());
+        }
+
+        for (&addr, layer) in layers.iter() {
+            let lyr_name = layer.sub().name();
+            let lyr_dims = layer.sub().dims().ok_or_else(|| CmnError::new(format!(
+                "Replay::new: Layer '{}' has no dimensions.", lyr_name)))?;
+            let lyr_tags = match *layer.sub().axon_domain() {
+                AxonDomain::Output(ref sig) => Some(sig.tags()),
+                _ => None,
+            };
+            let mut source = None;
+
+            'streams: for (stream_idx, stream) in reader.streams().iter().enumerate() {
+                if stream.is_signed() || !stream.is_axons() { continue; }
+
+                for &(ref stream_lyr_name, ref stream_lyr_tags) in stream.output_layers() {
+                    if Some(stream_lyr_tags) != lyr_tags { continue; }
+
+                    if let Some(axn_range) = find_slices(stream, stream_lyr_name, lyr_dims)? {
+                        source = Some(ReplaySource { stream_idx, axn_range });
+                        break 'streams;
+                    }
+                }
+            }
+
+            // Fall back to a lone single-layer stream for a lone layer:
+            if source.is_none() && layers.len() == 1 && reader.streams().len() == 1 {
+                let stream = &reader.streams()[0];
+                if !stream.is_signed() && stream.is_axons() {
+                    if let Some(stream_lyr_name) = stream.layer_name() {
+                        source = find_slices(stream, stream_lyr_name, lyr_dims)?
+                            .map(|axn_range| ReplaySource { stream_idx: 0, axn_range });
+                    }
+                }
+            }
+
+            match source {
+                Some(s) => { sources.insert(addr, s); },
+                None => return Err(format!("Replay::new: No recorded axon stream in '{}' \
+                    matches layer '{}' (depth: {}, dims: {}x{}).", file.display(), lyr_name,
+                    lyr_dims.depth(), lyr_dims.v_size(), lyr_dims.u_size()).into()),
+            }
+        }
+
+        let current = reader.next_cycle()?;
+        if current.is_none() {
+            return Err(format!("Replay::new: The recording '{}' contains no cycles.",
+                file.display()).into());
+        }
+
+        Ok(Replay {
+            reader,
+            sources,
+            cycles_per_frame,
+            looped,
+            repeat_counter: 0,
+            current,
+            error: None,
+        })
+    }
+
+    /// Advances to the next recorded cycle, rewinding if looped.
+    ///
+    /// The current cycle is cleared if an error occurs.
+    fn advance(&mut self) -> CmnResult<()> {
+        self.current = None;
+        self.current = self.reader.next_cycle()?;
+
+        if self.current.is_none() && self.looped {
+            self.reader.seek_cycle(0)?;
+            self.current = self.reader.next_cycle()?;
+        }
+        Ok(())
+    }
+
+    /// Returns true once a non-looped recording has been exhausted.
+    pub fn is_finished(&self) -> bool {
+        self.current.is_none()
+    }
+
+    /// Returns the first error encountered while reading the recording or
+    /// writing frames, if any.
+    ///
+    /// Frames which could not be written are zeroed.
+    pub fn error(&self) -> Option<&CmnError> {
+        self.error.as_ref()
+    }
+
+    /// Records `err` unless an earlier error has already been recorded.
+    fn set_error(&mut self, err: CmnError) {
+        if self.error.is_none() { self.error = Some(err); }
+    }
+}
+
+impl InputGeneratorTract for Replay {
+    fn write_into(&mut self, frame: &mut TractFrameMut, addr: LayerAddress) {
+        let res = match (self.sources.get(&addr), self.current.as_ref()) {
+            (Some(source), Some(rc)) => {
+                let src = rc.frame(source.stream_idx).get(source.axn_range.clone())
+                    .unwrap_or(&[]);
+                if src.len() == frame.frame_mut().len() {
+                    frame.frame_mut().copy_from_slice(src);
+                    Ok(())
+                } else {
+                    Err(CmnError::new(format!("Replay::write_into: Frame length mismatch \
+                        (recorded: {}, tract: {}).", src.len(), frame.frame_mut().len())))
+                }
+            },
+            (Some(_), None) => Ok(()),
+            (None, _) => Err(CmnError::new(format!("Replay::write_into: Unknown layer \
+                address: {}.", addr))),
+        };
+
+        if let Err(err) = res {
+            self.set_error(err);
+        }
+        if self.current.is_none() || self.error.is_some() {
+            for axn in frame.frame_mut().iter_mut() { *axn = 0; }
+        }
+    }
+
+    fn cycle_next(&mut self) {
+        self.repeat_counter += 1;
+
+        if self.repeat_counter >= self.cycles_per_frame {
+            self.repeat_counter = 0;
+            if self.current.is_some() && self.error.is_none() {
+                if let Err(err) = self.advance() {
+                    self.set_error(CmnError::new(format!("Replay::cycle_next: Error \
+                        reading recording: {}", err)));
+                }
+            }
+        }
+    }
+}
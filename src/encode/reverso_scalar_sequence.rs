@@ -3,6 +3,7 @@
 use std::fmt::Debug;
 use std::ops::AddAssign;
 use num::{Num, NumCast};
+use rand::{FromEntropy, rngs::SmallRng};
 use cmn::TractFrameMut;
 use ::{InputGeneratorTract};
 use encode::ScalarEncodable;
@@ -19,11 +20,18 @@ pub struct ReversoScalarSequence<T> {
     next: T,
     incr: T,
     layer_addrs: Vec<LayerAddress>,
+    rng: SmallRng,
 }
 
 impl<T> ReversoScalarSequence<T> where T: Num + NumCast + PartialOrd + Debug + Clone + AddAssign + Copy {
     pub fn new(range: (T, T), incr: T, layer_addrs: &[LayerAddress])
                 -> ReversoScalarSequence<T> {
+        ReversoScalarSequence::with_rng(range, incr, layer_addrs, SmallRng::from_entropy())
+    }
+
+    /// Returns a new sequence which draws axon intensities from `rng`.
+    pub fn with_rng(range: (T, T), incr: T, layer_addrs: &[LayerAddress], rng: SmallRng)
+                -> ReversoScalarSequence<T> {
         let next = range.0;
         let layer_addrs = layer_addrs.into_iter().cloned().collect();
 
@@ -32,6 +40,7 @@ impl<T> ReversoScalarSequence<T> where T: Num + NumCast + PartialOrd + Debug + C
             incr: incr,
             next: next,
             layer_addrs: layer_addrs,
+            rng,
         }
     }
 
@@ -48,9 +57,10 @@ impl<T> InputGeneratorTract for ReversoScalarSequence<T>
             .expect(&format!("ReversoScalarSequence::write_into(): No layers with address: {:?}", addr));
 
         if l_idx == 0 {
-            super::encode_scalar(self.next, self.range, tract_frame);
+            super::encode_scalar_with_rng(self.next, self.range, tract_frame, &mut self.rng);
         } else if l_idx == 1 {
-            super::encode_scalar(self.range.1 - self.next, self.range, tract_frame);
+            super::encode_scalar_with_rng(self.range.1 - self.next, self.range, tract_frame,
+                &mut self.rng);
         } else {
             panic!("ReversoScalarSequence::write_into(): Too many layers!");
         }
@@ -1,5 +1,6 @@
 //! Mostly for testing purposes.
 
+use rand::{FromEntropy, rngs::SmallRng};
 use cmn::{TractDims, TractFrameMut};
 use map::LayerAddress;
 use ::{InputGeneratorTract};
@@ -15,9 +16,15 @@ pub struct ScalarSdrGradiant<T> {
 
 impl<T> ScalarSdrGradiant<T> where T: ScalarEncodable {
     pub fn new(range: (T, T), way_span: T, incr: T, tract_dims: &TractDims) -> ScalarSdrGradiant<T> {
+        ScalarSdrGradiant::with_rng(range, way_span, incr, tract_dims, SmallRng::from_entropy())
+    }
+
+    /// Returns a new gradiant whose writer is seeded from `rng`.
+    pub fn with_rng(range: (T, T), way_span: T, incr: T, tract_dims: &TractDims, rng: SmallRng)
+            -> ScalarSdrGradiant<T> {
         let next = range.0;
 
-        let writer = ScalarSdrWriter::new(range.clone(), way_span, tract_dims);
+        let writer = ScalarSdrWriter::with_rng(range.clone(), way_span, tract_dims, rng);
 
         ScalarSdrGradiant {
             range: range,
@@ -0,0 +1,334 @@
+//! Character or word sequences from text, each symbol encoded as a stable
+//! sparse distributed representation.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use cmn::{CmnError, CmnResult, TractDims, TractFrameMut, SplitMix64, hash_str};
+use map::LayerAddress;
+use ::InputGeneratorTract;
+
+
+const AXON_VALUE: u8 = 127;
+const SENTENCE_ENDS: &'static [&'static str] = &[".", "!", "?"];
+/// The symbol representing anything outside of the vocabulary.
+pub const UNKNOWN_SYMBOL: &'static str = "<unk>";
+
+
+/// Splits text into symbols: either every character or words with
+/// punctuation split off into symbols of their own.
+pub fn tokenize(text: &str, words: bool) -> Vec<String> {
+    if !words {
+        return text.chars().map(|c| c.to_string()).collect();
+    }
+
+    let mut tokens = Vec::new();
+    for word in text.split_whitespace() {
+        let mut current = String::new();
+        for c in word.chars() {
+            if c.is_alphanumeric() || c == '\'' || c == '-' {
+                current.push(c);
+            } else {
+                if !current.is_empty() { tokens.push(current.clone()); current.clear(); }
+                tokens.push(c.to_string());
+            }
+        }
+        if !current.is_empty() { tokens.push(current); }
+    }
+    tokens
+}
+
+
+/// An ordered set of symbols. Id zero is always `UNKNOWN_SYMBOL`.
+#[derive(Debug, Clone)]
+pub struct Vocabulary {
+    symbols: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Vocabulary {
+    /// Builds a vocabulary of the (at most `max_size`, if non-zero) most
+    /// frequent symbols, sorted.
+    pub fn build(tokens: &[String], max_size: usize) -> Vocabulary {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for token in tokens {
+            *counts.entry(token.as_str()).or_insert(0) += 1;
+        }
+
+        let mut by_count: Vec<(&str, usize)> = counts.into_iter().collect();
+        by_count.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        if max_size > 0 { by_count.truncate(max_size); }
+
+        let mut symbols: Vec<String> = by_count.into_iter().map(|(s, _)| s.to_owned()).collect();
+        symbols.sort();
+        Vocabulary::from_symbols(symbols)
+    }
+
+    /// Creates a vocabulary from an explicit list of symbols.
+    pub fn from_symbols<S: Into<String>>(symbols: Vec<S>) -> Vocabulary {
+        let mut vocab = Vocabulary { symbols: vec![UNKNOWN_SYMBOL.to_owned()], ids: HashMap::new() };
+        vocab.ids.insert(UNKNOWN_SYMBOL.to_owned(), 0);
+
+        for symbol in symbols.into_iter().map(|s| s.into()) {
+            if !vocab.ids.contains_key(&symbol) {
+                vocab.ids.insert(symbol.clone(), vocab.symbols.len());
+                vocab.symbols.push(symbol);
+            }
+        }
+        vocab
+    }
+
+    /// Returns the id of `symbol` or zero if it is unknown.
+    pub fn id(&self, symbol: &str) -> usize {
+        self.ids.get(symbol).cloned().unwrap_or(0)
+    }
+
+    #[inline] pub fn symbol(&self, id: usize) -> &str { &self.symbols[id] }
+    #[inline] pub fn symbols(&self) -> &[String] { &self.symbols }
+    #[inline] pub fn len(&self) -> usize { self.symbols.len() }
+}
+
+
+/// Settings for a `TextSequence`.
+#[derive(Debug, Clone)]
+pub struct TextSettings {
+    words: bool,
+    sparsity: f32,
+    overlap: f32,
+    max_vocab: usize,
+    cycles_per_symbol: usize,
+    sentence_reset_cycles: usize,
+    seed: u64,
+}
+
+impl Default for TextSettings {
+    fn default() -> TextSettings {
+        TextSettings {
+            words: false,
+            sparsity: 0.02,
+            overlap: 0.0,
+            max_vocab: 0,
+            cycles_per_symbol: 1,
+            sentence_reset_cycles: 0,
+            seed: 0,
+        }
+    }
+}
+
+impl TextSettings {
+    pub fn new() -> TextSettings {
+        TextSettings::default()
+    }
+
+    /// Encode words (and punctuation) rather than characters.
+    pub fn words(mut self, words: bool) -> TextSettings {
+        self.words = words;
+        self
+    }
+
+    /// The fraction of cells active for each symbol (0.0 - 0.5].
+    pub fn sparsity(mut self, sparsity: f32) -> TextSettings {
+        self.sparsity = sparsity;
+        self
+    }
+
+    /// The fraction of active cells each symbol shares with its predecessor
+    /// in vocabulary order [0.0 - 1.0).
+    pub fn overlap(mut self, overlap: f32) -> TextSettings {
+        self.overlap = overlap;
+        self
+    }
+
+    /// The maximum vocabulary size, zero for unlimited. Less frequent
+    /// symbols are encoded as `UNKNOWN_SYMBOL`.
+    pub fn max_vocab(mut self, max_vocab: usize) -> TextSettings {
+        self.max_vocab = max_vocab;
+        self
+    }
+
+    pub fn cycles_per_symbol(mut self, cycles_per_symbol: usize) -> TextSettings {
+        self.cycles_per_symbol = cycles_per_symbol;
+        self
+    }
+
+    /// The number of blank cycles inserted after each sentence-ending
+    /// symbol ('.', '!', or '?').
+    pub fn sentence_reset_cycles(mut self, sentence_reset_cycles: usize) -> TextSettings {
+        self.sentence_reset_cycles = sentence_reset_cycles;
+        self
+    }
+
+    /// The seed from which every symbol representation is derived.
+    pub fn seed(mut self, seed: u64) -> TextSettings {
+        self.seed = seed;
+        self
+    }
+}
+
+
+/// Generates the active cell indices of each vocabulary symbol.
+///
+/// Each symbol's cells are derived only from the seed, the symbol itself,
+/// and (when overlapping) its predecessor's cells, so a given vocabulary
+/// always produces identical representations.
+fn gen_sdrs(vocab: &Vocabulary, cell_count: usize, active_count: usize, overlap: f32,
+        seed: u64) -> Vec<Vec<u32>>
+{
+    let shared_count = ((overlap * active_count as f32).round() as usize).min(active_count);
+    let mut sdrs: Vec<Vec<u32>> = Vec::with_capacity(vocab.len());
+
+    for symbol in vocab.symbols() {
+        let mut rng = SplitMix64::new(seed ^ hash_str(symbol));
+        let mut cells: Vec<u32> = Vec::with_capacity(active_count);
+
+        if let Some(prev) = sdrs.last() {
+            let mut prev = prev.clone();
+            for i in 0..shared_count {
+                let j = i + rng.below(prev.len() - i);
+                prev.swap(i, j);
+                cells.push(prev[i]);
+            }
+        }
+
+        let mut taken: HashSet<u32> = cells.iter().cloned().collect();
+        while cells.len() < active_count {
+            let cell = rng.below(cell_count) as u32;
+            if taken.insert(cell) { cells.push(cell); }
+        }
+        cells.sort();
+        sdrs.push(cells);
+    }
+    sdrs
+}
+
+
+/// Streams the characters or words of a text as sparse representations.
+///
+/// Every symbol is held for `cycles_per_symbol` cycles. The sequence loops
+/// back to the beginning after the last symbol.
+#[derive(Debug)]
+pub struct TextSequence {
+    vocab: Vocabulary,
+    sequence: Vec<usize>,
+    sdrs: Vec<Vec<u32>>,
+    settings: TextSettings,
+    tract_dims: TractDims,
+    position: usize,
+    repeat_counter: usize,
+    reset_remaining: usize,
+}
+
+impl TextSequence {
+    /// Creates a new sequence from `text`, building a vocabulary from it.
+    pub fn new(text: &str, tract_dims: &TractDims, settings: TextSettings) -> CmnResult<TextSequence> {
+        let tokens = tokenize(text, settings.words);
+        let vocab = Vocabulary::build(&tokens, settings.max_vocab);
+        TextSequence::with_vocabulary(&tokens, vocab, tract_dims, settings)
+    }
+
+    /// Creates a new sequence from the contents of a text file.
+    pub fn open(file_name: &str, tract_dims: &TractDims, settings: TextSettings)
+            -> CmnResult<TextSequence>
+    {
+        let path = super::resolve_data_file(file_name)?;
+        let mut text = String::new();
+        File::open(&path)?.read_to_string(&mut text)?;
+        TextSequence::new(&text, tract_dims, settings)
+    }
+
+    /// Creates a new sequence of `tokens` using an existing vocabulary.
+    pub fn with_vocabulary(tokens: &[String], vocab: Vocabulary, tract_dims: &TractDims,
+            settings: TextSettings) -> CmnResult<TextSequence>
+    {
+        if tokens.is_empty() {
+            return Err("TextSequence::new: The text is empty.".into());
+        }
+        if !(settings.sparsity > 0.0 && settings.sparsity <= 0.5) {
+            return Err(format!("TextSequence::new: Sparsity must be within (0.0, 0.5] \
+                (found: {}).", settings.sparsity).into());
+        }
+        if !(settings.overlap >= 0.0 && settings.overlap < 1.0) {
+            return Err(format!("TextSequence::new: Overlap must be within [0.0, 1.0) \
+                (found: {}).", settings.overlap).into());
+        }
+        if settings.cycles_per_symbol == 0 {
+            return Err("TextSequence::new: 'cycles_per_symbol' must be greater than zero.".into());
+        }
+
+        let cell_count = tract_dims.to_len();
+        let active_count = ((settings.sparsity * cell_count as f32).round() as usize).max(1);
+        if active_count * 2 > cell_count {
+            return Err(CmnError::new(format!("TextSequence::new: The tract ({} cells) is too \
+                small.", cell_count)));
+        }
+
+        let sequence = tokens.iter().map(|t| vocab.id(t)).collect();
+        let sdrs = gen_sdrs(&vocab, cell_count, active_count, settings.overlap, settings.seed);
+
+        Ok(TextSequence {
+            vocab,
+            sequence,
+            sdrs,
+            settings,
+            tract_dims: tract_dims.clone(),
+            position: 0,
+            repeat_counter: 0,
+            reset_remaining: 0,
+        })
+    }
+
+    /// Returns the active cells representing `symbol`.
+    pub fn sdr(&self, symbol: &str) -> &[u32] {
+        &self.sdrs[self.vocab.id(symbol)]
+    }
+
+    /// Returns the current symbol, or `None` during a sentence reset.
+    pub fn current(&self) -> Option<&str> {
+        if self.reset_remaining > 0 { return None; }
+        Some(self.vocab.symbol(self.sequence[self.position]))
+    }
+
+    /// Advances to the next symbol, inserting blank cycles first if the
+    /// current symbol ends a sentence.
+    pub fn advance(&mut self) {
+        if self.reset_remaining > 0 {
+            self.reset_remaining -= 1;
+            return;
+        } else if SENTENCE_ENDS.contains(&self.vocab.symbol(self.sequence[self.position])) &&
+                self.settings.sentence_reset_cycles > 0 {
+            self.reset_remaining = self.settings.sentence_reset_cycles;
+        }
+
+        self.position = (self.position + 1) % self.sequence.len();
+    }
+
+    /// Returns true if a sentence reset is in progress.
+    #[inline] pub fn is_resetting(&self) -> bool { self.reset_remaining > 0 }
+    #[inline] pub fn vocabulary(&self) -> &Vocabulary { &self.vocab }
+    #[inline] pub fn position(&self) -> usize { self.position }
+    #[inline] pub fn len(&self) -> usize { self.sequence.len() }
+}
+
+impl InputGeneratorTract for TextSequence {
+    fn write_into(&mut self, tract_frame: &mut TractFrameMut, _: LayerAddress) {
+        assert!(tract_frame.dims() == &self.tract_dims, "TextSequence::write_into: Tract \
+            dimension mismatch.");
+        let frame = tract_frame.frame_mut();
+        for axn in frame.iter_mut() { *axn = 0; }
+
+        if self.reset_remaining == 0 {
+            for &cell in self.sdrs[self.sequence[self.position]].iter() {
+                frame[cell as usize] = AXON_VALUE;
+            }
+        }
+    }
+
+    fn cycle_next(&mut self) {
+        self.repeat_counter += 1;
+
+        if self.reset_remaining > 0 || self.repeat_counter >= self.settings.cycles_per_symbol {
+            self.repeat_counter = 0;
+            self.advance();
+        }
+    }
+}
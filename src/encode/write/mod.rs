@@ -24,6 +24,13 @@ pub use self::vector_2d_writer::Vector2dWriter;
 ///
 pub fn encode_scalar<T>(val: T, val_range: (T, T), tract: &mut TractFrameMut)
             where T: ScalarEncodable {
+    encode_scalar_with_rng(val, val_range, tract, &mut SmallRng::from_entropy())
+}
+
+/// Encode a scalar as with `encode_scalar`, drawing axon intensities from
+/// `rng`.
+pub fn encode_scalar_with_rng<T>(val: T, val_range: (T, T), tract: &mut TractFrameMut,
+            rng: &mut SmallRng) where T: ScalarEncodable {
     assert!(val >= val_range.0 && val <= val_range.1, "Unable to encode scalar value: '{}'. The \
         value is outside of the allowed range: {:?}.", val, val_range);
     let v_size = tract.dims().v_size() as i32;
@@ -110,7 +117,6 @@ pub fn encode_scalar<T>(val: T, val_range: (T, T), tract: &mut TractFrameMut)
     // Save some inverses just to avoid repeated calculation:
     let radius_neg = 0 - radius;
 
-    let mut rng = SmallRng::from_entropy();
     let r_range = Range::<u8>::new(64, 128);
 
     // Clear tract frame:
@@ -135,7 +141,7 @@ pub fn encode_scalar<T>(val: T, val_range: (T, T), tract: &mut TractFrameMut)
         for u in u_z..u_n {
             let idx = (((v + center.v) * u_size) + u + center.u) as usize;
             unsafe {
-                *tract.get_unchecked_mut(idx) = r_range.sample(&mut rng);
+                *tract.get_unchecked_mut(idx) = r_range.sample(rng);
                 // *tract.get_unchecked_mut(idx) = 255;
             }
         }
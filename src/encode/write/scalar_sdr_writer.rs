@@ -78,6 +78,13 @@ pub struct ScalarSdrWriter<T> {
 
 impl<T: ScalarEncodable> ScalarSdrWriter<T> {
     pub fn new(val_range: (T, T), way_span: T, tract_dims: &TractDims) -> ScalarSdrWriter<T> {
+        ScalarSdrWriter::with_rng(val_range, way_span, tract_dims, SmallRng::from_entropy())
+    }
+
+    /// Returns a new writer which generates its waypoint SDRs (and all
+    /// subsequent randomness) using `rng`.
+    pub fn with_rng(val_range: (T, T), way_span: T, tract_dims: &TractDims, mut rng: SmallRng)
+            -> ScalarSdrWriter<T> {
         let v_size = tract_dims.v_size() as u32;
         let u_size = tract_dims.u_size() as u32;
         assert!(v_size >= 8 && u_size >= 8, "ScalarSdrWriter::new(): Tract frame too small. Side \
@@ -97,8 +104,6 @@ impl<T: ScalarEncodable> ScalarSdrWriter<T> {
         let sdr_len = tract_dims.to_len();
         let sdr_active_count = sdr_len / SPARSITY;
 
-        let mut rng = SmallRng::from_entropy();
-
         // let mut waypoint_indices = Vec::with_capacity(way_count);
         // for _ in 0..way_count {
         //     let sdr = gen_axn_idxs(&mut rng, sdr_active_count, sdr_len);
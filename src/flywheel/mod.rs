@@ -0,0 +1,799 @@
+//! Flywheel
+//!
+//! An event loop which cycles a cortex and services any number of clients.
+//!
+//! Clients may join (see `Connector`) and leave at any time. Every error,
+//! whether caused by a request, a command, or by cycling itself, is reported
+//! as a `Response::Error` rather than causing a panic.
+//!
+//! Clients in other processes may connect using a `RemoteServer` (see the
+//! `remote` module).
+//!
+//
+// * TODO:
+// - Optional command line printing (and possibly a menu here instead of in vibi).
+//
+
+
+use std::collections::HashMap;
+use std::num::Wrapping;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration as StdDuration;
+use std::sync::mpsc::{self, Sender, SyncSender, Receiver, TryRecvError, TrySendError,
+    RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use time::{self, Timespec, Duration};
+use cmn::{CmnError, CmnResult};
+use ::{Cortex, OclEvent, SamplerKind, SamplerBufferKind, TractReceiver, AnomalyScore,
+    LearningParams};
+use ::map::{SliceTractMap, LayerAddress};
+
+pub mod wire;
+pub mod remote;
+
+pub use self::remote::{RemoteServer, RemoteSettings, RemoteClient, RemoteRequest,
+    RemoteResponse, serve_connection};
+
+
+/// The amount of time to block waiting for a command while idle before
+/// servicing clients again.
+const IDLE_POLL_INTERVAL: StdDuration = StdDuration::from_millis(5);
+
+
+#[derive(Clone, Debug)]
+pub enum PathwayConfig {
+    EncoderRanges(Vec<(f32, f32)>),
+}
+
+
+#[derive(Clone, Debug)]
+pub enum Obs {
+    Float64 { p: usize, len: usize }
+}
+
+
+// [NOTE]: Remove this or re-use it to be the one-time designator for an
+// arc-mutex. Really no need to have a separate sensory frame unless sensory
+// data is highly sporadic.
+//
+#[derive(Clone, Debug)]
+pub enum SensoryFrame {
+    F32Array16([f32; 16]),
+    // TODO: Convert this into a `usize` referring to a previously stored
+    // arc-mutex reference avoiding the need to create a new reference for
+    // each frame (OR REDESIGN - SEE ABOVE):
+    Tract(Arc<Mutex<Vec<u8>>>),
+    PathwayConfig(PathwayConfig),
+}
+
+
+/// A decoded motor action (see `Arm`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum MotorFrame {
+    /// A continuous two dimensional action vector with a magnitude in [0.0,
+    /// 1.0].
+    Vector2d { lyr_addr: LayerAddress, xy: [f64; 2] },
+    /// A discrete action index or `None` if the motor layer was silent.
+    Discrete { lyr_addr: LayerAddress, action: Option<usize> },
+}
+
+
+#[derive(Clone, Debug)]
+pub struct AreaInfo {
+    pub name: String,
+    pub aff_out_slc_ids: Vec<u8>,
+    pub tract_map: SliceTractMap,
+}
+
+
+/// A runtime change to the settings of a single cortical area.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AreaSetting {
+    Learning(bool),
+    Regrowth(bool),
+    /// Enables or disables anomaly scoring for the named temporal layer.
+    /// Scores are broadcast each cycle as `Response::Anomaly`.
+    AnomalyTracking(String, bool),
+    /// Replaces the learning and regrowth parameters of every layer (see
+    /// `CorticalArea::set_learning_params`).
+    LearningParams(LearningParams),
+    /// Sets the neuromodulation value of every column (see
+    /// `CorticalArea::set_neuromodulation`).
+    Neuromodulation(i8),
+}
+
+
+/// Imperative cycle control commands.
+#[derive(Clone, Debug)]
+pub enum Command {
+    None,
+    /// Begins a new run of `n` cycles (`0` cycles indefinitely).
+    Iterate(u32),
+    /// Cycles `n` more times then pauses, beginning a new (indefinite) run
+    /// if none is in progress.
+    Step(u32),
+    /// Pauses the current run.
+    Pause,
+    /// Resumes a paused run.
+    Resume,
+    /// Ends the current run.
+    Stop,
+    Exit,
+    ExitAfterCycling,
+    /// Enables or disables learning for all areas.
+    SetLearning(bool),
+    /// Saves the state of the cortex to the specified file.
+    Snapshot(PathBuf),
+    /// Changes a setting for the named area.
+    AreaSetting { area_name: String, setting: AreaSetting },
+}
+
+
+// Requests for and submissions of data.
+#[derive(Clone, Debug)]
+pub enum Request {
+    CurrentIter,
+    Status,
+    AreaInfo { area_name: String },
+    Sampler { area_name: String, kind: SamplerKind, buffer_kind: SamplerBufferKind, backpressure: bool },
+    FinishQueues,
+    /// A command sent by a client rather than through the command channel.
+    Command(Command),
+}
+
+
+/// Cycle result responses.
+#[derive(Debug)]
+pub enum Response {
+    CycleStarted(u32),
+    CurrentIter(u32),
+    Status(Box<Status>),
+    Ready,
+    Motor(MotorFrame),
+    Anomaly(AnomalyScore),
+    AreaInfo(Box<AreaInfo>),
+    SampleProgress(Option<OclEvent>),
+    QueuesFinished(u64),
+    Sampler(TractReceiver),
+    SnapshotSaved(PathBuf),
+    Error(String),
+    Exiting,
+}
+
+
+/// Cycle status.
+#[derive(Clone, Debug)]
+pub struct Status {
+    pub cycling: bool,
+    pub paused: bool,
+    pub learning: bool,
+    pub cur_cycle: Wrapping<u32>,
+    pub prev_cycles: Wrapping<u32>,
+    pub prev_elapsed: Duration,
+    pub cur_start_time: Option<Timespec>,
+    pub cycle_counter: Wrapping<u64>,
+}
+
+impl Status {
+    pub fn new() -> Status {
+        Status {
+            cycling: false,
+            paused: false,
+            learning: true,
+            cur_cycle: Wrapping(0),
+            prev_cycles: Wrapping(0),
+            prev_elapsed: Duration::seconds(0),
+            cur_start_time: Some(time::get_time()),
+            cycle_counter: Wrapping(0),
+        }
+    }
+
+    pub fn cur_cycle(&self) -> u32 {
+        self.cur_cycle.0
+    }
+
+    pub fn cur_elapsed(&self) -> Duration {
+        match self.cur_start_time {
+            Some(start) => time::get_time() - start,
+            None => Duration::zero(),
+        }
+    }
+
+    pub fn cur_cps(&self) -> f32 {
+        match self.cur_start_time {
+            Some(_) => Status::cps(self.cur_cycle.0, self.cur_elapsed()),
+            None => 0.0,
+        }
+    }
+
+
+    pub fn ttl_cycles(&self) -> u32 {
+        self.cur_cycle.0 + self.prev_cycles.0
+    }
+
+    pub fn ttl_elapsed(&self) -> Duration {
+        self.prev_elapsed + self.cur_elapsed()
+    }
+
+    pub fn ttl_cps(&self) -> f32 {
+        Status::cps(self.ttl_cycles(), self.ttl_elapsed())
+    }
+
+    fn cps(cycle: u32, elapsed: Duration) -> f32 {
+        if elapsed.num_milliseconds() > 0 {
+            (cycle as f32 / elapsed.num_milliseconds() as f32) * 1000.0
+        } else {
+            0.0
+        }
+    }
+}
+
+
+pub enum LoopAction {
+    None,
+    Break,
+    Continue,
+}
+
+
+type ClientPair = (Receiver<Request>, Sender<Response>);
+
+
+/// The request sender and response receiver used by a client.
+pub struct Client {
+    pub req_tx: Sender<Request>,
+    pub res_rx: Receiver<Response>,
+}
+
+
+/// Connects new clients to a `Flywheel` from any thread.
+#[derive(Clone)]
+pub struct Connector {
+    join_tx: Sender<ClientPair>,
+}
+
+impl Connector {
+    /// Connects a new client. Dropping either half of the returned client
+    /// disconnects it.
+    pub fn connect(&self) -> CmnResult<Client> {
+        let (req_tx, req_rx) = mpsc::channel();
+        let (res_tx, res_rx) = mpsc::channel();
+        self.join_tx.send((req_rx, res_tx))
+            .map_err(|_| CmnError::from("Connector::connect: The flywheel has exited."))?;
+        Ok(Client { req_tx, res_rx })
+    }
+}
+
+
+/// An event loop for the cortex.
+///
+/// Exits upon receiving `Command::Exit` or once the command sender has been
+/// dropped and every client has disconnected.
+///
+pub struct Flywheel {
+    command_rx: Option<Receiver<Command>>,
+    join_tx: Sender<ClientPair>,
+    join_rx: Receiver<ClientPair>,
+    clients: Vec<ClientPair>,
+    dead_client_idxs: Vec<usize>,
+    sensory_rxs: Vec<(Receiver<SensoryFrame>, usize)>,
+    motor_rxs: Vec<Receiver<MotorFrame>>,
+    motor_txs: Vec<SyncSender<MotorFrame>>,
+    anomaly_cycles_sent: HashMap<LayerAddress, u64>,
+    cortex: Cortex,
+    cycle_iters_max: u32,
+    steps_remaining: Option<u32>,
+    exit_after_cycling: bool,
+    status: Status,
+    exiting: bool,
+}
+
+impl Flywheel {
+    pub fn new(cortex: Cortex, command_rx: Receiver<Command>) -> Flywheel {
+        let (join_tx, join_rx) = mpsc::channel();
+
+        Flywheel {
+            command_rx: Some(command_rx),
+            join_tx,
+            join_rx,
+            clients: Vec::with_capacity(16),
+            dead_client_idxs: Vec::with_capacity(16),
+            sensory_rxs: Vec::with_capacity(8),
+            motor_rxs: Vec::with_capacity(8),
+            motor_txs: Vec::with_capacity(8),
+            anomaly_cycles_sent: HashMap::new(),
+            cortex: cortex,
+            cycle_iters_max: 1,
+            steps_remaining: None,
+            exit_after_cycling: false,
+            status: Status::new(),
+            exiting: false,
+        }
+    }
+
+    pub fn add_req_res_pair(&mut self, req_rx: Receiver<Request>, res_tx: Sender<Response>) {
+        self.clients.push((req_rx, res_tx));
+    }
+
+    /// Returns a connector which can be used to add clients while spinning.
+    pub fn connector(&self) -> Connector {
+        Connector { join_tx: self.join_tx.clone() }
+    }
+
+    /// Adds a source of sensory frames for the input generator pathway
+    /// (area) named `pathway_name`.
+    pub fn add_sensory_rx<S: AsRef<str>>(&mut self, sensory_rx: Receiver<SensoryFrame>,
+            pathway_name: S) -> CmnResult<()>
+    {
+        let pathway_idx = self.cortex.thal().input_generator_idx(pathway_name.as_ref())?;
+        self.sensory_rxs.push((sensory_rx, pathway_idx));
+        Ok(())
+    }
+
+    /// Adds a source of motor frames (typically the receiving end of the
+    /// channel passed to `Arm::new`).
+    pub fn add_motor_rx(&mut self, motor_rx: Receiver<MotorFrame>) {
+        self.motor_rxs.push(motor_rx);
+    }
+
+    /// Adds a destination for motor frames. Every frame received from any
+    /// motor source is sent to every destination (as well as to every
+    /// client).
+    pub fn add_motor_tx(&mut self, motor_tx: SyncSender<MotorFrame>) {
+        self.motor_txs.push(motor_tx);
+    }
+
+    pub fn cortex(&self) -> &Cortex {
+        &self.cortex
+    }
+
+    pub fn cortex_mut(&mut self) -> &mut Cortex {
+        &mut self.cortex
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    pub fn spin(&mut self) {
+        while !self.exiting {
+            self.accept_clients();
+            self.process_commands();
+            self.fulfill_requests();
+            if self.exiting { break; }
+
+            if self.status.cycling && !self.status.paused {
+                self.cycle();
+            } else {
+                self.wait_idle();
+            }
+
+            self.remove_dead_clients();
+        }
+
+        // Broadcast an `Exiting` to everyone.
+        self.broadcast(|| Response::Exiting);
+        self.remove_dead_clients();
+    }
+
+    /// Cycles the cortex once, ending or pausing the current run when
+    /// appropriate.
+    fn cycle(&mut self) {
+        if let Err(err) = self.intake_sensory_frames() {
+            self.broadcast_error(format!("Flywheel::cycle: Error intaking sensory frames: {}",
+                err));
+        }
+
+        if let Err(err) = self.cortex.cycle() {
+            self.broadcast_error(format!("Flywheel::cycle: Error cycling cortex: {}", err));
+            self.end_run();
+            return;
+        }
+
+        // Update cycle_counts:
+        self.status.cur_cycle += Wrapping(1);
+        self.status.cycle_counter += Wrapping(1);
+
+        self.output_motor_frames();
+        self.broadcast_anomaly_scores();
+
+        self.steps_remaining = self.steps_remaining.map(|steps| steps.saturating_sub(1));
+        let steps_complete = self.steps_remaining == Some(0);
+
+        if (self.cycle_iters_max != 0) && (self.status.cur_cycle.0 >= self.cycle_iters_max) {
+            self.end_run();
+        } else if steps_complete {
+            self.steps_remaining = None;
+            self.status.paused = true;
+            self.broadcast_status();
+        }
+    }
+
+    /// Begins a new run of `iters` cycles (`0` cycles indefinitely).
+    fn start_run(&mut self, iters: u32, steps: Option<u32>) {
+        self.cycle_iters_max = iters;
+        self.steps_remaining = steps;
+        self.status.cur_cycle = Wrapping(0);
+        self.status.cur_start_time = Some(time::get_time());
+        self.status.cycling = true;
+        self.status.paused = steps == Some(0);
+        self.broadcast_status();
+    }
+
+    /// Ends the current run, if any.
+    fn end_run(&mut self) {
+        if self.status.cycling {
+            self.status.cycling = false;
+            self.status.paused = false;
+            self.status.prev_cycles += self.status.cur_cycle;
+            self.status.prev_elapsed = self.status.prev_elapsed + self.status.cur_elapsed();
+            self.status.cur_cycle = Wrapping(0);
+            self.status.cur_start_time = None;
+            self.steps_remaining = None;
+            self.broadcast_status();
+        }
+
+        if self.exit_after_cycling { self.exiting = true; }
+    }
+
+    fn handle_command(&mut self, cmd: Command) {
+        match cmd {
+            Command::None => (),
+            Command::Iterate(i) => self.start_run(i, None),
+            Command::Step(n) => {
+                if self.status.cycling {
+                    self.steps_remaining = Some(n);
+                    self.status.paused = n == 0;
+                    self.broadcast_status();
+                } else {
+                    self.start_run(0, Some(n));
+                }
+            },
+            Command::Pause => {
+                if self.status.cycling {
+                    self.steps_remaining = None;
+                    self.status.paused = true;
+                    self.broadcast_status();
+                }
+            },
+            Command::Resume => {
+                if self.status.cycling {
+                    self.steps_remaining = None;
+                    self.status.paused = false;
+                    self.broadcast_status();
+                }
+            },
+            Command::Stop => self.end_run(),
+            Command::Exit => self.exiting = true,
+            Command::ExitAfterCycling => {
+                self.exit_after_cycling = true;
+                if !self.status.cycling { self.exiting = true; }
+            },
+            Command::SetLearning(enabled) => {
+                self.cortex.set_learning_enabled(enabled);
+                self.status.learning = enabled;
+                self.broadcast_status();
+            },
+            Command::Snapshot(path) => {
+                let saved = self.cortex.save_state(&path);
+                match saved {
+                    Ok(_) => self.broadcast(|| Response::SnapshotSaved(path.clone())),
+                    Err(err) => self.broadcast_error(format!("Flywheel::handle_command: \
+                        Error saving snapshot to '{}': {}", path.display(), err)),
+                }
+            },
+            Command::AreaSetting { area_name, setting } => {
+                let res = match self.cortex.areas_mut().by_key_mut(area_name.as_str()) {
+                    Some(area) => {
+                        match setting {
+                            AreaSetting::Learning(enabled) => {
+                                area.set_learning_enabled(enabled);
+                                Ok(())
+                            },
+                            AreaSetting::Regrowth(enabled) => {
+                                area.set_regrowth_enabled(enabled);
+                                Ok(())
+                            },
+                            AreaSetting::AnomalyTracking(layer_name, enabled) => {
+                                let lyr_addr = area.area_map().layer_map()
+                                    .layer_info_by_name(&layer_name)
+                                    .map(|li| li.layer_addr());
+                                match lyr_addr {
+                                    Some(addr) => {
+                                        // A re-enabled layer's scores start again from cycle 0:
+                                        if !enabled { self.anomaly_cycles_sent.remove(&addr); }
+                                        area.set_anomaly_tracking(addr, enabled)
+                                    },
+                                    None => Err(format!("No layer named '{}' in area '{}'.",
+                                        layer_name, area_name).into()),
+                                }
+                            },
+                            AreaSetting::LearningParams(params) => {
+                                area.set_learning_params(params)
+                            },
+                            AreaSetting::Neuromodulation(value) => {
+                                area.set_neuromodulation(value)
+                            },
+                        }
+                    },
+                    None => Err(format!("No area named '{}'.", area_name).into()),
+                };
+
+                if let Err(err) = res {
+                    self.broadcast_error(format!("Flywheel::handle_command: {}", err));
+                }
+            },
+        }
+    }
+
+    /// Handles all pending commands without blocking.
+    fn process_commands(&mut self) {
+        loop {
+            let cmd = match self.command_rx.as_ref().map(|rx| rx.try_recv()) {
+                Some(Ok(cmd)) => cmd,
+                Some(Err(TryRecvError::Empty)) | None => break,
+                Some(Err(TryRecvError::Disconnected)) => {
+                    self.command_rx = None;
+                    break;
+                },
+            };
+            self.handle_command(cmd);
+        }
+    }
+
+    /// Blocks briefly waiting for a command.
+    fn wait_idle(&mut self) {
+        let cmd = match self.command_rx.as_ref().map(|rx| rx.recv_timeout(IDLE_POLL_INTERVAL)) {
+            Some(Ok(cmd)) => Some(cmd),
+            Some(Err(RecvTimeoutError::Timeout)) => None,
+            Some(Err(RecvTimeoutError::Disconnected)) => {
+                self.command_rx = None;
+                None
+            },
+            None => {
+                thread::sleep(IDLE_POLL_INTERVAL);
+                None
+            },
+        };
+
+        if let Some(cmd) = cmd { self.handle_command(cmd); }
+
+        if self.command_rx.is_none() && self.clients.len() == self.dead_client_idxs.len() {
+            self.exiting = true;
+        }
+    }
+
+    /// Adds any clients which have connected using a `Connector`.
+    fn accept_clients(&mut self) {
+        while let Ok(pair) = self.join_rx.try_recv() {
+            self.clients.push(pair);
+        }
+    }
+
+    fn handle_request(&mut self, req: Request) -> CmnResult<Option<Response>> {
+        match req {
+            Request::Sampler { area_name, kind, buffer_kind, backpressure } => {
+                let area = self.cortex.areas_mut().by_key_mut(area_name.as_str())
+                    .ok_or(CmnError::from(format!("Flywheel::handle_request: \
+                        No area named '{}'.", area_name)))?;
+                Ok(Some(Response::Sampler(area.sampler(kind, buffer_kind, backpressure)?)))
+            },
+            Request::AreaInfo { area_name } => {
+                Ok(Some(Response::AreaInfo(Box::new(self.area_info(&area_name)?))))
+            },
+            Request::Status => {
+                Ok(Some(Response::Status(Box::new(self.status.clone()))))
+            },
+            Request::CurrentIter => {
+                Ok(Some(Response::CurrentIter(self.status.cur_cycle.0)))
+            },
+            Request::FinishQueues => {
+                // Will block:
+                self.cortex.finish_queues();
+                Ok(Some(Response::QueuesFinished(self.status.cycle_counter.0)))
+            },
+            Request::Command(cmd) => {
+                self.handle_command(cmd);
+                Ok(None)
+            },
+        }
+    }
+
+    /// Handles all pending requests from every client.
+    ///
+    /// Clients are never removed while iterating (handling a request may
+    /// broadcast), only marked as dead and swept afterwards.
+    fn fulfill_requests(&mut self) {
+        for client_idx in 0..self.clients.len() {
+            loop {
+                if self.dead_client_idxs.contains(&client_idx) { break; }
+
+                let req = match self.clients[client_idx].0.try_recv() {
+                    Ok(req) => req,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.mark_dead_client(client_idx);
+                        break;
+                    },
+                };
+
+                let res = match self.handle_request(req) {
+                    Ok(Some(res)) => res,
+                    Ok(None) => continue,
+                    Err(err) => Response::Error(err.to_string()),
+                };
+
+                if self.clients[client_idx].1.send(res).is_err() {
+                    self.mark_dead_client(client_idx);
+                    break;
+                }
+            }
+        }
+
+        self.remove_dead_clients();
+    }
+
+    /// Marks a client as disconnected, to be removed by
+    /// `::remove_dead_clients`.
+    fn mark_dead_client(&mut self, client_idx: usize) {
+        if !self.dead_client_idxs.contains(&client_idx) {
+            self.dead_client_idxs.push(client_idx);
+        }
+    }
+
+    /// Removes every client marked as disconnected.
+    fn remove_dead_clients(&mut self) {
+        self.dead_client_idxs.sort();
+        for client_idx in self.dead_client_idxs.drain(..).rev() {
+            self.clients.remove(client_idx);
+        }
+    }
+
+    // [NOTE]: Incoming array values beyond the number of encoded layers will
+    // be silently ignored. Disconnected sources are removed.
+    fn intake_sensory_frames(&mut self) -> CmnResult<()> {
+        let mut disconnected_rx_idxs = Vec::new();
+
+        for (rx_idx, &(ref sen_rx, pathway_idx)) in self.sensory_rxs.iter().enumerate() {
+            loop {
+                match sen_rx.try_recv() {
+                    Ok(s) => {
+                        let pathway = self.cortex.thal().input_generator(pathway_idx)?;
+
+                        match s {
+                            SensoryFrame::F32Array16(arr) => {
+                                let count = pathway.value_count().unwrap_or(arr.len());
+                                pathway.write_values(&arr[..count.min(arr.len())])?
+                            },
+                            SensoryFrame::PathwayConfig(pc) => match pc {
+                                PathwayConfig::EncoderRanges(ranges) => {
+                                    pathway.set_encoder_ranges(ranges)?
+                                },
+                            },
+                            SensoryFrame::Tract(frame) => {
+                                let frame = frame.lock().map_err(|_| CmnError::from(
+                                    "Flywheel::intake_sensory_frames: Poisoned tract frame."))?;
+                                pathway.write_tract(&frame)?
+                            },
+                        }
+                    }
+                    Err(e) => match e {
+                        TryRecvError::Empty => break,
+                        TryRecvError::Disconnected => {
+                            disconnected_rx_idxs.push(rx_idx);
+                            break;
+                        },
+                    },
+                }
+            }
+        }
+
+        for rx_idx in disconnected_rx_idxs.into_iter().rev() {
+            self.sensory_rxs.remove(rx_idx);
+        }
+
+        Ok(())
+    }
+
+    // Every frame is sent to each destination and broadcast to every client
+    // as a `Response::Motor`.
+    //
+    // [NOTE]: Frames are dropped for any destination whose buffer is full.
+    // Disconnected sources and destinations are removed.
+    fn output_motor_frames(&mut self) {
+        let mut disconnected_rx_idxs = Vec::new();
+        let mut disconnected_tx_idxs = Vec::new();
+        let mut frames = Vec::new();
+
+        for (rx_idx, mot_rx) in self.motor_rxs.iter().enumerate() {
+            loop {
+                match mot_rx.try_recv() {
+                    Ok(frame) => {
+                        frames.push(frame.clone());
+                        for (tx_idx, mot_tx) in self.motor_txs.iter().enumerate() {
+                            match mot_tx.try_send(frame.clone()) {
+                                Ok(_) | Err(TrySendError::Full(_)) => (),
+                                Err(TrySendError::Disconnected(_)) => {
+                                    if !disconnected_tx_idxs.contains(&tx_idx) {
+                                        disconnected_tx_idxs.push(tx_idx);
+                                    }
+                                },
+                            }
+                        }
+                    },
+                    Err(e) => match e {
+                        TryRecvError::Empty => break,
+                        TryRecvError::Disconnected => {
+                            disconnected_rx_idxs.push(rx_idx);
+                            break;
+                        },
+                    },
+                }
+            }
+        }
+
+        for rx_idx in disconnected_rx_idxs.into_iter().rev() {
+            self.motor_rxs.remove(rx_idx);
+        }
+
+        disconnected_tx_idxs.sort();
+        for tx_idx in disconnected_tx_idxs.into_iter().rev() {
+            self.motor_txs.remove(tx_idx);
+        }
+
+        for frame in frames {
+            self.broadcast(|| Response::Motor(frame.clone()));
+        }
+    }
+
+    /// Broadcasts the latest anomaly score of every layer with anomaly
+    /// tracking enabled, unless that score has already been broadcast.
+    ///
+    /// Scores are computed asynchronously so a layer's latest score may not
+    /// change between calls.
+    fn broadcast_anomaly_scores(&mut self) {
+        let scores: Vec<AnomalyScore> = self.cortex.areas().iter()
+            .flat_map(|area| area.anomaly_scores())
+            .collect();
+        for score in scores {
+            if self.anomaly_cycles_sent.get(&score.lyr_addr()) == Some(&score.cycle()) {
+                continue;
+            }
+            self.anomaly_cycles_sent.insert(score.lyr_addr(), score.cycle());
+            self.broadcast(|| Response::Anomaly(score));
+        }
+    }
+
+    /// Sends a response to every client, marking any which have
+    /// disconnected (see `::remove_dead_clients`).
+    fn broadcast<F>(&mut self, res: F) where F: Fn() -> Response {
+        for (client_idx, &(_, ref res_tx)) in self.clients.iter().enumerate() {
+            if res_tx.send(res()).is_err() && !self.dead_client_idxs.contains(&client_idx) {
+                self.dead_client_idxs.push(client_idx);
+            }
+        }
+    }
+
+    fn broadcast_status(&mut self) {
+        // TODO: Remove unnecessary (redundant) heap allocation:
+        let status = self.status.clone();
+        self.broadcast(|| Response::Status(Box::new(status.clone())));
+    }
+
+    fn broadcast_error(&mut self, err: String) {
+        self.broadcast(|| Response::Error(err.clone()));
+    }
+
+    fn area_info(&self, area_name: &str) -> CmnResult<AreaInfo> {
+        let area = self.cortex.areas().by_key(area_name)
+            .ok_or(CmnError::from(format!("Flywheel::area_info: No area named '{}'.",
+                area_name)))?;
+
+        Ok(AreaInfo {
+            name: area_name.to_owned(),
+            aff_out_slc_ids: area.area_map().aff_out_slc_ids(),
+            tract_map: area.axon_tract_map(),
+        })
+    }
+}
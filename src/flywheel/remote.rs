@@ -0,0 +1,495 @@
+//! Remote flywheel control and sampling over TCP or Unix domain sockets.
+//!
+//! Each connection is a flywheel client. `RemoteRequest`s are sent to the
+//! server and `RemoteResponse`s (including broadcasts such as status changes)
+//! are sent back, each as a single frame (see the `wire` module).
+//!
+//! Samplers cannot cross a process boundary. Instead, a
+//! `Request::Sampler` is answered with a `RemoteResponse::Sampler` containing
+//! an id which can be used to read frames with `RemoteRequest::ReadSampler`.
+//!
+//! [NOTE]: Connections are not authenticated. Any peer able to reach the
+//! listening socket may control the flywheel. For this reason
+//! `RemoteServer::bind_tcp` listens only on loopback addresses and commands
+//! which write to a client specified path (`Command::Snapshot`) are refused
+//! unless explicitly allowed (see `RemoteSettings`). Unix domain sockets are
+//! restricted only by the permissions of the socket file.
+//!
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs, SocketAddr};
+#[cfg(unix)] use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)] use std::path::Path;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use futures::executor;
+use cmn::{CmnError, CmnResult};
+use subcortex::{TractReceiver, FutureReadGuardVec, ReadGuardVec};
+use cortex::AnomalyScore;
+use super::{Connector, Request, Response, Command, Status, MotorFrame, AreaInfo};
+use super::wire::{self, Wire, WireWriter, WireReader};
+
+
+/// A request sent from a remote client.
+#[derive(Clone, Debug)]
+pub enum RemoteRequest {
+    /// A request (or command) forwarded to the flywheel.
+    Flywheel(Request),
+    /// Reads the latest frame from a sampler. If `wait_for_frame` is set,
+    /// blocks until the next frame is available (the cortex must be
+    /// cycling).
+    ReadSampler { sampler_id: u32, wait_for_frame: bool },
+    /// Drops a sampler.
+    CloseSampler(u32),
+}
+
+
+/// A response sent to a remote client.
+#[derive(Clone, Debug)]
+pub enum RemoteResponse {
+    CycleStarted(u32),
+    CurrentIter(u32),
+    Status(Box<Status>),
+    Ready,
+    Motor(MotorFrame),
+    Anomaly(AnomalyScore),
+    AreaInfo(Box<AreaInfo>),
+    SampleProgress,
+    QueuesFinished(u64),
+    /// A sampler has been created and may be read using `sampler_id`.
+    Sampler { sampler_id: u32 },
+    /// A sampler frame or `None` if no fresh frame was available. Signed
+    /// (`i8`) samples are sent as their two's complement bytes.
+    SamplerFrame { sampler_id: u32, frame: Option<Vec<u8>> },
+    SnapshotSaved(PathBuf),
+    Error(String),
+    Exiting,
+}
+
+
+impl Wire for RemoteRequest {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            RemoteRequest::Flywheel(ref req) => { w.put_u8(0); req.encode(w); },
+            RemoteRequest::ReadSampler { sampler_id, wait_for_frame } => {
+                w.put_u8(1);
+                w.put_u32(sampler_id);
+                w.put_bool(wait_for_frame);
+            },
+            RemoteRequest::CloseSampler(sampler_id) => { w.put_u8(2); w.put_u32(sampler_id); },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<RemoteRequest> {
+        match r.get_u8()? {
+            0 => Ok(RemoteRequest::Flywheel(Wire::decode(r)?)),
+            1 => {
+                let sampler_id = r.get_u32()?;
+                let wait_for_frame = r.get_bool()?;
+                Ok(RemoteRequest::ReadSampler { sampler_id, wait_for_frame })
+            },
+            2 => Ok(RemoteRequest::CloseSampler(r.get_u32()?)),
+            tag => CmnError::err(format!("wire: Invalid 'RemoteRequest' tag: {}.", tag)),
+        }
+    }
+}
+
+
+impl Wire for RemoteResponse {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            RemoteResponse::CycleStarted(n) => { w.put_u8(0); w.put_u32(n); },
+            RemoteResponse::CurrentIter(n) => { w.put_u8(1); w.put_u32(n); },
+            RemoteResponse::Status(ref status) => { w.put_u8(2); status.encode(w); },
+            RemoteResponse::Ready => w.put_u8(3),
+            RemoteResponse::Motor(ref frame) => { w.put_u8(4); frame.encode(w); },
+            RemoteResponse::AreaInfo(ref info) => { w.put_u8(5); info.encode(w); },
+            RemoteResponse::SampleProgress => w.put_u8(6),
+            RemoteResponse::QueuesFinished(n) => { w.put_u8(7); w.put_u64(n); },
+            RemoteResponse::Sampler { sampler_id } => { w.put_u8(8); w.put_u32(sampler_id); },
+            RemoteResponse::SamplerFrame { sampler_id, ref frame } => {
+                w.put_u8(9);
+                w.put_u32(sampler_id);
+                match *frame {
+                    Some(ref bytes) => { w.put_u8(1); w.put_bytes(bytes); },
+                    None => w.put_u8(0),
+                }
+            },
+            RemoteResponse::SnapshotSaved(ref path) => { w.put_u8(10); path.encode(w); },
+            RemoteResponse::Error(ref err) => { w.put_u8(11); w.put_str(err); },
+            RemoteResponse::Exiting => w.put_u8(12),
+            RemoteResponse::Anomaly(ref score) => { w.put_u8(13); score.encode(w); },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<RemoteResponse> {
+        match r.get_u8()? {
+            0 => Ok(RemoteResponse::CycleStarted(r.get_u32()?)),
+            1 => Ok(RemoteResponse::CurrentIter(r.get_u32()?)),
+            2 => Ok(RemoteResponse::Status(Box::new(Wire::decode(r)?))),
+            3 => Ok(RemoteResponse::Ready),
+            4 => Ok(RemoteResponse::Motor(Wire::decode(r)?)),
+            5 => Ok(RemoteResponse::AreaInfo(Box::new(Wire::decode(r)?))),
+            6 => Ok(RemoteResponse::SampleProgress),
+            7 => Ok(RemoteResponse::QueuesFinished(r.get_u64()?)),
+            8 => Ok(RemoteResponse::Sampler { sampler_id: r.get_u32()? }),
+            9 => {
+                let sampler_id = r.get_u32()?;
+                let frame = match r.get_u8()? {
+                    0 => None,
+                    1 => Some(r.get_bytes()?),
+                    tag => return CmnError::err(format!("wire: Invalid sampler frame tag: {}.",
+                        tag)),
+                };
+                Ok(RemoteResponse::SamplerFrame { sampler_id, frame })
+            },
+            10 => Ok(RemoteResponse::SnapshotSaved(Wire::decode(r)?)),
+            11 => Ok(RemoteResponse::Error(r.get_string()?)),
+            12 => Ok(RemoteResponse::Exiting),
+            13 => Ok(RemoteResponse::Anomaly(Wire::decode(r)?)),
+            tag => CmnError::err(format!("wire: Invalid 'RemoteResponse' tag: {}.", tag)),
+        }
+    }
+}
+
+
+/// Access granted to remote clients.
+#[derive(Clone, Debug, Default)]
+pub struct RemoteSettings {
+    allow_file_commands: bool,
+    allow_non_loopback: bool,
+}
+
+impl RemoteSettings {
+    /// Returns new settings which refuse file path commands and permit
+    /// loopback TCP addresses only.
+    pub fn new() -> RemoteSettings {
+        RemoteSettings::default()
+    }
+
+    /// Allows remote clients to send commands which write to a path of their
+    /// choosing (`Command::Snapshot`).
+    pub fn allow_file_commands(mut self) -> RemoteSettings {
+        self.allow_file_commands = true;
+        self
+    }
+
+    /// Allows TCP servers to listen on addresses other than loopback
+    /// addresses.
+    pub fn allow_non_loopback(mut self) -> RemoteSettings {
+        self.allow_non_loopback = true;
+        self
+    }
+}
+
+
+/// Returns true if `req` causes the flywheel to access a client specified
+/// path.
+fn is_file_request(req: &Request) -> bool {
+    match *req {
+        Request::Command(Command::Snapshot(_)) => true,
+        _ => false,
+    }
+}
+
+
+/// Samplers belonging to a single connection.
+#[derive(Default)]
+struct Samplers {
+    next_id: u32,
+    rxs: HashMap<u32, Arc<TractReceiver>>,
+}
+
+impl Samplers {
+    fn insert(&mut self, rx: TractReceiver) -> u32 {
+        let sampler_id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.rxs.insert(sampler_id, Arc::new(rx));
+        sampler_id
+    }
+}
+
+
+/// Reads and copies a sampler frame.
+fn read_sampler(rx: &TractReceiver, wait_for_frame: bool) -> CmnResult<Option<Vec<u8>>> {
+    let read_buffer = match rx.recv(wait_for_frame).wait()? {
+        Some(rb) => rb,
+        None => return Ok(None),
+    };
+
+    let frame = match executor::block_on(FutureReadGuardVec::from(read_buffer))? {
+        ReadGuardVec::U8(ref rg) => rg.to_vec(),
+        ReadGuardVec::I8(ref rg) => rg.iter().map(|&v| v as u8).collect(),
+    };
+    Ok(Some(frame))
+}
+
+
+/// Converts a flywheel response, registering samplers as necessary.
+fn remote_response(res: Response, samplers: &Mutex<Samplers>) -> RemoteResponse {
+    match res {
+        Response::CycleStarted(n) => RemoteResponse::CycleStarted(n),
+        Response::CurrentIter(n) => RemoteResponse::CurrentIter(n),
+        Response::Status(status) => RemoteResponse::Status(status),
+        Response::Ready => RemoteResponse::Ready,
+        Response::Motor(frame) => RemoteResponse::Motor(frame),
+        Response::Anomaly(score) => RemoteResponse::Anomaly(score),
+        Response::AreaInfo(info) => RemoteResponse::AreaInfo(info),
+        Response::SampleProgress(_) => RemoteResponse::SampleProgress,
+        Response::QueuesFinished(n) => RemoteResponse::QueuesFinished(n),
+        Response::Sampler(rx) => match samplers.lock() {
+            Ok(mut samplers) => RemoteResponse::Sampler { sampler_id: samplers.insert(rx) },
+            Err(_) => RemoteResponse::Error("remote: Sampler registry poisoned.".to_owned()),
+        },
+        Response::SnapshotSaved(path) => RemoteResponse::SnapshotSaved(path),
+        Response::Error(err) => RemoteResponse::Error(err),
+        Response::Exiting => RemoteResponse::Exiting,
+    }
+}
+
+
+fn send<W: Write>(writer: &Mutex<W>, res: &RemoteResponse) -> CmnResult<()> {
+    let mut writer = writer.lock()
+        .map_err(|_| CmnError::from("remote: Connection writer poisoned."))?;
+    wire::write_frame(&mut *writer, res)
+}
+
+
+/// Serves a single remote client connection until either the connection is
+/// closed or the flywheel exits.
+///
+/// `reader` and `writer` are typically two handles to the same stream.
+/// Requests not permitted by `settings` are answered with an error.
+pub fn serve_connection<R, W>(mut reader: R, writer: W, connector: Connector,
+        settings: RemoteSettings) -> CmnResult<()>
+        where R: Read, W: Write + Send + 'static
+{
+    let client = connector.connect()?;
+    let writer = Arc::new(Mutex::new(writer));
+    let samplers = Arc::new(Mutex::new(Samplers::default()));
+
+    // Forward responses and broadcasts:
+    let res_thread = {
+        let writer = writer.clone();
+        let samplers = samplers.clone();
+        let res_rx = client.res_rx;
+
+        thread::Builder::new().name("bismit_remote_responder".to_owned()).spawn(move || {
+            for res in res_rx.iter() {
+                let exiting = if let Response::Exiting = res { true } else { false };
+                if send(&writer, &remote_response(res, &samplers)).is_err() || exiting {
+                    break;
+                }
+            }
+        })?
+    };
+
+    let req_tx = client.req_tx;
+    let result = loop {
+        let req = match wire::read_frame::<_, RemoteRequest>(&mut reader) {
+            Ok(Some(req)) => req,
+            Ok(None) => break Ok(()),
+            Err(err) => break Err(err),
+        };
+
+        let res = match req {
+            RemoteRequest::Flywheel(ref req) if is_file_request(req) &&
+                    !settings.allow_file_commands => {
+                RemoteResponse::Error(format!("remote: Commands which access a file path are \
+                    not permitted for remote clients (request: {:?}).", req))
+            },
+            RemoteRequest::Flywheel(req) => {
+                // The flywheel has exited:
+                if req_tx.send(req).is_err() { break Ok(()); }
+                continue;
+            },
+            RemoteRequest::ReadSampler { sampler_id, wait_for_frame } => {
+                // Avoid holding the registry lock while waiting:
+                let rx = samplers.lock().ok()
+                    .and_then(|samplers| samplers.rxs.get(&sampler_id).cloned());
+                match rx {
+                    Some(rx) => match read_sampler(&rx, wait_for_frame) {
+                        Ok(frame) => RemoteResponse::SamplerFrame { sampler_id, frame },
+                        Err(err) => RemoteResponse::Error(format!("remote: Error reading \
+                            sampler {}: {}", sampler_id, err)),
+                    },
+                    None => RemoteResponse::Error(format!("remote: No sampler with id: {}.",
+                        sampler_id)),
+                }
+            },
+            RemoteRequest::CloseSampler(sampler_id) => {
+                if let Ok(mut samplers) = samplers.lock() { samplers.rxs.remove(&sampler_id); }
+                continue;
+            },
+        };
+
+        if let Err(err) = send(&writer, &res) { break Err(err); }
+    };
+
+    // Disconnects this client from the flywheel, ending the responder:
+    drop(req_tx);
+    res_thread.join().map_err(|_| CmnError::from("remote: Responder thread panicked."))?;
+    result
+}
+
+
+/// Listens for remote clients, serving each on its own thread.
+///
+/// The listening thread runs for the life of the process.
+pub struct RemoteServer {
+    local_addr: Option<SocketAddr>,
+    _thread: JoinHandle<()>,
+}
+
+impl RemoteServer {
+    /// Listens for TCP connections on `addr`, which must be a loopback
+    /// address, using the default settings (see `RemoteSettings::new`).
+    pub fn bind_tcp<A: ToSocketAddrs>(addr: A, connector: Connector) -> CmnResult<RemoteServer> {
+        RemoteServer::bind_tcp_with_settings(addr, connector, RemoteSettings::new())
+    }
+
+    /// Listens for TCP connections on `addr`.
+    ///
+    /// Unless allowed by `settings`, every address `addr` resolves to must be
+    /// a loopback address.
+    pub fn bind_tcp_with_settings<A: ToSocketAddrs>(addr: A, connector: Connector,
+            settings: RemoteSettings) -> CmnResult<RemoteServer> {
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+        if !settings.allow_non_loopback {
+            if let Some(addr) = addrs.iter().find(|addr| !addr.ip().is_loopback()) {
+                return CmnError::err(format!("RemoteServer::bind_tcp: Refusing to listen on \
+                    non-loopback address: {} (see `RemoteSettings::allow_non_loopback`).",
+                    addr));
+            }
+        }
+        let listener = TcpListener::bind(&addrs[..])?;
+        let local_addr = listener.local_addr()?;
+
+        let thread = thread::Builder::new().name("bismit_remote_listener".to_owned())
+                .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                stream.set_nodelay(true).ok();
+                let connector = connector.clone();
+                let settings = settings.clone();
+
+                thread::spawn(move || {
+                    if let Ok(reader) = stream.try_clone() {
+                        serve_connection(reader, stream, connector, settings).ok();
+                    }
+                });
+            }
+        })?;
+
+        Ok(RemoteServer { local_addr: Some(local_addr), _thread: thread })
+    }
+
+    /// Listens for Unix domain socket connections at `path` using the
+    /// default settings (see `RemoteSettings::new`).
+    #[cfg(unix)]
+    pub fn bind_unix<P: AsRef<Path>>(path: P, connector: Connector) -> CmnResult<RemoteServer> {
+        RemoteServer::bind_unix_with_settings(path, connector, RemoteSettings::new())
+    }
+
+    /// Listens for Unix domain socket connections at `path`.
+    #[cfg(unix)]
+    pub fn bind_unix_with_settings<P: AsRef<Path>>(path: P, connector: Connector,
+            settings: RemoteSettings) -> CmnResult<RemoteServer> {
+        let listener = UnixListener::bind(path)?;
+
+        let thread = thread::Builder::new().name("bismit_remote_listener".to_owned())
+                .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                let connector = connector.clone();
+                let settings = settings.clone();
+
+                thread::spawn(move || {
+                    if let Ok(reader) = stream.try_clone() {
+                        serve_connection(reader, stream, connector, settings).ok();
+                    }
+                });
+            }
+        })?;
+
+        Ok(RemoteServer { local_addr: None, _thread: thread })
+    }
+
+    /// Returns the bound TCP address (useful when binding to port `0`).
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+}
+
+
+/// A blocking remote flywheel client.
+pub struct RemoteClient<S: Read + Write> {
+    stream: S,
+}
+
+impl RemoteClient<TcpStream> {
+    pub fn connect_tcp<A: ToSocketAddrs>(addr: A) -> CmnResult<RemoteClient<TcpStream>> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(RemoteClient::new(stream))
+    }
+}
+
+#[cfg(unix)]
+impl RemoteClient<UnixStream> {
+    pub fn connect_unix<P: AsRef<Path>>(path: P) -> CmnResult<RemoteClient<UnixStream>> {
+        Ok(RemoteClient::new(UnixStream::connect(path)?))
+    }
+}
+
+impl<S: Read + Write> RemoteClient<S> {
+    pub fn new(stream: S) -> RemoteClient<S> {
+        RemoteClient { stream }
+    }
+
+    pub fn send(&mut self, req: &RemoteRequest) -> CmnResult<()> {
+        wire::write_frame(&mut self.stream, req)
+    }
+
+    #[inline]
+    pub fn request(&mut self, req: Request) -> CmnResult<()> {
+        self.send(&RemoteRequest::Flywheel(req))
+    }
+
+    #[inline]
+    pub fn command(&mut self, cmd: Command) -> CmnResult<()> {
+        self.request(Request::Command(cmd))
+    }
+
+    /// Blocks until the next response arrives.
+    pub fn recv(&mut self) -> CmnResult<RemoteResponse> {
+        wire::read_frame(&mut self.stream)?
+            .ok_or(CmnError::from("RemoteClient::recv: Connection closed."))
+    }
+
+    /// Receives responses until one matches `pred`, discarding the rest
+    /// (typically status broadcasts). Returns an error if the server reports
+    /// one.
+    pub fn recv_until<F>(&mut self, mut pred: F) -> CmnResult<RemoteResponse>
+            where F: FnMut(&RemoteResponse) -> bool
+    {
+        loop {
+            match self.recv()? {
+                RemoteResponse::Error(err) => return CmnError::err(err),
+                res => if pred(&res) { return Ok(res); },
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
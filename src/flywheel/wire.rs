@@ -0,0 +1,588 @@
+//! A compact, length-prefixed binary encoding for flywheel messages.
+//!
+//! Every frame consists of a little-endian `u32` payload length followed by
+//! the payload. Integers are little-endian, strings and byte arrays are
+//! length-prefixed (`u32`), and enums are prefixed by a `u8` tag.
+//!
+
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::num::Wrapping;
+use time::{Timespec, Duration};
+use cmn::{CmnError, CmnResult};
+use map::{LayerAddress, SliceTractMap};
+use ::{SamplerKind, SamplerBufferKind, AnomalyScore, LearningParams};
+use super::{AreaSetting, Command, Request, MotorFrame, Status, AreaInfo};
+
+
+/// The largest payload which will be accepted when reading a frame.
+pub const MAX_FRAME_LEN: usize = 1 << 28;
+
+
+fn invalid_tag(type_name: &str, tag: u8) -> CmnError {
+    CmnError::new(format!("wire: Invalid '{}' tag: {}.", type_name, tag))
+}
+
+
+/// Accumulates an encoded payload.
+#[derive(Debug, Default)]
+pub struct WireWriter {
+    buf: Vec<u8>,
+}
+
+impl WireWriter {
+    pub fn new() -> WireWriter {
+        WireWriter { buf: Vec::with_capacity(64) }
+    }
+
+    #[inline]
+    pub fn put_u8(&mut self, val: u8) {
+        self.buf.push(val);
+    }
+
+    #[inline]
+    pub fn put_bool(&mut self, val: bool) {
+        self.buf.push(val as u8);
+    }
+
+    pub fn put_u32(&mut self, val: u32) {
+        for i in 0..4 { self.buf.push((val >> (i * 8)) as u8); }
+    }
+
+    pub fn put_u64(&mut self, val: u64) {
+        for i in 0..8 { self.buf.push((val >> (i * 8)) as u8); }
+    }
+
+    #[inline]
+    pub fn put_i64(&mut self, val: i64) {
+        self.put_u64(val as u64);
+    }
+
+    pub fn put_f64(&mut self, val: f64) {
+        self.put_u64(val.to_bits());
+    }
+
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        self.put_u32(bytes.len() as u32);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    #[inline]
+    pub fn put_str(&mut self, s: &str) {
+        self.put_bytes(s.as_bytes());
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+
+/// Reads values from an encoded payload.
+#[derive(Debug)]
+pub struct WireReader<'b> {
+    buf: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> WireReader<'b> {
+    pub fn new(buf: &'b [u8]) -> WireReader<'b> {
+        WireReader { buf, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> CmnResult<&'b [u8]> {
+        if self.buf.len() - self.pos < len {
+            return CmnError::err(format!("wire: Unexpected end of payload (needed: {}, \
+                remaining: {}).", len, self.buf.len() - self.pos));
+        }
+        let bytes = &self.buf[self.pos..(self.pos + len)];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub fn get_u8(&mut self) -> CmnResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn get_bool(&mut self) -> CmnResult<bool> {
+        match self.get_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => CmnError::err(format!("wire: Invalid bool: {}.", b)),
+        }
+    }
+
+    pub fn get_u32(&mut self) -> CmnResult<u32> {
+        Ok(self.take(4)?.iter().enumerate()
+            .fold(0u32, |val, (i, &b)| val | ((b as u32) << (i * 8))))
+    }
+
+    pub fn get_u64(&mut self) -> CmnResult<u64> {
+        Ok(self.take(8)?.iter().enumerate()
+            .fold(0u64, |val, (i, &b)| val | ((b as u64) << (i * 8))))
+    }
+
+    #[inline]
+    pub fn get_i64(&mut self) -> CmnResult<i64> {
+        self.get_u64().map(|val| val as i64)
+    }
+
+    pub fn get_f64(&mut self) -> CmnResult<f64> {
+        self.get_u64().map(f64::from_bits)
+    }
+
+    pub fn get_bytes(&mut self) -> CmnResult<Vec<u8>> {
+        let len = self.get_u32()? as usize;
+        self.take(len).map(|bytes| bytes.to_vec())
+    }
+
+    pub fn get_string(&mut self) -> CmnResult<String> {
+        String::from_utf8(self.get_bytes()?)
+            .map_err(|err| CmnError::new(format!("wire: Invalid string: {}", err)))
+    }
+
+    /// Returns an error if any bytes remain unread.
+    pub fn finish(&self) -> CmnResult<()> {
+        if self.pos == self.buf.len() {
+            Ok(())
+        } else {
+            CmnError::err(format!("wire: {} trailing bytes in payload.", self.buf.len() - self.pos))
+        }
+    }
+}
+
+
+/// A type which can be sent over the wire.
+pub trait Wire: Sized {
+    fn encode(&self, w: &mut WireWriter);
+    fn decode(r: &mut WireReader) -> CmnResult<Self>;
+}
+
+
+/// Writes a single length-prefixed frame containing `msg`.
+pub fn write_frame<W: Write, T: Wire>(writer: &mut W, msg: &T) -> CmnResult<()> {
+    let mut w = WireWriter::new();
+    // Reserve space for the length prefix:
+    w.put_u32(0);
+    msg.encode(&mut w);
+    let mut frame = w.into_bytes();
+
+    let payload_len = frame.len() - 4;
+    if payload_len > MAX_FRAME_LEN {
+        return CmnError::err(format!("wire: Frame length ({}) exceeds the maximum ({}).",
+            payload_len, MAX_FRAME_LEN));
+    }
+    for i in 0..4 { frame[i] = (payload_len >> (i * 8)) as u8; }
+
+    writer.write_all(&frame)?;
+    writer.flush()?;
+    Ok(())
+}
+
+
+/// Reads a single length-prefixed frame.
+///
+/// Returns `Ok(None)` if the stream was closed cleanly before the start of a
+/// frame.
+pub fn read_frame<R: Read, T: Wire>(reader: &mut R) -> CmnResult<Option<T>> {
+    let mut len_bytes = [0u8; 4];
+    let mut filled = 0;
+    while filled < len_bytes.len() {
+        match reader.read(&mut len_bytes[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                "wire: Stream closed mid-frame.").into()),
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let len = WireReader::new(&len_bytes).get_u32()? as usize;
+    if len > MAX_FRAME_LEN {
+        return CmnError::err(format!("wire: Frame length ({}) exceeds the maximum ({}).",
+            len, MAX_FRAME_LEN));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+
+    let mut r = WireReader::new(&payload);
+    let msg = T::decode(&mut r)?;
+    r.finish()?;
+    Ok(Some(msg))
+}
+
+
+impl Wire for bool {
+    fn encode(&self, w: &mut WireWriter) { w.put_bool(*self) }
+    fn decode(r: &mut WireReader) -> CmnResult<bool> { r.get_bool() }
+}
+
+impl Wire for u32 {
+    fn encode(&self, w: &mut WireWriter) { w.put_u32(*self) }
+    fn decode(r: &mut WireReader) -> CmnResult<u32> { r.get_u32() }
+}
+
+impl Wire for String {
+    fn encode(&self, w: &mut WireWriter) { w.put_str(self) }
+    fn decode(r: &mut WireReader) -> CmnResult<String> { r.get_string() }
+}
+
+impl Wire for PathBuf {
+    fn encode(&self, w: &mut WireWriter) { w.put_str(&self.to_string_lossy()) }
+    fn decode(r: &mut WireReader) -> CmnResult<PathBuf> { r.get_string().map(PathBuf::from) }
+}
+
+impl<T: Wire> Wire for Option<T> {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            Some(ref val) => { w.put_u8(1); val.encode(w); },
+            None => w.put_u8(0),
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<Option<T>> {
+        match r.get_u8()? {
+            0 => Ok(None),
+            1 => T::decode(r).map(Some),
+            tag => Err(invalid_tag("Option", tag)),
+        }
+    }
+}
+
+impl<T: Wire> Wire for Vec<T> {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_u32(self.len() as u32);
+        for val in self.iter() { val.encode(w); }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<Vec<T>> {
+        let len = r.get_u32()? as usize;
+        // Avoid trusting the length for the initial allocation:
+        let mut vals = Vec::with_capacity(len.min(1024));
+        for _ in 0..len { vals.push(T::decode(r)?); }
+        Ok(vals)
+    }
+}
+
+
+impl Wire for LayerAddress {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_u32(self.area_id() as u32);
+        w.put_u32(self.layer_id() as u32);
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<LayerAddress> {
+        let area_id = r.get_u32()? as usize;
+        let layer_id = r.get_u32()? as usize;
+        Ok(LayerAddress::new(area_id, layer_id))
+    }
+}
+
+
+// Tags are the stable codes of `SamplerKind::code`:
+impl Wire for SamplerKind {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_u8(self.code());
+        match *self {
+            SamplerKind::None => (),
+            SamplerKind::Axons(ref lyr_addr) => lyr_addr.encode(w),
+            _ => if let Some(lyr_addr) = self.layer_addr() { lyr_addr.encode(w) },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<SamplerKind> {
+        let code = r.get_u8()?;
+        let lyr_addr = match code {
+            0 => None,
+            1 => Wire::decode(r)?,
+            _ if SamplerKind::code_name(code).is_some() => Some(Wire::decode(r)?),
+            _ => return Err(invalid_tag("SamplerKind", code)),
+        };
+        SamplerKind::from_code(code, lyr_addr).ok_or_else(|| invalid_tag("SamplerKind", code))
+    }
+}
+
+
+impl Wire for SamplerBufferKind {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_u8(match *self {
+            SamplerBufferKind::None => 0,
+            SamplerBufferKind::Map => 1,
+            SamplerBufferKind::Single => 2,
+            SamplerBufferKind::Double => 3,
+            SamplerBufferKind::Triple => 4,
+        })
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<SamplerBufferKind> {
+        match r.get_u8()? {
+            0 => Ok(SamplerBufferKind::None),
+            1 => Ok(SamplerBufferKind::Map),
+            2 => Ok(SamplerBufferKind::Single),
+            3 => Ok(SamplerBufferKind::Double),
+            4 => Ok(SamplerBufferKind::Triple),
+            tag => Err(invalid_tag("SamplerBufferKind", tag)),
+        }
+    }
+}
+
+
+impl Wire for AreaSetting {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            AreaSetting::Learning(enabled) => { w.put_u8(0); w.put_bool(enabled); },
+            AreaSetting::Regrowth(enabled) => { w.put_u8(1); w.put_bool(enabled); },
+            AreaSetting::AnomalyTracking(ref layer_name, enabled) => {
+                w.put_u8(2);
+                w.put_str(layer_name);
+                w.put_bool(enabled);
+            },
+            AreaSetting::LearningParams(ref params) => { w.put_u8(3); params.encode(w); },
+            AreaSetting::Neuromodulation(value) => { w.put_u8(4); w.put_u8(value as u8); },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<AreaSetting> {
+        match r.get_u8()? {
+            0 => Ok(AreaSetting::Learning(r.get_bool()?)),
+            1 => Ok(AreaSetting::Regrowth(r.get_bool()?)),
+            2 => {
+                let layer_name = r.get_string()?;
+                Ok(AreaSetting::AnomalyTracking(layer_name, r.get_bool()?))
+            },
+            3 => Ok(AreaSetting::LearningParams(Wire::decode(r)?)),
+            4 => Ok(AreaSetting::Neuromodulation(r.get_u8()? as i8)),
+            tag => Err(invalid_tag("AreaSetting", tag)),
+        }
+    }
+}
+
+
+impl Wire for Command {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            Command::None => w.put_u8(0),
+            Command::Iterate(n) => { w.put_u8(1); w.put_u32(n); },
+            Command::Step(n) => { w.put_u8(2); w.put_u32(n); },
+            Command::Pause => w.put_u8(3),
+            Command::Resume => w.put_u8(4),
+            Command::Stop => w.put_u8(5),
+            Command::Exit => w.put_u8(6),
+            Command::ExitAfterCycling => w.put_u8(7),
+            Command::SetLearning(enabled) => { w.put_u8(8); w.put_bool(enabled); },
+            Command::Snapshot(ref path) => { w.put_u8(9); path.encode(w); },
+            Command::AreaSetting { ref area_name, ref setting } => {
+                w.put_u8(10);
+                w.put_str(area_name);
+                setting.encode(w);
+            },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<Command> {
+        match r.get_u8()? {
+            0 => Ok(Command::None),
+            1 => Ok(Command::Iterate(r.get_u32()?)),
+            2 => Ok(Command::Step(r.get_u32()?)),
+            3 => Ok(Command::Pause),
+            4 => Ok(Command::Resume),
+            5 => Ok(Command::Stop),
+            6 => Ok(Command::Exit),
+            7 => Ok(Command::ExitAfterCycling),
+            8 => Ok(Command::SetLearning(r.get_bool()?)),
+            9 => Ok(Command::Snapshot(Wire::decode(r)?)),
+            10 => {
+                let area_name = r.get_string()?;
+                let setting = Wire::decode(r)?;
+                Ok(Command::AreaSetting { area_name, setting })
+            },
+            tag => Err(invalid_tag("Command", tag)),
+        }
+    }
+}
+
+
+impl Wire for Request {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            Request::CurrentIter => w.put_u8(0),
+            Request::Status => w.put_u8(1),
+            Request::AreaInfo { ref area_name } => { w.put_u8(2); w.put_str(area_name); },
+            Request::Sampler { ref area_name, ref kind, ref buffer_kind, backpressure } => {
+                w.put_u8(3);
+                w.put_str(area_name);
+                kind.encode(w);
+                buffer_kind.encode(w);
+                w.put_bool(backpressure);
+            },
+            Request::FinishQueues => w.put_u8(4),
+            Request::Command(ref cmd) => { w.put_u8(5); cmd.encode(w); },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<Request> {
+        match r.get_u8()? {
+            0 => Ok(Request::CurrentIter),
+            1 => Ok(Request::Status),
+            2 => Ok(Request::AreaInfo { area_name: r.get_string()? }),
+            3 => {
+                let area_name = r.get_string()?;
+                let kind = Wire::decode(r)?;
+                let buffer_kind = Wire::decode(r)?;
+                let backpressure = r.get_bool()?;
+                Ok(Request::Sampler { area_name, kind, buffer_kind, backpressure })
+            },
+            4 => Ok(Request::FinishQueues),
+            5 => Ok(Request::Command(Wire::decode(r)?)),
+            tag => Err(invalid_tag("Request", tag)),
+        }
+    }
+}
+
+
+impl Wire for MotorFrame {
+    fn encode(&self, w: &mut WireWriter) {
+        match *self {
+            MotorFrame::Vector2d { ref lyr_addr, xy } => {
+                w.put_u8(0);
+                lyr_addr.encode(w);
+                w.put_f64(xy[0]);
+                w.put_f64(xy[1]);
+            },
+            MotorFrame::Discrete { ref lyr_addr, action } => {
+                w.put_u8(1);
+                lyr_addr.encode(w);
+                action.map(|a| a as u32).encode(w);
+            },
+        }
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<MotorFrame> {
+        match r.get_u8()? {
+            0 => {
+                let lyr_addr = Wire::decode(r)?;
+                let xy = [r.get_f64()?, r.get_f64()?];
+                Ok(MotorFrame::Vector2d { lyr_addr, xy })
+            },
+            1 => {
+                let lyr_addr = Wire::decode(r)?;
+                let action = Option::<u32>::decode(r)?.map(|a| a as usize);
+                Ok(MotorFrame::Discrete { lyr_addr, action })
+            },
+            tag => Err(invalid_tag("MotorFrame", tag)),
+        }
+    }
+}
+
+
+impl Wire for AnomalyScore {
+    fn encode(&self, w: &mut WireWriter) {
+        self.lyr_addr().encode(w);
+        w.put_bytes(&self.to_frame());
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<AnomalyScore> {
+        let lyr_addr = Wire::decode(r)?;
+        AnomalyScore::from_frame(lyr_addr, &r.get_bytes()?)
+    }
+}
+
+
+impl Wire for LearningParams {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_u8(self.syn_strength_floor as u8);
+        w.put_u64(self.syn_regrowth_interval as u64);
+        w.put_u8(self.syn_strength_initial_deviation as u8);
+        w.put_u8(self.dst_syn_strength_default as u8);
+        w.put_u8(self.prx_syn_strength_default as u8);
+        w.put_u32(self.dst_potentiation_rate_l2i as u32);
+        w.put_u32(self.dst_depression_rate_l2i as u32);
+        w.put_u32(self.prx_potentiation_rate_l2i as u32);
+        w.put_u32(self.prx_depression_rate_l2i as u32);
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<LearningParams> {
+        Ok(LearningParams {
+            syn_strength_floor: r.get_u8()? as i8,
+            syn_regrowth_interval: r.get_u64()? as usize,
+            syn_strength_initial_deviation: r.get_u8()? as i8,
+            dst_syn_strength_default: r.get_u8()? as i8,
+            prx_syn_strength_default: r.get_u8()? as i8,
+            dst_potentiation_rate_l2i: r.get_u32()? as i32,
+            dst_depression_rate_l2i: r.get_u32()? as i32,
+            prx_potentiation_rate_l2i: r.get_u32()? as i32,
+            prx_depression_rate_l2i: r.get_u32()? as i32,
+        })
+    }
+}
+
+
+impl Wire for Status {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_bool(self.cycling);
+        w.put_bool(self.paused);
+        w.put_bool(self.learning);
+        w.put_u32(self.cur_cycle.0);
+        w.put_u32(self.prev_cycles.0);
+        w.put_i64(self.prev_elapsed.num_milliseconds());
+        match self.cur_start_time {
+            Some(ts) => {
+                w.put_u8(1);
+                w.put_i64(ts.sec);
+                w.put_u32(ts.nsec as u32);
+            },
+            None => w.put_u8(0),
+        }
+        w.put_u64(self.cycle_counter.0);
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<Status> {
+        let cycling = r.get_bool()?;
+        let paused = r.get_bool()?;
+        let learning = r.get_bool()?;
+        let cur_cycle = Wrapping(r.get_u32()?);
+        let prev_cycles = Wrapping(r.get_u32()?);
+        let prev_elapsed = Duration::milliseconds(r.get_i64()?);
+        let cur_start_time = match r.get_u8()? {
+            0 => None,
+            1 => {
+                let sec = r.get_i64()?;
+                let nsec = r.get_u32()? as i32;
+                Some(Timespec::new(sec, nsec))
+            },
+            tag => return Err(invalid_tag("Option<Timespec>", tag)),
+        };
+        let cycle_counter = Wrapping(r.get_u64()?);
+
+        Ok(Status { cycling, paused, learning, cur_cycle, prev_cycles, prev_elapsed,
+            cur_start_time, cycle_counter })
+    }
+}
+
+
+impl Wire for AreaInfo {
+    fn encode(&self, w: &mut WireWriter) {
+        w.put_str(&self.name);
+        w.put_bytes(&self.aff_out_slc_ids);
+        self.tract_map.tags().to_vec().encode(w);
+        self.tract_map.v_sizes().to_vec().encode(w);
+        self.tract_map.u_sizes().to_vec().encode(w);
+    }
+
+    fn decode(r: &mut WireReader) -> CmnResult<AreaInfo> {
+        let name = r.get_string()?;
+        let aff_out_slc_ids = r.get_bytes()?;
+        let tags = Vec::<String>::decode(r)?;
+        let v_sizes = Vec::<u32>::decode(r)?;
+        let u_sizes = Vec::<u32>::decode(r)?;
+
+        if tags.len() != v_sizes.len() || tags.len() != u_sizes.len() {
+            return CmnError::err("wire: Mismatched slice tract map lengths.");
+        }
+
+        Ok(AreaInfo { name, aff_out_slc_ids,
+            tract_map: SliceTractMap::new(&tags, &v_sizes, &u_sizes) })
+    }
+}
@@ -15,6 +15,10 @@ extern crate futures_cpupool;
 extern crate tokio_core;
 extern crate crossbeam;
 extern crate ocl_extras;
+extern crate serde;
+extern crate ron;
+extern crate png;
+extern crate flate2;
 #[macro_use]
 extern crate bitflags;
 #[macro_use]
@@ -23,6 +27,8 @@ extern crate enum_primitive;
 extern crate colorify;
 #[macro_use]
 extern crate failure;
+#[macro_use]
+extern crate serde_derive;
 #[cfg(feature = "profile")]
 extern crate cpuprofiler;
 pub extern crate futures;
@@ -32,6 +38,7 @@ mod cortex;
 mod subcortex;
 mod tract_terminal;
 pub mod encode;
+pub mod decode;
 pub mod flywheel;
 pub mod map;
 #[macro_use] pub mod cmn;
@@ -39,10 +46,13 @@ pub mod map;
 pub mod tests;
 
 pub use ocl::Event as OclEvent;
-pub use self::cortex::{Cortex, CorticalArea, AxonSpace, Synapses,
+pub use self::cortex::{Cortex, CorticalArea, AxonSpace, IoQueues, Synapses,
     InhibitoryInterneuronNetwork, ActivitySmoother, PyramidalLayer,
     SpinyStellateLayer, Tufts, Dendrites, CorticalAreaSettings, DataCellLayer,
-    SamplerKind, SamplerBufferKind, CompletionPool, /*CompletionPoolRemote,*/ CorticalAreas};
+    SamplerKind, SamplerBufferKind, CompletionPool, /*CompletionPoolRemote,*/ CorticalAreas,
+    SnapshotWriter, SnapshotReader, DeviceSelector, Placement, Recorder, RecordingReader,
+    AnomalyScore, AnomalyTracker, LearningParams, Neuromodulation, Storage, Backend, host_kernels,
+    recorder};
 #[cfg(any(test, feature = "eval"))]
 pub use self::cortex::{CorticalAreaTest, SynCoords, SynapsesTest, syn_idx,
     AxonSpaceTest, AxnCoords, DenCoords, DendritesTest, den_idx,
@@ -51,13 +61,14 @@ pub use self::cortex::{CorticalAreaTest, SynCoords, SynapsesTest, syn_idx,
     CorticalLayerSamples, DataCellLayerMap};
 pub use self::subcortex::{Thalamus, Subcortex, SubcorticalNucleus,
     SubcorticalNucleusLayer, TestScNucleus, InputGenerator, InputGeneratorTract,
-    InputGeneratorEncoder, InputGeneratorFrame, TractBuffer, TractSender,
+    InputGeneratorEncoder, InputGeneratorFrame, InputGeneratorHandle, TractBuffer, TractSender,
+    Arm, MotorDecoder, MotorDecoding,
     TractReceiver, WriteBuffer, ReadBuffer, FutureSend, FutureRecv,
     /*FutureWriteGuardVec,*/ FutureReadGuardVec,
     /*WriteGuardVec,*/ ReadGuardVec};
 pub use self::flywheel::Flywheel;
 pub use self::map::{LayerMapSchemeList, AreaSchemeList, AreaMap, AxonTopology,
-    LayerAddress};
+    LayerAddress, SchemeConfig};
 pub use self::cmn::{util, TractDims, TypeId, CmnError as Error,
     CmnResult as Result, CorticalDims, MapStore, SrcOfs, SlcId};
 pub use self::encode::GlyphBuckets;
\ No newline at end of file
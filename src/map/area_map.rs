@@ -11,13 +11,13 @@ use {SrcOfs, SlcId};
 #[derive(Clone, Debug)]
 pub struct AreaMap {
     area_id: usize,
-    area_name: &'static str,
+    area_name: String,
     dims: CorticalDims,
     slice_map: SliceMap,
     layer_map: LayerMap,
-    eff_areas: Vec<&'static str>,
-    aff_areas: Vec<&'static str>,
-    other_areas: Vec<(&'static str, Option<Vec<(AxonTags, AxonTags)>>)>,
+    eff_areas: Vec<String>,
+    aff_areas: Vec<String>,
+    other_areas: Vec<(String, Option<Vec<(AxonTags, AxonTags)>>)>,
     filter_chain_schemes: Vec<(InputTrack, AxonTags, Vec<FilterScheme>)>,
 }
 
@@ -36,7 +36,7 @@ impl AreaMap {
 
         Ok(AreaMap {
             area_id: area_id,
-            area_name: area_sch.name(),
+            area_name: area_sch.name().to_owned(),
             dims: dims,
             slice_map: slice_map,
             layer_map: layer_map,
@@ -346,12 +346,12 @@ impl AreaMap {
     }
 
     // DEPRICATE?
-    pub fn aff_areas(&self) -> &Vec<&'static str> {
+    pub fn aff_areas(&self) -> &Vec<String> {
         &self.aff_areas
     }
 
     // DEPRICATE?
-    pub fn eff_areas(&self) -> &Vec<&'static str> {
+    pub fn eff_areas(&self) -> &Vec<String> {
         &self.eff_areas
     }
 
@@ -367,7 +367,7 @@ impl AreaMap {
     }
 
     pub fn area_id(&self) -> usize { self.area_id }
-    pub fn area_name(&self) -> &'static str { self.area_name }
+    pub fn area_name<'s>(&'s self) -> &'s str { &self.area_name }
     pub fn axon_idz(&self, slc_id: SlcId) -> u32 { self.slice_map.idz(slc_id) }
     pub fn slice_map(&self) -> &SliceMap { &self.slice_map }
     pub fn layer_map(&self) -> &LayerMap { &self.layer_map }
@@ -40,10 +40,12 @@
 //   * Pv-2
 
 
+use std::fmt::{self, Formatter};
 use std::ops::BitOr;
 use std::iter::FromIterator;
 use std::collections::BTreeSet;
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 
 const UID_IDZ: u64 = 1 << 16;
 const UID_IDN: u64 = 1 << 31;
@@ -67,6 +69,26 @@ pub const EXT:          AxonTag = AxonTag(PRESET_IDZ + 0x7000);
 pub const GLY_SEQ_IMG:    AxonTag = AxonTag(EXT.0 + 0x10);
 pub const GLY_SEQ_VAL:    AxonTag = AxonTag(EXT.0 + 0x11);
 
+/// Names of the presets above, used for (de)serialization.
+const PRESET_NAMES: &'static [(&'static str, AxonTag)] = &[
+    ("THAL_SP", THAL_SP),
+    ("THAL_NSP", THAL_NSP),
+    ("THAL_ASC", THAL_ASC),
+    ("L2", L2),
+    ("L3", L3),
+    ("L4", L4),
+    ("L5CC", L5CC),
+    ("L5CS", L5CS),
+    ("L5CC_NS", L5CC_NS),
+    ("EXT", EXT),
+    ("GLY_SEQ_IMG", GLY_SEQ_IMG),
+    ("GLY_SEQ_VAL", GLY_SEQ_VAL),
+];
+
+/// Prefixes of serialized custom and auto-generated tags (e.g. `"CUSTOM:5"`).
+const CUSTOM_PREFIX: &'static str = "CUSTOM:";
+const UNIQUE_PREFIX: &'static str = "UNIQUE:";
+
 
 // static NEXT_UID: AtomicUsize = AtomicUsize::new(UID_IDZ as usize);
 static NEXT_UID: AtomicUsize = ATOMIC_USIZE_INIT;
@@ -113,10 +135,82 @@ impl AxonTag {
     pub fn unique() -> AxonTag {
         AxonTag(uid())
     }
+
+    /// Returns the preset with the name `name` (case insensitive, e.g.
+    /// `"THAL_SP"`).
+    pub fn preset(name: &str) -> Option<AxonTag> {
+        let name = name.to_uppercase();
+        PRESET_NAMES.iter().find(|&&(n, _)| n == name).map(|&(_, tag)| tag)
+    }
+
+    /// Returns the symbolic name of this tag: the preset name for presets,
+    /// otherwise `"CUSTOM:<id>"` or `"UNIQUE:<id>"`.
+    ///
+    /// Returns `None` for unnamed values within the preset range.
+    pub fn name(&self) -> Option<String> {
+        if self.0 < UID_IDZ {
+            Some(format!("{}{}", CUSTOM_PREFIX, self.0))
+        } else if self.0 < UID_IDN {
+            Some(format!("{}{}", UNIQUE_PREFIX, self.0))
+        } else {
+            PRESET_NAMES.iter().find(|&&(_, tag)| tag == *self).map(|&(n, _)| n.to_owned())
+        }
+    }
+
+    /// Parses a name as returned by `::name`.
+    pub fn from_name(name: &str) -> Option<AxonTag> {
+        if name.starts_with(CUSTOM_PREFIX) {
+            name[CUSTOM_PREFIX.len()..].parse::<u16>().ok().map(AxonTag::custom)
+        } else if name.starts_with(UNIQUE_PREFIX) {
+            name[UNIQUE_PREFIX.len()..].parse::<u64>().ok()
+                .and_then(|id| if id >= UID_IDZ && id < UID_IDN { Some(AxonTag(id)) } else { None })
+        } else {
+            AxonTag::preset(name)
+        }
+    }
 }
 
+// Serialized by name (see `AxonTag::name`). Raw values are accepted when
+// deserializing.
+impl Serialize for AxonTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        let name = self.name().ok_or_else(|| S::Error::custom(format!(
+            "AxonTag: Unnamed preset value: {:#x}", self.0)))?;
+        serializer.serialize_str(&name)
+    }
+}
+
+impl<'de> Deserialize<'de> for AxonTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<AxonTag, D::Error> {
+        use serde::de::{self, Visitor};
+
+        struct AxonTagVisitor;
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        impl<'de> Visitor<'de> for AxonTagVisitor {
+            type Value = AxonTag;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("an axon tag name")
+            }
+
+            fn visit_u64<E: de::Error>(self, val: u64) -> Result<AxonTag, E> {
+                Ok(AxonTag(val))
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<AxonTag, E> {
+                AxonTag::from_name(name).ok_or_else(|| {
+                    E::custom(format!("Unknown axon tag: '{}'", name))
+                })
+            }
+        }
+
+        deserializer.deserialize_any(AxonTagVisitor)
+    }
+}
+
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AxonTags {
     tags: BTreeSet<AxonTag>,
 }
@@ -129,6 +223,11 @@ impl AxonTags {
     pub fn is_superset(&self, other: &AxonTags) -> bool {
         self.tags.is_superset(&other.tags)
     }
+
+    /// Returns an iterator over the tags, in order.
+    pub fn iter(&self) -> ::std::collections::btree_set::Iter<AxonTag> {
+        self.tags.iter()
+    }
 }
 
 impl<'a, 'b> BitOr<&'b AxonTags> for &'a AxonTags {
@@ -3,11 +3,14 @@
 use std::collections::{HashMap, BTreeMap};
 use std::error;
 use std::fmt;
-use ocl::{self, Event, Buffer, OclPrm, Error as OclError};
-use ocl::core::CommandExecutionStatus;
+use ocl::{self, Event, Context, Error as OclError};
+use ocl::core::{CommandExecutionStatus, EventInfo, EventInfoResult, ProfilingInfo};
 use ocl::ffi::{cl_event, c_void};
 use map::LayerAddress;
-use cmn::{util};
+use cmn::util::BufferUid;
+
+pub use self::export::{DependencyKind, DependencyEdge};
+pub use self::validation::ValidationIssue;
 
 const PRNT: bool = false;
 const PRINT_EVENT_DEBUG: bool = false;
@@ -59,6 +62,15 @@ impl MemBlockRwCmdIdxs {
 type MemBlockRwsMap = HashMap<MemoryBlock, MemBlockRwCmdIdxs>;
 
 
+/// Returns true if `event` was created within `context`.
+fn event_in_context(event: &Event, context: &Context) -> ExeGrResult<bool> {
+    match event.info(EventInfo::Context)? {
+        EventInfoResult::Context(ev_context) => Ok(ev_context.as_ptr() == context.as_ptr()),
+        _ => Ok(false),
+    }
+}
+
+
 #[derive(Debug, Clone)]
 enum RequisitePrecedence {
     Preceding,
@@ -83,6 +95,7 @@ pub enum ExecutionGraphError {
     Unlocked,
     OclError(OclError),
     EventsRequestOutOfOrder(usize, usize),
+    Invalid(Vec<ValidationIssue>),
 }
 
 impl error::Error for ExecutionGraphError {
@@ -95,6 +108,7 @@ impl error::Error for ExecutionGraphError {
             ExecutionGraphError::Unlocked => "Graph unlocked.",
             ExecutionGraphError::OclError(_) => "OpenCL Error.",
             ExecutionGraphError::EventsRequestOutOfOrder(..) => "Events requested out of order.",
+            ExecutionGraphError::Invalid(..) => "Invalid command ordering.",
         }
     }
 }
@@ -140,6 +154,13 @@ impl fmt::Display for ExecutionGraphError {
                     the commands were configured with `::order_command`. Ensure that each command is \
                     calling `::set_cmd_event` when enqueued.", expected_order, found_order))
             }
+            ExecutionGraphError::Invalid(ref issues) => {
+                f.write_str("Execution graph validation failed:")?;
+                for issue in issues.iter() {
+                    f.write_fmt(format_args!("\n  - {}", issue))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -166,6 +187,7 @@ pub enum CorticalBuffer {
     DataCellTuft { buffer_id: u64, layer_addr: LayerAddress, tuft_id: usize },
     DataCellSomaLayer { buffer_id: u64, layer_addr: LayerAddress },
     ControlCellSomaLayer { buffer_id: u64, layer_addr: LayerAddress },
+    Neuromodulation { buffer_id: u64, area_id: usize },
 }
 
 impl CorticalBuffer {
@@ -186,63 +208,70 @@ impl CorticalBuffer {
     //     }
     // }
 
-    pub fn axon_slice(buf: &Buffer<u8>, area_id: usize, slc_id: u8)
+    pub fn axon_slice<B: BufferUid>(buf: &B, area_id: usize, slc_id: u8)
             -> CorticalBuffer {
         CorticalBuffer::AxonSlice {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
             area_id: area_id,
             slc_id: slc_id,
         }
     }
 
-    pub fn axon_input_filter<T: OclPrm>(buf: &Buffer<T>) -> CorticalBuffer {
+    pub fn axon_input_filter<B: BufferUid>(buf: &B) -> CorticalBuffer {
         CorticalBuffer::AxonInputFilter {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
         }
     }
 
-    pub fn data_syn_tft<T: OclPrm>(buf: &Buffer<T>, layer_addr: LayerAddress, tuft_id: usize)
+    pub fn data_syn_tft<B: BufferUid>(buf: &B, layer_addr: LayerAddress, tuft_id: usize)
             -> CorticalBuffer {
         CorticalBuffer::DataCellSynapseTuft {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
             layer_addr: layer_addr,
             tuft_id: tuft_id,
         }
     }
 
-    pub fn data_den_tft<T: OclPrm>(buf: &Buffer<T>, layer_addr: LayerAddress, tuft_id: usize)
+    pub fn data_den_tft<B: BufferUid>(buf: &B, layer_addr: LayerAddress, tuft_id: usize)
             -> CorticalBuffer {
         CorticalBuffer::DataCellDendriteTuft {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
             layer_addr: layer_addr,
             tuft_id: tuft_id,
         }
     }
 
-    pub fn data_tft<T: OclPrm>(buf: &Buffer<T>, layer_addr: LayerAddress, tuft_id: usize)
+    pub fn data_tft<B: BufferUid>(buf: &B, layer_addr: LayerAddress, tuft_id: usize)
             -> CorticalBuffer {
         CorticalBuffer::DataCellTuft {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
             layer_addr,
             tuft_id,
         }
     }
 
-    pub fn data_soma_lyr<T: OclPrm>(buf: &Buffer<T>, layer_addr: LayerAddress)
+    pub fn data_soma_lyr<B: BufferUid>(buf: &B, layer_addr: LayerAddress)
             -> CorticalBuffer {
         CorticalBuffer::DataCellSomaLayer {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
             layer_addr,
         }
     }
 
-    pub fn control_soma_lyr<T: OclPrm>(buf: &Buffer<T>, layer_addr: LayerAddress)
+    pub fn control_soma_lyr<B: BufferUid>(buf: &B, layer_addr: LayerAddress)
             -> CorticalBuffer {
         CorticalBuffer::ControlCellSomaLayer {
-            buffer_id: util::buffer_uid(buf),
+            buffer_id: buf.buffer_uid(),
             layer_addr,
         }
     }
+
+    pub fn neuromodulation<B: BufferUid>(buf: &B, area_id: usize) -> CorticalBuffer {
+        CorticalBuffer::Neuromodulation {
+            buffer_id: buf.buffer_uid(),
+            area_id,
+        }
+    }
 }
 
 
@@ -282,6 +311,40 @@ pub enum MemoryBlock {
     ThalamicTract(ThalamicTract),
 }
 
+impl fmt::Display for MemoryBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MemoryBlock::CorticalBuffer(ref cb) => match *cb {
+                CorticalBuffer::AxonSlice { area_id, slc_id, .. } =>
+                    write!(f, "AxonSlice(area: {}, slc: {})", area_id, slc_id),
+                CorticalBuffer::AxonInputFilter { buffer_id } =>
+                    write!(f, "AxonInputFilter(buf: {:x})", buffer_id),
+                CorticalBuffer::DataCellSynapseTuft { buffer_id, layer_addr, tuft_id } =>
+                    write!(f, "DataCellSynapseTuft(lyr: {}.{}, tft: {}, buf: {:x})",
+                        layer_addr.area_id(), layer_addr.layer_id(), tuft_id, buffer_id),
+                CorticalBuffer::DataCellDendriteTuft { buffer_id, layer_addr, tuft_id } =>
+                    write!(f, "DataCellDendriteTuft(lyr: {}.{}, tft: {}, buf: {:x})",
+                        layer_addr.area_id(), layer_addr.layer_id(), tuft_id, buffer_id),
+                CorticalBuffer::DataCellTuft { buffer_id, layer_addr, tuft_id } =>
+                    write!(f, "DataCellTuft(lyr: {}.{}, tft: {}, buf: {:x})",
+                        layer_addr.area_id(), layer_addr.layer_id(), tuft_id, buffer_id),
+                CorticalBuffer::DataCellSomaLayer { buffer_id, layer_addr } =>
+                    write!(f, "DataCellSomaLayer(lyr: {}.{}, buf: {:x})",
+                        layer_addr.area_id(), layer_addr.layer_id(), buffer_id),
+                CorticalBuffer::ControlCellSomaLayer { buffer_id, layer_addr } =>
+                    write!(f, "ControlCellSomaLayer(lyr: {}.{}, buf: {:x})",
+                        layer_addr.area_id(), layer_addr.layer_id(), buffer_id),
+                CorticalBuffer::Neuromodulation { buffer_id, area_id } =>
+                    write!(f, "Neuromodulation(area: {}, buf: {:x})", area_id, buffer_id),
+            },
+            MemoryBlock::SubcorticalBuffer(SubcorticalBuffer::AxonSlice { area_id, slc_id, .. }) =>
+                write!(f, "SubcorticalAxonSlice(area: {}, slc: {})", area_id, slc_id),
+            MemoryBlock::ThalamicTract(ThalamicTract::AxonSlice { area_id, slc_id, .. }) =>
+                write!(f, "ThalamicTract(area: {}, slc: {})", area_id, slc_id),
+        }
+    }
+}
+
 
 /// An execution command kind.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -396,7 +459,8 @@ impl CommandRelations {
         }
     }
 
-    fn variant_string(&self) -> &'static str {
+    /// Returns the name of the command kind (e.g. "CorticalKernel").
+    pub fn variant_string(&self) -> &'static str {
         match self.kind {
             CommandRelationsKind::CorticalKernel { .. } => "CorticalKernel",
             CommandRelationsKind::CorticothalamicRead { .. } => "CorticothalamicRead",
@@ -407,7 +471,9 @@ impl CommandRelations {
         }
     }
 
-    fn kernel_name<'a>(&'a self) -> &'a str {
+    /// Returns the kernel name for `CorticalKernel` commands or an empty
+    /// string otherwise.
+    pub fn kernel_name<'a>(&'a self) -> &'a str {
         match self.kind {
             CommandRelationsKind::CorticalKernel { ref name, .. } => name,
             _ => "",
@@ -424,6 +490,10 @@ impl CommandRelations {
 pub struct ExecutionCommand {
     uid: CommandUid,
     event: Option<Event>,
+    bridged: bool,
+    duration_ns: Option<u64>,
+    ttl_duration_ns: u64,
+    duration_count: u64,
     requisite_cmd_idxs: Vec<usize>,
     requisite_cmd_precedence: Vec<RequisitePrecedence>,
     stale_events: Vec<Event>,
@@ -434,6 +504,10 @@ impl ExecutionCommand {
         ExecutionCommand {
             uid,
             event: None,
+            bridged: false,
+            duration_ns: None,
+            ttl_duration_ns: 0,
+            duration_count: 0,
             // [NOTE]: Sizing these vectors here could be delayed until
             // `::populate_requisites` to avoid creating canned sizes.
             requisite_cmd_idxs: Vec::with_capacity(8),
@@ -442,11 +516,45 @@ impl ExecutionCommand {
         }
     }
 
-    fn set_event(&mut self, event: Option<Event>) {
+    fn set_event(&mut self, event: Option<Event>, bridged: bool) {
         self.event = event;
+        self.bridged = bridged;
+    }
+
+    /// Records the device execution time of the current event if it has
+    /// completed. The event must have been enqueued on a queue with
+    /// profiling enabled.
+    fn record_duration(&mut self) -> ExeGrResult<()> {
+        if let Some(ref ev) = self.event {
+            if ev.is_complete()? {
+                let start = ev.profiling_info(ProfilingInfo::Start)?.time()?;
+                let end = ev.profiling_info(ProfilingInfo::End)?.time()?;
+                let duration_ns = end.saturating_sub(start);
+                self.duration_ns = Some(duration_ns);
+                self.ttl_duration_ns += duration_ns;
+                self.duration_count += 1;
+            }
+        }
+        Ok(())
     }
 
     #[inline] pub fn event(&self) -> Option<&Event> { self.event.as_ref() }
+    #[inline] pub fn uid(&self) -> CommandUid { self.uid }
+    #[inline] pub fn requisite_cmd_idxs(&self) -> &[usize] { &self.requisite_cmd_idxs }
+
+    /// Returns the most recently measured device execution time, in
+    /// nanoseconds, if profiling is enabled (see
+    /// `ExecutionGraph::set_profiling`).
+    #[inline] pub fn duration_ns(&self) -> Option<u64> { self.duration_ns }
+
+    /// Returns the mean measured device execution time, in nanoseconds.
+    pub fn mean_duration_ns(&self) -> Option<f64> {
+        if self.duration_count == 0 {
+            None
+        } else {
+            Some(self.ttl_duration_ns as f64 / self.duration_count as f64)
+        }
+    }
 }
 
 
@@ -460,6 +568,10 @@ pub struct ExecutionGraph {
     cmd_requisite_events: Vec<Vec<cl_event>>,
     next_cmd_idx: usize,
     locked: bool,
+    context: Option<Context>,
+    profiling: bool,
+    strict: bool,
+    issues: Vec<ValidationIssue>,
     // stale_events: HashMap<(Event, usize), usize>,
 }
 
@@ -473,6 +585,10 @@ impl ExecutionGraph {
             cmd_requisite_events: Vec::with_capacity(256),
             next_cmd_idx: 0,
             locked: false,
+            context: None,
+            profiling: false,
+            strict: false,
+            issues: Vec::new(),
             // stale_events: HashMap::with_capacity(64),
         }
     }
@@ -493,6 +609,16 @@ impl ExecutionGraph {
         Ok(cmd_uid)
     }
 
+    /// Removes a previously added command.
+    pub fn remove_command(&mut self, cmd_uid: CommandUid) -> ExeGrResult<()> {
+        if self.locked { return Err(ExecutionGraphError::Locked); }
+
+        match self.cmd_relations.remove(&cmd_uid) {
+            Some(_) => Ok(()),
+            None => Err(ExecutionGraphError::OrderInvalidCommandUid(cmd_uid)),
+        }
+    }
+
     /// Specifies a command (by index) as the next in the loose sequence and
     /// returns the command's ordered index (idx).
     pub fn order_command(&mut self, cmd_uid: CommandUid) -> ExeGrResult<usize> {
@@ -605,15 +731,23 @@ impl ExecutionGraph {
         }
     }
 
-    /// Populates the list of requisite commands for each command and locks
-    /// the graph, disallowing addition or removal of commands until unlocked
-    /// with `::unlock`.
-    pub fn lock(&mut self) {
-        assert!(self.cmd_relations.len() == self.cmds.len(), "ExecutionGraph::lock \
-            Not all commands have had their order properly set ({}/{}). Call '::order_command' to \
-            include commands in the execution order.", self.cmds.len(), self.cmd_relations.len());
+    /// Validates the command ordering, populates the list of requisite
+    /// commands for each command, and locks the graph, disallowing addition
+    /// or removal of commands until unlocked with `::unlock`.
+    ///
+    /// Commands which were never ordered or which read from and write to the
+    /// same block always cause an error. Other issues (see `::validate`)
+    /// cause an error only if strict validation is enabled (see
+    /// `::set_strict`) and are otherwise available from `::issues`.
+    pub fn lock(&mut self) -> ExeGrResult<()> {
         assert!(!self.locked, "Cannot populate this graph while locked. Use '::unlock_clear' first.");
 
+        let issues = self.validate();
+        if issues.iter().any(|i| i.is_fatal()) || (self.strict && !issues.is_empty()) {
+            return Err(ExecutionGraphError::Invalid(issues));
+        }
+        self.issues = issues;
+
         let mem_block_rws = self.readers_and_writers_by_mem_block();
 
         if PRNT { println!("\n##### Preceding Writers and Following Readers (CommandUid:[idx]:)"); }
@@ -647,9 +781,44 @@ impl ExecutionGraph {
 
         self.locked = true;
         self.next_cmd_idx = 0;
+        Ok(())
+    }
+
+    /// Enables or disables strict validation, causing `::lock` to fail on
+    /// any validation issue rather than only on fatal ones.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Returns the non-fatal validation issues found when this graph was
+    /// last locked.
+    #[inline] pub fn issues(&self) -> &[ValidationIssue] { &self.issues }
+
+    /// Sets the context which this graph's commands are enqueued within.
+    ///
+    /// When set, any command event belonging to a different context (an area
+    /// or nucleus placed on a device from another platform) is bridged:
+    /// waited upon by the host within `::get_req_events` instead of being
+    /// passed along in a device wait list, which OpenCL does not permit
+    /// across contexts. Leave unset when only one context is in use to avoid
+    /// querying the context of every event.
+    pub fn set_context(&mut self, context: Option<Context>) {
+        self.context = context;
+    }
+
+    /// Enables or disables recording of device execution times.
+    ///
+    /// Each command's duration is read from its previous event when that
+    /// event is replaced by `::set_cmd_event`. All commands must be enqueued
+    /// on queues created with `QUEUE_PROFILING_ENABLE`.
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profiling = profiling;
     }
 
     /// Returns the list of requisite events for a command.
+    ///
+    /// Events from foreign contexts (see `::set_context`) are waited upon
+    /// before returning and are not included in the list.
     pub fn get_req_events(&mut self, cmd_idx: usize) -> ExeGrResult<&[cl_event]> {
         if !self.locked { return Err(ExecutionGraphError::Unlocked); }
 
@@ -667,6 +836,11 @@ impl ExecutionGraph {
             let cmd = unsafe { self.cmds.get_unchecked(req_idx) };
 
             if let Some(event) = cmd.event() {
+                if cmd.bridged {
+                    event.wait_for()?;
+                    continue;
+                }
+
                 unsafe {
                     self.cmd_requisite_events.get_unchecked_mut(cmd_idx).push(*event.as_ptr_ref());
                 }
@@ -684,6 +858,12 @@ impl ExecutionGraph {
         if !self.locked { return Err(ExecutionGraphError::Unlocked); }
         let cmds_len = self.cmds.len();
 
+        let bridged = match (self.context.as_ref(), event.as_ref()) {
+            (Some(context), Some(ev)) => !event_in_context(ev, context)?,
+            _ => false,
+        };
+        let profiling = self.profiling;
+
         {
             let cmd = self.cmds.get_mut(cmd_idx)
                 .ok_or(ExecutionGraphError::InvalidCommandIndex(cmd_idx))?;
@@ -697,7 +877,8 @@ impl ExecutionGraph {
                     set_debug_callback_complete(ev, cmd_idx);
                 }
             }
-            cmd.set_event(event);
+            if profiling { cmd.record_duration()?; }
+            cmd.set_event(event, bridged);
         }
 
         if (self.next_cmd_idx + 1) == cmds_len {
@@ -759,8 +940,309 @@ impl ExecutionGraph {
     }
 
     #[inline] pub fn is_locked(&self) -> bool { self.locked }
+    #[inline] pub fn context(&self) -> Option<&Context> { self.context.as_ref() }
+    #[inline] pub fn profiling(&self) -> bool { self.profiling }
     #[inline] pub fn cmd(&self, cmd_idx: usize) -> Option<&ExecutionCommand> { self.cmds.get(cmd_idx) }
+    #[inline] pub fn cmd_count(&self) -> usize { self.cmds.len() }
+
+    /// Returns the relations (sources and targets) of an ordered command.
+    pub fn cmd_relations(&self, cmd_idx: usize) -> Option<&CommandRelations> {
+        self.cmds.get(cmd_idx).and_then(|cmd| self.cmd_relations.get(&cmd.uid))
+    }
 }
 
 unsafe impl Send for ExecutionGraph {}
 
+
+/// Static validation of command ordering.
+mod validation {
+    use std::fmt;
+    use super::{ExecutionGraph, MemoryBlock, CommandUid};
+
+    /// A problem with the ordering or relations of the commands within an
+    /// execution graph.
+    #[derive(Debug, Clone)]
+    pub enum ValidationIssue {
+        /// A command was added but never ordered with `::order_command`.
+        Unordered { uid: CommandUid, cmd: String },
+        /// A command lists the same block as both a source and a target.
+        /// Such blocks should be listed only as targets.
+        SelfDependency { cmd_idx: usize, cmd: String, block: MemoryBlock },
+        /// A command reads a block before every command which writes to that
+        /// block within the same cycle and will therefore always read the
+        /// previous cycle's contents.
+        StaleRead { reader_idx: usize, reader: String, writer_idx: usize, writer: String,
+            block: MemoryBlock },
+        /// A command shares no block with any other command and is neither
+        /// waited upon nor waits for anything.
+        Unreachable { cmd_idx: usize, cmd: String },
+    }
+
+    impl ValidationIssue {
+        /// Returns true if this issue makes it impossible to lock the graph.
+        pub fn is_fatal(&self) -> bool {
+            match *self {
+                ValidationIssue::Unordered { .. } | ValidationIssue::SelfDependency { .. } => true,
+                _ => false,
+            }
+        }
+    }
+
+    impl fmt::Display for ValidationIssue {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                ValidationIssue::Unordered { uid, ref cmd } => write!(f, "Command {} ({}) \
+                    was never ordered. Call '::order_command' to include it in the execution \
+                    order.", uid, cmd),
+                ValidationIssue::SelfDependency { cmd_idx, ref cmd, ref block } => write!(f,
+                    "Command [{}] ({}) both reads from and writes to '{}'. List the block only \
+                    as a target.", cmd_idx, cmd, block),
+                ValidationIssue::StaleRead { reader_idx, ref reader, writer_idx, ref writer,
+                        ref block } => write!(f, "Command [{}] ({}) reads '{}' before it is \
+                    written by command [{}] ({}) and will read the previous cycle's contents.",
+                    reader_idx, reader, block, writer_idx, writer),
+                ValidationIssue::Unreachable { cmd_idx, ref cmd } => write!(f, "Command [{}] \
+                    ({}) shares no memory block with any other command.", cmd_idx, cmd),
+            }
+        }
+    }
+
+    impl ExecutionGraph {
+        /// Returns a description of a command (its kind and kernel name).
+        fn cmd_description(&self, uid: CommandUid) -> String {
+            let cmd_rel = &self.cmd_relations[&uid];
+            match cmd_rel.kernel_name() {
+                "" => cmd_rel.variant_string().to_owned(),
+                name => format!("{}: '{}'", cmd_rel.variant_string(), name),
+            }
+        }
+
+        /// Checks the command ordering and relations for mistakes.
+        ///
+        /// Thalamic tract blocks are exempt from stale read detection as
+        /// reads from the tract are expected to lag writes by a cycle.
+        pub fn validate(&self) -> Vec<ValidationIssue> {
+            let mut issues = Vec::new();
+
+            for (&uid, cmd_rel) in self.cmd_relations.iter() {
+                if cmd_rel.cmd_idx.is_none() {
+                    issues.push(ValidationIssue::Unordered { uid, cmd: self.cmd_description(uid) });
+                }
+            }
+
+            let mem_block_rws = self.readers_and_writers_by_mem_block();
+            let mut connected = vec![false; self.cmds.len()];
+
+            for (cmd_idx, cmd) in self.cmds.iter().enumerate() {
+                let cmd_rel = &self.cmd_relations[&cmd.uid];
+                let targets = cmd_rel.targets();
+
+                for block in cmd_rel.sources().into_iter() {
+                    if targets.contains(&block) {
+                        issues.push(ValidationIssue::SelfDependency { cmd_idx,
+                            cmd: self.cmd_description(cmd.uid), block: block.clone() });
+                        continue;
+                    }
+
+                    let writers = &mem_block_rws[&block].writers;
+                    if writers.is_empty() { continue; }
+                    connected[cmd_idx] = true;
+                    for &writer_idx in writers.iter() { connected[writer_idx] = true; }
+
+                    if let MemoryBlock::ThalamicTract(_) = block { continue; }
+
+                    let first_writer_idx = *writers.iter().min().unwrap();
+                    if first_writer_idx > cmd_idx {
+                        issues.push(ValidationIssue::StaleRead {
+                            reader_idx: cmd_idx,
+                            reader: self.cmd_description(cmd.uid),
+                            writer_idx: first_writer_idx,
+                            writer: self.cmd_description(self.cmds[first_writer_idx].uid),
+                            block,
+                        });
+                    }
+                }
+            }
+
+            for (cmd_idx, &is_connected) in connected.iter().enumerate() {
+                if !is_connected {
+                    issues.push(ValidationIssue::Unreachable { cmd_idx,
+                        cmd: self.cmd_description(self.cmds[cmd_idx].uid) });
+                }
+            }
+
+            issues
+        }
+    }
+}
+
+
+/// Graphviz DOT and JSON export.
+mod export {
+    use std::fmt::Write;
+    use super::{ExecutionGraph, MemoryBlock};
+
+    /// The hazard a dependency edge guards against.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum DependencyKind {
+        /// The command at `to` reads a block written by the command at `from`.
+        ReadAfterWrite,
+        /// The command at `to` writes a block read by the command at `from`.
+        WriteAfterRead,
+    }
+
+    impl DependencyKind {
+        fn as_str(&self) -> &'static str {
+            match *self {
+                DependencyKind::ReadAfterWrite => "raw",
+                DependencyKind::WriteAfterRead => "war",
+            }
+        }
+    }
+
+    /// A buffer dependency between two ordered commands.
+    ///
+    /// An edge whose `from` command is ordered after its `to` command refers
+    /// to the previous cycle.
+    #[derive(Debug, Clone)]
+    pub struct DependencyEdge {
+        pub from: usize,
+        pub to: usize,
+        pub block: MemoryBlock,
+        pub kind: DependencyKind,
+    }
+
+    fn escape(s: &str) -> String {
+        let mut esc = String::with_capacity(s.len());
+        for c in s.chars() {
+            match c {
+                '"' => esc.push_str("\\\""),
+                '\\' => esc.push_str("\\\\"),
+                '\n' => esc.push_str("\\n"),
+                c if (c as u32) < 0x20 => { write!(esc, "\\u{:04x}", c as u32).unwrap(); },
+                c => esc.push(c),
+            }
+        }
+        esc
+    }
+
+    impl ExecutionGraph {
+        /// Returns every buffer dependency between ordered commands. These
+        /// are the dependencies from which requisite events are determined
+        /// when the graph is locked.
+        pub fn dependency_edges(&self) -> Vec<DependencyEdge> {
+            let mem_block_rws = self.readers_and_writers_by_mem_block();
+            let mut edges = Vec::with_capacity(mem_block_rws.len() * 2);
+
+            for (block, rws) in mem_block_rws.iter() {
+                for &writer in rws.writers.iter() {
+                    for &reader in rws.readers.iter().filter(|&&r| r != writer) {
+                        edges.push(DependencyEdge { from: writer, to: reader, block: block.clone(),
+                            kind: DependencyKind::ReadAfterWrite });
+                        edges.push(DependencyEdge { from: reader, to: writer, block: block.clone(),
+                            kind: DependencyKind::WriteAfterRead });
+                    }
+                }
+            }
+
+            edges.sort_by(|a, b| (a.to, a.from, a.kind.as_str(), a.block.to_string())
+                .cmp(&(b.to, b.from, b.kind.as_str(), b.block.to_string())));
+            edges
+        }
+
+        /// Returns a short label for an ordered command.
+        fn cmd_label(&self, cmd_idx: usize) -> String {
+            let cmd_rel = self.cmd_relations(cmd_idx).expect("invalid command index");
+            match cmd_rel.kernel_name() {
+                "" => format!("[{}] {}", cmd_idx, cmd_rel.variant_string()),
+                name => format!("[{}] {}: {}", cmd_idx, cmd_rel.variant_string(), name),
+            }
+        }
+
+        /// Renders the ordered commands of this graph as a Graphviz DOT
+        /// digraph.
+        ///
+        /// Commands are nodes, annotated with their most recent and mean
+        /// device execution times when profiling is enabled (see
+        /// `::set_profiling`). Each buffer dependency is an edge labeled with
+        /// the memory block involved. Read-after-write edges are solid,
+        /// write-after-read edges dashed, and edges referring to the previous
+        /// cycle are gray.
+        pub fn to_dot(&self) -> String {
+            let mut dot = String::with_capacity(self.cmds.len() * 128);
+            dot.push_str("digraph execution_graph {\n");
+            dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+            dot.push_str("    edge [fontname=\"monospace\", fontsize=9];\n");
+
+            for (cmd_idx, cmd) in self.cmds.iter().enumerate() {
+                let mut label = self.cmd_label(cmd_idx);
+                if let (Some(last), Some(mean)) = (cmd.duration_ns(), cmd.mean_duration_ns()) {
+                    write!(label, "\n{:.3} us (mean: {:.3} us)", last as f64 / 1000.0,
+                        mean / 1000.0).unwrap();
+                }
+                writeln!(dot, "    cmd{} [label=\"{}\"];", cmd_idx, escape(&label)).unwrap();
+            }
+
+            for edge in self.dependency_edges() {
+                let mut attrs = format!("label=\"{}\"", escape(&edge.block.to_string()));
+                if edge.kind == DependencyKind::WriteAfterRead { attrs.push_str(", style=dashed"); }
+                if edge.from > edge.to { attrs.push_str(", color=gray, constraint=false"); }
+                writeln!(dot, "    cmd{} -> cmd{} [{}];", edge.from, edge.to, attrs).unwrap();
+            }
+
+            dot.push_str("}\n");
+            dot
+        }
+
+        /// Renders the ordered commands of this graph and their buffer
+        /// dependencies as JSON.
+        ///
+        /// `duration_ns` and `mean_duration_ns` are `null` unless profiling
+        /// is enabled and a command has completed at least once.
+        pub fn to_json(&self) -> String {
+            let mut json = String::with_capacity(self.cmds.len() * 256);
+            json.push_str("{\"commands\":[");
+
+            for (cmd_idx, cmd) in self.cmds.iter().enumerate() {
+                let cmd_rel = self.cmd_relations(cmd_idx).expect("invalid command index");
+                if cmd_idx > 0 { json.push(','); }
+                write!(json, "{{\"idx\":{},\"uid\":{},\"kind\":\"{}\",\"name\":\"{}\"",
+                    cmd_idx, cmd.uid().0, cmd_rel.variant_string(),
+                    escape(cmd_rel.kernel_name())).unwrap();
+
+                for &(key, ref blocks) in [("sources", cmd_rel.sources()),
+                        ("targets", cmd_rel.targets())].iter() {
+                    write!(json, ",\"{}\":[", key).unwrap();
+                    for (i, block) in blocks.iter().enumerate() {
+                        if i > 0 { json.push(','); }
+                        write!(json, "\"{}\"", escape(&block.to_string())).unwrap();
+                    }
+                    json.push(']');
+                }
+
+                write!(json, ",\"requisites\":{:?}", cmd.requisite_cmd_idxs()).unwrap();
+                match cmd.duration_ns() {
+                    Some(d) => write!(json, ",\"duration_ns\":{}", d).unwrap(),
+                    None => json.push_str(",\"duration_ns\":null"),
+                }
+                match cmd.mean_duration_ns() {
+                    Some(d) => write!(json, ",\"mean_duration_ns\":{:.1}", d).unwrap(),
+                    None => json.push_str(",\"mean_duration_ns\":null"),
+                }
+                json.push('}');
+            }
+
+            json.push_str("],\"edges\":[");
+
+            for (i, edge) in self.dependency_edges().iter().enumerate() {
+                if i > 0 { json.push(','); }
+                write!(json, "{{\"from\":{},\"to\":{},\"kind\":\"{}\",\"block\":\"{}\"}}",
+                    edge.from, edge.to, edge.kind.as_str(), escape(&edge.block.to_string())).unwrap();
+            }
+
+            json.push_str("]}");
+            json
+        }
+    }
+}
+
@@ -28,14 +28,15 @@ fn matching_source_layers<'a>(area_sch: &'a AreaScheme, area_sch_list: &'a AreaS
         {
             InputTrack::Afferent => {
                 area_sch.get_eff_areas().iter()
-                    .map(|&an| (an, None)).collect()
+                    .map(|an| (an.as_str(), None)).collect()
             },
             InputTrack::Efferent => {
                 area_sch.get_aff_areas().iter()
-                    .map(|&an| (an, None)).collect()
+                    .map(|an| (an.as_str(), None)).collect()
             },
             InputTrack::Other => {
-                area_sch.get_other_areas().clone()
+                area_sch.get_other_areas().iter()
+                    .map(|&(ref an, ref masqs)| (an.as_str(), masqs.clone())).collect()
             },
         };
 
@@ -53,7 +53,7 @@ impl AxonDomainCache {
 
 #[derive(Clone, Debug)]
 pub struct LayerMap {
-    area_name: &'static str,
+    area_name: String,
     area_id: usize,
     layers: MapStore<String, LayerInfo>,
     depth: u8,
@@ -89,7 +89,7 @@ impl LayerMap {
         }
 
         let lm = LayerMap {
-            area_name: area_sch.name(),
+            area_name: area_sch.name().to_owned(),
             area_id: area_sch.area_id(),
             layers: layers,
             depth: slc_total,
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use std::fmt::{self, Formatter};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 
 
 // # Misc L5 Notes:
@@ -240,6 +241,111 @@ impl fmt::Display for LayerTags {
     }
 }
 
+/// Names of the individual (non-composite) flags, used for (de)serialization.
+const FLAG_NAMES: &'static [(&'static str, LayerTags)] = &[
+    ("INPUT", LayerTags::INPUT),
+    ("OUTPUT", LayerTags::OUTPUT),
+    ("PRIMARY", LayerTags::PRIMARY),
+    ("SPATIAL", LayerTags::SPATIAL),
+    ("TEMPORAL", LayerTags::TEMPORAL),
+    ("FOCUS", LayerTags::FOCUS),
+    ("MOTOR", LayerTags::MOTOR),
+    ("UNUSED", LayerTags::UNUSED),
+];
+
+/// Prefix of a serialized unique id (e.g. `"UID:7"`).
+const UID_PREFIX: &'static str = "UID:";
+
+impl LayerTags {
+    /// Returns the flag (or composite flag, e.g. `"PSAL"`) with the name
+    /// `name` (case insensitive).
+    pub fn from_name(name: &str) -> Option<LayerTags> {
+        let name = name.to_uppercase();
+        match name.as_str() {
+            "DEFAULT" => Some(LayerTags::DEFAULT),
+            "PSAL" => Some(LayerTags::PSAL),
+            "PTAL" => Some(LayerTags::PTAL),
+            "PFL" => Some(LayerTags::PFL),
+            "PML" => Some(LayerTags::PML),
+            _ => FLAG_NAMES.iter().find(|&&(n, _)| n == name).map(|&(_, tags)| tags),
+        }
+    }
+
+    /// Returns the name of each flag contained in `self` followed by its
+    /// unique id (as `"UID:<id>"`) if non-zero.
+    ///
+    /// Returns `None` if `self` contains bits which are neither named flags
+    /// nor part of the unique id.
+    pub fn names(&self) -> Option<Vec<String>> {
+        let mut names = Vec::new();
+        let mut remaining = self.bits & !0xFFFFFFFF;
+        for &(name, flag) in FLAG_NAMES.iter() {
+            if self.contains(flag) {
+                names.push(name.to_owned());
+                remaining &= !flag.bits;
+            }
+        }
+        if remaining != 0 { return None; }
+        if self.get_uid() != 0 { names.push(format!("{}{}", UID_PREFIX, self.get_uid())); }
+        Some(names)
+    }
+
+    /// Parses a single name as returned by `::names`.
+    fn from_name_or_uid(name: &str) -> Option<LayerTags> {
+        if name.starts_with(UID_PREFIX) {
+            name[UID_PREFIX.len()..].parse::<u32>().ok().map(LayerTags::uid)
+        } else {
+            LayerTags::from_name(name)
+        }
+    }
+}
+
+// Serialized as a list of flag names (e.g. `["PRIMARY", "SPATIAL"]`) with any
+// unique id last (e.g. `"UID:7"`). Raw bits are accepted when deserializing.
+impl Serialize for LayerTags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeSeq};
+        let names = self.names().ok_or_else(|| S::Error::custom(format!(
+            "LayerTags: Unnamed flag bits: {:#x}", self.bits)))?;
+        let mut seq = serializer.serialize_seq(Some(names.len()))?;
+        for name in names.iter() {
+            seq.serialize_element(name)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for LayerTags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LayerTags, D::Error> {
+        use serde::de::{self, Visitor, SeqAccess};
+
+        struct LayerTagsVisitor;
+
+        impl<'de> Visitor<'de> for LayerTagsVisitor {
+            type Value = LayerTags;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a list of layer tag names")
+            }
+
+            fn visit_u64<E: de::Error>(self, bits: u64) -> Result<LayerTags, E> {
+                Ok(LayerTags::from_u64(bits))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<LayerTags, A::Error> {
+                let mut tags = LayerTags::DEFAULT;
+                while let Some(name) = seq.next_element::<String>()? {
+                    tags |= LayerTags::from_name_or_uid(&name).ok_or_else(|| {
+                        de::Error::custom(format!("Unknown layer tag: '{}'", name))
+                    })?;
+                }
+                Ok(tags)
+            }
+        }
+
+        deserializer.deserialize_any(LayerTagsVisitor)
+    }
+}
 
 
 #[cfg(test)]
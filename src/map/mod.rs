@@ -20,7 +20,8 @@ pub use self::syn_src::{SynSrcSlices, SynSrcIdxCache, SynSrc, gen_syn_offs};
 pub use self::slice_tract_map::SliceTractMap;
 pub use self::scheme::{LayerMapScheme, LayerMapSchemeList, AreaScheme, AreaSchemeList,
     TuftSourceLayer, TuftSourceLayerDefinition, TuftScheme, TuftSchemeDefinition, CellScheme,
-    CellSchemeDefinition, LayerScheme, LayerSchemeDefinition, FilterScheme, EncoderScheme, LayerKind};
+    CellSchemeDefinition, LayerScheme, LayerSchemeDefinition, FilterScheme, EncoderScheme, LayerKind,
+    SchemeConfig};
 pub use self::layer_tags::LayerTags;
 
 /////// FIXME: IMPORT MANUALLY:
@@ -78,13 +79,13 @@ impl fmt::Display for LayerAddress {
 // }
 
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataCellKind {
     Pyramidal,
     SpinyStellate,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ControlCellKind {
     InhibitoryBasketSurround { host_lyr_name: String, field_radius: u8  },
     ActivitySmoother { host_lyr_name: String, field_radius: u8 },
@@ -105,7 +106,7 @@ impl ControlCellKind {
 
 /// Roughly whether or not a cell is excitatory or inhibitory.
 //
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CellClass {
     /// Cells that directly contribute to the stream of information.
     Data(DataCellKind),
@@ -124,7 +125,7 @@ impl CellClass {
 }
 
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DendriteKind {
     Proximal,
     Distal,
@@ -132,7 +133,7 @@ pub enum DendriteKind {
 }
 
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DendriteClass {
     Apical,
     Basal,
@@ -140,7 +141,7 @@ pub enum DendriteClass {
 }
 
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LayerMapKind {
     // Associational,
     // Sensory,
@@ -154,7 +155,7 @@ pub enum LayerMapKind {
 /// currently stored within `SourceLayerInfo` anyway. * TODO: Figure out what
 /// to do with `LayerKind::Axonal(_)` in the output case.
 ///
-#[derive(Debug, Clone, Hash, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum AxonTopology {
     Spatial,
     Nonspatial,
@@ -162,7 +163,7 @@ pub enum AxonTopology {
 }
 
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InputTrack {
     Afferent,
     Efferent,
@@ -178,7 +179,7 @@ pub enum AxonDomainRoute {
 }
 
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AxonSignature {
     track: Option<InputTrack>,
     tags: AxonTags,
@@ -227,7 +228,7 @@ impl<A> From<A> for AxonSignature where A: Into<AxonTags> {
 
 
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AxonDomain {
     Input(Vec<AxonSignature>),
     Output(AxonSignature),
@@ -1,34 +1,42 @@
+use std::fmt;
 use std::ops::{Deref, Index, IndexMut};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::SerializeSeq;
+use serde::de::{Visitor, SeqAccess};
 use map::{FilterScheme, EncoderScheme, AxonTags, InputTrack};
-use cmn::{self, CorticalDims, MapStore};
+use cmn::{self, CmnResult, CorticalDims, MapStore};
 
 
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct AreaScheme {
+    #[serde(skip)]
     area_id: Option<usize>,
-    name: &'static str,
-    layer_map_name: &'static str,
+    name: String,
+    layer_map_name: String,
     dims: CorticalDims,
     encoder: EncoderScheme,
     filter_chains: Vec<(InputTrack, AxonTags, Vec<FilterScheme>)>,
-    aff_areas: Vec<&'static str>,
-    eff_areas: Vec<&'static str>,
+    // Determined from the efferent areas of all other areas when frozen:
+    #[serde(skip)]
+    aff_areas: Vec<String>,
+    eff_areas: Vec<String>,
     // (area name, list of optional axon tag masquerades (original, replacement))):
-    other_areas: Vec<(&'static str, Option<Vec<(AxonTags, AxonTags)>>)>
+    other_areas: Vec<(String, Option<Vec<(AxonTags, AxonTags)>>)>
 }
 
 impl AreaScheme {
-    pub fn new(name: &'static str, layer_map_name: &'static str, dim: u32) -> AreaScheme {
+    pub fn new<S, L>(name: S, layer_map_name: L, dim: u32) -> AreaScheme
+            where S: Into<String>, L: Into<String> {
         AreaScheme::irregular(name, layer_map_name, [dim, dim])
     }
 
-    pub fn irregular(name: &'static str, layer_map_name: &'static str, dims: [u32; 2])
-            -> AreaScheme {
+    pub fn irregular<S, L>(name: S, layer_map_name: L, dims: [u32; 2]) -> AreaScheme
+            where S: Into<String>, L: Into<String> {
         AreaScheme {
             area_id: None,
-            name: name,
-            layer_map_name: layer_map_name,
+            name: name.into(),
+            layer_map_name: layer_map_name.into(),
             dims: CorticalDims::new(0, dims[0], dims[1]),
             encoder: EncoderScheme::None,
             filter_chains: Vec::with_capacity(4),
@@ -82,13 +90,14 @@ impl AreaScheme {
         self
     }
 
-    pub fn eff_areas(mut self, eff_areas: Vec<&'static str>) -> AreaScheme {
-        self.eff_areas = eff_areas;
+    pub fn eff_areas<S: Into<String>>(mut self, eff_areas: Vec<S>) -> AreaScheme {
+        self.set_eff_areas(eff_areas);
         self
     }
 
-    pub fn other_area(mut self, area_name: &'static str, new_tags: Option<&[(AxonTags, AxonTags)]>)
+    pub fn other_area<S>(mut self, area_name: S, new_tags: Option<&[(AxonTags, AxonTags)]>)
             -> AreaScheme
+            where S: Into<String>
             // where A: Into<AxonTags> + Clone
     {
         let new_tags_owned = new_tags.map(|nt| {
@@ -100,7 +109,7 @@ impl AreaScheme {
                 .collect()
         });
 
-        self.other_areas.push((area_name, new_tags_owned));
+        self.other_areas.push((area_name.into(), new_tags_owned));
         self
     }
 
@@ -114,12 +123,12 @@ impl AreaScheme {
         self.filter_chains.push((input_track, axn_tags.into(), filter_chain.into()));
     }
 
-    pub fn set_eff_areas(&mut self, eff_areas: Vec<&'static str>) {
-        self.eff_areas = eff_areas;
+    pub fn set_eff_areas<S: Into<String>>(&mut self, eff_areas: Vec<S>) {
+        self.eff_areas = eff_areas.into_iter().map(|an| an.into()).collect();
     }
 
     #[inline]
-    pub fn get_other_areas(&self) -> &Vec<(&'static str, Option<Vec<(AxonTags, AxonTags)>>)> {
+    pub fn get_other_areas(&self) -> &Vec<(String, Option<Vec<(AxonTags, AxonTags)>>)> {
         &self.other_areas
     }
 
@@ -134,19 +143,22 @@ impl AreaScheme {
     }
 
     #[inline] pub fn area_id(&self) -> usize { self.area_id.expect("Area ID not set!") }
-    #[inline] pub fn name(&self) -> &'static str { self.name }
-    #[inline] pub fn layer_map_name(&self) -> &'static str { self.layer_map_name }
+    #[inline] pub fn name<'s>(&'s self) -> &'s str { &self.name }
+    #[inline] pub fn layer_map_name<'s>(&'s self) -> &'s str { &self.layer_map_name }
     #[inline] pub fn dims(&self) -> &CorticalDims { &self.dims }
     #[inline] pub fn get_encoder(&self) -> &EncoderScheme { &self.encoder }
-    #[inline] pub fn get_eff_areas(&self) -> &Vec<&'static str> { &self.eff_areas }
-    #[inline] pub fn get_aff_areas(&self) -> &Vec<&'static str> { &self.aff_areas }
+    #[inline] pub fn get_eff_areas(&self) -> &Vec<String> { &self.eff_areas }
+    #[inline] pub fn get_aff_areas(&self) -> &Vec<String> { &self.aff_areas }
 
 }
 
 
 
+/// A map of `AreaScheme`s indexed by area name.
+///
+/// Serializes as a sequence of area schemes.
 pub struct AreaSchemeList {
-    areas: MapStore<&'static str, AreaScheme>,
+    areas: MapStore<String, AreaScheme>,
     frozen: bool,
 }
 
@@ -157,9 +169,9 @@ impl <'a>AreaSchemeList {
 
     fn add(&mut self, mut protoarea: AreaScheme) {
         if self.frozen { panic!("AreaSchemeList is frozen."); }
-        let name = protoarea.name;
+        let name = protoarea.name.clone();
         protoarea.area_id = Some(self.areas.len());
-        self.areas.insert(name, protoarea)
+        self.areas.insert(name.clone(), protoarea)
             .map(|_| panic!("AreaScheme::add(): Duplicate areas: (area: \"{}\")", name));
     }
 
@@ -168,27 +180,40 @@ impl <'a>AreaSchemeList {
         self
     }
 
-    pub fn freeze(&mut self) {
-        let mut aff_list: Vec<(&'static str, &'static str)> = Vec::with_capacity(5);
+    /// Determines the afferent areas of each area from the efferent areas
+    /// of every other area and prevents further additions.
+    ///
+    /// Returns an error if an efferent area does not exist or if there are
+    /// too many afferent areas.
+    pub fn freeze(&mut self) -> CmnResult<()> {
+        let mut aff_list: Vec<(String, String)> = Vec::with_capacity(5);
 
         for (area_id, area) in self.areas.values().iter().enumerate() {
             assert!(area.area_id() == area_id);
 
             for eff_area_name in &area.eff_areas {
-                aff_list.push((eff_area_name, area.name()));
+                if self.areas.by_key(eff_area_name.as_str()).is_none() {
+                    return Err(format!("AreaSchemeList::freeze: Efferent area: '{}' (of \
+                        area: '{}') not found.", eff_area_name, area.name).into());
+                }
+                aff_list.push((eff_area_name.clone(), area.name.clone()));
             }
         }
 
-        assert!(aff_list.len() <= cmn::MAX_FEEDFORWARD_AREAS, "areas::AreaSchemeList::freeze(): \
-                An area cannot have more than {} afferent areas.", cmn::MAX_FEEDFORWARD_AREAS);
+        if aff_list.len() > cmn::MAX_FEEDFORWARD_AREAS {
+            return Err(format!("AreaSchemeList::freeze: An area cannot have more than {} \
+                afferent areas.", cmn::MAX_FEEDFORWARD_AREAS).into());
+        }
 
         for (area_name, aff_area_name) in aff_list {
-            let emsg = format!("map::areas::AreaSchemeList::freeze(): Area: '{}' not found. ", area_name);
-            self.areas.by_key_mut(&area_name).expect(&emsg).aff_areas.push(aff_area_name);
+            if let Some(area) = self.areas.by_key_mut(area_name.as_str()) {
+                area.aff_areas.push(aff_area_name);
+            }
         }
 
         self.areas.shrink_to_fit();
         self.frozen = true;
+        Ok(())
     }
 
     pub fn get_area_by_key(&self, area_name: &str) -> Option<&AreaScheme> {
@@ -199,13 +224,51 @@ impl <'a>AreaSchemeList {
 }
 
 impl Deref for AreaSchemeList {
-    type Target = MapStore<&'static str, AreaScheme>;
+    type Target = MapStore<String, AreaScheme>;
 
-    fn deref(&self) -> &MapStore<&'static str, AreaScheme> {
+    fn deref(&self) -> &MapStore<String, AreaScheme> {
         &self.areas
     }
 }
 
+impl Serialize for AreaSchemeList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.areas.len()))?;
+        for area in self.areas.values() {
+            seq.serialize_element(area)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for AreaSchemeList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<AreaSchemeList, D::Error> {
+        struct AreaSchemeListVisitor;
+
+        impl<'de> Visitor<'de> for AreaSchemeListVisitor {
+            type Value = AreaSchemeList;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of area schemes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<AreaSchemeList, A::Error> {
+                let mut list = AreaSchemeList::new();
+                while let Some(area) = seq.next_element::<AreaScheme>()? {
+                    if list.areas.by_key(area.name()).is_some() {
+                        return Err(::serde::de::Error::custom(format!("duplicate area: '{}'",
+                            area.name())));
+                    }
+                    list.add(area);
+                }
+                Ok(list)
+            }
+        }
+
+        deserializer.deserialize_seq(AreaSchemeListVisitor)
+    }
+}
+
 impl<'b> Index<&'b str> for AreaSchemeList {
     type Output = AreaScheme;
 
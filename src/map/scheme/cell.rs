@@ -1,3 +1,4 @@
+use serde::{Deserialize, Deserializer};
 use map::{CellClass, LayerKind, DendriteClass, DendriteKind, DataCellKind, ControlCellKind};
 use SrcOfs;
 // use cmn;
@@ -11,7 +12,7 @@ use SrcOfs;
 /// the `5` will be five times more likely to form a synapse during
 /// regrowth/growth.
 ///
-#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+#[derive(PartialEq, Debug, Clone, Eq, Hash, Serialize, Deserialize)]
 pub struct TuftSourceLayer {
     name: String,
     syn_reach: SrcOfs,
@@ -51,6 +52,7 @@ impl<'a> From<&'a (&'a str, SrcOfs, u8)> for TuftSourceLayer {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct TuftSourceLayerDefinition {
     name: String,
     syn_reach: Option<SrcOfs>,
@@ -93,8 +95,10 @@ impl TuftSourceLayerDefinition {
 }
 
 
-#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+#[derive(PartialEq, Debug, Clone, Eq, Hash, Serialize, Deserialize)]
 pub struct TuftScheme {
+    // Assigned by the containing `CellScheme`:
+    #[serde(skip)]
     tft_id: usize,
     den_class: DendriteClass,
     den_kind: DendriteKind,
@@ -151,6 +155,7 @@ impl TuftScheme {
 }
 
 
+#[derive(Clone, Debug)]
 pub struct TuftSchemeDefinition {
     den_class: Option<DendriteClass>,
     den_kind: Option<DendriteKind>,
@@ -265,12 +270,42 @@ impl TuftSchemeDefinition {
 
 
 
-#[derive(PartialEq, Debug, Clone, Eq, Hash)]
+#[derive(PartialEq, Debug, Clone, Eq, Hash, Serialize)]
 pub struct CellScheme {
     cell_class: CellClass,
     tft_schemes: Vec<TuftScheme>,
 }
 
+impl<'de> Deserialize<'de> for CellScheme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CellScheme, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "CellScheme")]
+        struct CellSchemeRepr {
+            cell_class: CellClass,
+            tft_schemes: Vec<TuftScheme>,
+        }
+
+        let repr = CellSchemeRepr::deserialize(deserializer)?;
+        let mut tft_schemes = Vec::with_capacity(repr.tft_schemes.len());
+
+        // Tuft ids are assigned in order:
+        for (tft_id, mut tft_scheme) in repr.tft_schemes.into_iter().enumerate() {
+            for src_lyr in tft_scheme.src_lyrs.iter() {
+                if src_lyr.prevalence == 0 || src_lyr.syn_reach < 0 {
+                    return Err(<D::Error as ::serde::de::Error>::custom(format!("invalid tuft \
+                        source layer: {{ name: {}, reach: {}, prevalence: {} }} (reach must not \
+                        be negative and prevalence must be at least one)", src_lyr.name,
+                        src_lyr.syn_reach, src_lyr.prevalence)));
+                }
+            }
+            tft_scheme.tft_id = tft_id;
+            tft_schemes.push(tft_scheme);
+        }
+
+        Ok(CellScheme { cell_class: repr.cell_class, tft_schemes })
+    }
+}
+
 impl CellScheme {
     pub fn new(cell_class: CellClass, tft_schemes: Vec<TuftScheme>) -> CellScheme {
         // DO SOME CHECKS ON PARAMETERS (certain cell types must/mustn't have certain dendritic segments)
@@ -428,6 +463,7 @@ impl CellScheme {
 }
 
 
+#[derive(Clone, Debug)]
 pub struct CellSchemeDefinition {
     cell_class: CellClass,
     tft_schemes: Vec<TuftScheme>,
@@ -0,0 +1,65 @@
+//! Loading and saving network topologies from and to a human-editable
+//! config file (RON: https://github.com/ron-rs/ron).
+//!
+//! Layer ids, tuft ids, area ids, and afferent area lists are not stored and
+//! are determined by the order in which they are listed and by the listed
+//! efferent areas.
+//!
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use ron;
+use cmn::CmnResult;
+use map::{LayerMapSchemeList, AreaSchemeList};
+
+
+/// A complete network topology: a list of layer map schemes and a list of
+/// area schemes.
+#[derive(Serialize, Deserialize)]
+pub struct SchemeConfig {
+    layer_map_schemes: LayerMapSchemeList,
+    area_schemes: AreaSchemeList,
+}
+
+impl SchemeConfig {
+    pub fn new(layer_map_schemes: LayerMapSchemeList, area_schemes: AreaSchemeList)
+            -> SchemeConfig {
+        SchemeConfig { layer_map_schemes, area_schemes }
+    }
+
+    /// Parses a config from a RON string.
+    pub fn from_ron_str(config: &str) -> CmnResult<SchemeConfig> {
+        ron::de::from_str(config)
+            .map_err(|err| format!("SchemeConfig::from_ron_str: {}", err).into())
+    }
+
+    /// Returns this config as a pretty-printed RON string.
+    pub fn to_ron_string(&self) -> CmnResult<String> {
+        ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| format!("SchemeConfig::to_ron_string: {}", err).into())
+    }
+
+    /// Loads a config from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> CmnResult<SchemeConfig> {
+        let mut config = String::new();
+        File::open(path.as_ref())?.read_to_string(&mut config)?;
+        SchemeConfig::from_ron_str(&config)
+            .map_err(|err| err.prepend(format!("{}: ", path.as_ref().display())))
+    }
+
+    /// Saves this config to the file at `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> CmnResult<()> {
+        let config = self.to_ron_string()?;
+        File::create(path)?.write_all(config.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the layer map and area scheme lists.
+    pub fn into_parts(self) -> (LayerMapSchemeList, AreaSchemeList) {
+        (self.layer_map_schemes, self.area_schemes)
+    }
+
+    #[inline] pub fn layer_map_schemes(&self) -> &LayerMapSchemeList { &self.layer_map_schemes }
+    #[inline] pub fn area_schemes(&self) -> &AreaSchemeList { &self.area_schemes }
+}
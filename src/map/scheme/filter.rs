@@ -1,5 +1,5 @@
 
-#[derive(PartialEq, Debug, Clone, Eq)]
+#[derive(PartialEq, Debug, Clone, Eq, Serialize, Deserialize)]
 pub struct FilterScheme {
     filter_name: String,
     cl_file_name: Option<String>,
@@ -1,5 +1,5 @@
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub enum EncoderScheme {
     None,
     Subcortex,
@@ -12,6 +12,45 @@ pub enum EncoderScheme {
     // IdxStreamerLoop { file_name: String, cyc_per: usize, scale: f32, loop_frames: u32 },
     GlyphSequences { seq_lens: (usize, usize), seq_count: usize, scale: f32, hrz_dims: (u32, u32) },
     SensoryTract,
+    /// Replays the axon frames of a recording file (see `Recorder`), one
+    /// recorded cycle every `cycles_per_frame` cycles.
+    Replay {
+        file: String,
+        #[serde(rename = "loop")]
+        looped: bool,
+        cycles_per_frame: usize,
+    },
+    /// Streams WAV files as tonotopic activity: one frequency band (within
+    /// `freq_range`, in Hz) per row along v with intensity along u.
+    WavAudio {
+        file_names: Vec<String>,
+        freq_range: (f32, f32),
+        window_len: usize,
+        hop_len: usize,
+        cycles_per_frame: usize,
+        loop_files: bool,
+    },
+    /// Streams image files (PNG, PGM or PPM), or directories of them,
+    /// resampled onto the hex grid. Per-channel (non-grayscale) encoding
+    /// requires three layers (red, green, and blue).
+    ImageDir {
+        paths: Vec<String>,
+        grayscale: bool,
+        scale: f32,
+        cycles_per_frame: usize,
+        loop_images: bool,
+    },
+    /// Streams the characters (or words) of a text file, each symbol
+    /// encoded as a stable sparse representation (see `TextSettings`).
+    TextSequence {
+        file_name: String,
+        words: bool,
+        sparsity: f32,
+        overlap: f32,
+        max_vocab: usize,
+        cycles_per_symbol: usize,
+        sentence_reset_cycles: usize,
+    },
 
     // Possibly remove me eventually:
     ScalarSequence { range: (f32, f32), incr: f32 },
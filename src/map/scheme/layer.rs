@@ -5,7 +5,7 @@ use map::{LayerTags, AxonTopology, AxonDomain, CellScheme, CellSchemeDefinition}
 // input layer topology matters and since cellular layers are assigned
 // `AxonTopology::Spatial`.
 //
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LayerKind {
     Cellular(CellScheme),
     Axonal(AxonTopology),
@@ -28,8 +28,10 @@ impl LayerKind {
 }
 
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LayerScheme {
+    // Assigned by the containing `LayerMapScheme`:
+    #[serde(skip)]
     layer_id: usize,
     name: String,
     kind: LayerKind,
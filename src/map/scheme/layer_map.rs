@@ -1,9 +1,17 @@
+use std::fmt;
 use std::ops::{Index, IndexMut, Deref};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::{SerializeSeq, SerializeStruct};
+use serde::de::{Visitor, SeqAccess};
 use cmn::MapStore;
 use map::{LayerTags, LayerMapKind, LayerScheme, LayerSchemeDefinition, AxonDomain, AxonTags};
 
 
 
+/// A named list of layer schemes.
+///
+/// Layer ids are not serialized and are instead assigned according to the
+/// order in which layers are listed.
 #[derive(Debug, Clone)]
 pub struct LayerMapScheme {
     name: String,
@@ -80,6 +88,20 @@ impl LayerMapScheme {
                 (layer: \"{}\").", ls.name()));
     }
 
+    /// Adds an already assembled layer scheme, reassigning its layer id.
+    ///
+    /// Returns an error if a layer with the same name already exists.
+    fn add_layer_scheme(&mut self, ls: LayerScheme) -> Result<(), String> {
+        if self.layers.by_key(ls.name()).is_some() {
+            return Err(format!("duplicate layer name: '{}'", ls.name()));
+        }
+        let layer_id = self.layers.len();
+        let layer = LayerScheme::new(layer_id, ls.name(), ls.kind().clone(), ls.depth(),
+            ls.tags(), ls.axon_domain().clone());
+        self.layers.insert(layer.name().to_owned(), layer);
+        Ok(())
+    }
+
     /// Returns all layers containing 'tags'.
     pub fn layers_with_layer_tags(&self, layer_tags: LayerTags) -> Vec<&LayerScheme> {
         let mut layers: Vec<&LayerScheme> = Vec::with_capacity(16);
@@ -126,6 +148,36 @@ impl LayerMapScheme {
     }
 }
 
+impl Serialize for LayerMapScheme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut st = serializer.serialize_struct("LayerMapScheme", 3)?;
+        st.serialize_field("name", &self.name)?;
+        st.serialize_field("kind", &self.kind)?;
+        st.serialize_field("layers", self.layers.values())?;
+        st.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for LayerMapScheme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LayerMapScheme, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "LayerMapScheme")]
+        struct LayerMapSchemeRepr {
+            name: String,
+            kind: LayerMapKind,
+            layers: Vec<LayerScheme>,
+        }
+
+        let repr = LayerMapSchemeRepr::deserialize(deserializer)?;
+        let mut lm_scheme = LayerMapScheme::new(repr.name, repr.kind);
+
+        for ls in repr.layers {
+            lm_scheme.add_layer_scheme(ls).map_err(<D::Error as ::serde::de::Error>::custom)?;
+        }
+        Ok(lm_scheme)
+    }
+}
+
 // impl<'b> Index<&'b&'static str> for LayerMapScheme
 // {
 //     type Output = LayerScheme;
@@ -145,6 +197,8 @@ impl LayerMapScheme {
 
 
 /// A map of `LayerMapScheme`s indexed by layer map name.
+///
+/// Serializes as a sequence of layer map schemes.
 pub struct LayerMapSchemeList {
     schemes: MapStore<String, LayerMapScheme>,
 }
@@ -190,4 +244,43 @@ impl Deref for LayerMapSchemeList {
     fn deref(&self) -> &MapStore<String, LayerMapScheme> {
         &self.schemes
     }
-}
\ No newline at end of file
+}
+
+impl Serialize for LayerMapSchemeList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.schemes.len()))?;
+        for lm_scheme in self.schemes.values() {
+            seq.serialize_element(lm_scheme)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for LayerMapSchemeList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LayerMapSchemeList, D::Error> {
+        struct LayerMapSchemeListVisitor;
+
+        impl<'de> Visitor<'de> for LayerMapSchemeListVisitor {
+            type Value = LayerMapSchemeList;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of layer map schemes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A)
+                    -> Result<LayerMapSchemeList, A::Error> {
+                let mut list = LayerMapSchemeList::new();
+                while let Some(lm_scheme) = seq.next_element::<LayerMapScheme>()? {
+                    if list.schemes.by_key(lm_scheme.name()).is_some() {
+                        return Err(::serde::de::Error::custom(format!("duplicate layer map: \
+                            '{}'", lm_scheme.name())));
+                    }
+                    list.add(lm_scheme);
+                }
+                Ok(list)
+            }
+        }
+
+        deserializer.deserialize_seq(LayerMapSchemeListVisitor)
+    }
+}
@@ -10,6 +10,7 @@ mod area;
 mod layer;
 mod cell;
 mod input;
+mod config;
 
 pub use self::layer_map::{LayerMapSchemeList, LayerMapScheme};
 
@@ -23,3 +24,5 @@ pub use self::cell::{TuftSourceLayer, TuftSourceLayerDefinition, TuftScheme, Tuf
 pub use self::filter::{FilterScheme};
 
 pub use self::input::{EncoderScheme};
+
+pub use self::config::SchemeConfig;
@@ -93,6 +93,10 @@ impl SliceTractMap {
     pub fn tags_reversed(&self) -> Vec<String> {
         self.tags.iter().rev().map(|t| t.clone()).collect()
     }
+
+    #[inline] pub fn tags(&self) -> &[String] { &self.tags }
+    #[inline] pub fn v_sizes(&self) -> &[u32] { &self.v_sizes }
+    #[inline] pub fn u_sizes(&self) -> &[u32] { &self.u_sizes }
 }
 
 impl fmt::Display for SliceTractMap {
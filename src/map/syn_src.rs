@@ -6,12 +6,9 @@ use rand::rngs::SmallRng;
 use cortex::TuftDims;
 use cmn::{self, CmnError, CmnResult, CorticalDims, SliceDims};
 use map::{AreaMap, AxonTopology, TuftScheme};
+use cortex::host_kernels::SYN_SRC_POOL_ENTRY_LEN;
 use SrcOfs;
 
-const INTENSITY_REDUCTION_L2: i8 = 3;
-const STR_MIN: i8 = -3;
-const STR_MAX: i8 = 4;
-
 
 /// Tests to ensure a list of synapse source offsets has a balanced set.
 ///
@@ -203,6 +200,22 @@ impl SynSrcIdxCache {
         is_unique || self.source_saturated
     }
 
+    /// Removes every cached source offset.
+    pub fn clear(&mut self) {
+        for den in self.dens.iter_mut() {
+            den.clear();
+        }
+    }
+
+    /// Caches the source offset of an existing synapse without removing any
+    /// previous value. Used to repopulate the cache from restored state.
+    pub fn insert_existing(&mut self, syn_idx: usize, src: &SynSrc) {
+        let syn_id_tft = syn_idx - self.tft_syn_idz;
+        let den_id_tft = syn_id_tft / self.tft_dims.syns_per_den() as usize;
+        let ofs_key: i32 = self.axon_ofs(src);
+        self.dens[den_id_tft].insert(ofs_key);
+    }
+
     fn axon_ofs(&self, axon_ofs: &SynSrc) -> i32 {
         (axon_ofs.slc_id as i32 * self.dims.columns() as i32) +
             (axon_ofs.v_ofs as i32 * self.dims.u_size() as i32) +
@@ -349,6 +362,47 @@ impl SynSrcSlice {
     pub fn is_saturated(&self) -> bool {
         self.is_saturated
     }
+
+    /// Returns the source pool of this tuft flattened for use by the
+    /// `tft_regrow_syns` kernel.
+    ///
+    /// The first vector contains one entry of `SYN_SRC_POOL_ENTRY_LEN` values
+    /// (`[slc_id, ofs_idz, ofs_len, v_reach, u_reach]`) per element of
+    /// `::id_pools`. The second contains the offsets of every spatial source
+    /// slice as `(v, u)` pairs, indexed by `ofs_idz..(ofs_idz + ofs_len)`.
+    /// Nonspatial slices have an `ofs_len` of zero and use their reaches as
+    /// offset bounds instead.
+    pub fn regrowth_pool(&self) -> (Vec<i32>, Vec<SrcOfs>) {
+        let mut pool = Vec::with_capacity(self.id_pools.len() * SYN_SRC_POOL_ENTRY_LEN);
+        let mut offs = Vec::new();
+        let mut ofs_idzs_by_slc = BTreeMap::new();
+
+        for &slc_id in self.id_pools.iter() {
+            let slc_info = &self.info_by_slc[&slc_id];
+
+            match slc_info.slc_off_pool {
+                OfsPool::Nonspatial(..) => {
+                    pool.extend_from_slice(&[slc_id as i32, 0, 0, (slc_info.v_size / 2) as i32,
+                        (slc_info.u_size / 2) as i32]);
+                },
+                OfsPool::Spatial { offs: ref slc_offs, .. } => {
+                    let ofs_idz = *ofs_idzs_by_slc.entry(slc_id).or_insert_with(|| {
+                        let ofs_idz = offs.len() / 2;
+                        for &(v_ofs, u_ofs) in slc_offs.iter() {
+                            offs.push(v_ofs);
+                            offs.push(u_ofs);
+                        }
+                        ofs_idz
+                    });
+                    let syn_reaches = slc_info.scaled_syn_reaches();
+                    pool.extend_from_slice(&[slc_id as i32, ofs_idz as i32, slc_offs.len() as i32,
+                        syn_reaches.0 as i32, syn_reaches.1 as i32]);
+                },
+            }
+        }
+
+        (pool, offs)
+    }
 }
 
 
@@ -423,7 +477,7 @@ impl SynSrcSlices {
             //     is_saturated, poss_syn_offs_val_count, syns_per_den);
 
             let id_pool_ranges = RandRange::new(0, id_pools.len());
-            let str_ranges = RandRange::new(STR_MIN, STR_MAX);
+            let str_ranges = str_ranges(cmn::SYNAPSE_STRENGTH_INITIAL_DEVIATION);
 
             slices_by_tft.push(SynSrcSlice {
                 info_by_slc,
@@ -475,7 +529,8 @@ impl SynSrcSlices {
                 let syn_reaches = slc_info.scaled_syn_reaches();
 
                 let syn_str_intensity = (((syn_reaches.0 as i32 - v_ofs.abs() as i32) +
-                        (syn_reaches.1 as i32 - u_ofs.abs() as i32)) >> INTENSITY_REDUCTION_L2) as i8;
+                        (syn_reaches.1 as i32 - u_ofs.abs() as i32)) >>
+                    cmn::SYNAPSE_STRENGTH_INTENSITY_REDUCTION_L2) as i8;
 
                 let strength = syn_str_intensity * src_slices.str_ranges.sample(rng);
 
@@ -489,6 +544,14 @@ impl SynSrcSlices {
         }
     }
 
+    /// Sets the deviation of the strength factor used for newly generated
+    /// spatial sources (see `LearningParams::syn_strength_initial_deviation`).
+    pub fn set_str_deviation(&mut self, deviation: i8) {
+        for src_slices in self.slices_by_tft.iter_mut() {
+            src_slices.str_ranges = str_ranges(deviation);
+        }
+    }
+
     #[inline]
     pub fn by_tft(&self) -> &[SynSrcSlice] {
         &self.slices_by_tft
@@ -496,3 +559,10 @@ impl SynSrcSlices {
 }
 
 
+/// Returns the range of strength factors, `[-deviation, deviation]`.
+fn str_ranges(deviation: i8) -> RandRange<i8> {
+    debug_assert!(deviation >= 0 && deviation < ::std::i8::MAX);
+    RandRange::new(-deviation, deviation + 1)
+}
+
+
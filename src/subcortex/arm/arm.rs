@@ -1,11 +1,288 @@
+//! A motor output nucleus.
+//!
+//! Reads the axons of a designated cortical motor layer through a thalamic
+//! output pathway and decodes them into `MotorFrame`s.
+//!
+//!
+//! ### Conventions
+//!
+//! Columns are laid out on the same hex grid used by `Vector2dWriter`:
+//!
+//! x: 0˚, y: 90˚
+//! u: 330˚ (-30˚), v: 90˚
+//!
+//! Each column's preferred direction points from the center of the layer
+//! toward that column.
+//!
 
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::Sender;
+use futures::Never;
+use futures::future::FutureExt;
+use cmn::{CmnError, CmnResult, TractDims};
+use map::{LayerAddress, AreaSchemeList};
+use cortex::{CompletionPool, CorticalAreas};
+use subcortex::{Thalamus, SubcorticalNucleus, SubcorticalNucleusLayer, TractReceiver};
+use flywheel::MotorFrame;
 
-// * TODO: Take output data from cortex and encode into desired output format.
 
-pub struct Arm<T> {
-    tract: T,
+/// Converts a (v, u) hex offset into (x, y) coordinates.
+///
+/// x: 0˚, y: 90˚
+/// u: 330˚ (-30˚), v: 90˚
+///
+#[inline]
+fn hex_to_xy(v: f64, u: f64) -> [f64; 2] {
+    [u * (3.0f64).sqrt() / 2.0, v - (u / 2.0)]
 }
 
-impl<T> Arm<T> {
 
-}
\ No newline at end of file
+/// The way in which motor layer activity is decoded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MotorDecoding {
+    /// Population-vector decoding yielding a `MotorFrame::Vector2d`.
+    Vector2d,
+    /// Winner-take-all over the specified number of equally sized angular
+    /// sectors yielding a `MotorFrame::Discrete`.
+    Discrete(usize),
+}
+
+
+/// Decodes motor layer axon states into `MotorFrame`s.
+#[derive(Clone, Debug)]
+pub struct MotorDecoder {
+    lyr_addr: LayerAddress,
+    dims: TractDims,
+    decoding: MotorDecoding,
+    // Unit preferred direction of each column (zero for the center column):
+    pref_dirs: Vec<[f64; 2]>,
+}
+
+impl MotorDecoder {
+    /// Returns a new `MotorDecoder` for a layer with dimensions, `dims`.
+    pub fn new<Td: Into<TractDims>>(lyr_addr: LayerAddress, dims: Td, decoding: MotorDecoding)
+            -> CmnResult<MotorDecoder> {
+        let dims = dims.into();
+        if let MotorDecoding::Discrete(0) = decoding {
+            return Err("MotorDecoder::new: Discrete action count must be non-zero.".into());
+        }
+
+        let v_mid = (dims.v_size() as f64 - 1.) / 2.;
+        let u_mid = (dims.u_size() as f64 - 1.) / 2.;
+        let mut pref_dirs = Vec::with_capacity((dims.v_size() * dims.u_size()) as usize);
+
+        for v_id in 0..dims.v_size() {
+            for u_id in 0..dims.u_size() {
+                let xy = hex_to_xy(v_id as f64 - v_mid, u_id as f64 - u_mid);
+                let mag = (xy[0].powi(2) + xy[1].powi(2)).sqrt();
+                if mag > 0. {
+                    pref_dirs.push([xy[0] / mag, xy[1] / mag]);
+                } else {
+                    pref_dirs.push([0., 0.]);
+                }
+            }
+        }
+
+        Ok(MotorDecoder { lyr_addr, dims, decoding, pref_dirs })
+    }
+
+    /// Sums the activity of each column over all slices.
+    fn column_activities(&self, axns: &[u8]) -> CmnResult<Vec<u32>> {
+        if axns.len() != self.dims.to_len() {
+            return Err(format!("MotorDecoder::decode: Invalid axon slice length (expected: {}, \
+                found: {}).", self.dims.to_len(), axns.len()).into());
+        }
+        let mut activities = vec![0u32; self.pref_dirs.len()];
+
+        for slc_axns in axns.chunks(self.pref_dirs.len()) {
+            for (activity, &axn) in activities.iter_mut().zip(slc_axns.iter()) {
+                *activity += axn as u32;
+            }
+        }
+        Ok(activities)
+    }
+
+    /// Decodes the axon states of a motor layer into a `MotorFrame`.
+    pub fn decode(&self, axns: &[u8]) -> CmnResult<MotorFrame> {
+        let activities = self.column_activities(axns)?;
+
+        let frame = match self.decoding {
+            MotorDecoding::Vector2d => {
+                let mut sum = [0., 0.];
+                let mut ttl = 0.;
+                for (dir, &activity) in self.pref_dirs.iter().zip(activities.iter()) {
+                    sum[0] += dir[0] * activity as f64;
+                    sum[1] += dir[1] * activity as f64;
+                    ttl += activity as f64;
+                }
+
+                let xy = if ttl > 0. { [sum[0] / ttl, sum[1] / ttl] } else { [0., 0.] };
+                MotorFrame::Vector2d { lyr_addr: self.lyr_addr, xy }
+            },
+            MotorDecoding::Discrete(action_count) => {
+                let sector_size = (2. * PI) / action_count as f64;
+                let mut sector_activities = vec![0u64; action_count];
+
+                for (dir, &activity) in self.pref_dirs.iter().zip(activities.iter()) {
+                    // The center column has no preferred direction:
+                    if dir[0] == 0. && dir[1] == 0. { continue; }
+                    let mut angle = dir[1].atan2(dir[0]);
+                    if angle < 0. { angle += 2. * PI; }
+                    let sector = ((angle / sector_size) as usize).min(action_count - 1);
+                    sector_activities[sector] += activity as u64;
+                }
+
+                // The first sector with the greatest activity wins:
+                let mut action = None;
+                let mut best = 0;
+                for (sector, &activity) in sector_activities.iter().enumerate() {
+                    if activity > best {
+                        best = activity;
+                        action = Some(sector);
+                    }
+                }
+                MotorFrame::Discrete { lyr_addr: self.lyr_addr, action }
+            },
+        };
+        Ok(frame)
+    }
+
+    pub fn lyr_addr(&self) -> LayerAddress { self.lyr_addr }
+    pub fn dims(&self) -> &TractDims { &self.dims }
+    pub fn decoding(&self) -> &MotorDecoding { &self.decoding }
+}
+
+
+/// A motor output nucleus.
+///
+/// Decodes the designated motor layer once per cycle and sends the resulting
+/// `MotorFrame` to `motor_tx` (typically connected to a `Flywheel` using
+/// `Flywheel::add_motor_rx`). Decoding is done on the completion pool so
+/// frames may arrive after the cycle which produced them has completed. Any
+/// error encountered while reading or decoding is returned from the next
+/// call to `::post_cycle`.
+///
+pub struct Arm {
+    area_name: String,
+    area_id: usize,
+    motor_area_name: String,
+    motor_layer_name: String,
+    decoding: MotorDecoding,
+    decoder: Option<Arc<MotorDecoder>>,
+    pathway: Option<TractReceiver>,
+    motor_tx: Sender<MotorFrame>,
+    error: Arc<Mutex<Option<CmnError>>>,
+}
+
+impl Arm {
+    /// Returns a new `Arm`, for the area named `area_name` within
+    /// `area_schemes`, which will decode the layer named `motor_layer_name`
+    /// within the area named `motor_area_name`.
+    pub fn new<S1, S2>(area_schemes: &AreaSchemeList, area_name: &str, motor_area_name: S1,
+            motor_layer_name: S2, decoding: MotorDecoding, motor_tx: Sender<MotorFrame>)
+            -> CmnResult<Arm>
+            where S1: Into<String>, S2: Into<String> {
+        let area_id = area_schemes.get_area_by_key(area_name)
+            .ok_or_else(|| CmnError::new(format!("Arm::new: No area named '{}' found.",
+                area_name)))?
+            .area_id();
+
+        Ok(Arm {
+            area_name: area_name.to_owned(),
+            area_id,
+            motor_area_name: motor_area_name.into(),
+            motor_layer_name: motor_layer_name.into(),
+            decoding,
+            decoder: None,
+            pathway: None,
+            motor_tx,
+            error: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Returns the decoder, available once pathways have been created.
+    pub fn decoder(&self) -> Option<&MotorDecoder> {
+        self.decoder.as_ref().map(|d| &**d)
+    }
+}
+
+impl SubcorticalNucleus for Arm {
+    fn create_pathways(&mut self, thal: &mut Thalamus,
+            _cortical_areas: &mut CorticalAreas) -> CmnResult<()> {
+        let lyr_addr = thal.area_maps().by_key(self.motor_area_name.as_str())
+            .and_then(|area_map| area_map.layer_map().layers().by_key(self.motor_layer_name.as_str()))
+            .map(|layer| layer.layer_addr())
+            .ok_or_else(|| CmnError::new(format!("Arm::create_pathways: The motor layer, '{}' \
+                (area: '{}'), does not exist.", self.motor_layer_name, self.motor_area_name)))?;
+        let tract_area_id = thal.tract().index_of(&lyr_addr)
+            .ok_or(format!("Arm::create_pathways: The motor layer, '{}' (area: '{}'), \
+                is not an output layer.", self.motor_layer_name, self.motor_area_name))?;
+        let dims = *thal.tract().dims(tract_area_id)?;
+
+        self.decoder = Some(Arc::new(MotorDecoder::new(lyr_addr, dims, self.decoding.clone())?));
+        self.pathway = Some(thal.output_pathway(lyr_addr));
+        Ok(())
+    }
+
+    fn pre_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
+            _completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        Ok(())
+    }
+
+    /// Post-cycle:
+    ///
+    /// * Queues a read of the motor layer's tract area which decodes the
+    ///   axon states and sends the resulting `MotorFrame`.
+    ///
+    fn post_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
+            completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        if let Some(err) = self.error.lock()
+                .map_err(|_| CmnError::from("Arm::post_cycle: Error slot poisoned."))?.take() {
+            return Err(format!("Arm::post_cycle: Error decoding a previous cycle: {}", err).into());
+        }
+
+        let (pathway, decoder) = match (self.pathway.as_ref(), self.decoder.as_ref()) {
+            (Some(p), Some(d)) => (p, d.clone()),
+            _ => return Err("Arm::post_cycle: Pathways have not been created.".into()),
+        };
+
+        let read_buffer = match pathway.recv(false).try_recv() {
+            Ok(Some(Some(rb))) => rb,
+            // No fresh frame is available:
+            Ok(Some(None)) | Ok(None) => return Ok(()),
+            Err(err) => return Err(format!("Arm::post_cycle: {}", err).into()),
+        };
+
+        let motor_tx = self.motor_tx.clone();
+        let error = self.error.clone();
+
+        let future_decode = read_buffer.read_u8()
+            .map_err(CmnError::from)
+            .and_then(move |axns| decoder.decode(&axns))
+            .then(move |res| {
+                match res {
+                    // The receiver may have been dropped, in which case the
+                    // frame is simply discarded:
+                    Ok(frame) => { motor_tx.send(frame).ok(); },
+                    Err(err) => if let Ok(mut error) = error.lock() { *error = Some(err); },
+                }
+                Ok::<(), Never>(())
+            });
+
+        completion_pool.complete_work(Box::new(future_decode))?;
+        Ok(())
+    }
+
+    fn layer(&self, _addr: LayerAddress) -> Option<&SubcorticalNucleusLayer> {
+        None
+    }
+
+    fn area_name<'a>(&'a self) -> &'a str {
+        &self.area_name
+    }
+
+    fn area_id(&self) -> usize {
+        self.area_id
+    }
+}
@@ -1 +1,3 @@
-mod arm;
\ No newline at end of file
+mod arm;
+
+pub use self::arm::{Arm, MotorDecoder, MotorDecoding};
@@ -14,7 +14,7 @@ pub struct Cerebellum {
 impl Cerebellum {
     pub fn new(area_map: AreaMap, device_idx: usize, ocl_context: &Context, thal: &mut Thalamus) -> Cerebellum {
         Cerebellum {
-            cortex: CorticalArea::new(area_map, device_idx, ocl_context, None, thal).unwrap(),
+            cortex: CorticalArea::new(area_map, device_idx, Some(ocl_context), None, thal).unwrap(),
             inverso: true,
         }
     }
@@ -1,14 +1,15 @@
 use std::thread::{self, JoinHandle};
+use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{self, SyncSender};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use find_folder::Search;
-use cmn::{self, CorticalDims, CmnResult, TractDims, TractFrameMut};
+use cmn::{self, CorticalDims, CmnError, CmnResult, TractDims, TractFrameMut};
 use ocl::{FutureWriteGuard};
 use map::{LayerMapSchemeList, AreaSchemeList, EncoderScheme, LayerScheme, AxonTopology,
     LayerAddress, AxonDomain, AxonTags, AxonSignature};
 use encode::{IdxStreamer, GlyphSequences, SensoryTract, ScalarSequence, ReversoScalarSequence,
-    VectorEncoder, ScalarSdrGradiant};
+    VectorEncoder, ScalarSdrGradiant, Replay, WavAudio, ImageDir, TextSequence, TextSettings};
 use subcortex::{Thalamus, SubcorticalNucleus, SubcorticalNucleusLayer, TractSender};
 use cortex::{CompletionPool, CorticalAreas};
 
@@ -42,11 +43,26 @@ pub enum InputGeneratorEncoder {
     CustomUnspecified,
 }
 
+/// Returns the dimensions of the lone layer of an area whose encoder supports
+/// exactly one layer.
+fn lone_layer_dims(lyr_dims_list: &[Option<CorticalDims>], area_name: &str, encoder_name: &str)
+        -> CmnResult<CorticalDims> {
+    match lyr_dims_list.first() {
+        Some(&Some(dims)) if lyr_dims_list.len() == 1 => Ok(dims),
+        _ => Err(format!("InputGenerator::new(): The {} encoder requires exactly one \
+            spatial layer. [area: '{}', layer count: {}]", encoder_name, area_name,
+            lyr_dims_list.len()).into()),
+    }
+}
+
+
 impl InputGeneratorEncoder {
     /// Writes input data into a tract.
-    pub fn write_into(&mut self, addr: LayerAddress, dims: TractDims, future_write: FutureWriteGuard<Vec<u8>>) {
+    pub fn write_into(&mut self, addr: LayerAddress, dims: TractDims,
+            future_write: FutureWriteGuard<Vec<u8>>) -> CmnResult<()> {
         // println!("About to wait...");
-        let mut buffer = future_write.wait().expect("InputGeneratorEncoder::write_into");
+        let mut buffer = future_write.wait().map_err(|err| CmnError::new(format!(
+            "InputGeneratorEncoder::write_into: Unable to lock the tract buffer: {}", err)))?;
         // println!("Wait complete...");
         let mut frame = TractFrameMut::new(buffer.as_mut_slice(), dims);
 
@@ -64,13 +80,15 @@ impl InputGeneratorEncoder {
                 es.write_into(&mut frame, addr)
             },
             InputGeneratorEncoder::CustomUnspecified => {
-                panic!("InputGenerator::write_into: Custom pathway not specified.")
+                return CmnError::err("InputGeneratorEncoder::write_into: Custom pathway not \
+                    specified (see `InputGenerator::set_encoder`).")
             },
             _ => (),
         }
+        Ok(())
     }
 
-    pub fn cycle_next(&mut self) {
+    pub fn cycle_next(&mut self) -> CmnResult<()> {
         match *self {
             InputGeneratorEncoder::Custom(ref mut es) => {
                 es.cycle_next()
@@ -82,18 +100,55 @@ impl InputGeneratorEncoder {
                 es.cycle_next()
             },
             InputGeneratorEncoder::CustomUnspecified => {
-                panic!("InputGenerator::cycle_next: Custom pathway not specified.")
+                return CmnError::err("InputGeneratorEncoder::cycle_next: Custom pathway not \
+                    specified (see `InputGenerator::set_encoder`).")
             },
             _ => (),
         }
+        Ok(())
+    }
+
+    pub fn set_ranges(&mut self, ranges: Vec<(f32, f32)>) -> CmnResult<()> {
+        match *self {
+            InputGeneratorEncoder::VectorEncoder(ref mut v) => v.set_ranges(&ranges),
+            _ => CmnError::err("InputGeneratorEncoder::set_ranges: Not a vector encoder."),
+        }
+    }
+
+    /// Copies an externally supplied frame into a `SensoryTract` encoder.
+    pub fn set_tract(&mut self, frame: Vec<u8>) -> CmnResult<()> {
+        match *self {
+            InputGeneratorEncoder::SensoryTract(ref mut st) => {
+                if let InputGeneratorFrame::Tract(mut tf) = st.ext_frame_mut() {
+                    if tf.frame_mut().len() != frame.len() {
+                        return CmnError::err(format!("InputGeneratorEncoder::set_tract: Invalid \
+                            frame length (expected: {}, found: {}).", tf.frame_mut().len(),
+                            frame.len()));
+                    }
+                    tf.frame_mut().copy_from_slice(&frame);
+                }
+                Ok(())
+            },
+            _ => CmnError::err("InputGeneratorEncoder::set_tract: Not a sensory tract encoder."),
+        }
     }
 
-    pub fn set_ranges(&mut self, ranges: Vec<(f32, f32)>) {
+    /// Copies externally supplied values, one per encoded layer, into a
+    /// `VectorEncoder`.
+    pub fn set_values(&mut self, values: Vec<f32>) -> CmnResult<()> {
         match *self {
             InputGeneratorEncoder::VectorEncoder(ref mut v) => {
-                v.set_ranges(&ranges).unwrap();
-            }
-            _ => unimplemented!(),
+                if let InputGeneratorFrame::F32Slice(dst) = v.ext_frame_mut() {
+                    if dst.len() != values.len() {
+                        return CmnError::err(format!("InputGeneratorEncoder::set_values: \
+                            Invalid value count (expected: {}, found: {}).", dst.len(),
+                            values.len()));
+                    }
+                    dst.copy_from_slice(&values);
+                }
+                Ok(())
+            },
+            _ => CmnError::err("InputGeneratorEncoder::set_values: Not a vector encoder."),
         }
     }
 }
@@ -102,6 +157,8 @@ enum EncoderCmd {
     WriteInto {addr: LayerAddress, dims: TractDims, future_write: FutureWriteGuard<Vec<u8>> },
     Cycle,
     SetRanges(Vec<(f32, f32)>),
+    SetTract(Vec<u8>),
+    SetValues(Vec<f32>),
     SetEncoder(InputGeneratorEncoder),
     Exit,
 }
@@ -147,6 +204,100 @@ impl InputGeneratorLayer {
 }
 
 
+/// The kind of external (sensory) data an input generator accepts.
+#[derive(Clone, Debug)]
+enum SensoryInput {
+    Tract { len: usize },
+    Values { layer_count: usize },
+    None,
+}
+
+
+/// A handle used to feed an `InputGenerator` from outside of the cortex
+/// (see `Thalamus::input_generator`).
+///
+/// All data is validated before being sent to the encoder thread.
+#[derive(Clone, Debug)]
+pub struct InputGeneratorHandle {
+    area_name: String,
+    tx: SyncSender<EncoderCmd>,
+    input: SensoryInput,
+    disabled: bool,
+}
+
+impl InputGeneratorHandle {
+    fn send(&self, cmd: EncoderCmd) -> CmnResult<()> {
+        if self.disabled {
+            return CmnError::err(format!("InputGeneratorHandle::send: The input generator for \
+                area '{}' is disabled.", self.area_name));
+        }
+        self.tx.send(cmd).map_err(|_| CmnError::from(format!("InputGeneratorHandle::send: \
+            The encoder thread for area '{}' has exited.", self.area_name)))
+    }
+
+    /// Sets the ranges of a vector encoder.
+    pub fn set_encoder_ranges(&self, ranges: Vec<(f32, f32)>) -> CmnResult<()> {
+        match self.input {
+            SensoryInput::Values { layer_count } => {
+                if ranges.len() > layer_count {
+                    return CmnError::err(format!("InputGeneratorHandle::set_encoder_ranges: \
+                        Too many ranges provided for area '{}' ('{}'/'{}').", self.area_name,
+                        ranges.len(), layer_count));
+                }
+                self.send(EncoderCmd::SetRanges(ranges))
+            },
+            _ => CmnError::err(format!("InputGeneratorHandle::set_encoder_ranges: The input \
+                generator for area '{}' does not use a vector encoder.", self.area_name)),
+        }
+    }
+
+    /// Sets the frame written by a sensory tract encoder each cycle.
+    pub fn write_tract(&self, frame: &[u8]) -> CmnResult<()> {
+        match self.input {
+            SensoryInput::Tract { len } => {
+                if frame.len() != len {
+                    return CmnError::err(format!("InputGeneratorHandle::write_tract: Invalid \
+                        frame length for area '{}' (expected: {}, found: {}).", self.area_name,
+                        len, frame.len()));
+                }
+                self.send(EncoderCmd::SetTract(frame.to_vec()))
+            },
+            _ => CmnError::err(format!("InputGeneratorHandle::write_tract: The input \
+                generator for area '{}' does not use a sensory tract encoder.", self.area_name)),
+        }
+    }
+
+    /// Sets the values encoded by a vector encoder. Exactly one value must
+    /// be provided per encoded layer (see `::value_count`).
+    pub fn write_values(&self, values: &[f32]) -> CmnResult<()> {
+        match self.input {
+            SensoryInput::Values { layer_count } => {
+                if values.len() != layer_count {
+                    return CmnError::err(format!("InputGeneratorHandle::write_values: Invalid \
+                        value count for area '{}' (expected: {}, found: {}).", self.area_name,
+                        layer_count, values.len()));
+                }
+                self.send(EncoderCmd::SetValues(values.to_vec()))
+            },
+            _ => CmnError::err(format!("InputGeneratorHandle::write_values: The input \
+                generator for area '{}' does not use a vector encoder.", self.area_name)),
+        }
+    }
+
+    /// Returns the number of values accepted by `::write_values` or `None`
+    /// if the input generator does not use a vector encoder.
+    pub fn value_count(&self) -> Option<usize> {
+        match self.input {
+            SensoryInput::Values { layer_count } => Some(layer_count),
+            _ => None,
+        }
+    }
+
+    pub fn area_name<'a>(&'a self) -> &'a str { &self.area_name }
+    pub fn is_disabled(&self) -> bool { self.disabled }
+}
+
+
 /// An input source.
 //
 // [NOTE (out of date)]: To implement multiple layers from a single input source:
@@ -158,12 +309,28 @@ pub struct InputGenerator {
     layers: HashMap<LayerAddress, InputGeneratorLayer>,
     tx: SyncSender<EncoderCmd>,
     _thread: Option<JoinHandle<()>>,
+    error: Arc<Mutex<Option<CmnError>>>,
+    input: SensoryInput,
+    custom: bool,
     disabled: bool,
 }
 
 impl InputGenerator {
     pub fn new(layer_map_schemes: &LayerMapSchemeList, area_schemes: &AreaSchemeList,
             area_name: &str) -> CmnResult<InputGenerator> {
+        InputGenerator::build(layer_map_schemes, area_schemes, area_name, None)
+    }
+
+    /// Returns a new input generator whose encoder (if it is one of the
+    /// built-in, randomized encoders) derives its random number generator
+    /// from the master `seed` (see `CorticalAreaSettings::seed`).
+    pub fn with_seed(layer_map_schemes: &LayerMapSchemeList, area_schemes: &AreaSchemeList,
+            area_name: &str, seed: u64) -> CmnResult<InputGenerator> {
+        InputGenerator::build(layer_map_schemes, area_schemes, area_name, Some(seed))
+    }
+
+    fn build(layer_map_schemes: &LayerMapSchemeList, area_schemes: &AreaSchemeList,
+            area_name: &str, seed: Option<u64>) -> CmnResult<InputGenerator> {
         let area_scheme = &area_schemes[area_name];
         let layer_map_scheme = &layer_map_schemes[area_scheme.layer_map_name()];
         let layer_schemes: Vec<&LayerScheme> = layer_map_scheme.layers().iter().map(|ls| ls).collect();
@@ -183,7 +350,9 @@ impl InputGenerator {
                 AxonTopology::Spatial | AxonTopology::Nonspatial =>
                     Some(area_scheme.dims().clone_with_depth(lyr_depth)),
                 // AxonTopology::Nonspatial => None,
-                AxonTopology::None => panic!("InputGenerator::new: Invalid axon topology (None)."),
+                AxonTopology::None => return Err(format!("InputGenerator::new(): Invalid axon \
+                    topology (None). [area: '{}', layer: '{}']", area_scheme.name(),
+                    lyr_name).into()),
             };
 
             let lyr_axon_sig = match *layer_scheme.axon_domain() {
@@ -207,12 +376,12 @@ impl InputGenerator {
         }
 
         let mut disabled = false;
+        let area_id = area_scheme.area_id() as u64;
 
         let encoder = match *area_scheme.get_encoder() {
             EncoderScheme::IdxStreamer { ref file_name, cyc_per, scale, loop_frames } => {
-                assert_eq!(layers.len(), 1);
-                let mut is = IdxStreamer::new(layers[&lyr_addr_list[0]].sub.dims().unwrap().clone(),
-                    file_name.clone(), cyc_per, scale);
+                let lyr_dims = lone_layer_dims(&lyr_dims_list, area_name, "IdxStreamer")?;
+                let mut is = IdxStreamer::new(lyr_dims, file_name.clone(), cyc_per, scale)?;
 
                 if loop_frames > 0 {
                     is = is.loop_frames(loop_frames);
@@ -226,34 +395,30 @@ impl InputGenerator {
                 let image_file = Search::ParentsThenKids(3, 3).for_folder("tmp_data")
                     .expect("InputGenerator::new(): 'image file folder (tmp_data)'")
                     .join("train-images-idx3-ubyte");
-                let gs = GlyphSequences::new(&mut layers, seq_lens, seq_count, scale, hrz_dims,
-                    label_file, image_file);
+                let gs = GlyphSequences::with_rng(&mut layers, seq_lens, seq_count, scale, hrz_dims,
+                    label_file, image_file, cmn::seeded_rng(seed, "glyph_sequences", &[area_id]))?;
                 InputGeneratorEncoder::GlyphSequences(Box::new(gs))
             },
             EncoderScheme::SensoryTract => {
-                assert_eq!(layers.len(), 1);
-                let st = SensoryTract::new(layers[&lyr_addr_list[0]].sub.dims().unwrap());
+                let lyr_dims = lone_layer_dims(&lyr_dims_list, area_name, "SensoryTract")?;
+                let st = SensoryTract::new(lyr_dims);
                 InputGeneratorEncoder::SensoryTract(Box::new(st))
             },
             EncoderScheme::ScalarSequence { range, incr } => {
-                let tract_dims = {
-                    assert!(lyr_dims_list.len() == 1);
-                    lyr_dims_list[0].unwrap().into()
-                };
+                let tract_dims = lone_layer_dims(&lyr_dims_list, area_name, "ScalarSequence")?.into();
 
                 InputGeneratorEncoder::Custom(Box::new(ScalarSequence::new(range, incr, &tract_dims)))
             },
             EncoderScheme::ScalarSdrGradiant { range, way_span, incr } => {
-                let tract_dims = {
-                    assert!(lyr_dims_list.len() == 1);
-                    lyr_dims_list[0].unwrap().into()
-                };
+                let tract_dims = lone_layer_dims(&lyr_dims_list, area_name, "ScalarSdrGradiant")?.into();
 
-                InputGeneratorEncoder::Custom(Box::new(ScalarSdrGradiant::new(range, way_span, incr, &tract_dims)))
+                InputGeneratorEncoder::Custom(Box::new(ScalarSdrGradiant::with_rng(range, way_span,
+                    incr, &tract_dims, cmn::seeded_rng(seed, "scalar_sdr_gradiant", &[area_id]))))
             },
             EncoderScheme::ReversoScalarSequence { range, incr } => {
                 InputGeneratorEncoder::Custom(Box::new(
-                    ReversoScalarSequence::new(range, incr, &lyr_addr_list)))
+                    ReversoScalarSequence::with_rng(range, incr, &lyr_addr_list,
+                        cmn::seeded_rng(seed, "reverso_scalar_sequence", &[area_id]))))
             },
             EncoderScheme::VectorEncoder { ref ranges } => {
                 let tract_dims: Vec<_> = lyr_dims_list.iter().map(|d| d.unwrap().into()).collect();
@@ -262,6 +427,38 @@ impl InputGenerator {
                     VectorEncoder::new(ranges.clone(), &lyr_addr_list, &tract_dims)
                 )))
             },
+            EncoderScheme::Replay { ref file, looped, cycles_per_frame } => {
+                InputGeneratorEncoder::Custom(Box::new(
+                    Replay::new(file, &layers, cycles_per_frame, looped)?))
+            },
+            EncoderScheme::WavAudio { ref file_names, freq_range, window_len, hop_len,
+                    cycles_per_frame, loop_files } => {
+                let tract_dims = lone_layer_dims(&lyr_dims_list, area_name, "WavAudio")?.into();
+
+                InputGeneratorEncoder::Custom(Box::new(WavAudio::new(&file_names[..], freq_range,
+                    window_len, hop_len, cycles_per_frame, &tract_dims)?.loop_files(loop_files)))
+            },
+            EncoderScheme::ImageDir { ref paths, grayscale, scale, cycles_per_frame,
+                    loop_images } => {
+                let tract_dims: Vec<_> = lyr_dims_list.iter().map(|d| d.unwrap().into()).collect();
+
+                InputGeneratorEncoder::Custom(Box::new(ImageDir::new(&paths[..], &lyr_addr_list,
+                    &tract_dims, grayscale, scale, cycles_per_frame)?.loop_images(loop_images)))
+            },
+            EncoderScheme::TextSequence { ref file_name, words, sparsity, overlap, max_vocab,
+                    cycles_per_symbol, sentence_reset_cycles } => {
+                let tract_dims = lone_layer_dims(&lyr_dims_list, area_name, "TextSequence")?.into();
+                let settings = TextSettings::new()
+                    .words(words)
+                    .sparsity(sparsity)
+                    .overlap(overlap)
+                    .max_vocab(max_vocab)
+                    .cycles_per_symbol(cycles_per_symbol)
+                    .sentence_reset_cycles(sentence_reset_cycles);
+
+                InputGeneratorEncoder::Custom(Box::new(
+                    TextSequence::open(file_name, &tract_dims, settings)?))
+            },
             EncoderScheme::Custom => {
                 InputGeneratorEncoder::CustomUnspecified
             },
@@ -274,72 +471,152 @@ impl InputGenerator {
                 InputGeneratorEncoder::None
             }
             EncoderScheme::Zeros => InputGeneratorEncoder::None,
-            ref is @ _ => panic!("\nInputGenerator::new(): Input type: '{:?}' not yet supported.", is),
+            ref is @ _ => return Err(format!("InputGenerator::new(): Input type: '{:?}' not yet \
+                supported. [area: '{}']", is, area_name).into()),
+        };
+
+        let input = match encoder {
+            InputGeneratorEncoder::SensoryTract(_) => SensoryInput::Tract {
+                len: lyr_dims_list[0].as_ref().map(|d| d.to_len()).unwrap_or(0),
+            },
+            InputGeneratorEncoder::VectorEncoder(_) => SensoryInput::Values {
+                layer_count: lyr_addr_list.len(),
+            },
+            _ => SensoryInput::None,
+        };
+
+        let custom = match encoder {
+            InputGeneratorEncoder::CustomUnspecified => true,
+            _ => false,
         };
 
         let (tx, rx) = mpsc::sync_channel(3);
+        let error = Arc::new(Mutex::new(None));
+        let thread_error = error.clone();
         let thread_name = format!("InputGeneratorEncoder_{}", area_scheme.name());
         let thread_handle: JoinHandle<_> = thread::Builder::new().name(thread_name).spawn(move || {
             let mut encoder = encoder;
             let rx = rx;
 
-            loop {
-                match rx.recv().unwrap() {
-                    EncoderCmd::WriteInto { addr, dims, future_write } =>
-                        encoder.write_into(addr, dims, future_write),
+            // Runs until `Exit` is received or every sender has been dropped.
+            while let Ok(cmd) = rx.recv() {
+                // Sensory data is validated by `InputGeneratorHandle` before
+                // being sent. Any remaining errors are stored, to be returned
+                // by the next `pre_cycle` or `post_cycle`, and the offending
+                // command is dropped.
+                let res = match cmd {
+                    EncoderCmd::WriteInto { addr, dims, future_write } => {
+                        encoder.write_into(addr, dims, future_write)
+                    },
                     EncoderCmd::Cycle => encoder.cycle_next(),
                     EncoderCmd::SetRanges(ranges) => encoder.set_ranges(ranges),
-                    EncoderCmd::SetEncoder(e) => encoder = e,
+                    EncoderCmd::SetTract(frame) => encoder.set_tract(frame),
+                    EncoderCmd::SetValues(values) => encoder.set_values(values),
+                    EncoderCmd::SetEncoder(e) => { encoder = e; Ok(()) },
                     EncoderCmd::Exit => break,
+                };
+
+                if let Err(err) = res {
+                    // Only the first error since the last cycle is kept:
+                    if let Ok(mut error) = thread_error.lock() {
+                        if error.is_none() { *error = Some(err); }
+                    }
                 }
             }
-        }).unwrap();
+        }).map_err(|err| CmnError::new(format!("InputGenerator::new(): Unable to spawn the \
+            encoder thread for area '{}': {}", area_scheme.name(), err)))?;
 
         Ok(InputGenerator {
             area_id: area_scheme.area_id(),
             area_name: area_scheme.name().to_owned(),
             layers: layers,
             _thread: Some(thread_handle),
+            error,
             tx: tx,
+            input,
+            custom,
             disabled,
         })
     }
 
-    // Specify a custom encoder tract. Input scheme must have been configured
-    // as `EncoderScheme::Custom` in `AreaScheme`.
-    pub fn set_encoder(&self, tract: Box<InputGeneratorTract>) {
-        self.tx.send(EncoderCmd::SetEncoder(InputGeneratorEncoder::Custom(tract))).unwrap();
+    /// Specifies a custom encoder tract. The input scheme must have been
+    /// configured as `EncoderScheme::Custom` in `AreaScheme`.
+    pub fn set_encoder(&self, tract: Box<InputGeneratorTract>) -> CmnResult<()> {
+        if !self.custom {
+            return CmnError::err(format!("InputGenerator::set_encoder: The input generator for \
+                area '{}' was not configured with `EncoderScheme::Custom`.", self.area_name));
+        }
+        self.send(EncoderCmd::SetEncoder(InputGeneratorEncoder::Custom(tract)), "set_encoder")
     }
 
     /// Writes input data into a tract.
     ///
     /// Blocks when the pathway (`TractSender`) `backpressure = true`.
     ///
-    pub fn send_to_pathway(&self, layer: &InputGeneratorLayer, _completion_pool: &mut CompletionPool) {
+    pub fn send_to_pathway(&self, layer: &InputGeneratorLayer, _completion_pool: &mut CompletionPool)
+            -> CmnResult<()> {
         // println!("####### InputGenerator::send_to_pathway: self.disabled: {}", self.disabled);
-        if !self.disabled {
-            let pathway = layer.pathway.as_ref().expect("no pathway set");
-            // println!("About to wait...");
-            let future_write = match pathway.send().wait().unwrap() {
-                Some(fw) => fw.write_u8(),
-                None => panic!("tract wants to skip frame"),
-            };
-            // println!("Wait complete...");
+        if self.disabled { return Ok(()); }
 
-            self.tx.send(EncoderCmd::WriteInto {
-                addr: layer.sub().addr(),
-                dims: layer.sub.dims().unwrap().into(),
-                future_write,
-            }).unwrap();
+        let pathway = layer.pathway.as_ref().ok_or_else(|| CmnError::new(format!(
+            "InputGenerator::send_to_pathway: No pathway set for area '{}'.", self.area_name)))?;
+        // println!("About to wait...");
+        let future_write = match pathway.send().wait() {
+            Ok(Some(fw)) => fw.write_u8(),
+            Ok(None) => return CmnError::err(format!("InputGenerator::send_to_pathway: The \
+                tract for area '{}' wants to skip a frame.", self.area_name)),
+            Err(_) => return CmnError::err(format!("InputGenerator::send_to_pathway: The \
+                tract for area '{}' has been closed.", self.area_name)),
+        };
+        // println!("Wait complete...");
+        let dims = layer.sub.dims().ok_or_else(|| CmnError::new(format!(
+            "InputGenerator::send_to_pathway: Layer dimensions unset for area '{}'.",
+            self.area_name)))?;
+
+        self.send(EncoderCmd::WriteInto {
+            addr: layer.sub().addr(),
+            dims: dims.into(),
+            future_write,
+        }, "send_to_pathway")
+    }
+
+    pub fn cycle_next(&self, _completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        if self.disabled { return Ok(()); }
+        self.send(EncoderCmd::Cycle, "cycle_next")
+    }
+
+    /// Sends a command to the encoder thread.
+    fn send(&self, cmd: EncoderCmd, caller: &str) -> CmnResult<()> {
+        self.tx.send(cmd).map_err(|_| CmnError::new(format!("InputGenerator::{}: The encoder \
+            thread for area '{}' has exited.", caller, self.area_name)))
+    }
+
+    /// Returns the first error reported by the encoder thread since the last
+    /// call, if any.
+    fn take_error(&self, caller: &str) -> CmnResult<()> {
+        let error = self.error.lock().map_err(|_| CmnError::new(format!("InputGenerator::{}: \
+            Error slot poisoned.", caller)))?.take();
+
+        match error {
+            Some(err) => CmnError::err(format!("InputGenerator::{}: The encoder for area '{}' \
+                failed: {}", caller, self.area_name, err)),
+            None => Ok(()),
         }
     }
 
-    pub fn cycle_next(&self, _completion_pool: &mut CompletionPool) {
-        if !self.disabled { self.tx.send(EncoderCmd::Cycle).unwrap(); }
+    pub fn set_encoder_ranges(&self, ranges: Vec<(f32, f32)>) -> CmnResult<()> {
+        self.handle().set_encoder_ranges(ranges)
     }
 
-    pub fn set_encoder_ranges(&self, ranges: Vec<(f32, f32)>) {
-        if !self.disabled { self.tx.send(EncoderCmd::SetRanges(ranges)).unwrap(); }
+    /// Returns a handle which can be used to feed this input generator from
+    /// another thread.
+    pub fn handle(&self) -> InputGeneratorHandle {
+        InputGeneratorHandle {
+            area_name: self.area_name.clone(),
+            tx: self.tx.clone(),
+            input: self.input.clone(),
+            disabled: self.disabled,
+        }
     }
 
     pub fn layers_mut(&mut self) -> &mut HashMap<LayerAddress, InputGeneratorLayer> {
@@ -357,8 +634,9 @@ impl InputGenerator {
 
 impl Drop for InputGenerator {
     fn drop(&mut self) {
-        self.tx.send(EncoderCmd::Exit).unwrap();
-        self._thread.take().unwrap().join().unwrap();
+        // The thread may have already exited (after a panic):
+        self.tx.send(EncoderCmd::Exit).ok();
+        if let Some(thread) = self._thread.take() { thread.join().ok(); }
     }
 }
 
@@ -369,21 +647,23 @@ impl SubcorticalNucleus for InputGenerator {
             let tx = thal.input_pathway(layer.sub().addr(), true);
             layer.pathway = Some(tx);
         }
+        thal.add_input_generator(self.handle());
         Ok(())
     }
 
     fn pre_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
             completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        self.take_error("pre_cycle")?;
+
         for layer in self.layers.values() {
-            self.send_to_pathway(layer, completion_pool);
+            self.send_to_pathway(layer, completion_pool)?;
         }
-        self.cycle_next(completion_pool);
-        Ok(())
+        self.cycle_next(completion_pool)
     }
 
     fn post_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
             _completion_pool: &mut CompletionPool) -> CmnResult<()> {
-        Ok(())
+        self.take_error("post_cycle")
     }
 
     fn layer(&self, addr: LayerAddress) -> Option<&SubcorticalNucleusLayer> {
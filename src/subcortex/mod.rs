@@ -4,14 +4,16 @@ mod thalamus;
 mod tract_channel;
 mod input_generator;
 mod test_nucleus;
+mod arm;
 // mod cortical_sampler;
 
 pub use self::subcortex::{Subcortex, SubcorticalNucleus, SubcorticalNucleusLayer,  };
 pub use self::thalamus::{Thalamus, /*InputGeneratorTract, InputGenerator, InputGeneratorFrame,
     InputGeneratorEncoder, InputGeneratorLayer*/};
 pub use self::cerebellum::Cerebellum;
+pub use self::arm::{Arm, MotorDecoder, MotorDecoding};
 
-pub use self::input_generator::{InputGenerator, InputGeneratorFrame,
+pub use self::input_generator::{InputGenerator, InputGeneratorFrame, InputGeneratorHandle,
     InputGeneratorTract, InputGeneratorEncoder, InputGeneratorLayer};
 
 pub use self::tract_channel::{tract_channel_single_u8, tract_channel_single_i8,
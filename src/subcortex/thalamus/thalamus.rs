@@ -17,7 +17,8 @@ use std::borrow::Borrow;
 use cmn::{self, CmnError, CmnResult, TractDims, CorticalDims, MapStore};
 use map::{AreaMap, LayerAddress, AreaSchemeList, LayerMapSchemeList};
 use ocl::{Context, EventList, Buffer, RwVec, FutureReadGuard, FutureWriteGuard};
-use ::{InputGenerator, CompletionPool};
+use ::{CompletionPool};
+use subcortex::InputGeneratorHandle;
 use subcortex::{self, Subcortex, TractSender, TractReceiver};
 
 
@@ -113,6 +114,11 @@ impl ThalamicTract {
         self.tract_areas.by_index(idx).ok_or(CmnError::from("invalid tract idx"))
             .map(|ta| &ta.buffer)
     }
+
+    pub fn dims<'t>(&'t self, idx: usize) -> CmnResult<&TractDims> {
+        self.tract_areas.by_index(idx).ok_or(CmnError::from("invalid tract idx"))
+            .map(|ta| ta.dims())
+    }
 }
 
 
@@ -133,13 +139,14 @@ pub enum Pathway {
 pub struct Thalamus {
     tract: ThalamicTract,
     pathways: MapStore<LayerAddress, Pathway>,
+    input_generators: MapStore<String, InputGeneratorHandle>,
     area_maps: MapStore<String, AreaMap>,
 }
 
 impl Thalamus {
     pub fn new(layer_map_schemes: LayerMapSchemeList, mut area_schemes: AreaSchemeList,
-            subcortex: &Subcortex, _ocl_context: &Context) -> CmnResult<Thalamus> {
-        area_schemes.freeze();
+            subcortex: &Subcortex, _ocl_context: Option<&Context>) -> CmnResult<Thalamus> {
+        area_schemes.freeze()?;
         let area_schemes = area_schemes;
         let mut tract = ThalamicTract::new();
         let mut area_maps = MapStore::with_capacity(area_schemes.areas().len());
@@ -183,6 +190,7 @@ impl Thalamus {
         let thal = Thalamus {
             tract,
             pathways: MapStore::with_capacity(16),
+            input_generators: MapStore::with_capacity(8),
             area_maps: area_maps,
         };
 
@@ -315,23 +323,26 @@ impl Thalamus {
         unimplemented!();
     }
 
-    #[deprecated]
-    pub fn input_generator_idx<S: AsRef<str>>(&self, _pathway_name: S) -> CmnResult<usize> {
-        // match self.input_generators.indices().get(pathway_name.as_ref()) {
-        //     Some(&idx) => Ok(idx),
-        //     None => CmnError::err(format!("Thalamus::input_generator_idx(): \
-        //         No external pathway found named: '{}'.", pathway_name.as_ref())),
-        // }
-        unimplemented!();
+    /// Registers an input generator handle, making it available to external
+    /// sources by area (pathway) name.
+    pub fn add_input_generator(&mut self, handle: InputGeneratorHandle) {
+        self.input_generators.insert(handle.area_name().to_owned(), handle);
     }
 
-    #[deprecated]
-    pub fn input_generator(&mut self, _pathway_idx: usize) -> CmnResult<&mut InputGenerator> {
-        // let pathway = try!(self.input_generators.by_index_mut(pathway_idx).ok_or(
-        //     CmnError::new(format!("Thalamus::input_generator_frame(): Invalid pathway index: '{}'.",
-        //     pathway_idx))));
-        // Ok(&mut pathway.0)
-        unimplemented!();
+    /// Returns the index of the input generator for the area named
+    /// `pathway_name`.
+    pub fn input_generator_idx<S: AsRef<str>>(&self, pathway_name: S) -> CmnResult<usize> {
+        match self.input_generators.index_of(pathway_name.as_ref()) {
+            Some(idx) => Ok(idx),
+            None => CmnError::err(format!("Thalamus::input_generator_idx(): \
+                No external pathway found named: '{}'.", pathway_name.as_ref())),
+        }
+    }
+
+    /// Returns a handle to the input generator with the index, `pathway_idx`.
+    pub fn input_generator(&self, pathway_idx: usize) -> CmnResult<&InputGeneratorHandle> {
+        self.input_generators.by_index(pathway_idx).ok_or(CmnError::from(
+            format!("Thalamus::input_generator(): Invalid pathway index: '{}'.", pathway_idx)))
     }
 
     pub fn tract(&self) -> &ThalamicTract { &self.tract }
@@ -0,0 +1,63 @@
+use map::LayerAddress;
+use cortex::{AnomalyScore, AnomalyTracker, LIKELIHOOD_MIN_SAMPLES};
+
+
+const CELL_COUNT: usize = 8;
+
+
+#[test]
+fn anomaly_tracker() {
+    let lyr_addr = LayerAddress::new(2, 1);
+    assert!(AnomalyTracker::new(lyr_addr, CELL_COUNT, vec![]).is_err());
+
+    // Tuft 0 is proximal, tuft 1 is distal:
+    let mut tracker = AnomalyTracker::new(lyr_addr, CELL_COUNT, vec![1]).unwrap();
+    let axns = [0, 1, 1, 0, 0, 0, 0, 1];
+    let mut tufts = vec![0u8; CELL_COUNT * 2];
+
+    // Nothing has been predicted yet:
+    let score = tracker.update(&axns, &tufts).unwrap();
+    assert_eq!((score.active(), score.predicted(), score.raw()), (3, 0, 1.0));
+    assert_eq!(score.cycle(), 0);
+    assert_eq!(score.likelihood(), 0.5);
+
+    // Predict cells 1 and 2 (proximal tuft activity is ignored):
+    tufts[7] = 1;
+    tufts[CELL_COUNT + 1] = 1;
+    tufts[CELL_COUNT + 2] = 1;
+    tracker.update(&axns, &tufts).unwrap();
+    let score = tracker.update(&axns, &tufts).unwrap();
+    assert_eq!((score.active(), score.predicted()), (3, 2));
+    assert_eq!(score.cycle(), 2);
+    assert!((score.raw() - 1.0 / 3.0).abs() < 1e-6);
+    assert_eq!(tracker.latest(), Some(score));
+
+    // A well predicted history followed by a surprise:
+    tufts[CELL_COUNT + 7] = 1;
+    for _ in 0..LIKELIHOOD_MIN_SAMPLES { tracker.update(&axns, &tufts).unwrap(); }
+    assert!(tracker.latest().unwrap().likelihood() < 0.5);
+    for _ in 0..5 {
+        let score = tracker.update(&[1, 0, 0, 1, 1, 1, 1, 0], &tufts).unwrap();
+        assert_eq!(score.raw(), 1.0);
+    }
+    let likelihood = tracker.latest().unwrap().likelihood();
+    assert!(likelihood > 0.9, "likelihood: {}", likelihood);
+
+    tracker.reset();
+    assert!(tracker.latest().is_none());
+    let cycle = LIKELIHOOD_MIN_SAMPLES as u64 + 8;
+    assert_eq!(tracker.update(&axns, &tufts).unwrap().cycle(), cycle);
+    assert!(tracker.update(&axns[..4], &tufts).is_err());
+    assert!(tracker.update(&axns, &tufts[..CELL_COUNT]).is_err());
+}
+
+
+#[test]
+fn anomaly_score_frame() {
+    let lyr_addr = LayerAddress::new(0, 4);
+    let score = AnomalyScore::new(lyr_addr, (1 << 40) + 7, 300, 210, 0.125);
+    let frame = score.to_frame();
+    assert_eq!(AnomalyScore::from_frame(lyr_addr, &frame).unwrap(), score);
+    assert!(AnomalyScore::from_frame(lyr_addr, &frame[..12]).is_err());
+    assert_eq!(AnomalyScore::new(lyr_addr, 0, 0, 0, 0.5).raw(), 0.0);
+}
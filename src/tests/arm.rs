@@ -0,0 +1,72 @@
+use map::LayerAddress;
+use cmn::TractDims;
+use flywheel::MotorFrame;
+use subcortex::{MotorDecoder, MotorDecoding};
+
+
+// Five by five columns, two slices deep, centered at (2, 2):
+const DIMS: (u8, u32, u32) = (2, 5, 5);
+
+fn axns_with_active(cols: &[(u32, u32)]) -> Vec<u8> {
+    let dims = TractDims::new(DIMS.0, DIMS.1, DIMS.2);
+    let mut axns = vec![0u8; dims.to_len()];
+    for &(v_id, u_id) in cols {
+        // Activate the column in the second slice only:
+        axns[(DIMS.1 * DIMS.2 + v_id * DIMS.2 + u_id) as usize] = 255;
+    }
+    axns
+}
+
+
+#[test]
+fn decode_vector_2d() {
+    let lyr_addr = LayerAddress::new(0, 0);
+    let dec = MotorDecoder::new(lyr_addr, TractDims::new(DIMS.0, DIMS.1, DIMS.2),
+        MotorDecoding::Vector2d).unwrap();
+
+    // A silent layer decodes to the zero vector:
+    assert_eq!(dec.decode(&axns_with_active(&[])).unwrap(),
+        MotorFrame::Vector2d { lyr_addr, xy: [0., 0.] });
+
+    // A single column directly along `v` (90˚) points straight up:
+    match dec.decode(&axns_with_active(&[(4, 2)])).unwrap() {
+        MotorFrame::Vector2d { xy, .. } => {
+            assert!(xy[0].abs() < 1e-9 && (xy[1] - 1.).abs() < 1e-9, "xy: {:?}", xy);
+        },
+        f @ _ => panic!("unexpected frame: {:?}", f),
+    }
+
+    // Opposing columns cancel out:
+    match dec.decode(&axns_with_active(&[(4, 2), (0, 2)])).unwrap() {
+        MotorFrame::Vector2d { xy, .. } => {
+            assert!(xy[0].abs() < 1e-9 && xy[1].abs() < 1e-9, "xy: {:?}", xy);
+        },
+        f @ _ => panic!("unexpected frame: {:?}", f),
+    }
+}
+
+
+#[test]
+fn decode_discrete() {
+    let lyr_addr = LayerAddress::new(0, 0);
+    let dec = MotorDecoder::new(lyr_addr, TractDims::new(DIMS.0, DIMS.1, DIMS.2),
+        MotorDecoding::Discrete(4)).unwrap();
+
+    assert_eq!(dec.decode(&axns_with_active(&[])).unwrap(),
+        MotorFrame::Discrete { lyr_addr, action: None });
+
+    // (v: +2, u: +2) lies at 30˚ (sector 0), (v: -2, u: -1) at ~240˚ (sector 2):
+    assert_eq!(dec.decode(&axns_with_active(&[(4, 4)])).unwrap(),
+        MotorFrame::Discrete { lyr_addr, action: Some(0) });
+    assert_eq!(dec.decode(&axns_with_active(&[(0, 1)])).unwrap(),
+        MotorFrame::Discrete { lyr_addr, action: Some(2) });
+
+    // The center column has no preferred direction:
+    assert_eq!(dec.decode(&axns_with_active(&[(2, 2)])).unwrap(),
+        MotorFrame::Discrete { lyr_addr, action: None });
+
+    // Invalid configurations and inputs are errors:
+    assert!(MotorDecoder::new(lyr_addr, TractDims::new(DIMS.0, DIMS.1, DIMS.2),
+        MotorDecoding::Discrete(0)).is_err());
+    assert!(dec.decode(&[0u8; 3]).is_err());
+}
@@ -0,0 +1,96 @@
+use std::sync::mpsc;
+use map::{AreaScheme, AreaSchemeList};
+use subcortex::SubcorticalNucleus;
+use decode::{SdrClassifier, LayerClassifier};
+
+
+const INPUT_LEN: usize = 100;
+const PATTERN_COUNT: usize = 4;
+
+/// Returns a frame with every `PATTERN_COUNT`th cell, starting at `pattern`,
+/// active.
+fn pattern_frame(pattern: usize) -> Vec<u8> {
+    (0..INPUT_LEN).map(|i| if i % PATTERN_COUNT == pattern { 127 } else { 0 }).collect()
+}
+
+
+#[test]
+fn sdr_classifier_sequence() {
+    let mut sc = SdrClassifier::new(INPUT_LEN, &[0, 1], PATTERN_COUNT).unwrap().alpha(0.1);
+
+    // Patterns repeat in order, each labelled with its own index:
+    for cycle in 0..200 {
+        let pattern = cycle % PATTERN_COUNT;
+        sc.compute(&pattern_frame(pattern), Some(pattern)).unwrap();
+    }
+    assert!(sc.accuracy(0).unwrap().total() == 200);
+    assert!(sc.accuracy(1).unwrap().total() == 199);
+    assert!(sc.accuracy(2).is_none());
+
+    sc.reset_accuracy();
+    for cycle in 0..40 {
+        let pattern = cycle % PATTERN_COUNT;
+        let preds = sc.compute(&pattern_frame(pattern), Some(pattern)).unwrap();
+        assert_eq!(preds[0].bucket(), pattern);
+        assert_eq!(preds[1].step(), 1);
+        assert_eq!(preds[1].bucket(), (pattern + 1) % PATTERN_COUNT);
+        assert!(preds[1].confidence() > 0.9, "confidence: {}", preds[1].confidence());
+    }
+    assert_eq!(sc.accuracy(0).unwrap().ratio(), 1.0);
+    assert_eq!(sc.accuracy(1).unwrap().ratio(), 1.0);
+
+    // Unlabelled cycles neither train nor score:
+    sc.reset_accuracy();
+    sc.compute(&pattern_frame(0), None).unwrap();
+    assert_eq!(sc.accuracy(0).unwrap().total(), 0);
+
+    assert!(sc.compute(&pattern_frame(0)[..10], None).is_err());
+    assert!(sc.compute(&pattern_frame(0), Some(PATTERN_COUNT)).is_err());
+    assert!(SdrClassifier::new(INPUT_LEN, &[1, 1], PATTERN_COUNT).is_err());
+}
+
+
+#[test]
+fn sdr_classifier_scalar() {
+    let mut sc = SdrClassifier::new(INPUT_LEN, &[0], PATTERN_COUNT).unwrap()
+        .alpha(0.1).scalar_range(0.0, 40.0);
+    assert_eq!(sc.bucket_of(-5.0), Some(0));
+    assert_eq!(sc.bucket_of(25.0), Some(2));
+    assert_eq!(sc.bucket_of(100.0), Some(3));
+    assert_eq!(sc.bucket_value(1), Some(15.0));
+
+    for cycle in 0..100 {
+        let pattern = cycle % PATTERN_COUNT;
+        sc.compute_scalar(&pattern_frame(pattern), Some(pattern as f64 * 10.0 + 5.0)).unwrap();
+    }
+
+    // The estimated value approaches the center of the labelled bucket:
+    let preds = sc.compute_scalar(&pattern_frame(2), None).unwrap();
+    assert_eq!(preds[0].bucket(), 2);
+    assert!((preds[0].value().unwrap() - 25.0).abs() < 2.0, "value: {:?}", preds[0].value());
+
+    let unranged = SdrClassifier::new(INPUT_LEN, &[0], PATTERN_COUNT).unwrap();
+    assert!(unranged.clone().compute_scalar(&pattern_frame(0), Some(1.0)).is_err());
+    assert_eq!(unranged.bucket_of(1.0), None);
+}
+
+
+/// A `LayerClassifier` takes the id of its own area.
+#[test]
+fn layer_classifier_area_id() {
+    let area_schemes = AreaSchemeList::new()
+        .area(AreaScheme::new("v0", "external", 24))
+        .area(AreaScheme::new("classifier", "external", 24));
+    let classifier = || SdrClassifier::new(INPUT_LEN, &[0], PATTERN_COUNT).unwrap();
+
+    let (_label_tx, label_rx) = mpsc::channel();
+    let (classification_tx, _classification_rx) = mpsc::channel();
+    let lc = LayerClassifier::new(&area_schemes, "classifier", "v0", "ganglion", classifier(),
+        label_rx, classification_tx).unwrap();
+    assert_eq!(lc.area_id(), 1);
+
+    let (_label_tx, label_rx) = mpsc::channel();
+    let (classification_tx, _classification_rx) = mpsc::channel();
+    assert!(LayerClassifier::new(&area_schemes, "unknown", "v0", "ganglion", classifier(),
+        label_rx, classification_tx).is_err());
+}
@@ -28,8 +28,8 @@ fn cortex() {
 pub fn cycles(cortex: &mut Cortex, area_name: &str) {
     // let emsg = "\ntests::hybrid::test_cycles()";
 
-    /*cortex.areas_mut().by_key_mut(area_name).layer_test_mut(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().dens.syns().src_col_v_offs.cmd().fill(&[0], None).enq().unwrap();
-    cortex.areas_mut().by_key_mut(area_name).layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens.syns().src_col_v_offs.cmd().fill(&[0], None).enq().unwrap();
+    /*cortex.areas_mut().by_key_mut(area_name).layer_test_mut(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().dens.syns().src_col_v_offs.fill(&[0]).unwrap();
+    cortex.areas_mut().by_key_mut(area_name).layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens.syns().src_col_v_offs.fill(&[0]).unwrap();
 
     cortex.areas_mut().by_key_mut(area_name).layer_test_mut(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().dens.cycle();
     cortex.areas_mut().by_key_mut(area_name).layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens.cycle();*/
@@ -48,13 +48,13 @@ pub fn cycles(cortex: &mut Cortex, area_name: &str) {
     println!("Primary Spatial Associative Layer...");
     //let psal_name = cortex.areas().by_key(area_name).layer_test(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().layer_name();
     //cortex.enqueue_write(area_name, psal_name, &vec1);
-    cortex.areas_mut().by_key_mut(area_name).unwrap().layer_test_mut(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().soma().cmd().write(&vec1).offset(0).enq().unwrap();
+    cortex.areas_mut().by_key_mut(area_name).unwrap().layer_test_mut(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().soma().write_at(&vec1, 0).unwrap();
     syn_and_den_states(&mut cortex.areas_mut().by_key_mut(area_name).unwrap().layer_test_mut(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().dens_mut());
 
     println!("Primary Temporal Associative Layer...");
     //let ptal_name = cortex.areas().by_key(area_name).layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().layer_name();
     //cortex.enqueue_write(area_name, ptal_name, &vec1);
-    cortex.areas_mut().by_key_mut(area_name).unwrap().layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().soma().cmd().write(&vec1).offset(0).enq().unwrap();
+    cortex.areas_mut().by_key_mut(area_name).unwrap().layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().soma().write_at(&vec1, 0).unwrap();
     syn_and_den_states(&mut cortex.areas_mut().by_key_mut(area_name).unwrap().layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut());
 
 
@@ -75,9 +75,9 @@ fn pyr_preds(pyrs: &mut PyramidalLayer) {
     // let emsg = "\ntests::hybrid::test_pyr_preds()";
 
     io::stdout().flush().unwrap();
-    pyrs.dens_mut().states().default_queue().unwrap().finish().unwrap();
-    pyrs.dens_mut().states().cmd().fill(0, None).enq().unwrap();
-    pyrs.dens_mut().states().default_queue().unwrap().finish().unwrap();
+    pyrs.dens_mut().states().finish().unwrap();
+    pyrs.dens_mut().states().fill(0).unwrap();
+    pyrs.dens_mut().states().finish().unwrap();
 
     // Currently looking at the first tuft only:
     let tft_id = 0;
@@ -90,28 +90,26 @@ fn pyr_preds(pyrs: &mut PyramidalLayer) {
 
     // WRITE `255` TO THE DENDRITES CORRESPONDING TO THE FIRST AND LAST CELL
     // FOR THE FIRST TUFT ONLY
-    pyrs.dens_mut().states().default_queue().unwrap().finish().unwrap();
-    pyrs.dens_mut().states().cmd().fill(255, Some(dens_per_tuft)).offset(0).enq().unwrap();
-    pyrs.dens_mut().states().default_queue().unwrap().finish().unwrap();
+    pyrs.dens_mut().states().finish().unwrap();
+    pyrs.dens_mut().states().write_at(&vec![255; dens_per_tuft], 0).unwrap();
+    pyrs.dens_mut().states().finish().unwrap();
 
     let last_cel_den_idz =  den_tuft_len - dens_per_tuft;
 
     println!("\n\nDEBUG: pyrs.dens_mut().states().len(): {}\n", pyrs.dens_mut().states().len());
 
-    pyrs.dens_mut().states().default_queue().unwrap().finish().unwrap();
-    pyrs.dens_mut().states().cmd().fill(255, Some(den_tuft_len - last_cel_den_idz))
-        .offset(last_cel_den_idz).enq().unwrap();
-    pyrs.dens_mut().states().default_queue().unwrap().finish().unwrap();
+    pyrs.dens_mut().states().finish().unwrap();
+    pyrs.dens_mut().states().write_at(&vec![255; den_tuft_len - last_cel_den_idz], last_cel_den_idz).unwrap();
+    pyrs.dens_mut().states().finish().unwrap();
 
     // CYCLE THE PYRAMIDAL CELL ONLY, WITHOUT CYCLING IT'S DENS OR SYNS (WHICH WOULD OVERWRITE THE ABOVE)
     pyrs.cycle_solo();
 
-    pyrs.soma().default_queue().unwrap().finish().unwrap();
+    pyrs.soma().finish().unwrap();
 
     // READ THE PYRAMIDAL CELL SOMA STATES (PREDS)
     // pyrs.soma_mut().fill_vec();
-    let mut soma_vec = vec![0u8; pyrs.soma().len()];
-    pyrs.soma().cmd().read(&mut soma_vec).enq().unwrap();
+    let soma_vec = pyrs.soma().read().unwrap();
     //pyrs.dens_mut().states.print_simple();
     //pyrs.soma_mut().print_simple();
 
@@ -133,14 +131,14 @@ fn syn_and_den_states(dens: &mut Dendrites) {
     // let emsg = "\ntests::hybrid::test_syn_and_den_states()";
 
     io::stdout().flush().unwrap();
-    dens.syns_mut().src_col_v_offs().default_queue().unwrap().finish().unwrap();
-    dens.syns_mut().src_col_v_offs().cmd().fill(0, None).enq().unwrap();
-    dens.syns_mut().src_col_v_offs().default_queue().unwrap().finish().unwrap();
+    dens.syns_mut().src_col_v_offs().finish().unwrap();
+    dens.syns_mut().src_col_v_offs().fill(0).unwrap();
+    dens.syns_mut().src_col_v_offs().finish().unwrap();
 
     dens.syns().cycle_solo();
-    dens.states().default_queue().unwrap().finish().unwrap();
+    dens.states().finish().unwrap();
     dens.cycle_solo();
-    dens.states().default_queue().unwrap().finish().unwrap();
+    dens.states().finish().unwrap();
 
     // let syns_per_tft_l2: usize = dens.syns().dims().per_tft_l2_left() as usize;
     // let dens_per_tft_l2: usize = dens.dims().per_tft_l2_left() as usize;
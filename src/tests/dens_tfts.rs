@@ -26,9 +26,9 @@ fn cycle_random_pyrs() {
     // Zero all dendrite and synapse buffers:
     area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().set_all_to_zero(true);
 
-    area.axns().states().default_queue().unwrap().finish().unwrap();
-    area.axns().states().cmd().fill(0, None).enq().unwrap();
-    area.axns().states().default_queue().unwrap().finish().unwrap();
+    area.axns().states().finish().unwrap();
+    area.axns().states().fill(0).unwrap();
+    area.axns().states().finish().unwrap();
 
     // Set source slice to an unused slice for all synapses:
     let unused_slc_ranges = area.area_map().layer_map().layers_containing_tags_slc_range(LayerTags::UNUSED);
@@ -36,9 +36,9 @@ fn cycle_random_pyrs() {
     let zeroed_slc_id = unused_slc_ranges[0].start as u8;
     let unused_slc_id = unused_slc_ranges[1].start as u8;
 
-    area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
-    area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().cmd().fill(zeroed_slc_id, None).enq().unwrap();
-    area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
+    area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
+    area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().fill(zeroed_slc_id).unwrap();
+    area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
 
     // 'input' source slice which will be assigned to the synapses being tested:
     // let src_slc_ids = area.area_map().layer_map().layers_containing_tags_slc_range(map::FF_IN);
@@ -97,10 +97,9 @@ fn _test_rand_cel(area: &mut CorticalArea, zeroed_slc_id: u8, src_slc_id: u8, it
 
             let fill_size = den_syn_range.end - den_syn_range.start;
 
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().cmd()
-                .fill(src_slc_id, Some(fill_size)).offset(den_syn_range.start).enq().unwrap();
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().write_at(&vec![src_slc_id; fill_size], den_syn_range.start).unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
 
             // Write input:
             //area.write_to_axon(128, src_axon_idx);
@@ -125,8 +124,8 @@ fn _test_rand_cel(area: &mut CorticalArea, zeroed_slc_id: u8, src_slc_id: u8, it
 
             let mut den_state = vec![0];
             let mut cel_state = vec![0];
-            area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().states().cmd().read(&mut den_state).offset(den_idx as usize).enq().unwrap();
-            area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().soma().cmd().read(&mut cel_state).offset(cel_coords.idx() as usize).enq().unwrap();
+            area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().states().read_into(den_idx as usize, &mut den_state).unwrap();
+            area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().soma().read_into(cel_coords.idx() as usize, &mut cel_state).unwrap();
 
             // Finish queues:
             area.finish_queues();
@@ -169,10 +168,9 @@ fn _test_rand_cel(area: &mut CorticalArea, zeroed_slc_id: u8, src_slc_id: u8, it
             let fill_size = den_syn_range.end - den_syn_range.start;
             debug_assert_eq!(fill_size, den_syn_range.len());
 
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().cmd()
-                .fill(zeroed_slc_id, Some(fill_size)).offset(den_syn_range.start).enq().unwrap();
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().write_at(&vec![zeroed_slc_id; fill_size], den_syn_range.start).unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
 
             area.write_to_axon(0, src_axon_idx);
         }
@@ -207,9 +205,9 @@ fn cycle_random_dens() {
         .layers_containing_tags_slc_range(LayerTags::UNUSED)[0].clone();
     let zeroed_slc_id = zeroed_slc_range.start as u8;
 
-    area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().default_queue().unwrap().finish().unwrap();
-    area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().cmd().fill(zeroed_slc_id, None).enq().unwrap();
-    area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().default_queue().unwrap().finish().unwrap();
+    area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().finish().unwrap();
+    area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().fill(zeroed_slc_id).unwrap();
+    area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().finish().unwrap();
 
     // Finish queues:
     area.finish_queues();
@@ -284,12 +282,9 @@ fn cycle_random_dens() {
         // area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids.set_range_to(src_slc_id,
         //     cel_syn_range.clone()).unwrap();
 
-        area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().default_queue().unwrap().finish().unwrap();
+        area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().finish().unwrap();
 
-        area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().cmd()
-            .fill(src_slc_id, Some(cel_syn_range.len()))
-            .offset(cel_syn_range.start)
-            .enq().unwrap();
+        area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().write_at(&vec![src_slc_id; cel_syn_range.len()], cel_syn_range.start).unwrap();
 
         // Finish queues:
         area.finish_queues();
@@ -347,10 +342,7 @@ fn cycle_random_dens() {
 
         // area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids.set_range_to(zeroed_slc_id, cel_syn_range).unwrap();
 
-        area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().cmd()
-            .fill(zeroed_slc_id, Some(cel_syn_range.len()))
-            .offset(cel_syn_range.start)
-            .enq().unwrap();
+        area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns().src_slc_ids().write_at(&vec![zeroed_slc_id; cel_syn_range.len()], cel_syn_range.start).unwrap();
 
         area.write_to_axon(0, src_axon_idx);
 
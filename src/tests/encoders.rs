@@ -0,0 +1,232 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::f32::consts::PI;
+use cmn::{TractDims, TractFrameMut};
+use map::LayerAddress;
+use encode::{WavAudio, WavData, ImageDir, Image, IdxData, IdxType, TextSequence, TextSettings,
+    tokenize};
+use subcortex::InputGeneratorTract;
+
+
+/// Returns a mono 16-bit PCM WAV file containing a sine tone.
+fn sine_wav(freq: f32, sample_rate: u32, sample_count: usize) -> Vec<u8> {
+    let data_len = sample_count as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    let put_u32 = |bytes: &mut Vec<u8>, v: u32| bytes.extend_from_slice(&[v as u8,
+        (v >> 8) as u8, (v >> 16) as u8, (v >> 24) as u8]);
+
+    bytes.extend_from_slice(b"RIFF");
+    put_u32(&mut bytes, 36 + data_len);
+    bytes.extend_from_slice(b"WAVEfmt ");
+    put_u32(&mut bytes, 16);
+    bytes.extend_from_slice(&[1, 0, 1, 0]);
+    put_u32(&mut bytes, sample_rate);
+    put_u32(&mut bytes, sample_rate * 2);
+    bytes.extend_from_slice(&[2, 0, 16, 0]);
+    bytes.extend_from_slice(b"data");
+    put_u32(&mut bytes, data_len);
+
+    for i in 0..sample_count {
+        let val = ((2.0 * PI * freq * i as f32 / sample_rate as f32).sin() * 16384.0) as i16;
+        bytes.extend_from_slice(&[val as u8, (val >> 8) as u8]);
+    }
+    bytes
+}
+
+
+#[test]
+fn wav_audio() {
+    let wav = WavData::from_bytes(&sine_wav(1000.0, 16000, 4096)).unwrap();
+    assert_eq!(wav.sample_rate(), 16000);
+    assert_eq!(wav.samples().len(), 4096);
+    assert!(wav.samples().iter().all(|&s| s.abs() <= 0.5));
+    assert!(WavData::from_bytes(b"RIFF\0\0\0\0WAVE").is_err());
+
+    let path = env::temp_dir().join("bismit_wav_audio_test.wav");
+    File::create(&path).unwrap().write_all(&sine_wav(1000.0, 16000, 4096)).unwrap();
+    let file_name = path.to_str().unwrap().to_owned();
+
+    let dims = TractDims::new(1, 16, 16);
+    assert!(WavAudio::new(&[file_name.clone()], (100.0, 8000.0), 500, 256, 1, &dims).is_err());
+    assert!(WavAudio::new(&[file_name.clone()], (100.0, 8000.0), 512, 256, 1, &dims).unwrap()
+        .db_range((0.0, -60.0)).is_err());
+    let mut wa = WavAudio::new(&[file_name], (100.0, 8000.0), 512, 256, 1, &dims).unwrap()
+        .loop_files(false);
+
+    // The band containing the tone is the most intense:
+    let loudest = wa.intensities().iter().enumerate()
+        .fold((0, -1.0), |max, (v, &i)| if i > max.1 { (v, i) } else { max }).0;
+    let band_of = |freq: f32| ((freq / 100.0).ln() / (8000.0f32 / 100.0).ln() * 16.0) as usize;
+    assert_eq!(loudest, band_of(1000.0));
+
+    // One cell is active per audible band:
+    let mut buf = vec![0u8; dims.to_len()];
+    wa.write_into(&mut TractFrameMut::new(&mut buf, dims), LayerAddress::new(0, 0));
+    let active_bands = wa.intensities().iter().filter(|&&i| i >= 0.0).count();
+    assert_eq!(buf.iter().filter(|&&axn| axn > 0).count(), active_bands);
+    assert!(buf[(loudest * 16)..((loudest + 1) * 16)].iter().any(|&axn| axn > 0));
+
+    while !wa.is_finished() { wa.cycle_next(); }
+    assert!(wa.intensities().iter().all(|&i| i < 0.0));
+
+    fs::remove_file(&path).ok();
+}
+
+
+#[test]
+fn image_dir() {
+    let dir = env::temp_dir().join("bismit_image_dir_test");
+    fs::create_dir_all(&dir).unwrap();
+
+    // A white ASCII PGM, a pure red binary PPM, and an undecodable file:
+    let mut pgm = b"P2\n# comment\n4 4\n15\n".to_vec();
+    for _ in 0..16 { pgm.extend_from_slice(b"15 "); }
+    File::create(dir.join("a.pgm")).unwrap().write_all(&pgm).unwrap();
+    let mut ppm = b"P6 4 4 255\n".to_vec();
+    for _ in 0..16 { ppm.extend_from_slice(&[255, 0, 0]); }
+    File::create(dir.join("b.ppm")).unwrap().write_all(&ppm).unwrap();
+    File::create(dir.join("c.png")).unwrap().write_all(b"not an image").unwrap();
+
+    let white = Image::open(dir.join("a.pgm")).unwrap();
+    assert_eq!((white.width(), white.height(), white.channels()), (4, 4, 1));
+    assert!(white.data().iter().all(|&v| v == 255));
+    assert_eq!(Image::open(dir.join("b.ppm")).unwrap().channel(0), vec![255; 16]);
+    assert!(Image::open(dir.join("c.png")).is_err());
+
+    // Oversized or truncated rasters are errors rather than overflows or
+    // huge allocations:
+    let huge = env::temp_dir().join("bismit_image_dir_test_huge.pgm");
+    for header in [&b"P5 4294967296 4294967296 255\n"[..], &b"P6 100000 100000 65535\n\x00"[..],
+            &b"P2 100000 100000 255\n15 15"[..]].iter() {
+        File::create(&huge).unwrap().write_all(header).unwrap();
+        assert!(Image::open(&huge).is_err());
+    }
+    fs::remove_file(&huge).ok();
+
+    let dir_name = dir.to_str().unwrap().to_owned();
+    let addrs = [LayerAddress::new(0, 0), LayerAddress::new(0, 1), LayerAddress::new(0, 2)];
+    let dims = [TractDims::new(1, 8, 8); 3];
+    assert!(ImageDir::new(&[dir_name.clone()], &addrs[..2], &dims[..2], false, 1.0, 1).is_err());
+
+    let mut id = ImageDir::new(&[dir_name], &addrs, &dims, false, 1.0, 1).unwrap()
+        .loop_images(false);
+    let mut bufs = vec![vec![0u8; dims[0].to_len()]; 3];
+    let write_all = |id: &mut ImageDir, bufs: &mut Vec<Vec<u8>>| {
+        for (buf, (&addr, &d)) in bufs.iter_mut().zip(addrs.iter().zip(dims.iter())) {
+            id.write_into(&mut TractFrameMut::new(buf, d), addr);
+        }
+    };
+
+    // The white image activates every channel, the red image only the first:
+    write_all(&mut id, &mut bufs);
+    assert!(bufs.iter().all(|buf| buf.iter().any(|&axn| axn > 0)));
+    id.cycle_next();
+    write_all(&mut id, &mut bufs);
+    assert!(bufs[0].iter().any(|&axn| axn > 0));
+    assert!(bufs[1].iter().chain(bufs[2].iter()).all(|&axn| axn == 0));
+
+    // The undecodable file is skipped:
+    id.cycle_next();
+    assert!(id.image().is_none());
+    let bad_file = dir.join("c.png");
+    assert_eq!(id.skipped_files(), vec![bad_file.as_path()]);
+
+    // At least one file must decode:
+    assert!(ImageDir::new(&[bad_file.to_str().unwrap()], &addrs, &dims, false, 1.0, 1).is_err());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+
+#[test]
+fn idx_data() {
+    let dir = env::temp_dir();
+    let path = |name: &str| dir.join(format!("bismit_idx_test_{}", name));
+
+    // Every element type round trips, compressed or not:
+    IdxData::write(path("u8"), &[2, 3], &[0u8, 1, 2, 253, 254, 255], false).unwrap();
+    IdxData::write(path("i16.gz"), &[3], &[-300i16, 0, 300], true).unwrap();
+    IdxData::write(path("f64.gz"), &[2], &[-1.5f64, 2.25], true).unwrap();
+
+    let u8s = IdxData::new(path("u8"), false).unwrap();
+    assert_eq!(u8s.elem_type(), IdxType::U8);
+    assert_eq!(u8s.dims(), &[2, 3]);
+    assert_eq!(u8s.data(), &[0, 1, 2, 253, 254, 255]);
+
+    let i16s = IdxData::new(path("i16.gz"), false).unwrap();
+    assert_eq!(i16s.elements::<i16>().unwrap(), vec![-300, 0, 300]);
+    assert!(i16s.elements::<i32>().is_err());
+    assert_eq!(i16s.to_u8_normalized(), vec![0, 128, 255]);
+
+    let f64s = IdxData::new(path("f64.gz"), false).unwrap();
+    assert_eq!(f64s.elements::<f64>().unwrap(), vec![-1.5, 2.25]);
+
+    // Streaming reads one item at a time:
+    let mut stream = IdxData::new(path("u8"), true).unwrap();
+    assert_eq!(stream.next_item().unwrap(), Some(vec![0, 1, 2]));
+    assert_eq!(stream.next_item().unwrap(), Some(vec![253, 254, 255]));
+    assert_eq!(stream.next_item().unwrap(), None);
+    stream.rewind().unwrap();
+    assert_eq!(stream.next_item().unwrap(), Some(vec![0, 1, 2]));
+
+    // Malformed files produce errors:
+    File::create(path("bad")).unwrap().write_all(&[0, 0, 0x07, 1, 0, 0, 0, 1, 0]).unwrap();
+    assert!(IdxData::new(path("bad"), false).is_err());
+    File::create(path("short")).unwrap().write_all(&[0, 0, 0x08, 1, 0, 0, 0, 4, 1, 2]).unwrap();
+    assert!(IdxData::new(path("short"), false).is_err());
+    // Headers claiming more data than is present or addressable:
+    File::create(path("huge")).unwrap().write_all(&[0, 0, 0x08, 1, 0xFF, 0xFF, 0xFF, 0xFF, 1])
+        .unwrap();
+    assert!(IdxData::new(path("huge"), false).is_err());
+    File::create(path("overflow")).unwrap().write_all(&[0, 0, 0x0E, 3, 0xFF, 0xFF, 0xFF, 0xFF,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 1]).unwrap();
+    assert!(IdxData::new(path("overflow"), false).is_err());
+    assert!(IdxData::new(path("missing"), false).is_err());
+    assert!(IdxData::write(path("u8"), &[4], &[0u8; 3], false).is_err());
+
+    for name in &["u8", "i16.gz", "f64.gz", "bad", "short", "huge", "overflow"] {
+        fs::remove_file(path(name)).ok();
+    }
+}
+
+
+#[test]
+fn text_sequence() {
+    assert_eq!(tokenize("It was. The best!", true), vec!["It", "was", ".", "The", "best", "!"]);
+    assert_eq!(tokenize("ab c", false), vec!["a", "b", " ", "c"]);
+
+    let dims = TractDims::new(1, 32, 32);
+    let text = "the cat sat. the dog ran.";
+    let settings = TextSettings::new().words(true).sparsity(0.04).sentence_reset_cycles(2);
+    let mut ts = TextSequence::new(text, &dims, settings.clone()).unwrap();
+
+    // Representations are stable and of the configured sparsity:
+    let other = TextSequence::new(text, &dims, settings.clone()).unwrap();
+    assert_eq!(ts.sdr("cat"), other.sdr("cat"));
+    assert_eq!(ts.sdr("cat").len(), 41);
+    assert!(ts.sdr("cat") != ts.sdr("dog"));
+    assert_eq!(ts.sdr("zebra"), ts.sdr("<unk>"));
+
+    // Overlapping symbols share cells with their predecessor:
+    let ov = TextSequence::new(text, &dims, settings.clone().overlap(0.5)).unwrap();
+    let (prev, next) = (ov.vocabulary().symbol(1), ov.vocabulary().symbol(2));
+    let shared = ov.sdr(next).iter().filter(|c| ov.sdr(prev).contains(c)).count();
+    assert!(shared >= 20);
+
+    // Sentence ends are followed by blank cycles:
+    let mut buf = vec![0u8; dims.to_len()];
+    let mut seen = Vec::new();
+    for _ in 0..9 {
+        ts.write_into(&mut TractFrameMut::new(&mut buf, dims), LayerAddress::new(0, 0));
+        let active = buf.iter().filter(|&&axn| axn > 0).count();
+        seen.push(ts.current().map(|s| s.to_owned()));
+        assert_eq!(active, if ts.is_resetting() { 0 } else { 41 });
+        ts.cycle_next();
+    }
+    let seen: Vec<_> = seen.iter().map(|s| s.as_ref().map(|s| s.as_str()).unwrap_or("_")).collect();
+    assert_eq!(seen, vec!["the", "cat", "sat", ".", "_", "_", "the", "dog", "ran"]);
+
+    assert!(TextSequence::new("", &dims, TextSettings::new()).is_err());
+    assert!(TextSequence::new(text, &dims, TextSettings::new().sparsity(0.9)).is_err());
+}
@@ -0,0 +1,441 @@
+//! Kernel equivalence: compares the results of a cycle run with the OpenCL
+//! kernels against the same cycle run with the host reference kernels
+//! (`cortex::host_kernels`).
+//!
+//! A cortex using the device kernels is seeded with random axon and synapse
+//! state, its complete state is copied to a second cortex using the host
+//! kernels, then both are cycled once and every buffer named by a
+//! `SamplerKind` is compared.
+
+use std::env;
+use std::fs;
+use std::fmt;
+use rand::rngs::SmallRng;
+use rand::distributions::{Distribution, Range as RandRange};
+use cmn;
+use cortex::{Cortex, CorticalArea, CorticalAreaSettings, CorticalAreaTest, DataCellLayerTest,
+    SamplerKind, CelCoords, DenCoords, SynCoords, SynapsesTest};
+use map::DataCellKind;
+use tests::testbed;
+use tests::util::read_into_new_vec;
+use SrcOfs;
+
+/// The seed used for all randomized state so that any mismatch can be
+/// reproduced.
+const SEED: u64 = 0x5eed_0004;
+
+/// The number of synapses per layer to seed with random offsets and strengths.
+const SEEDED_SYN_COUNT: usize = 4096;
+
+/// The data cell layers of the primary area compared by the harness.
+pub static COMPARED_LAYER_NAMES: [&'static str; 2] = [testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME,
+    testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME];
+
+
+/// The contents of a buffer named by a `SamplerKind`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sample {
+    U8(Vec<u8>),
+    I8(Vec<i8>),
+}
+
+impl Sample {
+    /// Returns the index of the first element which differs from `other`
+    /// along with both values.
+    fn first_difference(&self, other: &Sample) -> Option<(usize, i32, i32)> {
+        fn diff<T: Copy + PartialEq + Into<i32>>(a: &[T], b: &[T]) -> Option<(usize, i32, i32)> {
+            assert_eq!(a.len(), b.len(), "Sample lengths differ.");
+            a.iter().zip(b.iter()).position(|(a, b)| a != b)
+                .map(|idx| (idx, a[idx].into(), b[idx].into()))
+        }
+
+        match (self, other) {
+            (&Sample::U8(ref a), &Sample::U8(ref b)) => diff(a, b),
+            (&Sample::I8(ref a), &Sample::I8(ref b)) => diff(a, b),
+            _ => panic!("Sample::first_difference: Sample types differ."),
+        }
+    }
+}
+
+
+/// The location of an element within a sampled buffer.
+#[derive(Debug, Clone)]
+pub enum Location {
+    Axon(u32),
+    Cell(CelCoords),
+    CellTuft(usize, CelCoords),
+    Dendrite(DenCoords),
+    Synapse(SynCoords),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Location::Axon(idx) => write!(f, "axon: {}", idx),
+            Location::Cell(ref cc) => write!(f, "{}", cc),
+            Location::CellTuft(tft_id, ref cc) => write!(f, "tft_id: {}, {}", tft_id, cc),
+            Location::Dendrite(ref dc) => write!(f, "{}", dc),
+            Location::Synapse(ref sc) => write!(f, "{}", sc),
+        }
+    }
+}
+
+
+/// The first differing element of a buffer after a cycle.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    /// The layer name or `None` for the area-wide axon space.
+    pub layer_name: Option<&'static str>,
+    pub kind: SamplerKind,
+    pub location: Location,
+    pub device: i32,
+    pub host: i32,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "layer: '{}', {:?}: {} (device: {}, host: {})",
+            self.layer_name.unwrap_or("<axons>"), self.kind, self.location, self.device, self.host)
+    }
+}
+
+
+/// Returns a cortex built from the unfiltered testbed schemes with the
+/// specified area settings.
+pub fn cortex_with_settings(settings: CorticalAreaSettings) -> Cortex {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = testbed::define_unfiltered_area_schemes();
+
+    Cortex::builder(layer_map_schemes, area_schemes)
+        .ca_settings(settings)
+        .input_generator("v0")
+        .build().unwrap()
+}
+
+
+/// Writes a random state to every axon and a random source offset and
+/// strength to `SEEDED_SYN_COUNT` synapses of each named layer.
+pub fn seed_area(area: &mut CorticalArea, layer_names: &[&'static str], rng: &mut SmallRng) {
+    let axon_count = area.axns().states().len();
+    let axn_state_range = RandRange::new(0, 256u32);
+
+    for axn_idx in 0..axon_count {
+        let axn_state = axn_state_range.sample(rng) as u8;
+        area.write_to_axon(axn_state, axn_idx as u32);
+    }
+
+    let reach = cmn::SYNAPSE_REACH as SrcOfs;
+    let ofs_range = RandRange::new(0 - reach, reach + 1);
+    let strength_range = RandRange::new(-128i32, 128);
+
+    for &layer_name in layer_names {
+        let syns = area.layer_test_mut(layer_name).unwrap().dens_mut().syns_mut();
+        let syn_idx_range = RandRange::new(0, syns.states().len());
+
+        for _ in 0..SEEDED_SYN_COUNT {
+            let syn_idx = syn_idx_range.sample(rng);
+            syns.set_src_offs(ofs_range.sample(rng), ofs_range.sample(rng), syn_idx);
+            syns.set_strength(strength_range.sample(rng) as i8, syn_idx);
+        }
+    }
+
+    area.finish_queues();
+}
+
+
+/// Returns every `SamplerKind` applicable to a layer.
+pub fn sampler_kinds(lyr: &DataCellLayerTest) -> Vec<SamplerKind> {
+    let addr = lyr.layer_addr();
+    let mut kinds = vec![SamplerKind::SomaStates(addr), SamplerKind::SomaEnergies(addr),
+        SamplerKind::SomaActivities(addr)];
+
+    if let Some(&DataCellKind::Pyramidal) = lyr.cell_scheme().data_cell_kind() {
+        kinds.extend_from_slice(&[SamplerKind::SomaFlagSets(addr),
+            SamplerKind::TuftStates(addr), SamplerKind::TuftBestDenIds(addr),
+            SamplerKind::TuftBestDenStatesRaw(addr), SamplerKind::TuftBestDenStates(addr),
+            SamplerKind::TuftPrevStates(addr), SamplerKind::TuftPrevBestDenIds(addr),
+            SamplerKind::TuftPrevBestDenStatesRaw(addr), SamplerKind::TuftPrevBestDenStates(addr)]);
+    }
+
+    kinds.extend_from_slice(&[SamplerKind::DenStates(addr), SamplerKind::DenStatesRaw(addr),
+        SamplerKind::DenEnergies(addr), SamplerKind::DenActivities(addr),
+        SamplerKind::DenThresholds(addr), SamplerKind::SynStates(addr),
+        SamplerKind::SynStrengths(addr), SamplerKind::SynSrcSlcIds(addr),
+        SamplerKind::SynSrcColVOffs(addr), SamplerKind::SynSrcColUOffs(addr),
+        SamplerKind::SynFlagSets(addr)]);
+
+    kinds
+}
+
+
+/// Reads the buffer named by `kind`.
+pub fn sample(area: &CorticalArea, lyr: &DataCellLayerTest, kind: &SamplerKind) -> Sample {
+    use self::Sample::{U8, I8};
+
+    match *kind {
+        SamplerKind::Axons(_) => U8(read_into_new_vec(area.axns().states())),
+        SamplerKind::SomaStates(_) => U8(read_into_new_vec(lyr.soma())),
+        SamplerKind::SomaEnergies(_) => U8(read_into_new_vec(lyr.energies())),
+        SamplerKind::SomaActivities(_) => U8(read_into_new_vec(lyr.activities())),
+        SamplerKind::SomaFlagSets(_) => U8(read_into_new_vec(lyr.flag_sets())),
+        SamplerKind::TuftStates(_) => U8(read_into_new_vec(lyr.tufts().states())),
+        SamplerKind::TuftBestDenIds(_) => U8(read_into_new_vec(lyr.tufts().best_den_ids())),
+        SamplerKind::TuftBestDenStatesRaw(_) => U8(read_into_new_vec(lyr.tufts().best_den_states_raw())),
+        SamplerKind::TuftBestDenStates(_) => U8(read_into_new_vec(lyr.tufts().best_den_states())),
+        SamplerKind::TuftPrevStates(_) => U8(read_into_new_vec(lyr.tufts().prev_states())),
+        SamplerKind::TuftPrevBestDenIds(_) => U8(read_into_new_vec(lyr.tufts().prev_best_den_ids())),
+        SamplerKind::TuftPrevBestDenStatesRaw(_) => U8(read_into_new_vec(lyr.tufts().prev_best_den_states_raw())),
+        SamplerKind::TuftPrevBestDenStates(_) => U8(read_into_new_vec(lyr.tufts().prev_best_den_states())),
+        SamplerKind::DenStates(_) => U8(read_into_new_vec(lyr.dens().states())),
+        SamplerKind::DenStatesRaw(_) => U8(read_into_new_vec(lyr.dens().states_raw())),
+        SamplerKind::DenEnergies(_) => U8(read_into_new_vec(lyr.dens().energies())),
+        SamplerKind::DenActivities(_) => U8(read_into_new_vec(lyr.dens().activities())),
+        SamplerKind::DenThresholds(_) => U8(read_into_new_vec(lyr.dens().thresholds())),
+        SamplerKind::SynStates(_) => U8(read_into_new_vec(lyr.dens().syns().states())),
+        SamplerKind::SynStrengths(_) => I8(read_into_new_vec(lyr.dens().syns().strengths())),
+        SamplerKind::SynSrcSlcIds(_) => U8(read_into_new_vec(lyr.dens().syns().src_slc_ids())),
+        SamplerKind::SynSrcColVOffs(_) => I8(read_into_new_vec(lyr.dens().syns().src_col_v_offs())),
+        SamplerKind::SynSrcColUOffs(_) => I8(read_into_new_vec(lyr.dens().syns().src_col_u_offs())),
+        SamplerKind::SynFlagSets(_) => U8(read_into_new_vec(lyr.dens().syns().flag_sets())),
+        SamplerKind::None | SamplerKind::Anomaly(_) => U8(Vec::new()),
+    }
+}
+
+
+/// Returns the coordinates of a cell from its index within a layer.
+pub fn cel_coords(lyr: &DataCellLayerTest, cel_idx: u32) -> CelCoords {
+    let dims = lyr.dims();
+    let slc_id_lyr = (cel_idx / dims.columns()) as u8;
+    let col_id = cel_idx % dims.columns();
+    CelCoords::new(lyr.base_axon_slc() + slc_id_lyr, slc_id_lyr, col_id / dims.u_size(),
+        col_id % dims.u_size(), dims.clone())
+}
+
+/// Returns the coordinates of a dendrite from its index within a layer.
+pub fn den_coords(lyr: &DataCellLayerTest, den_idx: u32) -> DenCoords {
+    let columns = lyr.dims().columns();
+    let mut tft_den_idz = 0;
+
+    for (tft_id, tft_dims) in lyr.dens().syns().tft_dims_by_tft().iter().enumerate() {
+        let dens_per_celtft = tft_dims.dens_per_tft();
+        let tft_den_count = lyr.dims().cells() * dens_per_celtft;
+
+        if den_idx < tft_den_idz + tft_den_count {
+            let den_id_tft = den_idx - tft_den_idz;
+            let dens_per_tftslc = columns * dens_per_celtft;
+            let slc_id_lyr = den_id_tft / dens_per_tftslc;
+            let den_id_tftslc = den_id_tft % dens_per_tftslc;
+            let cel_coords = cel_coords(lyr, (slc_id_lyr * columns) + (den_id_tftslc / dens_per_celtft));
+            let coords = DenCoords::new(cel_coords, tft_id, tft_den_idz, tft_dims.clone(),
+                den_id_tftslc % dens_per_celtft);
+            debug_assert_eq!(coords.idx, den_idx);
+            return coords;
+        }
+        tft_den_idz += tft_den_count;
+    }
+    panic!("den_coords: Dendrite index out of range: {}", den_idx);
+}
+
+/// Returns the coordinates of a synapse from its index within a layer.
+pub fn syn_coords(lyr: &DataCellLayerTest, syn_idx: u32) -> SynCoords {
+    let columns = lyr.dims().columns();
+    let mut tft_syn_idz = 0;
+
+    for (tft_id, tft_dims) in lyr.dens().syns().tft_dims_by_tft().iter().enumerate() {
+        let syns_per_den = tft_dims.syns_per_den();
+        let syns_per_celtft = tft_dims.dens_per_tft() * syns_per_den;
+        let tft_syn_count = lyr.dims().cells() * syns_per_celtft;
+
+        if syn_idx < tft_syn_idz + tft_syn_count {
+            let syn_id_tft = syn_idx - tft_syn_idz;
+            let syns_per_tftslc = columns * syns_per_celtft;
+            let slc_id_lyr = syn_id_tft / syns_per_tftslc;
+            let syn_id_tftslc = syn_id_tft % syns_per_tftslc;
+            let syn_id_celtft = syn_id_tftslc % syns_per_celtft;
+            let cel_coords = cel_coords(lyr, (slc_id_lyr * columns) + (syn_id_tftslc / syns_per_celtft));
+            let coords = SynCoords::new(cel_coords, tft_id, tft_syn_idz, tft_dims.clone(),
+                syn_id_celtft / syns_per_den, syn_id_celtft % syns_per_den);
+            debug_assert_eq!(coords.idx, syn_idx);
+            return coords;
+        }
+        tft_syn_idz += tft_syn_count;
+    }
+    panic!("syn_coords: Synapse index out of range: {}", syn_idx);
+}
+
+/// Returns the location of the element at `idx` within the buffer named by
+/// `kind`.
+pub fn locate(lyr: &DataCellLayerTest, kind: &SamplerKind, idx: usize) -> Location {
+    let idx = idx as u32;
+
+    match *kind {
+        SamplerKind::Axons(_) => Location::Axon(idx),
+        SamplerKind::SomaStates(_) | SamplerKind::SomaEnergies(_) |
+        SamplerKind::SomaActivities(_) | SamplerKind::SomaFlagSets(_) => {
+            Location::Cell(cel_coords(lyr, idx))
+        },
+        SamplerKind::TuftStates(_) | SamplerKind::TuftBestDenIds(_) |
+        SamplerKind::TuftBestDenStatesRaw(_) | SamplerKind::TuftBestDenStates(_) |
+        SamplerKind::TuftPrevStates(_) | SamplerKind::TuftPrevBestDenIds(_) |
+        SamplerKind::TuftPrevBestDenStatesRaw(_) | SamplerKind::TuftPrevBestDenStates(_) => {
+            let cells = lyr.dims().cells();
+            Location::CellTuft((idx / cells) as usize, cel_coords(lyr, idx % cells))
+        },
+        SamplerKind::DenStates(_) | SamplerKind::DenStatesRaw(_) |
+        SamplerKind::DenEnergies(_) | SamplerKind::DenActivities(_) |
+        SamplerKind::DenThresholds(_) => Location::Dendrite(den_coords(lyr, idx)),
+        _ => Location::Synapse(syn_coords(lyr, idx)),
+    }
+}
+
+
+/// Cycles both cortices once then compares every sampled buffer of each
+/// named layer within the area, returning the first mismatch of each buffer.
+pub fn cycle_and_compare(cortex_dev: &mut Cortex, cortex_host: &mut Cortex, area_name: &str,
+        layer_names: &[&'static str]) -> Vec<Mismatch> {
+    cortex_dev.cycle().unwrap();
+    cortex_host.cycle().unwrap();
+    cortex_dev.finish_queues();
+    cortex_host.finish_queues();
+
+    let area_dev = cortex_dev.areas().by_key(area_name).unwrap();
+    let area_host = cortex_host.areas().by_key(area_name).unwrap();
+    let mut mismatches = Vec::new();
+
+    let diff = Sample::U8(read_into_new_vec(area_dev.axns().states()))
+        .first_difference(&Sample::U8(read_into_new_vec(area_host.axns().states())));
+
+    if let Some((idx, device, host)) = diff {
+        mismatches.push(Mismatch {
+            layer_name: None,
+            kind: SamplerKind::Axons(None),
+            location: Location::Axon(idx as u32),
+            device,
+            host,
+        });
+    }
+
+    for &layer_name in layer_names {
+        let lyr_dev = area_dev.layer_test(layer_name).unwrap();
+        let lyr_host = area_host.layer_test(layer_name).unwrap();
+
+        for kind in sampler_kinds(lyr_dev) {
+            let diff = sample(area_dev, lyr_dev, &kind)
+                .first_difference(&sample(area_host, lyr_host, &kind));
+
+            if let Some((idx, device, host)) = diff {
+                mismatches.push(Mismatch {
+                    layer_name: Some(layer_name),
+                    location: locate(lyr_dev, &kind, idx),
+                    kind,
+                    device,
+                    host,
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+
+/// Copies the complete state of `cortex_dev` to a new host kernel cortex
+/// built with `host_settings` (by way of a snapshot named `snapshot_name`
+/// within the temp dir), calls `prepare` on both, then cycles and compares
+/// them with `cycle_and_compare`, panicking if any buffer differs.
+///
+/// The snapshot is loaded back into `cortex_dev` as well so that both
+/// continue from the same restored rng seeds.
+pub fn assert_host_equivalent<F>(cortex_dev: &mut Cortex, host_settings: CorticalAreaSettings,
+        snapshot_name: &str, prepare: F)
+        where F: FnOnce(&mut Cortex, &mut Cortex) {
+    let path = env::temp_dir().join(snapshot_name);
+    cortex_dev.save_state(&path).unwrap();
+    cortex_dev.load_state(&path).unwrap();
+
+    let mut cortex_host = cortex_with_settings(host_settings);
+    cortex_host.load_state(&path).unwrap();
+    fs::remove_file(&path).ok();
+
+    prepare(cortex_dev, &mut cortex_host);
+
+    let mismatches = cycle_and_compare(cortex_dev, &mut cortex_host,
+        testbed::PRIMARY_AREA_NAME, &COMPARED_LAYER_NAMES);
+
+    for mismatch in mismatches.iter() {
+        println!("{}", mismatch);
+    }
+    assert!(mismatches.is_empty(), "Device and host kernel results differ for {} buffer(s).",
+        mismatches.len());
+}
+
+
+#[test]
+fn device_and_host_kernels_equivalent() {
+    let mut rng = cmn::rng_from_u64(SEED);
+
+    let mut cortex_dev = cortex_with_settings(CorticalAreaSettings::new().seed(SEED));
+    seed_area(cortex_dev.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap(),
+        &COMPARED_LAYER_NAMES, &mut rng);
+
+    assert_host_equivalent(&mut cortex_dev, CorticalAreaSettings::new().host_kernels().seed(SEED),
+        "bismit_test_kernel_equivalence.bsnap", |_, _| ());
+}
+
+
+/// As above, with a random neuromodulation value (including negative values)
+/// for each column of the primary area.
+#[test]
+fn device_and_host_kernels_equivalent_modulated() {
+    let mut rng = cmn::rng_from_u64(SEED);
+
+    let mut cortex_dev = cortex_with_settings(CorticalAreaSettings::new().seed(SEED));
+    seed_area(cortex_dev.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap(),
+        &COMPARED_LAYER_NAMES, &mut rng);
+
+    let col_count = cortex_dev.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap()
+        .dims().columns();
+    let neuromod_range = RandRange::new(-127i32, 128);
+    let neuromods: Vec<i8> = (0..col_count).map(|_| neuromod_range.sample(&mut rng) as i8)
+        .collect();
+
+    assert_host_equivalent(&mut cortex_dev, CorticalAreaSettings::new().host_kernels().seed(SEED),
+            "bismit_test_kernel_equivalence_modulated.bsnap", |cortex_dev, cortex_host| {
+        cortex_dev.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap()
+            .set_neuromodulation_by_column(&neuromods).unwrap();
+        cortex_host.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap()
+            .set_neuromodulation_by_column(&neuromods).unwrap();
+    });
+}
+
+
+/// Areas using host kernels need no OpenCL platform and keep every buffer in
+/// host memory.
+#[test]
+fn host_kernels_cortex_cycles() {
+    let mut cortex = cortex_with_settings(CorticalAreaSettings::new().host_kernels().seed(SEED));
+
+    {
+        let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+        assert!(area.ocl_pq().is_none());
+        assert!(area.axns().states().is_host());
+    }
+
+    for _ in 0..20 {
+        cortex.cycle().unwrap();
+    }
+    cortex.finish_queues();
+}
+
+
+/// Sensory filters have no host implementation.
+#[test]
+fn host_kernels_reject_filter_chains() {
+    let result = Cortex::builder(testbed::define_layer_map_schemes(),
+            testbed::define_area_schemes())
+        .ca_settings(CorticalAreaSettings::new().host_kernels())
+        .input_generator("v0")
+        .build();
+
+    assert!(result.is_err());
+}
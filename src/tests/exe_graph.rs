@@ -0,0 +1,97 @@
+use cortex::{Cortex, CorticalAreaSettings};
+use subcortex::InputGenerator;
+use map::{DependencyKind, ExecutionGraph, CommandRelations, CorticalBuffer, LayerAddress,
+    ValidationIssue};
+use tests::testbed;
+
+
+#[test]
+fn exe_graph_export() {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = testbed::define_area_schemes();
+    let input_gen = InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").unwrap();
+
+    let mut cortex = Cortex::builder(layer_map_schemes, area_schemes)
+        .subcortical_nucleus(input_gen)
+        .ca_settings(CorticalAreaSettings::new().queue_profiling())
+        .build().unwrap();
+
+    for _ in 0..10 {
+        cortex.cycle().unwrap();
+    }
+    cortex.finish_queues();
+
+    let exe_graph = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap().exe_graph();
+    assert!(exe_graph.profiling());
+
+    let edges = exe_graph.dependency_edges();
+    assert!(edges.iter().any(|e| e.kind == DependencyKind::ReadAfterWrite));
+    assert!(edges.iter().all(|e| e.from < exe_graph.cmd_count() && e.to < exe_graph.cmd_count()));
+
+    // Device commands have completed and been timed:
+    assert!((0..exe_graph.cmd_count()).any(|i| exe_graph.cmd(i).unwrap().duration_ns().is_some()));
+
+    let dot = exe_graph.to_dot();
+    assert!(dot.starts_with("digraph execution_graph {"));
+    for cmd_idx in 0..exe_graph.cmd_count() {
+        assert!(dot.contains(&format!("    cmd{} [label=", cmd_idx)));
+    }
+    assert_eq!(dot.matches(" -> ").count(), edges.len());
+
+    let json = exe_graph.to_json();
+    assert!(json.starts_with("{\"commands\":[{\"idx\":0,"));
+    assert!(json.ends_with("]}"));
+    assert_eq!(json.matches("\"from\":").count(), edges.len());
+}
+
+
+fn soma(buffer_id: u64) -> CorticalBuffer {
+    CorticalBuffer::DataCellSomaLayer { buffer_id, layer_addr: LayerAddress::new(0, 0) }
+}
+
+
+#[test]
+fn exe_graph_validation() {
+    // Reader ordered before its writer:
+    let mut exe_graph = ExecutionGraph::new();
+    let writer = exe_graph.add_command(CommandRelations::cortical_kernel("writer",
+        vec![], vec![soma(1)])).unwrap();
+    let reader = exe_graph.add_command(CommandRelations::cortical_kernel("reader",
+        vec![soma(1)], vec![soma(2)])).unwrap();
+    let isolated = exe_graph.add_command(CommandRelations::cortical_kernel("isolated",
+        vec![], vec![soma(3)])).unwrap();
+    exe_graph.order_command(reader).unwrap();
+    exe_graph.order_command(writer).unwrap();
+    exe_graph.order_command(isolated).unwrap();
+
+    exe_graph.lock().unwrap();
+    let issues = exe_graph.issues().to_vec();
+    assert_eq!(issues.len(), 2);
+    match issues[0] {
+        ValidationIssue::StaleRead { reader_idx: 0, writer_idx: 1, ref reader, .. } =>
+            assert!(reader.contains("'reader'")),
+        ref i => panic!("Unexpected issue: {}", i),
+    }
+    match issues[1] {
+        ValidationIssue::Unreachable { cmd_idx: 2, .. } => (),
+        ref i => panic!("Unexpected issue: {}", i),
+    }
+
+    // Strict validation turns the same issues into errors:
+    exe_graph.unlock();
+    exe_graph.set_strict(true);
+    exe_graph.order_command(reader).unwrap();
+    exe_graph.order_command(writer).unwrap();
+    exe_graph.order_command(isolated).unwrap();
+    assert!(exe_graph.lock().is_err());
+
+    // Commands never ordered are always errors:
+    let mut exe_graph = ExecutionGraph::new();
+    let writer = exe_graph.add_command(CommandRelations::cortical_kernel("writer",
+        vec![], vec![soma(1)])).unwrap();
+    exe_graph.add_command(CommandRelations::cortical_kernel("reader",
+        vec![soma(1)], vec![])).unwrap();
+    exe_graph.order_command(writer).unwrap();
+    let err = exe_graph.lock().unwrap_err();
+    assert!(err.to_string().contains("never ordered"));
+}
@@ -161,8 +161,7 @@ pub fn smoother_overlap() {
         let energy_level_raw = smoother_layers * total_cycles;
         let energy_level = if energy_level_raw > 255 { 255 } else { energy_level_raw as u8 };
 
-        let mut cel_energies_vec = vec![0; l4_spt_cel_enrgs.len()];
-        l4_spt_cel_enrgs.read(&mut cel_energies_vec).enq().unwrap();
+        let cel_energies_vec = l4_spt_cel_enrgs.read().unwrap();
 
         for cel_idx in 0..cell_count {
             if cel_energies_vec[cel_idx] != energy_level {
@@ -0,0 +1,89 @@
+use cortex::host_kernels::{self, AxonSliceGeometry};
+
+
+#[test]
+fn syn_fire() {
+    assert_eq!(host_kernels::syn_fire(0), 0);
+    assert_eq!(host_kernels::syn_fire(1), 128);
+    assert_eq!(host_kernels::syn_fire(64), 128 + 32);
+    assert_eq!(host_kernels::syn_fire(255), 128 + 127);
+}
+
+
+#[test]
+fn rnd_mix_deterministic() {
+    for &(rnd, seed) in &[(0, 0), (1, 2), (-17, 9999), (i32::max_value(), i32::min_value())] {
+        assert_eq!(host_kernels::rnd_mix(rnd, seed), host_kernels::rnd_mix(rnd, seed));
+    }
+    assert!(host_kernels::rnd_mix(12345, 1) != host_kernels::rnd_mix(12345, 2));
+}
+
+
+#[test]
+fn tft_cycle() {
+    // Two cells, four dendrites each, tuft starting at cell 2 of 4:
+    let den_states_raw = [0, 9, 3, 9,  1, 1, 1, 200];
+    let den_states = [10, 20, 5, 0,  255, 255, 255, 255];
+    let mut prev_best_den_ids = vec![0u8; 4];
+    let mut prev_best_den_states_raw = vec![0u8; 4];
+    let mut prev_best_den_states = vec![0u8; 4];
+    let mut prev_states = vec![0u8; 4];
+    let mut best_den_ids = vec![0, 0, 7, 7];
+    let mut best_den_states_raw = vec![0, 0, 8, 8];
+    let mut best_den_states = vec![0, 0, 9, 9];
+    let mut states = vec![0, 0, 6, 6];
+
+    host_kernels::tft_cycle(&den_states_raw, &den_states, 2, 0, 4, 1, 2,
+        &mut prev_best_den_ids, &mut prev_best_den_states_raw, &mut prev_best_den_states,
+        &mut prev_states, &mut best_den_ids, &mut best_den_states_raw, &mut best_den_states,
+        &mut states);
+
+    // Previous values are shifted over and cells outside the tuft are untouched:
+    assert_eq!(prev_best_den_ids, [0, 0, 7, 7]);
+    assert_eq!(prev_best_den_states_raw, [0, 0, 8, 8]);
+    assert_eq!(prev_best_den_states, [0, 0, 9, 9]);
+    assert_eq!(prev_states, [0, 0, 6, 6]);
+
+    // The first strictly greatest dendrite wins:
+    assert_eq!(best_den_ids, [0, 0, 1, 3]);
+    assert_eq!(best_den_states_raw, [0, 0, 9, 200]);
+    assert_eq!(best_den_states, [0, 0, 20, 255]);
+
+    // (10 + 20 + 5) >> 1 and a saturated sum:
+    assert_eq!(states, [0, 0, 17, 255]);
+}
+
+
+#[test]
+fn inhib_intra_column() {
+    // Three slices of 2x2 cells:
+    let (depth, v_size, u_size) = (3u8, 2u32, 2u32);
+    let geo = AxonSliceGeometry::uniform(depth as usize, v_size, u_size);
+    let cel_states = [
+        10, 0, 40, 7,
+        20, 0, 40, 7,
+        5, 0, 30, 7,
+    ];
+    let mut axn_states = vec![99u8; cel_states.len()];
+
+    host_kernels::inhib_intra_column(&cel_states, &geo, depth, v_size, u_size, 0,
+        &mut axn_states);
+
+    // The running best is reset to zero by a less active cell in columns 0 and 2,
+    // which lets all three cells through (a quirk of the device kernel):
+    assert_eq!(axn_states, cel_states.to_vec());
+
+    let cel_states = [
+        5, 0, 30, 1,
+        20, 0, 40, 2,
+        20, 0, 40, 3,
+    ];
+    host_kernels::inhib_intra_column(&cel_states, &geo, depth, v_size, u_size, 0,
+        &mut axn_states);
+
+    assert_eq!(axn_states, [
+        0, 0, 0, 0,
+        20, 0, 40, 0,
+        20, 0, 40, 3,
+    ]);
+}
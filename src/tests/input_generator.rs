@@ -0,0 +1,90 @@
+use map::{AreaScheme, AreaSchemeList, EncoderScheme, LayerMapSchemeList, LayerMapScheme,
+    LayerMapKind, LayerScheme, AxonTopology, AxonDomain, AxonTag};
+use cortex::Cortex;
+use subcortex::InputGenerator;
+use tests::testbed;
+
+const AREA_SIDE: u32 = 24;
+
+
+fn area_schemes(encoder: EncoderScheme) -> AreaSchemeList {
+    AreaSchemeList::new()
+        .area(AreaScheme::new("v0", "external", AREA_SIDE).encoder(encoder))
+}
+
+
+#[test]
+fn handle_sensory_tract() {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = area_schemes(EncoderScheme::SensoryTract);
+    let input_gen = InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").unwrap();
+    let handle = input_gen.handle();
+    let tract_len = (AREA_SIDE * AREA_SIDE) as usize;
+
+    assert!(handle.write_tract(&vec![0u8; tract_len]).is_ok());
+    assert!(handle.write_tract(&vec![0u8; tract_len - 1]).is_err());
+    assert!(handle.write_values(&[0.5]).is_err());
+    assert!(handle.set_encoder_ranges(vec![(0., 1.)]).is_err());
+    assert_eq!(handle.value_count(), None);
+}
+
+
+#[test]
+fn handle_vector_encoder() {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = area_schemes(EncoderScheme::VectorEncoder { ranges: vec![(0., 1.)] });
+    let input_gen = InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").unwrap();
+    let handle = input_gen.handle();
+
+    // Exactly one value per layer is accepted:
+    assert_eq!(handle.value_count(), Some(1));
+    assert!(handle.write_values(&[0.5]).is_ok());
+    assert!(handle.write_values(&[0.5, 0.25]).is_err());
+    assert!(handle.write_values(&[]).is_err());
+    assert!(handle.set_encoder_ranges(vec![(-1., 1.)]).is_ok());
+    assert!(handle.set_encoder_ranges(vec![(-1., 1.), (0., 1.)]).is_err());
+    assert!(handle.write_tract(&[0u8; 4]).is_err());
+}
+
+
+/// Single layer encoders return an error for an area with two layers.
+#[test]
+fn single_layer_encoders() {
+    let layer_map_schemes = LayerMapSchemeList::new()
+        .lmap(LayerMapScheme::new("external_pair", LayerMapKind::Subcortical)
+            .layer(LayerScheme::define("ganglion_0")
+                .depth(1)
+                .axonal(AxonTopology::Spatial)
+                .axon_domain(AxonDomain::output(&[AxonTag::unique()]))
+            )
+            .layer(LayerScheme::define("ganglion_1")
+                .depth(1)
+                .axonal(AxonTopology::Spatial)
+                .axon_domain(AxonDomain::output(&[AxonTag::unique()]))
+            )
+        );
+
+    for encoder in vec![EncoderScheme::SensoryTract,
+            EncoderScheme::ScalarSequence { range: (0., 1.), incr: 0.1 }] {
+        let area_schemes = AreaSchemeList::new()
+            .area(AreaScheme::new("v0", "external_pair", AREA_SIDE).encoder(encoder));
+        assert!(InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").is_err());
+    }
+}
+
+
+/// Errors raised within the encoder thread are returned by a later cycle.
+#[test]
+fn encoder_errors_returned() {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = area_schemes(EncoderScheme::Custom)
+        .area(AreaScheme::new("v1", "visual", AREA_SIDE).eff_areas(vec!["v0"]));
+    let input_gen = InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").unwrap();
+    let mut cortex = Cortex::builder(layer_map_schemes, area_schemes)
+        .subcortical_nucleus(input_gen)
+        .build().unwrap();
+
+    // No custom encoder has been set. The failed write of the first cycle is
+    // returned by the third at the latest:
+    assert!((0..3).map(|_| cortex.cycle()).any(|res| res.is_err()));
+}
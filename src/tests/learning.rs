@@ -141,18 +141,18 @@ impl LearningTestBed {
             // Zero all dendrite and synapse buffers:
             area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().set_all_to_zero(true);
 
-            area.axns().states().default_queue().unwrap().finish().unwrap();
-            area.axns().states().cmd().fill(0, None).enq().unwrap();
-            area.axns().states().default_queue().unwrap().finish().unwrap();
+            area.axns().states().finish().unwrap();
+            area.axns().states().fill(0).unwrap();
+            area.axns().states().finish().unwrap();
 
             // Set source slice to an unused slice for all synapses:
             let unused_slc_ranges = area.area_map().layer_map().layers_containing_tags_slc_range(LayerTags::UNUSED);
             assert!(unused_slc_ranges.len() >= 3, "Make sure at least three axon layers have the UNUSED_TESTING flag.");
             let unused_slc_id = unused_slc_ranges[0].start as u8;
 
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().cmd().fill(unused_slc_id, None).enq().unwrap();
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().default_queue().unwrap().finish().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().fill(unused_slc_id).unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().finish().unwrap();
 
             // Finish queues:
             area.finish_queues();
@@ -484,7 +484,7 @@ impl LearningTestBed {
             "\n ====================== {}[{}] 1B ====================== \n", flpd_str, i); }
 
         if print_debug { println!("Finishing queue..."); }
-        area.ocl_pq().queue().finish().unwrap();
+        area.finish_queues();
 
         util::ptal_alco(area, PtalAlco::LEARN, print_debug);
 
@@ -741,28 +741,25 @@ impl LearningTestBed {
 
         printlnc!(yellow: "Cleaning up...");
 
-        area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids().cmd()
-            .fill(self.unused_slc_id, Some(self.syn_coords.syn_idx_range_celtft().len()))
-            .offset(self.syn_coords.syn_idx_range_celtft().start).enq().unwrap();
+        let syn_range = self.syn_coords.syn_idx_range_celtft();
+
+        area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_slc_ids()
+            .write_at(&vec![self.unused_slc_id; syn_range.len()], syn_range.start).unwrap();
 
-        area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_col_v_offs().cmd()
-            .fill(0, Some(self.syn_coords.syn_idx_range_celtft().len()))
-            .offset(self.syn_coords.syn_idx_range_celtft().start).enq().unwrap();
+        area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_col_v_offs()
+            .write_at(&vec![0; syn_range.len()], syn_range.start).unwrap();
 
-        area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_col_u_offs().cmd()
-            .fill(0, Some(self.syn_coords.syn_idx_range_celtft().len()))
-            .offset(self.syn_coords.syn_idx_range_celtft().start).enq().unwrap();
+        area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().src_col_u_offs()
+            .write_at(&vec![0; syn_range.len()], syn_range.start).unwrap();
 
         if zero_strengths {
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().strengths().cmd()
-            .fill(0, Some(self.syn_coords.syn_idx_range_celtft().len()))
-            .offset(self.syn_coords.syn_idx_range_celtft().start).enq().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().strengths()
+            .write_at(&vec![0; syn_range.len()], syn_range.start).unwrap();
         }
 
         if zero_flag_sets {
-            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().flag_sets().cmd()
-            .fill(0, Some(self.syn_coords.syn_idx_range_celtft().len()))
-            .offset(self.syn_coords.syn_idx_range_celtft().start).enq().unwrap();
+            area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens_mut().syns_mut().flag_sets()
+            .write_at(&vec![0; syn_range.len()], syn_range.start).unwrap();
         }
 
         // Finish queues:
@@ -5,6 +5,23 @@ mod multi;
 mod hex_tile_fields;
 mod cortex;
 mod async;
+mod snapshot;
+mod scheme_config;
+mod host_kernels;
+mod arm;
+mod input_generator;
+mod remote;
+mod placement;
+mod exe_graph;
+mod recorder;
+mod replay;
+mod encoders;
+mod classifier;
+mod anomaly;
+mod regrowth;
+mod seed;
+mod neuromodulation;
+pub mod equivalence;
 pub mod testbed;
 pub mod testbed_vibi;
 pub mod util;
@@ -0,0 +1,128 @@
+use cmn::{CmnResult, NEUROMODULATION_UNITY};
+use cortex::{Cortex, CorticalAreas, CorticalAreaSettings, CorticalAreaTest, CompletionPool,
+    host_kernels};
+use map::LayerAddress;
+use subcortex::{SubcorticalNucleus, SubcorticalNucleusLayer, Thalamus, InputGenerator};
+use tests::{testbed, util};
+use tests::equivalence::{COMPARED_LAYER_NAMES, cortex_with_settings};
+
+
+const CYCLES: usize = 20;
+
+
+/// Publishes a constant neuromodulation value to an area before each cycle.
+struct Neuromodulator {
+    target_area_name: &'static str,
+    value: i8,
+}
+
+impl SubcorticalNucleus for Neuromodulator {
+    fn create_pathways(&mut self, _thal: &mut Thalamus,
+            _cortical_areas: &mut CorticalAreas) -> CmnResult<()> {
+        Ok(())
+    }
+
+    fn pre_cycle(&mut self, _thal: &mut Thalamus, cortical_areas: &mut CorticalAreas,
+            _completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        cortical_areas.by_key_mut(self.target_area_name)
+            .ok_or("Neuromodulator::pre_cycle: Target area not found.")?
+            .set_neuromodulation(self.value)
+    }
+
+    fn post_cycle(&mut self, _thal: &mut Thalamus, _cortical_areas: &mut CorticalAreas,
+            _completion_pool: &mut CompletionPool) -> CmnResult<()> {
+        Ok(())
+    }
+
+    fn layer(&self, _addr: LayerAddress) -> Option<&SubcorticalNucleusLayer> { None }
+    fn area_name<'a>(&'a self) -> &'a str { "neuromodulator" }
+    fn area_id(&self) -> usize { 0 }
+}
+
+
+/// Returns the synapse strengths of each compared layer of the primary area.
+fn syn_strengths(cortex: &Cortex) -> Vec<Vec<i8>> {
+    cortex.finish_queues();
+    let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    COMPARED_LAYER_NAMES.iter()
+        .map(|&layer_name| util::read_into_new_vec(
+            area.layer_test(layer_name).unwrap().dens().syns().strengths()))
+        .collect()
+}
+
+
+#[test]
+fn neuromod_step() {
+    for &step in [-1, 0, 1].iter() {
+        for syn_idx in 0..256 {
+            assert_eq!(host_kernels::neuromod_step(step, NEUROMODULATION_UNITY, 7, syn_idx), step);
+            assert_eq!(host_kernels::neuromod_step(step, -NEUROMODULATION_UNITY, 7, syn_idx), -step);
+            assert_eq!(host_kernels::neuromod_step(step, 0, 7, syn_idx), 0);
+        }
+    }
+
+    // Roughly half of all steps are taken at half strength:
+    let taken = (0..4096).filter(|&syn_idx| host_kernels::neuromod_step(1, 64, 7, syn_idx) != 0)
+        .count();
+    assert!(taken > 1700 && taken < 2400, "taken: {}", taken);
+}
+
+
+#[test]
+fn invalid_values() {
+    let mut cortex = cortex_with_settings(CorticalAreaSettings::new());
+    let area = cortex.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap();
+    let col_count = area.dims().columns() as usize;
+
+    assert!(area.set_neuromodulation(-128).is_err());
+    assert!(area.set_neuromodulation_by_column(&vec![0; col_count - 1]).is_err());
+    let mut values = vec![0; col_count];
+    values[col_count - 1] = -128;
+    assert!(area.set_neuromodulation_by_column(&values).is_err());
+
+    // Rejected values leave the existing values unchanged:
+    assert!(area.neuromodulation().values().iter().all(|&v| v == NEUROMODULATION_UNITY));
+}
+
+
+/// A modulation of zero prevents all learning.
+#[test]
+fn zero_modulation_freezes_strengths() {
+    let mut cortex = cortex_with_settings(CorticalAreaSettings::new().disable_regrowth());
+    cortex.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap()
+        .set_neuromodulation(0).unwrap();
+    let strengths_before = syn_strengths(&cortex);
+
+    for _ in 0..CYCLES { cortex.cycle().unwrap(); }
+    assert!(syn_strengths(&cortex) == strengths_before);
+
+    // Restoring the modulation resumes learning:
+    cortex.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap()
+        .set_neuromodulation(NEUROMODULATION_UNITY).unwrap();
+    for _ in 0..CYCLES { cortex.cycle().unwrap(); }
+    assert!(syn_strengths(&cortex) != strengths_before);
+}
+
+
+/// Values published by a nucleus from within `::pre_cycle` take effect
+/// during the same cycle.
+#[test]
+fn nucleus_publishes_modulation() {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = testbed::define_area_schemes();
+    let input_gen = InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").unwrap();
+
+    let mut cortex = Cortex::builder(layer_map_schemes, area_schemes)
+        .ca_settings(CorticalAreaSettings::new().disable_regrowth())
+        .subcortical_nucleus(input_gen)
+        .subcortical_nucleus(Neuromodulator { target_area_name: testbed::PRIMARY_AREA_NAME,
+            value: 0 })
+        .build().unwrap();
+    let strengths_before = syn_strengths(&cortex);
+
+    for _ in 0..CYCLES { cortex.cycle().unwrap(); }
+
+    let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    assert!(area.neuromodulation().values().iter().all(|&v| v == 0));
+    assert!(syn_strengths(&cortex) == strengths_before);
+}
@@ -0,0 +1,45 @@
+use ocl::{Platform, Device};
+use cmn::CmnResult;
+use cortex::{Cortex, DeviceSelector, Placement};
+use subcortex::InputGenerator;
+use tests::testbed;
+
+
+fn build(placement: Placement) -> CmnResult<Cortex> {
+    let layer_map_schemes = testbed::define_layer_map_schemes();
+    let area_schemes = testbed::define_area_schemes();
+    let input_gen = InputGenerator::new(&layer_map_schemes, &area_schemes, "v0").unwrap();
+
+    Cortex::builder(layer_map_schemes, area_schemes)
+        .subcortical_nucleus(input_gen)
+        .placement(placement)
+        .build()
+}
+
+
+#[test]
+fn placement_explicit_and_round_robin() {
+    let mut cortex = build(Placement::new()
+        .default_selector(DeviceSelector::RoundRobin)
+        .area(testbed::PRIMARY_AREA_NAME, DeviceSelector::Index { platform: 0, device: 0 })
+    ).unwrap();
+
+    let expected = Device::list_all(Platform::list()[0]).unwrap()[0];
+    assert!(cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap().device() == Some(expected));
+
+    for _ in 0..20 {
+        cortex.cycle().unwrap();
+    }
+    cortex.finish_queues();
+}
+
+
+#[test]
+fn placement_invalid() {
+    assert!(build(Placement::new()
+        .area("nonexistent", DeviceSelector::Default)).is_err());
+
+    assert!(build(Placement::new()
+        .area(testbed::PRIMARY_AREA_NAME, DeviceSelector::Index { platform: 0, device: 9999 })
+    ).is_err());
+}
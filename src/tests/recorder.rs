@@ -0,0 +1,62 @@
+use std::env;
+use std::fs;
+use cortex::{DataCellLayer, SamplerKind};
+use cortex::recorder::{Recorder, RecordingReader, Compression};
+use tests::testbed;
+
+
+#[test]
+fn record_and_replay() {
+    let path = env::temp_dir().join("bismit_recorder_test.brec");
+    let mut cortex = testbed::fresh_cortex();
+
+    let lyr_addr = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap()
+        .layer(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().layer_addr();
+
+    let mut recorder = Recorder::create(&path, Compression::ZeroRuns).unwrap().chunk_cycles(4)
+        .unwrap();
+    let axons = recorder.subscribe_area(&mut cortex, testbed::PRIMARY_AREA_NAME,
+        SamplerKind::Axons(None)).unwrap();
+    let somas = recorder.subscribe_area(&mut cortex, testbed::PRIMARY_AREA_NAME,
+        SamplerKind::SomaStates(lyr_addr)).unwrap();
+
+    for _ in 0..10 {
+        cortex.cycle().unwrap();
+        recorder.record().unwrap();
+    }
+    assert_eq!(recorder.cycles_recorded(), 10);
+    recorder.finish().unwrap();
+
+    let mut reader = RecordingReader::open(&path).unwrap();
+    assert_eq!(reader.streams().len(), 2);
+    assert_eq!(reader.streams()[axons].area_name(), testbed::PRIMARY_AREA_NAME);
+    let area_tract_map = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap()
+        .axon_tract_map();
+    assert_eq!(reader.streams()[axons].tract_map().tags(), area_tract_map.tags());
+    assert!(!reader.streams()[somas].is_signed());
+    assert!(reader.streams()[axons].is_axons());
+    assert_eq!(reader.streams()[somas].kind_code(), SamplerKind::SomaStates(lyr_addr).code());
+    assert_eq!(reader.streams()[somas].kind_name(), "SomaStates");
+
+    let mut recorded = Vec::new();
+    for cycle in reader.by_ref() {
+        recorded.push(cycle.unwrap());
+    }
+    assert_eq!(recorded.len(), 10);
+    for (i, cycle) in recorded.iter().enumerate() {
+        assert_eq!(cycle.cycle(), i as u64);
+        assert_eq!(cycle.frame(axons).len(),
+            area_tract_map.axn_count(area_tract_map.slc_id_range()));
+    }
+
+    // Seeking skips whole chunks:
+    assert!(reader.seek_cycle(6).unwrap());
+    let cycle = reader.next_cycle().unwrap().unwrap();
+    assert_eq!(cycle.cycle(), 6);
+    assert_eq!(cycle.frames(), recorded[6].frames());
+    assert!(!reader.seek_cycle(10).unwrap());
+
+    assert!(Recorder::create(&path, Compression::None).unwrap().chunk_cycles(0).is_err());
+
+    fs::remove_file(&path).ok();
+}
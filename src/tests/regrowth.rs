@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use cmn;
+use cortex::{Cortex, CorticalAreaSettings, CorticalAreaTest, LearningParams};
+use tests::{testbed, util};
+use tests::equivalence::{self, cortex_with_settings};
+use {SlcId, SrcOfs};
+
+
+const CHUNK_SIZE: usize = 512;
+/// A chunk size larger than any testbed layer.
+const WHOLE_LAYER_CHUNK_SIZE: usize = 1 << 24;
+/// The seed of every randomized test.
+const SEED: u64 = 0x5eed_0022;
+/// The maximum total variation distance allowed between host and device
+/// regrowth distributions.
+const DISTRIBUTION_TVD_MAX: f64 = 0.1;
+
+
+/// Returns the source slice id and offsets of every synapse within the
+/// primary temporal layer.
+fn pyr_syn_srcs(cortex: &Cortex) -> Vec<(SlcId, SrcOfs, SrcOfs)> {
+    let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    let syns = area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns();
+    let slc_ids = util::read_into_new_vec(syns.src_slc_ids());
+    let v_offs = util::read_into_new_vec(syns.src_col_v_offs());
+    let u_offs = util::read_into_new_vec(syns.src_col_u_offs());
+
+    slc_ids.into_iter().zip(v_offs.into_iter()).zip(u_offs.into_iter())
+        .map(|((slc_id, v_ofs), u_ofs)| (slc_id, v_ofs, u_ofs))
+        .collect()
+}
+
+
+/// Returns the strength of every synapse within the primary temporal layer.
+fn pyr_syn_strengths(cortex: &Cortex) -> Vec<i8> {
+    let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    let syns = area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap().dens().syns();
+    util::read_into_new_vec(syns.strengths())
+}
+
+
+/// Sets the strength of every synapse within the primary temporal layer.
+fn fill_pyr_syn_strengths(cortex: &mut Cortex, strength: i8) {
+    {
+        let area = cortex.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap();
+        let lyr = area.layer_test_mut(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap();
+        lyr.dens_mut().syns_mut().strengths().fill(strength).unwrap();
+    }
+    cortex.finish_queues();
+}
+
+
+/// Sets the strength of every synapse within the primary temporal layer to
+/// the regrowth floor.
+fn floor_pyr_syn_strengths(cortex: &mut Cortex) {
+    fill_pyr_syn_strengths(cortex, cmn::SYNAPSE_STRENGTH_FLOOR)
+}
+
+
+/// Returns the normalized histogram of `vals`.
+fn histogram<T: Ord + Copy, I: Iterator<Item = T>>(vals: I) -> BTreeMap<T, f64> {
+    let mut counts = BTreeMap::new();
+    let mut total = 0usize;
+    for val in vals {
+        *counts.entry(val).or_insert(0usize) += 1;
+        total += 1;
+    }
+    counts.into_iter().map(|(val, count)| (val, count as f64 / total as f64)).collect()
+}
+
+
+/// Returns the total variation distance between two normalized histograms.
+fn tvd<T: Ord + Copy>(a: &BTreeMap<T, f64>, b: &BTreeMap<T, f64>) -> f64 {
+    let mut dist = 0.0;
+    for (val, &pa) in a.iter() {
+        dist += (pa - b.get(val).cloned().unwrap_or(0.0)).abs();
+    }
+    for (val, &pb) in b.iter() {
+        if !a.contains_key(val) { dist += pb; }
+    }
+    dist / 2.0
+}
+
+
+/// Regrowth proceeds one chunk per cycle: a chunk queued during one cycle is
+/// applied when its layer next cycles.
+#[test]
+fn incremental_regrowth() {
+    let mut cortex = cortex_with_settings(CorticalAreaSettings::new()
+        .disable_learning()
+        .regrowth_chunk_size(CHUNK_SIZE));
+    floor_pyr_syn_strengths(&mut cortex);
+
+    let srcs_before = pyr_syn_srcs(&cortex);
+    assert!(srcs_before.len() > CHUNK_SIZE * 4);
+
+    for _ in 0..3 { cortex.cycle().unwrap(); }
+    cortex.finish_queues();
+    let srcs_after = pyr_syn_srcs(&cortex);
+
+    // Two chunks have been applied and the third is still pending:
+    let regrown_len = CHUNK_SIZE * 2;
+    assert!(srcs_before[regrown_len..] == srcs_after[regrown_len..]);
+
+    let changed = srcs_before[..regrown_len].iter().zip(srcs_after[..regrown_len].iter())
+        .filter(|&(before, after)| before != after)
+        .count();
+    assert!(changed > regrown_len / 2, "Only {} of {} synapses were regrown.", changed,
+        regrown_len);
+}
+
+
+/// Device regrowth regrows each chunk in place during the cycle it is
+/// queued.
+#[test]
+fn incremental_device_regrowth() {
+    let mut cortex = cortex_with_settings(CorticalAreaSettings::new()
+        .disable_learning()
+        .regrowth_chunk_size(CHUNK_SIZE)
+        .device_regrowth());
+    floor_pyr_syn_strengths(&mut cortex);
+
+    let srcs_before = pyr_syn_srcs(&cortex);
+    assert!(srcs_before.len() > CHUNK_SIZE * 4);
+
+    for _ in 0..2 { cortex.cycle().unwrap(); }
+    cortex.finish_queues();
+    let srcs_after = pyr_syn_srcs(&cortex);
+
+    let regrown_len = CHUNK_SIZE * 2;
+    assert!(srcs_before[regrown_len..] == srcs_after[regrown_len..]);
+
+    let changed = srcs_before[..regrown_len].iter().zip(srcs_after[..regrown_len].iter())
+        .filter(|&(before, after)| before != after)
+        .count();
+    assert!(changed > regrown_len / 2, "Only {} of {} synapses were regrown.", changed,
+        regrown_len);
+}
+
+
+/// The `tft_regrow_syns` kernel and its host reference regrow identically
+/// given the same state.
+#[test]
+fn device_regrowth_kernel_equivalent() {
+    let mut rng = cmn::rng_from_u64(SEED);
+    let settings = CorticalAreaSettings::new()
+        .regrowth_chunk_size(WHOLE_LAYER_CHUNK_SIZE)
+        .device_regrowth()
+        .seed(SEED);
+
+    let mut cortex_dev = cortex_with_settings(settings.clone());
+    equivalence::seed_area(cortex_dev.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap(),
+        &equivalence::COMPARED_LAYER_NAMES, &mut rng);
+    let srcs_before = pyr_syn_srcs(&cortex_dev);
+
+    equivalence::assert_host_equivalent(&mut cortex_dev, settings.host_kernels(),
+        "bismit_test_device_regrowth_equivalence.bsnap", |_, _| ());
+
+    // Seeding leaves many synapses below the floor:
+    assert!(srcs_before != pyr_syn_srcs(&cortex_dev));
+}
+
+
+/// Host regrowth (`Synapses::regrow_syn`) and device regrowth
+/// (`tft_regrow_syns`) draw replacement source slices, offsets, and
+/// strengths from the same distributions.
+///
+/// Their sequences differ and, unlike the host, the device leaves a synapse
+/// at the floor after `SYN_REGROW_ATTEMPTS_MAX` failed attempts, so only
+/// the distributions of regrown synapses are compared.
+#[test]
+fn device_regrowth_distribution_equivalent() {
+    let regrown = |device_regrowth: bool| {
+        let mut settings = CorticalAreaSettings::new()
+            .disable_learning()
+            .regrowth_chunk_size(WHOLE_LAYER_CHUNK_SIZE)
+            .seed(SEED);
+        if device_regrowth { settings = settings.device_regrowth(); }
+
+        let mut cortex = cortex_with_settings(settings);
+        floor_pyr_syn_strengths(&mut cortex);
+        for _ in 0..3 { cortex.cycle().unwrap(); }
+        cortex.finish_queues();
+
+        pyr_syn_srcs(&cortex).into_iter().zip(pyr_syn_strengths(&cortex).into_iter())
+            .filter(|&(_, strength)| strength > cmn::SYNAPSE_STRENGTH_FLOOR)
+            .collect::<Vec<_>>()
+    };
+
+    let host = regrown(false);
+    let dev = regrown(true);
+    assert!(host.len() > CHUNK_SIZE * 4 && dev.len() > CHUNK_SIZE * 4,
+        "host regrown: {}, device regrown: {}", host.len(), dev.len());
+
+    let slc_ids = tvd(&histogram(host.iter().map(|&((slc_id, _, _), _)| slc_id)),
+        &histogram(dev.iter().map(|&((slc_id, _, _), _)| slc_id)));
+    let v_offs = tvd(&histogram(host.iter().map(|&((_, v_ofs, _), _)| v_ofs)),
+        &histogram(dev.iter().map(|&((_, v_ofs, _), _)| v_ofs)));
+    let u_offs = tvd(&histogram(host.iter().map(|&((_, _, u_ofs), _)| u_ofs)),
+        &histogram(dev.iter().map(|&((_, _, u_ofs), _)| u_ofs)));
+    let strengths = tvd(&histogram(host.iter().map(|&(_, strength)| strength)),
+        &histogram(dev.iter().map(|&(_, strength)| strength)));
+
+    assert!(slc_ids < DISTRIBUTION_TVD_MAX, "source slice distance: {}", slc_ids);
+    assert!(v_offs < DISTRIBUTION_TVD_MAX, "v offset distance: {}", v_offs);
+    assert!(u_offs < DISTRIBUTION_TVD_MAX, "u offset distance: {}", u_offs);
+    assert!(strengths < DISTRIBUTION_TVD_MAX, "strength distance: {}", strengths);
+}
+
+
+
+/// Changing the regrowth floor and new synapse strengths between cycles takes
+/// effect on the next regrowth, whether regrown on the host or the device.
+#[test]
+fn runtime_learning_params() {
+    const STRENGTH: i8 = -10;
+    const STRENGTH_DEFAULT: i8 = 7;
+
+    for &device_regrowth in [false, true].iter() {
+        let mut settings = CorticalAreaSettings::new()
+            .disable_learning()
+            .regrowth_chunk_size(WHOLE_LAYER_CHUNK_SIZE);
+        if device_regrowth { settings = settings.device_regrowth(); }
+
+        let mut cortex = cortex_with_settings(settings.seed(SEED));
+        assert!(STRENGTH > cmn::SYNAPSE_STRENGTH_FLOOR);
+        fill_pyr_syn_strengths(&mut cortex, STRENGTH);
+        let srcs_before = pyr_syn_srcs(&cortex);
+
+        // Nothing is at or below the default floor:
+        for _ in 0..3 { cortex.cycle().unwrap(); }
+        cortex.finish_queues();
+        assert!(srcs_before == pyr_syn_srcs(&cortex));
+
+        let params = LearningParams::new()
+            .syn_strength_floor(STRENGTH)
+            .syn_strength_initial_deviation(0)
+            .dst_syn_strength_default(STRENGTH_DEFAULT)
+            .prx_syn_strength_default(STRENGTH_DEFAULT);
+
+        cortex.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap()
+            .set_learning_params(params).unwrap();
+
+        for _ in 0..3 { cortex.cycle().unwrap(); }
+        cortex.finish_queues();
+
+        // With no deviation, every regrown synapse has the default strength
+        // (the kernel leaves a synapse for its next pass after failing to
+        // find a unique source):
+        assert!(srcs_before != pyr_syn_srcs(&cortex));
+        let strengths = pyr_syn_strengths(&cortex);
+        let regrown = strengths.iter().filter(|&&s| s == STRENGTH_DEFAULT).count();
+        assert!(strengths.iter().all(|&s| s == STRENGTH_DEFAULT || s == STRENGTH));
+        assert!(regrown > strengths.len() * 9 / 10, "device_regrowth: {}, regrown: {} of {}",
+            device_regrowth, regrown, strengths.len());
+
+        let area = cortex.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap();
+        assert_eq!(*area.learning_params(), params);
+        assert!(area.set_learning_params(params.syn_regrowth_interval(0)).is_err());
+        assert!(area.set_learning_params(params.dst_rates_l2i(0, 25)).is_err());
+        assert_eq!(*area.learning_params(), params);
+    }
+}
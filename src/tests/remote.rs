@@ -0,0 +1,137 @@
+use std::env;
+use std::sync::mpsc;
+use std::thread;
+use flywheel::{Flywheel, Command, Request, RemoteServer, RemoteClient, RemoteRequest,
+    RemoteResponse, AreaSetting};
+use flywheel::wire::{Wire, WireWriter, WireReader};
+use map::LayerAddress;
+use cortex::{AnomalyScore, LearningParams};
+use ::{SamplerKind, SamplerBufferKind};
+use tests::testbed;
+
+
+fn round_trip<T: Wire>(msg: &T) -> T {
+    let mut w = WireWriter::new();
+    msg.encode(&mut w);
+    let bytes = w.into_bytes();
+    let mut r = WireReader::new(&bytes);
+    let decoded = T::decode(&mut r).unwrap();
+    r.finish().unwrap();
+    decoded
+}
+
+
+#[test]
+fn wire_round_trip() {
+    let req = RemoteRequest::Flywheel(Request::Sampler {
+        area_name: "v1".to_owned(),
+        kind: SamplerKind::TuftStates(LayerAddress::new(1, 3)),
+        buffer_kind: SamplerBufferKind::Single,
+        backpressure: true,
+    });
+    assert_eq!(format!("{:?}", round_trip(&req)), format!("{:?}", req));
+
+    let res = RemoteResponse::SamplerFrame { sampler_id: 7, frame: Some(vec![0, 1, 255]) };
+    assert_eq!(format!("{:?}", round_trip(&res)), format!("{:?}", res));
+
+    let score = AnomalyScore::new(LayerAddress::new(1, 3), (1 << 33) + 12, 40, 30, 0.75);
+    let res = RemoteResponse::Anomaly(score);
+    assert_eq!(format!("{:?}", round_trip(&res)), format!("{:?}", res));
+
+    let setting = AreaSetting::LearningParams(LearningParams::new()
+        .syn_strength_floor(-40)
+        .syn_regrowth_interval(1000)
+        .prx_syn_strength_default(-3)
+        .dst_rates_l2i(1, 6));
+    assert_eq!(round_trip(&setting), setting);
+
+    let setting = AreaSetting::Neuromodulation(-96);
+    assert_eq!(round_trip(&setting), setting);
+
+    // Truncated payloads must be rejected:
+    let mut w = WireWriter::new();
+    req.encode(&mut w);
+    let bytes = w.into_bytes();
+    assert!(RemoteRequest::decode(&mut WireReader::new(&bytes[..bytes.len() - 1])).is_err());
+}
+
+
+#[test]
+fn loopback_session() {
+    let (connector_tx, connector_rx) = mpsc::channel();
+
+    let flywheel_thread = thread::spawn(move || {
+        let (_command_tx, command_rx) = mpsc::channel();
+        let mut flywheel = Flywheel::new(testbed::fresh_cortex(), command_rx);
+        connector_tx.send(flywheel.connector()).unwrap();
+        flywheel.spin();
+    });
+
+    let connector = connector_rx.recv().unwrap();
+
+    // Only loopback addresses are permitted by default:
+    assert!(RemoteServer::bind_tcp("0.0.0.0:0", connector.clone()).is_err());
+
+    let server = RemoteServer::bind_tcp("127.0.0.1:0", connector).unwrap();
+    let mut client = RemoteClient::connect_tcp(server.local_addr().unwrap()).unwrap();
+
+    // Sample:
+    client.request(Request::Sampler {
+        area_name: testbed::PRIMARY_AREA_NAME.to_owned(),
+        kind: SamplerKind::Axons(None),
+        buffer_kind: SamplerBufferKind::Single,
+        backpressure: false,
+    }).unwrap();
+    let sampler_id = match client.recv_until(|res| match *res {
+                RemoteResponse::Sampler { .. } => true, _ => false }).unwrap() {
+        RemoteResponse::Sampler { sampler_id } => sampler_id,
+        _ => unreachable!(),
+    };
+
+    // Iterate:
+    client.command(Command::Iterate(20)).unwrap();
+    client.recv_until(|res| match *res {
+        RemoteResponse::Status(ref s) => !s.cycling && s.ttl_cycles() >= 20,
+        _ => false,
+    }).unwrap();
+
+    client.send(&RemoteRequest::ReadSampler { sampler_id, wait_for_frame: false }).unwrap();
+    match client.recv_until(|res| match *res {
+            RemoteResponse::SamplerFrame { .. } => true, _ => false }).unwrap() {
+        RemoteResponse::SamplerFrame { frame, .. } => assert!(frame.unwrap().len() > 0),
+        _ => unreachable!(),
+    }
+
+    // Unknown areas produce an error rather than a panic:
+    client.request(Request::AreaInfo { area_name: "nonexistent".to_owned() }).unwrap();
+    match client.recv().unwrap() {
+        RemoteResponse::Error(_) => (),
+        res => panic!("Unexpected response: {:?}", res),
+    }
+
+    // As do samplers of nonexistent layers:
+    client.request(Request::Sampler {
+        area_name: testbed::PRIMARY_AREA_NAME.to_owned(),
+        kind: SamplerKind::SomaStates(LayerAddress::new(0, 250)),
+        buffer_kind: SamplerBufferKind::Single,
+        backpressure: false,
+    }).unwrap();
+    match client.recv().unwrap() {
+        RemoteResponse::Error(_) => (),
+        res => panic!("Unexpected response: {:?}", res),
+    }
+
+    // Commands which write to a client specified path are refused:
+    let path = env::temp_dir().join("bismit_remote_test_refused.bsnap");
+    client.command(Command::Snapshot(path.clone())).unwrap();
+    match client.recv().unwrap() {
+        RemoteResponse::Error(_) => (),
+        res => panic!("Unexpected response: {:?}", res),
+    }
+    assert!(!path.exists());
+
+    // Exit:
+    client.command(Command::Exit).unwrap();
+    client.recv_until(|res| match *res { RemoteResponse::Exiting => true, _ => false }).unwrap();
+    flywheel_thread.join().unwrap();
+}
@@ -0,0 +1,132 @@
+use std::env;
+use std::fs;
+use cmn::TractFrameMut;
+use map::{LayerAddress, AxonTags, LayerMapSchemeList, LayerMapScheme, LayerMapKind, LayerScheme, AxonTopology,
+    AxonDomain, AreaSchemeList, AreaScheme, EncoderScheme, THAL_SP};
+use cortex::{DataCellLayer, SamplerKind};
+use cortex::recorder::{Recorder, RecordingReader, Compression};
+use encode::Replay;
+use subcortex::{InputGenerator, InputGeneratorTract};
+use tests::testbed;
+
+
+fn replay_schemes(path: &str, depth: u8, area_side: u32) -> (LayerMapSchemeList, AreaSchemeList) {
+    let mut layer_map_schemes = LayerMapSchemeList::new();
+    layer_map_schemes.add(LayerMapScheme::new("replay_ext", LayerMapKind::Subcortical)
+        .layer(LayerScheme::define("ganglion")
+            .depth(depth)
+            .axonal(AxonTopology::Spatial)
+            .axon_domain(AxonDomain::output(&[THAL_SP]))
+        )
+    );
+
+    let area_schemes = AreaSchemeList::new()
+        .area(AreaScheme::new("v0", "replay_ext", area_side)
+            .encoder(EncoderScheme::Replay {
+                file: path.to_owned(),
+                looped: false,
+                cycles_per_frame: 2,
+            })
+        );
+
+    (layer_map_schemes, area_schemes)
+}
+
+
+#[test]
+fn replay_recorded_layer() {
+    let path = env::temp_dir().join("bismit_replay_test.brec");
+    let path_str = path.to_str().unwrap().to_owned();
+    let mut cortex = testbed::fresh_cortex();
+
+    let lyr_addr = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap()
+        .layer(testbed::PRIMARY_SPATIAL_SSC_LAYER_NAME).unwrap().layer_addr();
+
+    let mut recorder = Recorder::create(&path, Compression::ZeroRuns).unwrap();
+    recorder.subscribe_area(&mut cortex, testbed::PRIMARY_AREA_NAME,
+        SamplerKind::Axons(Some(lyr_addr))).unwrap();
+    for _ in 0..3 {
+        cortex.cycle().unwrap();
+        recorder.record().unwrap();
+    }
+    recorder.finish().unwrap();
+
+    let recorded: Vec<_> = RecordingReader::open(&path).unwrap()
+        .map(|rc| rc.unwrap().frame(0).to_vec()).collect();
+    let depth = RecordingReader::open(&path).unwrap().streams()[0].tract_map().tags().len();
+
+    // Mismatched dimensions are rejected:
+    let (lm_schemes, a_schemes) = replay_schemes(&path_str, depth as u8, 16);
+    assert!(InputGenerator::new(&lm_schemes, &a_schemes, "v0").is_err());
+
+    let (lm_schemes, a_schemes) = replay_schemes(&path_str, depth as u8, 24);
+    let mut input_gen = InputGenerator::new(&lm_schemes, &a_schemes, "v0").unwrap();
+    let addr = input_gen.layer_addrs()[0];
+    let dims = input_gen.layers_mut()[&addr].sub().dims().unwrap().clone();
+    let mut replay = Replay::new(&path, input_gen.layers_mut(), 2, false).unwrap();
+
+    // Each recorded frame is repeated for two cycles then zeros follow:
+    let mut buf = vec![0u8; recorded[0].len()];
+    for cycle in 0..8 {
+        replay.write_into(&mut TractFrameMut::new(&mut buf, dims.into()), addr);
+        match recorded.get(cycle / 2) {
+            Some(frame) => assert_eq!(&buf, frame),
+            None => assert!(buf.iter().all(|&axn| axn == 0)),
+        }
+        replay.cycle_next();
+    }
+    assert!(replay.is_finished());
+    assert!(replay.error().is_none());
+
+    // An unknown layer zeroes the frame and records an error rather than
+    // panicking:
+    for axn in buf.iter_mut() { *axn = 1; }
+    replay.write_into(&mut TractFrameMut::new(&mut buf, dims.into()),
+        LayerAddress::new(addr.area_id() + 1, 0));
+    assert!(buf.iter().all(|&axn| axn == 0));
+    assert!(replay.error().is_some());
+
+    fs::remove_file(&path).ok();
+}
+
+
+#[test]
+fn replay_by_axon_tags() {
+    let path = env::temp_dir().join("bismit_replay_tags_test.brec");
+    let path_str = path.to_str().unwrap().to_owned();
+    let mut cortex = testbed::fresh_cortex();
+
+    // Record every layer of the primary area, including its 'out' layer
+    // (output tags: `THAL_SP`):
+    let mut recorder = Recorder::create(&path, Compression::ZeroRuns).unwrap();
+    recorder.subscribe_area(&mut cortex, testbed::PRIMARY_AREA_NAME,
+        SamplerKind::Axons(None)).unwrap();
+    cortex.cycle().unwrap();
+    recorder.record().unwrap();
+    recorder.finish().unwrap();
+
+    let mut reader = RecordingReader::open(&path).unwrap();
+    let stream = reader.streams()[0].clone();
+    assert!(stream.output_layers().iter().any(|&(ref name, ref tags)| {
+        name == "out" && *tags == AxonTags::from(THAL_SP)
+    }));
+    let slc_ids: Vec<usize> = stream.tract_map().tags().iter().enumerate()
+        .filter(|&(_, tag)| tag == "out").map(|(slc_id, _)| slc_id).collect();
+    let out_range = stream.tract_map().axn_id_range(slc_ids[0]..(slc_ids[0] + 1));
+    let recorded = reader.next_cycle().unwrap().unwrap().frame(0)[out_range].to_vec();
+
+    // The 'ganglion' layer shares no name with any recorded layer but has
+    // the same output tags as 'out':
+    let (lm_schemes, a_schemes) = replay_schemes(&path_str, 1, 24);
+    let mut input_gen = InputGenerator::new(&lm_schemes, &a_schemes, "v0").unwrap();
+    let addr = input_gen.layer_addrs()[0];
+    let dims = input_gen.layers_mut()[&addr].sub().dims().unwrap().clone();
+    let mut replay = Replay::new(&path, input_gen.layers_mut(), 1, false).unwrap();
+
+    let mut buf = vec![0u8; recorded.len()];
+    replay.write_into(&mut TractFrameMut::new(&mut buf, dims.into()), addr);
+    assert_eq!(buf, recorded);
+    assert!(replay.error().is_none());
+
+    fs::remove_file(&path).ok();
+}
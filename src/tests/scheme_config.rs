@@ -0,0 +1,84 @@
+use std::env;
+use std::fs;
+use ron;
+use cortex::Cortex;
+use subcortex::InputGenerator;
+use map::SchemeConfig;
+use tests::testbed;
+
+
+#[test]
+fn scheme_config_round_trip() {
+    let config = SchemeConfig::new(testbed::define_layer_map_schemes(),
+        testbed::define_area_schemes());
+    let ron_string = config.to_ron_string().unwrap();
+
+    let config_2 = SchemeConfig::from_ron_str(&ron_string).unwrap();
+    assert_eq!(ron_string, config_2.to_ron_string().unwrap());
+
+    assert_eq!(config.area_schemes().areas(), config_2.area_schemes().areas());
+    assert_eq!(config.layer_map_schemes().len(), config_2.layer_map_schemes().len());
+
+    for (lm_scheme, lm_scheme_2) in config.layer_map_schemes().values().iter()
+            .zip(config_2.layer_map_schemes().values().iter()) {
+        assert_eq!(lm_scheme.name(), lm_scheme_2.name());
+        assert_eq!(lm_scheme.layers(), lm_scheme_2.layers());
+    }
+}
+
+
+#[test]
+fn cortex_from_scheme_config_file() {
+    let path = env::temp_dir().join("bismit_test_scheme_config.ron");
+    SchemeConfig::new(testbed::define_layer_map_schemes(), testbed::define_area_schemes())
+        .save(&path).unwrap();
+
+    let config = SchemeConfig::load(&path).unwrap();
+    let input_gen = InputGenerator::new(config.layer_map_schemes(), config.area_schemes(),
+        "v0").unwrap();
+
+    let mut cortex = Cortex::builder_from_file(&path).unwrap()
+        .subcortical_nucleus(input_gen)
+        .build().unwrap();
+
+    cortex.cycle().unwrap();
+
+    fs::remove_file(&path).ok();
+}
+
+
+#[test]
+fn scheme_config_tags_by_name() {
+    use map::{AxonTag, LayerTags, THAL_SP};
+
+    let ron_string = SchemeConfig::new(testbed::define_layer_map_schemes(),
+        testbed::define_area_schemes()).to_ron_string().unwrap();
+    assert!(ron_string.contains("\"THAL_SP\""));
+    assert!(ron_string.contains("\"SPATIAL\""));
+
+    let tags = LayerTags::PSAL | LayerTags::uid(7);
+    let tags_ron = ron::ser::to_string(&tags).unwrap();
+    assert_eq!(tags_ron, ron::ser::to_string(&vec!["PRIMARY", "SPATIAL", "UID:7"]).unwrap());
+    assert_eq!(ron::de::from_str::<LayerTags>(&tags_ron).unwrap(), tags);
+    assert!(ron::de::from_str::<LayerTags>("[\"NOT_A_TAG\"]").is_err());
+
+    // Raw values (as previously written) are still accepted:
+    let bits = tags.bits().to_string();
+    assert_eq!(ron::de::from_str::<LayerTags>(&bits).unwrap(), tags);
+
+    for &tag in [THAL_SP, AxonTag::custom(5), AxonTag::unique()].iter() {
+        let tag_ron = ron::ser::to_string(&tag).unwrap();
+        assert_eq!(ron::de::from_str::<AxonTag>(&tag_ron).unwrap(), tag);
+    }
+    assert_eq!(ron::ser::to_string(&AxonTag::custom(5)).unwrap(), "\"CUSTOM:5\"");
+}
+
+
+#[test]
+fn unknown_efferent_area() {
+    use map::{AreaScheme, AreaSchemeList};
+
+    let mut area_schemes = AreaSchemeList::new()
+        .area(AreaScheme::new("v1", "visual", 16).eff_areas(vec!["nowhere"]));
+    assert!(area_schemes.freeze().is_err());
+}
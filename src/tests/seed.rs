@@ -0,0 +1,94 @@
+use rand::Rng;
+use cmn;
+use cortex::{Cortex, CorticalAreaTest};
+use tests::{util, testbed};
+use {SlcId, SrcOfs};
+
+
+const SEED: u64 = 0x5eed;
+
+
+/// Returns a testbed cortex (and input generator) built with the master seed
+/// `seed`, which the builder passes on to the input generator.
+fn seeded_cortex(seed: u64) -> Cortex {
+    Cortex::builder(testbed::define_layer_map_schemes(), testbed::define_area_schemes())
+        .input_generator("v0")
+        .seed(seed)
+        .build().unwrap()
+}
+
+
+/// Returns the axon states of the primary area along with the somas,
+/// synapse strengths and synapse sources of its primary temporal layer.
+fn primary_area_state(cortex: &Cortex) -> (Vec<u8>, Vec<u8>, Vec<i8>, Vec<(SlcId, SrcOfs, SrcOfs)>) {
+    cortex.finish_queues();
+    let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    let lyr = area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap();
+    let syns = lyr.dens().syns();
+    let srcs = util::read_into_new_vec(syns.src_slc_ids()).into_iter()
+        .zip(util::read_into_new_vec(syns.src_col_v_offs()).into_iter())
+        .zip(util::read_into_new_vec(syns.src_col_u_offs()).into_iter())
+        .map(|((slc_id, v_ofs), u_ofs)| (slc_id, v_ofs, u_ofs))
+        .collect();
+
+    (util::read_into_new_vec(area.axns().states()),
+        util::read_into_new_vec(lyr.soma()),
+        util::read_into_new_vec(syns.strengths()),
+        srcs)
+}
+
+
+#[test]
+fn child_seeds_stable() {
+    let seed = cmn::child_seed(SEED, "synapses", &[1, 2]);
+    assert_eq!(seed, cmn::child_seed(SEED, "synapses", &[1, 2]));
+    assert!(seed != cmn::child_seed(SEED + 1, "synapses", &[1, 2]));
+    assert!(seed != cmn::child_seed(SEED, "dendrites", &[1, 2]));
+    assert!(seed != cmn::child_seed(SEED, "synapses", &[2, 1]));
+    assert!(seed != cmn::child_seed(SEED, "synapses", &[1, 2, 0]));
+
+    let mut rng_a = cmn::seeded_rng(Some(SEED), "synapses", &[1, 2]);
+    let mut rng_b = cmn::seeded_rng(Some(SEED), "synapses", &[1, 2]);
+    for _ in 0..64 {
+        assert_eq!(rng_a.gen::<u64>(), rng_b.gen::<u64>());
+    }
+
+    let mut gen = cmn::SplitMix64::new(SEED);
+    assert!(gen.next_u64() != gen.next_u64());
+    assert!((0..64).all(|_| gen.below(5) < 5));
+}
+
+
+#[test]
+fn sparse_vec_seeded() {
+    let sparse = |seed: u64| cmn::sparse_vec(256, 0u8, 127, 3, &mut cmn::rng_from_u64(seed));
+    assert!(sparse(SEED) == sparse(SEED));
+    assert!(sparse(SEED) != sparse(SEED + 1));
+}
+
+
+/// Cortices built from identical configurations and seeds grow identical
+/// synapses and produce identical axon traces.
+#[test]
+fn identical_seeds_identical_runs() {
+    let mut cortex_a = seeded_cortex(SEED);
+    let mut cortex_b = seeded_cortex(SEED);
+    assert!(primary_area_state(&cortex_a) == primary_area_state(&cortex_b));
+
+    for i in 0..50 {
+        cortex_a.cycle().unwrap();
+        cortex_b.cycle().unwrap();
+        assert!(primary_area_state(&cortex_a).0 == primary_area_state(&cortex_b).0,
+            "Axon states diverged after cycle {}.", i);
+    }
+
+    assert!(primary_area_state(&cortex_a) == primary_area_state(&cortex_b));
+}
+
+
+#[test]
+fn different_seeds_differ() {
+    let cortex_a = seeded_cortex(SEED);
+    let cortex_b = seeded_cortex(SEED + 1);
+    assert!(primary_area_state(&cortex_a).3 != primary_area_state(&cortex_b).3);
+}
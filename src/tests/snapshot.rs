@@ -0,0 +1,142 @@
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use cortex::{Cortex, CorticalAreaTest, LearningParams};
+use tests::{util, testbed};
+
+
+const SEED: u64 = 0x5eed_0001;
+
+
+fn primary_area_state(cortex: &Cortex) -> (Vec<u8>, Vec<u8>, Vec<i8>) {
+    let area = cortex.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    let lyr = area.layer_test(testbed::PRIMARY_TEMPORAL_PYR_LAYER_NAME).unwrap();
+    (util::read_into_new_vec(area.axns().states()),
+        util::read_into_new_vec(lyr.soma()),
+        util::read_into_new_vec(lyr.dens().syns().strengths()))
+}
+
+
+fn seeded_cortex() -> Cortex {
+    Cortex::builder(testbed::define_layer_map_schemes(), testbed::define_area_schemes())
+        .input_generator("v0")
+        .seed(SEED)
+        .build().unwrap()
+}
+
+
+#[test]
+fn save_and_load_state() {
+    let path = env::temp_dir().join("bismit_snapshot_test_save_and_load.bsnap");
+
+    let mut cortex_a = testbed::fresh_cortex();
+    for _ in 0..50 { cortex_a.cycle().unwrap(); }
+    cortex_a.save_state(&path).unwrap();
+
+    // Input generators are not part of a snapshot. Cycling the second
+    // cortex as many times brings its input to the same position:
+    let mut cortex_b = testbed::fresh_cortex();
+    for _ in 0..50 { cortex_b.cycle().unwrap(); }
+    cortex_b.load_state(&path).unwrap();
+    cortex_a.finish_queues();
+    cortex_b.finish_queues();
+    assert!(primary_area_state(&cortex_a) == primary_area_state(&cortex_b));
+
+    // Saving leaves the rngs of the saving cortex untouched. Once restored
+    // from the same snapshot, both continue identically:
+    cortex_a.load_state(&path).unwrap();
+    for _ in 0..50 {
+        cortex_a.cycle().unwrap();
+        cortex_b.cycle().unwrap();
+    }
+    cortex_a.finish_queues();
+    cortex_b.finish_queues();
+    assert!(primary_area_state(&cortex_a) == primary_area_state(&cortex_b));
+
+    fs::remove_file(&path).ok();
+}
+
+
+#[test]
+fn save_and_load_neuromodulation() {
+    let path = env::temp_dir().join("bismit_snapshot_test_neuromodulation.bsnap");
+
+    let mut cortex_a = testbed::fresh_cortex();
+    {
+        let area = cortex_a.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap();
+        let values: Vec<i8> = (0..area.dims().columns() as usize)
+            .map(|col_id| (col_id % 128) as i8).collect();
+        area.set_neuromodulation_by_column(&values).unwrap();
+    }
+    cortex_a.save_state(&path).unwrap();
+
+    let mut cortex_b = testbed::fresh_cortex();
+    cortex_b.load_state(&path).unwrap();
+    let area_a = cortex_a.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    let area_b = cortex_b.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap();
+    assert!(area_a.neuromodulation().values() == area_b.neuromodulation().values());
+
+    fs::remove_file(&path).ok();
+}
+
+
+#[test]
+fn load_state_mismatch() {
+    let path = env::temp_dir().join("bismit_snapshot_test_mismatch.bsnap");
+
+    let mut cortex_a = testbed::fresh_cortex();
+    cortex_a.save_state(&path).unwrap();
+
+    let mut cortex_b = testbed::cortex_with_lots_of_apical_tufts();
+    assert!(cortex_b.load_state(&path).is_err());
+
+    fs::remove_file(&path).ok();
+}
+
+
+/// Saving a snapshot does not change the course of the saving run.
+#[test]
+fn save_state_undisturbed() {
+    let path = env::temp_dir().join("bismit_snapshot_test_undisturbed.bsnap");
+
+    let mut cortex_a = seeded_cortex();
+    let mut cortex_b = seeded_cortex();
+    for _ in 0..20 {
+        cortex_a.cycle().unwrap();
+        cortex_b.cycle().unwrap();
+    }
+    cortex_a.save_state(&path).unwrap();
+
+    for _ in 0..20 {
+        cortex_a.cycle().unwrap();
+        cortex_b.cycle().unwrap();
+    }
+    cortex_a.finish_queues();
+    cortex_b.finish_queues();
+    assert!(primary_area_state(&cortex_a) == primary_area_state(&cortex_b));
+
+    fs::remove_file(&path).ok();
+}
+
+
+#[test]
+fn save_and_load_learning_params() {
+    let path = env::temp_dir().join("bismit_snapshot_test_learning_params.bsnap");
+    let params = LearningParams::new()
+        .syn_strength_floor(-10)
+        .syn_regrowth_interval(7)
+        .dst_rates_l2i(3, 5);
+
+    let mut cortex_a = testbed::fresh_cortex();
+    cortex_a.areas_mut().by_key_mut(testbed::PRIMARY_AREA_NAME).unwrap()
+        .set_learning_params(params).unwrap();
+    cortex_a.save_state(&path).unwrap();
+
+    let mut cortex_b = testbed::fresh_cortex();
+    cortex_b.load_state(&path).unwrap();
+    assert!(*cortex_b.areas().by_key(testbed::PRIMARY_AREA_NAME).unwrap().learning_params()
+        == params);
+
+    fs::remove_file(&path).ok();
+}
@@ -207,6 +207,25 @@ pub fn define_area_schemes() -> AreaSchemeList {
     area_schemes
 }
 
+/// Returns the schemes of `define_area_schemes` without the sensory filter
+/// chain, which is not supported by areas using host kernels.
+pub fn define_unfiltered_area_schemes() -> AreaSchemeList {
+    let area_side = 24 as u32;
+
+    AreaSchemeList::new()
+        .area(AreaScheme::new("v0", "external", area_side)
+            .encoder(EncoderScheme::IdxStreamer {
+                file_name: "train-images-idx3-ubyte".to_owned(),
+                cyc_per: CYCLES_PER_FRAME,
+                scale: 1.3,
+                loop_frames: 1,
+            })
+        )
+        .area(AreaScheme::new("v1", "visual", area_side)
+            .eff_areas(vec!["v0"])
+        )
+}
+
 
 // FRESH_CORTEX(): Mmmm... Yummy.
 pub fn fresh_cortex() -> Cortex {
@@ -435,7 +454,7 @@ impl TestBed {
         let ocl_context: Context = Context::builder()
             .build().expect("CorticalArea::new(): ocl_context creation error");
 
-        let thal = Thalamus::new(layer_map_schemes, area_schemes, &subcortex, &ocl_context).unwrap();
+        let thal = Thalamus::new(layer_map_schemes, area_schemes, &subcortex, Some(&ocl_context)).unwrap();
         let area_map = thal.area_maps().by_key(PRIMARY_AREA_NAME).unwrap().clone();
 
         let ocl_pq = ProQue::builder()
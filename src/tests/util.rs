@@ -2,9 +2,8 @@
 use std::ops::{Range};
 // use std::fmt::Display;
 
-use ocl::Buffer;
 use ocl::traits::{OclPrm, OclScl};
-use cortex::{CorticalArea, CorticalAreaTest, DendritesTest, SynapsesTest};
+use cortex::{CorticalArea, CorticalAreaTest, DendritesTest, SynapsesTest, Storage};
 use cmn;
 use tests::testbed;
 
@@ -119,7 +118,7 @@ pub fn ptal_alco(_area: &mut CorticalArea, _switches: PtalAlco, print: bool) {
 // ASSERT_RANGE():
 // - [FIXME] TODO: Use env.read_direct and read the entire range at once into a Vec.
 // - [FIXME] TODO: See if using an iterator (map?) function would be more idiomatic.
-pub fn eval_range<T: OclPrm, F>(idx_range: Range<usize>, buf: &Buffer<T>, comparison: F) -> bool
+pub fn eval_range<T: OclPrm, F>(idx_range: Range<usize>, buf: &Storage<T>, comparison: F) -> bool
     where F: Fn(T)-> bool
 {
     let vec = read_idx_range_direct(idx_range.clone(), buf);
@@ -137,16 +136,14 @@ pub fn eval_range<T: OclPrm, F>(idx_range: Range<usize>, buf: &Buffer<T>, compar
     true
 }
 
-pub fn read_idx_direct<T: OclPrm>(idx: usize, buf: &Buffer<T>) -> T {
+pub fn read_idx_direct<T: OclPrm>(idx: usize, buf: &Storage<T>) -> T {
     let mut val: [T; 1] = [Default::default()];
-    buf.cmd().read(&mut val[..]).offset(idx).enq().unwrap();
+    buf.read_into(idx, &mut val[..]).unwrap();
     val[0]
 }
 
-pub fn read_idx_range_direct<T: OclPrm>(idx_range: Range<usize>, buf: &Buffer<T>) -> Vec<T> {
-    let mut vec = vec![Default::default(); idx_range.len()];
-    buf.cmd().read(&mut vec).offset(idx_range.start).enq().unwrap();
-    vec
+pub fn read_idx_range_direct<T: OclPrm>(idx_range: Range<usize>, buf: &Storage<T>) -> Vec<T> {
+    buf.read_range(idx_range).unwrap()
 }
 
 // pub fn fill_vec<T: OclPrm>(buf: &Buffer<T>, vec: &mut Vec<T>) -> Event {
@@ -154,10 +151,8 @@ pub fn read_idx_range_direct<T: OclPrm>(idx_range: Range<usize>, buf: &Buffer<T>
 //     buf.cmd().read(vec).enq().unwrap();
 // }
 
-pub fn read_into_new_vec<T: OclPrm>(buf: &Buffer<T>) -> Vec<T> {
-    let mut vec = vec![Default::default(); buf.len()];
-    buf.cmd().read(&mut vec).enq().unwrap();
-    vec
+pub fn read_into_new_vec<T: OclPrm>(buf: &Storage<T>) -> Vec<T> {
+    buf.read().unwrap()
 }
 
 pub fn print_all(area: &mut CorticalArea, desc: &'static str) {
@@ -204,7 +199,7 @@ pub fn print_all_syn_range(range: Range<usize>, area: &mut CorticalArea, desc: &
 // }
 
 
-pub fn compare_buffers<T: OclScl>(env1: &Buffer<T>, env2: &Buffer<T>) -> bool {
+pub fn compare_buffers<T: OclScl>(env1: &Storage<T>, env2: &Storage<T>) -> bool {
     if PRINT_DETAILS { print!("\nVector comparison:\n"); }
     assert!(env1.len() == env2.len());
 
@@ -237,7 +232,7 @@ pub fn compare_buffers<T: OclScl>(env1: &Buffer<T>, env2: &Buffer<T>) -> bool {
 // TEST_NEARBY(): Ensure that elements near a focal index are equal to a particular value.
 //        - idz and idm (first and last elements) are also checked along with their nearby elements
 // <<<<< [FIXME] TODO: THIS FUNCTION NEEDS SERIOUS STREAMLINING & OPTIMIZATION >>>>>
-pub fn eval_others<T: OclScl>(env: &Buffer<T>, foc_idx: usize, other_val: T) {    // -> Result<(), &'static str>
+pub fn eval_others<T: OclScl>(env: &Storage<T>, foc_idx: usize, other_val: T) {    // -> Result<(), &'static str>
     // let mut checklist = Vec::new();
     let check_margin = 384;
 